@@ -0,0 +1,171 @@
+import { Program, BN } from "@coral-xyz/anchor";
+import { Time } from "../target/types/time";
+import { PublicKey, Transaction } from "@solana/web3.js";
+
+/**
+ * Solana Actions (Blinks) for managing a session key from a link, e.g. the
+ * "revoke" / "extend" buttons in a notification email. These are framework
+ * agnostic: mount `sessionActionMetadata` on GET and
+ * `buildSessionActionTransaction` on POST of the same URL, with
+ * `ACTIONS_CORS_HEADERS` on every response (including OPTIONS).
+ */
+
+/**
+ * Headers wallets require on every Actions response
+ */
+export const ACTIONS_CORS_HEADERS = {
+  "Access-Control-Allow-Origin": "*",
+  "Access-Control-Allow-Methods": "GET,POST,PUT,OPTIONS",
+  "Access-Control-Allow-Headers":
+    "Content-Type, Authorization, Content-Encoding, Accept-Encoding",
+  "Content-Type": "application/json",
+};
+
+export type SessionAction = "revoke" | "extend";
+
+export interface ActionParameter {
+  name: string;
+  label: string;
+  required?: boolean;
+}
+
+export interface LinkedAction {
+  label: string;
+  href: string;
+  parameters?: ActionParameter[];
+}
+
+/**
+ * Body of the GET response (`ActionGetResponse` in the Actions spec)
+ */
+export interface ActionGetResponse {
+  type: "action";
+  icon: string;
+  title: string;
+  description: string;
+  label: string;
+  links: { actions: LinkedAction[] };
+}
+
+/**
+ * Body of the POST request the wallet sends after the user picks an action
+ */
+export interface ActionPostRequest {
+  account: string;
+}
+
+/**
+ * Body of the POST response: a base64 transaction for the wallet to sign
+ */
+export interface ActionPostResponse {
+  transaction: string;
+  message?: string;
+}
+
+/**
+ * `actions.json` served at the site root so wallets map page URLs to the
+ * Actions API
+ */
+export function actionsJson(apiPathPattern = "/api/actions/**") {
+  return {
+    rules: [{ pathPattern: "/sessions/**", apiPath: apiPathPattern }],
+  };
+}
+
+/**
+ * GET metadata for a session key; `baseUrl` is the Actions endpoint without a
+ * trailing slash
+ */
+export function sessionActionMetadata(
+  baseUrl: string,
+  sessionKey: PublicKey,
+  icon: string
+): ActionGetResponse {
+  const session = sessionKey.toBase58();
+  return {
+    type: "action",
+    icon,
+    title: "Manage session key",
+    description:
+      `Revoke or extend session ${session}. ` +
+      "Only the account that created it can sign.",
+    label: "Manage",
+    links: {
+      actions: [
+        {
+          label: "Revoke session",
+          href: `${baseUrl}/revoke?session=${session}`,
+        },
+        {
+          label: "Extend session",
+          href: `${baseUrl}/extend?session=${session}&hours={hours}`,
+          parameters: [
+            { name: "hours", label: "Extend by (hours)", required: true },
+          ],
+        },
+      ],
+    },
+  };
+}
+
+/**
+ * POST handler body: builds an unsigned transaction for `account` (the session
+ * key's authority, who also pays the fee). Extending moves the time bound,
+ * counting from the later of now and the current expiry; a slot bound stays.
+ */
+export async function buildSessionActionTransaction(
+  program: Program<Time>,
+  action: SessionAction,
+  request: ActionPostRequest,
+  sessionKey: PublicKey,
+  extendSeconds = 0
+): Promise<ActionPostResponse> {
+  const authority = new PublicKey(request.account);
+  const [userAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from("user_account"), authority.toBuffer()],
+    program.programId
+  );
+  const accounts = { userAccount, authority };
+
+  let ix;
+  let message: string;
+  if (action === "revoke") {
+    ix = await program.methods
+      .revokeSessionKey(sessionKey, { manual: {} })
+      .accountsStrict(accounts)
+      .instruction();
+    message = "Session key revoked";
+  } else {
+    if (extendSeconds <= 0) {
+      throw new Error("Extension must be a positive number of seconds");
+    }
+    const acct = await program.account.userAccount.fetch(userAccount);
+    const entry = acct.sessionKeys.find((k) => k.pubkey.equals(sessionKey));
+    if (!entry) {
+      throw new Error("Session key not found for this account");
+    }
+    if (entry.expiresAtTime === null) {
+      throw new Error("Only time-bounded session keys can be extended");
+    }
+    const now = Math.floor(Date.now() / 1000);
+    const from = Math.max(now, entry.expiresAtTime.toNumber());
+    ix = await program.methods
+      .updateSessionKey(sessionKey, new BN(from + extendSeconds), null, null)
+      .accountsStrict(accounts)
+      .instruction();
+    message = "Session key extended";
+  }
+
+  const connection = program.provider.connection;
+  const { blockhash } = await connection.getLatestBlockhash();
+  const tx = new Transaction({
+    feePayer: authority,
+    recentBlockhash: blockhash,
+  }).add(ix);
+  return {
+    transaction: tx
+      .serialize({ requireAllSignatures: false, verifySignatures: false })
+      .toString("base64"),
+    message,
+  };
+}
@@ -0,0 +1,166 @@
+import {
+  ComputeBudgetProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
+
+/**
+ * Compute unit upper bounds per instruction, mirroring
+ * `programs/time/src/compute_budget.rs` (enable the `cu-hints` feature to use
+ * them from Rust). Keep both in sync; `tests/compute_budget.spec.ts` checks
+ * measured usage against these values.
+ */
+export const COMPUTE_UNIT_HINTS = {
+  initializeUserAccount: 15_000,
+  initializeUserAccountWithConfig: 20_000,
+  initializeWithSessionKey: 35_000,
+  createSessionKey: 20_000,
+  createSessionKeyV2: 20_000,
+  createSessionKeyInitIfNeeded: 35_000,
+  createSessionKeyFromPreset: 25_000,
+  updateSessionKey: 12_000,
+  updateSessionKeyLabel: 10_000,
+  verifySessionDomain: 8_000,
+  validateSession: 10_000,
+  revokeSessionKey: 10_000,
+  suspendSessionKey: 10_000,
+  resumeSessionKey: 10_000,
+  selfRestrict: 12_000,
+  delegateSessionKey: 30_000,
+  setMaxDelegationDepth: 8_000,
+  revokeAllSessionKeys: 12_000,
+  revokeSessionsExpiringAfter: 30_000,
+  resizeUserAccount: 15_000,
+  migrateUserAccount: 60_000,
+  closeUserAccount: 20_000,
+  archiveUserAccount: 30_000,
+  restoreUserAccount: 30_000,
+  createSessionKeyAccount: 25_000,
+  revokeSessionKeyAccount: 10_000,
+  closeSessionKeyAccount: 10_000,
+  transferSessionToNewKey: 12_000,
+  rotateSessionKey: 20_000,
+  cleanupSessionKeys: 15_000,
+  crankCleanup: 20_000,
+  setCleanupRetention: 8_000,
+  updateAllowedMints: 12_000,
+  initializeSpendBudget: 20_000,
+  updateSpendBudget: 10_000,
+  setProgramBudget: 12_000,
+  closeSpendBudget: 12_000,
+  initializePolicy: 20_000,
+  updatePolicy: 12_000,
+  closePolicy: 10_000,
+  setKillPhrase: 8_000,
+  triggerKillPhrase: 20_000,
+  setGuardian: 8_000,
+  guardianRevokeAll: 20_000,
+  setAccountPaused: 8_000,
+  setRecipientConsentRequired: 8_000,
+  acceptPayments: 15_000,
+  withdrawPaymentConsent: 8_000,
+  enableActivityDigest: 20_000,
+  setDigestInterval: 25_000,
+  initializeAccountStats: 25_000,
+  scheduleSessionKey: 20_000,
+  cancelScheduledSession: 10_000,
+  activateScheduledSession: 25_000,
+  createPipeline: 20_000,
+  closePipeline: 8_000,
+  /** Transfer steps only; add the swap programs' own usage */
+  executePipeline: 80_000,
+  /** Excludes the target program's own usage */
+  executeCustom: 25_000,
+  splApproveDelegate: 15_000,
+  approveAndCreateSession: 35_000,
+  splRevokeDelegate: 12_000,
+  splDelegatedTransfer: 45_000,
+  /** Includes creating the recipient's token account */
+  splDelegatedTransferWithAta: 80_000,
+  setGuardProgram: 8_000,
+  setLockoutPolicy: 8_000,
+  recordRefusedSpend: 10_000,
+  setMaxSlotAge: 8_000,
+  setPermanentDelegateOptIn: 8_000,
+  setSavingsRule: 8_000,
+  setSpendCategories: 12_000,
+  setWideningDelay: 8_000,
+  depositSol: 10_000,
+  withdrawSol: 8_000,
+  sessionWithdrawSol: 15_000,
+  sessionTransferSol: 15_000,
+  initializeGasTank: 15_000,
+  setGasTankFee: 8_000,
+  topUpGasTank: 10_000,
+  setBlackoutWindows: 10_000,
+  setAttestationGate: 8_000,
+  setBlocklistEnforcement: 8_000,
+  initializeBlocklist: 20_000,
+  updateBlocklist: 60_000,
+  initializeProgramConfig: 20_000,
+  setPaused: 8_000,
+  updateConfig: 8_000,
+  updateMintDenylist: 30_000,
+  setFee: 8_000,
+  withdrawFees: 20_000,
+  setBondRequirement: 8_000,
+  setOperatorSignatureRequired: 8_000,
+  postBond: 25_000,
+  postBondSpl: 45_000,
+  requestBondRelease: 12_000,
+  slashBond: 35_000,
+  withdrawBond: 40_000,
+  reportViolation: 20_000,
+  resolveDispute: 15_000,
+  initializeOperatorReputation: 15_000,
+  registerService: 20_000,
+  updateService: 12_000,
+  closeService: 8_000,
+  setServiceVerified: 10_000,
+  publishPreset: 20_000,
+  updatePreset: 12_000,
+  publishBuildHash: 20_000,
+} as const;
+
+/**
+ * Extra units `splDelegatedTransfer` needs for each optional check the
+ * account has enabled
+ */
+export const TRANSFER_CHECK_HINTS = {
+  blocklist: 10_000,
+  attestation: 5_000,
+  bond: 8_000,
+  spendBudget: 5_000,
+  /** Worst case: the execution completes a batch and computes its root */
+  activityDigest: 25_000,
+  /** Appends to the history tree and recomputes its root */
+  accountStats: 15_000,
+  /** Second transfer into the savings vault */
+  savings: 20_000,
+  /** Derives the treasury and transfers the protocol fee into it */
+  protocolFee: 25_000,
+  /** Pays the session signer's fee out of the gas tank */
+  gasReimbursement: 5_000,
+  /** Scans the SlotHashes sysvar for the referenced slot */
+  slotProof: 10_000,
+  /** Excludes the guard program's own usage, which the caller must add */
+  guard: 10_000,
+  /** Finds the operator's Ed25519 instruction via the Instructions sysvar */
+  operatorSignature: 8_000,
+} as const;
+
+export type HintedInstruction = keyof typeof COMPUTE_UNIT_HINTS;
+
+/**
+ * Build a `SetComputeUnitLimit` instruction covering the given instructions,
+ * plus `extra` units for CPIs the hints do not include (e.g. a guard program)
+ */
+export function computeBudgetIx(
+  instructions: HintedInstruction[],
+  extra = 0
+): TransactionInstruction {
+  const units = instructions.reduce(
+    (sum, name) => sum + COMPUTE_UNIT_HINTS[name],
+    extra
+  );
+  return ComputeBudgetProgram.setComputeUnitLimit({ units });
+}
@@ -0,0 +1,977 @@
+import { AnchorError } from "@coral-xyz/anchor";
+import { SendTransactionError } from "@solana/web3.js";
+import { TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+
+/**
+ * Languages the error catalog is translated into
+ */
+export type Locale = "en" | "es" | "fr";
+
+type Messages = Record<Locale, string>;
+
+/**
+ * Program error names in declaration order (`programs/time/src/errors.rs`).
+ * Anchor numbers custom errors from 6000, so the index gives the code.
+ */
+export const PROGRAM_ERRORS = [
+  "InvalidExpiry",
+  "TooManySessionKeys",
+  "SessionKeyAlreadyExists",
+  "SessionKeyNotFound",
+  "SessionKeyRevoked",
+  "SessionKeyAlreadyRevoked",
+  "SessionKeyExpired",
+  "InsufficientPermissions",
+  "MintNotAllowed",
+  "TooManyAllowedMints",
+  "GuardProgramMissing",
+  "GuardProgramMismatch",
+  "AttestationMissing",
+  "AttestationInvalid",
+  "Unauthorized",
+  "BlocklistFull",
+  "BlocklistMissing",
+  "BondRequired",
+  "BondInsufficient",
+  "BondReleasePending",
+  "SessionKeyStillActive",
+  "BondLocked",
+  "DisputeWindowClosed",
+  "BondTokenAccountsInvalid",
+  "InvalidAmount",
+  "DisputeTimelockActive",
+  "DisputeAlreadyResolved",
+  "ReportNotSlashable",
+  "DisputesOpen",
+  "OperatorReputationMismatch",
+  "TooManyServiceSessionKeys",
+  "PresetVersionMismatch",
+  "PresetDurationExceeded",
+  "SpendBudgetMissing",
+  "SpendBudgetExceeded",
+  "TooManySpendBudgets",
+  "ActivityDigestMissing",
+  "InvalidDigestInterval",
+  "SessionSpendCapExceeded",
+  "DailyLimitExceeded",
+  "AccountStatsMissing",
+  "HistoryFull",
+  "RateLimitExceeded",
+  "SessionKeyExhausted",
+  "SessionKeyNotYetValid",
+  "InvalidActivationTime",
+  "ScheduledSessionNotDue",
+  "TooManyBlackoutWindows",
+  "InvalidBlackoutWindow",
+  "BlackoutActive",
+  "InvalidMaxSlotAge",
+  "SlotProofRequired",
+  "SlotProofInvalid",
+  "TooManyAllowedRecipients",
+  "RecipientNotAllowed",
+  "InvalidPipeline",
+  "PipelineStepMismatch",
+  "PipelineStepLimitExceeded",
+  "TooManySessionMints",
+  "InvalidSavingsRule",
+  "SavingsVaultMissing",
+  "SavingsVaultMismatch",
+  "TooManyAllowedPrograms",
+  "ProgramNotAllowed",
+  "TooManyAllowedDiscriminators",
+  "InstructionNotAllowed",
+  "TooManySpendCategories",
+  "DuplicateSpendCategory",
+  "UnknownSpendCategory",
+  "InvalidDiscriminatorFilter",
+  "InvalidWideningDelay",
+  "InsufficientVaultBalance",
+  "InvalidGasFee",
+  "TooManyDelegations",
+  "SessionKeysStillActive",
+  "DelegationsStillActive",
+  "InvalidSessionKeyCapacity",
+  "SessionKeyAccountsOpen",
+  "ArchiveMismatch",
+  "ArchiveTooLarge",
+  "PermanentDelegateNotAllowed",
+  "SessionKeySuspended",
+  "SessionKeyNotSuspended",
+  "CpiDepthExceeded",
+  "TooManyCustomAccounts",
+  "InvalidPolicy",
+  "PolicyVersionUnsupported",
+  "PolicyRequired",
+  "PolicyViolation",
+  "PolicyOpen",
+  "RestrictionWidens",
+  "KillPhraseNotSet",
+  "KillPhraseMismatch",
+  "AccountPaused",
+  "NothingToCleanUp",
+  "SessionKeyLocked",
+  "InvalidLockoutPolicy",
+  "DelegationWidens",
+  "RecipientConsentMissing",
+  "DelegationTooDeep",
+  "OperatorSignatureMissing",
+  "NotGuardian",
+  "ProgramBudgetMissing",
+  "ProgramBudgetExceeded",
+  "TooManyProgramBudgets",
+  "SessionScopeMismatch",
+  "SessionDomainMismatch",
+  "ProgramPaused",
+  "GlobalLimitExceeded",
+  "MintDenied",
+  "MintDenylistFull",
+  "InvalidProtocolFee",
+  "FeeAccountMissing",
+  "FeeAccountMismatch",
+  "InsufficientTreasuryBalance",
+  "AccountAlreadyMigrated",
+  "SourceTokenOwnerMismatch",
+  "SourceTokenMintMismatch",
+  "RecipientMismatch",
+  "RecipientNotCanonicalAta",
+  "SwapOutputTooLow",
+  "SolSpendUnsupported",
+  "CustomActionUnsupported",
+  "PipelineControlsUnsupported",
+  "SwapAccountNotAllowed",
+  "LockoutPolicyMissing",
+  "SpendNotRefused",
+  "RecipientBlocked",
+] as const;
+
+export type ProgramErrorName = (typeof PROGRAM_ERRORS)[number];
+
+const PROGRAM_MESSAGES: Record<ProgramErrorName, Messages> = {
+  InvalidExpiry: {
+    en: "The session must expire in the future.",
+    es: "La sesión debe expirar en el futuro.",
+    fr: "La session doit expirer dans le futur.",
+  },
+  TooManySessionKeys: {
+    en: "You have reached the maximum number of sessions. Remove an old one first.",
+    es: "Has alcanzado el número máximo de sesiones. Elimina una antigua primero.",
+    fr: "Vous avez atteint le nombre maximal de sessions. Supprimez-en une ancienne d'abord.",
+  },
+  SessionKeyAlreadyExists: {
+    en: "This session key is already registered.",
+    es: "Esta clave de sesión ya está registrada.",
+    fr: "Cette clé de session est déjà enregistrée.",
+  },
+  SessionKeyNotFound: {
+    en: "This session key is not registered on your account.",
+    es: "Esta clave de sesión no está registrada en tu cuenta.",
+    fr: "Cette clé de session n'est pas enregistrée sur votre compte.",
+  },
+  SessionKeyRevoked: {
+    en: "This session has been revoked.",
+    es: "Esta sesión ha sido revocada.",
+    fr: "Cette session a été révoquée.",
+  },
+  SessionKeyAlreadyRevoked: {
+    en: "This session was already revoked.",
+    es: "Esta sesión ya estaba revocada.",
+    fr: "Cette session était déjà révoquée.",
+  },
+  SessionKeyExpired: {
+    en: "This session has expired. Approve a new one to continue.",
+    es: "Esta sesión ha expirado. Aprueba una nueva para continuar.",
+    fr: "Cette session a expiré. Approuvez-en une nouvelle pour continuer.",
+  },
+  InsufficientPermissions: {
+    en: "This session is not allowed to perform this action or amount.",
+    es: "Esta sesión no tiene permiso para esta acción o cantidad.",
+    fr: "Cette session n'est pas autorisée pour cette action ou ce montant.",
+  },
+  MintNotAllowed: {
+    en: "This token is not on your account's allowed list.",
+    es: "Este token no está en la lista permitida de tu cuenta.",
+    fr: "Ce jeton ne figure pas dans la liste autorisée de votre compte.",
+  },
+  TooManyAllowedMints: {
+    en: "Too many tokens in the allowed list.",
+    es: "Demasiados tokens en la lista permitida.",
+    fr: "Trop de jetons dans la liste autorisée.",
+  },
+  GuardProgramMissing: {
+    en: "Your account's guard program was not included in the transaction.",
+    es: "El programa guardián de tu cuenta no se incluyó en la transacción.",
+    fr: "Le programme de garde de votre compte n'a pas été inclus dans la transaction.",
+  },
+  GuardProgramMismatch: {
+    en: "The transaction used a different guard program than your account requires.",
+    es: "La transacción usó un programa guardián distinto al que exige tu cuenta.",
+    fr: "La transaction utilise un programme de garde différent de celui exigé par votre compte.",
+  },
+  AttestationMissing: {
+    en: "A verification credential is required for this transfer.",
+    es: "Se requiere una credencial de verificación para esta transferencia.",
+    fr: "Une attestation de vérification est requise pour ce transfert.",
+  },
+  AttestationInvalid: {
+    en: "The verification credential is missing, empty, or belongs to someone else.",
+    es: "La credencial de verificación falta, está vacía o pertenece a otra persona.",
+    fr: "L'attestation de vérification est absente, vide ou appartient à quelqu'un d'autre.",
+  },
+  Unauthorized: {
+    en: "You are not authorized to perform this action.",
+    es: "No estás autorizado para realizar esta acción.",
+    fr: "Vous n'êtes pas autorisé à effectuer cette action.",
+  },
+  BlocklistFull: {
+    en: "The blocklist is full.",
+    es: "La lista de bloqueo está llena.",
+    fr: "La liste de blocage est pleine.",
+  },
+  BlocklistMissing: {
+    en: "Your account screens recipients, but the blocklist was not included.",
+    es: "Tu cuenta filtra destinatarios, pero no se incluyó la lista de bloqueo.",
+    fr: "Votre compte filtre les destinataires, mais la liste de blocage n'a pas été incluse.",
+  },
+  BondRequired: {
+    en: "The service must post a bond before acting on your behalf.",
+    es: "El servicio debe depositar una garantía antes de actuar en tu nombre.",
+    fr: "Le service doit déposer une caution avant d'agir en votre nom.",
+  },
+  BondInsufficient: {
+    en: "The service's bond is too small or in the wrong token.",
+    es: "La garantía del servicio es insuficiente o está en el token equivocado.",
+    fr: "La caution du service est insuffisante ou dans le mauvais jeton.",
+  },
+  BondReleasePending: {
+    en: "The service is withdrawing its bond and can no longer act for you.",
+    es: "El servicio está retirando su garantía y ya no puede actuar por ti.",
+    fr: "Le service retire sa caution et ne peut plus agir pour vous.",
+  },
+  SessionKeyStillActive: {
+    en: "The bond cannot be released while its session is still active.",
+    es: "La garantía no puede liberarse mientras su sesión siga activa.",
+    fr: "La caution ne peut pas être libérée tant que sa session est active.",
+  },
+  BondLocked: {
+    en: "The bond is still within its dispute window.",
+    es: "La garantía sigue dentro de su periodo de disputa.",
+    fr: "La caution est encore dans sa période de contestation.",
+  },
+  DisputeWindowClosed: {
+    en: "The dispute window for this bond has closed.",
+    es: "El periodo de disputa de esta garantía ha terminado.",
+    fr: "La période de contestation de cette caution est close.",
+  },
+  BondTokenAccountsInvalid: {
+    en: "The token accounts for this bond are missing or incorrect.",
+    es: "Las cuentas de token de esta garantía faltan o son incorrectas.",
+    fr: "Les comptes de jetons de cette caution sont absents ou incorrects.",
+  },
+  InvalidAmount: {
+    en: "Enter an amount greater than zero and within the available balance.",
+    es: "Introduce una cantidad mayor que cero y dentro del saldo disponible.",
+    fr: "Saisissez un montant supérieur à zéro et dans la limite du solde disponible.",
+  },
+  DisputeTimelockActive: {
+    en: "This dispute cannot be resolved yet. Try again after the waiting period.",
+    es: "Esta disputa aún no puede resolverse. Inténtalo tras el periodo de espera.",
+    fr: "Ce litige ne peut pas encore être résolu. Réessayez après le délai d'attente.",
+  },
+  DisputeAlreadyResolved: {
+    en: "This dispute has already been resolved.",
+    es: "Esta disputa ya ha sido resuelta.",
+    fr: "Ce litige a déjà été résolu.",
+  },
+  ReportNotSlashable: {
+    en: "This report cannot be used to slash the bond.",
+    es: "Este reporte no puede usarse para penalizar la garantía.",
+    fr: "Ce signalement ne permet pas de pénaliser la caution.",
+  },
+  DisputesOpen: {
+    en: "The bond has unresolved disputes.",
+    es: "La garantía tiene disputas sin resolver.",
+    fr: "La caution a des litiges non résolus.",
+  },
+  OperatorReputationMismatch: {
+    en: "The service's reputation record is missing or does not match.",
+    es: "El registro de reputación del servicio falta o no coincide.",
+    fr: "Le dossier de réputation du service est absent ou ne correspond pas.",
+  },
+  TooManyServiceSessionKeys: {
+    en: "Too many session keys listed for this service.",
+    es: "Demasiadas claves de sesión para este servicio.",
+    fr: "Trop de clés de session pour ce service.",
+  },
+  PresetVersionMismatch: {
+    en: "The service changed what it is asking for. Review the new request.",
+    es: "El servicio cambió lo que solicita. Revisa la nueva solicitud.",
+    fr: "Le service a modifié sa demande. Vérifiez la nouvelle demande.",
+  },
+  PresetDurationExceeded: {
+    en: "The requested session is longer than the service allows.",
+    es: "La sesión solicitada es más larga de lo que permite el servicio.",
+    fr: "La session demandée dépasse la durée autorisée par le service.",
+  },
+  SpendBudgetMissing: {
+    en: "Your spending limit for this token was not included in the transaction.",
+    es: "Tu límite de gasto para este token no se incluyó en la transacción.",
+    fr: "Votre plafond de dépenses pour ce jeton n'a pas été inclus dans la transaction.",
+  },
+  SpendBudgetExceeded: {
+    en: "This would exceed your spending limit for this period.",
+    es: "Esto superaría tu límite de gasto para este periodo.",
+    fr: "Cela dépasserait votre plafond de dépenses pour cette période.",
+  },
+  TooManySpendBudgets: {
+    en: "You have reached the maximum number of spending limits.",
+    es: "Has alcanzado el número máximo de límites de gasto.",
+    fr: "Vous avez atteint le nombre maximal de plafonds de dépenses.",
+  },
+  ActivityDigestMissing: {
+    en: "Your activity log account was not included in the transaction.",
+    es: "Tu cuenta de registro de actividad no se incluyó en la transacción.",
+    fr: "Votre compte de journal d'activité n'a pas été inclus dans la transaction.",
+  },
+  InvalidDigestInterval: {
+    en: "Choose a digest interval between 1 and 65,535 actions.",
+    es: "Elige un intervalo de resumen entre 1 y 65.535 acciones.",
+    fr: "Choisissez un intervalle de synthèse entre 1 et 65 535 actions.",
+  },
+  SessionSpendCapExceeded: {
+    en: "This session has used up its spending allowance. Approve a new session to continue.",
+    es: "Esta sesión agotó su límite de gasto. Aprueba una nueva sesión para continuar.",
+    fr: "Cette session a épuisé son allocation de dépenses. Approuvez une nouvelle session pour continuer.",
+  },
+  DailyLimitExceeded: {
+    en: "This session has reached its spending limit for today. It resets automatically.",
+    es: "Esta sesión alcanzó su límite de gasto de hoy. Se restablece automáticamente.",
+    fr: "Cette session a atteint son plafond de dépenses du jour. Il se réinitialise automatiquement.",
+  },
+  AccountStatsMissing: {
+    en: "Your activity history account was not included in the transaction.",
+    es: "Tu cuenta de historial de actividad no se incluyó en la transacción.",
+    fr: "Votre compte d'historique d'activité n'a pas été inclus dans la transaction.",
+  },
+  HistoryFull: {
+    en: "Your activity history is full.",
+    es: "Tu historial de actividad está lleno.",
+    fr: "Votre historique d'activité est plein.",
+  },
+  RateLimitExceeded: {
+    en: "This session has been used too often. Try again later.",
+    es: "Esta sesión se ha usado demasiadas veces. Inténtalo más tarde.",
+    fr: "Cette session a été utilisée trop souvent. Réessayez plus tard.",
+  },
+  SessionKeyExhausted: {
+    en: "This session has already been used the maximum number of times.",
+    es: "Esta sesión ya se usó el número máximo de veces.",
+    fr: "Cette session a déjà été utilisée le nombre maximal de fois.",
+  },
+  SessionKeyNotYetValid: {
+    en: "This session isn't active yet.",
+    es: "Esta sesión todavía no está activa.",
+    fr: "Cette session n'est pas encore active.",
+  },
+  InvalidActivationTime: {
+    en: "Schedule the session to start in the future and before it expires.",
+    es: "Programa la sesión para que empiece en el futuro y antes de expirar.",
+    fr: "Programmez la session pour qu'elle commence plus tard et avant son expiration.",
+  },
+  ScheduledSessionNotDue: {
+    en: "This scheduled session can't be activated yet.",
+    es: "Esta sesión programada todavía no se puede activar.",
+    fr: "Cette session programmée ne peut pas encore être activée.",
+  },
+  TooManyBlackoutWindows: {
+    en: "Too many blackout periods. Remove one and try again.",
+    es: "Demasiados periodos de bloqueo. Elimina uno e inténtalo de nuevo.",
+    fr: "Trop de périodes de blocage. Supprimez-en une et réessayez.",
+  },
+  InvalidBlackoutWindow: {
+    en: "A blackout period must end after it starts.",
+    es: "Un periodo de bloqueo debe terminar después de empezar.",
+    fr: "Une période de blocage doit se terminer après son début.",
+  },
+  BlackoutActive: {
+    en: "Session activity is paused during a scheduled blackout.",
+    es: "La actividad de las sesiones está pausada durante un bloqueo programado.",
+    fr: "L'activité des sessions est suspendue pendant une période de blocage.",
+  },
+  InvalidMaxSlotAge: {
+    en: "The maximum slot age can be at most 512 slots.",
+    es: "La antigüedad máxima del slot puede ser de 512 slots como máximo.",
+    fr: "L'ancienneté maximale du slot est de 512 slots au plus.",
+  },
+  SlotProofRequired: {
+    en: "This account requires a recent slot reference on every execution.",
+    es: "Esta cuenta requiere una referencia de slot reciente en cada ejecución.",
+    fr: "Ce compte exige une référence de slot récente à chaque exécution.",
+  },
+  SlotProofInvalid: {
+    en: "The transaction is too old; rebuild it and sign again.",
+    es: "La transacción es demasiado antigua; reconstrúyala y fírmela de nuevo.",
+    fr: "La transaction est trop ancienne ; reconstruisez-la et signez à nouveau.",
+  },
+  TooManyAllowedRecipients: {
+    en: "A session key can be restricted to at most 4 recipients.",
+    es: "Una clave de sesión puede limitarse a 4 destinatarios como máximo.",
+    fr: "Une clé de session peut être limitée à 4 destinataires au plus.",
+  },
+  RecipientNotAllowed: {
+    en: "This session can't send funds to that recipient.",
+    es: "Esta sesión no puede enviar fondos a ese destinatario.",
+    fr: "Cette session ne peut pas envoyer de fonds à ce destinataire.",
+  },
+  InvalidPipeline: {
+    en: "A pipeline needs 1 to 4 steps, each with a limit above zero.",
+    es: "Un pipeline necesita de 1 a 4 pasos, cada uno con un límite mayor que cero.",
+    fr: "Un pipeline doit compter de 1 à 4 étapes, chacune avec une limite non nulle.",
+  },
+  PipelineStepMismatch: {
+    en: "The pipeline request doesn't match its template.",
+    es: "La solicitud del pipeline no coincide con su plantilla.",
+    fr: "La demande ne correspond pas au modèle du pipeline.",
+  },
+  PipelineStepLimitExceeded: {
+    en: "A pipeline step would move more than its limit allows.",
+    es: "Un paso del pipeline movería más de lo que permite su límite.",
+    fr: "Une étape du pipeline dépasserait sa limite.",
+  },
+  TooManySessionMints: {
+    en: "A session key can be restricted to at most 4 tokens.",
+    es: "Una clave de sesión puede limitarse a 4 tokens como máximo.",
+    fr: "Une clé de session peut être limitée à 4 jetons au plus.",
+  },
+  InvalidSavingsRule: {
+    en: "Choose a savings percentage of at most 100% or a round-up amount.",
+    es: "Elija un porcentaje de ahorro de hasta el 100 % o un redondeo.",
+    fr: "Choisissez un pourcentage d'épargne d'au plus 100 % ou un arrondi.",
+  },
+  SavingsVaultMissing: {
+    en: "This transfer must include the savings account.",
+    es: "Esta transferencia debe incluir la cuenta de ahorro.",
+    fr: "Ce transfert doit inclure le compte d'épargne.",
+  },
+  SavingsVaultMismatch: {
+    en: "The savings account doesn't match your savings rule.",
+    es: "La cuenta de ahorro no coincide con su regla de ahorro.",
+    fr: "Le compte d'épargne ne correspond pas à votre règle d'épargne.",
+  },
+  TooManyAllowedPrograms: {
+    en: "A session key can call at most 4 programs.",
+    es: "Una clave de sesión puede llamar a 4 programas como máximo.",
+    fr: "Une clé de session peut appeler 4 programmes au plus.",
+  },
+  ProgramNotAllowed: {
+    en: "This session isn't allowed to call that program.",
+    es: "Esta sesión no tiene permiso para llamar a ese programa.",
+    fr: "Cette session n'est pas autorisée à appeler ce programme.",
+  },
+  TooManyAllowedDiscriminators: {
+    en: "A session key can be limited to at most 8 instructions.",
+    es: "Una clave de sesión puede limitarse a 8 instrucciones como máximo.",
+    fr: "Une clé de session peut être limitée à 8 instructions au plus.",
+  },
+  InstructionNotAllowed: {
+    en: "This session isn't allowed to send that instruction.",
+    es: "Esta sesión no tiene permiso para enviar esa instrucción.",
+    fr: "Cette session n'est pas autorisée à envoyer cette instruction.",
+  },
+  TooManySpendCategories: {
+    en: "You can define at most 16 spending categories.",
+    es: "Puede definir 16 categorías de gasto como máximo.",
+    fr: "Vous pouvez définir au plus 16 catégories de dépenses.",
+  },
+  DuplicateSpendCategory: {
+    en: "Each spending category needs its own id.",
+    es: "Cada categoría de gasto necesita su propio identificador.",
+    fr: "Chaque catégorie de dépenses doit avoir son propre identifiant.",
+  },
+  UnknownSpendCategory: {
+    en: "That spending category isn't set up for this account.",
+    es: "Esa categoría de gasto no está configurada para esta cuenta.",
+    fr: "Cette catégorie de dépenses n'est pas configurée pour ce compte.",
+  },
+  InvalidDiscriminatorFilter: {
+    en: "Instruction limits must name a program this session can call.",
+    es: "Los límites de instrucciones deben indicar un programa que esta sesión pueda llamar.",
+    fr: "Les limites d'instructions doivent désigner un programme que cette session peut appeler.",
+  },
+  InvalidWideningDelay: {
+    en: "The delay for new session permissions can be at most 7 days.",
+    es: "El retraso de los nuevos permisos de sesión puede ser de 7 días como máximo.",
+    fr: "Le délai des nouvelles autorisations de session est d'au plus 7 jours.",
+  },
+  InsufficientVaultBalance: {
+    en: "Your account doesn't hold enough SOL for this withdrawal.",
+    es: "Su cuenta no tiene suficiente SOL para este retiro.",
+    fr: "Votre compte ne détient pas assez de SOL pour ce retrait.",
+  },
+  InvalidGasFee: {
+    en: "The fee reimbursed per action can be at most 0.0001 SOL.",
+    es: "La comisión reembolsada por acción puede ser de 0,0001 SOL como máximo.",
+    fr: "Les frais remboursés par action sont limités à 0,0001 SOL.",
+  },
+  TooManyDelegations: {
+    en: "Too many token accounts are approved for spending. Revoke one first.",
+    es: "Hay demasiadas cuentas de tokens autorizadas para gastar. Revoque una primero.",
+    fr: "Trop de comptes de jetons sont autorisés à dépenser. Révoquez-en un d'abord.",
+  },
+  SessionKeysStillActive: {
+    en: "Revoke all active session keys before closing your account.",
+    es: "Revoque todas las claves de sesión activas antes de cerrar su cuenta.",
+    fr: "Révoquez toutes les clés de session actives avant de fermer votre compte.",
+  },
+  DelegationsStillActive: {
+    en: "Revoke every token spending approval before closing your account.",
+    es: "Revoque todas las autorizaciones de gasto de tokens antes de cerrar su cuenta.",
+    fr: "Révoquez toutes les autorisations de dépense de jetons avant de fermer votre compte.",
+  },
+  InvalidSessionKeyCapacity: {
+    en: "Your account can hold between your current number of session keys and 7 keys.",
+    es: "Su cuenta puede tener entre su número actual de claves de sesión y 7 claves.",
+    fr: "Votre compte peut contenir entre votre nombre actuel de clés de session et 7 clés.",
+  },
+  SessionKeyAccountsOpen: {
+    en: "Close all of your separately stored session keys before closing your account.",
+    es: "Cierre todas sus claves de sesión almacenadas por separado antes de cerrar su cuenta.",
+    fr: "Fermez toutes vos clés de session stockées séparément avant de fermer votre compte.",
+  },
+  ArchiveMismatch: {
+    en: "This data doesn't match your archived account.",
+    es: "Estos datos no coinciden con su cuenta archivada.",
+    fr: "Ces données ne correspondent pas à votre compte archivé.",
+  },
+  ArchiveTooLarge: {
+    en: "Your account has too many settings to archive. Remove some allowlist entries, blackout windows or spend categories first.",
+    es: "Su cuenta tiene demasiados ajustes para archivarla. Elimine primero algunas entradas de la lista permitida, ventanas de bloqueo o categorías de gasto.",
+    fr: "Votre compte a trop de paramètres pour être archivé. Supprimez d'abord des entrées de liste autorisée, des fenêtres d'interdiction ou des catégories de dépenses.",
+  },
+  PermanentDelegateNotAllowed: {
+    en: "This token's issuer can move tokens out of any wallet. Allow such tokens in your settings to use it.",
+    es: "El emisor de este token puede mover tokens de cualquier billetera. Permita este tipo de tokens en su configuración para usarlo.",
+    fr: "L'émetteur de ce jeton peut retirer des jetons de n'importe quel portefeuille. Autorisez ce type de jetons dans vos paramètres pour l'utiliser.",
+  },
+  SessionKeySuspended: {
+    en: "This session is paused. Resume it from your wallet to use it again.",
+    es: "Esta sesión está en pausa. Reanúdela desde su billetera para volver a usarla.",
+    fr: "Cette session est en pause. Reprenez-la depuis votre portefeuille pour l'utiliser à nouveau.",
+  },
+  SessionKeyNotSuspended: {
+    en: "This session isn't paused.",
+    es: "Esta sesión no está en pausa.",
+    fr: "Cette session n'est pas en pause.",
+  },
+  CpiDepthExceeded: {
+    en: "This session can only run actions sent directly, not from inside another program.",
+    es: "Esta sesión solo puede ejecutar acciones enviadas directamente, no desde otro programa.",
+    fr: "Cette session ne peut exécuter que des actions envoyées directement, pas depuis un autre programme.",
+  },
+  TooManyCustomAccounts: {
+    en: "This action involves more accounts than the session allows.",
+    es: "Esta acción involucra más cuentas de las que permite la sesión.",
+    fr: "Cette action implique plus de comptes que la session ne l'autorise.",
+  },
+  InvalidPolicy: {
+    en: "This policy has invalid or too many rules.",
+    es: "Esta política tiene reglas no válidas o demasiadas reglas.",
+    fr: "Cette politique contient des règles invalides ou trop nombreuses.",
+  },
+  PolicyVersionUnsupported: {
+    en: "This policy was written for a different version of the app. Update and try again.",
+    es: "Esta política se escribió para otra versión de la aplicación. Actualice e inténtelo de nuevo.",
+    fr: "Cette politique a été écrite pour une autre version de l'application. Mettez à jour et réessayez.",
+  },
+  PolicyRequired: {
+    en: "Your account has a spending policy that this request didn't include. Please try again.",
+    es: "Su cuenta tiene una política de gasto que esta solicitud no incluyó. Inténtelo de nuevo.",
+    fr: "Votre compte a une politique de dépenses que cette demande n'a pas incluse. Veuillez réessayer.",
+  },
+  PolicyViolation: {
+    en: "Your account's spending policy doesn't allow this payment.",
+    es: "La política de gasto de su cuenta no permite este pago.",
+    fr: "La politique de dépenses de votre compte n'autorise pas ce paiement.",
+  },
+  PolicyOpen: {
+    en: "Remove your spending policy before closing the account.",
+    es: "Elimine su política de gasto antes de cerrar la cuenta.",
+    fr: "Supprimez votre politique de dépenses avant de fermer le compte.",
+  },
+  RestrictionWidens: {
+    en: "A session can only reduce its own access. Ask the account owner for more.",
+    es: "Una sesión solo puede reducir su propio acceso. Pida más al titular de la cuenta.",
+    fr: "Une session ne peut que réduire son propre accès. Demandez-en davantage au titulaire du compte.",
+  },
+  KillPhraseNotSet: {
+    en: "This account has no emergency phrase set up.",
+    es: "Esta cuenta no tiene una frase de emergencia configurada.",
+    fr: "Ce compte n'a pas de phrase d'urgence configurée.",
+  },
+  KillPhraseMismatch: {
+    en: "That emergency phrase isn't correct.",
+    es: "Esa frase de emergencia no es correcta.",
+    fr: "Cette phrase d'urgence est incorrecte.",
+  },
+  AccountPaused: {
+    en: "This account is paused. The owner must unpause it before sessions can be used.",
+    es: "Esta cuenta está en pausa. El titular debe reactivarla antes de usar sesiones.",
+    fr: "Ce compte est en pause. Le titulaire doit le réactiver avant d'utiliser des sessions.",
+  },
+  NothingToCleanUp: {
+    en: "There are no expired sessions to clean up.",
+    es: "No hay sesiones caducadas que limpiar.",
+    fr: "Il n'y a aucune session expirée à nettoyer.",
+  },
+  SessionKeyLocked: {
+    en: "This session is locked for a while after too many refused payments.",
+    es: "Esta sesión está bloqueada un tiempo tras demasiados pagos rechazados.",
+    fr: "Cette session est verrouillée un moment après trop de paiements refusés.",
+  },
+  InvalidLockoutPolicy: {
+    en: "The lockout needs a number of failures, a window and a lock duration.",
+    es: "El bloqueo necesita un número de fallos, una ventana y una duración.",
+    fr: "Le verrouillage nécessite un nombre d'échecs, une fenêtre et une durée.",
+  },
+  DelegationWidens: {
+    en: "A sub-session can't have more access or time than the session creating it.",
+    es: "Una subsesión no puede tener más acceso ni tiempo que la sesión que la crea.",
+    fr: "Une sous-session ne peut pas avoir plus d'accès ni de durée que sa session.",
+  },
+  RecipientConsentMissing: {
+    en: "The recipient hasn't agreed to receive payments from this account yet.",
+    es: "El destinatario aún no ha aceptado recibir pagos de esta cuenta.",
+    fr: "Le destinataire n'a pas encore accepté de recevoir des paiements de ce compte.",
+  },
+  DelegationTooDeep: {
+    en: "This session can't hand out sub-sessions any further down the chain.",
+    es: "Esta sesión no puede crear subsesiones más abajo en la cadena.",
+    fr: "Cette session ne peut plus créer de sous-sessions plus bas dans la chaîne.",
+  },
+  OperatorSignatureMissing: {
+    en: "The session's operator didn't sign off on this transfer.",
+    es: "El operador de la sesión no firmó esta transferencia.",
+    fr: "L'opérateur de la session n'a pas signé ce transfert.",
+  },
+  NotGuardian: {
+    en: "Only this account's guardian can do that.",
+    es: "Solo el guardián de esta cuenta puede hacer eso.",
+    fr: "Seul le gardien de ce compte peut faire cela.",
+  },
+  ProgramBudgetMissing: {
+    en: "Your spending limit for that program was not included in the transaction.",
+    es: "Tu límite de gasto para ese programa no se incluyó en la transacción.",
+    fr: "Votre plafond de dépenses pour ce programme n'a pas été inclus dans la transaction.",
+  },
+  ProgramBudgetExceeded: {
+    en: "This would exceed your spending limit for that program this period.",
+    es: "Esto superaría tu límite de gasto para ese programa en este periodo.",
+    fr: "Cela dépasserait votre plafond de dépenses pour ce programme sur cette période.",
+  },
+  TooManyProgramBudgets: {
+    en: "You can't limit more programs, or that program already has a limit in another token.",
+    es: "No puedes limitar más programas, o ese programa ya tiene un límite en otro token.",
+    fr: "Impossible de plafonner plus de programmes, ou ce programme a déjà un plafond dans un autre jeton.",
+  },
+  SessionScopeMismatch: {
+    en: "This session only works with a different app.",
+    es: "Esta sesión solo funciona con otra aplicación.",
+    fr: "Cette session ne fonctionne qu'avec une autre application.",
+  },
+  SessionDomainMismatch: {
+    en: "This session was approved on a different website.",
+    es: "Esta sesión se aprobó en otro sitio web.",
+    fr: "Cette session a été approuvée sur un autre site web.",
+  },
+  ProgramPaused: {
+    en: "Sessions are paused for everyone while an incident is handled.",
+    es: "Las sesiones están pausadas para todos mientras se atiende un incidente.",
+    fr: "Les sessions sont suspendues pour tous le temps de traiter un incident.",
+  },
+  GlobalLimitExceeded: {
+    en: "This amount is above the limit that applies to every account.",
+    es: "Este importe supera el límite que se aplica a todas las cuentas.",
+    fr: "Ce montant dépasse la limite qui s'applique à tous les comptes.",
+  },
+  MintDenied: {
+    en: "This token has been blocked for everyone.",
+    es: "Este token ha sido bloqueado para todos.",
+    fr: "Ce jeton a été bloqué pour tous.",
+  },
+  MintDenylistFull: {
+    en: "The token denylist is full. Remove a token before adding another.",
+    es: "La lista de tokens bloqueados está llena. Quite uno antes de añadir otro.",
+    fr: "La liste des jetons bloqués est pleine. Retirez-en un avant d'en ajouter.",
+  },
+  InvalidProtocolFee: {
+    en: "The protocol fee can be at most 10%.",
+    es: "La comisión del protocolo puede ser como máximo del 10 %.",
+    fr: "Les frais du protocole ne peuvent pas dépasser 10 %.",
+  },
+  FeeAccountMissing: {
+    en: "A protocol fee applies, but the fee account was not included.",
+    es: "Se aplica una comisión del protocolo, pero no se incluyó la cuenta de comisiones.",
+    fr: "Des frais de protocole s'appliquent, mais le compte de frais est absent.",
+  },
+  FeeAccountMismatch: {
+    en: "The fee account does not belong to the protocol treasury for this token.",
+    es: "La cuenta de comisiones no pertenece a la tesorería del protocolo para este token.",
+    fr: "Le compte de frais n'appartient pas à la trésorerie du protocole pour ce jeton.",
+  },
+  InsufficientTreasuryBalance: {
+    en: "The treasury does not hold enough fees to withdraw that amount.",
+    es: "La tesorería no tiene suficientes comisiones para retirar ese importe.",
+    fr: "La trésorerie ne détient pas assez de frais pour retirer ce montant.",
+  },
+  AccountAlreadyMigrated: {
+    en: "This account is already up to date.",
+    es: "Esta cuenta ya está actualizada.",
+    fr: "Ce compte est déjà à jour.",
+  },
+  SourceTokenOwnerMismatch: {
+    en: "Payments can only come from your own token account.",
+    es: "Los pagos solo pueden salir de su propia cuenta de tokens.",
+    fr: "Les paiements ne peuvent provenir que de votre propre compte de jetons.",
+  },
+  SourceTokenMintMismatch: {
+    en: "The paying token account holds a different token.",
+    es: "La cuenta de tokens que paga contiene otro token.",
+    fr: "Le compte de jetons payeur contient un autre jeton.",
+  },
+  RecipientMismatch: {
+    en: "The destination account doesn't belong to the intended recipient.",
+    es: "La cuenta de destino no pertenece al destinatario previsto.",
+    fr: "Le compte de destination n'appartient pas au destinataire prévu.",
+  },
+  RecipientNotCanonicalAta: {
+    en: "Send to the recipient's main token account for this token.",
+    es: "Envíe a la cuenta de tokens principal del destinatario para este token.",
+    fr: "Envoyez vers le compte de jetons principal du destinataire pour ce jeton.",
+  },
+  SwapOutputTooLow: {
+    en: "The swap returned fewer tokens than the pipeline's minimum.",
+    es: "El intercambio devolvió menos tokens que el mínimo del pipeline.",
+    fr: "L'échange a rendu moins de jetons que le minimum du pipeline.",
+  },
+  SolSpendUnsupported: {
+    en: "Session keys can't pay out SOL while this account uses token-only protections.",
+    es: "Las claves de sesión no pueden pagar SOL mientras esta cuenta use protecciones solo para tokens.",
+    fr: "Les clés de session ne peuvent pas payer en SOL tant que ce compte utilise des protections réservées aux jetons.",
+  },
+  CustomActionUnsupported: {
+    en: "Session keys can't run custom actions while this account uses token-only protections.",
+    es: "Las claves de sesión no pueden ejecutar acciones personalizadas mientras esta cuenta use protecciones exclusivas de tokens.",
+    fr: "Les clés de session ne peuvent pas exécuter d'actions personnalisées tant que ce compte utilise des protections réservées aux jetons.",
+  },
+  PipelineControlsUnsupported: {
+    en: "Pipelines can't run while this account uses protections or fees their steps don't apply.",
+    es: "Los pipelines no pueden ejecutarse mientras esta cuenta use protecciones o comisiones que sus pasos no aplican.",
+    fr: "Les pipelines ne peuvent pas s'exécuter tant que ce compte utilise des protections ou des frais que leurs étapes n'appliquent pas.",
+  },
+  SwapAccountNotAllowed: {
+    en: "The swap was given another token account this wallet approved for session spending.",
+    es: "El intercambio recibió otra cuenta de tokens que esta billetera aprobó para gastos de sesión.",
+    fr: "L'échange a reçu un autre compte de jetons que ce portefeuille a approuvé pour les dépenses de session.",
+  },
+  LockoutPolicyMissing: {
+    en: "This wallet has no lockout policy to count refused spends toward.",
+    es: "Esta billetera no tiene una política de bloqueo para contar gastos rechazados.",
+    fr: "Ce portefeuille n'a pas de politique de verrouillage pour compter les dépenses refusées.",
+  },
+  SpendNotRefused: {
+    en: "The session key would allow this spend, so there is no refusal to count.",
+    es: "La clave de sesión permitiría este gasto, así que no hay rechazo que contar.",
+    fr: "La clé de session autoriserait cette dépense, il n'y a donc aucun refus à compter.",
+  },
+  RecipientBlocked: {
+    en: "This recipient is blocked, so the transfer was refused.",
+    es: "Este destinatario está bloqueado, así que se rechazó la transferencia.",
+    fr: "Ce destinataire est bloqué, le transfert a donc été refusé.",
+  },
+};
+
+/**
+ * Failures raised by other programs or Anchor account validation that commonly
+ * surface through our instructions
+ */
+const COMMON_MESSAGES: Record<string, Messages> = {
+  // SPL Token / Token-2022 (custom error codes)
+  "token:1": {
+    en: "Not enough tokens in your account.",
+    es: "No hay suficientes tokens en tu cuenta.",
+    fr: "Pas assez de jetons sur votre compte.",
+  },
+  "token:3": {
+    en: "The token account does not match the token being sent.",
+    es: "La cuenta de token no coincide con el token enviado.",
+    fr: "Le compte de jetons ne correspond pas au jeton envoyé.",
+  },
+  "token:4": {
+    en: "The session is not approved to move tokens from this account. Approve it again.",
+    es: "La sesión no está aprobada para mover tokens de esta cuenta. Apruébala de nuevo.",
+    fr: "La session n'est pas autorisée à déplacer les jetons de ce compte. Approuvez-la à nouveau.",
+  },
+  "token:17": {
+    en: "This token account is frozen.",
+    es: "Esta cuenta de token está congelada.",
+    fr: "Ce compte de jetons est gelé.",
+  },
+  // System program
+  "system:1": {
+    en: "Not enough SOL to complete this transaction.",
+    es: "No hay suficiente SOL para completar esta transacción.",
+    fr: "Pas assez de SOL pour effectuer cette transaction.",
+  },
+  insufficientFunds: {
+    en: "Not enough SOL to pay transaction fees.",
+    es: "No hay suficiente SOL para pagar las comisiones.",
+    fr: "Pas assez de SOL pour payer les frais de transaction.",
+  },
+  // Anchor account validation
+  AccountNotInitialized: {
+    en: "Your session account has not been set up yet.",
+    es: "Tu cuenta de sesiones aún no ha sido creada.",
+    fr: "Votre compte de sessions n'a pas encore été créé.",
+  },
+  ConstraintSeeds: {
+    en: "An account in the transaction does not belong to this wallet.",
+    es: "Una cuenta de la transacción no pertenece a esta billetera.",
+    fr: "Un compte de la transaction n'appartient pas à ce portefeuille.",
+  },
+  ConstraintHasOne: {
+    en: "Only the account owner can do this.",
+    es: "Solo el propietario de la cuenta puede hacer esto.",
+    fr: "Seul le propriétaire du compte peut faire cela.",
+  },
+  ConstraintRaw: {
+    en: "An account in the transaction is not the one expected.",
+    es: "Una cuenta de la transacción no es la esperada.",
+    fr: "Un compte de la transaction n'est pas celui attendu.",
+  },
+  ConstraintSigner: {
+    en: "A required signature is missing.",
+    es: "Falta una firma requerida.",
+    fr: "Une signature requise est manquante.",
+  },
+  unknown: {
+    en: "The transaction failed. Please try again.",
+    es: "La transacción falló. Inténtalo de nuevo.",
+    fr: "La transaction a échoué. Veuillez réessayer.",
+  },
+};
+
+const ANCHOR_NAMES: Record<number, string> = {
+  2001: "ConstraintHasOne",
+  2002: "ConstraintSigner",
+  2003: "ConstraintRaw",
+  2006: "ConstraintSeeds",
+  3012: "AccountNotInitialized",
+};
+
+const SYSTEM_PROGRAM_ID = "11111111111111111111111111111111";
+
+/**
+ * A failure translated for display
+ */
+export interface ExplainedError {
+  /** Catalog key: a program error name, an Anchor error name, or `<program>:<code>` */
+  key: string;
+  /** Numeric error code, when one was found */
+  code?: number;
+  /** Whether the error came from the session key program itself */
+  isProgramError: boolean;
+  message: string;
+}
+
+/**
+ * Look up the user-facing message for a program error name or code
+ */
+export function programErrorMessage(
+  error: ProgramErrorName | number,
+  locale: Locale = "en"
+): string | undefined {
+  const name = typeof error === "number" ? PROGRAM_ERRORS[error - 6000] : error;
+  return name ? PROGRAM_MESSAGES[name][locale] : undefined;
+}
+
+/**
+ * Translate any error thrown while sending one of our instructions into a
+ * consistent, localized message. Handles Anchor errors, simulation logs from
+ * `SendTransactionError`, and raw `custom program error: 0x..` strings.
+ */
+export function explainError(
+  err: unknown,
+  locale: Locale = "en"
+): ExplainedError {
+  const { code, programId } = extractCode(err);
+
+  if (code !== undefined) {
+    if (code >= 6000 && PROGRAM_ERRORS[code - 6000]) {
+      const key = PROGRAM_ERRORS[code - 6000];
+      return {
+        key,
+        code,
+        isProgramError: true,
+        message: PROGRAM_MESSAGES[key][locale],
+      };
+    }
+    const key = commonKey(code, programId);
+    if (key && COMMON_MESSAGES[key]) {
+      return {
+        key,
+        code,
+        isProgramError: false,
+        message: COMMON_MESSAGES[key][locale],
+      };
+    }
+  }
+
+  if (/insufficient (funds|lamports)/i.test(String(err))) {
+    return {
+      key: "insufficientFunds",
+      isProgramError: false,
+      message: COMMON_MESSAGES.insufficientFunds[locale],
+    };
+  }
+
+  return {
+    key: "unknown",
+    code,
+    isProgramError: false,
+    message: COMMON_MESSAGES.unknown[locale],
+  };
+}
+
+function extractCode(err: unknown): { code?: number; programId?: string } {
+  if (err instanceof AnchorError) {
+    return {
+      code: err.error.errorCode.number,
+      programId: err.program.toBase58(),
+    };
+  }
+
+  const logs: string[] =
+    err instanceof SendTransactionError
+      ? err.logs ?? []
+      : ((err as any)?.logs as string[] | undefined) ?? [];
+  const text = [String(err), ...logs].join("\n");
+
+  const custom = /custom program error: (0x[0-9a-f]+)/i.exec(text);
+  if (!custom) return {};
+
+  // The failing program is the last one to report failure in the logs
+  const failed = [...text.matchAll(/Program (\w+) failed/g)].pop();
+  return { code: parseInt(custom[1], 16), programId: failed?.[1] };
+}
+
+function commonKey(code: number, programId?: string): string | undefined {
+  if (ANCHOR_NAMES[code]) return ANCHOR_NAMES[code];
+  if (!programId) return undefined;
+  if (
+    programId === TOKEN_PROGRAM_ID.toBase58() ||
+    programId === TOKEN_2022_PROGRAM_ID.toBase58()
+  ) {
+    return `token:${code}`;
+  }
+  if (programId === SYSTEM_PROGRAM_ID) return `system:${code}`;
+  return undefined;
+}
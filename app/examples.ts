@@ -65,8 +65,15 @@ async function expirationTypes() {
       canTransfer: true,
       canDelegate: false,
       canExecuteCustom: false,
+      canSelfRotate: false,
       maxTransferAmount: new BN(1000000000), // 1 SOL
+      dailyLimit: new BN(0),
+      spendWindowSeconds: 0,
+      maxUsesPerWindow: 0,
+      useWindowSeconds: 0,
       customFlags: 0,
+      maxCpiDepth: 0,
+      maxCustomAccounts: 0,
     }
   );
   console.log("✅ Block-based key: expires after 900 blocks");
@@ -97,8 +104,15 @@ async function permissionExamples() {
       canTransfer: true,
       canDelegate: false,
       canExecuteCustom: false,
+      canSelfRotate: false,
       maxTransferAmount: new BN(0.01 * LAMPORTS_PER_SOL), // 0.01 SOL limit
+      dailyLimit: new BN(0),
+      spendWindowSeconds: 0,
+      maxUsesPerWindow: 0,
+      useWindowSeconds: 0,
       customFlags: 0,
+      maxCpiDepth: 0,
+      maxCustomAccounts: 0,
     },
   });
   console.log("✅ Mobile key: limited to 0.01 SOL transfers");
@@ -113,8 +127,15 @@ async function permissionExamples() {
       canTransfer: true,
       canDelegate: false,
       canExecuteCustom: true, // Can interact with DEXs
+      canSelfRotate: false,
       maxTransferAmount: new BN(5 * LAMPORTS_PER_SOL),
+      dailyLimit: new BN(0),
+      spendWindowSeconds: 0,
+      maxUsesPerWindow: 0,
+      useWindowSeconds: 0,
       customFlags: 1, // Custom flag for "trading only"
+      maxCpiDepth: 0,
+      maxCustomAccounts: 0,
     },
   });
   console.log("✅ Bot key: can trade up to 5 SOL with DEX access");
@@ -159,8 +180,15 @@ async function teamWallet() {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: true,
+          canSelfRotate: false,
           maxTransferAmount: new BN(role.maxTransfer * LAMPORTS_PER_SOL),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
         },
       });
     }
@@ -190,7 +218,9 @@ async function keyManagement() {
     3600,
     PermissionPreset.FULL_ACCESS
   );
-  await sdk.revokeSessionKey(authority.publicKey, oldKey.publicKey);
+  await sdk.revokeSessionKey(authority.publicKey, oldKey.publicKey, {
+    rotated: {},
+  });
   console.log("✅ Rotated: old key revoked, new key active");
 
   // Emergency revoke all
@@ -0,0 +1,479 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { PolicyRule } from "./sdk";
+
+/**
+ * Authoring helpers for policy documents (`initialize_policy` /
+ * `update_policy`): build rules, lint them for combinations the authority
+ * can't have meant, dry-run sample spends with the same logic the program
+ * evaluates, and print the effective permissions for a confirmation screen.
+ * `lintPolicy`, `simulatePolicy` and `describePolicy` also take rules fetched
+ * from an existing `PolicyAccount`.
+ */
+
+/** Mirrors `MAX_POLICY_RULES` */
+export const MAX_POLICY_RULES = 8;
+/** Mirrors `MAX_POLICY_LIST_LEN` */
+export const MAX_POLICY_LIST_LEN = 4;
+
+const MINUTES_PER_DAY = 24 * 60;
+const WEEKDAYS = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"] as const;
+
+export type Weekday = typeof WEEKDAYS[number];
+export type PolicyCondition = PolicyRule["condition"];
+
+/**
+ * Bitmask for a `schedule` rule (bit 0 = Monday)
+ */
+export function weekdayMask(...days: Weekday[]): number {
+  return days.reduce((mask, day) => mask | (1 << WEEKDAYS.indexOf(day)), 0);
+}
+
+export interface PolicyFinding {
+  /**
+   * `invalid` rules are rejected on-chain; the other kinds are accepted but
+   * are probably not what the authority meant
+   */
+  kind: "invalid" | "conflict" | "unreachable" | "always-deny";
+  /** Indexes of the rules involved */
+  rules: number[];
+  message: string;
+}
+
+/**
+ * A spend to dry-run, shaped like the program's `PolicyRequest`
+ */
+export interface PolicySample {
+  sessionKey: PublicKey;
+  /** Unix timestamp; defaults to now */
+  now?: number;
+  amount: BN | number;
+  /** Wrapped SOL mint for native SOL; omit for custom actions */
+  mint?: PublicKey;
+  /** Receiving wallet, or the owner of the receiving token account */
+  recipient?: PublicKey;
+  /** Receiving token account; defaults to `recipient` */
+  recipientAccount?: PublicKey;
+}
+
+export interface PolicyDecision {
+  allowed: boolean;
+  /** First rule that rejected the sample, as the program logs it */
+  rule?: number;
+}
+
+const nowSeconds = () => Math.floor(Date.now() / 1000);
+
+const num = (value: BN | number) =>
+  BN.isBN(value) ? (value as BN).toNumber() : (value as number);
+
+const appliesTo = (rule: PolicyRule, sessionKey: PublicKey) =>
+  !rule.sessionKey || rule.sessionKey.equals(sessionKey);
+
+/** Some session key is subject to both rules */
+const overlaps = (a: PolicyRule, b: PolicyRule) =>
+  !a.sessionKey || !b.sessionKey || a.sessionKey.equals(b.sessionKey);
+
+/** Every key subject to `b` is also subject to `a` */
+const covers = (a: PolicyRule, b: PolicyRule) =>
+  !a.sessionKey || (!!b.sessionKey && a.sessionKey.equals(b.sessionKey));
+
+const containsKey = (list: PublicKey[], key: PublicKey) =>
+  list.some((entry) => entry.equals(key));
+
+const isSubset = (a: PublicKey[], b: PublicKey[]) =>
+  a.every((key) => containsKey(b, key));
+
+const intersect = (a: PublicKey[], b: PublicKey[]) =>
+  a.filter((key) => containsKey(b, key));
+
+/**
+ * Same check as `PolicyCondition::allows` on-chain
+ */
+function allows(condition: PolicyCondition, sample: PolicySample): boolean {
+  const now = sample.now ?? nowSeconds();
+  if ("maxAmount" in condition) {
+    return new BN(sample.amount).lte(condition.maxAmount[0]);
+  }
+  if ("activeBetween" in condition) {
+    const { start, end } = condition.activeBetween;
+    return num(start) <= now && now < num(end);
+  }
+  if ("schedule" in condition) {
+    const { weekdays, startMinute, endMinute } = condition.schedule;
+    // 1970-01-01 was a Thursday, day 3 counting from Monday
+    const day = (((Math.floor(now / 86_400) + 3) % 7) + 7) % 7;
+    const minute = Math.floor((((now % 86_400) + 86_400) % 86_400) / 60);
+    const inHours =
+      startMinute <= endMinute
+        ? startMinute <= minute && minute < endMinute
+        : minute >= startMinute || minute < endMinute;
+    return (weekdays & (1 << day)) !== 0 && inHours;
+  }
+  if ("recipients" in condition) {
+    const recipientAccount = sample.recipientAccount ?? sample.recipient;
+    if (!sample.recipient || !recipientAccount) return true;
+    const list = condition.recipients[0];
+    return (
+      containsKey(list, sample.recipient) || containsKey(list, recipientAccount)
+    );
+  }
+  return !sample.mint || containsKey(condition.mints[0], sample.mint);
+}
+
+/**
+ * Dry-run a spend against a policy, mirroring `PolicyDocument::evaluate`
+ */
+export function simulatePolicy(
+  rules: PolicyRule[],
+  sample: PolicySample
+): PolicyDecision {
+  const rule = rules.findIndex(
+    (r) => appliesTo(r, sample.sessionKey) && !allows(r.condition, sample)
+  );
+  return rule === -1 ? { allowed: true } : { allowed: false, rule };
+}
+
+/** Why the program's `validate` would reject this rule, if it would */
+function invalidReason(condition: PolicyCondition): string | null {
+  if ("activeBetween" in condition) {
+    const { start, end } = condition.activeBetween;
+    return num(start) < num(end) ? null : "start must be before end";
+  }
+  if ("schedule" in condition) {
+    const { weekdays, startMinute, endMinute } = condition.schedule;
+    if ((weekdays & 0x7f) === 0) return "no weekdays are selected";
+    if (startMinute >= MINUTES_PER_DAY || endMinute >= MINUTES_PER_DAY) {
+      return "minutes must be below 1440";
+    }
+    return null;
+  }
+  const list =
+    "recipients" in condition
+      ? condition.recipients[0]
+      : "mints" in condition
+      ? condition.mints[0]
+      : [];
+  return list.length > MAX_POLICY_LIST_LEN
+    ? `lists hold at most ${MAX_POLICY_LIST_LEN} entries`
+    : null;
+}
+
+/** Why this rule on its own rejects every spend it looks at, if it does */
+function alwaysDenyReason(
+  condition: PolicyCondition,
+  now: number
+): string | null {
+  if ("maxAmount" in condition) {
+    return condition.maxAmount[0].isZero()
+      ? "a zero max amount only lets custom actions through"
+      : null;
+  }
+  if ("activeBetween" in condition) {
+    return num(condition.activeBetween.end) <= now
+      ? "its active range has already ended"
+      : null;
+  }
+  if ("schedule" in condition) {
+    const { startMinute, endMinute } = condition.schedule;
+    return startMinute === endMinute ? "its daily window is empty" : null;
+  }
+  if ("recipients" in condition) {
+    return condition.recipients[0].length === 0
+      ? "an empty recipient list rejects every payment"
+      : null;
+  }
+  return condition.mints[0].length === 0
+    ? "an empty mint list rejects every transfer"
+    : null;
+}
+
+/** `a` and `b` can never both pass, so a key under both is always denied */
+function conflicts(a: PolicyCondition, b: PolicyCondition): boolean {
+  if ("activeBetween" in a && "activeBetween" in b) {
+    const [x, y] = [a.activeBetween, b.activeBetween];
+    return num(x.end) <= num(y.start) || num(y.end) <= num(x.start);
+  }
+  if ("schedule" in a && "schedule" in b) {
+    return (a.schedule.weekdays & b.schedule.weekdays) === 0;
+  }
+  if ("recipients" in a && "recipients" in b) {
+    return intersect(a.recipients[0], b.recipients[0]).length === 0;
+  }
+  if ("mints" in a && "mints" in b) {
+    return intersect(a.mints[0], b.mints[0]).length === 0;
+  }
+  return false;
+}
+
+/** Everything `a` allows, `b` allows too */
+function stricter(a: PolicyCondition, b: PolicyCondition): boolean {
+  if ("maxAmount" in a && "maxAmount" in b) {
+    return a.maxAmount[0].lte(b.maxAmount[0]);
+  }
+  if ("activeBetween" in a && "activeBetween" in b) {
+    const [x, y] = [a.activeBetween, b.activeBetween];
+    return num(x.start) >= num(y.start) && num(x.end) <= num(y.end);
+  }
+  if ("schedule" in a && "schedule" in b) {
+    const [x, y] = [a.schedule, b.schedule];
+    return (
+      (x.weekdays & ~y.weekdays & 0x7f) === 0 &&
+      x.startMinute === y.startMinute &&
+      x.endMinute === y.endMinute
+    );
+  }
+  if ("recipients" in a && "recipients" in b) {
+    return isSubset(a.recipients[0], b.recipients[0]);
+  }
+  if ("mints" in a && "mints" in b) {
+    return isSubset(a.mints[0], b.mints[0]);
+  }
+  return false;
+}
+
+/**
+ * Check a rule list for what the program would reject (`invalid`) and for
+ * combinations it accepts but that are likely mistakes: rules no key can
+ * satisfy together (`conflict`), rules a stricter rule over the same keys
+ * makes redundant (`unreachable`), and rules that deny everything on their
+ * own (`always-deny`)
+ */
+export function lintPolicy(
+  rules: PolicyRule[],
+  now = nowSeconds()
+): PolicyFinding[] {
+  const findings: PolicyFinding[] = [];
+  if (rules.length > MAX_POLICY_RULES) {
+    findings.push({
+      kind: "invalid",
+      rules: [],
+      message: `A policy holds at most ${MAX_POLICY_RULES} rules`,
+    });
+  }
+  rules.forEach((rule, index) => {
+    const invalid = invalidReason(rule.condition);
+    if (invalid) {
+      findings.push({
+        kind: "invalid",
+        rules: [index],
+        message: `Rule ${index}: ${invalid}`,
+      });
+      return;
+    }
+    const deny = alwaysDenyReason(rule.condition, now);
+    if (deny) {
+      findings.push({
+        kind: "always-deny",
+        rules: [index],
+        message: `Rule ${index}: ${deny}`,
+      });
+    }
+  });
+
+  for (let i = 0; i < rules.length; i++) {
+    for (let j = i + 1; j < rules.length; j++) {
+      const [a, b] = [rules[i], rules[j]];
+      if (!overlaps(a, b)) continue;
+      if (conflicts(a.condition, b.condition)) {
+        findings.push({
+          kind: "conflict",
+          rules: [i, j],
+          message:
+            `Rules ${i} and ${j} can't both pass, ` +
+            "so keys under both are always denied",
+        });
+      } else if (covers(a, b) && stricter(a.condition, b.condition)) {
+        findings.push({
+          kind: "unreachable",
+          rules: [j, i],
+          message: `Rule ${j} never rejects anything rule ${i} allows`,
+        });
+      } else if (covers(b, a) && stricter(b.condition, a.condition)) {
+        findings.push({
+          kind: "unreachable",
+          rules: [i, j],
+          message: `Rule ${i} never rejects anything rule ${j} allows`,
+        });
+      }
+    }
+  }
+  return findings;
+}
+
+const shortKey = (key: PublicKey) => {
+  const text = key.toBase58();
+  return `${text.slice(0, 4)}…${text.slice(-4)}`;
+};
+
+const twoDigits = (value: number) => (value < 10 ? `0${value}` : `${value}`);
+
+const clock = (minute: number) =>
+  `${twoDigits(Math.floor(minute / 60))}:${twoDigits(minute % 60)}`;
+
+const date = (timestamp: BN | number) =>
+  new Date(num(timestamp) * 1000).toISOString();
+
+/** Effective limits for the rules that apply to one group of keys */
+function describeScope(rules: PolicyRule[]): string[] {
+  const conditions = rules.map((r) => r.condition);
+  const lines: string[] = [];
+
+  const amounts: BN[] = [];
+  const ranges: { start: BN; end: BN }[] = [];
+  for (const c of conditions) {
+    if ("maxAmount" in c) amounts.push(c.maxAmount[0]);
+    if ("activeBetween" in c) ranges.push(c.activeBetween);
+  }
+  lines.push(
+    `  Max per spend: ${
+      amounts.length
+        ? amounts.reduce((min, amount) => BN.min(min, amount)).toString()
+        : "no limit"
+    }`
+  );
+
+  if (ranges.length) {
+    const start = Math.max(...ranges.map((r) => num(r.start)));
+    const end = Math.min(...ranges.map((r) => num(r.end)));
+    lines.push(
+      start < end
+        ? `  Active: ${date(start)} to ${date(end)}`
+        : "  Active: never (the date ranges don't overlap)"
+    );
+  } else {
+    lines.push("  Active: always");
+  }
+
+  for (const c of conditions) {
+    if (!("schedule" in c)) continue;
+    const { weekdays, startMinute, endMinute } = c.schedule;
+    const days = WEEKDAYS.filter((_, day) => weekdays & (1 << day));
+    lines.push(
+      `  Schedule: ${days.join(", ")} ${clock(startMinute)}-${clock(
+        endMinute
+      )} UTC`
+    );
+  }
+
+  const lists = (pick: (c: PolicyCondition) => PublicKey[] | null) => {
+    const matching = conditions
+      .map(pick)
+      .filter((list): list is PublicKey[] => list !== null);
+    if (!matching.length) return "any";
+    const allowed = matching.reduce(intersect);
+    return allowed.length ? allowed.map(shortKey).join(", ") : "none";
+  };
+  lines.push(
+    `  Recipients: ${lists((c) =>
+      "recipients" in c ? c.recipients[0] : null
+    )}`
+  );
+  lines.push(`  Mints: ${lists((c) => ("mints" in c ? c.mints[0] : null))}`);
+  return lines;
+}
+
+/**
+ * Human-readable effective permissions: one block for every session key,
+ * then one per key with rules of its own (its rules combined with the
+ * account-wide ones)
+ */
+export function describePolicy(rules: PolicyRule[]): string {
+  const global = rules.filter((r) => !r.sessionKey);
+  const keys: PublicKey[] = [];
+  for (const rule of rules) {
+    if (rule.sessionKey && !containsKey(keys, rule.sessionKey)) {
+      keys.push(rule.sessionKey);
+    }
+  }
+  const blocks = [["Every session key:", ...describeScope(global)]];
+  for (const key of keys) {
+    blocks.push([
+      `Session key ${shortKey(key)}:`,
+      ...describeScope(rules.filter((r) => appliesTo(r, key))),
+    ]);
+  }
+  return blocks.map((lines) => lines.join("\n")).join("\n\n");
+}
+
+/**
+ * Fluent builder for a policy's rules. Each method adds one rule, scoped to
+ * `sessionKey` when given and to every key otherwise; pass `build()` to
+ * `TimeSDK.initializePolicy` / `updatePolicy`.
+ */
+export class PolicyBuilder {
+  private readonly entries: PolicyRule[];
+
+  constructor(rules: PolicyRule[] = []) {
+    this.entries = [...rules];
+  }
+
+  get rules(): PolicyRule[] {
+    return [...this.entries];
+  }
+
+  private add(condition: PolicyCondition, sessionKey?: PublicKey): this {
+    this.entries.push({ sessionKey: sessionKey ?? null, condition });
+    return this;
+  }
+
+  maxAmount(amount: BN | number, sessionKey?: PublicKey): this {
+    return this.add({ maxAmount: { 0: new BN(amount) } }, sessionKey);
+  }
+
+  /** Unix timestamps; spends are allowed in `[start, end)` */
+  activeBetween(start: number, end: number, sessionKey?: PublicKey): this {
+    return this.add(
+      { activeBetween: { start: new BN(start), end: new BN(end) } },
+      sessionKey
+    );
+  }
+
+  /**
+   * UTC minutes of the day; an end before the start wraps past midnight
+   */
+  schedule(
+    days: Weekday[],
+    startMinute: number,
+    endMinute: number,
+    sessionKey?: PublicKey
+  ): this {
+    return this.add(
+      {
+        schedule: { weekdays: weekdayMask(...days), startMinute, endMinute },
+      },
+      sessionKey
+    );
+  }
+
+  recipients(list: PublicKey[], sessionKey?: PublicKey): this {
+    return this.add({ recipients: { 0: list } }, sessionKey);
+  }
+
+  mints(list: PublicKey[], sessionKey?: PublicKey): this {
+    return this.add({ mints: { 0: list } }, sessionKey);
+  }
+
+  lint(now?: number): PolicyFinding[] {
+    return lintPolicy(this.entries, now);
+  }
+
+  simulate(sample: PolicySample): PolicyDecision {
+    return simulatePolicy(this.entries, sample);
+  }
+
+  describe(): string {
+    return describePolicy(this.entries);
+  }
+
+  /**
+   * The rules, or an error listing what the program would reject
+   */
+  build(): PolicyRule[] {
+    const invalid = this.lint().filter((f) => f.kind === "invalid");
+    if (invalid.length) {
+      throw new Error(invalid.map((f) => f.message).join("; "));
+    }
+    return this.rules;
+  }
+}
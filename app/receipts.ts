@@ -0,0 +1,126 @@
+import { PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
+
+/**
+ * Depth of the lifetime history tree in `AccountStats` (HISTORY_TREE_DEPTH)
+ */
+export const HISTORY_TREE_DEPTH = 32;
+
+/**
+ * Depth of the per-batch tree in `ActivityDigestEmitted` (DIGEST_TREE_DEPTH)
+ */
+export const DIGEST_TREE_DEPTH = 16;
+
+/**
+ * A transfer as committed on-chain; `slot` is the slot the transaction landed in
+ */
+export interface ExecutionReceipt {
+  sessionKey: PublicKey;
+  mint: PublicKey;
+  destination: PublicKey;
+  amount: bigint;
+  slot: bigint;
+}
+
+/**
+ * Sibling hashes from a leaf up to the root, lowest level first
+ */
+export type MerkleProof = Buffer[];
+
+function sha256(...parts: Buffer[]): Buffer {
+  return createHash("sha256").update(Buffer.concat(parts)).digest();
+}
+
+function u64le(value: bigint): Buffer {
+  const buf = Buffer.alloc(8);
+  buf.writeBigUInt64LE(value);
+  return buf;
+}
+
+/**
+ * Leaf hash of a receipt, matching `receipts::receipt_leaf` in the program
+ */
+export function receiptLeaf(receipt: ExecutionReceipt): Buffer {
+  return sha256(
+    receipt.sessionKey.toBuffer(),
+    receipt.mint.toBuffer(),
+    receipt.destination.toBuffer(),
+    u64le(receipt.amount),
+    u64le(receipt.slot)
+  );
+}
+
+/**
+ * Hashes of all-zero subtrees, indexed by level
+ */
+function zeroHashes(depth: number): Buffer[] {
+  const zeros = [Buffer.alloc(32)];
+  for (let h = 0; h < depth; h++) {
+    zeros.push(sha256(zeros[h], zeros[h]));
+  }
+  return zeros;
+}
+
+/**
+ * Root of a fixed-depth tree over `leaves`, padded with zero leaves; matches
+ * `MerkleFrontier::root`
+ */
+export function merkleRoot(
+  leaves: Buffer[],
+  depth = HISTORY_TREE_DEPTH
+): Buffer {
+  const zeros = zeroHashes(depth);
+  let level = leaves;
+  for (let h = 0; h < depth; h++) {
+    const next: Buffer[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(sha256(level[i], level[i + 1] ?? zeros[h]));
+    }
+    level = next;
+  }
+  return level[0] ?? zeros[depth];
+}
+
+/**
+ * Inclusion proof for `leaves[index]`, to hand to a third party alongside the
+ * receipt and the on-chain root
+ */
+export function merkleProof(
+  leaves: Buffer[],
+  index: number,
+  depth = HISTORY_TREE_DEPTH
+): MerkleProof {
+  const zeros = zeroHashes(depth);
+  const proof: MerkleProof = [];
+  let level = leaves;
+  let position = index;
+  for (let h = 0; h < depth; h++) {
+    const sibling = position % 2 ? position - 1 : position + 1;
+    proof.push(level[sibling] ?? zeros[h]);
+    const next: Buffer[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(sha256(level[i], level[i + 1] ?? zeros[h]));
+    }
+    level = next;
+    position = Math.floor(position / 2);
+  }
+  return proof;
+}
+
+/**
+ * Check that `leaf` sits at `index` in the tree with the given root
+ */
+export function verifyMerkleProof(
+  leaf: Buffer,
+  index: number,
+  proof: MerkleProof,
+  root: Buffer | Uint8Array
+): boolean {
+  let node = leaf;
+  let position = index;
+  for (const sibling of proof) {
+    node = position % 2 ? sha256(sibling, node) : sha256(node, sibling);
+    position = Math.floor(position / 2);
+  }
+  return node.equals(Buffer.from(root));
+}
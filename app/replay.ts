@@ -0,0 +1,329 @@
+import { BN, EventParser, Idl, Program } from "@coral-xyz/anchor";
+import { Connection, PublicKey } from "@solana/web3.js";
+import { readFileSync } from "fs";
+import { Time } from "../target/types/time";
+
+/**
+ * Something the replay found wrong: a spend the reconstructed policy forbids, or
+ * on-chain state that doesn't match the replayed history
+ */
+export interface ReplayFinding {
+  kind: "violation" | "divergence";
+  sessionKey: PublicKey;
+  signature?: string;
+  slot?: number;
+  message: string;
+}
+
+export interface ReplayReport {
+  userAccount: PublicKey;
+  transactions: number;
+  /** Spends replayed against a reconstructed policy */
+  checkedSpends: number;
+  /** Session keys first seen after their creation, whose spends can't be checked */
+  unknownKeys: PublicKey[];
+  findings: ReplayFinding[];
+}
+
+interface SessionModel {
+  permissions: any;
+  priorPermissions: any | null;
+  wideningActiveAt: number;
+  expiresAtTime: number | null;
+  expiresAtSlot: number | null;
+  /** Sliding keys move `expiresAtTime` on each use, up to `maxExpiresAt` */
+  idleTimeout: number;
+  maxExpiresAt: number;
+  validFrom: number;
+  revoked: boolean;
+  suspended: boolean;
+  totalSpent: BN;
+  spendWindowStart: number;
+  spentInWindow: BN;
+}
+
+const SECONDS_PER_DAY = 86_400;
+
+/**
+ * Signatures touching `address` within `[fromSlot, toSlot]`, oldest first
+ */
+async function signaturesInRange(
+  connection: Connection,
+  address: PublicKey,
+  fromSlot: number,
+  toSlot: number
+) {
+  const found = [];
+  let before: string | undefined;
+  for (;;) {
+    const page = await connection.getSignaturesForAddress(address, {
+      before,
+      limit: 1000,
+    });
+    if (page.length === 0) break;
+    for (const s of page) {
+      if (!s.err && s.slot >= fromSlot && s.slot <= toSlot) found.push(s);
+    }
+    if (page[page.length - 1].slot < fromSlot) break;
+    before = page[page.length - 1].signature;
+  }
+  return found.reverse();
+}
+
+function activePermissions(model: SessionModel, now: number) {
+  return model.priorPermissions && now < model.wideningActiveAt
+    ? model.priorPermissions
+    : model.permissions;
+}
+
+/**
+ * Check a spend against the policy the key had at `now`, mirroring the program's
+ * session checks, and apply it to the model. Returns the reasons it should have failed.
+ */
+function checkSpend(
+  model: SessionModel,
+  amount: BN,
+  now: number,
+  slot: number
+): string[] {
+  const problems: string[] = [];
+  const permissions = activePermissions(model, now);
+  if (model.revoked) problems.push("key was revoked");
+  if (model.suspended) problems.push("key was suspended");
+  if (now < model.validFrom) problems.push("key was not yet valid");
+  if (
+    (model.expiresAtTime !== null && model.expiresAtTime <= now) ||
+    (model.expiresAtSlot !== null && model.expiresAtSlot <= slot)
+  ) {
+    problems.push("key had expired");
+  }
+  if (!permissions.canTransfer) problems.push("key lacked can_transfer");
+
+  model.totalSpent = model.totalSpent.add(amount);
+  const cap: BN = permissions.maxTransferAmount;
+  if (!cap.isZero() && model.totalSpent.gt(cap)) {
+    problems.push(`lifetime spend ${model.totalSpent} exceeds cap ${cap}`);
+  }
+
+  const window = permissions.spendWindowSeconds || SECONDS_PER_DAY;
+  if (now >= model.spendWindowStart + window) {
+    const elapsed = now - model.spendWindowStart;
+    model.spendWindowStart += elapsed - (elapsed % window);
+    model.spentInWindow = new BN(0);
+  }
+  model.spentInWindow = model.spentInWindow.add(amount);
+  const limit: BN = permissions.dailyLimit;
+  if (!limit.isZero() && model.spentInWindow.gt(limit)) {
+    problems.push(
+      `window spend ${model.spentInWindow} exceeds limit ${limit}`
+    );
+  }
+  return problems;
+}
+
+/**
+ * A successful use at `now` slides a sliding key's expiry forward
+ */
+function markUsed(model: SessionModel, now: number) {
+  if (model.idleTimeout > 0) {
+    model.expiresAtTime = Math.min(now + model.idleTimeout, model.maxExpiresAt);
+  }
+}
+
+/**
+ * Replay every successful transaction on `authority`'s user account in a slot range.
+ * Session key policy is rebuilt from the program's events and each session spend is
+ * re-checked against it; when the range reaches the present, replayed spend totals
+ * are compared with the account's current state. Only keys created inside the range
+ * can be checked, and digest-mode accounts (no per-transfer events) are not covered.
+ */
+export async function replay(
+  program: Program<Time>,
+  authority: PublicKey,
+  fromSlot: number,
+  toSlot: number
+): Promise<ReplayReport> {
+  const connection = program.provider.connection;
+  const [userAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from("user_account"), authority.toBuffer()],
+    program.programId
+  );
+  const parser = new EventParser(program.programId, program.coder);
+  const sessions = new Map<string, SessionModel>();
+  const unknown = new Map<string, PublicKey>();
+  const report: ReplayReport = {
+    userAccount,
+    transactions: 0,
+    checkedSpends: 0,
+    unknownKeys: [],
+    findings: [],
+  };
+
+  const spend = (
+    sessionKey: PublicKey,
+    amount: BN,
+    now: number,
+    slot: number,
+    signature: string
+  ) => {
+    const model = sessions.get(sessionKey.toBase58());
+    if (!model) {
+      unknown.set(sessionKey.toBase58(), sessionKey);
+      return;
+    }
+    report.checkedSpends++;
+    for (const message of checkSpend(model, amount, now, slot)) {
+      report.findings.push({
+        kind: "violation",
+        sessionKey,
+        signature,
+        slot,
+        message,
+      });
+    }
+    markUsed(model, now);
+  };
+
+  const signatures = await signaturesInRange(
+    connection,
+    userAccount,
+    fromSlot,
+    toSlot
+  );
+  for (const { signature, slot } of signatures) {
+    const tx = await connection.getTransaction(signature, {
+      maxSupportedTransactionVersion: 0,
+    });
+    if (!tx?.meta?.logMessages) continue;
+    report.transactions++;
+    const now = tx.blockTime ?? 0;
+
+    for (const event of parser.parseLogs(tx.meta.logMessages)) {
+      const data: any = event.data;
+      if (data.authority && !data.authority.equals(authority)) continue;
+      switch (event.name) {
+        case "sessionKeyCreated":
+          sessions.set(data.sessionKey.toBase58(), {
+            permissions: data.permissions,
+            priorPermissions: null,
+            wideningActiveAt: 0,
+            expiresAtTime: data.expiresAtTime?.toNumber() ?? null,
+            expiresAtSlot: data.expiresAtSlot?.toNumber() ?? null,
+            idleTimeout: data.idleTimeout,
+            maxExpiresAt: data.maxExpiresAt.toNumber(),
+            validFrom: data.validFrom.toNumber(),
+            revoked: false,
+            suspended: false,
+            totalSpent: new BN(0),
+            spendWindowStart: now,
+            spentInWindow: new BN(0),
+          });
+          break;
+        case "sessionKeyUpdated": {
+          const model = sessions.get(data.sessionKey.toBase58());
+          if (!model) break;
+          const activeAt = data.permissionsActiveAt.toNumber();
+          model.priorPermissions =
+            activeAt > now ? activePermissions(model, now) : null;
+          model.wideningActiveAt = activeAt;
+          model.permissions = data.permissions;
+          model.expiresAtTime = data.expiresAtTime?.toNumber() ?? null;
+          model.expiresAtSlot = data.expiresAtSlot?.toNumber() ?? null;
+          model.maxExpiresAt = data.maxExpiresAt.toNumber();
+          break;
+        }
+        case "sessionKeyRevoked": {
+          const model = sessions.get(data.sessionKey.toBase58());
+          if (model) model.revoked = true;
+          break;
+        }
+        case "sessionKeySuspended":
+        case "sessionKeyResumed": {
+          const model = sessions.get(data.sessionKey.toBase58());
+          if (model) model.suspended = event.name === "sessionKeySuspended";
+          break;
+        }
+        case "allSessionKeysRevoked":
+          sessions.forEach((model) => (model.revoked = true));
+          break;
+        case "sessionKeyTransferred": {
+          const from = data.oldSessionKey.toBase58();
+          const model = sessions.get(from);
+          if (!model) break;
+          sessions.delete(from);
+          sessions.set(data.newSessionKey.toBase58(), model);
+          break;
+        }
+        case "transferExecuted":
+          spend(data.sessionKey, data.amount, now, slot, signature);
+          break;
+        case "customActionExecuted":
+        case "pipelineExecuted": {
+          const model = sessions.get(data.sessionKey.toBase58());
+          if (model) markUsed(model, now);
+          break;
+        }
+        case "solWithdrawn":
+          if (data.sessionKey) {
+            spend(data.sessionKey, data.amount, now, slot, signature);
+          }
+          break;
+      }
+    }
+  }
+
+  // With the whole recent history replayed, the stored counters must agree
+  if (toSlot >= (await connection.getSlot())) {
+    const account = await program.account.userAccount.fetchNullable(
+      userAccount
+    );
+    for (const key of account?.sessionKeys ?? []) {
+      const model = sessions.get(key.pubkey.toBase58());
+      if (model && !model.totalSpent.eq(key.totalSpent)) {
+        report.findings.push({
+          kind: "divergence",
+          sessionKey: key.pubkey,
+          message: `replayed spend ${model.totalSpent} but account records ${key.totalSpent}`,
+        });
+      }
+    }
+  }
+
+  report.unknownKeys = Array.from(unknown.values());
+  return report;
+}
+
+// `yarn time-replay <authority> <from slot> <to slot> [rpc url]`
+if (require.main === module) {
+  const [authority, from, to, url] = process.argv.slice(2);
+  if (!authority || !from || !to) {
+    console.error(
+      "usage: time-replay <authority> <from slot> <to slot> [rpc url]"
+    );
+    process.exit(1);
+  }
+  const idl: Idl = JSON.parse(readFileSync("target/idl/time.json", "utf8"));
+  const connection = new Connection(url ?? "http://127.0.0.1:8899");
+  const program = new Program(idl, { connection }) as Program<Time>;
+
+  replay(program, new PublicKey(authority), Number(from), Number(to)).then(
+    (report) => {
+      console.log(
+        `${report.transactions} transactions, ${report.checkedSpends} spends checked`
+      );
+      for (const key of report.unknownKeys) {
+        console.log(
+          `unchecked: ${key.toBase58()} was created before the range`
+        );
+      }
+      for (const f of report.findings) {
+        console.log(
+          `${f.kind}: ${f.sessionKey.toBase58()} ${f.signature ?? ""} ${
+            f.message
+          }`
+        );
+      }
+      process.exit(report.findings.length > 0 ? 2 : 0);
+    }
+  );
+}
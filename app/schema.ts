@@ -0,0 +1,131 @@
+import { Idl, IdlField, IdlType, IdlTypeDef } from "@coral-xyz/anchor";
+import { readFileSync } from "fs";
+
+/**
+ * Field type in a layout: a primitive name, or a container around other types.
+ * `pubkey` is 32 raw bytes, `string` and `bytes` a u32 length then the bytes.
+ */
+export type FieldLayout =
+  | string
+  | { vec: FieldLayout }
+  | { option: FieldLayout }
+  | { array: [FieldLayout, number] }
+  | { defined: string };
+
+export type NamedField = { name: string; type: FieldLayout };
+
+export type TypeLayout =
+  | { kind: "struct"; fields: NamedField[] }
+  | { kind: "enum"; variants: { name: string; fields: NamedField[] }[] };
+
+/**
+ * An account or event: 8 discriminator bytes, then the Borsh encoding of `type`
+ */
+export interface DiscriminatedLayout {
+  name: string;
+  discriminator: number[];
+  type: string;
+}
+
+export interface BorshSchemas {
+  accounts: DiscriminatedLayout[];
+  events: DiscriminatedLayout[];
+  /** Every struct and enum referenced from an account or event, by name */
+  types: Record<string, TypeLayout>;
+  /** How each container is encoded, for decoders written without Anchor */
+  encoding: Record<string, string>;
+}
+
+const ENCODING = {
+  pubkey: "32 bytes",
+  string: "u32 little-endian byte length, then UTF-8 bytes",
+  bytes: "u32 little-endian length, then the bytes",
+  vec: "u32 little-endian element count, then each element",
+  option: "u8 tag (0 = none, 1 = some), then the value when some",
+  array: "each element in order, no length prefix",
+  enum: "u8 variant index, then the variant's fields",
+  integers: "little-endian; u128/i128 are 16 bytes",
+};
+
+function fieldLayout(type: IdlType): FieldLayout {
+  if (typeof type === "string") {
+    return type;
+  }
+  if ("vec" in type) {
+    return { vec: fieldLayout(type.vec) };
+  }
+  if ("option" in type) {
+    return { option: fieldLayout(type.option) };
+  }
+  if ("array" in type) {
+    const [inner, len] = type.array;
+    if (typeof len !== "number") {
+      throw new Error("generic array lengths are not supported");
+    }
+    return { array: [fieldLayout(inner), len] };
+  }
+  if ("defined" in type) {
+    return { defined: type.defined.name };
+  }
+  throw new Error(`unsupported IDL type ${JSON.stringify(type)}`);
+}
+
+function fieldsLayout(
+  fields: IdlField[] | IdlType[] | undefined
+): NamedField[] {
+  return (fields ?? []).map((field: IdlField | IdlType, i: number) =>
+    typeof field === "object" && "name" in field
+      ? { name: field.name, type: fieldLayout(field.type) }
+      : { name: String(i), type: fieldLayout(field as IdlType) }
+  );
+}
+
+function typeLayout(def: IdlTypeDef): TypeLayout {
+  const ty = def.type;
+  if (ty.kind === "struct") {
+    return { kind: "struct", fields: fieldsLayout(ty.fields) };
+  }
+  if (ty.kind === "enum") {
+    return {
+      kind: "enum",
+      variants: ty.variants.map((v) => ({
+        name: v.name,
+        fields: fieldsLayout(v.fields),
+      })),
+    };
+  }
+  throw new Error(`unsupported type definition ${def.name}`);
+}
+
+/**
+ * Borsh layouts of every account and event in the program, derived from its IDL,
+ * so indexers in other languages can decode them without hand-written layouts
+ */
+export function schemas(idl: Idl): BorshSchemas {
+  const types: Record<string, TypeLayout> = {};
+  for (const def of idl.types ?? []) {
+    types[def.name] = typeLayout(def);
+  }
+  const discriminated = (
+    entries: { name: string; discriminator: number[] }[]
+  ) =>
+    entries.map((e) => ({
+      name: e.name,
+      discriminator: Array.from(e.discriminator),
+      type: e.name,
+    }));
+
+  return {
+    accounts: discriminated(idl.accounts ?? []),
+    events: discriminated(idl.events ?? []),
+    types,
+    encoding: ENCODING,
+  };
+}
+
+// `yarn dump-schema [path/to/idl.json]` prints the schemas as JSON
+if (require.main === module) {
+  const path = process.argv[2] ?? "target/idl/time.json";
+  const idl: Idl = JSON.parse(readFileSync(path, "utf8"));
+  console.log(JSON.stringify(schemas(idl), null, 2));
+}
@@ -1,15 +1,25 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, AnchorProvider, BN } from "@coral-xyz/anchor";
 import { Time } from "../target/types/time";
+import { createHash } from "crypto";
 import {
   PublicKey,
   Keypair,
   SystemProgram,
+  SYSVAR_SLOT_HASHES_PUBKEY,
   Connection,
   Transaction,
   TransactionInstruction,
+  AccountMeta,
   sendAndConfirmTransaction,
+  Ed25519Program,
 } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { programDataAddress } from "./verify_deployment";
 
 /**
  * Expiration type for session keys
@@ -25,6 +35,17 @@ export enum ExpirationType {
  * This SDK provides high-level functions for managing session keys on Solana,
  * showcasing the power of account abstraction and delegated authority.
  */
+/**
+ * Bits of the `flags` in `MintRiskAssessed` events and `update_allowed_mints` return
+ * data, mirroring `MINT_RISK_*` in the program's `mint_risk` module
+ */
+export const MINT_RISK = {
+  freezeAuthority: 1 << 0,
+  transferFee: 1 << 1,
+  permanentDelegate: 1 << 2,
+  transferHook: 1 << 3,
+} as const;
+
 export class SessionKeySDK {
   private program: Program<Time>;
   private provider: AnchorProvider;
@@ -51,6 +72,7 @@ export class SessionKeySDK {
       .splApproveDelegate(amount)
       .accountsStrict({
         userAccount: userAccountPDA,
+        programConfig: this.getProgramConfigPDA()[0],
         authority,
         tokenAccount,
         mint,
@@ -62,29 +84,110 @@ export class SessionKeySDK {
       .rpc();
   }
 
+  /**
+   * Create a session key and approve the mint's delegate in one transaction,
+   * so onboarding takes a single signature
+   */
+  async approveAndCreateSession(
+    authority: PublicKey,
+    tokenAccount: PublicKey,
+    mint: PublicKey,
+    amount: BN,
+    sessionKeyPubkey: PublicKey,
+    durationSeconds: number,
+    permissions: SessionPermissions
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [delegateAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), userAccountPDA.toBuffer(), mint.toBuffer()],
+      this.program.programId
+    );
+
+    return this.program.methods
+      .approveAndCreateSession(
+        {
+          sessionPubkey: sessionKeyPubkey,
+          expiresAtTime: new BN(
+            Math.floor(Date.now() / 1000) + durationSeconds
+          ),
+          expiresAtSlot: null,
+          permissions,
+          termsHash: null,
+          label: encodeLabel(),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        },
+        amount
+      )
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        programConfig: this.getProgramConfigPDA()[0],
+        authority,
+        tokenAccount,
+        mint,
+        delegateAuthority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
   async splDelegatedTransfer(
     authority: PublicKey,
     sessionKeySigner: Keypair,
     fromToken: PublicKey,
     toToken: PublicKey,
     mint: PublicKey,
-    amount: BN
+    amount: BN,
+    category: number | null = null,
+    scope: PublicKey | null = null,
+    recipient: RecipientCheck | null = null
   ): Promise<string> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
     const [delegateAuthority] = PublicKey.findProgramAddressSync(
       [Buffer.from("delegate"), userAccountPDA.toBuffer(), mint.toBuffer()],
       this.program.programId
     );
+    const proof = await this.slotProofFor(userAccountPDA);
 
     const instructions = await this.program.methods
-      .splDelegatedTransfer(amount)
+      .splDelegatedTransfer(amount, proof.slotRef, category, scope, recipient)
       .accountsStrict({
         sessionSigner: sessionKeySigner.publicKey,
         userAccount: userAccountPDA,
+        programConfig: this.getProgramConfigPDA()[0],
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeySigner.publicKey
+        ),
+        policy: await this.findPolicy(userAccountPDA),
         fromToken,
         toToken,
         mint,
         delegateAuthority,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: await this.tokenRecipientConsent(
+          userAccountPDA,
+          toToken
+        ),
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: await this.spendBudgetFor(userAccountPDA, mint),
+        activityDigest: await this.activityDigestFor(userAccountPDA),
+        accountStats: await this.accountStatsFor(userAccountPDA),
+        savingsVault: await this.savingsVaultFor(userAccountPDA, mint),
+        feeToken: await this.feeTokenFor(mint),
+        slotHashes: proof.slotHashes,
+        gasTank: await this.gasTankFor(userAccountPDA),
+        instructionsSysvar: null,
         tokenProgram: new PublicKey(
           "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
         ),
@@ -101,6 +204,50 @@ export class SessionKeySDK {
     return sig;
   }
 
+  /**
+   * Build a transfer to `recipient`'s associated token account that creates
+   * the account first when it doesn't exist; the session key pays its rent
+   */
+  async buildSplDelegatedTransferWithAtaIx(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    fromToken: PublicKey,
+    recipient: PublicKey,
+    mint: PublicKey,
+    amount: BN,
+    category: number | null = null,
+    scope: PublicKey | null = null
+  ): Promise<TransactionInstruction> {
+    const toToken = getAssociatedTokenAddressSync(mint, recipient, true);
+    // The plain transfer's accounts ride along as remaining accounts
+    const inner = await this.buildSplDelegatedTransferIx(
+      authority,
+      sessionKeyPubkey,
+      fromToken,
+      toToken,
+      mint,
+      amount,
+      category,
+      scope
+    );
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const proof = await this.slotProofFor(userAccountPDA);
+    return this.program.methods
+      .splDelegatedTransferWithAta(amount, proof.slotRef, category, scope)
+      .accountsStrict({
+        sessionSigner: sessionKeyPubkey,
+        recipient,
+        toToken,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        timeProgram: this.program.programId,
+      })
+      .remainingAccounts(inner.keys)
+      .instruction();
+  }
+
   // Build-only variant to avoid implicit provider signing; use with sendAndConfirmTransaction
   async buildSplDelegatedTransferIx(
     authority: PublicKey,
@@ -108,22 +255,49 @@ export class SessionKeySDK {
     fromToken: PublicKey,
     toToken: PublicKey,
     mint: PublicKey,
-    amount: BN
+    amount: BN,
+    category: number | null = null,
+    scope: PublicKey | null = null,
+    recipient: RecipientCheck | null = null
   ): Promise<TransactionInstruction> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
     const [delegateAuthority] = PublicKey.findProgramAddressSync(
       [Buffer.from("delegate"), userAccountPDA.toBuffer(), mint.toBuffer()],
       this.program.programId
     );
+    const proof = await this.slotProofFor(userAccountPDA);
     return this.program.methods
-      .splDelegatedTransfer(amount)
+      .splDelegatedTransfer(amount, proof.slotRef, category, scope, recipient)
       .accountsStrict({
         sessionSigner: sessionKeyPubkey,
         userAccount: userAccountPDA,
+        programConfig: this.getProgramConfigPDA()[0],
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+        policy: await this.findPolicy(userAccountPDA),
         fromToken,
         toToken,
         mint,
         delegateAuthority,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: await this.tokenRecipientConsent(
+          userAccountPDA,
+          toToken
+        ),
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: await this.spendBudgetFor(userAccountPDA, mint),
+        activityDigest: await this.activityDigestFor(userAccountPDA),
+        accountStats: await this.accountStatsFor(userAccountPDA),
+        savingsVault: await this.savingsVaultFor(userAccountPDA, mint),
+        feeToken: await this.feeTokenFor(mint),
+        slotHashes: proof.slotHashes,
+        gasTank: await this.gasTankFor(userAccountPDA),
+        instructionsSysvar: null,
         tokenProgram: new PublicKey(
           "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
         ),
@@ -162,26 +336,2645 @@ export class SessionKeySDK {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
 
     return this.program.methods
-      .splRevokeDelegate()
+      .splRevokeDelegate()
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+        tokenAccount,
+        tokenProgram: new PublicKey(
+          "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        ),
+      })
+      .rpc();
+  }
+
+  async updateAllowedMints(
+    authority: PublicKey,
+    mints: PublicKey[]
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .updateAllowedMints(mints)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      // Lets the program refuse permanent-delegate mints now rather than at approval
+      .remainingAccounts(
+        mints.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }))
+      )
+      .rpc();
+  }
+
+  /**
+   * Risk flags (`MINT_RISK` bits) of each mint, by simulating an allowlist update;
+   * nothing is written. Fails with PermanentDelegateNotAllowed for mints the account
+   * hasn't opted in to.
+   */
+  async previewMintRisks(
+    authority: PublicKey,
+    mints: PublicKey[]
+  ): Promise<{ mint: PublicKey; flags: number }[]> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const { events } = await this.program.methods
+      .updateAllowedMints(mints)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .remainingAccounts(
+        mints.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }))
+      )
+      .simulate();
+    return events
+      .filter((e) => e.name === "mintRiskAssessed")
+      .map((e) => ({ mint: e.data.mint, flags: e.data.flags }));
+  }
+
+  // ===== POLICY =====
+
+  /**
+   * Attach a declarative policy; every session spend is then checked against
+   * its rules on top of the key's own permissions
+   */
+  async initializePolicy(
+    authority: PublicKey,
+    rules: PolicyRule[]
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .initializePolicy({ version: POLICY_VERSION, rules })
+      .accountsStrict({
+        policy: this.getPolicyPDA(userAccountPDA)[0],
+        userAccount: userAccountPDA,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Replace the account's policy rules
+   */
+  async updatePolicy(
+    authority: PublicKey,
+    rules: PolicyRule[]
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .updatePolicy({ version: POLICY_VERSION, rules })
+      .accountsStrict({
+        policy: this.getPolicyPDA(userAccountPDA)[0],
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Remove the account's policy, returning its rent
+   */
+  async closePolicy(authority: PublicKey): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .closePolicy()
+      .accountsStrict({
+        policy: this.getPolicyPDA(userAccountPDA)[0],
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  // ===== SPEND BUDGETS =====
+
+  getSpendBudgetPDA(userAccount: PublicKey, mint: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("spend_budget"), userAccount.toBuffer(), mint.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Cap how much of `mint` all session keys together can spend per window.
+   * A `windowSeconds` of 0 makes `limit` a lifetime cap; windows are at most
+   * a year.
+   */
+  async initializeSpendBudget(
+    authority: PublicKey,
+    mint: PublicKey,
+    limit: BN,
+    windowSeconds: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [spendBudget] = this.getSpendBudgetPDA(userAccountPDA, mint);
+    return this.program.methods
+      .initializeSpendBudget(limit, new BN(windowSeconds))
+      .accountsStrict({
+        spendBudget,
+        userAccount: userAccountPDA,
+        mint,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  async updateSpendBudget(
+    authority: PublicKey,
+    mint: PublicKey,
+    limit: BN,
+    windowSeconds: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [spendBudget] = this.getSpendBudgetPDA(userAccountPDA, mint);
+    return this.program.methods
+      .updateSpendBudget(limit, new BN(windowSeconds))
+      .accountsStrict({ spendBudget, userAccount: userAccountPDA, authority })
+      .rpc();
+  }
+
+  /**
+   * Cap how much of the budget's `mint` custom actions may route into
+   * `programId` per budget window; `null` removes the cap. A program can be
+   * capped under one mint only.
+   */
+  async setProgramBudget(
+    authority: PublicKey,
+    mint: PublicKey,
+    programId: PublicKey,
+    limit: BN | null
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [spendBudget] = this.getSpendBudgetPDA(userAccountPDA, mint);
+    return this.program.methods
+      .setProgramBudget(programId, limit)
+      .accountsStrict({ spendBudget, userAccount: userAccountPDA, authority })
+      .rpc();
+  }
+
+  async closeSpendBudget(authority: PublicKey, mint: PublicKey): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [spendBudget] = this.getSpendBudgetPDA(userAccountPDA, mint);
+    return this.program.methods
+      .closeSpendBudget()
+      .accountsStrict({ spendBudget, userAccount: userAccountPDA, authority })
+      .rpc();
+  }
+
+  /**
+   * Spend budget PDA to pass on transfers of `mint`, or null if the account has none
+   */
+  private async spendBudgetFor(
+    userAccount: PublicKey,
+    mint: PublicKey
+  ): Promise<PublicKey | null> {
+    const { budgetedMints } = await this.program.account.userAccount.fetch(
+      userAccount
+    );
+    return budgetedMints.some((m) => m.equals(mint))
+      ? this.getSpendBudgetPDA(userAccount, mint)[0]
+      : null;
+  }
+
+  /**
+   * Spend budget PDA capping custom actions into `programId`, or null if the
+   * account doesn't cap it
+   */
+  private async programBudgetFor(
+    userAccount: PublicKey,
+    programId: PublicKey
+  ): Promise<PublicKey | null> {
+    const { budgetedMints, budgetedPrograms } =
+      await this.program.account.userAccount.fetch(userAccount);
+    if (!budgetedPrograms.some((p) => p.equals(programId))) return null;
+    for (const mint of budgetedMints) {
+      const [budget] = this.getSpendBudgetPDA(userAccount, mint);
+      const { programBudgets } = await this.program.account.spendBudget.fetch(
+        budget
+      );
+      if (programBudgets.some((b) => b.program.equals(programId))) {
+        return budget;
+      }
+    }
+    return null;
+  }
+
+  // ===== ACTIVITY DIGESTS =====
+
+  getActivityDigestPDA(userAccount: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("activity_digest"), userAccount.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Replace per-transfer `TransferExecuted` events with one `ActivityDigestEmitted`
+   * event every `interval` executions
+   */
+  async enableActivityDigest(
+    authority: PublicKey,
+    interval: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [activityDigest] = this.getActivityDigestPDA(userAccountPDA);
+    return this.program.methods
+      .enableActivityDigest(interval)
+      .accountsStrict({
+        activityDigest,
+        userAccount: userAccountPDA,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Change the digest interval (0 = per-transfer events). Any partial batch is emitted first.
+   */
+  async setDigestInterval(
+    authority: PublicKey,
+    interval: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [activityDigest] = this.getActivityDigestPDA(userAccountPDA);
+    return this.program.methods
+      .setDigestInterval(interval)
+      .accountsStrict({ activityDigest, userAccount: userAccountPDA, authority })
+      .rpc();
+  }
+
+  /**
+   * Activity digest PDA to pass on transfers, or null if digests are off
+   */
+  private async activityDigestFor(
+    userAccount: PublicKey
+  ): Promise<PublicKey | null> {
+    const { digestInterval } = await this.program.account.userAccount.fetch(
+      userAccount
+    );
+    return digestInterval > 0 ? this.getActivityDigestPDA(userAccount)[0] : null;
+  }
+
+  // ===== ACCOUNT STATS =====
+
+  getAccountStatsPDA(userAccount: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), userAccount.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Start recording every execution into the account's lifetime history tree.
+   * Use `app/receipts.ts` to build inclusion proofs against `historyRoot`.
+   */
+  async initializeAccountStats(authority: PublicKey): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [accountStats] = this.getAccountStatsPDA(userAccountPDA);
+    return this.program.methods
+      .initializeAccountStats()
+      .accountsStrict({
+        accountStats,
+        userAccount: userAccountPDA,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Account stats PDA to pass on transfers, or null if stats are off
+   */
+  private async accountStatsFor(
+    userAccount: PublicKey
+  ): Promise<PublicKey | null> {
+    const { statsEnabled } = await this.program.account.userAccount.fetch(
+      userAccount
+    );
+    return statsEnabled ? this.getAccountStatsPDA(userAccount)[0] : null;
+  }
+
+  // ===== SAVINGS RULES =====
+
+  /**
+   * Save `basisPoints` of every outgoing transfer of `mint` and/or round each
+   * transfer up to a multiple of `roundUpUnit` base units, moving the savings
+   * into `vault` (an authority-owned token account). Pass null to turn off.
+   */
+  async setSavingsRule(
+    authority: PublicKey,
+    rule: {
+      mint: PublicKey;
+      vault: PublicKey;
+      basisPoints: number;
+      roundUpUnit: BN;
+    } | null
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setSavingsRule(rule)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  private async savingsVaultFor(
+    userAccount: PublicKey,
+    mint: PublicKey
+  ): Promise<PublicKey | null> {
+    const { savingsRule } = await this.program.account.userAccount.fetch(
+      userAccount
+    );
+    return savingsRule?.mint.equals(mint) ? savingsRule.vault : null;
+  }
+
+  /**
+   * Commit to an emergency kill phrase; only its hash goes on-chain. Anyone
+   * who later presents the phrase with `triggerKillPhrase` revokes every
+   * session key and pauses the account. `null` clears it.
+   */
+  async setKillPhrase(
+    authority: PublicKey,
+    phrase: string | null
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setKillPhrase(
+        phrase === null
+          ? new Array(32).fill(0)
+          : killPhraseCommitment(userAccountPDA, phrase)
+      )
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Revoke every session key of `authority`'s account and pause it, signed by
+   * whoever holds the phrase (the provider wallet by default)
+   */
+  async triggerKillPhrase(
+    authority: PublicKey,
+    phrase: string,
+    caller: PublicKey = this.provider.wallet.publicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .triggerKillPhrase(Buffer.from(phrase, "utf8"))
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        caller,
+      })
+      .rpc();
+  }
+  /**
+   * Designate a guardian key that can revoke every session key if the
+   * authority is unavailable; `null` removes it
+   */
+  async setGuardian(
+    authority: PublicKey,
+    guardian: PublicKey | null
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setGuardian(guardian)
+      .accountsStrict({ userAccount: userAccountPDA, authority })
+      .rpc();
+  }
+
+  /**
+   * Revoke every session key of `authority`'s account, signed by its guardian
+   * (the provider wallet by default)
+   */
+  async guardianRevokeAll(
+    authority: PublicKey,
+    guardian: PublicKey = this.provider.wallet.publicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .guardianRevokeAll()
+      .accountsStrict({ userAccount: userAccountPDA, guardian })
+      .rpc();
+  }
+
+
+  /**
+   * Pause or unpause every session execution, e.g. to resume after a kill
+   * phrase was used
+   */
+  async setAccountPaused(
+    authority: PublicKey,
+    paused: boolean
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setAccountPaused(paused)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Lock a session key for `lockSeconds` once `maxFailures` of its spends are
+   * refused within `windowSeconds`. Refused spends still fail, so report them
+   * with `recordRefusedSpend`. Pass null to turn off.
+   */
+  async setLockoutPolicy(
+    authority: PublicKey,
+    policy: {
+      maxFailures: number;
+      windowSeconds: number;
+      lockSeconds: number;
+    } | null
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setLockoutPolicy(policy)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Count a spend of `amount` the session key refused toward the account's
+   * lockout policy, signed by the authority or its guardian (the provider
+   * wallet by default). Fails unless the key still refuses that spend.
+   */
+  async recordRefusedSpend(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    amount: BN,
+    reporter: PublicKey = this.provider.wallet.publicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .recordRefusedSpend(sessionKeyPubkey, amount)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+        reporter,
+      })
+      .rpc();
+  }
+
+  /**
+   * Only pay recipients that accepted payouts from this account with
+   * `acceptPayments`, so funds can't land somewhere unexpected
+   */
+  async setRecipientConsentRequired(
+    authority: PublicKey,
+    required: boolean
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setRecipientConsentRequired(required)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Signed by a recipient: accept session payouts from `authority`'s account.
+   * For token payouts, the recipient is the token account's owner.
+   */
+  async acceptPayments(
+    authority: PublicKey,
+    recipient: Keypair
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [recipientConsent] = this.getRecipientConsentPDA(
+      userAccountPDA,
+      recipient.publicKey
+    );
+    return this.program.methods
+      .acceptPayments()
+      .accountsStrict({
+        recipientConsent,
+        userAccount: userAccountPDA,
+        recipient: recipient.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+  }
+
+  /**
+   * Signed by a recipient: stop accepting payouts from `authority`'s account
+   * and reclaim the consent's rent
+   */
+  async withdrawPaymentConsent(
+    authority: PublicKey,
+    recipient: Keypair
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [recipientConsent] = this.getRecipientConsentPDA(
+      userAccountPDA,
+      recipient.publicKey
+    );
+    return this.program.methods
+      .withdrawPaymentConsent()
+      .accountsStrict({ recipientConsent, recipient: recipient.publicKey })
+      .signers([recipient])
+      .rpc();
+  }
+
+  /**
+   * Make widened session permissions wait `seconds` after each update before
+   * taking effect (0 turns it off, max 7 days); the previous permissions keep
+   * applying meanwhile
+   */
+  async setWideningDelay(
+    authority: PublicKey,
+    seconds: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setWideningDelay(seconds)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Allow or refuse Token-2022 mints with a permanent delegate, whose issuer can
+   * move tokens out of any holder's account. Refused by default.
+   */
+  async setPermanentDelegateOptIn(
+    authority: PublicKey,
+    allowed: boolean
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setPermanentDelegateOptIn(allowed)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  // ===== SOL VAULT =====
+
+  /**
+   * Fund the user account PDA's SOL vault; any wallet can deposit
+   */
+  async depositSol(
+    authority: PublicKey,
+    depositor: PublicKey,
+    lamports: BN
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .depositSol(lamports)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        depositor,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Withdraw lamports above the rent reserve back to the authority
+   */
+  async withdrawSol(authority: PublicKey, lamports: BN): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .withdrawSol(lamports)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Build an instruction paying lamports from the vault to the session key,
+   * charged against its limits; the session key signs and receives
+   */
+  async buildSessionWithdrawSolIx(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    lamports: BN,
+    scope: PublicKey | null = null
+  ): Promise<TransactionInstruction> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const proof = await this.slotProofFor(userAccountPDA);
+    return this.program.methods
+      .sessionWithdrawSol(lamports, proof.slotRef, scope)
+      .accountsStrict({
+        sessionSigner: sessionKeyPubkey,
+        userAccount: userAccountPDA,
+        programConfig: this.getProgramConfigPDA()[0],
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+        policy: await this.findPolicy(userAccountPDA),
+        slotHashes: proof.slotHashes,
+        treasury: await this.treasuryForFees(),
+      })
+      .remainingAccounts(await this.guardAccountsFor(userAccountPDA))
+      .instruction();
+  }
+
+  /**
+   * Build an instruction paying lamports from the vault to `destination`,
+   * charged against the session key's limits and recipient allowlist
+   */
+  async buildSessionTransferSolIx(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    destination: PublicKey,
+    lamports: BN,
+    scope: PublicKey | null = null
+  ): Promise<TransactionInstruction> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const proof = await this.slotProofFor(userAccountPDA);
+    return this.program.methods
+      .sessionTransferSol(lamports, proof.slotRef, scope)
+      .accountsStrict({
+        sessionSigner: sessionKeyPubkey,
+        userAccount: userAccountPDA,
+        programConfig: this.getProgramConfigPDA()[0],
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+        policy: await this.findPolicy(userAccountPDA),
+        destination,
+        recipientConsent: await this.findRecipientConsent(
+          userAccountPDA,
+          destination
+        ),
+        slotHashes: proof.slotHashes,
+        treasury: await this.treasuryForFees(),
+      })
+      .remainingAccounts(await this.guardAccountsFor(userAccountPDA))
+      .instruction();
+  }
+
+  // ===== GAS TANK =====
+
+  getGasTankPDA(userAccount: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("gas_tank"), userAccount.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Create the account's gas tank; each delegated transfer then reimburses
+   * the session signer `feePerExecution` lamports (max 100_000) from it
+   */
+  async initializeGasTank(
+    authority: PublicKey,
+    feePerExecution: BN
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .initializeGasTank(feePerExecution)
+      .accountsStrict({
+        gasTank: this.getGasTankPDA(userAccountPDA)[0],
+        userAccount: userAccountPDA,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  async setGasTankFee(
+    authority: PublicKey,
+    feePerExecution: BN
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setGasTankFee(feePerExecution)
+      .accountsStrict({
+        gasTank: this.getGasTankPDA(userAccountPDA)[0],
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Add lamports to the gas tank; they can only leave as fee reimbursements
+   */
+  async topUpGasTank(
+    authority: PublicKey,
+    payer: PublicKey,
+    lamports: BN
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .topUpGasTank(lamports)
+      .accountsStrict({
+        gasTank: this.getGasTankPDA(userAccountPDA)[0],
+        userAccount: userAccountPDA,
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Gas tank PDA to pass on transfers, or null if the account has none
+   */
+  private async gasTankFor(userAccount: PublicKey): Promise<PublicKey | null> {
+    const [gasTank] = this.getGasTankPDA(userAccount);
+    const info = await this.provider.connection.getAccountInfo(gasTank);
+    return info ? gasTank : null;
+  }
+
+  // ===== RECENT SLOT PROOFS =====
+
+  /**
+   * Require session executions to reference a slot hash at most
+   * `maxSlotAge` slots old (0 turns the check off, max 512)
+   */
+  async setMaxSlotAge(
+    authority: PublicKey,
+    maxSlotAge: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setMaxSlotAge(maxSlotAge)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Newest entry of the SlotHashes sysvar, for the `slotRef` argument of
+   * delegated transfers
+   */
+  async getRecentSlotReference(): Promise<{ slot: BN; hash: number[] }> {
+    const info = await this.provider.connection.getAccountInfo(
+      SYSVAR_SLOT_HASHES_PUBKEY
+    );
+    if (!info || info.data.readBigUInt64LE(0) === BigInt(0)) {
+      throw new Error("SlotHashes sysvar is empty");
+    }
+    return {
+      slot: new BN(info.data.subarray(8, 16), "le"),
+      hash: Array.from(info.data.subarray(16, 48)),
+    };
+  }
+
+  private async slotProofFor(userAccount: PublicKey): Promise<{
+    slotRef: { slot: BN; hash: number[] } | null;
+    slotHashes: PublicKey | null;
+  }> {
+    const { maxSlotAge } = await this.program.account.userAccount.fetch(
+      userAccount
+    );
+    if (maxSlotAge === 0) {
+      return { slotRef: null, slotHashes: null };
+    }
+    return {
+      slotRef: await this.getRecentSlotReference(),
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+    };
+  }
+
+  // ===== SCHEDULED GRANTS =====
+
+  getScheduledSessionPDA(
+    userAccount: PublicKey,
+    sessionKey: PublicKey
+  ): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("scheduled_session"),
+        userAccount.toBuffer(),
+        sessionKey.toBuffer(),
+      ],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Grant a time-based session key that only gets added at `activateAt`
+   * (unix seconds), e.g. for a planned maintenance window
+   */
+  async scheduleSessionKey(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    activateAt: number,
+    durationSeconds: number,
+    permissions: SessionPermissions,
+    label?: string
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [scheduledSession] = this.getScheduledSessionPDA(
+      userAccountPDA,
+      sessionKeyPubkey
+    );
+    return this.program.methods
+      .scheduleSessionKey(
+        {
+          sessionPubkey: sessionKeyPubkey,
+          expiresAtTime: new BN(activateAt + durationSeconds),
+          expiresAtSlot: null,
+          permissions,
+          termsHash: null,
+          label: encodeLabel(label),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        },
+        new BN(activateAt)
+      )
+      .accountsStrict({
+        scheduledSession,
+        userAccount: userAccountPDA,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Cancel a scheduled grant before it activates
+   */
+  async cancelScheduledSession(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [scheduledSession] = this.getScheduledSessionPDA(
+      userAccountPDA,
+      sessionKeyPubkey
+    );
+    return this.program.methods
+      .cancelScheduledSession()
+      .accountsStrict({
+        scheduledSession,
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Activate a due grant; any wallet can send this, rent goes back to the
+   * authority
+   */
+  async activateScheduledSession(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [scheduledSession] = this.getScheduledSessionPDA(
+      userAccountPDA,
+      sessionKeyPubkey
+    );
+    return this.program.methods
+      .activateScheduledSession()
+      .accountsStrict({
+        scheduledSession,
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  // ===== PAYOUT PIPELINES =====
+
+  getPipelinePDA(
+    userAccount: PublicKey,
+    pipelineId: number
+  ): [PublicKey, number] {
+    const id = Buffer.alloc(2);
+    id.writeUInt16LE(pipelineId);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("pipeline"), userAccount.toBuffer(), id],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Define a payout pipeline (up to 4 steps) session keys can run without
+   * the custom-CPI permission
+   */
+  async createPipeline(
+    authority: PublicKey,
+    pipelineId: number,
+    steps: PipelineStep[]
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [pipeline] = this.getPipelinePDA(userAccountPDA, pipelineId);
+    return this.program.methods
+      .createPipeline(pipelineId, steps)
+      .accountsStrict({
+        pipeline,
+        userAccount: userAccountPDA,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  async closePipeline(
+    authority: PublicKey,
+    pipelineId: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [pipeline] = this.getPipelinePDA(userAccountPDA, pipelineId);
+    return this.program.methods
+      .closePipeline()
+      .accountsStrict({
+        pipeline,
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Build the instruction running a pipeline with a session key; `inputs`
+   * has one entry per template step, in order
+   */
+  async buildExecutePipelineIx(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    pipelineId: number,
+    inputs: PipelineStepInput[],
+    scope: PublicKey | null = null
+  ): Promise<TransactionInstruction> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [pipeline] = this.getPipelinePDA(userAccountPDA, pipelineId);
+    const { steps } = await this.program.account.payoutPipeline.fetch(
+      pipeline
+    );
+    const delegateFor = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("delegate"), userAccountPDA.toBuffer(), mint.toBuffer()],
+        this.program.programId
+      )[0];
+
+    const stepArgs = [];
+    const remaining: AccountMeta[] = [];
+    let spendBudget: PublicKey | null = null;
+    for (const step of steps) {
+      const mint = step.transfer?.mint ?? step.swap.mintIn;
+      spendBudget ??= await this.spendBudgetFor(userAccountPDA, mint);
+    }
+    steps.forEach((step, i) => {
+      const input = inputs[i];
+      if (step.transfer) {
+        const { mint, destination } = step.transfer;
+        stepArgs.push({
+          amount: input.amount ?? new BN(0),
+          data: Buffer.alloc(0),
+          accountCount: 0,
+        });
+        remaining.push(
+          { pubkey: input.sourceToken, isSigner: false, isWritable: true },
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: destination, isSigner: false, isWritable: true },
+          { pubkey: delegateFor(mint), isSigner: false, isWritable: false }
+        );
+      } else {
+        const { program, mintIn, destination } = step.swap;
+        const forwarded = input.accounts ?? [];
+        stepArgs.push({
+          amount: new BN(0),
+          data: input.data ?? Buffer.alloc(0),
+          accountCount: forwarded.length,
+        });
+        remaining.push(
+          { pubkey: program, isSigner: false, isWritable: false },
+          { pubkey: input.sourceToken, isSigner: false, isWritable: false },
+          { pubkey: destination, isSigner: false, isWritable: false },
+          { pubkey: delegateFor(mintIn), isSigner: false, isWritable: false },
+          ...forwarded
+        );
+      }
+    });
+
+    const proof = await this.slotProofFor(userAccountPDA);
+    return this.program.methods
+      .executePipeline(stepArgs, proof.slotRef, scope)
+      .accountsStrict({
+        sessionSigner: sessionKeyPubkey,
+        userAccount: userAccountPDA,
+        programConfig: this.getProgramConfigPDA()[0],
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+        policy: await this.findPolicy(userAccountPDA),
+        pipeline,
+        spendBudget,
+        slotHashes: proof.slotHashes,
+        tokenProgram: new PublicKey(
+          "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        ),
+      })
+      .remainingAccounts([
+        ...(await this.guardAccountsFor(userAccountPDA)),
+        ...remaining,
+      ])
+      .instruction();
+  }
+
+  // ===== CUSTOM ACTIONS =====
+
+  /**
+   * Executor PDA that signs custom actions; fund it (or its token accounts)
+   * with whatever the allowed programs need to act on
+   */
+  getSessionKeyAccountPDA(
+    userAccount: PublicKey,
+    sessionKey: PublicKey
+  ): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("session"), userAccount.toBuffer(), sessionKey.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * The session key's PDA if it was created with `createSessionKeyAccount`, or
+   * null for a key stored inline in the user account
+   */
+  async findSessionKeyAccount(
+    userAccount: PublicKey,
+    sessionKey: PublicKey
+  ): Promise<PublicKey | null> {
+    const [pda] = this.getSessionKeyAccountPDA(userAccount, sessionKey);
+    const info = await this.provider.connection.getAccountInfo(pda);
+    return info ? pda : null;
+  }
+
+  getPolicyPDA(userAccount: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("policy"), userAccount.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * The account's policy PDA if it has one, or null; session spends must pass
+   * it whenever it exists
+   */
+  async findPolicy(userAccount: PublicKey): Promise<PublicKey | null> {
+    const [pda] = this.getPolicyPDA(userAccount);
+    const info = await this.provider.connection.getAccountInfo(pda);
+    return info ? pda : null;
+  }
+
+  /**
+   * The account's guard program as a leading remaining account, or nothing
+   * when it has none
+   */
+  private async guardAccountsFor(
+    userAccount: PublicKey
+  ): Promise<AccountMeta[]> {
+    const { guardProgram } = await this.program.account.userAccount.fetch(
+      userAccount
+    );
+    return guardProgram
+      ? [{ pubkey: guardProgram, isSigner: false, isWritable: false }]
+      : [];
+  }
+
+  getRecipientConsentPDA(
+    userAccount: PublicKey,
+    recipient: PublicKey
+  ): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("consent"), userAccount.toBuffer(), recipient.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * `recipient`'s consent to payouts from the account, or null if it hasn't
+   * accepted them; required once the account requires recipient consent
+   */
+  async findRecipientConsent(
+    userAccount: PublicKey,
+    recipient: PublicKey
+  ): Promise<PublicKey | null> {
+    const [pda] = this.getRecipientConsentPDA(userAccount, recipient);
+    const info = await this.provider.connection.getAccountInfo(pda);
+    return info ? pda : null;
+  }
+
+  /**
+   * Consent of the owner of `tokenAccount`, for delegated token transfers
+   */
+  private async tokenRecipientConsent(
+    userAccount: PublicKey,
+    tokenAccount: PublicKey
+  ): Promise<PublicKey | null> {
+    const info = await this.provider.connection.getAccountInfo(tokenAccount);
+    if (!info) return null;
+    // Token accounts store the owner after the 32-byte mint
+    const owner = new PublicKey(info.data.subarray(32, 64));
+    return this.findRecipientConsent(userAccount, owner);
+  }
+
+  /**
+   * Every PDA-stored session key of a user account, via getProgramAccounts
+   */
+  async listSessionKeyAccounts(authority: PublicKey) {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.account.sessionKeyAccount.all([
+      { memcmp: { offset: 8, bytes: userAccountPDA.toBase58() } },
+    ]);
+  }
+
+  getExecutorPDA(userAccount: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("executor"), userAccount.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Build an instruction that has the session key call `programId` with
+   * `data`, signed by the account's executor PDA. The program must be on the
+   * key's `allowedPrograms` and `data` must match one of its
+   * `allowedDiscriminators` for that program, if any. Include the executor in
+   * `accounts` wherever the target expects its signer.
+   */
+  async buildExecuteCustomIx(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    programId: PublicKey,
+    data: Buffer,
+    accounts: AccountMeta[],
+    scope: PublicKey | null = null
+  ): Promise<TransactionInstruction> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const proof = await this.slotProofFor(userAccountPDA);
+    return this.program.methods
+      .executeCustom(data, proof.slotRef, scope)
+      .accountsStrict({
+        sessionSigner: sessionKeyPubkey,
+        userAccount: userAccountPDA,
+        programConfig: this.getProgramConfigPDA()[0],
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+        policy: await this.findPolicy(userAccountPDA),
+        spendBudget: await this.programBudgetFor(userAccountPDA, programId),
+        executor: this.getExecutorPDA(userAccountPDA)[0],
+        slotHashes: proof.slotHashes,
+      })
+      .remainingAccounts([
+        ...(await this.guardAccountsFor(userAccountPDA)),
+        { pubkey: programId, isSigner: false, isWritable: false },
+        ...accounts,
+      ])
+      .instruction();
+  }
+
+  // ===== OPERATOR BONDS =====
+
+  getBondPDA(userAccount: PublicKey, sessionKey: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("bond"), userAccount.toBuffer(), sessionKey.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Require session operators to lock a bond (mint null = SOL) before executing
+   */
+  async setBondRequirement(
+    authority: PublicKey,
+    requirement: {
+      mint: PublicKey | null;
+      minAmount: BN;
+      disputeWindowSeconds: BN;
+    } | null
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setBondRequirement(requirement)
+      .accountsStrict({ userAccount: userAccountPDA, authority })
+      .rpc();
+  }
+
+  /**
+   * Require bonded operators to countersign each delegated transfer with an
+   * Ed25519 instruction over `operatorPayload` in the same transaction
+   */
+  async setOperatorSignatureRequired(
+    authority: PublicKey,
+    required: boolean
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setOperatorSignatureRequired(required)
+      .accountsStrict({ userAccount: userAccountPDA, authority })
+      .rpc();
+  }
+
+  /**
+   * Operator locks SOL as the bond backing a session key
+   */
+  async postBond(
+    authority: PublicKey,
+    operator: PublicKey,
+    sessionKey: PublicKey,
+    lamports: BN
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [bond] = this.getBondPDA(userAccountPDA, sessionKey);
+    return this.program.methods
+      .postBond(sessionKey, lamports)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        bond,
+        operator,
+        reputation: this.getReputationPDA(operator)[0],
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Authority slashes a SOL bond, backed by an upheld violation report
+   */
+  async slashSolBond(
+    authority: PublicKey,
+    sessionKey: PublicKey,
+    reportIndex: number,
+    amount: BN
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [bond] = this.getBondPDA(userAccountPDA, sessionKey);
+    const [report] = this.getViolationReportPDA(userAccountPDA, reportIndex);
+    const { operator } = await this.program.account.operatorBond.fetch(bond);
+    return this.program.methods
+      .slashBond(amount)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+        bond,
+        report,
+        reputation: this.getReputationPDA(operator)[0],
+        bondVault: null,
+        destinationToken: null,
+        mint: null,
+        tokenProgram: null,
+      })
+      .rpc();
+  }
+
+  // ===== OPERATOR REPUTATION =====
+
+  getReputationPDA(operator: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("reputation"), operator.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  private async bondReputationPDA(
+    bond: PublicKey | null
+  ): Promise<PublicKey | null> {
+    if (!bond) return null;
+    const { operator } = await this.program.account.operatorBond.fetch(bond);
+    return this.getReputationPDA(operator)[0];
+  }
+
+  /**
+   * Operator creates its public reputation record (required before posting bonds)
+   */
+  async initializeOperatorReputation(operator: PublicKey): Promise<string> {
+    return this.program.methods
+      .initializeOperatorReputation()
+      .accountsStrict({
+        reputation: this.getReputationPDA(operator)[0],
+        operator,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Fetch an operator's track record before granting it a session
+   */
+  async getOperatorReputation(operator: PublicKey) {
+    const rep = await this.program.account.operatorReputation.fetch(
+      this.getReputationPDA(operator)[0]
+    );
+    const completed = rep.sessionsCompleted;
+    return {
+      ...rep,
+      averageSessionSeconds:
+        completed > 0 ? rep.totalSessionSeconds.toNumber() / completed : 0,
+    };
+  }
+
+  // ===== SERVICE REGISTRY =====
+
+  getServicePDA(operator: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("service"), operator.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Operator publishes its display name, URL hash and session keys
+   */
+  async registerService(
+    operator: PublicKey,
+    name: string,
+    urlHash: Uint8Array,
+    sessionKeys: PublicKey[]
+  ): Promise<string> {
+    return this.program.methods
+      .registerService(encodeName(name), Array.from(urlHash), sessionKeys)
+      .accountsStrict({
+        service: this.getServicePDA(operator)[0],
+        operator,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Resolve which registered service (if any) advertises a session key, so
+   * wallets can show "Granting session to: <name> (verified)"
+   */
+  async findServiceForSessionKey(
+    sessionKey: PublicKey
+  ): Promise<{ name: string; operator: PublicKey; verified: boolean } | null> {
+    const entries = await this.program.account.serviceEntry.all();
+    const match = entries.find((e) =>
+      e.account.sessionKeys.some((k) => k.equals(sessionKey))
+    );
+    if (!match) return null;
+    return {
+      name: decodeName(match.account.name),
+      operator: match.account.operator,
+      verified: match.account.verified,
+    };
+  }
+
+  getPresetPDA(service: PublicKey, presetId: number): [PublicKey, number] {
+    const idBytes = Buffer.alloc(2);
+    idBytes.writeUInt16LE(presetId);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("preset"), service.toBuffer(), idBytes],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Operator publishes a recommended permission preset for its service
+   */
+  async publishPreset(
+    operator: PublicKey,
+    presetId: number,
+    permissions: SessionPermissions,
+    maxDurationSeconds: number
+  ): Promise<string> {
+    const [service] = this.getServicePDA(operator);
+    return this.program.methods
+      .publishPreset(presetId, permissions, new BN(maxDurationSeconds))
+      .accountsStrict({
+        service,
+        preset: this.getPresetPDA(service, presetId)[0],
+        operator,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Grant a session exactly what a service preset requests. The preset's current
+   * version is pinned so a last-second change by the operator is rejected.
+   */
+  async createSessionKeyFromPreset(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    operator: PublicKey,
+    presetId: number,
+    durationSeconds: number,
+    terms?: string
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [preset] = this.getPresetPDA(this.getServicePDA(operator)[0], presetId);
+    const { version } = await this.program.account.servicePreset.fetch(preset);
+    return this.program.methods
+      .createSessionKeyFromPreset(
+        sessionKeyPubkey,
+        new BN(Math.floor(Date.now() / 1000) + durationSeconds),
+        version,
+        terms === undefined ? null : hashTerms(terms)
+      )
+      .accountsStrict({ userAccount: userAccountPDA, authority, preset })
+      .rpc();
+  }
+
+  /**
+   * List sessions whose preset has changed since they were granted, with the
+   * granted and current permissions side by side
+   */
+  async getPresetDrift(authority: PublicKey) {
+    const keys = await this.getSessionKeys(authority);
+    const drift = [];
+    for (const key of keys) {
+      if (!key.preset) continue;
+      const current = await this.program.account.servicePreset.fetch(
+        key.preset
+      );
+      if (current.version !== key.presetVersion) {
+        drift.push({
+          sessionKey: key.pubkey,
+          grantedVersion: key.presetVersion,
+          currentVersion: current.version,
+          granted: key.permissions,
+          current: current.permissions,
+        });
+      }
+    }
+    return drift;
+  }
+
+  // ===== DISPUTES =====
+
+  getViolationReportPDA(
+    userAccount: PublicKey,
+    index: number
+  ): [PublicKey, number] {
+    const indexBytes = Buffer.alloc(4);
+    indexBytes.writeUInt32LE(index);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("violation"), userAccount.toBuffer(), indexBytes],
+      this.program.programId
+    );
+  }
+
+  /**
+   * File a violation report against a session key, citing the offending
+   * transaction signature. Pass `bonded` when the session has an operator bond.
+   */
+  async reportViolation(
+    authority: PublicKey,
+    sessionKey: PublicKey,
+    evidenceSignature: Uint8Array,
+    bonded = false
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const userAccount = await this.program.account.userAccount.fetch(
+      userAccountPDA
+    );
+    const [report] = this.getViolationReportPDA(
+      userAccountPDA,
+      userAccount.violationReports
+    );
+    const bond = bonded ? this.getBondPDA(userAccountPDA, sessionKey)[0] : null;
+    return this.program.methods
+      .reportViolation(sessionKey, Array.from(evidenceSignature))
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        report,
+        bond,
+        reputation: await this.bondReputationPDA(bond),
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Uphold or dismiss a violation report once its timelock has elapsed
+   */
+  async resolveDispute(
+    authority: PublicKey,
+    reportIndex: number,
+    upheld: boolean,
+    bondedSession?: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [report] = this.getViolationReportPDA(userAccountPDA, reportIndex);
+    const bond = bondedSession
+      ? this.getBondPDA(userAccountPDA, bondedSession)[0]
+      : null;
+    return this.program.methods
+      .resolveDispute(upheld)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        report,
+        bond,
+        reputation: await this.bondReputationPDA(bond),
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Operator starts the dispute window after its session is revoked or expired
+   */
+  async requestBondRelease(
+    authority: PublicKey,
+    operator: PublicKey,
+    sessionKey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [bond] = this.getBondPDA(userAccountPDA, sessionKey);
+    return this.program.methods
+      .requestBondRelease()
+      .accountsStrict({ userAccount: userAccountPDA, bond, operator })
+      .rpc();
+  }
+
+  /**
+   * Operator reclaims a SOL bond once the dispute window has elapsed
+   */
+  async withdrawSolBond(
+    authority: PublicKey,
+    operator: PublicKey,
+    sessionKey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [bond] = this.getBondPDA(userAccountPDA, sessionKey);
+    return this.program.methods
+      .withdrawBond()
+      .accountsStrict({
+        bond,
+        operator,
+        reputation: this.getReputationPDA(operator)[0],
+        bondVault: null,
+        destinationToken: null,
+        mint: null,
+        tokenProgram: null,
+      })
+      .rpc();
+  }
+
+  /**
+   * Opt in or out of screening session transfers against the program blocklist.
+   * When enabled, pass `getBlocklistPDA()` as the `blocklist` account on transfers.
+   */
+  async setBlocklistEnforcement(
+    authority: PublicKey,
+    enabled: boolean
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setBlocklistEnforcement(enabled)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Derive the program-wide blocklist registry PDA
+   */
+  getBlocklistPDA(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist")],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Add/remove addresses from the blocklist registry (admin only)
+   */
+  async updateBlocklist(
+    admin: PublicKey,
+    additions: PublicKey[],
+    removals: PublicKey[]
+  ): Promise<string> {
+    const [blocklist] = this.getBlocklistPDA();
+    return this.program.methods
+      .updateBlocklist(additions, removals)
+      .accountsStrict({ blocklist, admin })
+      .rpc();
+  }
+
+  /**
+   * Derive the program config PDA, passed to every session execution
+   */
+  getProgramConfigPDA(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("program_config")],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Derive the treasury PDA collecting protocol fees
+   */
+  getTreasuryPDA(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Treasury token account collecting fees in `mint`: its associated token
+   * account, which the admin creates before setting a fee
+   */
+  getFeeTokenAccount(mint: PublicKey): PublicKey {
+    return getAssociatedTokenAddressSync(mint, this.getTreasuryPDA()[0], true);
+  }
+
+  private async protocolFeeBps(): Promise<number> {
+    const config = await this.program.account.programConfig.fetchNullable(
+      this.getProgramConfigPDA()[0]
+    );
+    return config?.feeBps ?? 0;
+  }
+
+  private async feeTokenFor(mint: PublicKey): Promise<PublicKey | null> {
+    return (await this.protocolFeeBps()) > 0
+      ? this.getFeeTokenAccount(mint)
+      : null;
+  }
+
+  private async treasuryForFees(): Promise<PublicKey | null> {
+    return (await this.protocolFeeBps()) > 0 ? this.getTreasuryPDA()[0] : null;
+  }
+
+  /**
+   * Create the program config and fee treasury with a per-transfer cap for
+   * every account (0 = none); the program upgrade authority signs and
+   * becomes its admin
+   */
+  async initializeProgramConfig(
+    admin: PublicKey,
+    maxTransferAmount: BN
+  ): Promise<string> {
+    return this.program.methods
+      .initializeProgramConfig(maxTransferAmount)
+      .accountsStrict({
+        programConfig: this.getProgramConfigPDA()[0],
+        treasury: this.getTreasuryPDA()[0],
+        admin,
+        program: this.program.programId,
+        programData: programDataAddress(this.program.programId),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Pause or resume every session execution across the program (admin only)
+   */
+  async setPaused(admin: PublicKey, paused: boolean): Promise<string> {
+    return this.program.methods
+      .setPaused(paused)
+      .accountsStrict({ programConfig: this.getProgramConfigPDA()[0], admin })
+      .rpc();
+  }
+
+  /**
+   * Change the config's admin and per-transfer cap (admin only)
+   */
+  async updateConfig(
+    admin: PublicKey,
+    newAdmin: PublicKey,
+    maxTransferAmount: BN
+  ): Promise<string> {
+    return this.program.methods
+      .updateConfig(newAdmin, maxTransferAmount)
+      .accountsStrict({ programConfig: this.getProgramConfigPDA()[0], admin })
+      .rpc();
+  }
+
+  /** Deny mints program-wide, or lift a denial (config admin only) */
+  async updateMintDenylist(
+    admin: PublicKey,
+    additions: PublicKey[],
+    removals: PublicKey[]
+  ): Promise<string> {
+    return this.program.methods
+      .updateMintDenylist(additions, removals)
+      .accountsStrict({ programConfig: this.getProgramConfigPDA()[0], admin })
+      .rpc();
+  }
+
+  /**
+   * Set the protocol fee charged on top of session transfers, in basis
+   * points (admin only)
+   */
+  async setFee(admin: PublicKey, feeBps: number): Promise<string> {
+    return this.program.methods
+      .setFee(feeBps)
+      .accountsStrict({ programConfig: this.getProgramConfigPDA()[0], admin })
+      .rpc();
+  }
+
+  /**
+   * Withdraw collected fees to `destination` (admin only): lamports, or
+   * tokens of `mint` into the token account `destination` when one is given
+   */
+  async withdrawFees(
+    admin: PublicKey,
+    destination: PublicKey,
+    amount: BN,
+    mint: PublicKey | null = null
+  ): Promise<string> {
+    return this.program.methods
+      .withdrawFees(amount)
+      .accountsStrict({
+        programConfig: this.getProgramConfigPDA()[0],
+        admin,
+        treasury: this.getTreasuryPDA()[0],
+        destination,
+        mint,
+        feeToken: mint ? this.getFeeTokenAccount(mint) : null,
+        tokenProgram: mint
+          ? new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+          : null,
+      })
+      .rpc();
+  }
+
+  /**
+   * Set (or clear with null) the attestation token gate, e.g.
+   * `{ mint: kycMint, subject: { recipient: {} } }`
+   */
+  async setAttestationGate(
+    authority: PublicKey,
+    gate: AttestationGate | null
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setAttestationGate(gate)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Set (or clear with null) the guard program CPI'd into before every session execution.
+   * When set, pass the guard program as the first remaining account on delegated
+   * transfers; the SOL, custom action and pipeline builders add it themselves.
+   */
+  async setGuardProgram(
+    authority: PublicKey,
+    guardProgram: PublicKey | null
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setGuardProgram(guardProgram)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Replace the blackout calendar (unix second ranges, end exclusive); every
+   * session execution inside a window is refused. Pass [] to lift all.
+   */
+  async setBlackoutWindows(
+    authority: PublicKey,
+    windows: { start: number; end: number }[]
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setBlackoutWindows(
+        windows.map((w) => ({ start: new BN(w.start), end: new BN(w.end) }))
+      )
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Replace the spend taxonomy transfers can be tagged with. Labels are
+   * UTF-8, at most 16 bytes. Pass [] to clear it.
+   */
+  async setSpendCategories(
+    authority: PublicKey,
+    categories: { id: number; label: string }[]
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setSpendCategories(
+        categories.map((c) => {
+          const label = Buffer.alloc(16);
+          Buffer.from(c.label, "utf8").copy(label, 0, 0, 16);
+          return { id: c.id, label: Array.from(label) };
+        })
+      )
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Initialize the SDK with a connection and program ID
+   */
+  static async init(
+    connection: Connection,
+    programId: PublicKey,
+    wallet?: anchor.Wallet
+  ): Promise<SessionKeySDK> {
+    const provider = new AnchorProvider(
+      connection,
+      wallet || new anchor.Wallet(Keypair.generate()),
+      { commitment: "confirmed" }
+    );
+
+    const idl = await Program.fetchIdl(programId, provider);
+    if (!idl) throw new Error("IDL not found");
+
+    const program = new Program(idl as Time, provider) as Program<Time>;
+    return new SessionKeySDK(program, provider);
+  }
+
+  /**
+   * Derive the user account PDA for a given authority
+   */
+  async getUserAccountPDA(authority: PublicKey): Promise<[PublicKey, number]> {
+    return PublicKey.findProgramAddress(
+      [Buffer.from("user_account"), authority.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /**
+   * Initialize a user account for managing session keys
+   */
+  /**
+   * Create the user account with room for `maxSessionKeys` session keys (at most 7).
+   * A separate `payer` covers the rent, e.g. a dapp sponsoring its users; it
+   * must sign alongside the authority
+   */
+  async initializeUserAccount(
+    authority: PublicKey,
+    maxSessionKeys = 6,
+    payer: PublicKey = authority
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    console.log("User account PDA:", userAccountPDA.toBase58());
+    const tx = await this.program.methods
+      .initializeUserAccount(maxSessionKeys)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority: authority,
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    console.log("Initialize user account tx:", tx);
+
+    return tx;
+  }
+
+  /**
+   * Create the user account with its first session key in one transaction,
+   * so a new user can start a dapp session right away
+   */
+  async initializeWithSessionKey(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    durationSeconds: number,
+    permissions: SessionPermissions,
+    maxSessionKeys = 6,
+    payer: PublicKey = authority
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .initializeWithSessionKey(maxSessionKeys, {
+        sessionPubkey: sessionKeyPubkey,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + durationSeconds),
+        expiresAtSlot: null,
+        permissions,
+        termsHash: null,
+        label: encodeLabel(),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Create a session key, creating the user account first if the authority
+   * doesn't have one yet
+   */
+  async createSessionKeyInitIfNeeded(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    durationSeconds: number,
+    permissions: SessionPermissions,
+    payer: PublicKey = authority
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .createSessionKeyInitIfNeeded({
+        sessionPubkey: sessionKeyPubkey,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + durationSeconds),
+        expiresAtSlot: null,
+        permissions,
+        termsHash: null,
+        label: encodeLabel(),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  async initializeUserAccountWithConfig(
+    authority: PublicKey,
+    allowedMints: PublicKey[],
+    initialDepositLamports: BN,
+    payer: PublicKey = authority
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .initializeUserAccountWithConfig(allowedMints, initialDepositLamports)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Create a new session key with specified permissions
+   */
+  async createSessionKey({
+    authority,
+    sessionKeyPubkey,
+    durationSeconds,
+    permissions,
+    terms,
+    label,
+    maxUses = 0,
+    validFrom = 0,
+    maxSlots,
+    allowedRecipients = [],
+    allowedMints = [],
+    allowedPrograms = [],
+    allowedDiscriminators = [],
+    idleTimeout = 0,
+    scope = PublicKey.default,
+    domain,
+  }: {
+    authority: Keypair;
+    sessionKeyPubkey: PublicKey;
+    /** Lifetime of the key; with `idleTimeout`, the most it can slide up to */
+    durationSeconds: number;
+    permissions: SessionPermissions;
+    /** Grant description shown to the user; its hash is stored as consent */
+    terms?: string;
+    /** Display label, at most 32 UTF-8 bytes */
+    label?: string;
+    /** Total executions allowed; 1 for single-use links, 0 = unlimited */
+    maxUses?: number;
+    /** Unix timestamp the key becomes usable at; 0 = immediately */
+    validFrom?: number;
+    /** Also expire after this many slots, whichever bound hits first */
+    maxSlots?: number;
+    /** Wallets or token accounts the key may pay (at most 4); empty = any */
+    allowedRecipients?: PublicKey[];
+    /** Mints the key may move (at most 4), within the account's allowlist */
+    allowedMints?: PublicKey[];
+    /** Programs the key may call with `executeCustom` (at most 4) */
+    allowedPrograms?: PublicKey[];
+    /**
+     * Per-program instruction filters for `executeCustom` (at most 8); a
+     * program without filters accepts any instruction
+     */
+    allowedDiscriminators?: DiscriminatorFilter[];
+    /**
+     * Expire the key after this many seconds without use; each use extends
+     * it, up to `durationSeconds` from now. 0 = fixed expiry
+     */
+    idleTimeout?: number;
+    /**
+     * Dapp or program the key only works with; executing instructions must
+     * pass the same scope. Default = usable anywhere
+     */
+    scope?: PublicKey;
+    /** Origin of the dapp requesting the grant, e.g. `https://app.example` */
+    domain?: string;
+  }): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority.publicKey);
+    const expiresAtTime = new BN(
+      Math.floor(Date.now() / 1000) + durationSeconds
+    );
+    const expiresAtSlot =
+      maxSlots === undefined
+        ? null
+        : new BN((await this.provider.connection.getSlot()) + maxSlots);
+
+    const ix = await this.program.methods
+      .createSessionKeyV2({
+        sessionPubkey: sessionKeyPubkey,
+        expiresAtTime,
+        expiresAtSlot,
+        permissions,
+        termsHash: terms === undefined ? null : hashTerms(terms),
+        label: encodeLabel(label),
+        maxUses,
+        validFrom: new BN(validFrom),
+        allowedRecipients,
+        allowedMints,
+        allowedPrograms,
+        allowedDiscriminators,
+        idleTimeout,
+        scope,
+        domainHash: domain === undefined ? null : hashDomain(domain),
+      })
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+
+    const connection = new Connection("http://localhost:8899", {
+      commitment: "confirmed",
+    });
+    const tx = new Transaction().add(ix);
+    const sig = await connection.sendTransaction(tx, [authority]);
+    console.log("sig", sig);
+    await connection.confirmTransaction(sig, "confirmed");
+    return sig;
+  }
+
+  /**
+   * Create a new session key with block height expiration
+   */
+  async createSessionKeyWithBlockHeight(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    blocksFromNow: number,
+    permissions: SessionPermissions,
+    label?: string
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+
+    // Get current slot (block height)
+    const currentSlot = await this.provider.connection.getSlot();
+    const expiresAtSlot = new BN(currentSlot + blocksFromNow);
+
+    const tx = await this.program.methods
+      .createSessionKeyV2({
+        sessionPubkey: sessionKeyPubkey,
+        expiresAtTime: null,
+        expiresAtSlot,
+        permissions,
+        termsHash: null,
+        label: encodeLabel(label),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  /**
+   * Create a session key with preset permission templates
+   */
+  async createSessionKeyWithPreset(
+    authority: Keypair,
+    sessionKeyPubkey: PublicKey,
+    durationSeconds: number,
+    preset: PermissionPreset
+  ): Promise<string> {
+    const permissions = this.getPermissionsFromPreset(preset);
+    return this.createSessionKey({
+      authority,
+      sessionKeyPubkey,
+      durationSeconds,
+      permissions,
+    });
+  }
+
+  /**
+   * Update an existing session key's expiry or permissions
+   */
+  async updateSessionKey(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    newExpirySeconds?: number,
+    newPermissions?: SessionPermissions
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const newExpiresAt = newExpirySeconds
+      ? new BN(Math.floor(Date.now() / 1000) + newExpirySeconds)
+      : null;
+
+    const tx = await this.program.methods
+      .updateSessionKey(sessionKeyPubkey, newExpiresAt, null, newPermissions)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority: authority,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  /**
+   * Set a session key's display label (at most 32 UTF-8 bytes)
+   */
+  async updateSessionKeyLabel(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    label: string
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .updateSessionKeyLabel(sessionKeyPubkey, encodeLabel(label))
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Build an instruction that fails unless the session key was granted from
+   * `domain`; prepend it to refuse keys granted on another site
+   */
+  async buildVerifySessionDomainIx(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    domain: string
+  ): Promise<TransactionInstruction> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .verifySessionDomain(sessionKeyPubkey, hashDomain(domain))
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+      })
+      .instruction();
+  }
+
+  /**
+   * Whether the session key was granted from `domain`, by simulating
+   * `verifySessionDomain`; wallets can flag keys from unexpected origins
+   */
+  async sessionDomainMatches(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    domain: string
+  ): Promise<boolean> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    try {
+      await this.program.methods
+        .verifySessionDomain(sessionKeyPubkey, hashDomain(domain))
+        .accountsStrict({
+          userAccount: userAccountPDA,
+          sessionKeyAccount: await this.findSessionKeyAccount(
+            userAccountPDA,
+            sessionKeyPubkey
+          ),
+        })
+        .simulate();
+      return true;
+    } catch (e) {
+      if (String(e).includes("SessionDomainMismatch")) return false;
+      throw e;
+    }
+  }
+
+  /**
+   * Whether the session key is active and grants `requiredPermissions` for
+   * `scope`, read from `validateSession`'s return data. On-chain programs CPI
+   * into the same instruction to gate their own on a session signer
+   */
+  async validateSession(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    requiredPermissions: SessionPermissions,
+    scope: PublicKey | null = null
+  ): Promise<boolean> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .validateSession(sessionKeyPubkey, requiredPermissions, scope)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+      })
+      .view();
+  }
+
+  /**
+   * Pause a session key without revoking it, e.g. while its bot is under
+   * maintenance; `resumeSessionKey` re-enables it with its limits intact
+   */
+  async suspendSessionKey(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .suspendSessionKey(sessionKeyPubkey)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Let a suspended session key execute again
+   */
+  async resumeSessionKey(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .resumeSessionKey(sessionKeyPubkey)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeyPubkey
+        ),
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Signed by the session key alone: narrow its own permissions and/or
+   * shorten its own expiry, e.g. after a setup phase. Anything that would
+   * widen the key fails with `RestrictionWidens`.
+   */
+  async selfRestrict(
+    authority: PublicKey,
+    sessionKeypair: Keypair,
+    restriction: {
+      expiresAtTime?: number;
+      expiresAtSlot?: number;
+      permissions?: SessionPermissions;
+    }
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const toBN = (value?: number) =>
+      value === undefined ? null : new BN(value);
+    return this.program.methods
+      .selfRestrict(
+        toBN(restriction.expiresAtTime),
+        toBN(restriction.expiresAtSlot),
+        restriction.permissions ?? null
+      )
+      .accountsStrict({
+        sessionSigner: sessionKeypair.publicKey,
+        userAccount: userAccountPDA,
+        sessionKeyAccount: await this.findSessionKeyAccount(
+          userAccountPDA,
+          sessionKeypair.publicKey
+        ),
+      })
+      .signers([sessionKeypair])
+      .rpc();
+  }
+
+  /**
+   * Signed by a session key with `canDelegate`: create a sub-key that expires
+   * with it (its expiry bounds are capped at the parent's) and stays within
+   * its permissions and allowlists. When the parent has a lifetime cap or
+   * `maxUses`, the child's are reserved out of the parent's remainder.
+   */
+  async delegateSessionKey(
+    authority: PublicKey,
+    parentKeypair: Keypair,
+    {
+      sessionKeyPubkey,
+      durationSeconds,
+      permissions,
+      label,
+      maxUses = 0,
+      allowedRecipients = [],
+      allowedMints = [],
+      allowedPrograms = [],
+      allowedDiscriminators = [],
+    }: {
+      sessionKeyPubkey: PublicKey;
+      durationSeconds: number;
+      permissions: SessionPermissions;
+      label?: string;
+      maxUses?: number;
+      allowedRecipients?: PublicKey[];
+      allowedMints?: PublicKey[];
+      allowedPrograms?: PublicKey[];
+      allowedDiscriminators?: DiscriminatorFilter[];
+    }
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const sessionKeyAccount = await this.findSessionKeyAccount(
+      userAccountPDA,
+      parentKeypair.publicKey
+    );
+    const parent = sessionKeyAccount
+      ? (await this.program.account.sessionKeyAccount.fetch(sessionKeyAccount))
+          .sessionKey
+      : (
+          await this.program.account.userAccount.fetch(userAccountPDA)
+        ).sessionKeys.find((k) => k.pubkey.equals(parentKeypair.publicKey));
+    if (!parent) throw new Error("Delegating session key not found");
+
+    const requested = Math.floor(Date.now() / 1000) + durationSeconds;
+    const expiresAtTime = parent.expiresAtTime
+      ? BN.min(parent.expiresAtTime, new BN(requested))
+      : new BN(requested);
+    return this.program.methods
+      .delegateSessionKey({
+        sessionPubkey: sessionKeyPubkey,
+        expiresAtTime,
+        expiresAtSlot: parent.expiresAtSlot,
+        permissions,
+        termsHash: null,
+        label: encodeLabel(label),
+        maxUses,
+        validFrom: parent.validFrom,
+        allowedRecipients,
+        allowedMints,
+        allowedPrograms,
+        allowedDiscriminators,
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        sessionSigner: parentKeypair.publicKey,
+        userAccount: userAccountPDA,
+        sessionKeyAccount,
+      })
+      .signers([parentKeypair])
+      .rpc();
+  }
+
+  /**
+   * Limit how many levels of sub-keys can hang below a key the authority
+   * granted. New accounts allow 1 (sub-keys can't delegate further); 0 turns
+   * delegation off.
+   */
+  async setMaxDelegationDepth(
+    authority: PublicKey,
+    maxDelegationDepth: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .setMaxDelegationDepth(maxDelegationDepth)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Revoke a specific session key; `reason` is stored on the key and emitted
+   * in `SessionKeyRevoked` so dashboards can flag compromises
+   */
+  async revokeSessionKey(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    reason: RevocationReason = { manual: {} }
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+
+    const tx = await this.program.methods
+      .revokeSessionKey(sessionKeyPubkey, reason)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority: authority,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  /**
+   * Hand a session over to a new pubkey, keeping its limits and usage.
+   * `signer` is the authority, or the old session key itself when it has
+   * `canSelfRotate`, so a holder switching devices doesn't need the authority.
+   */
+  async transferSessionToNewKey(
+    authority: PublicKey,
+    signer: Keypair,
+    oldSessionKey: PublicKey,
+    newSessionKey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .transferSessionToNewKey(oldSessionKey, newSessionKey)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        signer: signer.publicKey,
+      })
+      .signers([signer])
+      .rpc();
+  }
+
+  /**
+   * Revoke a session key and re-grant it to `newSessionKey` with the same
+   * permissions, limits, usage and remaining expiry, in one transaction
+   */
+  async rotateSessionKey(
+    authority: PublicKey,
+    oldSessionKey: PublicKey,
+    newSessionKey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .rotateSessionKey(oldSessionKey, newSessionKey)
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Revoke all session keys for a user (emergency function)
+   */
+  async revokeAllSessionKeys(authority: PublicKey): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+
+    const tx = await this.program.methods
+      .revokeAllSessionKeys()
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority: authority,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  /**
+   * Revoke every inline session key that can still be valid after `horizon`
+   * (unix seconds), e.g. to cut long-lived grants without touching short-lived
+   * operational keys
+   */
+  async revokeSessionsExpiringAfter(
+    authority: PublicKey,
+    horizon: number
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    return this.program.methods
+      .revokeSessionsExpiringAfter(new BN(horizon))
+      .accountsStrict({
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
+  }
+
+  /**
+   * Create a session key in its own PDA rather than inline, so it doesn't count
+   * against the account's capacity and its rent can be reclaimed on close
+   */
+  async createSessionKeyAccount(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey,
+    durationSeconds: number,
+    permissions: SessionPermissions
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const [sessionKeyAccount] = this.getSessionKeyAccountPDA(
+      userAccountPDA,
+      sessionKeyPubkey
+    );
+
+    return this.program.methods
+      .createSessionKeyAccount({
+        sessionPubkey: sessionKeyPubkey,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + durationSeconds),
+        expiresAtSlot: null,
+        permissions,
+        termsHash: null,
+        label: encodeLabel(),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
       .accountsStrict({
+        sessionKeyAccount,
         userAccount: userAccountPDA,
         authority,
-        tokenAccount,
-        tokenProgram: new PublicKey(
-          "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
-        ),
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
   }
 
-  async updateAllowedMints(
+  /**
+   * Revoke a PDA-stored session key
+   */
+  async revokeSessionKeyAccount(
     authority: PublicKey,
-    mints: PublicKey[]
+    sessionKeyPubkey: PublicKey,
+    reason: RevocationReason = { manual: {} }
   ): Promise<string> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
+
     return this.program.methods
-      .updateAllowedMints(mints)
+      .revokeSessionKeyAccount(reason)
       .accountsStrict({
+        sessionKeyAccount: this.getSessionKeyAccountPDA(
+          userAccountPDA,
+          sessionKeyPubkey
+        )[0],
         userAccount: userAccountPDA,
         authority,
       })
@@ -189,198 +2982,170 @@ export class SessionKeySDK {
   }
 
   /**
-   * Initialize the SDK with a connection and program ID
+   * Close a PDA-stored session key, returning its rent to the authority
    */
-  static async init(
-    connection: Connection,
-    programId: PublicKey,
-    wallet?: anchor.Wallet
-  ): Promise<SessionKeySDK> {
-    const provider = new AnchorProvider(
-      connection,
-      wallet || new anchor.Wallet(Keypair.generate()),
-      { commitment: "confirmed" }
-    );
-
-    const idl = await Program.fetchIdl(programId, provider);
-    if (!idl) throw new Error("IDL not found");
-
-    const program = new Program(idl as Time, provider) as Program<Time>;
-    return new SessionKeySDK(program, provider);
-  }
+  async closeSessionKeyAccount(
+    authority: PublicKey,
+    sessionKeyPubkey: PublicKey
+  ): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
 
-  /**
-   * Derive the user account PDA for a given authority
-   */
-  async getUserAccountPDA(authority: PublicKey): Promise<[PublicKey, number]> {
-    return PublicKey.findProgramAddress(
-      [Buffer.from("user_account"), authority.toBuffer()],
-      this.program.programId
-    );
+    return this.program.methods
+      .closeSessionKeyAccount()
+      .accountsStrict({
+        sessionKeyAccount: this.getSessionKeyAccountPDA(
+          userAccountPDA,
+          sessionKeyPubkey
+        )[0],
+        userAccount: userAccountPDA,
+        authority,
+      })
+      .rpc();
   }
 
   /**
-   * Initialize a user account for managing session keys
+   * Change how many session keys the account has space for (at most 10; new
+   * accounts hold at most 7). Growing charges rent to the authority; shrinking
+   * refunds it. A call grows the account by at most 10 KB, so grow in steps of
+   * up to 8 keys.
    */
-  async initializeUserAccount(authority: PublicKey): Promise<string> {
+  async resizeUserAccount(
+    authority: PublicKey,
+    newCapacity: number
+  ): Promise<string> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
-    console.log("User account PDA:", userAccountPDA.toBase58());
-    const tx = await this.program.methods
-      .initializeUserAccount()
+
+    return this.program.methods
+      .resizeUserAccount(newCapacity)
       .accountsStrict({
         userAccount: userAccountPDA,
-        authority: authority,
+        authority,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
-    console.log("Initialize user account tx:", tx);
-
-    return tx;
   }
 
-  async initializeUserAccountWithConfig(
-    authority: PublicKey,
-    allowedMints: PublicKey[],
-    initialDepositLamports: BN
-  ): Promise<string> {
+  /**
+   * Upgrade `authority`'s account to the current layout if an older program
+   * version wrote it; the provider wallet pays for any added space
+   */
+  async migrateUserAccount(authority: PublicKey): Promise<string> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
     return this.program.methods
-      .initializeUserAccountWithConfig(allowedMints, initialDepositLamports)
+      .migrateUserAccount()
       .accountsStrict({
         userAccount: userAccountPDA,
-        authority,
+        payer: this.provider.wallet.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
   }
 
   /**
-   * Create a new session key with specified permissions
+   * Close the user account, returning its rent and vault balance to the authority.
+   * Every session key must be inactive and every delegate approval revoked.
    */
-  async createSessionKey({
-    authority,
-    sessionKeyPubkey,
-    durationSeconds,
-    permissions,
-  }: {
-    authority: Keypair;
-    sessionKeyPubkey: PublicKey;
-    durationSeconds: number;
-    permissions: SessionPermissions;
-  }): Promise<string> {
-    const [userAccountPDA] = await this.getUserAccountPDA(authority.publicKey);
-    const expiresAt = new BN(Math.floor(Date.now() / 1000) + durationSeconds);
+  async closeUserAccount(authority: PublicKey): Promise<string> {
+    const [userAccountPDA] = await this.getUserAccountPDA(authority);
+    const userAccount = await this.program.account.userAccount.fetch(
+      userAccountPDA
+    );
 
-    const ix = await this.program.methods
-      .createSessionKey(
-        sessionKeyPubkey,
-        expiresAt,
-        { time: {} }, // ExpirationType.Time
-        permissions
-      )
+    return this.program.methods
+      .closeUserAccount()
       .accountsStrict({
         userAccount: userAccountPDA,
-        authority: authority.publicKey,
-        systemProgram: SystemProgram.programId,
+        authority,
       })
-      .instruction();
+      .remainingAccounts(
+        userAccount.delegatedTokenAccounts.map((pubkey) => ({
+          pubkey,
+          isSigner: false,
+          isWritable: false,
+        }))
+      )
+      .rpc();
+  }
 
-    const connection = new Connection("http://localhost:8899", {
-      commitment: "confirmed",
-    });
-    const tx = new Transaction().add(ix);
-    const sig = await connection.sendTransaction(tx, [authority]);
-    console.log("sig", sig);
-    await connection.confirmTransaction(sig, "confirmed");
-    return sig;
+  getArchivePDA(authority: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("archive"), authority.toBuffer()],
+      this.program.programId
+    );
   }
 
   /**
-   * Create a new session key with block height expiration
+   * Archive a dormant account: its rent and vault balance return to the authority
+   * and only a hash of its state is kept. Returns the payload needed to restore it;
+   * it can also be recovered later from the transaction's `UserAccountArchived` event.
    */
-  async createSessionKeyWithBlockHeight(
-    authority: PublicKey,
-    sessionKeyPubkey: PublicKey,
-    blocksFromNow: number,
-    permissions: SessionPermissions
-  ): Promise<string> {
+  async archiveUserAccount(
+    authority: PublicKey
+  ): Promise<{ signature: string; data: Buffer }> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
 
-    // Get current slot (block height)
-    const currentSlot = await this.provider.connection.getSlot();
-    const expiresAt = new BN(currentSlot + blocksFromNow);
-
-    const tx = await this.program.methods
-      .createSessionKey(
-        sessionKeyPubkey,
-        expiresAt,
-        { blockHeight: {} }, // ExpirationType.BlockHeight
-        permissions
-      )
+    const signature = await this.program.methods
+      .archiveUserAccount()
       .accountsStrict({
         userAccount: userAccountPDA,
-        authority: authority,
+        archive: this.getArchivePDA(authority)[0],
+        authority,
         systemProgram: SystemProgram.programId,
       })
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
-    return tx;
+    return { signature, data: await this.archivedState(signature) };
   }
 
   /**
-   * Create a session key with preset permission templates
+   * The restore payload emitted by an `archiveUserAccount` transaction
    */
-  async createSessionKeyWithPreset(
-    authority: Keypair,
-    sessionKeyPubkey: PublicKey,
-    durationSeconds: number,
-    preset: PermissionPreset
-  ): Promise<string> {
-    const permissions = this.getPermissionsFromPreset(preset);
-    return this.createSessionKey({
-      authority,
-      sessionKeyPubkey,
-      durationSeconds,
-      permissions,
+  async archivedState(signature: string): Promise<Buffer> {
+    const tx = await this.provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
     });
+    const parser = new anchor.EventParser(
+      this.program.programId,
+      this.program.coder
+    );
+    for (const event of parser.parseLogs(tx?.meta?.logMessages ?? [])) {
+      if (event.name === "userAccountArchived") {
+        return Buffer.from(event.data.data as Buffer);
+      }
+    }
+    throw new Error(`no UserAccountArchived event in ${signature}`);
   }
 
   /**
-   * Update an existing session key's expiry or permissions
+   * Recreate an archived account from its payload, closing the archive
    */
-  async updateSessionKey(
+  async restoreUserAccount(
     authority: PublicKey,
-    sessionKeyPubkey: PublicKey,
-    newExpirySeconds?: number,
-    newPermissions?: SessionPermissions
+    data: Buffer
   ): Promise<string> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
-    const newExpiresAt = newExpirySeconds
-      ? new BN(Math.floor(Date.now() / 1000) + newExpirySeconds)
-      : null;
 
-    const tx = await this.program.methods
-      .updateSessionKey(sessionKeyPubkey, newExpiresAt, newPermissions)
+    return this.program.methods
+      .restoreUserAccount(data)
       .accountsStrict({
+        archive: this.getArchivePDA(authority)[0],
         userAccount: userAccountPDA,
-        authority: authority,
+        authority,
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
-
-    return tx;
   }
 
   /**
-   * Revoke a specific session key
+   * Clean up expired and revoked session keys, at most `limit` of them (0 =
+   * all); `SessionKeysCleaned.pendingCount` says whether another call is needed
    */
-  async revokeSessionKey(
-    authority: PublicKey,
-    sessionKeyPubkey: PublicKey
-  ): Promise<string> {
+  async cleanupSessionKeys(authority: PublicKey, limit = 0): Promise<string> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
 
     const tx = await this.program.methods
-      .revokeSessionKey(sessionKeyPubkey)
+      .cleanupSessionKeys(limit)
       .accountsStrict({
         userAccount: userAccountPDA,
         authority: authority,
@@ -391,37 +3156,34 @@ export class SessionKeySDK {
   }
 
   /**
-   * Revoke all session keys for a user (emergency function)
+   * Keep expired, revoked or used-up session keys for `retentionSeconds`
+   * before either cleanup may remove them (0 = remove right away)
    */
-  async revokeAllSessionKeys(authority: PublicKey): Promise<string> {
+  async setCleanupRetention(
+    authority: PublicKey,
+    retentionSeconds: number
+  ): Promise<string> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
-
-    const tx = await this.program.methods
-      .revokeAllSessionKeys()
-      .accountsStrict({
-        userAccount: userAccountPDA,
-        authority: authority,
-      })
+    return this.program.methods
+      .setCleanupRetention(retentionSeconds)
+      .accountsStrict({ userAccount: userAccountPDA, authority })
       .rpc();
-
-    return tx;
   }
 
   /**
-   * Clean up expired and revoked session keys
+   * Remove expired session keys from any user's account, at most `limit` (0 =
+   * all), earning a bounty per key from its SOL vault. Signed by the provider
+   * wallet, which receives the bounty.
    */
-  async cleanupSessionKeys(authority: PublicKey): Promise<string> {
+  async crankCleanup(authority: PublicKey, limit = 0): Promise<string> {
     const [userAccountPDA] = await this.getUserAccountPDA(authority);
-
-    const tx = await this.program.methods
-      .cleanupSessionKeys()
+    return this.program.methods
+      .crankCleanup(limit)
       .accountsStrict({
         userAccount: userAccountPDA,
-        authority: authority,
+        cranker: this.provider.wallet.publicKey,
       })
       .rpc();
-
-    return tx;
   }
 
   /**
@@ -435,21 +3197,51 @@ export class SessionKeySDK {
         userAccountPDA
       );
       const currentTime = Math.floor(Date.now() / 1000);
+      const currentSlot = await this.provider.connection.getSlot();
 
       return userAccount.sessionKeys.map((key) => ({
         pubkey: key.pubkey,
         createdAt: key.createdAt.toNumber(),
-        expiresAt: key.expiresAt.toNumber(),
-        expirationType: key.expirationType,
-        isExpired: this.isKeyExpired(key, currentTime),
+        expiresAtTime: key.expiresAtTime?.toNumber() ?? null,
+        expiresAtSlot: key.expiresAtSlot?.toNumber() ?? null,
+        isExpired: this.isKeyExpired(key, currentTime, currentSlot),
         isRevoked: key.isRevoked,
-        isActive: !key.isRevoked && !this.isKeyExpired(key, currentTime),
+        revokedAt: key.revokedAt.toNumber(),
+        revocationReason: key.revocationReason as RevocationReason | null,
+        isSuspended: key.isSuspended,
+        lockedUntil: key.lockedUntil.toNumber(),
+        isActive:
+          !key.isRevoked &&
+          !key.isSuspended &&
+          !this.isKeyExpired(key, currentTime, currentSlot) &&
+          key.validFrom.toNumber() <= currentTime &&
+          key.lockedUntil.toNumber() <= currentTime,
         permissions: key.permissions,
         label: new Uint8Array(key.label),
-        remainingTimeSeconds: Math.max(
-          0,
-          key.expiresAt.toNumber() - currentTime
-        ),
+        preset: key.preset,
+        presetVersion: key.presetVersion,
+        termsHash: new Uint8Array(key.termsHash),
+        totalSpent: key.totalSpent,
+        spentInWindow: key.spentInWindow,
+        usesInWindow: key.usesInWindow.toNumber(),
+        maxUses: key.maxUses,
+        validFrom: key.validFrom.toNumber(),
+        allowedRecipients: key.allowedRecipients,
+        allowedMints: key.allowedMints,
+        allowedPrograms: key.allowedPrograms,
+        allowedDiscriminators: key.allowedDiscriminators,
+        priorPermissions: key.priorPermissions,
+        wideningActiveAt: key.wideningActiveAt.toNumber(),
+        useCount: key.useCount,
+        lastUsedAt: key.lastUsedAt.toNumber(),
+        idleTimeout: key.idleTimeout,
+        maxExpiresAt: key.maxExpiresAt.toNumber(),
+        scope: key.scope,
+        domainHash: new Uint8Array(key.domainHash),
+        delegatedBy: key.delegatedBy,
+        remainingTimeSeconds: key.expiresAtTime
+          ? Math.max(0, key.expiresAtTime.toNumber() - currentTime)
+          : Infinity,
       }));
     } catch (error) {
       // Account doesn't exist yet
@@ -465,6 +3257,19 @@ export class SessionKeySDK {
     return allKeys.filter((key) => key.isActive);
   }
 
+  /**
+   * Active session keys idle for at least `idleSeconds`, counting from their
+   * grant when never used; candidates for revocation
+   */
+  async getDormantSessionKeys(
+    authority: PublicKey,
+    idleSeconds: number
+  ): Promise<SessionKeyInfo[]> {
+    const cutoff = Math.floor(Date.now() / 1000) - idleSeconds;
+    const keys = await this.getActiveSessionKeys(authority);
+    return keys.filter((key) => (key.lastUsedAt || key.createdAt) <= cutoff);
+  }
+
   /**
    * Check if a specific session key is valid
    */
@@ -514,8 +3319,15 @@ export class SessionKeySDK {
           canTransfer: true,
           canDelegate: true,
           canExecuteCustom: true,
+          canSelfRotate: false,
           maxTransferAmount: new BN(0), // 0 means unlimited
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
         };
 
       case PermissionPreset.TRANSFER_ONLY:
@@ -523,8 +3335,15 @@ export class SessionKeySDK {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(0),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
         };
 
       case PermissionPreset.LIMITED_TRANSFER:
@@ -532,8 +3351,15 @@ export class SessionKeySDK {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(100_000_000), // 0.1 SOL
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
         };
 
       case PermissionPreset.DELEGATE_ONLY:
@@ -541,8 +3367,15 @@ export class SessionKeySDK {
           canTransfer: false,
           canDelegate: true,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(0),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
         };
 
       case PermissionPreset.CUSTOM_ONLY:
@@ -550,8 +3383,15 @@ export class SessionKeySDK {
           canTransfer: false,
           canDelegate: false,
           canExecuteCustom: true,
+          canSelfRotate: false,
           maxTransferAmount: new BN(0),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
         };
 
       case PermissionPreset.READ_ONLY:
@@ -559,8 +3399,15 @@ export class SessionKeySDK {
           canTransfer: false,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(0),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
         };
 
       default:
@@ -615,17 +3462,18 @@ export class SessionKeySDK {
     return () => clearInterval(interval);
   }
 
-  // Helper method to check if a key is expired
-  private isKeyExpired(key: any, currentTime: number): boolean {
-    if (key.expirationType?.time !== undefined) {
-      return key.expiresAt.toNumber() < currentTime;
-    } else if (key.expirationType?.blockHeight !== undefined) {
-      // For block height, we'd need to get current slot
-      // This is a simplified check - in production, you'd want to fetch current slot
-      return false;
-    }
-    // Default to time-based check for backward compatibility
-    return key.expiresAt.toNumber() < currentTime;
+  // Helper method to check if a key is expired; whichever bound is hit first
+  private isKeyExpired(
+    key: any,
+    currentTime: number,
+    currentSlot: number
+  ): boolean {
+    return (
+      (key.expiresAtTime !== null &&
+        key.expiresAtTime.toNumber() <= currentTime) ||
+      (key.expiresAtSlot !== null &&
+        key.expiresAtSlot.toNumber() <= currentSlot)
+    );
   }
 }
 
@@ -634,23 +3482,167 @@ export interface SessionPermissions {
   canTransfer: boolean;
   canDelegate: boolean;
   canExecuteCustom: boolean;
+  canSelfRotate: boolean;
+  /** Cumulative cap over the key's lifetime (0 = unlimited) */
   maxTransferAmount: BN;
+  /** Cap per spend window (0 = none) */
+  dailyLimit: BN;
+  /** Spend window length; 0 = 24 hours */
+  spendWindowSeconds: number;
+  /** Executions allowed per use window (0 = unlimited) */
+  maxUsesPerWindow: number;
+  /** Use window length; 0 = 24 hours */
+  useWindowSeconds: number;
   customFlags: number;
+  /**
+   * Highest invoke stack height `executeCustom` may run at; 1 = top-level
+   * instructions only (0 = no limit)
+   */
+  maxCpiDepth: number;
+  /** Accounts `executeCustom` may forward (0 = no limit) */
+  maxCustomAccounts: number;
+}
+
+export interface AttestationGate {
+  mint: PublicKey;
+  subject: { sessionHolder: {} } | { recipient: {} } | { both: {} };
+}
+
+/**
+ * Wallet a token transfer is meant for; the program refuses a `toToken` it
+ * doesn't own, or that isn't its associated token account with `requireAta`
+ */
+export interface RecipientCheck {
+  owner: PublicKey;
+  requireAta: boolean;
 }
 
 export interface SessionKeyInfo {
   pubkey: PublicKey;
   createdAt: number;
-  expiresAt: number;
-  expirationType?: { time?: {} } | { blockHeight?: {} }; // Expiration type (Time or BlockHeight)
+  /** Unix timestamp bound, if any */
+  expiresAtTime: number | null;
+  /** Slot bound, if any; the key expires at whichever bound hits first */
+  expiresAtSlot: number | null;
   isExpired: boolean;
   isRevoked: boolean;
+  /** Unix timestamp the key was revoked at; 0 = not revoked */
+  revokedAt: number;
+  /** Why the key was revoked; null if it wasn't, or was revoked with the rest */
+  revocationReason: RevocationReason | null;
+  /** Paused with `suspendSessionKey`; resumable, unlike revocation */
+  isSuspended: boolean;
+  /** Unix timestamp a lockout ends at (0 = never locked) */
+  lockedUntil: number;
   isActive: boolean;
   permissions: SessionPermissions;
   label: Uint8Array;
+  preset: PublicKey | null;
+  presetVersion: number;
+  termsHash: Uint8Array;
+  totalSpent: BN;
+  spentInWindow: BN;
+  usesInWindow: number;
+  maxUses: number;
+  validFrom: number;
+  useCount: number;
+  /** Unix timestamp of the last successful spend or execution; 0 = never */
+  lastUsedAt: number;
+  /** Seconds without use that expire a sliding key; 0 = fixed expiry */
+  idleTimeout: number;
+  /** Latest `expiresAtTime` a sliding key can be extended to */
+  maxExpiresAt: number;
+  /** Dapp the key only works with; `PublicKey.default` = unscoped */
+  scope: PublicKey;
+  /** `hashDomain` of the origin that requested the grant; zero if none */
+  domainHash: Uint8Array;
+  /** Recipients the key may pay; empty = any */
+  allowedRecipients: PublicKey[];
+  /** Mints the key may move on top of the account allowlist; empty = any */
+  allowedMints: PublicKey[];
+  /** Programs the key may call with custom actions */
+  allowedPrograms: PublicKey[];
+  /** Per-program instruction filters for custom actions */
+  allowedDiscriminators: DiscriminatorFilter[];
+  /** Permissions still in force while a widening waits out its delay */
+  priorPermissions: SessionPermissions | null;
+  /** When `permissions` take effect if they were widened; 0 = in effect */
+  wideningActiveAt: number;
+  /** Session key that delegated this one; null if the authority granted it */
+  delegatedBy: PublicKey | null;
+  /** Seconds until the time bound; Infinity for slot-only keys */
   remainingTimeSeconds: number;
 }
 
+/**
+ * Instruction a session key may send to `program` with `executeCustom`: the
+ * first `len` bytes of the data must equal `discriminator`
+ */
+export type DiscriminatorFilter = {
+  program: PublicKey;
+  discriminator: number[];
+  len: number;
+};
+
+/** Policy document version this SDK writes, matching `POLICY_VERSION` */
+export const POLICY_VERSION = 1;
+
+/**
+ * One policy rule; `sessionKey` null applies it to every key. Native SOL
+ * spends are checked as the wrapped SOL mint, and recipient or mint rules are
+ * skipped for actions without one (custom actions, swap steps' recipients).
+ */
+export interface PolicyRule {
+  sessionKey: PublicKey | null;
+  condition:
+    | { maxAmount: { 0: BN } }
+    | { activeBetween: { start: BN; end: BN } }
+    | {
+        schedule: {
+          /** Bit 0 = Monday, UTC */
+          weekdays: number;
+          startMinute: number;
+          endMinute: number;
+        };
+      }
+    | { recipients: { 0: PublicKey[] } }
+    | { mints: { 0: PublicKey[] } };
+}
+
+export type RevocationReason =
+  | { compromised: {} }
+  | { expired: {} }
+  | { rotated: {} }
+  | { manual: {} }
+  | { other: { 0: number } };
+
+export type PipelineStep =
+  | {
+      transfer: { mint: PublicKey; destination: PublicKey; maxAmount: BN };
+    }
+  | {
+      swap: {
+        program: PublicKey;
+        mintIn: PublicKey;
+        maxAmountIn: BN;
+        destination: PublicKey;
+        minAmountOut: BN;
+      };
+    };
+
+/**
+ * Session-supplied input for one pipeline step. `sourceToken` is the owner's
+ * token account the step spends from; swaps also pass their instruction data
+ * and accounts, with the delegate PDA wherever the swap expects its signer.
+ */
+export interface PipelineStepInput {
+  sourceToken: PublicKey;
+  /** Transfer amount; swaps are limited by measuring the source balance */
+  amount?: BN;
+  data?: Buffer;
+  accounts?: AccountMeta[];
+}
+
 export interface BatchSessionKeyParams {
   pubkey: PublicKey;
   durationSeconds: number;
@@ -680,6 +3672,134 @@ export async function createSessionKeySDK(
   return SessionKeySDK.init(connection, programId, wallet);
 }
 
+export function encodeName(name: string): number[] {
+  const bytes = Buffer.alloc(32);
+  Buffer.from(name, "utf8").copy(bytes, 0, 0, 32);
+  return Array.from(bytes);
+}
+
+export function decodeName(bytes: number[] | Uint8Array): string {
+  return Buffer.from(bytes).toString("utf8").replace(/\0+$/, "");
+}
+
+/**
+ * Commitment `setKillPhrase` stores for `phrase`:
+ * `sha256("kill_phrase" || userAccount || phrase)`
+ */
+export function killPhraseCommitment(
+  userAccount: PublicKey,
+  phrase: string
+): number[] {
+  const hash = createHash("sha256")
+    .update(Buffer.from("kill_phrase"))
+    .update(userAccount.toBuffer())
+    .update(Buffer.from(phrase, "utf8"))
+    .digest();
+  return Array.from(hash);
+}
+
+/**
+ * Message a bonded operator signs to authorize one delegated transfer:
+ * `sha256("operator_receipt" || userAccount || sessionKey || mint ||
+ * destination || amount_le || useCount_le)`, where `useCount` is the session
+ * key's count before the transfer
+ */
+export function operatorPayload(
+  userAccount: PublicKey,
+  sessionKey: PublicKey,
+  mint: PublicKey,
+  destination: PublicKey,
+  amount: BN,
+  useCount: number
+): Buffer {
+  const useCountBytes = Buffer.alloc(4);
+  useCountBytes.writeUInt32LE(useCount);
+  return createHash("sha256")
+    .update(Buffer.from("operator_receipt"))
+    .update(userAccount.toBuffer())
+    .update(sessionKey.toBuffer())
+    .update(mint.toBuffer())
+    .update(destination.toBuffer())
+    .update(amount.toArrayLike(Buffer, "le", 8))
+    .update(useCountBytes)
+    .digest();
+}
+
+/**
+ * Ed25519 program instruction carrying the operator's signature over
+ * `payload`; place it before the transfer in the same transaction
+ */
+export function operatorSignatureInstruction(
+  operator: Keypair,
+  payload: Buffer
+): TransactionInstruction {
+  return Ed25519Program.createInstructionWithPrivateKey({
+    privateKey: operator.secretKey,
+    message: payload,
+  });
+}
+
+/**
+ * SHA-256 of the human-readable grant description, as stored in a session's terms_hash
+ */
+export function hashTerms(terms: string): number[] {
+  return Array.from(Buffer.from(anchor.utils.sha256.hash(terms), "hex"));
+}
+
+/**
+ * SHA-256 of a dapp's origin, as stored in a session's domain_hash. Any URL
+ * on the site gives the same hash, e.g. `https://app.example/play`
+ */
+export function hashDomain(url: string): number[] {
+  const origin = new URL(url).origin;
+  return Array.from(Buffer.from(anchor.utils.sha256.hash(origin), "hex"));
+}
+
+/**
+ * Zero-padded 32-byte session key label; throws if the text doesn't fit
+ */
+export function encodeLabel(label = ""): number[] {
+  const bytes = Buffer.from(label, "utf8");
+  if (bytes.length > 32) {
+    throw new Error("Session key label exceeds 32 bytes");
+  }
+  return Array.from(Buffer.concat([bytes, Buffer.alloc(32 - bytes.length)]));
+}
+
+/**
+ * Text of a stored session key label, without its zero padding
+ */
+export function decodeLabel(label: number[] | Uint8Array): string {
+  const bytes = Buffer.from(label);
+  const end = bytes.indexOf(0);
+  return bytes.subarray(0, end === -1 ? bytes.length : end).toString("utf8");
+}
+
+/**
+ * Filter allowing one Anchor instruction, identified by its 8-byte
+ * `sha256("global:<name>")` discriminator
+ */
+export function anchorInstructionFilter(
+  program: PublicKey,
+  instructionName: string
+): DiscriminatorFilter {
+  const hash = Buffer.from(
+    anchor.utils.sha256.hash(`global:${instructionName}`),
+    "hex"
+  );
+  return { program, discriminator: Array.from(hash.subarray(0, 8)), len: 8 };
+}
+
+/**
+ * Filter allowing one native program instruction by its leading tag byte
+ */
+export function nativeInstructionFilter(
+  program: PublicKey,
+  tag: number
+): DiscriminatorFilter {
+  return { program, discriminator: [tag, 0, 0, 0, 0, 0, 0, 0], len: 1 };
+}
+
 export function generateSessionKey(): Keypair {
   return Keypair.generate();
 }
@@ -97,8 +97,15 @@ export async function runSplTokenTransferExample() {
       canTransfer: true,
       canDelegate: false,
       canExecuteCustom: false,
+      canSelfRotate: false,
       maxTransferAmount: new BN(500_000_000), // 0.5 tokens
+      dailyLimit: new BN(0),
+      spendWindowSeconds: 0,
+      maxUsesPerWindow: 0,
+      useWindowSeconds: 0,
       customFlags: 0,
+      maxCpiDepth: 0,
+      maxCustomAccounts: 0,
     },
   });
 
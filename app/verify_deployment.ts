@@ -0,0 +1,151 @@
+import { Idl, Program } from "@coral-xyz/anchor";
+import {
+  Connection,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+} from "@solana/web3.js";
+import { createHash } from "crypto";
+import { readFileSync } from "fs";
+import { Time } from "../target/types/time";
+
+export const BPF_LOADER_UPGRADEABLE = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+/** Bytes before the executable in a `ProgramData` account */
+const PROGRAM_DATA_HEADER = 45;
+
+export interface DeploymentReport {
+  programId: PublicKey;
+  /** Hash of the deployed executable */
+  deployedHash: string;
+  /** Slot the program was last deployed at */
+  deploymentSlot: number;
+  /** Hash of the local build, when one was given */
+  localHash: string | null;
+  /** The upgrade authority's record for `deployedHash`, if it published one */
+  published: {
+    sourceCommit: string;
+    deploymentSlot: number;
+    publishedAt: number;
+  } | null;
+}
+
+/**
+ * SHA-256 of an executable with trailing zero padding dropped, matching
+ * `solana-verify get-executable-hash` and `get-program-hash`
+ */
+export function executableHash(executable: Buffer): string {
+  let end = executable.length;
+  while (end > 0 && executable[end - 1] === 0) end--;
+  return createHash("sha256").update(executable.subarray(0, end)).digest("hex");
+}
+
+export function programDataAddress(programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE
+  )[0];
+}
+
+export function publishedBuildAddress(
+  programId: PublicKey,
+  buildHash: string
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("build"), Buffer.from(buildHash, "hex")],
+    programId
+  )[0];
+}
+
+/**
+ * Hash the deployed program and look up the build record published for it,
+ * comparing against a local build (e.g. from `solana-verify build`) if given
+ */
+export async function verifyDeployment(
+  program: Program<Time>,
+  localBuild?: Buffer
+): Promise<DeploymentReport> {
+  const connection = program.provider.connection;
+  const info = await connection.getAccountInfo(
+    programDataAddress(program.programId)
+  );
+  if (!info) throw new Error("Program is not deployed as upgradeable");
+
+  const deployedHash = executableHash(info.data.subarray(PROGRAM_DATA_HEADER));
+  const record = await program.account.publishedBuild.fetchNullable(
+    publishedBuildAddress(program.programId, deployedHash)
+  );
+  return {
+    programId: program.programId,
+    deployedHash,
+    deploymentSlot: Number(info.data.readBigUInt64LE(4)),
+    localHash: localBuild ? executableHash(localBuild) : null,
+    published: record && {
+      sourceCommit: Buffer.from(record.sourceCommit).toString("hex"),
+      deploymentSlot: record.deploymentSlot.toNumber(),
+      publishedAt: record.publishedAt.toNumber(),
+    },
+  };
+}
+
+/**
+ * Upgrade authority publishes `buildHash` (hex) as a verified build made from
+ * git commit `sourceCommit` (hex)
+ */
+export async function publishBuildHash(
+  program: Program<Time>,
+  admin: Keypair,
+  buildHash: string,
+  sourceCommit: string
+): Promise<string> {
+  return program.methods
+    .publishBuildHash(
+      Array.from(Buffer.from(buildHash, "hex")),
+      Array.from(Buffer.from(sourceCommit, "hex"))
+    )
+    .accountsStrict({
+      publishedBuild: publishedBuildAddress(program.programId, buildHash),
+      admin: admin.publicKey,
+      program: program.programId,
+      programData: programDataAddress(program.programId),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([admin])
+    .rpc();
+}
+
+if (require.main === module) {
+  const [soPath, url] = process.argv.slice(2);
+  const idl: Idl = JSON.parse(readFileSync("target/idl/time.json", "utf8"));
+  const connection = new Connection(url ?? "http://127.0.0.1:8899");
+  const program = new Program(idl, { connection }) as Program<Time>;
+
+  verifyDeployment(program, soPath ? readFileSync(soPath) : undefined).then(
+    (report) => {
+      console.log(`deployed: ${report.deployedHash}`);
+      let ok = true;
+      if (report.localHash) {
+        ok = report.localHash === report.deployedHash;
+        console.log(
+          `local:    ${report.localHash} (${ok ? "matches" : "differs"})`
+        );
+      }
+      if (report.published) {
+        console.log(
+          `published for commit ${report.published.sourceCommit} ` +
+            `(deployment slot ${report.published.deploymentSlot})`
+        );
+      } else {
+        ok = false;
+        console.log("no published build record for the deployed hash");
+      }
+      process.exit(ok ? 0 : 2);
+    },
+    (err) => {
+      console.error(err);
+      process.exit(1);
+    }
+  );
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock::{DEFAULT_MS_PER_SLOT, DEFAULT_SLOTS_PER_EPOCH};
+
+/// Clock sysvar at `slot` and `unix_timestamp`, to install with the harness's sysvar
+/// setter (e.g. LiteSVM's `set_sysvar`) when a test needs keys to expire or windows to
+/// roll over. Epoch fields are derived from the slot at the default 432,000 slots per
+/// epoch, with the epoch's first slot placed at the default 400 ms per slot before
+/// `unix_timestamp`.
+pub fn clock_at(slot: u64, unix_timestamp: i64) -> Clock {
+    let epoch = slot / DEFAULT_SLOTS_PER_EPOCH;
+    let slots_into_epoch = slot % DEFAULT_SLOTS_PER_EPOCH;
+    Clock {
+        slot,
+        epoch_start_timestamp: unix_timestamp
+            - (slots_into_epoch * DEFAULT_MS_PER_SLOT / 1000) as i64,
+        epoch,
+        leader_schedule_epoch: epoch + 1,
+        unix_timestamp,
+    }
+}
+
+/// `clock` moved forward by `seconds`, with the slot advanced at the default 400 ms slot
+pub fn warp(clock: &Clock, seconds: i64) -> Clock {
+    let slots = (seconds.max(0) as u64) * 1000 / DEFAULT_MS_PER_SLOT;
+    clock_at(clock.slot + slots, clock.unix_timestamp + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn clock_at_dates_the_epoch_from_its_first_slot() {
+        let clock = clock_at(2 * DEFAULT_SLOTS_PER_EPOCH + 2_500, NOW);
+        assert_eq!(clock.epoch, 2);
+        assert_eq!(clock.leader_schedule_epoch, 3);
+        assert_eq!(clock.epoch_start_timestamp, NOW - 1_000);
+
+        let first_slot = clock_at(DEFAULT_SLOTS_PER_EPOCH, NOW);
+        assert_eq!(first_slot.epoch, 1);
+        assert_eq!(first_slot.epoch_start_timestamp, NOW);
+    }
+
+    #[test]
+    fn warp_advances_time_slots_and_epochs() {
+        let clock = warp(&clock_at(0, NOW), 60);
+        assert_eq!((clock.slot, clock.unix_timestamp), (150, NOW + 60));
+        assert_eq!(clock.epoch_start_timestamp, NOW);
+
+        // Crossing into the next epoch moves its start along
+        let clock = warp(&clock_at(DEFAULT_SLOTS_PER_EPOCH - 1, NOW), 4);
+        assert_eq!((clock.slot, clock.epoch), (DEFAULT_SLOTS_PER_EPOCH + 9, 1));
+        assert_eq!(clock.epoch_start_timestamp, NOW + 1);
+    }
+}
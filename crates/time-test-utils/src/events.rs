@@ -0,0 +1,94 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+/// Every event of type `E` in a transaction's log messages, in emission order. Anchor
+/// logs each event as `Program data: <base64(discriminator || borsh)>`.
+pub fn decode_events<E: Discriminator + AnchorDeserialize>(logs: &[String]) -> Vec<E> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|bytes| {
+            let payload = bytes.strip_prefix(E::DISCRIMINATOR)?;
+            E::deserialize(&mut &payload[..]).ok()
+        })
+        .collect()
+}
+
+/// The single event of type `E` in `logs`; panics if there is none or more than one
+pub fn expect_event<E: Discriminator + AnchorDeserialize>(logs: &[String]) -> E {
+    let mut events = decode_events::<E>(logs);
+    assert_eq!(
+        events.len(),
+        1,
+        "expected exactly one {} event",
+        std::any::type_name::<E>()
+    );
+    events.remove(0)
+}
+
+/// Panics if `logs` contain any event of type `E`
+pub fn expect_no_event<E: Discriminator + AnchorDeserialize>(logs: &[String]) {
+    assert!(
+        decode_events::<E>(logs).is_empty(),
+        "unexpected {} event",
+        std::any::type_name::<E>()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Event;
+    use time::events::{SessionKeyLocked, TransferBlocked};
+
+    fn log_line(event: &impl Event) -> String {
+        format!("Program data: {}", STANDARD.encode(event.data()))
+    }
+
+    fn blocked(amount: u64) -> TransferBlocked {
+        TransferBlocked {
+            authority: Pubkey::new_unique(),
+            session_key: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn decodes_only_events_of_the_requested_type() {
+        let locked = SessionKeyLocked {
+            authority: Pubkey::new_unique(),
+            session_key: Pubkey::new_unique(),
+            locked_until: 60,
+        };
+        let logs = vec![
+            "Program log: Instruction: SplDelegatedTransfer".to_string(),
+            log_line(&blocked(1)),
+            log_line(&locked),
+            "Program data: not base64".to_string(),
+            log_line(&blocked(2)),
+        ];
+
+        let amounts: Vec<u64> = decode_events::<TransferBlocked>(&logs)
+            .iter()
+            .map(|event| event.amount)
+            .collect();
+        assert_eq!(amounts, [1, 2]);
+        assert_eq!(expect_event::<SessionKeyLocked>(&logs).locked_until, 60);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected exactly one")]
+    fn expect_event_rejects_repeats() {
+        let logs = vec![log_line(&blocked(1)), log_line(&blocked(2))];
+        expect_event::<TransferBlocked>(&logs);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected")]
+    fn expect_no_event_rejects_any() {
+        expect_no_event::<TransferBlocked>(&[log_line(&blocked(1))]);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::FixtureAccount;
+use anchor_lang::prelude::*;
+use time::constants::{
+    DEFAULT_MAX_DELEGATION_DEPTH, DEFAULT_SESSION_KEY_CAPACITY, USER_ACCOUNT_VERSION,
+};
+use time::state::{SessionKey, SessionPermissions, UserAccount};
+
+/// User account PDA of `authority`
+pub fn user_account_address(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UserAccount::SEED_PREFIX, authority.as_ref()], &time::ID)
+}
+
+/// Delegate PDA that signs `mint` transfers for `user_account`
+pub fn delegate_address(user_account: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"delegate", user_account.as_ref(), mint.as_ref()],
+        &time::ID,
+    )
+}
+
+/// User account exactly as `initialize_user_account` leaves it, with no session keys.
+/// Fields are public, so tests can set up any state before calling `user_account`.
+pub fn blank_user_account(authority: &Pubkey) -> UserAccount {
+    UserAccount {
+        authority: *authority,
+        session_keys: Vec::new(),
+        bump: user_account_address(authority).1,
+        allowed_mints: Vec::new(),
+        guard_program: None,
+        attestation_gate: None,
+        enforce_blocklist: false,
+        bond_requirement: None,
+        violation_reports: 0,
+        budgeted_mints: Vec::new(),
+        digest_interval: 0,
+        stats_enabled: false,
+        blackout_windows: Vec::new(),
+        max_slot_age: 0,
+        savings_rule: None,
+        spend_categories: Vec::new(),
+        widening_delay_seconds: 0,
+        delegated_token_accounts: Vec::new(),
+        session_key_capacity: DEFAULT_SESSION_KEY_CAPACITY as u16,
+        session_generation: 0,
+        session_key_accounts: 0,
+        delegate_bumps: Vec::new(),
+        allow_permanent_delegate_mints: false,
+        has_policy: false,
+        kill_phrase_hash: [0; 32],
+        is_paused: false,
+        lockout_policy: None,
+        require_recipient_consent: false,
+        max_delegation_depth: DEFAULT_MAX_DELEGATION_DEPTH,
+        require_operator_signature: false,
+        retention_seconds: 0,
+        guardian: None,
+        budgeted_programs: Vec::new(),
+        version: USER_ACCOUNT_VERSION,
+    }
+}
+
+/// Unused session key as `create_session_key_v2` grants it at `now`, expiring at
+/// `expires_at_time`. Set fields such as `is_revoked`, `use_count` or `total_spent`
+/// afterwards to fabricate any usage or lifecycle state.
+pub fn session_key(
+    pubkey: &Pubkey,
+    permissions: SessionPermissions,
+    expires_at_time: i64,
+    now: i64,
+) -> SessionKey {
+    SessionKey {
+        pubkey: *pubkey,
+        created_at: now,
+        expires_at_time: Some(expires_at_time),
+        expires_at_slot: None,
+        permissions,
+        is_revoked: false,
+        label: [0; 32],
+        preset: None,
+        preset_version: 0,
+        terms_hash: [0; 32],
+        total_spent: 0,
+        spend_window_start: now,
+        spent_in_window: 0,
+        use_window_start: now,
+        uses_in_window: 0,
+        max_uses: 0,
+        use_count: 0,
+        valid_from: 0,
+        allowed_recipients: Vec::new(),
+        allowed_mints: Vec::new(),
+        allowed_programs: Vec::new(),
+        allowed_discriminators: Vec::new(),
+        prior_permissions: None,
+        widening_active_at: 0,
+        is_suspended: false,
+        revocation_reason: None,
+        failed_attempts: 0,
+        failure_window_start: now,
+        locked_until: 0,
+        delegated_by: None,
+        last_used_at: 0,
+        idle_timeout: 0,
+        max_expires_at: 0,
+        revoked_at: 0,
+        scope: Pubkey::default(),
+        domain_hash: [0; 32],
+    }
+}
+
+/// Permissions of a transfer-only key capped at `max_transfer_amount` (0 = unlimited)
+pub fn transfer_permissions(max_transfer_amount: u64) -> SessionPermissions {
+    SessionPermissions {
+        can_transfer: true,
+        max_transfer_amount,
+        ..Default::default()
+    }
+}
+
+/// Serialized user account, sized for its `session_key_capacity` like an initialized one.
+/// Inline keys are kept sorted by pubkey, as the program expects.
+pub fn user_account(account: &UserAccount) -> FixtureAccount {
+    let mut account = account.clone();
+    account.session_keys.sort_by_key(|key| key.pubkey);
+    let mut data = Vec::with_capacity(UserAccount::space(account.session_key_capacity as usize));
+    account.try_serialize(&mut data).unwrap();
+    data.resize(UserAccount::space(account.session_key_capacity as usize), 0);
+    FixtureAccount::rent_exempt(data, time::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock_at;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn user_account_serializes_like_the_program() {
+        let authority = Pubkey::new_unique();
+        let mut account = blank_user_account(&authority);
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        account.session_keys = keys
+            .iter()
+            .map(|key| session_key(key, transfer_permissions(0), NOW + 60, NOW))
+            .collect();
+
+        let fixture = user_account(&account);
+        assert_eq!(fixture.owner, time::ID);
+        assert_eq!(
+            fixture.data.len(),
+            UserAccount::space(DEFAULT_SESSION_KEY_CAPACITY)
+        );
+        assert_eq!(
+            fixture.lamports,
+            Rent::default().minimum_balance(fixture.data.len())
+        );
+
+        let stored = UserAccount::try_deserialize(&mut &fixture.data[..]).unwrap();
+        assert_eq!(stored.authority, authority);
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[UserAccount::SEED_PREFIX, authority.as_ref(), &[stored.bump]],
+                &time::ID
+            )
+            .unwrap(),
+            user_account_address(&authority).0
+        );
+        let mut sorted = keys;
+        sorted.sort();
+        let stored_keys: Vec<Pubkey> = stored.session_keys.iter().map(|k| k.pubkey).collect();
+        assert_eq!(stored_keys, sorted);
+    }
+
+    #[test]
+    fn session_key_is_usable_until_it_expires() {
+        let key = session_key(
+            &Pubkey::new_unique(),
+            transfer_permissions(100),
+            NOW + 60,
+            NOW,
+        );
+        assert!(key.is_valid(&clock_at(1_000, NOW)));
+        assert!(key.check_spend(100, &clock_at(1_000, NOW)).is_ok());
+        assert!(!key.is_valid(&clock_at(1_150, NOW + 60)));
+    }
+}
@@ -0,0 +1,44 @@
+use crate::FixtureAccount;
+use solana_program_test::ProgramTest;
+use solana_sdk::account::Account;
+
+/// `processor!` for an Anchor program's `entry`, e.g. `native_processor!(time::entry)`.
+/// Anchor ties its accounts to one lifetime, which `processor!` can't name, so this
+/// wraps `entry` in a function that can.
+#[macro_export]
+macro_rules! native_processor {
+    ($entry:path) => {
+        ::solana_program_test::processor!({
+            fn process_instruction(
+                program_id: &::anchor_lang::prelude::Pubkey,
+                accounts: &[::anchor_lang::prelude::AccountInfo],
+                data: &[u8],
+            ) -> ::anchor_lang::solana_program::entrypoint::ProgramResult {
+                // SAFETY: the accounts outlive this call, which is all `entry` relies on
+                let accounts: &[::anchor_lang::prelude::AccountInfo] =
+                    unsafe { ::std::mem::transmute(accounts) };
+                $entry(program_id, accounts, data)
+            }
+            process_instruction
+        })
+    };
+}
+
+/// `ProgramTest` with the time program built in and run natively, so tests need no
+/// compiled `.so`. The SPL Token and associated token programs are loaded by default;
+/// add programs that call `time` with `add_program` and `native_processor!`.
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("time", time::ID, native_processor!(time::entry))
+}
+
+impl From<FixtureAccount> for Account {
+    fn from(fixture: FixtureAccount) -> Self {
+        Self {
+            lamports: fixture.lamports,
+            data: fixture.data,
+            owner: fixture.owner,
+            executable: fixture.executable,
+            rent_epoch: 0,
+        }
+    }
+}
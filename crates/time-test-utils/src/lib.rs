@@ -0,0 +1,45 @@
+//! Test scaffolding for programs and clients built on the time program: raw account
+//! fixtures to load into any SVM test harness, and decoding of the events the program
+//! emits. Fixtures are plain data, so they work with LiteSVM's `set_account`,
+//! `solana-program-test`'s `add_account` or a hand-rolled harness alike. The
+//! `program-test` feature adds a `solana-program-test` harness running the program
+//! natively.
+
+pub mod clock;
+pub mod events;
+pub mod fixtures;
+#[cfg(feature = "program-test")]
+pub mod harness;
+pub mod tokens;
+
+pub use clock::*;
+pub use events::*;
+pub use fixtures::*;
+#[cfg(feature = "program-test")]
+pub use harness::*;
+pub use tokens::*;
+
+use anchor_lang::prelude::*;
+
+/// Raw account state to load into a test validator. Convert it into the harness's own
+/// account type, e.g. `solana_account::Account { lamports, data, owner, executable,
+/// rent_epoch: 0 }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixtureAccount {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+impl FixtureAccount {
+    /// Rent-exempt account holding `data`
+    pub fn rent_exempt(data: Vec<u8>, owner: Pubkey) -> Self {
+        Self {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner,
+            executable: false,
+        }
+    }
+}
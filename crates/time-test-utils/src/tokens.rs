@@ -0,0 +1,90 @@
+use crate::FixtureAccount;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token::state::{Account, AccountState, Mint};
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+
+/// Initialized SPL Token mint with `supply` already issued
+pub fn mint(authority: &Pubkey, decimals: u8, supply: u64) -> FixtureAccount {
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(
+        Mint {
+            mint_authority: COption::Some(*authority),
+            supply,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    FixtureAccount::rent_exempt(data, TOKEN_PROGRAM_ID)
+}
+
+/// SPL Token account of `mint` owned by `owner`, holding `amount`
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> FixtureAccount {
+    let mut data = vec![0; Account::LEN];
+    Account::pack(
+        Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    FixtureAccount::rent_exempt(data, TOKEN_PROGRAM_ID)
+}
+
+/// `owner`'s associated token account for `mint`, funded with `amount`
+pub fn funded_ata(mint: &Pubkey, owner: &Pubkey, amount: u64) -> (Pubkey, FixtureAccount) {
+    (
+        get_associated_token_address(owner, mint),
+        token_account(mint, owner, amount),
+    )
+}
+
+/// Token account that has approved `delegate` for `delegated_amount`, as
+/// `spl_approve_delegate` leaves it
+pub fn delegated_token_account(
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    delegate: &Pubkey,
+    delegated_amount: u64,
+) -> FixtureAccount {
+    let mut account = token_account(mint, owner, amount);
+    let mut state = Account::unpack(&account.data).unwrap();
+    state.delegate = COption::Some(*delegate);
+    state.delegated_amount = delegated_amount;
+    Account::pack(state, &mut account.data).unwrap();
+    account
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delegated_token_account_approves_the_delegate() {
+        let (mint, owner, delegate) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let fixture = delegated_token_account(&mint, &owner, 500, &delegate, 200);
+        assert_eq!(fixture.owner, TOKEN_PROGRAM_ID);
+
+        let state = Account::unpack(&fixture.data).unwrap();
+        assert_eq!((state.mint, state.owner, state.amount), (mint, owner, 500));
+        assert_eq!(state.delegate, COption::Some(delegate));
+        assert_eq!(state.delegated_amount, 200);
+    }
+}
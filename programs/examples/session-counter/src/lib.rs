@@ -0,0 +1,123 @@
+//! Example counter that a `time` session key can increment instead of the user's
+//! wallet. It never reads the `UserAccount` itself: each increment CPIs into
+//! `validate_session` and proceeds only when `time` reports the signer as an active
+//! session key with the counter's custom flag, either unscoped or scoped to this
+//! program.
+
+// Anchor 0.31's generated IDL/realloc code calls the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use time::program::Time;
+use time::state::{SessionPermissions, UserAccount};
+
+declare_id!("EdmDbsLixFFVCpr8rkGKHif78NdDtSi3NNPE25gu9Spy");
+
+/// Bit in `SessionPermissions::custom_flags` a session key needs to increment
+pub const INCREMENT_FLAG: u32 = 1 << 1;
+
+#[program]
+pub mod session_counter {
+    use super::*;
+
+    /// Create the user's counter; signed by the wallet that owns the user account
+    pub fn initialize_counter(ctx: Context<InitializeCounter>) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.user_account = ctx.accounts.user_account.key();
+        counter.count = 0;
+        counter.bump = ctx.bumps.counter;
+        Ok(())
+    }
+
+    /// Increment the counter, signed by a session key `time` validates
+    pub fn increment(ctx: Context<Increment>) -> Result<()> {
+        let valid = time::cpi_helpers::validate(
+            ctx.accounts.time_program.to_account_info(),
+            ctx.accounts.user_account.to_account_info(),
+            ctx.accounts
+                .session_key_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts.session_signer.key(),
+            SessionPermissions {
+                custom_flags: INCREMENT_FLAG,
+                ..Default::default()
+            },
+            Some(crate::ID),
+        )?;
+        require!(valid, CounterError::SessionRejected);
+
+        let counter = &mut ctx.accounts.counter;
+        counter.count = counter.count.saturating_add(1);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeCounter<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Counter::SPACE,
+        seeds = [Counter::SEED_PREFIX, user_account.key().as_ref()],
+        bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, authority.key().as_ref()],
+        bump = user_account.bump,
+        seeds::program = time::ID,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Increment<'info> {
+    pub session_signer: Signer<'info>,
+
+    /// CHECK: `time` checks the user account PDA in `validate_session`
+    pub user_account: UncheckedAccount<'info>,
+
+    /// CHECK: the signer's `SessionKeyAccount` when its key isn't stored inline;
+    /// `time` checks its address in `validate_session`
+    pub session_key_account: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [Counter::SEED_PREFIX, user_account.key().as_ref()],
+        bump = counter.bump,
+        has_one = user_account
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub time_program: Program<'info, Time>,
+}
+
+#[account]
+pub struct Counter {
+    /// The `time` user account whose session keys may increment
+    pub user_account: Pubkey,
+    pub count: u64,
+    pub bump: u8,
+}
+
+impl Counter {
+    pub const SEED_PREFIX: &'static [u8] = b"counter";
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        8 + // count
+        1; // bump
+}
+
+#[error_code]
+pub enum CounterError {
+    #[msg("time rejected the signer as a session key for this counter")]
+    SessionRejected,
+}
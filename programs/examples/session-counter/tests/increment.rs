@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use session_counter::{Counter, CounterError, INCREMENT_FLAG};
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use time::state::SessionPermissions;
+use time_test_utils::*;
+
+async fn increment(
+    context: &mut ProgramTestContext,
+    user: Pubkey,
+    counter: Pubkey,
+    signer: &Keypair,
+) -> std::result::Result<(), BanksClientError> {
+    let increment = Instruction {
+        program_id: session_counter::ID,
+        accounts: session_counter::accounts::Increment {
+            session_signer: signer.pubkey(),
+            user_account: user,
+            session_key_account: None,
+            counter,
+            time_program: time::ID,
+        }
+        .to_account_metas(None),
+        data: session_counter::instruction::Increment {}.data(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[increment],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, signer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// `validate_session` in the natively run time program accepts keys scoped to the
+/// counter or unscoped, and rejects ones scoped to another program
+#[tokio::test]
+async fn increments_need_a_key_scoped_to_the_counter() {
+    let mut test = program_test();
+    test.add_program(
+        "session_counter",
+        session_counter::ID,
+        native_processor!(session_counter::entry),
+    );
+    let mut context = test.start_with_context().await;
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let now = clock.unix_timestamp;
+    let authority = context.payer.pubkey();
+
+    let (scoped, unscoped, elsewhere) = (Keypair::new(), Keypair::new(), Keypair::new());
+    let permissions = SessionPermissions {
+        custom_flags: INCREMENT_FLAG,
+        ..Default::default()
+    };
+    let key = |signer: &Keypair, scope: Pubkey| {
+        let mut key = session_key(&signer.pubkey(), permissions, now + 3_600, now);
+        key.scope = scope;
+        key
+    };
+    let mut account = blank_user_account(&authority);
+    account.session_keys = vec![
+        key(&scoped, session_counter::ID),
+        key(&unscoped, Pubkey::default()),
+        key(&elsewhere, Pubkey::new_unique()),
+    ];
+    let (user, _) = user_account_address(&authority);
+    context.set_account(
+        &user,
+        &solana_sdk::account::Account::from(user_account(&account)).into(),
+    );
+
+    let counter =
+        Pubkey::find_program_address(&[Counter::SEED_PREFIX, user.as_ref()], &session_counter::ID)
+            .0;
+    let initialize = Instruction {
+        program_id: session_counter::ID,
+        accounts: session_counter::accounts::InitializeCounter {
+            counter,
+            user_account: user,
+            authority,
+            system_program: anchor_lang::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: session_counter::instruction::InitializeCounter {}.data(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[initialize],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    for signer in [&scoped, &unscoped] {
+        increment(&mut context, user, counter, signer)
+            .await
+            .unwrap();
+    }
+    let error = increment(&mut context, user, counter, &elsewhere)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code)
+        )) if code == u32::from(CounterError::SessionRejected)
+    ));
+
+    let counter = context
+        .banks_client
+        .get_account(counter)
+        .await
+        .unwrap()
+        .unwrap();
+    let counter = Counter::try_deserialize(&mut &counter.data[..]).unwrap();
+    assert_eq!(counter.count, 2);
+}
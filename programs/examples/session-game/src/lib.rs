@@ -0,0 +1,130 @@
+//! Example game that lets players move with a `time` session key instead of their
+//! wallet. Each move CPIs into `validate_session` and is recorded only when `time`
+//! reports the signer as an active session key with the game's custom flag, either
+//! unscoped or scoped to this program.
+
+// Anchor 0.31's generated IDL/realloc code calls the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use time::program::Time;
+use time::state::{SessionPermissions, UserAccount};
+
+declare_id!("66nGZcBAfShgG9poDZYn4hDi6bd7AyrDATbJaKjeoCLw");
+
+/// Bit in `SessionPermissions::custom_flags` a session key needs to play
+pub const PLAY_FLAG: u32 = 1 << 0;
+
+#[program]
+pub mod session_game {
+    use super::*;
+
+    /// Create the player's game state; signed by the wallet that owns the user account
+    pub fn initialize_player(ctx: Context<InitializePlayer>) -> Result<()> {
+        let player = &mut ctx.accounts.player;
+        player.user_account = ctx.accounts.user_account.key();
+        player.score = 0;
+        player.moves = 0;
+        player.bump = ctx.bumps.player;
+        Ok(())
+    }
+
+    /// Record a move, signed by a session key `time` validates
+    pub fn play_move(ctx: Context<PlayMove>, points: u32) -> Result<()> {
+        let valid = time::cpi_helpers::validate(
+            ctx.accounts.time_program.to_account_info(),
+            ctx.accounts.user_account.to_account_info(),
+            ctx.accounts
+                .session_key_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts.session_signer.key(),
+            SessionPermissions {
+                custom_flags: PLAY_FLAG,
+                ..Default::default()
+            },
+            Some(crate::ID),
+        )?;
+        require!(valid, GameError::SessionRejected);
+
+        let player = &mut ctx.accounts.player;
+        player.score = player.score.saturating_add(points as u64);
+        player.moves = player.moves.saturating_add(1);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializePlayer<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Player::SPACE,
+        seeds = [Player::SEED_PREFIX, user_account.key().as_ref()],
+        bump
+    )]
+    pub player: Account<'info, Player>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, authority.key().as_ref()],
+        bump = user_account.bump,
+        seeds::program = time::ID,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayMove<'info> {
+    pub session_signer: Signer<'info>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        seeds::program = time::ID
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: the signer's `SessionKeyAccount` when its key isn't stored inline;
+    /// `time` checks its address in `validate_session`
+    pub session_key_account: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [Player::SEED_PREFIX, user_account.key().as_ref()],
+        bump = player.bump,
+        has_one = user_account
+    )]
+    pub player: Account<'info, Player>,
+
+    pub time_program: Program<'info, Time>,
+}
+
+#[account]
+pub struct Player {
+    /// The `time` user account whose session keys may play
+    pub user_account: Pubkey,
+    pub score: u64,
+    pub moves: u64,
+    pub bump: u8,
+}
+
+impl Player {
+    pub const SEED_PREFIX: &'static [u8] = b"player";
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        8 + // score
+        8 + // moves
+        1; // bump
+}
+
+#[error_code]
+pub enum GameError {
+    #[msg("time rejected the signer as a session key for this game")]
+    SessionRejected,
+}
@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use session_game::{GameError, Player, PLAY_FLAG};
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use time::state::SessionPermissions;
+use time_test_utils::*;
+
+async fn play_move(
+    context: &mut ProgramTestContext,
+    user: Pubkey,
+    player: Pubkey,
+    signer: &Keypair,
+    points: u32,
+) -> std::result::Result<(), BanksClientError> {
+    let play = Instruction {
+        program_id: session_game::ID,
+        accounts: session_game::accounts::PlayMove {
+            session_signer: signer.pubkey(),
+            user_account: user,
+            session_key_account: None,
+            player,
+            time_program: time::ID,
+        }
+        .to_account_metas(None),
+        data: session_game::instruction::PlayMove { points }.data(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[play],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, signer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Moves go through `validate_session` in the natively run time program: only a live
+/// key with `PLAY_FLAG` is accepted, and `time`'s verdict is what the game enforces
+#[tokio::test]
+async fn moves_need_a_session_key_with_the_play_flag() {
+    let mut test = program_test();
+    test.add_program(
+        "session_game",
+        session_game::ID,
+        native_processor!(session_game::entry),
+    );
+    let mut context = test.start_with_context().await;
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let now = clock.unix_timestamp;
+    let authority = context.payer.pubkey();
+
+    let (gamer, transfer_only, stranger) = (Keypair::new(), Keypair::new(), Keypair::new());
+    let play = SessionPermissions {
+        custom_flags: PLAY_FLAG,
+        ..Default::default()
+    };
+    let mut account = blank_user_account(&authority);
+    account.session_keys = vec![
+        session_key(&gamer.pubkey(), play, now + 3_600, now),
+        session_key(
+            &transfer_only.pubkey(),
+            transfer_permissions(0),
+            now + 3_600,
+            now,
+        ),
+    ];
+    let (user, _) = user_account_address(&authority);
+    context.set_account(
+        &user,
+        &solana_sdk::account::Account::from(user_account(&account)).into(),
+    );
+
+    let player =
+        Pubkey::find_program_address(&[Player::SEED_PREFIX, user.as_ref()], &session_game::ID).0;
+    let initialize = Instruction {
+        program_id: session_game::ID,
+        accounts: session_game::accounts::InitializePlayer {
+            player,
+            user_account: user,
+            authority,
+            system_program: anchor_lang::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: session_game::instruction::InitializePlayer {}.data(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[initialize],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    play_move(&mut context, user, player, &gamer, 10)
+        .await
+        .unwrap();
+    for signer in [&transfer_only, &stranger] {
+        let error = play_move(&mut context, user, player, signer, 10)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            BanksClientError::TransactionError(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(code)
+            )) if code == u32::from(GameError::SessionRejected)
+        ));
+    }
+
+    let player = context
+        .banks_client
+        .get_account(player)
+        .await
+        .unwrap()
+        .unwrap();
+    let player = Player::try_deserialize(&mut &player.data[..]).unwrap();
+    assert_eq!((player.score, player.moves), (10, 1));
+}
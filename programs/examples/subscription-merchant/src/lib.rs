@@ -0,0 +1,233 @@
+//! Example merchant that bills a subscription through `time`'s delegate rails. The
+//! subscriber grants a session key to the subscription's session PDA, limited to the
+//! merchant's token account. Anyone can then crank `collect` once a period is due;
+//! the program signs `spl_delegated_transfer` as that session key, naming this program
+//! as its scope, so the key can be scoped to the merchant.
+
+// Anchor 0.31's generated IDL/realloc code calls the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use time::program::Time;
+use time::state::UserAccount;
+
+declare_id!("4uoe6hBaPA9RAwQ4dzT6FWxB4gECkKvfNxxJDqPYRzzt");
+
+#[program]
+pub mod subscription_merchant {
+    use super::*;
+
+    /// Start a subscription, first billable immediately. The subscriber still has to
+    /// create a session key for the subscription's session PDA.
+    pub fn create_subscription(
+        ctx: Context<CreateSubscription>,
+        amount: u64,
+        period_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            amount > 0 && period_seconds > 0,
+            MerchantError::InvalidTerms
+        );
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.user_account = ctx.accounts.user_account.key();
+        subscription.merchant_token = ctx.accounts.merchant_token.key();
+        subscription.mint = ctx.accounts.mint.key();
+        subscription.amount = amount;
+        subscription.period_seconds = period_seconds;
+        subscription.next_due = Clock::get()?.unix_timestamp;
+        subscription.bump = ctx.bumps.subscription;
+        subscription.session_bump = ctx.bumps.session;
+        Ok(())
+    }
+
+    /// Charge one period once it is due; callable by anyone
+    pub fn collect(ctx: Context<Collect>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &ctx.accounts.subscription;
+        require!(now >= subscription.next_due, MerchantError::NotDue);
+
+        let subscription_key = subscription.key();
+        let seeds: &[&[u8]] = &[
+            Subscription::SESSION_SEED,
+            subscription_key.as_ref(),
+            &[subscription.session_bump],
+        ];
+        time::cpi_helpers::spl_delegated_transfer(
+            time::cpi_helpers::DelegatedTransfer {
+                time_program: ctx.accounts.time_program.to_account_info(),
+                session_signer: ctx.accounts.session.to_account_info(),
+                user_account: ctx.accounts.user_account.to_account_info(),
+                program_config: ctx.accounts.program_config.to_account_info(),
+                from_token: ctx.accounts.from_token.to_account_info(),
+                to_token: ctx.accounts.merchant_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                delegate_authority: ctx.accounts.delegate_authority.to_account_info(),
+                fee_token: ctx
+                    .accounts
+                    .fee_token
+                    .as_ref()
+                    .map(|fee_token| fee_token.to_account_info()),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            subscription.amount,
+            Some(crate::ID),
+            &[seeds],
+        )?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.next_due = subscription
+            .next_due
+            .saturating_add(subscription.period_seconds);
+        Ok(())
+    }
+
+    /// End the subscription and reclaim its rent. Revoking the session key in `time`
+    /// is still up to the subscriber.
+    pub fn cancel_subscription(_ctx: Context<CancelSubscription>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateSubscription<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Subscription::SPACE,
+        seeds = [Subscription::SEED_PREFIX, user_account.key().as_ref(), merchant_token.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// CHECK: signer-only PDA, the session key the subscriber grants
+    #[account(seeds = [Subscription::SESSION_SEED, subscription.key().as_ref()], bump)]
+    pub session: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, authority.key().as_ref()],
+        bump = user_account.bump,
+        seeds::program = time::ID,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(token::mint = mint)]
+    pub merchant_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Collect<'info> {
+    #[account(
+        mut,
+        seeds = [Subscription::SEED_PREFIX, user_account.key().as_ref(), merchant_token.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user_account,
+        has_one = merchant_token,
+        has_one = mint
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// CHECK: signer-only PDA; mutable because `time` may reimburse its fees
+    #[account(
+        mut,
+        seeds = [Subscription::SESSION_SEED, subscription.key().as_ref()],
+        bump = subscription.session_bump
+    )]
+    pub session: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: `time` checks this is its program config PDA
+    pub program_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub from_token: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub merchant_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: `time` checks this is the subscriber's delegate PDA for `mint`
+    pub delegate_authority: UncheckedAccount<'info>,
+
+    /// `time`'s treasury token account for `mint`, needed while a protocol fee is set
+    #[account(mut)]
+    pub fee_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub time_program: Program<'info, Time>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [Subscription::SEED_PREFIX, user_account.key().as_ref(), subscription.merchant_token.as_ref()],
+        bump = subscription.bump,
+        has_one = user_account
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, authority.key().as_ref()],
+        bump = user_account.bump,
+        seeds::program = time::ID,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct Subscription {
+    /// The subscriber's `time` user account
+    pub user_account: Pubkey,
+    /// Token account payments go to
+    pub merchant_token: Pubkey,
+    pub mint: Pubkey,
+    /// Charged once per period
+    pub amount: u64,
+    pub period_seconds: i64,
+    /// Unix timestamp the next period can be collected from
+    pub next_due: i64,
+    pub bump: u8,
+    pub session_bump: u8,
+}
+
+impl Subscription {
+    pub const SEED_PREFIX: &'static [u8] = b"subscription";
+    pub const SESSION_SEED: &'static [u8] = b"session";
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        32 + // merchant_token
+        32 + // mint
+        8 + // amount
+        8 + // period_seconds
+        8 + // next_due
+        1 + // bump
+        1; // session_bump
+}
+
+#[error_code]
+pub enum MerchantError {
+    #[msg("Amount and period must be positive")]
+    InvalidTerms,
+
+    #[msg("The current period has already been collected")]
+    NotDue,
+}
@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token::state::Account as TokenState;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use subscription_merchant::{MerchantError, Subscription};
+use time::state::ProgramConfig;
+use time_test_utils::*;
+
+const AMOUNT: u64 = 5_000_000;
+const PERIOD: i64 = 30 * 86_400;
+
+struct Billing {
+    subscription: Pubkey,
+    session: Pubkey,
+    user: Pubkey,
+    from: Pubkey,
+    merchant_token: Pubkey,
+    mint: Pubkey,
+    delegate: Pubkey,
+}
+
+async fn collect(
+    context: &mut ProgramTestContext,
+    billing: &Billing,
+) -> std::result::Result<(), BanksClientError> {
+    let collect = Instruction {
+        program_id: subscription_merchant::ID,
+        accounts: subscription_merchant::accounts::Collect {
+            subscription: billing.subscription,
+            session: billing.session,
+            user_account: billing.user,
+            program_config: Pubkey::find_program_address(&[ProgramConfig::SEED_PREFIX], &time::ID)
+                .0,
+            from_token: billing.from,
+            merchant_token: billing.merchant_token,
+            mint: billing.mint,
+            delegate_authority: billing.delegate,
+            fee_token: None,
+            time_program: time::ID,
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        .to_account_metas(None),
+        data: subscription_merchant::instruction::Collect {}.data(),
+    };
+    // Cranks repeat byte for byte, so each gets a fresh blockhash
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[collect],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// `collect` signs `spl_delegated_transfer` as the subscription's session PDA, so the
+/// natively run time program moves the subscriber's tokens through its delegate once
+/// per period
+#[tokio::test]
+async fn collect_charges_once_per_period_through_the_delegate() {
+    let mut test = program_test();
+    test.add_program(
+        "subscription_merchant",
+        subscription_merchant::ID,
+        native_processor!(subscription_merchant::entry),
+    );
+    let mut context = test.start_with_context().await;
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let now = clock.unix_timestamp;
+    let authority = context.payer.pubkey();
+
+    let (user, _) = user_account_address(&authority);
+    let mint = Pubkey::new_unique();
+    let (delegate, _) = delegate_address(&user, &mint);
+    let from = Pubkey::new_unique();
+    let (merchant_token, merchant_account) = funded_ata(&mint, &Pubkey::new_unique(), 0);
+    let subscription = Pubkey::find_program_address(
+        &[
+            Subscription::SEED_PREFIX,
+            user.as_ref(),
+            merchant_token.as_ref(),
+        ],
+        &subscription_merchant::ID,
+    )
+    .0;
+    let session = Pubkey::find_program_address(
+        &[Subscription::SESSION_SEED, subscription.as_ref()],
+        &subscription_merchant::ID,
+    )
+    .0;
+
+    // The subscriber's grant: a year of billing to the session PDA, scoped to the
+    // merchant program
+    let mut key = session_key(
+        &session,
+        transfer_permissions(12 * AMOUNT),
+        now + 365 * 86_400,
+        now,
+    );
+    key.scope = subscription_merchant::ID;
+    let mut account = blank_user_account(&authority);
+    account.session_keys = vec![key];
+    for (address, fixture) in [
+        (user, user_account(&account)),
+        (mint, tokens::mint(&authority, 6, 100_000_000)),
+        (
+            from,
+            delegated_token_account(&mint, &authority, 100_000_000, &delegate, 100_000_000),
+        ),
+        (merchant_token, merchant_account),
+    ] {
+        context.set_account(
+            &address,
+            &solana_sdk::account::Account::from(fixture).into(),
+        );
+    }
+
+    let create = Instruction {
+        program_id: subscription_merchant::ID,
+        accounts: subscription_merchant::accounts::CreateSubscription {
+            subscription,
+            session,
+            user_account: user,
+            merchant_token,
+            mint,
+            authority,
+            system_program: anchor_lang::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: subscription_merchant::instruction::CreateSubscription {
+            amount: AMOUNT,
+            period_seconds: PERIOD,
+        }
+        .data(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[create],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let billing = Billing {
+        subscription,
+        session,
+        user,
+        from,
+        merchant_token,
+        mint,
+        delegate,
+    };
+    collect(&mut context, &billing).await.unwrap();
+    let error = collect(&mut context, &billing).await.unwrap_err();
+    assert!(matches!(
+        error,
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code)
+        )) if code == u32::from(MerchantError::NotDue)
+    ));
+
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.set_sysvar(&warp(&clock, PERIOD));
+    collect(&mut context, &billing).await.unwrap();
+
+    let received = context
+        .banks_client
+        .get_account(merchant_token)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        TokenState::unpack(&received.data).unwrap().amount,
+        2 * AMOUNT
+    );
+    let subscription = context
+        .banks_client
+        .get_account(subscription)
+        .await
+        .unwrap()
+        .unwrap();
+    let subscription = Subscription::try_deserialize(&mut &subscription.data[..]).unwrap();
+    assert_eq!(subscription.next_due, now + 2 * PERIOD);
+}
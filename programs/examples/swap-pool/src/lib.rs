@@ -0,0 +1,109 @@
+//! Example fixed-rate swap pool for the swap steps of `time` payout pipelines. `swap`
+//! takes `amount_in` of one mint from the caller's token account, which the caller may
+//! sign for as its delegate, and pays the same amount of another mint out of the pool's
+//! vault. It pays into whatever token account it is given: the pipeline step's pinned
+//! `destination` and `min_amount_out` are what keep a session key from redirecting the
+//! output.
+
+// Anchor 0.31's generated IDL/realloc code calls the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+declare_id!("84TfAStm1fv86dB8yGy4Yixqqiz33vhup5C815cyq2Kd");
+
+/// Seed of the PDA owning a pair's vaults, followed by the input and output mints
+pub const POOL_SEED: &[u8] = b"pool";
+
+#[program]
+pub mod swap_pool {
+    use super::*;
+
+    /// Swap `amount_in` of `mint_in` from `source` for as much `mint_out` into
+    /// `destination`; signed by the owner or delegate of `source`
+    pub fn swap(ctx: Context<Swap>, amount_in: u64) -> Result<()> {
+        require!(amount_in > 0, SwapError::ZeroAmount);
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.source.to_account_info(),
+                    mint: ctx.accounts.mint_in.to_account_info(),
+                    to: ctx.accounts.vault_in.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount_in,
+            ctx.accounts.mint_in.decimals,
+        )?;
+
+        let mint_in = ctx.accounts.mint_in.key();
+        let mint_out = ctx.accounts.mint_out.key();
+        let seeds: &[&[u8]] = &[
+            POOL_SEED,
+            mint_in.as_ref(),
+            mint_out.as_ref(),
+            &[ctx.bumps.pool],
+        ];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_out.to_account_info(),
+                    mint: ctx.accounts.mint_out.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount_in,
+            ctx.accounts.mint_out.decimals,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    /// Owner or delegate of `source`
+    pub user: Signer<'info>,
+
+    #[account(mut, token::mint = mint_in, token::token_program = token_program)]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint_out, token::token_program = token_program)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: signer-only PDA owning the pair's vaults
+    #[account(seeds = [POOL_SEED, mint_in.key().as_ref(), mint_out.key().as_ref()], bump)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = mint_in,
+        token::authority = pool,
+        token::token_program = token_program
+    )]
+    pub vault_in: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_out,
+        token::authority = pool,
+        token::token_program = token_program
+    )]
+    pub vault_out: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint_in: InterfaceAccount<'info, Mint>,
+
+    pub mint_out: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[error_code]
+pub enum SwapError {
+    #[msg("Swap amount must be positive")]
+    ZeroAmount,
+}
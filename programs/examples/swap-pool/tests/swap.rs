@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token::state::Account as TokenState;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use swap_pool::{SwapError, POOL_SEED};
+use time_test_utils::*;
+
+async fn swap(
+    context: &mut ProgramTestContext,
+    accounts: swap_pool::accounts::Swap,
+    user: &Keypair,
+    amount_in: u64,
+) -> std::result::Result<(), BanksClientError> {
+    let swap = Instruction {
+        program_id: swap_pool::ID,
+        accounts: accounts.to_account_metas(None),
+        data: swap_pool::instruction::Swap { amount_in }.data(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[swap],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+async fn balance(context: &mut ProgramTestContext, token: Pubkey) -> u64 {
+    let account = context
+        .banks_client
+        .get_account(token)
+        .await
+        .unwrap()
+        .unwrap();
+    TokenState::unpack(&account.data).unwrap().amount
+}
+
+/// A delegate of `source`, as a payout pipeline's delegate PDA is, swaps one for one
+/// out of the pool's vault into the destination it names
+#[tokio::test]
+async fn delegate_swaps_one_for_one_from_the_vault() {
+    let mut context = ProgramTest::new(
+        "swap_pool",
+        swap_pool::ID,
+        native_processor!(swap_pool::entry),
+    )
+    .start_with_context()
+    .await;
+    let authority = context.payer.pubkey();
+
+    let (owner, delegate) = (Pubkey::new_unique(), Keypair::new());
+    let (mint_in, mint_out) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let pool = Pubkey::find_program_address(
+        &[POOL_SEED, mint_in.as_ref(), mint_out.as_ref()],
+        &swap_pool::ID,
+    )
+    .0;
+    let (source, vault_in, vault_out) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let (destination, destination_account) = funded_ata(&mint_out, &owner, 0);
+    for (address, fixture) in [
+        (mint_in, tokens::mint(&authority, 6, 1_000_000)),
+        (mint_out, tokens::mint(&authority, 6, 1_000_000)),
+        (
+            source,
+            delegated_token_account(&mint_in, &owner, 1_000, &delegate.pubkey(), 600),
+        ),
+        (destination, destination_account),
+        (vault_in, tokens::token_account(&mint_in, &pool, 0)),
+        (vault_out, tokens::token_account(&mint_out, &pool, 10_000)),
+    ] {
+        context.set_account(
+            &address,
+            &solana_sdk::account::Account::from(fixture).into(),
+        );
+    }
+    let accounts = || swap_pool::accounts::Swap {
+        user: delegate.pubkey(),
+        source,
+        destination,
+        pool,
+        vault_in,
+        vault_out,
+        mint_in,
+        mint_out,
+        token_program: TOKEN_PROGRAM_ID,
+    };
+
+    swap(&mut context, accounts(), &delegate, 400)
+        .await
+        .unwrap();
+    let error = swap(&mut context, accounts(), &delegate, 0)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code)
+        )) if code == u32::from(SwapError::ZeroAmount)
+    ));
+
+    assert_eq!(balance(&mut context, source).await, 600);
+    assert_eq!(balance(&mut context, vault_in).await, 400);
+    assert_eq!(balance(&mut context, vault_out).await, 9_600);
+    assert_eq!(balance(&mut context, destination).await, 400);
+}
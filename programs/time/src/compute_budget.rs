@@ -0,0 +1,259 @@
+//! Compute unit budget hints per instruction.
+//!
+//! Each value is an upper bound on the compute units the instruction consumes on its
+//! most expensive path (full session key list, Token-2022 CPI), rounded up with headroom.
+//! `tests/compute_budget.spec.ts` simulates the instructions and fails if a measured
+//! value exceeds its hint, so bump the constant whenever a handler gets heavier.
+//! Clients pass these to `ComputeBudgetInstruction::set_compute_unit_limit` instead of
+//! relying on the 200k default or requesting the 1.4M maximum.
+
+pub const INITIALIZE_USER_ACCOUNT: u32 = 15_000;
+pub const INITIALIZE_USER_ACCOUNT_WITH_CONFIG: u32 = 20_000;
+pub const INITIALIZE_WITH_SESSION_KEY: u32 = INITIALIZE_USER_ACCOUNT + CREATE_SESSION_KEY_V2;
+pub const CREATE_SESSION_KEY: u32 = 20_000;
+pub const CREATE_SESSION_KEY_V2: u32 = 20_000;
+pub const CREATE_SESSION_KEY_INIT_IF_NEEDED: u32 = INITIALIZE_USER_ACCOUNT + CREATE_SESSION_KEY_V2;
+pub const CREATE_SESSION_KEY_FROM_PRESET: u32 = 25_000;
+pub const UPDATE_SESSION_KEY: u32 = 12_000;
+pub const UPDATE_SESSION_KEY_LABEL: u32 = 10_000;
+pub const VERIFY_SESSION_DOMAIN: u32 = 8_000;
+pub const VALIDATE_SESSION: u32 = 10_000;
+pub const REVOKE_SESSION_KEY: u32 = 10_000;
+pub const SUSPEND_SESSION_KEY: u32 = 10_000;
+pub const RESUME_SESSION_KEY: u32 = 10_000;
+pub const SELF_RESTRICT: u32 = 12_000;
+pub const DELEGATE_SESSION_KEY: u32 = 30_000;
+pub const SET_MAX_DELEGATION_DEPTH: u32 = 8_000;
+pub const REVOKE_ALL_SESSION_KEYS: u32 = 12_000;
+pub const REVOKE_SESSIONS_EXPIRING_AFTER: u32 = 30_000;
+pub const RESIZE_USER_ACCOUNT: u32 = 15_000;
+/// Worst case: a full account is deserialized, grown and written back
+pub const MIGRATE_USER_ACCOUNT: u32 = 60_000;
+pub const CLOSE_USER_ACCOUNT: u32 = 20_000;
+pub const ARCHIVE_USER_ACCOUNT: u32 = 30_000;
+pub const RESTORE_USER_ACCOUNT: u32 = 30_000;
+pub const CREATE_SESSION_KEY_ACCOUNT: u32 = 25_000;
+pub const REVOKE_SESSION_KEY_ACCOUNT: u32 = 10_000;
+pub const CLOSE_SESSION_KEY_ACCOUNT: u32 = 10_000;
+pub const TRANSFER_SESSION_TO_NEW_KEY: u32 = 12_000;
+pub const ROTATE_SESSION_KEY: u32 = 20_000;
+pub const CLEANUP_SESSION_KEYS: u32 = 15_000;
+pub const CRANK_CLEANUP: u32 = 20_000;
+pub const SET_CLEANUP_RETENTION: u32 = 8_000;
+pub const UPDATE_ALLOWED_MINTS: u32 = 12_000;
+pub const INITIALIZE_SPEND_BUDGET: u32 = 20_000;
+pub const UPDATE_SPEND_BUDGET: u32 = 10_000;
+pub const SET_PROGRAM_BUDGET: u32 = 12_000;
+pub const CLOSE_SPEND_BUDGET: u32 = 12_000;
+pub const INITIALIZE_POLICY: u32 = 20_000;
+pub const UPDATE_POLICY: u32 = 12_000;
+pub const CLOSE_POLICY: u32 = 10_000;
+pub const SET_KILL_PHRASE: u32 = 8_000;
+pub const TRIGGER_KILL_PHRASE: u32 = 20_000;
+pub const SET_GUARDIAN: u32 = 8_000;
+pub const GUARDIAN_REVOKE_ALL: u32 = 20_000;
+pub const SET_ACCOUNT_PAUSED: u32 = 8_000;
+pub const SET_RECIPIENT_CONSENT_REQUIRED: u32 = 8_000;
+pub const ACCEPT_PAYMENTS: u32 = 15_000;
+pub const WITHDRAW_PAYMENT_CONSENT: u32 = 8_000;
+pub const ENABLE_ACTIVITY_DIGEST: u32 = 20_000;
+pub const SET_DIGEST_INTERVAL: u32 = 25_000;
+pub const INITIALIZE_ACCOUNT_STATS: u32 = 25_000;
+pub const SCHEDULE_SESSION_KEY: u32 = 20_000;
+pub const CANCEL_SCHEDULED_SESSION: u32 = 10_000;
+pub const ACTIVATE_SCHEDULED_SESSION: u32 = 25_000;
+pub const CREATE_PIPELINE: u32 = 20_000;
+pub const CLOSE_PIPELINE: u32 = 8_000;
+/// Pipeline with transfer steps only; add the swap programs' own usage for swap steps
+pub const EXECUTE_PIPELINE: u32 = 80_000;
+/// Excludes the target program's own usage, which the caller must add
+pub const EXECUTE_CUSTOM: u32 = 25_000;
+pub const SPL_APPROVE_DELEGATE: u32 = 15_000;
+pub const APPROVE_AND_CREATE_SESSION: u32 = SPL_APPROVE_DELEGATE + CREATE_SESSION_KEY_V2;
+pub const SPL_REVOKE_DELEGATE: u32 = 12_000;
+/// Transfer with no optional checks configured
+pub const SPL_DELEGATED_TRANSFER: u32 = 45_000;
+/// `SPL_DELEGATED_TRANSFER` plus creating the recipient's token account and the call back
+/// into the program; the optional check extras below apply the same way
+pub const SPL_DELEGATED_TRANSFER_WITH_ATA: u32 = SPL_DELEGATED_TRANSFER + 35_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when the recipient is screened against the blocklist
+pub const BLOCKLIST_CHECK: u32 = 10_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when an attestation gate is configured
+pub const ATTESTATION_CHECK: u32 = 5_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when a bond is required; includes the reputation update
+pub const BOND_CHECK: u32 = 8_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when the mint has an account-level spend budget
+pub const SPEND_BUDGET_CHECK: u32 = 5_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when digests are on; worst case completes a batch
+pub const ACTIVITY_DIGEST: u32 = 25_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when stats are on: history append plus root refresh
+pub const ACCOUNT_STATS: u32 = 15_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when a recent slot proof is required (SlotHashes scan)
+pub const SLOT_PROOF_CHECK: u32 = 10_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when a savings rule covers the mint (second transfer CPI)
+pub const SAVINGS_TRANSFER: u32 = 20_000;
+/// Added to `SPL_DELEGATED_TRANSFER` while a protocol fee is set (treasury derivation plus
+/// fee transfer CPI)
+pub const PROTOCOL_FEE_TRANSFER: u32 = 25_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when a gas tank reimburses the signer
+pub const GAS_REIMBURSEMENT: u32 = 5_000;
+/// Added to `SPL_DELEGATED_TRANSFER` for the guard program CPI, excluding the guard's own usage
+pub const GUARD_CPI: u32 = 10_000;
+/// Added to `SPL_DELEGATED_TRANSFER` when operator signatures are required (Instructions scan)
+pub const OPERATOR_SIGNATURE_CHECK: u32 = 8_000;
+pub const SET_GUARD_PROGRAM: u32 = 8_000;
+pub const SET_LOCKOUT_POLICY: u32 = 8_000;
+pub const RECORD_REFUSED_SPEND: u32 = 10_000;
+pub const SET_MAX_SLOT_AGE: u32 = 8_000;
+pub const SET_PERMANENT_DELEGATE_OPT_IN: u32 = 8_000;
+pub const SET_SAVINGS_RULE: u32 = 8_000;
+pub const SET_SPEND_CATEGORIES: u32 = 12_000;
+pub const SET_WIDENING_DELAY: u32 = 8_000;
+pub const DEPOSIT_SOL: u32 = 10_000;
+pub const WITHDRAW_SOL: u32 = 8_000;
+pub const SESSION_WITHDRAW_SOL: u32 = 15_000;
+pub const SESSION_TRANSFER_SOL: u32 = 15_000;
+pub const INITIALIZE_GAS_TANK: u32 = 15_000;
+pub const SET_GAS_TANK_FEE: u32 = 8_000;
+pub const TOP_UP_GAS_TANK: u32 = 10_000;
+pub const SET_BLACKOUT_WINDOWS: u32 = 10_000;
+pub const SET_ATTESTATION_GATE: u32 = 8_000;
+pub const SET_BLOCKLIST_ENFORCEMENT: u32 = 8_000;
+pub const INITIALIZE_BLOCKLIST: u32 = 20_000;
+pub const UPDATE_BLOCKLIST: u32 = 60_000;
+pub const INITIALIZE_PROGRAM_CONFIG: u32 = 20_000;
+pub const SET_PAUSED: u32 = 8_000;
+pub const UPDATE_CONFIG: u32 = 8_000;
+pub const UPDATE_MINT_DENYLIST: u32 = 30_000;
+pub const SET_FEE: u32 = 8_000;
+pub const WITHDRAW_FEES: u32 = 20_000;
+pub const SET_BOND_REQUIREMENT: u32 = 8_000;
+pub const SET_OPERATOR_SIGNATURE_REQUIRED: u32 = 8_000;
+pub const POST_BOND: u32 = 25_000;
+pub const POST_BOND_SPL: u32 = 45_000;
+pub const REQUEST_BOND_RELEASE: u32 = 12_000;
+pub const SLASH_BOND: u32 = 35_000;
+pub const WITHDRAW_BOND: u32 = 40_000;
+pub const REPORT_VIOLATION: u32 = 20_000;
+pub const RESOLVE_DISPUTE: u32 = 15_000;
+pub const INITIALIZE_OPERATOR_REPUTATION: u32 = 15_000;
+pub const REGISTER_SERVICE: u32 = 20_000;
+pub const UPDATE_SERVICE: u32 = 12_000;
+pub const CLOSE_SERVICE: u32 = 8_000;
+pub const SET_SERVICE_VERIFIED: u32 = 10_000;
+pub const PUBLISH_PRESET: u32 = 20_000;
+pub const UPDATE_PRESET: u32 = 12_000;
+pub const PUBLISH_BUILD_HASH: u32 = 20_000;
+
+/// Look up the hint for an instruction by its snake_case name
+pub fn for_instruction(name: &str) -> Option<u32> {
+    Some(match name {
+        "initialize_user_account" => INITIALIZE_USER_ACCOUNT,
+        "initialize_user_account_with_config" => INITIALIZE_USER_ACCOUNT_WITH_CONFIG,
+        "initialize_with_session_key" => INITIALIZE_WITH_SESSION_KEY,
+        "create_session_key" => CREATE_SESSION_KEY,
+        "create_session_key_v2" => CREATE_SESSION_KEY_V2,
+        "create_session_key_init_if_needed" => CREATE_SESSION_KEY_INIT_IF_NEEDED,
+        "create_session_key_from_preset" => CREATE_SESSION_KEY_FROM_PRESET,
+        "update_session_key" => UPDATE_SESSION_KEY,
+        "update_session_key_label" => UPDATE_SESSION_KEY_LABEL,
+        "verify_session_domain" => VERIFY_SESSION_DOMAIN,
+        "validate_session" => VALIDATE_SESSION,
+        "revoke_session_key" => REVOKE_SESSION_KEY,
+        "suspend_session_key" => SUSPEND_SESSION_KEY,
+        "resume_session_key" => RESUME_SESSION_KEY,
+        "self_restrict" => SELF_RESTRICT,
+        "delegate_session_key" => DELEGATE_SESSION_KEY,
+        "set_max_delegation_depth" => SET_MAX_DELEGATION_DEPTH,
+        "revoke_all_session_keys" => REVOKE_ALL_SESSION_KEYS,
+        "revoke_sessions_expiring_after" => REVOKE_SESSIONS_EXPIRING_AFTER,
+        "resize_user_account" => RESIZE_USER_ACCOUNT,
+        "migrate_user_account" => MIGRATE_USER_ACCOUNT,
+        "close_user_account" => CLOSE_USER_ACCOUNT,
+        "archive_user_account" => ARCHIVE_USER_ACCOUNT,
+        "restore_user_account" => RESTORE_USER_ACCOUNT,
+        "create_session_key_account" => CREATE_SESSION_KEY_ACCOUNT,
+        "revoke_session_key_account" => REVOKE_SESSION_KEY_ACCOUNT,
+        "close_session_key_account" => CLOSE_SESSION_KEY_ACCOUNT,
+        "transfer_session_to_new_key" => TRANSFER_SESSION_TO_NEW_KEY,
+        "rotate_session_key" => ROTATE_SESSION_KEY,
+        "cleanup_session_keys" => CLEANUP_SESSION_KEYS,
+        "crank_cleanup" => CRANK_CLEANUP,
+        "set_cleanup_retention" => SET_CLEANUP_RETENTION,
+        "update_allowed_mints" => UPDATE_ALLOWED_MINTS,
+        "initialize_spend_budget" => INITIALIZE_SPEND_BUDGET,
+        "update_spend_budget" => UPDATE_SPEND_BUDGET,
+        "set_program_budget" => SET_PROGRAM_BUDGET,
+        "close_spend_budget" => CLOSE_SPEND_BUDGET,
+        "initialize_policy" => INITIALIZE_POLICY,
+        "update_policy" => UPDATE_POLICY,
+        "close_policy" => CLOSE_POLICY,
+        "set_kill_phrase" => SET_KILL_PHRASE,
+        "trigger_kill_phrase" => TRIGGER_KILL_PHRASE,
+        "set_guardian" => SET_GUARDIAN,
+        "guardian_revoke_all" => GUARDIAN_REVOKE_ALL,
+        "set_account_paused" => SET_ACCOUNT_PAUSED,
+        "set_recipient_consent_required" => SET_RECIPIENT_CONSENT_REQUIRED,
+        "accept_payments" => ACCEPT_PAYMENTS,
+        "withdraw_payment_consent" => WITHDRAW_PAYMENT_CONSENT,
+        "enable_activity_digest" => ENABLE_ACTIVITY_DIGEST,
+        "set_digest_interval" => SET_DIGEST_INTERVAL,
+        "initialize_account_stats" => INITIALIZE_ACCOUNT_STATS,
+        "schedule_session_key" => SCHEDULE_SESSION_KEY,
+        "cancel_scheduled_session" => CANCEL_SCHEDULED_SESSION,
+        "activate_scheduled_session" => ACTIVATE_SCHEDULED_SESSION,
+        "create_pipeline" => CREATE_PIPELINE,
+        "close_pipeline" => CLOSE_PIPELINE,
+        "execute_pipeline" => EXECUTE_PIPELINE,
+        "execute_custom" => EXECUTE_CUSTOM,
+        "spl_approve_delegate" => SPL_APPROVE_DELEGATE,
+        "approve_and_create_session" => APPROVE_AND_CREATE_SESSION,
+        "spl_revoke_delegate" => SPL_REVOKE_DELEGATE,
+        "spl_delegated_transfer" => SPL_DELEGATED_TRANSFER,
+        "spl_delegated_transfer_with_ata" => SPL_DELEGATED_TRANSFER_WITH_ATA,
+        "set_guard_program" => SET_GUARD_PROGRAM,
+        "set_lockout_policy" => SET_LOCKOUT_POLICY,
+        "record_refused_spend" => RECORD_REFUSED_SPEND,
+        "set_max_slot_age" => SET_MAX_SLOT_AGE,
+        "set_permanent_delegate_opt_in" => SET_PERMANENT_DELEGATE_OPT_IN,
+        "set_savings_rule" => SET_SAVINGS_RULE,
+        "set_spend_categories" => SET_SPEND_CATEGORIES,
+        "set_widening_delay" => SET_WIDENING_DELAY,
+        "deposit_sol" => DEPOSIT_SOL,
+        "withdraw_sol" => WITHDRAW_SOL,
+        "session_withdraw_sol" => SESSION_WITHDRAW_SOL,
+        "session_transfer_sol" => SESSION_TRANSFER_SOL,
+        "initialize_gas_tank" => INITIALIZE_GAS_TANK,
+        "set_gas_tank_fee" => SET_GAS_TANK_FEE,
+        "top_up_gas_tank" => TOP_UP_GAS_TANK,
+        "set_blackout_windows" => SET_BLACKOUT_WINDOWS,
+        "set_attestation_gate" => SET_ATTESTATION_GATE,
+        "set_blocklist_enforcement" => SET_BLOCKLIST_ENFORCEMENT,
+        "initialize_blocklist" => INITIALIZE_BLOCKLIST,
+        "update_blocklist" => UPDATE_BLOCKLIST,
+        "initialize_program_config" => INITIALIZE_PROGRAM_CONFIG,
+        "set_paused" => SET_PAUSED,
+        "update_config" => UPDATE_CONFIG,
+        "update_mint_denylist" => UPDATE_MINT_DENYLIST,
+        "set_fee" => SET_FEE,
+        "withdraw_fees" => WITHDRAW_FEES,
+        "set_bond_requirement" => SET_BOND_REQUIREMENT,
+        "set_operator_signature_required" => SET_OPERATOR_SIGNATURE_REQUIRED,
+        "post_bond" => POST_BOND,
+        "post_bond_spl" => POST_BOND_SPL,
+        "request_bond_release" => REQUEST_BOND_RELEASE,
+        "slash_bond" => SLASH_BOND,
+        "withdraw_bond" => WITHDRAW_BOND,
+        "report_violation" => REPORT_VIOLATION,
+        "resolve_dispute" => RESOLVE_DISPUTE,
+        "initialize_operator_reputation" => INITIALIZE_OPERATOR_REPUTATION,
+        "register_service" => REGISTER_SERVICE,
+        "update_service" => UPDATE_SERVICE,
+        "close_service" => CLOSE_SERVICE,
+        "set_service_verified" => SET_SERVICE_VERIFIED,
+        "publish_preset" => PUBLISH_PRESET,
+        "update_preset" => UPDATE_PRESET,
+        "publish_build_hash" => PUBLISH_BUILD_HASH,
+        _ => return None,
+    })
+}
@@ -1,9 +1,198 @@
-/// Maximum number of session keys per user account
-pub const MAX_SESSION_KEYS: usize = 10;
+/// Session key capacity of a new user account
+pub const DEFAULT_SESSION_KEY_CAPACITY: usize = 6;
+
+/// Largest session key capacity an account can be created with. Each inline key takes
+/// `SESSION_KEY_SIZE` bytes, so this keeps `UserAccount::space` within the 10,240 bytes a
+/// CPI `init` can allocate.
+pub const MAX_INIT_SESSION_KEY_CAPACITY: usize = 7;
+
+/// Largest session key capacity `resize_user_account` accepts, the 10 keys accounts have
+/// always been able to hold. Past `MAX_INIT_SESSION_KEY_CAPACITY` the account grows by
+/// `realloc`, at most 10,240 bytes per call; further keys go in `SessionKeyAccount`s.
+pub const MAX_SESSION_KEY_CAPACITY: usize = 10;
+
+/// Layout version written by this program. Bump it when a change to `UserAccount` needs
+/// `migrate_user_account` to fill in defaults for accounts created before it.
+pub const USER_ACCOUNT_VERSION: u8 = 1;
 
 /// Size of each session key entry in bytes
-/// 32 (pubkey) + 8 (created_at) + 8 (expires_at) + 1 (expiration_type) + 32 (permissions) + 1 (is_revoked) + 32 (label)
-pub const SESSION_KEY_SIZE: usize = 32 + 8 + 8 + 1 + 32 + 1 + 32;
+/// 32 (pubkey) + 8 (created_at) + 9 (expires_at_time) + 9 (expires_at_slot) + 42 (permissions) + 1 (is_revoked) + 32 (label)
+/// + 33 (preset) + 4 (preset_version) + 32 (terms_hash) + 8 (total_spent) + 8 (spend_window_start)
+/// + 8 (spent_in_window) + 8 (use_window_start) + 8 (uses_in_window) + 4 (max_uses) + 4 (use_count)
+/// + 8 (valid_from) + 4 + 32 * MAX_ALLOWED_RECIPIENTS (allowed_recipients)
+/// + 4 + 32 * MAX_SESSION_MINTS (allowed_mints) + 4 + 32 * MAX_ALLOWED_PROGRAMS (allowed_programs)
+/// + 4 + DISCRIMINATOR_FILTER_SIZE * MAX_ALLOWED_DISCRIMINATORS (allowed_discriminators)
+/// + 1 + 42 (prior_permissions) + 8 (widening_active_at) + 1 (is_suspended)
+/// + 1 + 2 (revocation_reason) + 1 (failed_attempts) + 8 (failure_window_start)
+/// + 8 (locked_until) + 1 + 32 (delegated_by) + 8 (last_used_at) + 4 (idle_timeout)
+/// + 8 (max_expires_at) + 8 (revoked_at) + 32 (scope) + 32 (domain_hash)
+pub const SESSION_KEY_SIZE: usize = 32
+    + 8
+    + 9
+    + 9
+    + 42
+    + 1
+    + 32
+    + 33
+    + 4
+    + 32
+    + 8
+    + 8
+    + 8
+    + 8
+    + 8
+    + 4
+    + 4
+    + 8
+    + (4 + 32 * MAX_ALLOWED_RECIPIENTS)
+    + (4 + 32 * MAX_SESSION_MINTS)
+    + (4 + 32 * MAX_ALLOWED_PROGRAMS)
+    + (4 + DISCRIMINATOR_FILTER_SIZE * MAX_ALLOWED_DISCRIMINATORS)
+    + (1 + 42)
+    + 8
+    + 1
+    + (1 + 2)
+    + 1
+    + 8
+    + 8
+    + (1 + 32)
+    + 8
+    + 4
+    + 8
+    + 8
+    + 32
+    + 32;
+
+/// Maximum number of recipients a single session key can be restricted to
+pub const MAX_ALLOWED_RECIPIENTS: usize = 4;
+
+/// Maximum number of mints a single session key can be restricted to
+pub const MAX_SESSION_MINTS: usize = 4;
+
+/// Maximum number of programs a session key may target with custom actions
+pub const MAX_ALLOWED_PROGRAMS: usize = 4;
+
+/// Maximum number of instruction discriminators a session key may be restricted to
+pub const MAX_ALLOWED_DISCRIMINATORS: usize = 8;
+
+/// Size of a discriminator filter: 32 (program) + 8 (discriminator) + 1 (len)
+pub const DISCRIMINATOR_FILTER_SIZE: usize = 32 + 8 + 1;
 
 /// Maximum number of allowed SPL token mints
 pub const MAX_ALLOWED_MINTS: usize = 8;
+
+/// Maximum number of token accounts with an open delegate approval at once
+pub const MAX_DELEGATED_TOKEN_ACCOUNTS: usize = 8;
+
+/// Largest archived state `restore_user_account` can take, leaving room in the
+/// transaction for its accounts and a compute budget instruction
+pub const MAX_ARCHIVE_DATA_LEN: usize = 800;
+
+/// Size of a cached delegate bump: 32 (mint) + 1 (bump)
+pub const DELEGATE_BUMP_SIZE: usize = 32 + 1;
+
+/// Maximum number of addresses in the program-wide blocklist registry
+pub const MAX_BLOCKLIST_ENTRIES: usize = 200;
+
+/// Maximum number of mints in the program config's global denylist
+pub const MAX_DENIED_MINTS: usize = 64;
+
+/// Size of an attestation gate: 32 (mint) + 1 (subject)
+pub const ATTESTATION_GATE_SIZE: usize = 32 + 1;
+
+/// Maximum number of session keys a service registry entry can advertise
+pub const MAX_SERVICE_SESSION_KEYS: usize = 8;
+
+/// Minimum time between filing a violation report and resolving it, giving the operator time to respond
+pub const DISPUTE_TIMELOCK_SECONDS: i64 = 24 * 60 * 60;
+
+/// Size of a bond requirement: 33 (optional mint) + 8 (min_amount) + 8 (dispute_window_seconds)
+pub const BOND_REQUIREMENT_SIZE: usize = 33 + 8 + 8;
+
+/// Depth of the per-batch receipt tree in an activity digest; bounds the digest interval
+pub const DIGEST_TREE_DEPTH: usize = 16;
+
+/// Depth of the lifetime receipt tree in account stats (up to 2^32 - 1 executions)
+pub const HISTORY_TREE_DEPTH: usize = 32;
+
+/// Default spend window for `SessionPermissions::daily_limit`
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Longest window a spend budget can be set to (one year)
+pub const MAX_SPEND_BUDGET_WINDOW_SECONDS: i64 = 365 * SECONDS_PER_DAY;
+
+/// Maximum number of blackout windows on a user account
+pub const MAX_BLACKOUT_WINDOWS: usize = 8;
+
+/// Size of a blackout window: 8 (start) + 8 (end)
+pub const BLACKOUT_WINDOW_SIZE: usize = 8 + 8;
+
+/// Number of recent slots kept in the `SlotHashes` sysvar, bounding `max_slot_age`
+pub const MAX_SLOT_HASH_AGE: u32 = 512;
+
+/// Longest delay an account can put on widened session permissions (7 days)
+pub const MAX_WIDENING_DELAY_SECONDS: u32 = 7 * 24 * 60 * 60;
+
+/// Maximum number of steps in a payout pipeline
+pub const MAX_PIPELINE_STEPS: usize = 4;
+
+/// Size of a pipeline step: 1 (variant) + 32 + 32 + 8 + 32 + 8 (largest variant, a swap)
+pub const PIPELINE_STEP_SIZE: usize = 1 + 32 + 32 + 8 + 32 + 8;
+
+/// Sub-key depth a new user account allows: direct sub-keys only
+pub const DEFAULT_MAX_DELEGATION_DEPTH: u8 = 1;
+
+/// Deepest sub-key chain `set_max_delegation_depth` accepts
+pub const MAX_DELEGATION_DEPTH: u8 = 4;
+
+/// Size of a lockout policy: 1 (max_failures) + 4 (window_seconds) + 4 (lock_seconds)
+pub const LOCKOUT_POLICY_SIZE: usize = 1 + 4 + 4;
+
+/// Size of a savings rule: 32 (mint) + 32 (vault) + 2 (basis_points) + 8 (round_up_unit)
+pub const SAVINGS_RULE_SIZE: usize = 32 + 32 + 2 + 8;
+
+/// Maximum number of spend categories in an account's taxonomy
+pub const MAX_SPEND_CATEGORIES: usize = 16;
+
+/// Size of a spend category: 2 (id) + 16 (label)
+pub const SPEND_CATEGORY_SIZE: usize = 2 + 16;
+
+/// Size of a per-category stats entry: 2 (category) + 16 (volume) + 8 (count)
+pub const CATEGORY_TOTAL_SIZE: usize = 2 + 16 + 8;
+
+/// Hourly usage buckets kept per session key in account stats (7 days)
+pub const HEATMAP_HOURS: usize = 7 * 24;
+
+/// Maximum number of session keys with a usage heatmap in account stats
+pub const MAX_HEATMAP_SESSIONS: usize = 8;
+
+/// Size of a session heatmap: 32 (session_key) + 8 (last_hour) + 2 per hourly bucket
+pub const SESSION_HEATMAP_SIZE: usize = 32 + 8 + 2 * HEATMAP_HOURS;
+
+/// Most lamports a gas tank may reimburse per execution
+pub const MAX_GAS_FEE_PER_EXECUTION: u64 = 100_000;
+
+/// Highest protocol fee the config admin may set, in basis points (10%)
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+
+/// Most rules a policy document may hold
+pub const MAX_POLICY_RULES: usize = 8;
+
+/// Most entries in a policy rule's recipient or mint list
+pub const MAX_POLICY_LIST_LEN: usize = 4;
+
+/// Size of a policy rule: 33 (session_key) + 1 (condition tag) + the largest condition,
+/// a list of 4 + 32 * MAX_POLICY_LIST_LEN
+pub const POLICY_RULE_SIZE: usize = 33 + 1 + 4 + 32 * MAX_POLICY_LIST_LEN;
+
+/// Lamports `crank_cleanup` pays per removed key, from the account's SOL vault
+pub const CLEANUP_BOUNTY_LAMPORTS: u64 = 10_000;
+
+/// Domain prefix of kill phrase commitments
+pub const KILL_PHRASE_DOMAIN: &[u8] = b"kill_phrase";
+
+/// Most target programs an account can cap with per-program spend budgets
+pub const MAX_PROGRAM_BUDGETS: usize = 4;
+
+/// Size of a per-program budget: 32 (program) + 8 (limit) + 8 (window_start) + 8 (spent)
+pub const PROGRAM_BUDGET_SIZE: usize = 32 + 8 + 8 + 8;
@@ -1,24 +1,37 @@
-use crate::constants::MAX_SESSION_KEYS;
-use crate::state::UserAccount;
+use crate::constants::{DEFAULT_SESSION_KEY_CAPACITY, MAX_BLOCKLIST_ENTRIES};
+use crate::errors::ErrorCode;
+use crate::state::{
+    AccountStats, ActivityDigest, ArchivedUserAccount, BlocklistRegistry, CreateSessionKeyArgs,
+    GasTank, OperatorBond, OperatorReputation, PayoutPipeline, PolicyAccount, ProgramConfig,
+    PublishedBuild, RecipientConsent, ScheduledSession, ServiceEntry, ServicePreset,
+    SessionKeyAccount, SpendBudget, Treasury, UserAccount, ViolationReport,
+};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 // ===== CONTEXTS =====
 
 #[derive(Accounts)]
+#[instruction(max_session_keys: u16)]
 pub struct InitializeUserAccount<'info> {
     #[account(
         init,
-        payer = authority,
-        space = UserAccount::space(MAX_SESSION_KEYS),
+        payer = payer,
+        space = UserAccount::space(max_session_keys as usize),
         seeds = [UserAccount::SEED_PREFIX, authority.key().as_ref()],
         bump
     )]
     pub user_account: Account<'info, UserAccount>,
 
-    #[account(mut)]
     pub authority: Signer<'info>,
 
+    /// Pays the account's rent; may be a dapp sponsoring its users
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -26,16 +39,21 @@ pub struct InitializeUserAccount<'info> {
 pub struct InitializeUserAccountWithConfig<'info> {
     #[account(
         init,
-        payer = authority,
-        space = UserAccount::space(MAX_SESSION_KEYS),
+        payer = payer,
+        space = UserAccount::space(DEFAULT_SESSION_KEY_CAPACITY),
         seeds = [UserAccount::SEED_PREFIX, authority.key().as_ref()],
         bump
     )]
     pub user_account: Account<'info, UserAccount>,
 
+    /// Funds the initial deposit
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    /// Pays the account's rent; may be a dapp sponsoring its users
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -54,6 +72,27 @@ pub struct CreateSessionKey<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct CreateSessionKeyInitIfNeeded<'info> {
+    /// Created with the default session key capacity when it doesn't exist yet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserAccount::space(DEFAULT_SESSION_KEY_CAPACITY),
+        seeds = [UserAccount::SEED_PREFIX, authority.key().as_ref()],
+        bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+
+    /// Pays the account's rent; may be a dapp sponsoring its users
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct RevokeSessionKey<'info> {
     #[account(
@@ -80,7 +119,143 @@ pub struct UpdateSessionKey<'info> {
     pub authority: Signer<'info>,
 }
 
-// Removed SOL execute context to focus on SPL delegation only
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct VerifySessionDomain<'info> {
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// The key's PDA, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_pubkey.as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct ValidateSession<'info> {
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// The key's PDA, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_pubkey.as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct SuspendSessionKey<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// The key's PDA, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_pubkey.as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SelfRestrict<'info> {
+    /// The session key narrowing itself
+    pub session_signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// The signer's key, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_signer.key().as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateSessionKey<'info> {
+    /// The session key creating the sub-key; needs `can_delegate`
+    pub session_signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// The signer's key, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_signer.key().as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxDelegationDepth<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CrankCleanup<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// Anyone; receives the bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCleanupRetention<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
 
 #[derive(Accounts)]
 pub struct CleanupSessionKeys<'info> {
@@ -95,6 +270,19 @@ pub struct CleanupSessionKeys<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct TransferSessionToNewKey<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// The authority, or the session key being handed over
+    pub signer: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct RevokeAllSessionKeys<'info> {
     #[account(
@@ -108,10 +296,18 @@ pub struct RevokeAllSessionKeys<'info> {
     pub authority: Signer<'info>,
 }
 
-// ===== SPL TOKEN CONTEXTS =====
-
 #[derive(Accounts)]
-pub struct SplApproveDelegate<'info> {
+#[instruction(args: CreateSessionKeyArgs)]
+pub struct CreateSessionKeyAccount<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = SessionKeyAccount::SPACE,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), args.session_pubkey.as_ref()],
+        bump
+    )]
+    pub session_key_account: Account<'info, SessionKeyAccount>,
+
     #[account(
         mut,
         seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
@@ -123,46 +319,93 @@ pub struct SplApproveDelegate<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    #[account(mut)]
-    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
 
-    pub mint: InterfaceAccount<'info, Mint>,
+#[derive(Accounts)]
+pub struct RevokeSessionKeyAccount<'info> {
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_key_account.session_key.pubkey.as_ref()],
+        bump = session_key_account.bump,
+        has_one = user_account
+    )]
+    pub session_key_account: Account<'info, SessionKeyAccount>,
 
-    /// CHECK: derived and checked in handler
-    pub delegate_authority: UncheckedAccount<'info>,
+    /// Mutable to revoke the key's sub-keys
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
 
-    pub token_program: Interface<'info, TokenInterface>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct SplDelegatedTransfer<'info> {
-    /// Session key must sign
-    pub session_signer: Signer<'info>,
+pub struct CloseSessionKeyAccount<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_key_account.session_key.pubkey.as_ref()],
+        bump = session_key_account.bump,
+        has_one = user_account
+    )]
+    pub session_key_account: Account<'info, SessionKeyAccount>,
 
     #[account(
+        mut,
         seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
-        bump = user_account.bump
+        bump = user_account.bump,
+        has_one = authority
     )]
     pub user_account: Account<'info, UserAccount>,
 
     #[account(mut)]
-    pub from_token: InterfaceAccount<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_capacity: u16)]
+pub struct ResizeUserAccount<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority,
+        realloc = UserAccount::space(new_capacity as usize),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub user_account: Account<'info, UserAccount>,
 
     #[account(mut)]
-    pub to_token: InterfaceAccount<'info, TokenAccount>,
+    pub authority: Signer<'info>,
 
-    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
 
-    /// CHECK: PDA signs via program
-    pub delegate_authority: UncheckedAccount<'info>,
+#[derive(Accounts)]
+pub struct MigrateUserAccount<'info> {
+    /// CHECK: may predate the current layout, so the handler reads it leniently and
+    /// checks its address against the stored authority and bump
+    #[account(mut, owner = crate::ID)]
+    pub user_account: UncheckedAccount<'info>,
 
-    pub token_program: Interface<'info, TokenInterface>,
+    /// Pays rent for any space the current layout adds
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct SplRevokeDelegate<'info> {
+pub struct CloseUserAccount<'info> {
     #[account(
         mut,
+        close = authority,
         seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
         bump = user_account.bump,
         has_one = authority
@@ -171,15 +414,64 @@ pub struct SplRevokeDelegate<'info> {
 
     #[account(mut)]
     pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveUserAccount<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ArchivedUserAccount::SPACE,
+        seeds = [ArchivedUserAccount::SEED_PREFIX, authority.key().as_ref()],
+        bump
+    )]
+    pub archive: Account<'info, ArchivedUserAccount>,
 
     #[account(mut)]
-    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    pub authority: Signer<'info>,
 
-    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateAllowedMints<'info> {
+pub struct RestoreUserAccount<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [ArchivedUserAccount::SEED_PREFIX, authority.key().as_ref()],
+        bump = archive.bump,
+        has_one = authority
+    )]
+    pub archive: Account<'info, ArchivedUserAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = UserAccount::space(archive.session_key_capacity as usize),
+        seeds = [UserAccount::SEED_PREFIX, authority.key().as_ref()],
+        bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ===== SPL TOKEN CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct SplApproveDelegate<'info> {
     #[account(
         mut,
         seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
@@ -188,5 +480,1731 @@ pub struct UpdateAllowedMints<'info> {
     )]
     pub user_account: Account<'info, UserAccount>,
 
+    /// CHECK: the program config PDA, read for its mint denylist once it has been
+    /// initialized
+    #[account(seeds = [ProgramConfig::SEED_PREFIX], bump)]
+    pub program_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: derived and checked in handler
+    pub delegate_authority: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SplDelegatedTransfer<'info> {
+    /// Session key must sign; mutable to receive gas tank reimbursements
+    #[account(mut)]
+    pub session_signer: Signer<'info>,
+
+    /// Mutable to record the session key's cumulative spend
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: the program config PDA, read for its pause switch and global limits once
+    /// it has been initialized
+    #[account(seeds = [ProgramConfig::SEED_PREFIX], bump)]
+    pub program_config: UncheckedAccount<'info>,
+
+    /// The signer's key, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_signer.key().as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+
+    /// The account's policy, required once it has one
+    #[account(
+        seeds = [PolicyAccount::SEED_PREFIX, user_account.key().as_ref()],
+        bump = policy.bump
+    )]
+    pub policy: Option<Account<'info, PolicyAccount>>,
+
+    #[account(
+        mut,
+        constraint = from_token.owner == user_account.authority @ ErrorCode::SourceTokenOwnerMismatch,
+        constraint = from_token.mint == mint.key() @ ErrorCode::SourceTokenMintMismatch
+    )]
+    pub from_token: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub to_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signs via program
+    pub delegate_authority: UncheckedAccount<'info>,
+
+    /// Session holder's attestation token account, required when the gate covers the session holder
+    pub session_attestation: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Recipient's attestation token account, required when the gate covers the recipient
+    pub recipient_attestation: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Program-wide blocklist, required when the account enforces it
+    #[account(seeds = [BlocklistRegistry::SEED_PREFIX], bump = blocklist.bump)]
+    pub blocklist: Option<Account<'info, BlocklistRegistry>>,
+
+    /// Consent of the `to_token` owner, required when the account requires recipient consent
+    #[account(
+        seeds = [RecipientConsent::SEED_PREFIX, user_account.key().as_ref(), to_token.owner.as_ref()],
+        bump = recipient_consent.bump
+    )]
+    pub recipient_consent: Option<Account<'info, RecipientConsent>>,
+
+    /// Session operator's bond, required when the account has a bond requirement
+    #[account(
+        seeds = [OperatorBond::SEED_PREFIX, user_account.key().as_ref(), session_signer.key().as_ref()],
+        bump = operator_bond.bump
+    )]
+    pub operator_bond: Option<Account<'info, OperatorBond>>,
+
+    /// Reputation of the bonded operator, required alongside `operator_bond` to record volume
+    #[account(
+        mut,
+        seeds = [OperatorReputation::SEED_PREFIX, operator_reputation.operator.as_ref()],
+        bump = operator_reputation.bump
+    )]
+    pub operator_reputation: Option<Account<'info, OperatorReputation>>,
+
+    /// Account-level budget for `mint`, required when the account has one for this mint
+    #[account(
+        mut,
+        seeds = [SpendBudget::SEED_PREFIX, user_account.key().as_ref(), mint.key().as_ref()],
+        bump = spend_budget.bump
+    )]
+    pub spend_budget: Option<Account<'info, SpendBudget>>,
+
+    /// Running activity digest, required when the account aggregates execution events
+    #[account(
+        mut,
+        seeds = [ActivityDigest::SEED_PREFIX, user_account.key().as_ref()],
+        bump = activity_digest.bump
+    )]
+    pub activity_digest: Option<Account<'info, ActivityDigest>>,
+
+    /// Lifetime execution history, required once the account has enabled stats
+    #[account(
+        mut,
+        seeds = [AccountStats::SEED_PREFIX, user_account.key().as_ref()],
+        bump = account_stats.bump
+    )]
+    pub account_stats: Option<Account<'info, AccountStats>>,
+
+    /// Savings destination, required when the account's savings rule covers the mint
+    #[account(mut)]
+    pub savings_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury's token account for the mint, required while a protocol fee is set
+    #[account(mut)]
+    pub fee_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: the SlotHashes sysvar, required once the account sets a max slot age
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+
+    /// Reimburses the session signer's fee when given
+    #[account(
+        mut,
+        seeds = [GasTank::SEED_PREFIX, user_account.key().as_ref()],
+        bump = gas_tank.bump
+    )]
+    pub gas_tank: Option<Account<'info, GasTank>>,
+
+    /// CHECK: the Instructions sysvar, required when the account requires operator
+    /// signatures
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SplDelegatedTransferWithAta<'info> {
+    /// Session key; pays the rent when the recipient's token account has to be created
+    #[account(mut)]
+    pub session_signer: Signer<'info>,
+
+    /// CHECK: wallet receiving the transfer; only used to derive its token account
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: the recipient's associated token account for `mint`, created by the
+    /// Associated Token program when missing
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &recipient.key(),
+            &mint.key(),
+            &token_program.key()
+        )
+    )]
+    pub to_token: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+
+    pub time_program: Program<'info, crate::program::Time>,
+}
+
+#[derive(Accounts)]
+pub struct SplRevokeDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttestationGate<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetBlackoutWindows<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxSlotAge<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWideningDelay<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPermanentDelegateOptIn<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLockoutPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct RecordRefusedSpend<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        constraint = reporter.key() == user_account.authority
+            || user_account.guardian == Some(reporter.key()) @ ErrorCode::Unauthorized
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// The key's PDA, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_pubkey.as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+
+    /// The account's authority or guardian
+    pub reporter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSavingsRule<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSpendCategories<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardProgram<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAllowedMints<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+// ===== SPEND BUDGET CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct InitializeSpendBudget<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = SpendBudget::SPACE,
+        seeds = [SpendBudget::SEED_PREFIX, user_account.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub spend_budget: Account<'info, SpendBudget>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSpendBudget<'info> {
+    #[account(
+        mut,
+        seeds = [SpendBudget::SEED_PREFIX, user_account.key().as_ref(), spend_budget.mint.as_ref()],
+        bump = spend_budget.bump,
+        has_one = user_account
+    )]
+    pub spend_budget: Account<'info, SpendBudget>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProgramBudget<'info> {
+    #[account(
+        mut,
+        seeds = [SpendBudget::SEED_PREFIX, user_account.key().as_ref(), spend_budget.mint.as_ref()],
+        bump = spend_budget.bump,
+        has_one = user_account
+    )]
+    pub spend_budget: Account<'info, SpendBudget>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSpendBudget<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [SpendBudget::SEED_PREFIX, user_account.key().as_ref(), spend_budget.mint.as_ref()],
+        bump = spend_budget.bump,
+        has_one = user_account
+    )]
+    pub spend_budget: Account<'info, SpendBudget>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// ===== ACTIVITY DIGEST CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct EnableActivityDigest<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ActivityDigest::SPACE,
+        seeds = [ActivityDigest::SEED_PREFIX, user_account.key().as_ref()],
+        bump
+    )]
+    pub activity_digest: Account<'info, ActivityDigest>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetDigestInterval<'info> {
+    #[account(
+        mut,
+        seeds = [ActivityDigest::SEED_PREFIX, user_account.key().as_ref()],
+        bump = activity_digest.bump
+    )]
+    pub activity_digest: Account<'info, ActivityDigest>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAccountStats<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = AccountStats::SPACE,
+        seeds = [AccountStats::SEED_PREFIX, user_account.key().as_ref()],
+        bump
+    )]
+    pub account_stats: Account<'info, AccountStats>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ===== SCHEDULED GRANT CONTEXTS =====
+
+#[derive(Accounts)]
+#[instruction(args: CreateSessionKeyArgs)]
+pub struct ScheduleSessionKey<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ScheduledSession::SPACE,
+        seeds = [ScheduledSession::SEED_PREFIX, user_account.key().as_ref(), args.session_pubkey.as_ref()],
+        bump
+    )]
+    pub scheduled_session: Account<'info, ScheduledSession>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelScheduledSession<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [ScheduledSession::SEED_PREFIX, user_account.key().as_ref(), scheduled_session.args.session_pubkey.as_ref()],
+        bump = scheduled_session.bump,
+        has_one = user_account
+    )]
+    pub scheduled_session: Account<'info, ScheduledSession>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateScheduledSession<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [ScheduledSession::SEED_PREFIX, user_account.key().as_ref(), scheduled_session.args.session_pubkey.as_ref()],
+        bump = scheduled_session.bump,
+        has_one = user_account
+    )]
+    pub scheduled_session: Account<'info, ScheduledSession>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: receives the grant's rent back; pinned by `has_one` on user_account
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+// ===== PAYOUT PIPELINE CONTEXTS =====
+
+#[derive(Accounts)]
+#[instruction(pipeline_id: u16)]
+pub struct CreatePipeline<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PayoutPipeline::SPACE,
+        seeds = [PayoutPipeline::SEED_PREFIX, user_account.key().as_ref(), &pipeline_id.to_le_bytes()],
+        bump
+    )]
+    pub pipeline: Account<'info, PayoutPipeline>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePipeline<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PayoutPipeline::SEED_PREFIX, user_account.key().as_ref(), &pipeline.pipeline_id.to_le_bytes()],
+        bump = pipeline.bump,
+        has_one = user_account
+    )]
+    pub pipeline: Account<'info, PayoutPipeline>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecutePipeline<'info> {
+    /// Session key must sign
+    pub session_signer: Signer<'info>,
+
+    /// Mutable to record the session key's use count
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: the program config PDA, read for its pause switch and global limits once
+    /// it has been initialized
+    #[account(seeds = [ProgramConfig::SEED_PREFIX], bump)]
+    pub program_config: UncheckedAccount<'info>,
+
+    /// The signer's key, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_signer.key().as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+
+    /// The account's policy, required once it has one
+    #[account(
+        seeds = [PolicyAccount::SEED_PREFIX, user_account.key().as_ref()],
+        bump = policy.bump
+    )]
+    pub policy: Option<Account<'info, PolicyAccount>>,
+
+    #[account(
+        seeds = [PayoutPipeline::SEED_PREFIX, user_account.key().as_ref(), &pipeline.pipeline_id.to_le_bytes()],
+        bump = pipeline.bump,
+        has_one = user_account
+    )]
+    pub pipeline: Account<'info, PayoutPipeline>,
+
+    /// Account-level budget for the mint the pipeline spends, required when one of its
+    /// mints has a budget
+    #[account(
+        mut,
+        seeds = [SpendBudget::SEED_PREFIX, user_account.key().as_ref(), spend_budget.mint.as_ref()],
+        bump = spend_budget.bump
+    )]
+    pub spend_budget: Option<Account<'info, SpendBudget>>,
+
+    /// CHECK: the SlotHashes sysvar, required once the account sets a max slot age
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// ===== CUSTOM ACTION CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct ExecuteCustom<'info> {
+    /// Session key must sign
+    pub session_signer: Signer<'info>,
+
+    /// Mutable to record the session key's use count
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: the program config PDA, read for its pause switch and global limits once
+    /// it has been initialized
+    #[account(seeds = [ProgramConfig::SEED_PREFIX], bump)]
+    pub program_config: UncheckedAccount<'info>,
+
+    /// The signer's key, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_signer.key().as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+
+    /// The account's policy, required once it has one
+    #[account(
+        seeds = [PolicyAccount::SEED_PREFIX, user_account.key().as_ref()],
+        bump = policy.bump
+    )]
+    pub policy: Option<Account<'info, PolicyAccount>>,
+
+    /// Spend budget capping the target program, required when the account caps it
+    #[account(
+        mut,
+        seeds = [SpendBudget::SEED_PREFIX, user_account.key().as_ref(), spend_budget.mint.as_ref()],
+        bump = spend_budget.bump
+    )]
+    pub spend_budget: Option<Account<'info, SpendBudget>>,
+
+    /// CHECK: per-account executor PDA; signs custom CPIs and holds any assets they need
+    #[account(seeds = [b"executor", user_account.key().as_ref()], bump)]
+    pub executor: UncheckedAccount<'info>,
+
+    /// CHECK: the SlotHashes sysvar, required once the account sets a max slot age
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+}
+
+// ===== SOL VAULT CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// Anyone can fund the vault
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// Receives the lamports
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SessionWithdrawSol<'info> {
+    /// Session key must sign; receives the lamports
+    #[account(mut)]
+    pub session_signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: the program config PDA, read for its pause switch and global limits once
+    /// it has been initialized
+    #[account(seeds = [ProgramConfig::SEED_PREFIX], bump)]
+    pub program_config: UncheckedAccount<'info>,
+
+    /// The signer's key, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_signer.key().as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+
+    /// The account's policy, required once it has one
+    #[account(
+        seeds = [PolicyAccount::SEED_PREFIX, user_account.key().as_ref()],
+        bump = policy.bump
+    )]
+    pub policy: Option<Account<'info, PolicyAccount>>,
+
+    /// CHECK: the SlotHashes sysvar, required once the account sets a max slot age
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+
+    /// Receives the protocol fee, required while one is set
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+}
+
+#[derive(Accounts)]
+pub struct SessionTransferSol<'info> {
+    /// Session key must sign
+    pub session_signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: the program config PDA, read for its pause switch and global limits once
+    /// it has been initialized
+    #[account(seeds = [ProgramConfig::SEED_PREFIX], bump)]
+    pub program_config: UncheckedAccount<'info>,
+
+    /// The signer's key, when it lives in a `SessionKeyAccount` instead of inline
+    #[account(
+        mut,
+        seeds = [SessionKeyAccount::SEED_PREFIX, user_account.key().as_ref(), session_signer.key().as_ref()],
+        bump = session_key_account.bump
+    )]
+    pub session_key_account: Option<Account<'info, SessionKeyAccount>>,
+
+    /// The account's policy, required once it has one
+    #[account(
+        seeds = [PolicyAccount::SEED_PREFIX, user_account.key().as_ref()],
+        bump = policy.bump
+    )]
+    pub policy: Option<Account<'info, PolicyAccount>>,
+
+    /// Receives the lamports
+    #[account(mut)]
+    pub destination: SystemAccount<'info>,
+
+    /// The destination's consent, required when the account requires recipient consent
+    #[account(
+        seeds = [RecipientConsent::SEED_PREFIX, user_account.key().as_ref(), destination.key().as_ref()],
+        bump = recipient_consent.bump
+    )]
+    pub recipient_consent: Option<Account<'info, RecipientConsent>>,
+
+    /// CHECK: the SlotHashes sysvar, required once the account sets a max slot age
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+
+    /// Receives the protocol fee, required while one is set
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+}
+
+// ===== GAS TANK CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct InitializeGasTank<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = GasTank::SPACE,
+        seeds = [GasTank::SEED_PREFIX, user_account.key().as_ref()],
+        bump
+    )]
+    pub gas_tank: Account<'info, GasTank>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGasTankFee<'info> {
+    #[account(
+        mut,
+        seeds = [GasTank::SEED_PREFIX, user_account.key().as_ref()],
+        bump = gas_tank.bump
+    )]
+    pub gas_tank: Account<'info, GasTank>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpGasTank<'info> {
+    #[account(
+        mut,
+        seeds = [GasTank::SEED_PREFIX, user_account.key().as_ref()],
+        bump = gas_tank.bump
+    )]
+    pub gas_tank: Account<'info, GasTank>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// Anyone can top up
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ===== BLOCKLIST CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct InitializeBlocklist<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = BlocklistRegistry::space(MAX_BLOCKLIST_ENTRIES),
+        seeds = [BlocklistRegistry::SEED_PREFIX],
+        bump
+    )]
+    pub blocklist: Account<'info, BlocklistRegistry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Time>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBlocklist<'info> {
+    #[account(
+        mut,
+        seeds = [BlocklistRegistry::SEED_PREFIX],
+        bump = blocklist.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub blocklist: Account<'info, BlocklistRegistry>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = ProgramConfig::SPACE,
+        seeds = [ProgramConfig::SEED_PREFIX],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Treasury::SPACE,
+        seeds = [Treasury::SEED_PREFIX],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Time>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramConfig<'info> {
+    #[account(
+        mut,
+        seeds = [ProgramConfig::SEED_PREFIX],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(
+        seeds = [ProgramConfig::SEED_PREFIX],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// CHECK: receives the lamports, or is the token account receiving the tokens
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// Mint of the fees to withdraw; omit to withdraw lamports
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Treasury's token account for `mint`, required with it
+    #[account(mut)]
+    pub fee_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct SetBlocklistEnforcement<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+// ===== OPERATOR BOND CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct SetBondRequirement<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperatorSignatureRequired<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct PostBond<'info> {
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        init,
+        payer = operator,
+        space = OperatorBond::SPACE,
+        seeds = [OperatorBond::SEED_PREFIX, user_account.key().as_ref(), session_pubkey.as_ref()],
+        bump
+    )]
+    pub bond: Account<'info, OperatorBond>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OperatorReputation::SEED_PREFIX, operator.key().as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Account<'info, OperatorReputation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct PostBondSpl<'info> {
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        init,
+        payer = operator,
+        space = OperatorBond::SPACE,
+        seeds = [OperatorBond::SEED_PREFIX, user_account.key().as_ref(), session_pubkey.as_ref()],
+        bump
+    )]
+    pub bond: Account<'info, OperatorBond>,
+
+    #[account(
+        init,
+        payer = operator,
+        seeds = [OperatorBond::VAULT_SEED_PREFIX, bond.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = bond,
+        token::token_program = token_program
+    )]
+    pub bond_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OperatorReputation::SEED_PREFIX, operator.key().as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Account<'info, OperatorReputation>,
+
+    #[account(mut, token::mint = mint, token::authority = operator)]
+    pub operator_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestBondRelease<'info> {
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [OperatorBond::SEED_PREFIX, user_account.key().as_ref(), bond.session_key.as_ref()],
+        bump = bond.bump,
+        has_one = user_account,
+        has_one = operator
+    )]
+    pub bond: Account<'info, OperatorBond>,
+
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashBond<'info> {
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OperatorBond::SEED_PREFIX, user_account.key().as_ref(), bond.session_key.as_ref()],
+        bump = bond.bump,
+        has_one = user_account
+    )]
+    pub bond: Account<'info, OperatorBond>,
+
+    #[account(
+        mut,
+        seeds = [ViolationReport::SEED_PREFIX, user_account.key().as_ref(), &report.index.to_le_bytes()],
+        bump = report.bump,
+        has_one = user_account
+    )]
+    pub report: Account<'info, ViolationReport>,
+
+    #[account(
+        mut,
+        seeds = [OperatorReputation::SEED_PREFIX, bond.operator.as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Account<'info, OperatorReputation>,
+
+    /// SPL bonds only: escrow vault owned by the bond PDA
+    #[account(mut, seeds = [OperatorBond::VAULT_SEED_PREFIX, bond.key().as_ref()], bump)]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL bonds only: receives the slashed tokens
+    #[account(mut)]
+    pub destination_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBond<'info> {
+    #[account(
+        mut,
+        close = operator,
+        seeds = [OperatorBond::SEED_PREFIX, bond.user_account.as_ref(), bond.session_key.as_ref()],
+        bump = bond.bump,
+        has_one = operator
+    )]
+    pub bond: Account<'info, OperatorBond>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OperatorReputation::SEED_PREFIX, operator.key().as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Account<'info, OperatorReputation>,
+
+    /// SPL bonds only: escrow vault owned by the bond PDA, closed on withdrawal
+    #[account(mut, seeds = [OperatorBond::VAULT_SEED_PREFIX, bond.key().as_ref()], bump)]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL bonds only: receives the remaining bond
+    #[account(mut)]
+    pub destination_token: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+// ===== DISPUTE CONTEXTS =====
+
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct ReportViolation<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ViolationReport::SPACE,
+        seeds = [ViolationReport::SEED_PREFIX, user_account.key().as_ref(), &user_account.violation_reports.to_le_bytes()],
+        bump
+    )]
+    pub report: Account<'info, ViolationReport>,
+
+    /// Bond backing the accused session, if any; its withdrawal is frozen until resolution
+    #[account(
+        mut,
+        seeds = [OperatorBond::SEED_PREFIX, user_account.key().as_ref(), session_pubkey.as_ref()],
+        bump = bond.bump
+    )]
+    pub bond: Option<Account<'info, OperatorBond>>,
+
+    /// Reputation of the bonded operator, required alongside `bond`
+    #[account(
+        mut,
+        seeds = [OperatorReputation::SEED_PREFIX, reputation.operator.as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Option<Account<'info, OperatorReputation>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [ViolationReport::SEED_PREFIX, user_account.key().as_ref(), &report.index.to_le_bytes()],
+        bump = report.bump,
+        has_one = user_account
+    )]
+    pub report: Account<'info, ViolationReport>,
+
+    #[account(
+        mut,
+        seeds = [OperatorBond::SEED_PREFIX, user_account.key().as_ref(), report.session_key.as_ref()],
+        bump = bond.bump
+    )]
+    pub bond: Option<Account<'info, OperatorBond>>,
+
+    /// Reputation of the bonded operator, required alongside `bond`
+    #[account(
+        mut,
+        seeds = [OperatorReputation::SEED_PREFIX, reputation.operator.as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Option<Account<'info, OperatorReputation>>,
+
+    pub authority: Signer<'info>,
+}
+
+// ===== OPERATOR REPUTATION CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct InitializeOperatorReputation<'info> {
+    #[account(
+        init,
+        payer = operator,
+        space = OperatorReputation::SPACE,
+        seeds = [OperatorReputation::SEED_PREFIX, operator.key().as_ref()],
+        bump
+    )]
+    pub reputation: Account<'info, OperatorReputation>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ===== SERVICE REGISTRY CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct RegisterService<'info> {
+    #[account(
+        init,
+        payer = operator,
+        space = ServiceEntry::SPACE,
+        seeds = [ServiceEntry::SEED_PREFIX, operator.key().as_ref()],
+        bump
+    )]
+    pub service: Account<'info, ServiceEntry>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateService<'info> {
+    #[account(
+        mut,
+        seeds = [ServiceEntry::SEED_PREFIX, operator.key().as_ref()],
+        bump = service.bump,
+        has_one = operator
+    )]
+    pub service: Account<'info, ServiceEntry>,
+
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseService<'info> {
+    #[account(
+        mut,
+        close = operator,
+        seeds = [ServiceEntry::SEED_PREFIX, operator.key().as_ref()],
+        bump = service.bump,
+        has_one = operator
+    )]
+    pub service: Account<'info, ServiceEntry>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetServiceVerified<'info> {
+    #[account(
+        mut,
+        seeds = [ServiceEntry::SEED_PREFIX, service.operator.as_ref()],
+        bump = service.bump
+    )]
+    pub service: Account<'info, ServiceEntry>,
+
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Time>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+#[instruction(preset_id: u16)]
+pub struct PublishPreset<'info> {
+    #[account(
+        seeds = [ServiceEntry::SEED_PREFIX, operator.key().as_ref()],
+        bump = service.bump,
+        has_one = operator
+    )]
+    pub service: Account<'info, ServiceEntry>,
+
+    #[account(
+        init,
+        payer = operator,
+        space = ServicePreset::SPACE,
+        seeds = [ServicePreset::SEED_PREFIX, service.key().as_ref(), &preset_id.to_le_bytes()],
+        bump
+    )]
+    pub preset: Account<'info, ServicePreset>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePreset<'info> {
+    #[account(
+        mut,
+        seeds = [ServicePreset::SEED_PREFIX, preset.service.as_ref(), &preset.preset_id.to_le_bytes()],
+        bump = preset.bump,
+        has_one = operator
+    )]
+    pub preset: Account<'info, ServicePreset>,
+
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSessionKeyFromPreset<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [ServicePreset::SEED_PREFIX, preset.service.as_ref(), &preset.preset_id.to_le_bytes()],
+        bump = preset.bump
+    )]
+    pub preset: Account<'info, ServicePreset>,
+}
+
+// ===== POLICY CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct InitializePolicy<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PolicyAccount::SPACE,
+        seeds = [PolicyAccount::SEED_PREFIX, user_account.key().as_ref()],
+        bump
+    )]
+    pub policy: Account<'info, PolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [PolicyAccount::SEED_PREFIX, user_account.key().as_ref()],
+        bump = policy.bump,
+        has_one = user_account
+    )]
+    pub policy: Account<'info, PolicyAccount>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePolicy<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PolicyAccount::SEED_PREFIX, user_account.key().as_ref()],
+        bump = policy.bump,
+        has_one = user_account
+    )]
+    pub policy: Account<'info, PolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// ===== EMERGENCY CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct SetKillPhrase<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TriggerKillPhrase<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// Whoever presents the phrase; needs no relation to the account
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianRevokeAll<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.guardian == Some(guardian.key()) @ ErrorCode::NotGuardian
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAccountPaused<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+// ===== RECIPIENT CONSENT CONTEXTS =====
+
+#[derive(Accounts)]
+pub struct SetRecipientConsentRequired<'info> {
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump,
+        has_one = authority
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptPayments<'info> {
+    #[account(
+        init,
+        payer = recipient,
+        space = RecipientConsent::SPACE,
+        seeds = [RecipientConsent::SEED_PREFIX, user_account.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_consent: Account<'info, RecipientConsent>,
+
+    #[account(
+        seeds = [UserAccount::SEED_PREFIX, user_account.authority.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// Wallet accepting payouts; pays the consent's rent
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawPaymentConsent<'info> {
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [RecipientConsent::SEED_PREFIX, recipient_consent.user_account.as_ref(), recipient.key().as_ref()],
+        bump = recipient_consent.bump,
+        has_one = recipient
+    )]
+    pub recipient_consent: Account<'info, RecipientConsent>,
+
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+}
+
+// ===== BUILD VERIFICATION CONTEXTS =====
+
+#[derive(Accounts)]
+#[instruction(build_hash: [u8; 32])]
+pub struct PublishBuildHash<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = PublishedBuild::SPACE,
+        seeds = [PublishedBuild::SEED_PREFIX, build_hash.as_ref()],
+        bump
+    )]
+    pub published_build: Account<'info, PublishedBuild>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Time>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,123 @@
+//! Builders for the CPIs integrating programs make most, on top of the Anchor-generated
+//! `cpi` module (enable the `cpi` feature). They fill in the optional accounts an
+//! integration doesn't use, so callers only pass what their own instruction has.
+//! `signer_seeds` signs for a PDA authority or session key; pass `&[]` when the
+//! signer signed the outer transaction.
+
+use crate::cpi;
+use crate::state::{CreateSessionKeyArgs, SessionPermissions};
+use anchor_lang::prelude::*;
+
+/// Create a session key on `user_account` with `create_session_key_v2`, signed by its
+/// `authority`
+pub fn create_session_key<'info>(
+    time_program: AccountInfo<'info>,
+    user_account: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    args: CreateSessionKeyArgs,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    cpi::create_session_key_v2(
+        CpiContext::new_with_signer(
+            time_program,
+            cpi::accounts::CreateSessionKey {
+                user_account,
+                authority,
+                system_program,
+            },
+            signer_seeds,
+        ),
+        args,
+    )
+}
+
+/// Whether `session_pubkey` is an active session key of `user_account` granting
+/// `required` for `scope`, from `validate_session`'s return data. Pass the key's
+/// `SessionKeyAccount` when it isn't stored inline. Checking that the key signed the
+/// caller's instruction is still up to the caller.
+pub fn validate<'info>(
+    time_program: AccountInfo<'info>,
+    user_account: AccountInfo<'info>,
+    session_key_account: Option<AccountInfo<'info>>,
+    session_pubkey: Pubkey,
+    required: SessionPermissions,
+    scope: Option<Pubkey>,
+) -> Result<bool> {
+    let valid = cpi::validate_session(
+        CpiContext::new(
+            time_program,
+            cpi::accounts::ValidateSession {
+                user_account,
+                session_key_account,
+            },
+        ),
+        session_pubkey,
+        required,
+        scope,
+    )?;
+    Ok(valid.get())
+}
+
+/// Accounts of a plain `spl_delegated_transfer`: no attestation, blocklist, budget,
+/// stats or other per-account extras
+pub struct DelegatedTransfer<'info> {
+    pub time_program: AccountInfo<'info>,
+    pub session_signer: AccountInfo<'info>,
+    pub user_account: AccountInfo<'info>,
+    pub program_config: AccountInfo<'info>,
+    pub from_token: AccountInfo<'info>,
+    pub to_token: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub delegate_authority: AccountInfo<'info>,
+    /// The treasury's token account for `mint`, required while a protocol fee is set
+    pub fee_token: Option<AccountInfo<'info>>,
+    pub token_program: AccountInfo<'info>,
+}
+
+/// Move `amount` from `from_token` to `to_token` with `spl_delegated_transfer`, signed
+/// by the session key, for a key scoped to `scope` if it has one
+pub fn spl_delegated_transfer(
+    accounts: DelegatedTransfer<'_>,
+    amount: u64,
+    scope: Option<Pubkey>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    cpi::spl_delegated_transfer(
+        CpiContext::new_with_signer(
+            accounts.time_program,
+            cpi::accounts::SplDelegatedTransfer {
+                session_signer: accounts.session_signer,
+                user_account: accounts.user_account,
+                program_config: accounts.program_config,
+                session_key_account: None,
+                policy: None,
+                from_token: accounts.from_token,
+                to_token: accounts.to_token,
+                mint: accounts.mint,
+                delegate_authority: accounts.delegate_authority,
+                session_attestation: None,
+                recipient_attestation: None,
+                blocklist: None,
+                recipient_consent: None,
+                operator_bond: None,
+                operator_reputation: None,
+                spend_budget: None,
+                activity_digest: None,
+                account_stats: None,
+                savings_vault: None,
+                fee_token: accounts.fee_token,
+                slot_hashes: None,
+                gas_tank: None,
+                instructions_sysvar: None,
+                token_program: accounts.token_program,
+            },
+            signer_seeds,
+        ),
+        amount,
+        None,
+        None,
+        scope,
+        None,
+    )
+}
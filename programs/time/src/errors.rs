@@ -33,4 +33,360 @@ pub enum ErrorCode {
 
     #[msg("Too many allowed mints provided")]
     TooManyAllowedMints,
+
+    #[msg("Guard program account must be passed as the first remaining account")]
+    GuardProgramMissing,
+
+    #[msg("Guard program account does not match the configured guard program")]
+    GuardProgramMismatch,
+
+    #[msg("Attestation token account is required for this execution")]
+    AttestationMissing,
+
+    #[msg("Attestation token account is not held by the required party")]
+    AttestationInvalid,
+
+    #[msg("Signer is not authorized to perform this admin action")]
+    Unauthorized,
+
+    #[msg("Blocklist registry is full")]
+    BlocklistFull,
+
+    #[msg("Blocklist registry account is required when enforcement is enabled")]
+    BlocklistMissing,
+
+    #[msg("This account requires the session operator to post a bond")]
+    BondRequired,
+
+    #[msg("Operator bond is below the required amount or in the wrong asset")]
+    BondInsufficient,
+
+    #[msg("Operator bond is being released and can no longer back executions")]
+    BondReleasePending,
+
+    #[msg("Bond cannot be released while its session key is still valid")]
+    SessionKeyStillActive,
+
+    #[msg("Bond is still within its dispute window")]
+    BondLocked,
+
+    #[msg("Dispute window for this bond has closed")]
+    DisputeWindowClosed,
+
+    #[msg("Token accounts for an SPL bond are missing or do not match")]
+    BondTokenAccountsInvalid,
+
+    #[msg("Amount must be greater than zero and within available funds")]
+    InvalidAmount,
+
+    #[msg("Dispute cannot be resolved before its timelock elapses")]
+    DisputeTimelockActive,
+
+    #[msg("Dispute has already been resolved")]
+    DisputeAlreadyResolved,
+
+    #[msg("Violation report is not upheld, already applied, or for another session")]
+    ReportNotSlashable,
+
+    #[msg("Bond has unresolved violation reports")]
+    DisputesOpen,
+
+    #[msg("Reputation account of the bonded operator is required")]
+    OperatorReputationMismatch,
+
+    #[msg("Too many session keys listed for this service")]
+    TooManyServiceSessionKeys,
+
+    #[msg("Preset changed since it was reviewed")]
+    PresetVersionMismatch,
+
+    #[msg("Session duration exceeds the preset's maximum")]
+    PresetDurationExceeded,
+
+    #[msg("Spend budget account for this mint is required")]
+    SpendBudgetMissing,
+
+    #[msg("Transfer exceeds the account's spend budget for this window")]
+    SpendBudgetExceeded,
+
+    #[msg("Maximum number of spend budgets reached")]
+    TooManySpendBudgets,
+
+    #[msg("Activity digest account is required when digests are enabled")]
+    ActivityDigestMissing,
+
+    #[msg("Digest interval must be between 1 and the receipt tree capacity")]
+    InvalidDigestInterval,
+
+    #[msg("Transfer would exceed the session key's cumulative spend cap")]
+    SessionSpendCapExceeded,
+
+    #[msg("Transfer would exceed the session key's limit for the current spend window")]
+    DailyLimitExceeded,
+
+    #[msg("Account stats are required when history tracking is enabled")]
+    AccountStatsMissing,
+
+    #[msg("Execution history tree is full")]
+    HistoryFull,
+
+    #[msg("Session key exceeded its maximum uses for the current window")]
+    RateLimitExceeded,
+
+    #[msg("Session key has used up its maximum number of executions")]
+    SessionKeyExhausted,
+
+    #[msg("Session key is not valid yet")]
+    SessionKeyNotYetValid,
+
+    #[msg("Activation time must be in the future and before the session expires")]
+    InvalidActivationTime,
+
+    #[msg("Scheduled session can't be activated yet")]
+    ScheduledSessionNotDue,
+
+    #[msg("Too many blackout windows")]
+    TooManyBlackoutWindows,
+
+    #[msg("Blackout window must end after it starts")]
+    InvalidBlackoutWindow,
+
+    #[msg("Session executions are halted during a blackout window")]
+    BlackoutActive,
+
+    #[msg("Max slot age exceeds the slots kept in SlotHashes")]
+    InvalidMaxSlotAge,
+
+    #[msg("A recent slot reference and the SlotHashes sysvar are required")]
+    SlotProofRequired,
+
+    #[msg("Referenced slot hash is too old or does not match SlotHashes")]
+    SlotProofInvalid,
+
+    #[msg("Too many allowed recipients for a session key")]
+    TooManyAllowedRecipients,
+
+    #[msg("Recipient is not on the session key's allowlist")]
+    RecipientNotAllowed,
+
+    #[msg("Pipeline needs between 1 and MAX_PIPELINE_STEPS valid steps")]
+    InvalidPipeline,
+
+    #[msg("Pipeline step arguments or accounts do not match the template")]
+    PipelineStepMismatch,
+
+    #[msg("Pipeline step exceeds its limit")]
+    PipelineStepLimitExceeded,
+
+    #[msg("Too many allowed mints for a session key")]
+    TooManySessionMints,
+
+    #[msg("Savings rule needs a percentage of at most 100% or a round-up unit")]
+    InvalidSavingsRule,
+
+    #[msg("Savings vault account is required for this mint")]
+    SavingsVaultMissing,
+
+    #[msg("Savings vault does not match the account's savings rule")]
+    SavingsVaultMismatch,
+
+    #[msg("Too many allowed programs for a session key")]
+    TooManyAllowedPrograms,
+
+    #[msg("Target program is not on the session key's allowlist")]
+    ProgramNotAllowed,
+
+    #[msg("Too many allowed instruction discriminators for a session key")]
+    TooManyAllowedDiscriminators,
+
+    #[msg("Instruction is not on the session key's discriminator allowlist")]
+    InstructionNotAllowed,
+
+    #[msg("Too many spend categories")]
+    TooManySpendCategories,
+
+    #[msg("Spend category ids must be unique")]
+    DuplicateSpendCategory,
+
+    #[msg("Spend category is not in the account's taxonomy")]
+    UnknownSpendCategory,
+
+    #[msg("Discriminator filters must compare 1 to 8 bytes and name an allowed program")]
+    InvalidDiscriminatorFilter,
+
+    #[msg("Permission widening delay exceeds the maximum")]
+    InvalidWideningDelay,
+
+    #[msg("Vault balance above the rent reserve is too low")]
+    InsufficientVaultBalance,
+
+    #[msg("Gas tank fee exceeds the per-execution maximum")]
+    InvalidGasFee,
+
+    #[msg("Too many token accounts with an open delegate approval")]
+    TooManyDelegations,
+
+    #[msg("Revoke or let expire every session key before closing the account")]
+    SessionKeysStillActive,
+
+    #[msg("Revoke every token delegate approval before closing the account")]
+    DelegationsStillActive,
+
+    #[msg("Session key capacity must fit the existing keys and stay within the maximum")]
+    InvalidSessionKeyCapacity,
+
+    #[msg("Close every session key account before closing the user account")]
+    SessionKeyAccountsOpen,
+
+    #[msg("Payload doesn't match the archived account state")]
+    ArchiveMismatch,
+
+    #[msg("Account state is too large to archive")]
+    ArchiveTooLarge,
+
+    #[msg("Mint has a permanent delegate; opt in before using it")]
+    PermanentDelegateNotAllowed,
+
+    #[msg("Session key is suspended")]
+    SessionKeySuspended,
+
+    #[msg("Session key is not suspended")]
+    SessionKeyNotSuspended,
+
+    #[msg("Custom action invoked at a deeper CPI level than the session key allows")]
+    CpiDepthExceeded,
+
+    #[msg("Custom action forwards more accounts than the session key allows")]
+    TooManyCustomAccounts,
+
+    #[msg("Policy document is malformed or too large")]
+    InvalidPolicy,
+
+    #[msg("Policy document version is not supported by this program")]
+    PolicyVersionUnsupported,
+
+    #[msg("Account has a policy; pass its policy account")]
+    PolicyRequired,
+
+    #[msg("Spend rejected by the account's policy")]
+    PolicyViolation,
+
+    #[msg("Close the account's policy first")]
+    PolicyOpen,
+
+    #[msg("A session key can only narrow its own permissions or expiry")]
+    RestrictionWidens,
+
+    #[msg("No kill phrase is set for this account")]
+    KillPhraseNotSet,
+
+    #[msg("Kill phrase does not match the account's commitment")]
+    KillPhraseMismatch,
+
+    #[msg("Account is paused")]
+    AccountPaused,
+
+    #[msg("No expired session keys to clean up")]
+    NothingToCleanUp,
+
+    #[msg("Session key is locked after repeated refused spends")]
+    SessionKeyLocked,
+
+    #[msg("Lockout policy needs non-zero failures, window and lock duration")]
+    InvalidLockoutPolicy,
+
+    #[msg("A sub-key can't exceed the key delegating it")]
+    DelegationWidens,
+
+    #[msg("Recipient hasn't accepted payouts from this account")]
+    RecipientConsentMissing,
+
+    #[msg("Sub-key would be nested deeper than the account allows")]
+    DelegationTooDeep,
+
+    #[msg("Transfer needs the bonded operator's signature over the action")]
+    OperatorSignatureMissing,
+
+    #[msg("Signer is not this account's guardian")]
+    NotGuardian,
+
+    #[msg("Custom action into this program needs the spend budget that caps it")]
+    ProgramBudgetMissing,
+
+    #[msg("Custom action exceeds the spend budget for this program")]
+    ProgramBudgetExceeded,
+
+    #[msg(
+        "Maximum number of program budgets reached, or the program is capped under another mint"
+    )]
+    TooManyProgramBudgets,
+
+    #[msg("Session key is scoped to a different dapp")]
+    SessionScopeMismatch,
+
+    #[msg("Session key was granted from a different dapp origin")]
+    SessionDomainMismatch,
+
+    #[msg("The program is paused by its admin")]
+    ProgramPaused,
+
+    #[msg("Transfer exceeds the program-wide limit")]
+    GlobalLimitExceeded,
+
+    #[msg("Mint is on the program-wide denylist")]
+    MintDenied,
+
+    #[msg("Mint denylist is full")]
+    MintDenylistFull,
+
+    #[msg("Protocol fee exceeds the maximum")]
+    InvalidProtocolFee,
+
+    #[msg("Protocol fee account is missing")]
+    FeeAccountMissing,
+
+    #[msg("Fee token account is not the treasury's account for this mint")]
+    FeeAccountMismatch,
+
+    #[msg("Insufficient treasury balance")]
+    InsufficientTreasuryBalance,
+
+    #[msg("User account already uses the current layout")]
+    AccountAlreadyMigrated,
+
+    #[msg("Source token account is not owned by the account authority")]
+    SourceTokenOwnerMismatch,
+
+    #[msg("Source token account does not hold the transferred mint")]
+    SourceTokenMintMismatch,
+
+    #[msg("Destination token account is not owned by the expected recipient")]
+    RecipientMismatch,
+
+    #[msg("Destination is not the recipient's associated token account")]
+    RecipientNotCanonicalAta,
+
+    #[msg("Swap step returned less than its minimum output")]
+    SwapOutputTooLow,
+
+    #[msg("Session SOL payouts are refused while the account enforces token-only controls")]
+    SolSpendUnsupported,
+
+    #[msg("Custom actions are refused while the account enforces token-only controls")]
+    CustomActionUnsupported,
+
+    #[msg("Pipelines are refused while the account or program applies controls their steps can't")]
+    PipelineControlsUnsupported,
+
+    #[msg("Swap steps can't be handed token accounts the delegate can spend besides their source")]
+    SwapAccountNotAllowed,
+
+    #[msg("No lockout policy is set to count refusals toward")]
+    LockoutPolicyMissing,
+
+    #[msg("The session key would not refuse this spend")]
+    SpendNotRefused,
+
+    #[msg("The recipient is on the program blocklist")]
+    RecipientBlocked,
 }
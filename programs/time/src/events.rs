@@ -1,4 +1,7 @@
-use crate::state::SessionPermissions;
+use crate::state::{
+    AttestationGate, BlackoutWindow, BondRequirement, DiscriminatorFilter, DisputeStatus,
+    LockoutPolicy, PipelineStep, RevocationReason, SavingsRule, SessionPermissions, SpendCategory,
+};
 use anchor_lang::prelude::*;
 
 // ===== EVENTS =====
@@ -7,22 +10,59 @@ use anchor_lang::prelude::*;
 pub struct SessionKeyCreated {
     pub authority: Pubkey,
     pub session_key: Pubkey,
-    pub expires_at: i64,
+    pub expires_at_time: Option<i64>,
+    pub expires_at_slot: Option<i64>,
     pub permissions: SessionPermissions,
+    pub terms_hash: [u8; 32],
+    /// Display label, UTF-8 padded with zeros
+    pub label: [u8; 32],
+    pub valid_from: i64,
+    pub allowed_recipients: Vec<Pubkey>,
+    pub allowed_mints: Vec<Pubkey>,
+    pub allowed_programs: Vec<Pubkey>,
+    pub allowed_discriminators: Vec<DiscriminatorFilter>,
+    /// Idle timeout of a sliding key (0 = fixed expiry)
+    pub idle_timeout: u32,
+    /// Cap on a sliding key's `expires_at_time` (0 = not sliding)
+    pub max_expires_at: i64,
+    /// Dapp the key is bound to (default pubkey = unscoped)
+    pub scope: Pubkey,
+    /// SHA-256 of the dapp origin that requested the grant (zero if none)
+    pub domain_hash: [u8; 32],
+    /// Created through a deprecated instruction version
+    pub deprecated: bool,
+}
+
+#[event]
+pub struct SessionKeyTransferred {
+    pub authority: Pubkey,
+    pub old_session_key: Pubkey,
+    pub new_session_key: Pubkey,
+    /// False when the old key handed itself over
+    pub by_authority: bool,
 }
 
 #[event]
 pub struct SessionKeyRevoked {
     pub authority: Pubkey,
     pub session_key: Pubkey,
+    pub terms_hash: [u8; 32],
+    pub reason: RevocationReason,
 }
 
 #[event]
 pub struct SessionKeyUpdated {
     pub authority: Pubkey,
     pub session_key: Pubkey,
-    pub expires_at: i64,
+    pub expires_at_time: Option<i64>,
+    pub expires_at_slot: Option<i64>,
+    /// Cap on a sliding key's `expires_at_time` (0 = not sliding)
+    pub max_expires_at: i64,
     pub permissions: SessionPermissions,
+    /// When widened `permissions` take effect (0 = immediately)
+    pub permissions_active_at: i64,
+    pub terms_hash: [u8; 32],
+    pub label: [u8; 32],
 }
 
 #[event]
@@ -30,3 +70,593 @@ pub struct AllSessionKeysRevoked {
     pub authority: Pubkey,
     pub count: u32,
 }
+
+#[event]
+pub struct BlocklistUpdated {
+    pub admin: Pubkey,
+    pub added: u32,
+    pub removed: u32,
+    pub total: u32,
+}
+
+#[event]
+pub struct BlocklistEnforcementUpdated {
+    pub authority: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct TransferBlocked {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AttestationGateUpdated {
+    pub authority: Pubkey,
+    pub attestation_gate: Option<AttestationGate>,
+}
+
+#[event]
+pub struct GuardProgramUpdated {
+    pub authority: Pubkey,
+    pub guard_program: Option<Pubkey>,
+}
+
+#[event]
+pub struct BondRequirementUpdated {
+    pub authority: Pubkey,
+    pub bond_requirement: Option<BondRequirement>,
+}
+
+#[event]
+pub struct OperatorSignatureRequirementUpdated {
+    pub authority: Pubkey,
+    pub required: bool,
+}
+
+/// An execution the bonded operator countersigned; `receipt` is the leaf committed to
+/// the account's history and digest
+#[event]
+pub struct ExecutionAttested {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub operator: Pubkey,
+    pub receipt: [u8; 32],
+    pub signature: [u8; 64],
+}
+
+#[event]
+pub struct BondPosted {
+    pub user_account: Pubkey,
+    pub session_key: Pubkey,
+    pub operator: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BondReleaseRequested {
+    pub user_account: Pubkey,
+    pub session_key: Pubkey,
+    pub releasable_at: i64,
+}
+
+#[event]
+pub struct BondSlashed {
+    pub user_account: Pubkey,
+    pub session_key: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+    /// Upheld violation report justifying the slash
+    pub report: Pubkey,
+    /// Signature of the offending transaction
+    pub evidence: [u8; 64],
+}
+
+#[event]
+pub struct BondWithdrawn {
+    pub user_account: Pubkey,
+    pub session_key: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ViolationReported {
+    pub user_account: Pubkey,
+    pub session_key: Pubkey,
+    pub reporter: Pubkey,
+    pub report: Pubkey,
+    pub evidence: [u8; 64],
+    pub resolvable_at: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub user_account: Pubkey,
+    pub session_key: Pubkey,
+    pub report: Pubkey,
+    pub status: DisputeStatus,
+}
+
+#[event]
+pub struct OperatorReputationInitialized {
+    pub operator: Pubkey,
+}
+
+#[event]
+pub struct ServiceRegistered {
+    pub operator: Pubkey,
+    pub name: [u8; 32],
+    pub url_hash: [u8; 32],
+    pub session_keys: Vec<Pubkey>,
+}
+
+#[event]
+pub struct ServiceVerificationUpdated {
+    pub operator: Pubkey,
+    pub verified: bool,
+}
+
+#[event]
+pub struct PresetPublished {
+    pub service: Pubkey,
+    pub preset: Pubkey,
+    pub preset_id: u16,
+    pub version: u32,
+    pub previous_permissions: Option<SessionPermissions>,
+    pub permissions: SessionPermissions,
+    pub max_duration_seconds: i64,
+}
+
+#[event]
+pub struct SessionKeyCreatedFromPreset {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub preset: Pubkey,
+    pub preset_version: u32,
+}
+
+#[event]
+pub struct SpendBudgetUpdated {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub limit: u64,
+    pub window_seconds: i64,
+}
+
+#[event]
+pub struct SpendBudgetClosed {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct TransferExecuted {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    /// Spend category from the account's taxonomy, if tagged
+    pub category: Option<u16>,
+}
+
+/// Aggregate of `count` executions, replacing their individual `TransferExecuted` events.
+/// `receipts_root` commits to each execution's `receipt_leaf`, in execution order.
+#[event]
+pub struct ActivityDigestEmitted {
+    pub user_account: Pubkey,
+    pub sequence: u64,
+    pub count: u64,
+    pub total_amount: u128,
+    pub receipts_root: [u8; 32],
+    pub first_slot: u64,
+    pub last_slot: u64,
+}
+
+#[event]
+pub struct DigestIntervalUpdated {
+    pub authority: Pubkey,
+    pub digest_interval: u32,
+}
+
+#[event]
+pub struct AccountStatsInitialized {
+    pub authority: Pubkey,
+    pub account_stats: Pubkey,
+}
+
+#[event]
+pub struct SessionKeyScheduled {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub activate_at: i64,
+    pub expires_at_time: Option<i64>,
+    pub expires_at_slot: Option<i64>,
+}
+
+#[event]
+pub struct ScheduledSessionCancelled {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct BlackoutWindowsUpdated {
+    pub authority: Pubkey,
+    pub windows: Vec<BlackoutWindow>,
+}
+
+#[event]
+pub struct MaxSlotAgeUpdated {
+    pub authority: Pubkey,
+    pub max_slot_age: u32,
+}
+
+#[event]
+pub struct WideningDelayUpdated {
+    pub authority: Pubkey,
+    pub widening_delay_seconds: u32,
+}
+
+#[event]
+pub struct PipelineCreated {
+    pub authority: Pubkey,
+    pub pipeline: Pubkey,
+    pub pipeline_id: u16,
+    pub steps: Vec<PipelineStep>,
+}
+
+#[event]
+pub struct PipelineClosed {
+    pub authority: Pubkey,
+    pub pipeline: Pubkey,
+}
+
+#[event]
+pub struct PipelineExecuted {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub pipeline: Pubkey,
+    /// Amount moved by each step: transferred, or spent by the swap
+    pub amounts: Vec<u64>,
+}
+
+#[event]
+pub struct SavingsRuleUpdated {
+    pub authority: Pubkey,
+    pub savings_rule: Option<SavingsRule>,
+}
+
+#[event]
+pub struct SavingsDiverted {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    /// Amount of the outgoing transfer the savings were computed from
+    pub amount: u64,
+    pub saved: u64,
+}
+
+#[event]
+pub struct CustomActionExecuted {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub program: Pubkey,
+    pub data_len: u32,
+}
+
+#[event]
+pub struct SpendCategoriesUpdated {
+    pub authority: Pubkey,
+    pub categories: Vec<SpendCategory>,
+}
+
+#[event]
+pub struct SolDeposited {
+    pub authority: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SolWithdrawn {
+    pub authority: Pubkey,
+    /// Session key that withdrew, or None for the authority
+    pub session_key: Option<Pubkey>,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct GasTankFeeUpdated {
+    pub authority: Pubkey,
+    pub gas_tank: Pubkey,
+    pub fee_per_execution: u64,
+}
+
+#[event]
+pub struct GasTankToppedUp {
+    pub authority: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct GasReimbursed {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct UserAccountClosed {
+    pub authority: Pubkey,
+    /// Rent plus vault balance returned to the authority
+    pub lamports: u64,
+}
+
+#[event]
+pub struct UserAccountResized {
+    pub authority: Pubkey,
+    pub old_capacity: u16,
+    pub new_capacity: u16,
+}
+
+#[event]
+pub struct SessionKeyAccountClosed {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct UserAccountArchived {
+    pub authority: Pubkey,
+    pub state_hash: [u8; 32],
+    /// Rent plus vault balance returned to the authority
+    pub lamports: u64,
+    /// Borsh-serialized state to pass to `restore_user_account`
+    pub data: Vec<u8>,
+}
+
+#[event]
+pub struct UserAccountRestored {
+    pub authority: Pubkey,
+    pub state_hash: [u8; 32],
+}
+
+#[event]
+pub struct PermanentDelegateOptInUpdated {
+    pub authority: Pubkey,
+    pub allowed: bool,
+}
+
+/// Risk flags of a mint being allowlisted or approved, for wallets to warn on
+#[event]
+pub struct MintRiskAssessed {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    /// `MINT_RISK_*` bits from `mint_risk`
+    pub flags: u8,
+}
+
+#[event]
+pub struct SessionKeySuspended {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct SessionKeyResumed {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct PolicyUpdated {
+    pub authority: Pubkey,
+    pub version: u8,
+    pub rules: u8,
+}
+
+#[event]
+pub struct PolicyClosed {
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct SessionKeyRotated {
+    pub authority: Pubkey,
+    pub old_session_key: Pubkey,
+    pub new_session_key: Pubkey,
+}
+
+#[event]
+pub struct SessionKeysCleaned {
+    pub authority: Pubkey,
+    pub removed_count: u32,
+    /// Inline keys still stored after the cleanup
+    pub remaining_count: u32,
+    /// Removable keys left for another call because of the limit
+    pub pending_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KillPhraseSet {
+    pub authority: Pubkey,
+    /// False when the commitment was cleared
+    pub enabled: bool,
+}
+
+#[event]
+pub struct KillPhraseTriggered {
+    pub authority: Pubkey,
+    pub triggered_by: Pubkey,
+    /// Inline keys revoked by the trigger
+    pub revoked: u32,
+}
+
+#[event]
+pub struct AccountPauseChanged {
+    pub authority: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct CleanupCranked {
+    pub authority: Pubkey,
+    pub cranker: Pubkey,
+    pub removed: u32,
+    /// Lamports paid to the cranker from the SOL vault
+    pub bounty: u64,
+}
+
+#[event]
+pub struct LockoutPolicyUpdated {
+    pub authority: Pubkey,
+    pub lockout_policy: Option<LockoutPolicy>,
+}
+
+/// A session spend refused and counted toward the account's lockout policy
+#[event]
+pub struct SpendAttemptFailed {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    /// Program error code the spend was refused with
+    pub error_code: u32,
+    /// Refusals in the current window; reset to 0 when the key locks
+    pub failed_attempts: u8,
+}
+
+#[event]
+pub struct SessionKeyLocked {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub locked_until: i64,
+}
+
+#[event]
+pub struct SessionKeyDelegated {
+    pub authority: Pubkey,
+    /// Session key that created the sub-key
+    pub delegated_by: Pubkey,
+    pub session_key: Pubkey,
+    /// Lifetime cap and uses reserved out of the delegating key
+    pub reserved_amount: u64,
+    pub reserved_uses: u32,
+}
+
+#[event]
+pub struct MaxDelegationDepthUpdated {
+    pub authority: Pubkey,
+    pub max_delegation_depth: u8,
+}
+
+#[event]
+pub struct RecipientConsentRequirementUpdated {
+    pub authority: Pubkey,
+    pub required: bool,
+}
+
+#[event]
+pub struct RecipientConsentChanged {
+    pub user_account: Pubkey,
+    pub recipient: Pubkey,
+    /// False when the recipient withdrew its consent
+    pub accepted: bool,
+}
+
+#[event]
+pub struct CleanupRetentionUpdated {
+    pub authority: Pubkey,
+    pub retention_seconds: u32,
+}
+
+#[event]
+pub struct BuildHashPublished {
+    pub build_hash: [u8; 32],
+    pub source_commit: [u8; 20],
+    pub deployment_slot: u64,
+}
+
+#[event]
+pub struct GuardianSet {
+    pub authority: Pubkey,
+    /// None when the guardian was removed
+    pub guardian: Option<Pubkey>,
+}
+
+#[event]
+pub struct GuardianRevokedAll {
+    pub authority: Pubkey,
+    pub guardian: Pubkey,
+    /// Inline keys revoked by the guardian
+    pub revoked: u32,
+}
+
+#[event]
+pub struct ProgramBudgetUpdated {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub program: Pubkey,
+    /// None when the cap was removed
+    pub limit: Option<u64>,
+}
+
+#[event]
+pub struct ProgramPauseSet {
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct ProgramConfigUpdated {
+    pub admin: Pubkey,
+    /// Per-transfer cap across all accounts (0 = none)
+    pub max_transfer_amount: u64,
+}
+
+#[event]
+pub struct MintDenylistUpdated {
+    pub admin: Pubkey,
+    pub added: u32,
+    pub removed: u32,
+    pub total: u32,
+}
+
+#[event]
+pub struct ProtocolFeeSet {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct ProtocolFeeCollected {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    /// Native mint for SOL transfers
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub admin: Pubkey,
+    /// None for lamports
+    pub mint: Option<Pubkey>,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct UserAccountMigrated {
+    pub authority: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
@@ -0,0 +1,123 @@
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+
+// ===== GUARD PROGRAMS =====
+
+/// Instruction discriminator a guard program must handle: `sha256("global:check_action")[..8]`,
+/// so an Anchor guard can simply expose a `check_action(check: GuardCheck)` instruction.
+pub const GUARD_CHECK_DISCRIMINATOR: [u8; 8] = [43, 242, 57, 110, 178, 244, 40, 235];
+
+/// Action being attempted by a session key, as described to the guard program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum GuardedAction {
+    /// Delegated SPL token transfer through the delegate PDA
+    SplTransfer {
+        mint: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+    },
+    /// Custom action CPI into `program` through the executor PDA; `discriminator` is the
+    /// start of its instruction data, zero padded
+    Custom {
+        program: Pubkey,
+        discriminator: [u8; 8],
+    },
+    /// Payout pipeline run, checked once its steps have run with the `total` they moved;
+    /// a veto reverts them
+    Pipeline { pipeline: Pubkey, total: u64 },
+    /// Lamports paid from the SOL vault to `destination`
+    SolTransfer { destination: Pubkey, amount: u64 },
+}
+
+impl GuardedAction {
+    /// Describe a custom action sending `data` to `program`
+    pub fn custom(program: Pubkey, data: &[u8]) -> Self {
+        let mut discriminator = [0u8; 8];
+        let len = data.len().min(8);
+        discriminator[..len].copy_from_slice(&data[..len]);
+        GuardedAction::Custom {
+            program,
+            discriminator,
+        }
+    }
+}
+
+/// Payload passed to the guard program. Returning an error from the guard vetoes the action.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct GuardCheck {
+    pub user_account: Pubkey,
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub action: GuardedAction,
+}
+
+/// Remaining accounts left for the action itself: everything after the guard program when
+/// the account has one, since it always comes first
+pub fn action_accounts<'a, 'info>(
+    guard_program: Option<Pubkey>,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> &'a [AccountInfo<'info>] {
+    match guard_program {
+        Some(_) => remaining_accounts.get(1..).unwrap_or_default(),
+        None => remaining_accounts,
+    }
+}
+
+/// CPI into the account's guard program, if one is configured.
+///
+/// The guard program must be the first remaining account; any further remaining accounts
+/// are forwarded to it untouched (oracles, attestation accounts, or the accounts of a
+/// custom action or pipeline). The guard always receives
+/// `[user_account, session_signer, ..forwarded]` as its leading accounts.
+pub fn enforce_guard<'info>(
+    guard_program: Option<Pubkey>,
+    user_account: &AccountInfo<'info>,
+    session_signer: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    check: &GuardCheck,
+) -> Result<()> {
+    let Some(guard_program) = guard_program else {
+        return Ok(());
+    };
+
+    let (guard_info, forwarded) = remaining_accounts
+        .split_first()
+        .ok_or(ErrorCode::GuardProgramMissing)?;
+    require_keys_eq!(
+        guard_info.key(),
+        guard_program,
+        ErrorCode::GuardProgramMismatch
+    );
+    require!(guard_info.executable, ErrorCode::GuardProgramMismatch);
+
+    let mut data = GUARD_CHECK_DISCRIMINATOR.to_vec();
+    check.serialize(&mut data)?;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(user_account.key(), false),
+        AccountMeta::new_readonly(session_signer.key(), true),
+    ];
+    accounts.extend(forwarded.iter().map(|a| {
+        if a.is_writable {
+            AccountMeta::new(a.key(), a.is_signer)
+        } else {
+            AccountMeta::new_readonly(a.key(), a.is_signer)
+        }
+    }));
+
+    let mut infos = vec![user_account.clone(), session_signer.clone()];
+    infos.extend(forwarded.iter().cloned());
+    infos.push(guard_info.clone());
+
+    invoke(
+        &Instruction {
+            program_id: guard_program,
+            accounts,
+            data,
+        },
+        &infos,
+    )
+    .map_err(Into::into)
+}
@@ -0,0 +1,27 @@
+use crate::contexts::InitializeAccountStats;
+use crate::events::AccountStatsInitialized;
+use crate::receipts::MerkleFrontier;
+use anchor_lang::prelude::*;
+
+/// Start recording every execution into the account's lifetime history tree
+pub fn initialize_handler(ctx: Context<InitializeAccountStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.account_stats;
+    stats.user_account = ctx.accounts.user_account.key();
+    stats.total_volume = 0;
+    stats.last_execution_at = 0;
+    stats.history = MerkleFrontier::default();
+    stats.history_root = stats.history.root();
+    stats.bump = ctx.bumps.account_stats;
+    stats.category_totals = Vec::new();
+    stats.session_heatmaps = Vec::new();
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.stats_enabled = true;
+
+    emit!(AccountStatsInitialized {
+        authority: user_account.authority,
+        account_stats: stats.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::constants::DIGEST_TREE_DEPTH;
+use crate::contexts::{EnableActivityDigest, SetDigestInterval};
+use crate::errors::ErrorCode;
+use crate::events::{ActivityDigestEmitted, DigestIntervalUpdated};
+use crate::receipts::MerkleFrontier;
+use crate::state::{ActivityDigest, UserAccount};
+use anchor_lang::prelude::*;
+
+/// Create the digest PDA and start aggregating executions every `interval` actions
+pub fn enable_handler(ctx: Context<EnableActivityDigest>, interval: u32) -> Result<()> {
+    validate_interval(interval)?;
+
+    let digest = &mut ctx.accounts.activity_digest;
+    digest.user_account = ctx.accounts.user_account.key();
+    digest.sequence = 0;
+    digest.reset();
+    digest.bump = ctx.bumps.activity_digest;
+
+    set_interval(&mut ctx.accounts.user_account, interval)
+}
+
+/// Change the interval (0 switches back to per-execution events), flushing any partial batch
+pub fn set_interval_handler(ctx: Context<SetDigestInterval>, interval: u32) -> Result<()> {
+    if interval > 0 {
+        validate_interval(interval)?;
+    }
+    flush(&mut ctx.accounts.activity_digest, Clock::get()?.slot);
+    set_interval(&mut ctx.accounts.user_account, interval)
+}
+
+/// Add an execution receipt to the current batch, emitting the digest once it holds
+/// `interval` receipts
+pub fn record_execution(
+    digest: &mut ActivityDigest,
+    interval: u32,
+    leaf: [u8; 32],
+    amount: u64,
+    slot: u64,
+) {
+    if digest.receipts.count == 0 {
+        digest.first_slot = slot;
+    }
+    digest.receipts.append(leaf);
+    digest.total_amount += amount as u128;
+
+    if digest.receipts.count >= interval as u64 {
+        flush(digest, slot);
+    }
+}
+
+/// Emit the pending batch, if any, and start a new one
+fn flush(digest: &mut ActivityDigest, last_slot: u64) {
+    if digest.receipts.count == 0 {
+        return;
+    }
+
+    emit!(ActivityDigestEmitted {
+        user_account: digest.user_account,
+        sequence: digest.sequence,
+        count: digest.receipts.count,
+        total_amount: digest.total_amount,
+        receipts_root: digest.receipts.root(),
+        first_slot: digest.first_slot,
+        last_slot,
+    });
+
+    digest.sequence += 1;
+    digest.reset();
+}
+
+fn validate_interval(interval: u32) -> Result<()> {
+    require!(
+        interval > 0 && interval as u64 <= MerkleFrontier::<DIGEST_TREE_DEPTH>::CAPACITY,
+        ErrorCode::InvalidDigestInterval
+    );
+    Ok(())
+}
+
+fn set_interval(user_account: &mut UserAccount, interval: u32) -> Result<()> {
+    user_account.digest_interval = interval;
+
+    emit!(DigestIntervalUpdated {
+        authority: user_account.authority,
+        digest_interval: interval,
+    });
+
+    Ok(())
+}
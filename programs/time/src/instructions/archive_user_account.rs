@@ -0,0 +1,80 @@
+use crate::constants::{MAX_ARCHIVE_DATA_LEN, MAX_INIT_SESSION_KEY_CAPACITY};
+use crate::contexts::{ArchiveUserAccount, RestoreUserAccount};
+use crate::errors::ErrorCode;
+use crate::events::{UserAccountArchived, UserAccountRestored};
+use crate::state::UserAccount;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+/// Snapshot a dormant account into its archive PDA and close it, returning its rent and
+/// vault balance to the authority. Inactive session keys are dropped from the snapshot;
+/// the full state is emitted in `UserAccountArchived` for the later restore.
+pub fn archive_handler(ctx: Context<ArchiveUserAccount>) -> Result<()> {
+    let clock = Clock::get()?;
+    let user_account = &mut ctx.accounts.user_account;
+
+    require!(
+        user_account.session_key_accounts == 0,
+        ErrorCode::SessionKeyAccountsOpen
+    );
+    require!(
+        user_account
+            .session_keys
+            .iter()
+            .all(|k| !k.is_valid(&clock)),
+        ErrorCode::SessionKeysStillActive
+    );
+    user_account.session_keys.clear();
+    // The restore recreates the account with `init`, which can't allocate a larger one;
+    // `resize_user_account` can grow it again afterwards
+    user_account.session_key_capacity = user_account
+        .session_key_capacity
+        .min(MAX_INIT_SESSION_KEY_CAPACITY as u16);
+
+    let data = user_account.try_to_vec()?;
+    // Otherwise the state could never be restored in a single transaction
+    require!(
+        data.len() <= MAX_ARCHIVE_DATA_LEN,
+        ErrorCode::ArchiveTooLarge
+    );
+    let state_hash = hash(&data).to_bytes();
+
+    let archive = &mut ctx.accounts.archive;
+    archive.authority = user_account.authority;
+    archive.state_hash = state_hash;
+    archive.session_key_capacity = user_account.session_key_capacity;
+    archive.archived_at = clock.unix_timestamp;
+    archive.bump = ctx.bumps.archive;
+
+    emit!(UserAccountArchived {
+        authority: user_account.authority,
+        state_hash,
+        lamports: user_account.to_account_info().lamports(),
+        data,
+    });
+
+    Ok(())
+}
+
+/// Recreate an archived account from the payload `archive_user_account` emitted and
+/// close the archive
+pub fn restore_handler(ctx: Context<RestoreUserAccount>, data: Vec<u8>) -> Result<()> {
+    require!(
+        hash(&data).to_bytes() == ctx.accounts.archive.state_hash,
+        ErrorCode::ArchiveMismatch
+    );
+    let state = UserAccount::deserialize(&mut data.as_slice())?;
+    require_keys_eq!(
+        state.authority,
+        ctx.accounts.authority.key(),
+        ErrorCode::ArchiveMismatch
+    );
+    ctx.accounts.user_account.set_inner(state);
+
+    emit!(UserAccountRestored {
+        authority: ctx.accounts.authority.key(),
+        state_hash: ctx.accounts.archive.state_hash,
+    });
+
+    Ok(())
+}
@@ -1,19 +1,136 @@
-use crate::contexts::CleanupSessionKeys;
+use crate::constants::CLEANUP_BOUNTY_LAMPORTS;
+use crate::contexts::{CleanupSessionKeys, CrankCleanup, SetCleanupRetention};
+use crate::errors::ErrorCode;
+use crate::events::{CleanupCranked, CleanupRetentionUpdated, SessionKeysCleaned};
+use crate::instructions::sol_vault::pay_from_vault;
+use crate::state::SessionKey;
 use anchor_lang::prelude::*;
 
-/// Clean up expired or revoked session keys to save space
-pub fn handler(ctx: Context<CleanupSessionKeys>) -> Result<()> {
+/// Clean up expired or revoked session keys to save space, removing at most `limit`
+/// (0 = all) so large accounts can be cleaned over several transactions. Keys stay
+/// until they have been dead for the account's `retention_seconds`.
+pub fn handler(ctx: Context<CleanupSessionKeys>, limit: u8) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
     let clock = Clock::get()?;
+    let retention = user_account.retention_seconds;
 
-    let initial_count = user_account.session_keys.len();
+    let (removed, pending) = remove_keys(&mut user_account.session_keys, limit, |key| {
+        past_retention(key, retention, &clock)
+    });
 
-    // Remove expired and revoked keys
-    user_account.session_keys.retain(|key| key.is_valid(&clock));
+    msg!("Cleaned up {} expired/revoked session keys", removed);
 
-    let removed_count = initial_count - user_account.session_keys.len();
+    emit!(SessionKeysCleaned {
+        authority: user_account.authority,
+        removed_count: removed,
+        remaining_count: user_account.session_keys.len() as u32,
+        pending_count: pending,
+        timestamp: clock.unix_timestamp,
+    });
 
-    msg!("Cleaned up {} expired/revoked session keys", removed_count);
+    Ok(())
+}
+
+/// Permissionless cleanup of expired keys, so accounts stay tidy when their owner goes
+/// inactive. Revoked keys that haven't expired are the authority's record and stay, and
+/// so does any key still inside the account's `retention_seconds`.
+/// The cranker earns `CLEANUP_BOUNTY_LAMPORTS` per removed key from the SOL vault, as
+/// far as the balance above the rent reserve allows.
+pub fn crank_handler(ctx: Context<CrankCleanup>, limit: u8) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let clock = Clock::get()?;
+    let retention = user_account.retention_seconds;
+
+    let (removed, pending) = remove_keys(&mut user_account.session_keys, limit, |key| {
+        key.is_expired(&clock) && past_retention(key, retention, &clock)
+    });
+    require!(removed > 0, ErrorCode::NothingToCleanUp);
+
+    let vault = user_account.to_account_info();
+    let available = vault
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(vault.data_len()));
+    let bounty = (removed as u64)
+        .saturating_mul(CLEANUP_BOUNTY_LAMPORTS)
+        .min(available);
+    if bounty > 0 {
+        pay_from_vault(
+            user_account,
+            &ctx.accounts.cranker.to_account_info(),
+            bounty,
+        )?;
+    }
+
+    msg!(
+        "Cranked cleanup of {} expired session keys, bounty {}",
+        removed,
+        bounty
+    );
+
+    emit!(SessionKeysCleaned {
+        authority: user_account.authority,
+        removed_count: removed,
+        remaining_count: user_account.session_keys.len() as u32,
+        pending_count: pending,
+        timestamp: clock.unix_timestamp,
+    });
+    emit!(CleanupCranked {
+        authority: user_account.authority,
+        cranker: ctx.accounts.cranker.key(),
+        removed,
+        bounty,
+    });
+
+    Ok(())
+}
+
+/// Set how long dead session keys are kept before cleanup may remove them
+pub fn set_retention_handler(
+    ctx: Context<SetCleanupRetention>,
+    retention_seconds: u32,
+) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.retention_seconds = retention_seconds;
+
+    msg!("Cleanup retention set to: {}s", retention_seconds);
+
+    emit!(CleanupRetentionUpdated {
+        authority: user_account.authority,
+        retention_seconds,
+    });
 
     Ok(())
 }
+
+/// Whether `key` stopped being valid at least `retention` seconds ago
+fn past_retention(key: &SessionKey, retention: u32, clock: &Clock) -> bool {
+    key.ended_at(clock)
+        .is_some_and(|ended| ended.saturating_add(retention as i64) <= clock.unix_timestamp)
+}
+
+/// Remove up to `limit` (0 = all) keys matching `dead`, keeping any past the limit for
+/// the next call. Returns how many were removed and how many matching keys are left.
+fn remove_keys(
+    session_keys: &mut Vec<SessionKey>,
+    limit: u8,
+    dead: impl Fn(&SessionKey) -> bool,
+) -> (u32, u32) {
+    let limit = match limit {
+        0 => u32::MAX,
+        limit => limit as u32,
+    };
+    let mut removed = 0u32;
+    let mut pending = 0u32;
+    session_keys.retain(|key| {
+        if !dead(key) {
+            return true;
+        }
+        if removed < limit {
+            removed += 1;
+            return false;
+        }
+        pending += 1;
+        true
+    });
+    (removed, pending)
+}
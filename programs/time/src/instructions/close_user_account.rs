@@ -0,0 +1,68 @@
+use crate::contexts::CloseUserAccount;
+use crate::errors::ErrorCode;
+use crate::events::UserAccountClosed;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+/// Close the user account once nothing can act on it any more: every session key must
+/// be revoked, expired or used up, and every delegate approval revoked. Each tracked
+/// token account is passed in remaining accounts so approvals cleared directly through
+/// the token program, or on since-closed accounts, don't block the exit.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, CloseUserAccount<'info>>) -> Result<()> {
+    let clock = Clock::get()?;
+    let user_account = &ctx.accounts.user_account;
+
+    // Key accounts would outlive the account and revive if it were re-created
+    require!(
+        user_account.session_key_accounts == 0,
+        ErrorCode::SessionKeyAccountsOpen
+    );
+    require!(!user_account.has_policy, ErrorCode::PolicyOpen);
+    require!(
+        user_account
+            .session_keys
+            .iter()
+            .all(|k| !k.is_valid(&clock)),
+        ErrorCode::SessionKeysStillActive
+    );
+
+    for tracked in &user_account.delegated_token_accounts {
+        let info = ctx
+            .remaining_accounts
+            .iter()
+            .find(|a| a.key == tracked)
+            .ok_or(ErrorCode::DelegationsStillActive)?;
+        require!(
+            delegation_cleared(&user_account.key(), info, ctx.program_id)?,
+            ErrorCode::DelegationsStillActive
+        );
+    }
+
+    emit!(UserAccountClosed {
+        authority: user_account.authority,
+        lamports: user_account.to_account_info().lamports(),
+    });
+
+    Ok(())
+}
+
+/// Whether `info` no longer has this account's delegate PDA approved for a non-zero amount
+fn delegation_cleared<'info>(
+    user_account: &Pubkey,
+    info: &'info AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<bool> {
+    if info.data_is_empty() {
+        return Ok(true);
+    }
+    let token_account = InterfaceAccount::<TokenAccount>::try_from(info)?;
+    let (delegate_pda, _) = Pubkey::find_program_address(
+        &[
+            b"delegate",
+            user_account.as_ref(),
+            token_account.mint.as_ref(),
+        ],
+        program_id,
+    );
+    Ok(token_account.delegated_amount == 0 || !token_account.delegate.contains(&delegate_pda))
+}
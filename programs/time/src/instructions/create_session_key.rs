@@ -1,74 +1,249 @@
-use crate::constants::MAX_SESSION_KEYS;
-use crate::contexts::CreateSessionKey;
+use crate::constants::DEFAULT_SESSION_KEY_CAPACITY;
+use crate::constants::{
+    MAX_ALLOWED_DISCRIMINATORS, MAX_ALLOWED_PROGRAMS, MAX_ALLOWED_RECIPIENTS, MAX_SESSION_MINTS,
+};
+use crate::contexts::{CreateSessionKey, CreateSessionKeyInitIfNeeded};
 use crate::errors::ErrorCode;
 use crate::events::SessionKeyCreated;
-use crate::state::{ExpirationType, SessionKey, SessionPermissions};
+use crate::instructions::initialize_user_account::initialize_fields;
+use crate::state::{
+    CreateSessionKeyArgs, DiscriminatorFilter, ExpirationType, SessionKey, SessionPermissions,
+    UserAccount,
+};
 use anchor_lang::prelude::*;
 
-/// Create a new session key with specified permissions and expiry
+/// Create a new session key (v1). Kept for existing clients; new integrations should
+/// call `create_session_key_v2`, which also takes a label.
 pub fn handler(
     ctx: Context<CreateSessionKey>,
     session_pubkey: Pubkey,
     expires_at: i64,
     expiration_type: ExpirationType,
     permissions: SessionPermissions,
+    terms_hash: Option<[u8; 32]>,
+) -> Result<()> {
+    msg!("Deprecated: create_session_key is superseded by create_session_key_v2");
+
+    let (expires_at_time, expires_at_slot) = expiration_type.bounds(expires_at);
+    let args = CreateSessionKeyArgs {
+        session_pubkey,
+        expires_at_time,
+        expires_at_slot,
+        permissions,
+        terms_hash,
+        label: [0; 32],
+        max_uses: 0,
+        valid_from: 0,
+        allowed_recipients: Vec::new(),
+        allowed_mints: Vec::new(),
+        allowed_programs: Vec::new(),
+        allowed_discriminators: Vec::new(),
+        idle_timeout: 0,
+        scope: Pubkey::default(),
+        domain_hash: None,
+    };
+    create(ctx, args, true)
+}
+
+/// Create a new session key from a versioned argument struct
+pub fn handler_v2(ctx: Context<CreateSessionKey>, args: CreateSessionKeyArgs) -> Result<()> {
+    create(ctx, args, false)
+}
+
+/// Create a session key, first initializing the user account when it doesn't exist,
+/// so a dapp can onboard a new user with a single signature
+pub fn init_if_needed_handler(
+    ctx: Context<CreateSessionKeyInitIfNeeded>,
+    args: CreateSessionKeyArgs,
 ) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
+    // A zeroed authority means `init_if_needed` just created the account
+    if user_account.authority == Pubkey::default() {
+        initialize_fields(
+            user_account,
+            ctx.accounts.authority.key(),
+            ctx.bumps.user_account,
+            DEFAULT_SESSION_KEY_CAPACITY as u16,
+        );
+        msg!(
+            "User account initialized for authority: {}",
+            user_account.authority
+        );
+    }
+
+    let clock = Clock::get()?;
+    let session_key = build_session_key(&args, &clock);
+    add_session_key(user_account, session_key, &clock, false)
+}
+
+fn create(
+    ctx: Context<CreateSessionKey>,
+    args: CreateSessionKeyArgs,
+    deprecated: bool,
+) -> Result<()> {
     let clock = Clock::get()?;
+    let session_key = build_session_key(&args, &clock);
+    add_session_key(
+        &mut ctx.accounts.user_account,
+        session_key,
+        &clock,
+        deprecated,
+    )
+}
 
-    // Validate expiry based on type
-    match expiration_type {
-        ExpirationType::Time => {
-            // Validate timestamp is in the future
-            require!(expires_at > clock.unix_timestamp, ErrorCode::InvalidExpiry);
-        }
-        ExpirationType::BlockHeight => {
-            // Validate block height is in the future
-            require!(expires_at > clock.slot as i64, ErrorCode::InvalidExpiry);
+/// Build a fresh session key entry from creation arguments
+pub fn build_session_key(args: &CreateSessionKeyArgs, clock: &Clock) -> SessionKey {
+    // A sliding key starts with one idle timeout from when it becomes valid
+    let (expires_at_time, max_expires_at) = match (args.idle_timeout, args.expires_at_time) {
+        (0, time) | (_, time @ None) => (time, 0),
+        (idle_timeout, Some(cap)) => {
+            let start = clock.unix_timestamp.max(args.valid_from);
+            let deadline = start.saturating_add(idle_timeout as i64);
+            (Some(deadline.min(cap)), cap)
         }
+    };
+    SessionKey {
+        pubkey: args.session_pubkey,
+        created_at: clock.unix_timestamp,
+        expires_at_time,
+        expires_at_slot: args.expires_at_slot,
+        permissions: args.permissions,
+        is_revoked: false,
+        label: args.label,
+        preset: None,
+        preset_version: 0,
+        terms_hash: args.terms_hash.unwrap_or_default(),
+        total_spent: 0,
+        spend_window_start: clock.unix_timestamp,
+        spent_in_window: 0,
+        use_window_start: clock.unix_timestamp,
+        uses_in_window: 0,
+        max_uses: args.max_uses,
+        use_count: 0,
+        valid_from: args.valid_from,
+        allowed_recipients: args.allowed_recipients.clone(),
+        allowed_mints: args.allowed_mints.clone(),
+        allowed_programs: args.allowed_programs.clone(),
+        allowed_discriminators: args.allowed_discriminators.clone(),
+        prior_permissions: None,
+        widening_active_at: 0,
+        is_suspended: false,
+        revocation_reason: None,
+        failed_attempts: 0,
+        failure_window_start: clock.unix_timestamp,
+        locked_until: 0,
+        delegated_by: None,
+        last_used_at: 0,
+        idle_timeout: args.idle_timeout,
+        max_expires_at,
+        revoked_at: 0,
+        scope: args.scope,
+        domain_hash: args.domain_hash.unwrap_or_default(),
     }
+}
+
+/// Validate and append a session key to the account, emitting `SessionKeyCreated`.
+/// `deprecated` marks the event when the key came in through a superseded instruction.
+pub fn add_session_key(
+    user_account: &mut UserAccount,
+    session_key: SessionKey,
+    clock: &Clock,
+    deprecated: bool,
+) -> Result<()> {
+    let session_pubkey = session_key.pubkey;
+    let expires_at_time = session_key.expires_at_time;
+    let expires_at_slot = session_key.expires_at_slot;
+    validate_new_session_key(&session_key, clock)?;
 
     // Check if we've reached the maximum number of session keys
     require!(
-        user_account.session_keys.len() < MAX_SESSION_KEYS,
+        user_account.session_keys.len() < user_account.session_key_capacity as usize,
         ErrorCode::TooManySessionKeys
     );
 
     // Check if session key already exists
     require!(
-        !user_account
-            .session_keys
-            .iter()
-            .any(|k| k.pubkey == session_pubkey),
+        user_account.find_session_key(&session_pubkey).is_none(),
         ErrorCode::SessionKeyAlreadyExists
     );
 
-    // Create new session key
-    let session_key = SessionKey {
-        pubkey: session_pubkey,
-        created_at: clock.unix_timestamp,
-        expires_at,
-        expiration_type,
-        permissions,
-        is_revoked: false,
-        label: [0; 32], // Can be used for custom labeling
-    };
-
-    user_account.session_keys.push(session_key);
+    let event = created_event(user_account.authority, &session_key, deprecated);
+    user_account.insert_session_key(session_key);
 
     msg!(
-        "Session key created: {} (expires at: {} - type: {:?})",
+        "Session key created: {} (expires at time: {:?}, slot: {:?})",
         session_pubkey,
-        expires_at,
-        expiration_type
+        expires_at_time,
+        expires_at_slot
     );
 
-    emit!(SessionKeyCreated {
-        authority: user_account.authority,
-        session_key: session_pubkey,
-        expires_at,
-        permissions,
-    });
+    emit!(event);
 
     Ok(())
 }
+
+/// Check a new key's expiry bounds and list sizes, wherever it is stored
+pub fn validate_new_session_key(session_key: &SessionKey, clock: &Clock) -> Result<()> {
+    // Validate the expiry bounds are in the future and after the key becomes valid
+    SessionKey::check_expiry(
+        session_key.expires_at_time,
+        session_key.expires_at_slot,
+        clock,
+    )?;
+    if let Some(time) = session_key.expires_at_time {
+        require!(time > session_key.valid_from, ErrorCode::InvalidExpiry);
+    }
+    // Sliding keys need a time cap to slide up to
+    require!(
+        session_key.idle_timeout == 0 || session_key.max_expires_at > 0,
+        ErrorCode::InvalidExpiry
+    );
+
+    require!(
+        session_key.allowed_recipients.len() <= MAX_ALLOWED_RECIPIENTS,
+        ErrorCode::TooManyAllowedRecipients
+    );
+    require!(
+        session_key.allowed_mints.len() <= MAX_SESSION_MINTS,
+        ErrorCode::TooManySessionMints
+    );
+    require!(
+        session_key.allowed_programs.len() <= MAX_ALLOWED_PROGRAMS,
+        ErrorCode::TooManyAllowedPrograms
+    );
+    require!(
+        session_key.allowed_discriminators.len() <= MAX_ALLOWED_DISCRIMINATORS,
+        ErrorCode::TooManyAllowedDiscriminators
+    );
+    DiscriminatorFilter::check_all(
+        &session_key.allowed_discriminators,
+        &session_key.allowed_programs,
+    )
+}
+
+/// `SessionKeyCreated` for a freshly added key
+pub fn created_event(
+    authority: Pubkey,
+    session_key: &SessionKey,
+    deprecated: bool,
+) -> SessionKeyCreated {
+    SessionKeyCreated {
+        authority,
+        session_key: session_key.pubkey,
+        expires_at_time: session_key.expires_at_time,
+        expires_at_slot: session_key.expires_at_slot,
+        permissions: session_key.permissions,
+        terms_hash: session_key.terms_hash,
+        label: session_key.label,
+        valid_from: session_key.valid_from,
+        allowed_recipients: session_key.allowed_recipients.clone(),
+        allowed_mints: session_key.allowed_mints.clone(),
+        allowed_programs: session_key.allowed_programs.clone(),
+        allowed_discriminators: session_key.allowed_discriminators.clone(),
+        idle_timeout: session_key.idle_timeout,
+        max_expires_at: session_key.max_expires_at,
+        scope: session_key.scope,
+        domain_hash: session_key.domain_hash,
+        deprecated,
+    }
+}
@@ -0,0 +1,116 @@
+use crate::constants::MAX_DELEGATION_DEPTH;
+use crate::contexts::{DelegateSessionKey, SetMaxDelegationDepth};
+use crate::errors::ErrorCode;
+use crate::events::{MaxDelegationDepthUpdated, SessionKeyDelegated};
+use crate::instructions::create_session_key::{add_session_key, build_session_key};
+use crate::state::CreateSessionKeyArgs;
+use anchor_lang::prelude::*;
+
+/// Let a session key hand part of its grant to a sub-key, e.g. a bot spawning workers,
+/// without the authority co-signing. The sub-key must stay within the signer (see
+/// `SessionKey::covers`). A capped signer must cap the sub-key too, and that cap and
+/// any use limit are reserved out of what the signer has left, so delegating never
+/// adds spending power. The sub-key is stored inline and revoked with its parent, and
+/// chains stop at the account's `max_delegation_depth`.
+pub fn handler(ctx: Context<DelegateSessionKey>, args: CreateSessionKeyArgs) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let parent_pubkey = ctx.accounts.session_signer.key();
+    let clock = Clock::get()?;
+
+    require!(!user_account.is_paused, ErrorCode::AccountPaused);
+    let parent =
+        user_account.session_key(ctx.accounts.session_key_account.as_deref(), &parent_pubkey)?;
+    require!(!parent.is_revoked, ErrorCode::SessionKeyRevoked);
+    require!(!parent.is_suspended, ErrorCode::SessionKeySuspended);
+    require!(!parent.is_exhausted(), ErrorCode::SessionKeyExhausted);
+    require!(
+        parent.valid_from <= clock.unix_timestamp,
+        ErrorCode::SessionKeyNotYetValid
+    );
+    require!(!parent.is_expired(&clock), ErrorCode::SessionKeyExpired);
+    require!(
+        !parent.is_locked(clock.unix_timestamp),
+        ErrorCode::SessionKeyLocked
+    );
+    let permissions = parent.permissions_at(clock.unix_timestamp);
+    require!(permissions.can_delegate, ErrorCode::InsufficientPermissions);
+
+    let mut child = build_session_key(&args, &clock);
+    child.delegated_by = Some(parent_pubkey);
+    require!(
+        parent.covers(&child, clock.unix_timestamp),
+        ErrorCode::DelegationWidens
+    );
+    require!(
+        user_account.delegation_depth(&child, ctx.accounts.session_key_account.as_deref())
+            <= user_account.max_delegation_depth as usize,
+        ErrorCode::DelegationTooDeep
+    );
+
+    // Reserve the sub-key's lifetime cap and uses out of the parent's remainder
+    let reserved_amount = match permissions.max_transfer_amount {
+        0 => 0,
+        cap => {
+            let amount = child.permissions.max_transfer_amount;
+            require!(
+                amount > 0 && amount <= cap.saturating_sub(parent.total_spent),
+                ErrorCode::DelegationWidens
+            );
+            amount
+        }
+    };
+    let reserved_uses = match parent.max_uses {
+        0 => 0,
+        max_uses => {
+            require!(
+                child.max_uses > 0 && child.max_uses <= max_uses - parent.use_count,
+                ErrorCode::DelegationWidens
+            );
+            child.max_uses
+        }
+    };
+
+    let parent = user_account.session_key_mut(
+        ctx.accounts.session_key_account.as_deref_mut(),
+        &parent_pubkey,
+    )?;
+    parent.total_spent += reserved_amount;
+    parent.use_count += reserved_uses;
+
+    let session_pubkey = child.pubkey;
+    add_session_key(user_account, child, &clock, false)?;
+
+    emit!(SessionKeyDelegated {
+        authority: user_account.authority,
+        delegated_by: parent_pubkey,
+        session_key: session_pubkey,
+        reserved_amount,
+        reserved_uses,
+    });
+
+    Ok(())
+}
+
+/// Set how many levels of sub-keys may hang below a granted key. Existing sub-keys
+/// deeper than the new limit keep working until revoked; they just can't delegate.
+pub fn set_max_depth_handler(
+    ctx: Context<SetMaxDelegationDepth>,
+    max_delegation_depth: u8,
+) -> Result<()> {
+    require!(
+        max_delegation_depth <= MAX_DELEGATION_DEPTH,
+        ErrorCode::DelegationTooDeep
+    );
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.max_delegation_depth = max_delegation_depth;
+
+    msg!("Max delegation depth set to: {}", max_delegation_depth);
+
+    emit!(MaxDelegationDepthUpdated {
+        authority: user_account.authority,
+        max_delegation_depth,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use crate::constants::DISPUTE_TIMELOCK_SECONDS;
+use crate::contexts::{ReportViolation, ResolveDispute};
+use crate::errors::ErrorCode;
+use crate::events::{DisputeResolved, ViolationReported};
+use crate::instructions::operator_reputation::bonded_reputation;
+use crate::state::DisputeStatus;
+use anchor_lang::prelude::*;
+
+/// File a violation report; freezes withdrawal of the session's bond until resolved
+pub fn report_handler(
+    ctx: Context<ReportViolation>,
+    session_pubkey: Pubkey,
+    evidence: [u8; 64],
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        ctx.accounts
+            .user_account
+            .find_session_key(&session_pubkey)
+            .is_some(),
+        ErrorCode::SessionKeyNotFound
+    );
+
+    if let Some(reputation) =
+        bonded_reputation(ctx.accounts.bond.as_ref(), ctx.accounts.reputation.as_mut())?
+    {
+        reputation.violations_reported += 1;
+    }
+    if let Some(bond) = ctx.accounts.bond.as_mut() {
+        require!(!bond.is_releasable(&clock), ErrorCode::DisputeWindowClosed);
+        bond.open_disputes += 1;
+    }
+
+    let user_account = &mut ctx.accounts.user_account;
+    let report = &mut ctx.accounts.report;
+    report.user_account = user_account.key();
+    report.session_key = session_pubkey;
+    report.reporter = ctx.accounts.authority.key();
+    report.evidence = evidence;
+    report.index = user_account.violation_reports;
+    report.reported_at = clock.unix_timestamp;
+    report.resolvable_at = clock.unix_timestamp + DISPUTE_TIMELOCK_SECONDS;
+    report.resolved_at = 0;
+    report.status = DisputeStatus::Pending;
+    report.slash_applied = false;
+    report.bump = ctx.bumps.report;
+
+    user_account.violation_reports += 1;
+
+    msg!(
+        "Violation reported against session {} (report #{})",
+        session_pubkey,
+        report.index
+    );
+
+    emit!(ViolationReported {
+        user_account: report.user_account,
+        session_key: session_pubkey,
+        reporter: report.reporter,
+        report: report.key(),
+        evidence,
+        resolvable_at: report.resolvable_at,
+    });
+
+    Ok(())
+}
+
+/// Uphold or dismiss a pending report after the timelock; upheld reports can back a slash
+pub fn resolve_handler(ctx: Context<ResolveDispute>, upheld: bool) -> Result<()> {
+    let clock = Clock::get()?;
+    let report = &mut ctx.accounts.report;
+
+    require!(
+        report.status == DisputeStatus::Pending,
+        ErrorCode::DisputeAlreadyResolved
+    );
+    require!(
+        clock.unix_timestamp >= report.resolvable_at,
+        ErrorCode::DisputeTimelockActive
+    );
+
+    report.status = if upheld {
+        DisputeStatus::Upheld
+    } else {
+        DisputeStatus::Dismissed
+    };
+    report.resolved_at = clock.unix_timestamp;
+
+    if let Some(reputation) =
+        bonded_reputation(ctx.accounts.bond.as_ref(), ctx.accounts.reputation.as_mut())?
+    {
+        if upheld {
+            reputation.violations_upheld += 1;
+        }
+    }
+    if let Some(bond) = ctx.accounts.bond.as_mut() {
+        bond.open_disputes = bond.open_disputes.saturating_sub(1);
+    }
+
+    emit!(DisputeResolved {
+        user_account: report.user_account,
+        session_key: report.session_key,
+        report: report.key(),
+        status: report.status,
+    });
+
+    Ok(())
+}
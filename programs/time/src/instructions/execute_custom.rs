@@ -0,0 +1,235 @@
+use crate::contexts::ExecuteCustom;
+use crate::errors::ErrorCode;
+use crate::events::CustomActionExecuted;
+use crate::guard::{action_accounts, enforce_guard, GuardCheck, GuardedAction};
+use crate::policy::{enforce_policy, PolicyRequest};
+use crate::slot_proof::{verify_recent_slot, SlotReference};
+use crate::state::{roll_window, ProgramConfig};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token_interface::TokenAccount;
+
+/// Forward `data` to an allowlisted program, gated by `can_execute_custom`, the key's
+/// program allowlist and its discriminator filters for that program. The account's
+/// executor PDA signs the CPI. Refused outright while the account has controls only
+/// token transfers can check.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteCustom<'info>>,
+    data: Vec<u8>,
+    slot_ref: Option<SlotReference>,
+    scope: Option<Pubkey>,
+) -> Result<()> {
+    let user_account = &ctx.accounts.user_account;
+    let session_pubkey = ctx.accounts.session_signer.key();
+    let clock = Clock::get()?;
+
+    let session_key =
+        user_account.session_key(ctx.accounts.session_key_account.as_deref(), &session_pubkey)?;
+    let permissions = session_key.permissions_at(clock.unix_timestamp);
+
+    // Validate
+    require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
+    require!(!session_key.is_suspended, ErrorCode::SessionKeySuspended);
+    require!(
+        !session_key.is_locked(clock.unix_timestamp),
+        ErrorCode::SessionKeyLocked
+    );
+    require!(!session_key.is_exhausted(), ErrorCode::SessionKeyExhausted);
+    require!(
+        session_key.valid_from <= clock.unix_timestamp,
+        ErrorCode::SessionKeyNotYetValid
+    );
+    require!(
+        !session_key.is_expired(&clock),
+        ErrorCode::SessionKeyExpired
+    );
+    require!(!user_account.is_paused, ErrorCode::AccountPaused);
+    // A custom action has no recipient, operator or recorded amount these controls could
+    // check, so an account relying on them doesn't let session keys run one at all
+    require!(
+        !user_account.has_token_only_controls(),
+        ErrorCode::CustomActionUnsupported
+    );
+    ProgramConfig::enforce(&ctx.accounts.program_config, 0)?;
+    require!(
+        session_key.allows_scope(scope.as_ref()),
+        ErrorCode::SessionScopeMismatch
+    );
+    if let Some(window) = user_account.active_blackout(clock.unix_timestamp) {
+        msg!("Blackout in effect until {}", window.end);
+        return err!(ErrorCode::BlackoutActive);
+    }
+    if user_account.max_slot_age > 0 {
+        let slot_hashes = ctx
+            .accounts
+            .slot_hashes
+            .as_ref()
+            .ok_or(ErrorCode::SlotProofRequired)?;
+        let reference = slot_ref.as_ref().ok_or(ErrorCode::SlotProofRequired)?;
+        verify_recent_slot(
+            slot_hashes,
+            reference,
+            user_account.max_slot_age,
+            clock.slot,
+        )?;
+    }
+    require!(
+        permissions.can_execute_custom,
+        ErrorCode::InsufficientPermissions
+    );
+
+    let (target, forwarded) = action_accounts(user_account.guard_program, ctx.remaining_accounts)
+        .split_first()
+        .ok_or(ErrorCode::ProgramNotAllowed)?;
+    require!(
+        target.executable
+            && target.key() != crate::ID
+            && session_key.allowed_programs.contains(&target.key()),
+        ErrorCode::ProgramNotAllowed
+    );
+    require!(
+        session_key.allows_discriminator(&target.key(), &data),
+        ErrorCode::InstructionNotAllowed
+    );
+    // A payload wrapped in outer CPIs, or forwarding a large account set, can hide
+    // nested calls from whoever reviews the top-level transaction
+    if permissions.max_cpi_depth > 0 {
+        require!(
+            get_stack_height() <= permissions.max_cpi_depth as usize,
+            ErrorCode::CpiDepthExceeded
+        );
+    }
+    // Custom actions move nothing through this program, so only rules that don't need an
+    // amount, mint or recipient can reject them
+    enforce_policy(
+        user_account,
+        ctx.accounts.policy.as_deref(),
+        &PolicyRequest {
+            session_key: session_pubkey,
+            now: clock.unix_timestamp,
+            amount: 0,
+            mint: None,
+            recipient: None,
+            recipient_account: None,
+        },
+    )?;
+    if permissions.max_custom_accounts > 0 {
+        require!(
+            forwarded.len() <= permissions.max_custom_accounts as usize,
+            ErrorCode::TooManyCustomAccounts
+        );
+    }
+
+    // Give the account's guard program, if any, a chance to veto the action
+    enforce_guard(
+        user_account.guard_program,
+        &user_account.to_account_info(),
+        &ctx.accounts.session_signer.to_account_info(),
+        ctx.remaining_accounts,
+        &GuardCheck {
+            user_account: user_account.key(),
+            authority: user_account.authority,
+            session_key: session_pubkey,
+            action: GuardedAction::custom(target.key(), &data),
+        },
+    )?;
+
+    // Value routed into a capped program is measured as the drop in the budget mint's
+    // balance held by the executor or the authority, over every account handed to it
+    let executor = ctx.accounts.executor.key();
+    let owners = [executor, user_account.authority];
+    let balance_before = if user_account.budgeted_programs.contains(&target.key()) {
+        let budget = ctx
+            .accounts
+            .spend_budget
+            .as_ref()
+            .filter(|b| b.program_budgets.iter().any(|p| p.program == target.key()))
+            .ok_or(ErrorCode::ProgramBudgetMissing)?;
+        Some(routed_balance(forwarded, &budget.mint, &owners))
+    } else {
+        None
+    };
+
+    let mut use_window_start = session_key.use_window_start;
+    let mut uses_in_window = session_key.uses_in_window;
+    roll_window(
+        &mut use_window_start,
+        &mut uses_in_window,
+        permissions.use_window(),
+        clock.unix_timestamp,
+    );
+    let uses_in_window = uses_in_window + 1;
+    if permissions.max_uses_per_window > 0 {
+        require!(
+            uses_in_window <= permissions.max_uses_per_window as u64,
+            ErrorCode::RateLimitExceeded
+        );
+    }
+
+    {
+        let key = ctx.accounts.user_account.session_key_mut(
+            ctx.accounts.session_key_account.as_deref_mut(),
+            &session_pubkey,
+        )?;
+        key.use_window_start = use_window_start;
+        key.uses_in_window = uses_in_window;
+        key.use_count += 1;
+        key.mark_used(clock.unix_timestamp);
+    }
+
+    // Forward the accounts as given, with the executor PDA promoted to signer
+    let accounts = forwarded
+        .iter()
+        .map(|a| {
+            let is_signer = a.is_signer || a.key() == executor;
+            if a.is_writable {
+                AccountMeta::new(a.key(), is_signer)
+            } else {
+                AccountMeta::new_readonly(a.key(), is_signer)
+            }
+        })
+        .collect();
+    let mut infos = forwarded.to_vec();
+    infos.push(target.clone());
+
+    let user_key = ctx.accounts.user_account.key();
+    let seeds: &[&[u8]] = &[b"executor", user_key.as_ref(), &[ctx.bumps.executor]];
+    let data_len = data.len() as u32;
+    invoke_signed(
+        &Instruction {
+            program_id: target.key(),
+            accounts,
+            data,
+        },
+        &infos,
+        &[seeds],
+    )?;
+
+    if let (Some(before), Some(budget)) = (balance_before, ctx.accounts.spend_budget.as_mut()) {
+        let after = routed_balance(forwarded, &budget.mint, &owners);
+        budget.charge_program(&target.key(), before.saturating_sub(after), &clock)?;
+    }
+
+    emit!(CustomActionExecuted {
+        authority: ctx.accounts.user_account.authority,
+        session_key: session_pubkey,
+        program: target.key(),
+        data_len,
+    });
+
+    Ok(())
+}
+
+/// Total balance of `mint` held by `owners` across the token accounts among `accounts`
+fn routed_balance<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    mint: &Pubkey,
+    owners: &[Pubkey],
+) -> u64 {
+    accounts
+        .iter()
+        .filter_map(|a| InterfaceAccount::<TokenAccount>::try_from(a).ok())
+        .filter(|t| t.mint == *mint && owners.contains(&t.owner))
+        .fold(0u64, |total, t| total.saturating_add(t.amount))
+}
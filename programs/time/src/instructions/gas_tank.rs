@@ -0,0 +1,110 @@
+use crate::constants::MAX_GAS_FEE_PER_EXECUTION;
+use crate::contexts::{InitializeGasTank, SetGasTankFee, TopUpGasTank};
+use crate::errors::ErrorCode;
+use crate::events::{GasReimbursed, GasTankFeeUpdated, GasTankToppedUp};
+use crate::state::GasTank;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Create an empty gas tank for the account
+pub fn initialize_handler(ctx: Context<InitializeGasTank>, fee_per_execution: u64) -> Result<()> {
+    require!(
+        fee_per_execution <= MAX_GAS_FEE_PER_EXECUTION,
+        ErrorCode::InvalidGasFee
+    );
+
+    let gas_tank = &mut ctx.accounts.gas_tank;
+    gas_tank.user_account = ctx.accounts.user_account.key();
+    gas_tank.fee_per_execution = fee_per_execution;
+    gas_tank.total_topped_up = 0;
+    gas_tank.total_reimbursed = 0;
+    gas_tank.bump = ctx.bumps.gas_tank;
+
+    emit!(GasTankFeeUpdated {
+        authority: ctx.accounts.authority.key(),
+        gas_tank: gas_tank.key(),
+        fee_per_execution,
+    });
+
+    Ok(())
+}
+
+/// Change how much each execution is reimbursed
+pub fn set_fee_handler(ctx: Context<SetGasTankFee>, fee_per_execution: u64) -> Result<()> {
+    require!(
+        fee_per_execution <= MAX_GAS_FEE_PER_EXECUTION,
+        ErrorCode::InvalidGasFee
+    );
+
+    let gas_tank = &mut ctx.accounts.gas_tank;
+    gas_tank.fee_per_execution = fee_per_execution;
+
+    msg!("Gas tank fee set to: {} lamports", fee_per_execution);
+
+    emit!(GasTankFeeUpdated {
+        authority: ctx.accounts.authority.key(),
+        gas_tank: gas_tank.key(),
+        fee_per_execution,
+    });
+
+    Ok(())
+}
+
+/// Move lamports from the payer into the gas tank
+pub fn top_up_handler(ctx: Context<TopUpGasTank>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.gas_tank.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let gas_tank = &mut ctx.accounts.gas_tank;
+    gas_tank.total_topped_up = gas_tank.total_topped_up.saturating_add(amount);
+
+    emit!(GasTankToppedUp {
+        authority: ctx.accounts.user_account.authority,
+        payer: ctx.accounts.payer.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Reimburse the session signer's fee from the tank. A tank that runs dry skips the
+/// reimbursement rather than failing the execution.
+pub(crate) fn reimburse_fee(
+    gas_tank: &mut Account<GasTank>,
+    authority: Pubkey,
+    session_signer: &AccountInfo,
+) -> Result<()> {
+    let fee = gas_tank.fee_per_execution;
+    if fee == 0 {
+        return Ok(());
+    }
+
+    let tank = gas_tank.to_account_info();
+    let reserve = Rent::get()?.minimum_balance(tank.data_len());
+    if tank.lamports().saturating_sub(reserve) < fee {
+        msg!("Gas tank empty, fee not reimbursed");
+        return Ok(());
+    }
+
+    **tank.try_borrow_mut_lamports()? -= fee;
+    **session_signer.try_borrow_mut_lamports()? += fee;
+    gas_tank.total_reimbursed = gas_tank.total_reimbursed.saturating_add(fee);
+
+    emit!(GasReimbursed {
+        authority,
+        session_key: session_signer.key(),
+        amount: fee,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::contexts::{GuardianRevokeAll, SetGuardian};
+use crate::events::{AllSessionKeysRevoked, GuardianRevokedAll, GuardianSet};
+use crate::state::RevocationReason;
+use anchor_lang::prelude::*;
+
+/// Designate the guardian key, or remove it with None
+pub fn set_handler(ctx: Context<SetGuardian>, guardian: Option<Pubkey>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.guardian = guardian;
+
+    msg!("Guardian set to: {:?}", guardian);
+
+    emit!(GuardianSet {
+        authority: user_account.authority,
+        guardian,
+    });
+
+    Ok(())
+}
+
+/// Revoke every session key on behalf of an unavailable or compromised authority. The
+/// guardian only signs this; keys, funds and the guardian setting stay with the authority.
+pub fn revoke_all_handler(ctx: Context<GuardianRevokeAll>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let now = Clock::get()?.unix_timestamp;
+
+    let mut revoked = 0u32;
+    for session_key in &mut user_account.session_keys {
+        if !session_key.is_revoked {
+            session_key.revoke(Some(RevocationReason::Compromised), now);
+            revoked += 1;
+        }
+    }
+    // Same as `revoke_all_session_keys`: invalidates every SessionKeyAccount
+    user_account.session_generation = user_account.session_generation.wrapping_add(1);
+
+    msg!(
+        "Guardian {} revoked {} session keys",
+        ctx.accounts.guardian.key(),
+        revoked
+    );
+
+    emit!(AllSessionKeysRevoked {
+        authority: user_account.authority,
+        count: user_account.session_keys.len() as u32,
+    });
+    emit!(GuardianRevokedAll {
+        authority: user_account.authority,
+        guardian: ctx.accounts.guardian.key(),
+        revoked,
+    });
+
+    Ok(())
+}
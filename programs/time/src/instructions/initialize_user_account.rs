@@ -1,16 +1,45 @@
-use crate::constants::MAX_ALLOWED_MINTS;
+use crate::constants::{
+    DEFAULT_MAX_DELEGATION_DEPTH, DEFAULT_SESSION_KEY_CAPACITY, MAX_ALLOWED_MINTS,
+    MAX_INIT_SESSION_KEY_CAPACITY, USER_ACCOUNT_VERSION,
+};
 use crate::contexts::InitializeUserAccount;
 use crate::contexts::InitializeUserAccountWithConfig;
+use crate::instructions::create_session_key::{add_session_key, build_session_key};
+use crate::state::{CreateSessionKeyArgs, UserAccount};
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 
-/// Initialize a user account that can hold session keys
-pub fn handler(ctx: Context<InitializeUserAccount>) -> Result<()> {
+/// Initialize a user account sized for `max_session_keys` session keys
+pub fn handler(mut ctx: Context<InitializeUserAccount>, max_session_keys: u16) -> Result<()> {
+    initialize(&mut ctx, max_session_keys)
+}
+
+/// Initialize a user account and register its first session key, so a new user can
+/// start a dapp session after a single transaction
+pub fn with_session_key_handler(
+    mut ctx: Context<InitializeUserAccount>,
+    max_session_keys: u16,
+    args: CreateSessionKeyArgs,
+) -> Result<()> {
+    initialize(&mut ctx, max_session_keys)?;
+    let clock = Clock::get()?;
+    let session_key = build_session_key(&args, &clock);
+    add_session_key(&mut ctx.accounts.user_account, session_key, &clock, false)
+}
+
+fn initialize(ctx: &mut Context<InitializeUserAccount>, max_session_keys: u16) -> Result<()> {
+    require!(
+        max_session_keys > 0 && max_session_keys as usize <= MAX_INIT_SESSION_KEY_CAPACITY,
+        crate::errors::ErrorCode::InvalidSessionKeyCapacity
+    );
+
     let user_account = &mut ctx.accounts.user_account;
-    user_account.authority = ctx.accounts.authority.key();
-    user_account.session_keys = Vec::new();
-    user_account.bump = ctx.bumps.user_account;
-    user_account.allowed_mints = Vec::new();
+    initialize_fields(
+        user_account,
+        ctx.accounts.authority.key(),
+        ctx.bumps.user_account,
+        max_session_keys,
+    );
 
     msg!(
         "User account initialized for authority: {}",
@@ -31,9 +60,12 @@ pub fn handler_with_config(
     );
 
     let user_account = &mut ctx.accounts.user_account;
-    user_account.authority = ctx.accounts.authority.key();
-    user_account.session_keys = Vec::new();
-    user_account.bump = ctx.bumps.user_account;
+    initialize_fields(
+        user_account,
+        ctx.accounts.authority.key(),
+        ctx.bumps.user_account,
+        DEFAULT_SESSION_KEY_CAPACITY as u16,
+    );
     user_account.allowed_mints = allowed_mints;
 
     if initial_deposit_lamports > 0 {
@@ -51,3 +83,46 @@ pub fn handler_with_config(
 
     Ok(())
 }
+
+/// Set every field of a freshly created account to its initial value
+pub fn initialize_fields(
+    user_account: &mut UserAccount,
+    authority: Pubkey,
+    bump: u8,
+    session_key_capacity: u16,
+) {
+    user_account.authority = authority;
+    user_account.session_keys = Vec::new();
+    user_account.bump = bump;
+    user_account.allowed_mints = Vec::new();
+    user_account.guard_program = None;
+    user_account.attestation_gate = None;
+    user_account.enforce_blocklist = false;
+    user_account.bond_requirement = None;
+    user_account.violation_reports = 0;
+    user_account.budgeted_mints = Vec::new();
+    user_account.digest_interval = 0;
+    user_account.stats_enabled = false;
+    user_account.blackout_windows = Vec::new();
+    user_account.max_slot_age = 0;
+    user_account.savings_rule = None;
+    user_account.spend_categories = Vec::new();
+    user_account.widening_delay_seconds = 0;
+    user_account.delegated_token_accounts = Vec::new();
+    user_account.session_generation = 0;
+    user_account.session_key_accounts = 0;
+    user_account.delegate_bumps = Vec::new();
+    user_account.allow_permanent_delegate_mints = false;
+    user_account.has_policy = false;
+    user_account.kill_phrase_hash = [0; 32];
+    user_account.is_paused = false;
+    user_account.lockout_policy = None;
+    user_account.require_recipient_consent = false;
+    user_account.max_delegation_depth = DEFAULT_MAX_DELEGATION_DEPTH;
+    user_account.require_operator_signature = false;
+    user_account.retention_seconds = 0;
+    user_account.guardian = None;
+    user_account.budgeted_programs = Vec::new();
+    user_account.version = USER_ACCOUNT_VERSION;
+    user_account.session_key_capacity = session_key_capacity;
+}
@@ -0,0 +1,78 @@
+use crate::constants::KILL_PHRASE_DOMAIN;
+use crate::contexts::{SetKillPhrase, TriggerKillPhrase};
+use crate::errors::ErrorCode;
+use crate::events::{AllSessionKeysRevoked, KillPhraseSet, KillPhraseTriggered};
+use crate::state::RevocationReason;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// Commitment stored for `phrase`: `sha256("kill_phrase" || user_account || phrase)`, so
+/// the same phrase commits differently on every account
+pub fn kill_phrase_commitment(user_account: &Pubkey, phrase: &[u8]) -> [u8; 32] {
+    hashv(&[KILL_PHRASE_DOMAIN, user_account.as_ref(), phrase]).to_bytes()
+}
+
+/// Set the kill phrase commitment, computed off-chain with `kill_phrase_commitment`;
+/// zero clears it. The phrase itself never touches the chain until it is used.
+pub fn set_handler(ctx: Context<SetKillPhrase>, commitment: [u8; 32]) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.kill_phrase_hash = commitment;
+    let enabled = commitment != [0; 32];
+
+    msg!("Kill phrase {}", if enabled { "set" } else { "cleared" });
+
+    emit!(KillPhraseSet {
+        authority: user_account.authority,
+        enabled,
+    });
+
+    Ok(())
+}
+
+/// Anyone holding the phrase (support staff, family) can revoke every session key and
+/// pause the account without holding any key. The phrase is public once used, so the
+/// commitment is cleared and the authority sets a new one after unpausing.
+pub fn trigger_handler(ctx: Context<TriggerKillPhrase>, phrase: Vec<u8>) -> Result<()> {
+    let user_account_key = ctx.accounts.user_account.key();
+    let user_account = &mut ctx.accounts.user_account;
+
+    require!(
+        user_account.kill_phrase_hash != [0; 32],
+        ErrorCode::KillPhraseNotSet
+    );
+    require!(
+        kill_phrase_commitment(&user_account_key, &phrase) == user_account.kill_phrase_hash,
+        ErrorCode::KillPhraseMismatch
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut revoked = 0u32;
+    for session_key in &mut user_account.session_keys {
+        if !session_key.is_revoked {
+            session_key.revoke(Some(RevocationReason::Compromised), now);
+            revoked += 1;
+        }
+    }
+    // Same as `revoke_all_session_keys`: invalidates every SessionKeyAccount
+    user_account.session_generation = user_account.session_generation.wrapping_add(1);
+    user_account.is_paused = true;
+    user_account.kill_phrase_hash = [0; 32];
+
+    msg!(
+        "Kill phrase used by {}: {} session keys revoked, account paused",
+        ctx.accounts.caller.key(),
+        revoked
+    );
+
+    emit!(AllSessionKeysRevoked {
+        authority: user_account.authority,
+        count: user_account.session_keys.len() as u32,
+    });
+    emit!(KillPhraseTriggered {
+        authority: user_account.authority,
+        triggered_by: ctx.accounts.caller.key(),
+        revoked,
+    });
+
+    Ok(())
+}
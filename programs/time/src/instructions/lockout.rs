@@ -0,0 +1,116 @@
+use crate::contexts::{RecordRefusedSpend, SetLockoutPolicy};
+use crate::errors::ErrorCode;
+use crate::events::{LockoutPolicyUpdated, SessionKeyLocked, SpendAttemptFailed};
+use crate::state::{LockoutPolicy, SessionKeyAccount, UserAccount};
+use anchor_lang::prelude::*;
+
+/// Refusals that count toward a lockout: the spend checks a caller can probe by varying
+/// amounts and timing. Revoked, suspended and exhausted keys can't be probed further.
+const COUNTED_FAILURES: [ErrorCode; 6] = [
+    ErrorCode::SessionKeyNotYetValid,
+    ErrorCode::SessionKeyExpired,
+    ErrorCode::InsufficientPermissions,
+    ErrorCode::SessionSpendCapExceeded,
+    ErrorCode::DailyLimitExceeded,
+    ErrorCode::RateLimitExceeded,
+];
+
+/// Set or clear the account's lockout policy
+pub fn set_handler(
+    ctx: Context<SetLockoutPolicy>,
+    lockout_policy: Option<LockoutPolicy>,
+) -> Result<()> {
+    if let Some(policy) = lockout_policy {
+        require!(
+            policy.max_failures > 0 && policy.window_seconds > 0 && policy.lock_seconds > 0,
+            ErrorCode::InvalidLockoutPolicy
+        );
+    }
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.lockout_policy = lockout_policy;
+
+    msg!("Lockout policy set to: {:?}", lockout_policy);
+
+    emit!(LockoutPolicyUpdated {
+        authority: user_account.authority,
+        lockout_policy,
+    });
+
+    Ok(())
+}
+
+/// Count a refused spend of `amount` by `session_pubkey` toward the account's lockout
+/// policy. Refused spends revert, so the authority or guardian reports them here; the
+/// spend is replayed against the key as it stands, and only a refusal
+/// `COUNTED_FAILURES` lists is counted.
+pub fn record_refused_handler(
+    ctx: Context<RecordRefusedSpend>,
+    session_pubkey: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let user_account = &mut ctx.accounts.user_account;
+    require!(
+        user_account.lockout_policy.is_some(),
+        ErrorCode::LockoutPolicyMissing
+    );
+
+    let key_account = ctx.accounts.session_key_account.as_deref_mut();
+    let session_key = user_account.session_key(key_account.as_deref(), &session_pubkey)?;
+    let Err(error) = session_key.check_spend(amount, &clock) else {
+        return err!(ErrorCode::SpendNotRefused);
+    };
+    record_failed_attempt(
+        user_account,
+        key_account,
+        &session_pubkey,
+        error,
+        clock.unix_timestamp,
+    )
+}
+
+/// Count a spend refused with `error` toward the account's lockout policy, reporting it
+/// through `SpendAttemptFailed` (and `SessionKeyLocked` once the key locks). Without a
+/// policy, or for other errors, `error` is returned unchanged.
+fn record_failed_attempt(
+    user_account: &mut UserAccount,
+    key_account: Option<&mut SessionKeyAccount>,
+    session_pubkey: &Pubkey,
+    error: Error,
+    now: i64,
+) -> Result<()> {
+    let Some(policy) = user_account.lockout_policy else {
+        return Err(error);
+    };
+    if !COUNTED_FAILURES
+        .iter()
+        .any(|code| error == Error::from(*code))
+    {
+        return Err(error);
+    }
+
+    let authority = user_account.authority;
+    let session_key = user_account.session_key_mut(key_account, session_pubkey)?;
+    let locked = session_key.record_failure(&policy, now);
+    msg!("Spend refused: {}", error);
+
+    emit!(SpendAttemptFailed {
+        authority,
+        session_key: *session_pubkey,
+        error_code: match &error {
+            Error::AnchorError(e) => e.error_code_number,
+            Error::ProgramError(_) => 0,
+        },
+        failed_attempts: session_key.failed_attempts,
+    });
+    if locked {
+        emit!(SessionKeyLocked {
+            authority,
+            session_key: *session_pubkey,
+            locked_until: session_key.locked_until,
+        });
+    }
+
+    Ok(())
+}
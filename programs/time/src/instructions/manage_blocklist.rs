@@ -0,0 +1,65 @@
+use crate::constants::MAX_BLOCKLIST_ENTRIES;
+use crate::contexts::{InitializeBlocklist, SetBlocklistEnforcement, UpdateBlocklist};
+use crate::errors::ErrorCode;
+use crate::events::{BlocklistEnforcementUpdated, BlocklistUpdated};
+use anchor_lang::prelude::*;
+
+/// Create the blocklist registry; the program upgrade authority becomes its admin
+pub fn initialize_handler(ctx: Context<InitializeBlocklist>) -> Result<()> {
+    let blocklist = &mut ctx.accounts.blocklist;
+    blocklist.admin = ctx.accounts.admin.key();
+    blocklist.bump = ctx.bumps.blocklist;
+    blocklist.addresses = Vec::new();
+
+    msg!("Blocklist registry initialized, admin: {}", blocklist.admin);
+    Ok(())
+}
+
+/// Apply additions and removals, keeping the registry sorted and deduplicated
+pub fn update_handler(
+    ctx: Context<UpdateBlocklist>,
+    additions: Vec<Pubkey>,
+    removals: Vec<Pubkey>,
+) -> Result<()> {
+    let blocklist = &mut ctx.accounts.blocklist;
+    let before = blocklist.addresses.len();
+
+    blocklist.addresses.retain(|a| !removals.contains(a));
+    let removed = before - blocklist.addresses.len();
+
+    let mut added = 0u32;
+    for address in additions {
+        if let Err(pos) = blocklist.addresses.binary_search(&address) {
+            blocklist.addresses.insert(pos, address);
+            added += 1;
+        }
+    }
+    require!(
+        blocklist.addresses.len() <= MAX_BLOCKLIST_ENTRIES,
+        ErrorCode::BlocklistFull
+    );
+
+    emit!(BlocklistUpdated {
+        admin: blocklist.admin,
+        added,
+        removed: removed as u32,
+        total: blocklist.addresses.len() as u32,
+    });
+
+    Ok(())
+}
+
+/// Opt the user account in or out of blocklist screening
+pub fn set_enforcement_handler(ctx: Context<SetBlocklistEnforcement>, enabled: bool) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.enforce_blocklist = enabled;
+
+    msg!("Blocklist enforcement set to: {}", enabled);
+
+    emit!(BlocklistEnforcementUpdated {
+        authority: user_account.authority,
+        enabled,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use crate::contexts::{ClosePolicy, InitializePolicy, UpdatePolicy};
+use crate::events::{PolicyClosed, PolicyUpdated};
+use crate::policy::PolicyDocument;
+use anchor_lang::prelude::*;
+
+/// Attach a policy document to the account. From then on every session spend must pass
+/// the policy account and satisfy its rules.
+pub fn initialize_handler(ctx: Context<InitializePolicy>, document: PolicyDocument) -> Result<()> {
+    document.validate()?;
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.has_policy = true;
+
+    emit!(PolicyUpdated {
+        authority: user_account.authority,
+        version: document.version,
+        rules: document.rules.len() as u8,
+    });
+
+    let policy = &mut ctx.accounts.policy;
+    policy.user_account = user_account.key();
+    policy.document = document;
+    policy.updated_at = Clock::get()?.unix_timestamp;
+    policy.bump = ctx.bumps.policy;
+
+    Ok(())
+}
+
+/// Replace the policy document
+pub fn update_handler(ctx: Context<UpdatePolicy>, document: PolicyDocument) -> Result<()> {
+    document.validate()?;
+
+    emit!(PolicyUpdated {
+        authority: ctx.accounts.user_account.authority,
+        version: document.version,
+        rules: document.rules.len() as u8,
+    });
+
+    let policy = &mut ctx.accounts.policy;
+    policy.document = document;
+    policy.updated_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+/// Remove the policy; spends are then limited only by session permissions
+pub fn close_handler(ctx: Context<ClosePolicy>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.has_policy = false;
+
+    emit!(PolicyClosed {
+        authority: user_account.authority,
+    });
+
+    Ok(())
+}
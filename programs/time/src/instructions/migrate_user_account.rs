@@ -0,0 +1,86 @@
+use crate::constants::{DEFAULT_SESSION_KEY_CAPACITY, USER_ACCOUNT_VERSION};
+use crate::contexts::MigrateUserAccount;
+use crate::errors::ErrorCode;
+use crate::events::UserAccountMigrated;
+use crate::state::UserAccount;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Bring an account written by an older program version up to the current layout.
+/// `UserAccount` fields are only ever appended, so the stored bytes are read with zeros
+/// standing in for fields the old layout lacks; each version step then sets defaults
+/// that aren't zero, and the account grows to the current size before it's rewritten.
+pub fn handler(ctx: Context<MigrateUserAccount>) -> Result<()> {
+    let info = ctx.accounts.user_account.to_account_info();
+    let mut user_account = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == *UserAccount::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        let mut padded = data[8..].to_vec();
+        padded.resize(padded.len() + UserAccount::space(0), 0);
+        UserAccount::deserialize(&mut &padded[..])?
+    };
+
+    let expected = Pubkey::create_program_address(
+        &[
+            UserAccount::SEED_PREFIX,
+            user_account.authority.as_ref(),
+            &[user_account.bump],
+        ],
+        ctx.program_id,
+    )
+    .map_err(|_| error!(anchor_lang::error::ErrorCode::ConstraintSeeds))?;
+    require_keys_eq!(
+        expected,
+        info.key(),
+        anchor_lang::error::ErrorCode::ConstraintSeeds
+    );
+
+    let from_version = user_account.version;
+    require!(
+        from_version < USER_ACCOUNT_VERSION,
+        ErrorCode::AccountAlreadyMigrated
+    );
+
+    // Version 0: accounts from before configurable capacity read a capacity of 0
+    if user_account.session_key_capacity == 0 {
+        user_account.session_key_capacity = user_account
+            .session_keys
+            .len()
+            .max(DEFAULT_SESSION_KEY_CAPACITY) as u16;
+    }
+    user_account.version = USER_ACCOUNT_VERSION;
+
+    let new_len = UserAccount::space(user_account.session_key_capacity as usize);
+    if new_len > info.data_len() {
+        let shortfall = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        info.resize(new_len)?;
+    }
+
+    let mut data = info.try_borrow_mut_data()?;
+    user_account.try_serialize(&mut &mut data[..])?;
+
+    emit!(UserAccountMigrated {
+        authority: user_account.authority,
+        from_version,
+        to_version: USER_ACCOUNT_VERSION,
+    });
+
+    Ok(())
+}
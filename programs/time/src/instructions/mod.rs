@@ -1,10 +1,54 @@
+pub mod account_stats;
+pub mod activity_digest;
+pub mod archive_user_account;
 pub mod cleanup_session_keys;
+pub mod close_user_account;
 pub mod create_session_key;
+pub mod delegate_session_key;
+pub mod disputes;
+pub mod execute_custom;
+pub mod gas_tank;
+pub mod guardian;
 pub mod initialize_user_account;
+pub mod kill_phrase;
+pub mod lockout;
+pub mod manage_blocklist;
+pub mod manage_policy;
+pub mod migrate_user_account;
+pub mod operator_bond;
+pub mod operator_reputation;
+pub mod pipeline;
+pub mod program_config;
+pub mod publish_build_hash;
+pub mod recipient_consent;
+pub mod resize_user_account;
 pub mod revoke_all_session_keys;
 pub mod revoke_session_key;
+pub mod revoke_sessions_expiring_after;
+pub mod rotate_session_key;
+pub mod scheduled_session;
+pub mod self_restrict;
+pub mod service_presets;
+pub mod service_registry;
+pub mod session_key_account;
+pub mod set_account_paused;
+pub mod set_attestation_gate;
+pub mod set_blackout_windows;
+pub mod set_guard_program;
+pub mod set_max_slot_age;
+pub mod set_permanent_delegate_opt_in;
+pub mod set_savings_rule;
+pub mod set_spend_categories;
+pub mod set_widening_delay;
+pub mod sol_vault;
+pub mod spend_budget;
 pub mod spl_approve_delegate;
 pub mod spl_delegated_transfer;
 pub mod spl_revoke_delegate;
+pub mod suspend_session_key;
+pub mod transfer_session_to_new_key;
 pub mod update_allowed_mints;
 pub mod update_session_key;
+pub mod update_session_key_label;
+pub mod validate_session;
+pub mod verify_session_domain;
@@ -0,0 +1,366 @@
+use crate::contexts::{
+    PostBond, PostBondSpl, RequestBondRelease, SetBondRequirement, SetOperatorSignatureRequired,
+    SlashBond, WithdrawBond,
+};
+use crate::errors::ErrorCode;
+use crate::events::{
+    BondPosted, BondReleaseRequested, BondRequirementUpdated, BondSlashed, BondWithdrawn,
+    OperatorSignatureRequirementUpdated,
+};
+use crate::state::{BondRequirement, DisputeStatus, OperatorBond, UserAccount};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+/// Set or clear the bond session operators must lock before executing
+pub fn set_requirement_handler(
+    ctx: Context<SetBondRequirement>,
+    bond_requirement: Option<BondRequirement>,
+) -> Result<()> {
+    if let Some(requirement) = bond_requirement {
+        require!(requirement.min_amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            requirement.dispute_window_seconds >= 0,
+            ErrorCode::InvalidExpiry
+        );
+    }
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.bond_requirement = bond_requirement;
+
+    emit!(BondRequirementUpdated {
+        authority: user_account.authority,
+        bond_requirement,
+    });
+
+    Ok(())
+}
+
+/// Turn on or off operator countersigning. While on, every delegated transfer needs the
+/// session's bond and an Ed25519 instruction in which the bond's operator signs
+/// `receipts::operator_payload`, so each execution is provably authorized by the operator
+/// even if the session keypair is shared.
+pub fn set_signature_required_handler(
+    ctx: Context<SetOperatorSignatureRequired>,
+    required: bool,
+) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.require_operator_signature = required;
+
+    msg!("Operator signature required: {}", required);
+
+    emit!(OperatorSignatureRequirementUpdated {
+        authority: user_account.authority,
+        required,
+    });
+
+    Ok(())
+}
+
+/// Lock SOL into the bond PDA backing `session_pubkey`
+pub fn post_sol_handler(ctx: Context<PostBond>, session_pubkey: Pubkey, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    let dispute_window_seconds =
+        bonded_session_window(&ctx.accounts.user_account, &session_pubkey)?;
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.operator.to_account_info(),
+                to: ctx.accounts.bond.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.accounts_served += 1;
+    reputation.last_updated = Clock::get()?.unix_timestamp;
+
+    init_bond(
+        &mut ctx.accounts.bond,
+        ctx.accounts.user_account.key(),
+        session_pubkey,
+        ctx.accounts.operator.key(),
+        None,
+        amount,
+        dispute_window_seconds,
+        ctx.bumps.bond,
+    )
+}
+
+/// Lock SPL tokens into an escrow vault owned by the bond PDA backing `session_pubkey`
+pub fn post_spl_handler(
+    ctx: Context<PostBondSpl>,
+    session_pubkey: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    let dispute_window_seconds =
+        bonded_session_window(&ctx.accounts.user_account, &session_pubkey)?;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.operator_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.bond_vault.to_account_info(),
+                authority: ctx.accounts.operator.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.accounts_served += 1;
+    reputation.last_updated = Clock::get()?.unix_timestamp;
+
+    init_bond(
+        &mut ctx.accounts.bond,
+        ctx.accounts.user_account.key(),
+        session_pubkey,
+        ctx.accounts.operator.key(),
+        Some(ctx.accounts.mint.key()),
+        amount,
+        dispute_window_seconds,
+        ctx.bumps.bond,
+    )
+}
+
+/// Start the dispute window; the bonded session must no longer be usable
+pub fn request_release_handler(ctx: Context<RequestBondRelease>) -> Result<()> {
+    let clock = Clock::get()?;
+    let bond = &mut ctx.accounts.bond;
+    require!(
+        bond.release_requested_at == 0,
+        ErrorCode::BondReleasePending
+    );
+
+    let still_active = ctx
+        .accounts
+        .user_account
+        .find_session_key(&bond.session_key)
+        .is_some_and(|k| k.is_valid(&clock));
+    require!(!still_active, ErrorCode::SessionKeyStillActive);
+
+    bond.release_requested_at = clock.unix_timestamp;
+
+    emit!(BondReleaseRequested {
+        user_account: bond.user_account,
+        session_key: bond.session_key,
+        releasable_at: bond.release_requested_at + bond.dispute_window_seconds,
+    });
+
+    Ok(())
+}
+
+/// Slash part or all of a bond to the authority, backed by an upheld violation report
+pub fn slash_handler(ctx: Context<SlashBond>, amount: u64) -> Result<()> {
+    let bond = &ctx.accounts.bond;
+    let report = &ctx.accounts.report;
+    require!(
+        amount > 0 && amount <= bond.amount,
+        ErrorCode::InvalidAmount
+    );
+    require!(
+        report.status == DisputeStatus::Upheld
+            && !report.slash_applied
+            && report.session_key == bond.session_key,
+        ErrorCode::ReportNotSlashable
+    );
+
+    pay_out(
+        bond,
+        &ctx.accounts.authority.to_account_info(),
+        ctx.accounts.bond_vault.as_ref(),
+        ctx.accounts.destination_token.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        amount,
+    )?;
+
+    ctx.accounts.report.slash_applied = true;
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.total_slashed += amount;
+    reputation.last_updated = Clock::get()?.unix_timestamp;
+    let bond = &mut ctx.accounts.bond;
+    bond.amount -= amount;
+    bond.slashed_amount += amount;
+
+    msg!(
+        "Slashed {} from bond of session {}",
+        amount,
+        bond.session_key
+    );
+
+    emit!(BondSlashed {
+        user_account: bond.user_account,
+        session_key: bond.session_key,
+        operator: bond.operator,
+        amount,
+        report: ctx.accounts.report.key(),
+        evidence: ctx.accounts.report.evidence,
+    });
+
+    Ok(())
+}
+
+/// Return the remaining bond to the operator once the dispute window has elapsed
+pub fn withdraw_handler(ctx: Context<WithdrawBond>) -> Result<()> {
+    let clock = Clock::get()?;
+    let bond = &ctx.accounts.bond;
+    require!(bond.is_releasable(&clock), ErrorCode::BondLocked);
+    require!(bond.open_disputes == 0, ErrorCode::DisputesOpen);
+
+    // SOL bonds are returned with the rent when Anchor closes the bond account
+    if bond.mint.is_some() {
+        pay_out(
+            bond,
+            &ctx.accounts.operator.to_account_info(),
+            ctx.accounts.bond_vault.as_ref(),
+            ctx.accounts.destination_token.as_ref(),
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            bond.amount,
+        )?;
+
+        let (vault, token_program) = (
+            ctx.accounts.bond_vault.as_ref().unwrap(),
+            ctx.accounts.token_program.as_ref().unwrap(),
+        );
+        let bump = [bond.bump];
+        let seeds: &[&[u8]] = &[
+            OperatorBond::SEED_PREFIX,
+            bond.user_account.as_ref(),
+            bond.session_key.as_ref(),
+            &bump,
+        ];
+        token_interface::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: vault.to_account_info(),
+                destination: ctx.accounts.operator.to_account_info(),
+                authority: bond.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+    }
+
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.sessions_completed += 1;
+    reputation.total_session_seconds += (bond.release_requested_at - bond.posted_at).max(0) as u64;
+    reputation.last_updated = clock.unix_timestamp;
+
+    emit!(BondWithdrawn {
+        user_account: bond.user_account,
+        session_key: bond.session_key,
+        operator: bond.operator,
+        amount: bond.amount,
+    });
+
+    Ok(())
+}
+
+/// Ensure the session exists and return the dispute window to apply to its bond
+fn bonded_session_window(user_account: &UserAccount, session_pubkey: &Pubkey) -> Result<i64> {
+    require!(
+        user_account.find_session_key(session_pubkey).is_some(),
+        ErrorCode::SessionKeyNotFound
+    );
+    Ok(user_account
+        .bond_requirement
+        .map(|r| r.dispute_window_seconds)
+        .unwrap_or(0))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn init_bond(
+    bond: &mut OperatorBond,
+    user_account: Pubkey,
+    session_key: Pubkey,
+    operator: Pubkey,
+    mint: Option<Pubkey>,
+    amount: u64,
+    dispute_window_seconds: i64,
+    bump: u8,
+) -> Result<()> {
+    bond.user_account = user_account;
+    bond.session_key = session_key;
+    bond.operator = operator;
+    bond.mint = mint;
+    bond.amount = amount;
+    bond.slashed_amount = 0;
+    bond.posted_at = Clock::get()?.unix_timestamp;
+    bond.release_requested_at = 0;
+    bond.dispute_window_seconds = dispute_window_seconds;
+    bond.open_disputes = 0;
+    bond.bump = bump;
+
+    emit!(BondPosted {
+        user_account,
+        session_key,
+        operator,
+        mint,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Move `amount` of the bonded asset out of escrow: lamports to `recipient` for SOL bonds,
+/// tokens to `destination_token` for SPL bonds
+fn pay_out<'info>(
+    bond: &Account<'info, OperatorBond>,
+    recipient: &AccountInfo<'info>,
+    bond_vault: Option<&InterfaceAccount<'info, TokenAccount>>,
+    destination_token: Option<&InterfaceAccount<'info, TokenAccount>>,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    token_program: Option<&Interface<'info, TokenInterface>>,
+    amount: u64,
+) -> Result<()> {
+    let Some(bond_mint) = bond.mint else {
+        let bond_info = bond.to_account_info();
+        **bond_info.try_borrow_mut_lamports()? -= amount;
+        **recipient.try_borrow_mut_lamports()? += amount;
+        return Ok(());
+    };
+
+    let (Some(vault), Some(destination), Some(mint), Some(token_program)) =
+        (bond_vault, destination_token, mint, token_program)
+    else {
+        return err!(ErrorCode::BondTokenAccountsInvalid);
+    };
+    require_keys_eq!(mint.key(), bond_mint, ErrorCode::BondTokenAccountsInvalid);
+    require_keys_eq!(
+        destination.owner,
+        recipient.key(),
+        ErrorCode::BondTokenAccountsInvalid
+    );
+
+    let bump = [bond.bump];
+    let seeds: &[&[u8]] = &[
+        OperatorBond::SEED_PREFIX,
+        bond.user_account.as_ref(),
+        bond.session_key.as_ref(),
+        &bump,
+    ];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: destination.to_account_info(),
+                authority: bond.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+        mint.decimals,
+    )
+}
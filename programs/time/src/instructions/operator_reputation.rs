@@ -0,0 +1,44 @@
+use crate::contexts::InitializeOperatorReputation;
+use crate::errors::ErrorCode;
+use crate::events::OperatorReputationInitialized;
+use crate::state::{OperatorBond, OperatorReputation};
+use anchor_lang::prelude::*;
+
+/// Create the reputation record an operator needs before posting bonds
+pub fn initialize_handler(ctx: Context<InitializeOperatorReputation>) -> Result<()> {
+    let reputation = &mut ctx.accounts.reputation;
+    reputation.operator = ctx.accounts.operator.key();
+    reputation.accounts_served = 0;
+    reputation.total_volume = 0;
+    reputation.violations_reported = 0;
+    reputation.violations_upheld = 0;
+    reputation.total_slashed = 0;
+    reputation.sessions_completed = 0;
+    reputation.total_session_seconds = 0;
+    reputation.last_updated = Clock::get()?.unix_timestamp;
+    reputation.bump = ctx.bumps.reputation;
+
+    emit!(OperatorReputationInitialized {
+        operator: reputation.operator,
+    });
+
+    Ok(())
+}
+
+/// When a bond is involved, return its operator's reputation record, which must be supplied
+pub fn bonded_reputation<'a, 'info>(
+    bond: Option<&Account<'info, OperatorBond>>,
+    reputation: Option<&'a mut Account<'info, OperatorReputation>>,
+) -> Result<Option<&'a mut Account<'info, OperatorReputation>>> {
+    let Some(bond) = bond else {
+        return Ok(None);
+    };
+    let reputation = reputation.ok_or(ErrorCode::OperatorReputationMismatch)?;
+    require_keys_eq!(
+        reputation.operator,
+        bond.operator,
+        ErrorCode::OperatorReputationMismatch
+    );
+    reputation.last_updated = Clock::get()?.unix_timestamp;
+    Ok(Some(reputation))
+}
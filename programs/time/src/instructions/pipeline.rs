@@ -0,0 +1,427 @@
+use crate::constants::MAX_PIPELINE_STEPS;
+use crate::contexts::{ClosePipeline, CreatePipeline, ExecutePipeline};
+use crate::errors::ErrorCode;
+use crate::events::{PipelineClosed, PipelineCreated, PipelineExecuted};
+use crate::guard::{action_accounts, enforce_guard, GuardCheck, GuardedAction};
+use crate::mint_risk::require_mint_allowed;
+use crate::policy::{enforce_policy, PolicyRequest};
+use crate::slot_proof::{verify_recent_slot, SlotReference};
+use crate::state::{PipelineStep, PipelineStepArgs, ProgramConfig, UserAccount};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TransferChecked};
+use anchor_spl::{token, token_2022};
+
+/// Define a payout pipeline for the account's session keys
+pub fn create_handler(
+    ctx: Context<CreatePipeline>,
+    pipeline_id: u16,
+    steps: Vec<PipelineStep>,
+) -> Result<()> {
+    require!(
+        !steps.is_empty() && steps.len() <= MAX_PIPELINE_STEPS,
+        ErrorCode::InvalidPipeline
+    );
+    for step in &steps {
+        let valid = match step {
+            PipelineStep::Transfer { max_amount, .. } => *max_amount > 0,
+            // The delegate PDA signs swap CPIs, so never hand it to this program itself or
+            // to a token program, where its signature moves tokens anywhere directly
+            PipelineStep::Swap {
+                program,
+                max_amount_in,
+                min_amount_out,
+                ..
+            } => {
+                *max_amount_in > 0
+                    && *min_amount_out > 0
+                    && ![crate::ID, token::ID, token_2022::ID].contains(program)
+            }
+        };
+        require!(valid, ErrorCode::InvalidPipeline);
+    }
+
+    let pipeline = &mut ctx.accounts.pipeline;
+    pipeline.user_account = ctx.accounts.user_account.key();
+    pipeline.pipeline_id = pipeline_id;
+    pipeline.steps = steps.clone();
+    pipeline.bump = ctx.bumps.pipeline;
+
+    msg!(
+        "Pipeline {} created with {} steps",
+        pipeline_id,
+        steps.len()
+    );
+
+    emit!(PipelineCreated {
+        authority: ctx.accounts.authority.key(),
+        pipeline: pipeline.key(),
+        pipeline_id,
+        steps,
+    });
+
+    Ok(())
+}
+
+/// Delete a pipeline; the PDA is closed back to the authority
+pub fn close_handler(ctx: Context<ClosePipeline>) -> Result<()> {
+    emit!(PipelineClosed {
+        authority: ctx.accounts.authority.key(),
+        pipeline: ctx.accounts.pipeline.key(),
+    });
+
+    Ok(())
+}
+
+/// Run every step of the pipeline in order, signed by the per-mint delegate PDAs.
+///
+/// On top of the template's per-step limits, everything the steps move is charged to the
+/// session's caps and to the account's spend budget like a delegated transfer; its
+/// validity, use limits, mint and recipient allowlists, the account's blackout calendar
+/// and slot proof requirement all apply. A run counts as one use of the key. Accounts
+/// using controls the steps don't apply, such as the blocklist or recipient consent, and
+/// steps that could owe a protocol fee or savings cut are refused.
+pub fn execute_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecutePipeline<'info>>,
+    step_args: Vec<PipelineStepArgs>,
+    slot_ref: Option<SlotReference>,
+    scope: Option<Pubkey>,
+) -> Result<()> {
+    let user_account = &ctx.accounts.user_account;
+    let session_pubkey = ctx.accounts.session_signer.key();
+    let steps = ctx.accounts.pipeline.steps.clone();
+    let clock = Clock::get()?;
+
+    require!(
+        step_args.len() == steps.len(),
+        ErrorCode::PipelineStepMismatch
+    );
+
+    let session_key =
+        user_account.session_key(ctx.accounts.session_key_account.as_deref(), &session_pubkey)?;
+
+    // Validate; the amounts are charged once the steps have run and been measured
+    require!(
+        !session_key.is_locked(clock.unix_timestamp),
+        ErrorCode::SessionKeyLocked
+    );
+    session_key.check_spend(0, &clock)?;
+    require!(!user_account.is_paused, ErrorCode::AccountPaused);
+    ProgramConfig::enforce(&ctx.accounts.program_config, 0)?;
+    require!(
+        session_key.allows_scope(scope.as_ref()),
+        ErrorCode::SessionScopeMismatch
+    );
+    if let Some(window) = user_account.active_blackout(clock.unix_timestamp) {
+        msg!("Blackout in effect until {}", window.end);
+        return err!(ErrorCode::BlackoutActive);
+    }
+    if user_account.max_slot_age > 0 {
+        let slot_hashes = ctx
+            .accounts
+            .slot_hashes
+            .as_ref()
+            .ok_or(ErrorCode::SlotProofRequired)?;
+        let reference = slot_ref.as_ref().ok_or(ErrorCode::SlotProofRequired)?;
+        verify_recent_slot(
+            slot_hashes,
+            reference,
+            user_account.max_slot_age,
+            clock.slot,
+        )?;
+    }
+    // Steps move tokens without the attestation, blocklist, consent, bond or operator
+    // checks of a direct transfer, so accounts relying on any of them refuse runs
+    require!(
+        !user_account.has_token_only_controls() && !user_account.require_recipient_consent,
+        ErrorCode::PipelineControlsUnsupported
+    );
+
+    // Every mint the pipeline touches must pass the account and session allowlists, and
+    // a budgeted one needs its budget passed; a run can charge a single budget. Steps take
+    // no protocol fee or savings cut either, so a step that would owe one is refused
+    for step in &steps {
+        let (mint, limit) = match step {
+            PipelineStep::Transfer {
+                mint, max_amount, ..
+            } => (mint, *max_amount),
+            PipelineStep::Swap {
+                mint_in,
+                max_amount_in,
+                ..
+            } => (mint_in, *max_amount_in),
+        };
+        require!(
+            ProgramConfig::protocol_fee(&ctx.accounts.program_config, limit)? == 0
+                && user_account
+                    .savings_rule
+                    .filter(|r| r.mint == *mint)
+                    .is_none(),
+            ErrorCode::PipelineControlsUnsupported
+        );
+        require!(
+            session_key.allows_mint(&user_account.allowed_mints, mint),
+            ErrorCode::MintNotAllowed
+        );
+        if user_account.budgeted_mints.contains(mint) {
+            require!(
+                ctx.accounts
+                    .spend_budget
+                    .as_ref()
+                    .is_some_and(|budget| budget.mint == *mint),
+                ErrorCode::SpendBudgetMissing
+            );
+        }
+    }
+
+    let mut remaining = action_accounts(user_account.guard_program, ctx.remaining_accounts);
+    let mut amounts = Vec::with_capacity(steps.len());
+    let mut total: u64 = 0;
+    let mut budgeted: u64 = 0;
+    for (step, args) in steps.iter().zip(step_args.iter()) {
+        let amount = match *step {
+            PipelineStep::Transfer {
+                mint,
+                destination,
+                max_amount,
+            } => {
+                let [from_token, mint_info, to_token, delegate, rest @ ..] = remaining else {
+                    return err!(ErrorCode::PipelineStepMismatch);
+                };
+                remaining = rest;
+                require_keys_eq!(mint_info.key(), mint, ErrorCode::PipelineStepMismatch);
+                require_keys_eq!(to_token.key(), destination, ErrorCode::PipelineStepMismatch);
+                require!(
+                    args.amount <= max_amount,
+                    ErrorCode::PipelineStepLimitExceeded
+                );
+                ProgramConfig::enforce(&ctx.accounts.program_config, args.amount)?;
+                let recipient = InterfaceAccount::<TokenAccount>::try_from(to_token)?;
+                require!(
+                    session_key.allows_recipient(&recipient.owner, &destination),
+                    ErrorCode::RecipientNotAllowed
+                );
+                enforce_policy(
+                    user_account,
+                    ctx.accounts.policy.as_deref(),
+                    &PolicyRequest {
+                        session_key: session_pubkey,
+                        now: clock.unix_timestamp,
+                        amount: args.amount,
+                        mint: Some(mint),
+                        recipient: Some(recipient.owner),
+                        recipient_account: Some(destination),
+                    },
+                )?;
+                require_mint_allowed(user_account, mint_info)?;
+                ProgramConfig::enforce_mint(&ctx.accounts.program_config, &mint)?;
+                let source = InterfaceAccount::<TokenAccount>::try_from(from_token)?;
+                user_account.check_source_token(&source, &mint)?;
+                let bump = delegate_bump(user_account, &mint, delegate, ctx.program_id)?;
+                let decimals = InterfaceAccount::<Mint>::try_from(mint_info)?.decimals;
+
+                let user_key = user_account.key();
+                let seeds: &[&[u8]] = &[b"delegate", user_key.as_ref(), mint.as_ref(), &[bump]];
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        TransferChecked {
+                            from: from_token.clone(),
+                            mint: mint_info.clone(),
+                            to: to_token.clone(),
+                            authority: delegate.clone(),
+                        },
+                        &[seeds],
+                    ),
+                    args.amount,
+                    decimals,
+                )?;
+                args.amount
+            }
+            PipelineStep::Swap {
+                program,
+                mint_in,
+                max_amount_in,
+                destination,
+                min_amount_out,
+            } => {
+                let [swap_program, source_token, destination_token, delegate, rest @ ..] =
+                    remaining
+                else {
+                    return err!(ErrorCode::PipelineStepMismatch);
+                };
+                let count = args.account_count as usize;
+                require!(rest.len() >= count, ErrorCode::PipelineStepMismatch);
+                let (forwarded, rest) = rest.split_at(count);
+                remaining = rest;
+                require_keys_eq!(swap_program.key(), program, ErrorCode::PipelineStepMismatch);
+                require!(swap_program.executable, ErrorCode::PipelineStepMismatch);
+                let bump = delegate_bump(user_account, &mint_in, delegate, ctx.program_id)?;
+
+                let source = InterfaceAccount::<TokenAccount>::try_from(source_token)?;
+                require_keys_eq!(source.mint, mint_in, ErrorCode::PipelineStepMismatch);
+                require_keys_eq!(
+                    source.owner,
+                    user_account.authority,
+                    ErrorCode::PipelineStepMismatch
+                );
+                let before = source.amount;
+
+                // The output lands in the pinned account, which must be the authority's
+                require_keys_eq!(
+                    destination_token.key(),
+                    destination,
+                    ErrorCode::PipelineStepMismatch
+                );
+                require_keys_neq!(
+                    destination_token.key(),
+                    source_token.key(),
+                    ErrorCode::PipelineStepMismatch
+                );
+                let output = InterfaceAccount::<TokenAccount>::try_from(destination_token)?;
+                require_keys_eq!(
+                    output.owner,
+                    user_account.authority,
+                    ErrorCode::PipelineStepMismatch
+                );
+                let output_before = output.amount;
+
+                // The delegate PDA signs for every account of `mint_in` approved to it, so
+                // the swap is only handed the one whose balance is measured
+                require!(
+                    forwarded
+                        .iter()
+                        .all(|a| a.key() == source_token.key() || !approves(a, &delegate.key())),
+                    ErrorCode::SwapAccountNotAllowed
+                );
+
+                // Forward the accounts as given, with the delegate PDA promoted to signer
+                let accounts = forwarded
+                    .iter()
+                    .map(|a| {
+                        let is_signer = a.is_signer || a.key() == delegate.key();
+                        if a.is_writable {
+                            AccountMeta::new(a.key(), is_signer)
+                        } else {
+                            AccountMeta::new_readonly(a.key(), is_signer)
+                        }
+                    })
+                    .collect();
+                let mut infos = forwarded.to_vec();
+                infos.push(swap_program.clone());
+
+                let user_key = user_account.key();
+                let seeds: &[&[u8]] = &[b"delegate", user_key.as_ref(), mint_in.as_ref(), &[bump]];
+                invoke_signed(
+                    &Instruction {
+                        program_id: program,
+                        accounts,
+                        data: args.data.clone(),
+                    },
+                    &infos,
+                    &[seeds],
+                )?;
+
+                // Measure what the swap actually took rather than trusting its instruction data
+                let after = InterfaceAccount::<TokenAccount>::try_from(source_token)?.amount;
+                let spent = before.saturating_sub(after);
+                require!(spent <= max_amount_in, ErrorCode::PipelineStepLimitExceeded);
+                let received = InterfaceAccount::<TokenAccount>::try_from(destination_token)?
+                    .amount
+                    .saturating_sub(output_before);
+                require!(received >= min_amount_out, ErrorCode::SwapOutputTooLow);
+                ProgramConfig::enforce(&ctx.accounts.program_config, spent)?;
+                enforce_policy(
+                    user_account,
+                    ctx.accounts.policy.as_deref(),
+                    &PolicyRequest {
+                        session_key: session_pubkey,
+                        now: clock.unix_timestamp,
+                        amount: spent,
+                        mint: Some(mint_in),
+                        recipient: None,
+                        recipient_account: None,
+                    },
+                )?;
+                spent
+            }
+        };
+        let (PipelineStep::Transfer { mint, .. } | PipelineStep::Swap { mint_in: mint, .. }) = step;
+        if user_account.budgeted_mints.contains(mint) {
+            budgeted = budgeted
+                .checked_add(amount)
+                .ok_or(ErrorCode::SpendBudgetExceeded)?;
+        }
+        total = total
+            .checked_add(amount)
+            .ok_or(ErrorCode::SessionSpendCapExceeded)?;
+        amounts.push(amount);
+    }
+
+    // Give the account's guard program, if any, a chance to veto the run
+    enforce_guard(
+        user_account.guard_program,
+        &user_account.to_account_info(),
+        &ctx.accounts.session_signer.to_account_info(),
+        ctx.remaining_accounts,
+        &GuardCheck {
+            user_account: user_account.key(),
+            authority: user_account.authority,
+            session_key: session_pubkey,
+            action: GuardedAction::Pipeline {
+                pipeline: ctx.accounts.pipeline.key(),
+                total,
+            },
+        },
+    )?;
+
+    // Charge the run to the account's budget and the session's caps, as one use
+    if budgeted > 0 {
+        ctx.accounts
+            .spend_budget
+            .as_mut()
+            .ok_or(ErrorCode::SpendBudgetMissing)?
+            .charge(budgeted, &clock)?;
+    }
+    let charge = ctx
+        .accounts
+        .user_account
+        .session_key(ctx.accounts.session_key_account.as_deref(), &session_pubkey)?
+        .check_spend(total, &clock)?;
+    ctx.accounts
+        .user_account
+        .session_key_mut(
+            ctx.accounts.session_key_account.as_deref_mut(),
+            &session_pubkey,
+        )?
+        .record_spend(&charge);
+    let user_account = &ctx.accounts.user_account;
+
+    emit!(PipelineExecuted {
+        authority: user_account.authority,
+        session_key: session_pubkey,
+        pipeline: ctx.accounts.pipeline.key(),
+        amounts,
+    });
+
+    Ok(())
+}
+
+/// Whether `account` is a token account approving `delegate`
+fn approves<'info>(account: &'info AccountInfo<'info>, delegate: &Pubkey) -> bool {
+    InterfaceAccount::<TokenAccount>::try_from(account)
+        .is_ok_and(|token| Option::from(token.delegate) == Some(*delegate))
+}
+
+/// Check `delegate` is the account's delegate PDA for `mint` and return its bump
+fn delegate_bump(
+    user_account: &Account<UserAccount>,
+    mint: &Pubkey,
+    delegate: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<u8> {
+    let (expected, bump) = user_account.delegate_address(&user_account.key(), mint, program_id);
+    require_keys_eq!(expected, delegate.key(), ErrorCode::InsufficientPermissions);
+    Ok(bump)
+}
@@ -0,0 +1,190 @@
+use crate::constants::{MAX_DENIED_MINTS, MAX_PROTOCOL_FEE_BPS};
+use crate::contexts::{InitializeProgramConfig, UpdateProgramConfig, WithdrawFees};
+use crate::errors::ErrorCode;
+use crate::events::{
+    FeesWithdrawn, MintDenylistUpdated, ProgramConfigUpdated, ProgramPauseSet, ProtocolFeeSet,
+};
+use crate::state::Treasury;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, TransferChecked};
+
+/// Create the program config and the fee treasury; the program upgrade authority
+/// becomes their admin
+pub fn initialize_handler(
+    ctx: Context<InitializeProgramConfig>,
+    max_transfer_amount: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.program_config;
+    config.admin = ctx.accounts.admin.key();
+    config.is_paused = false;
+    config.max_transfer_amount = max_transfer_amount;
+    config.bump = ctx.bumps.program_config;
+    config.denied_mints = Vec::new();
+    config.fee_bps = 0;
+    ctx.accounts.treasury.bump = ctx.bumps.treasury;
+
+    msg!("Program config initialized, admin: {}", config.admin);
+
+    emit!(ProgramConfigUpdated {
+        admin: config.admin,
+        max_transfer_amount,
+    });
+
+    Ok(())
+}
+
+/// Flip the circuit breaker; while paused no session key can execute anywhere
+pub fn set_paused_handler(ctx: Context<UpdateProgramConfig>, paused: bool) -> Result<()> {
+    let config = &mut ctx.accounts.program_config;
+    config.is_paused = paused;
+
+    msg!("Program paused: {}", paused);
+
+    emit!(ProgramPauseSet {
+        admin: config.admin,
+        paused,
+    });
+
+    Ok(())
+}
+
+/// Hand the config to a new admin and change the global limits
+pub fn update_handler(
+    ctx: Context<UpdateProgramConfig>,
+    admin: Pubkey,
+    max_transfer_amount: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.program_config;
+    config.admin = admin;
+    config.max_transfer_amount = max_transfer_amount;
+
+    emit!(ProgramConfigUpdated {
+        admin,
+        max_transfer_amount,
+    });
+
+    Ok(())
+}
+
+/// Apply additions and removals to the mint denylist, keeping it sorted and deduplicated
+pub fn update_mint_denylist_handler(
+    ctx: Context<UpdateProgramConfig>,
+    additions: Vec<Pubkey>,
+    removals: Vec<Pubkey>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.program_config;
+    let before = config.denied_mints.len();
+
+    config.denied_mints.retain(|m| !removals.contains(m));
+    let removed = before - config.denied_mints.len();
+
+    let mut added = 0u32;
+    for mint in additions {
+        if let Err(pos) = config.denied_mints.binary_search(&mint) {
+            config.denied_mints.insert(pos, mint);
+            added += 1;
+        }
+    }
+    require!(
+        config.denied_mints.len() <= MAX_DENIED_MINTS,
+        ErrorCode::MintDenylistFull
+    );
+
+    emit!(MintDenylistUpdated {
+        admin: config.admin,
+        added,
+        removed: removed as u32,
+        total: config.denied_mints.len() as u32,
+    });
+
+    Ok(())
+}
+
+/// Set the protocol fee charged on top of each session transfer
+pub fn set_fee_handler(ctx: Context<UpdateProgramConfig>, fee_bps: u16) -> Result<()> {
+    require!(
+        fee_bps <= MAX_PROTOCOL_FEE_BPS,
+        ErrorCode::InvalidProtocolFee
+    );
+
+    let config = &mut ctx.accounts.program_config;
+    config.fee_bps = fee_bps;
+
+    msg!("Protocol fee set to: {} bps", fee_bps);
+
+    emit!(ProtocolFeeSet {
+        admin: config.admin,
+        fee_bps,
+    });
+
+    Ok(())
+}
+
+/// Move collected fees out of the treasury: tokens from its account for `mint` when one
+/// is passed, lamports above its rent reserve otherwise
+pub fn withdraw_fees_handler(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    let destination = ctx.accounts.destination.to_account_info();
+
+    let mint = match ctx.accounts.mint.as_ref() {
+        Some(mint) => {
+            let fee_token = ctx
+                .accounts
+                .fee_token
+                .as_ref()
+                .ok_or(ErrorCode::FeeAccountMissing)?;
+            let token_program = ctx
+                .accounts
+                .token_program
+                .as_ref()
+                .ok_or(ErrorCode::FeeAccountMissing)?;
+            require!(
+                fee_token.owner == ctx.accounts.treasury.key() && fee_token.mint == mint.key(),
+                ErrorCode::FeeAccountMismatch
+            );
+            require!(
+                fee_token.amount >= amount,
+                ErrorCode::InsufficientTreasuryBalance
+            );
+
+            let seeds: &[&[u8]] = &[Treasury::SEED_PREFIX, &[ctx.accounts.treasury.bump]];
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: fee_token.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: destination.clone(),
+                        authority: ctx.accounts.treasury.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                amount,
+                mint.decimals,
+            )?;
+            Some(mint.key())
+        }
+        None => {
+            // Same as the user vault: the treasury carries data, so the program debits it
+            // directly and keeps it rent exempt
+            let treasury = ctx.accounts.treasury.to_account_info();
+            let reserve = Rent::get()?.minimum_balance(treasury.data_len());
+            require!(
+                treasury.lamports().saturating_sub(reserve) >= amount,
+                ErrorCode::InsufficientTreasuryBalance
+            );
+            **treasury.try_borrow_mut_lamports()? -= amount;
+            **destination.try_borrow_mut_lamports()? += amount;
+            None
+        }
+    };
+
+    emit!(FeesWithdrawn {
+        admin: ctx.accounts.admin.key(),
+        mint,
+        destination: destination.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::contexts::PublishBuildHash;
+use crate::events::BuildHashPublished;
+use anchor_lang::prelude::*;
+
+/// Record `build_hash` as a verified build of this program. Hashing the deployed
+/// executable doesn't fit in a transaction, so the upgrade authority vouches for it;
+/// `app/verify_deployment.ts` checks the claim against the deployed bytes off-chain.
+pub fn handler(
+    ctx: Context<PublishBuildHash>,
+    build_hash: [u8; 32],
+    source_commit: [u8; 20],
+) -> Result<()> {
+    let published_build = &mut ctx.accounts.published_build;
+    published_build.build_hash = build_hash;
+    published_build.source_commit = source_commit;
+    published_build.deployment_slot = ctx.accounts.program_data.slot;
+    published_build.published_at = Clock::get()?.unix_timestamp;
+    published_build.bump = ctx.bumps.published_build;
+
+    emit!(BuildHashPublished {
+        build_hash,
+        source_commit,
+        deployment_slot: published_build.deployment_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::contexts::{AcceptPayments, SetRecipientConsentRequired, WithdrawPaymentConsent};
+use crate::events::{RecipientConsentChanged, RecipientConsentRequirementUpdated};
+use anchor_lang::prelude::*;
+
+/// Turn the recipient consent requirement on or off. While on, SOL and delegated token
+/// payouts must pass the recipient's `RecipientConsent`, so funds can't be pushed to
+/// addresses that never agreed to receive them.
+pub fn set_required_handler(
+    ctx: Context<SetRecipientConsentRequired>,
+    required: bool,
+) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.require_recipient_consent = required;
+
+    msg!("Recipient consent required: {}", required);
+
+    emit!(RecipientConsentRequirementUpdated {
+        authority: user_account.authority,
+        required,
+    });
+
+    Ok(())
+}
+
+/// Record the signer's acceptance of session payouts from the user account
+pub fn accept_handler(ctx: Context<AcceptPayments>) -> Result<()> {
+    let consent = &mut ctx.accounts.recipient_consent;
+    consent.user_account = ctx.accounts.user_account.key();
+    consent.recipient = ctx.accounts.recipient.key();
+    consent.accepted_at = Clock::get()?.unix_timestamp;
+    consent.bump = ctx.bumps.recipient_consent;
+
+    emit!(RecipientConsentChanged {
+        user_account: consent.user_account,
+        recipient: consent.recipient,
+        accepted: true,
+    });
+
+    Ok(())
+}
+
+/// Close the signer's consent; later payouts to it fail while consent is required
+pub fn withdraw_handler(ctx: Context<WithdrawPaymentConsent>) -> Result<()> {
+    let consent = &ctx.accounts.recipient_consent;
+
+    emit!(RecipientConsentChanged {
+        user_account: consent.user_account,
+        recipient: consent.recipient,
+        accepted: false,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use crate::constants::MAX_SESSION_KEY_CAPACITY;
+use crate::contexts::ResizeUserAccount;
+use crate::errors::ErrorCode;
+use crate::events::UserAccountResized;
+use anchor_lang::prelude::*;
+
+/// Change how many session keys the account has space for. The `realloc` constraint
+/// has already resized the data: growing draws rent from the vault before charging the
+/// authority, and shrinking sends everything above the new rent minimum to the authority.
+/// A call grows the account by at most 10,240 bytes, so larger jumps take several calls.
+pub fn handler(ctx: Context<ResizeUserAccount>, new_capacity: u16) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    require!(
+        new_capacity as usize <= MAX_SESSION_KEY_CAPACITY
+            && new_capacity as usize >= user_account.session_keys.len(),
+        ErrorCode::InvalidSessionKeyCapacity
+    );
+
+    let old_capacity = user_account.session_key_capacity;
+    user_account.session_key_capacity = new_capacity;
+
+    emit!(UserAccountResized {
+        authority: user_account.authority,
+        old_capacity,
+        new_capacity,
+    });
+
+    Ok(())
+}
@@ -1,14 +1,19 @@
-use anchor_lang::prelude::*;
 use crate::contexts::RevokeAllSessionKeys;
 use crate::events::AllSessionKeysRevoked;
+use anchor_lang::prelude::*;
 
 /// Revoke all session keys at once (emergency function)
 pub fn handler(ctx: Context<RevokeAllSessionKeys>) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
+    let now = Clock::get()?.unix_timestamp;
 
     for session_key in &mut user_account.session_keys {
-        session_key.is_revoked = true;
+        if !session_key.is_revoked {
+            session_key.revoke(None, now);
+        }
     }
+    // Invalidates every SessionKeyAccount without having to touch each one
+    user_account.session_generation = user_account.session_generation.wrapping_add(1);
 
     msg!(
         "All session keys revoked for authority: {}",
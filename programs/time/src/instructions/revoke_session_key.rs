@@ -1,32 +1,66 @@
-use anchor_lang::prelude::*;
 use crate::contexts::RevokeSessionKey;
 use crate::errors::ErrorCode;
 use crate::events::SessionKeyRevoked;
+use crate::state::{RevocationReason, UserAccount};
+use anchor_lang::prelude::*;
 
-/// Revoke an existing session key
+/// Revoke an existing session key and any sub-keys it delegated, recording why
 pub fn handler(
     ctx: Context<RevokeSessionKey>,
     session_pubkey: Pubkey,
+    reason: RevocationReason,
 ) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
+    let now = Clock::get()?.unix_timestamp;
 
     // Find and revoke the session key
     let session_key = user_account
-        .session_keys
-        .iter_mut()
-        .find(|k| k.pubkey == session_pubkey)
+        .find_session_key_mut(&session_pubkey)
         .ok_or(ErrorCode::SessionKeyNotFound)?;
 
     require!(!session_key.is_revoked, ErrorCode::SessionKeyAlreadyRevoked);
 
-    session_key.is_revoked = true;
+    session_key.revoke(Some(reason), now);
+    let terms_hash = session_key.terms_hash;
 
-    msg!("Session key revoked: {}", session_pubkey);
+    msg!("Session key revoked: {} ({:?})", session_pubkey, reason);
 
     emit!(SessionKeyRevoked {
         authority: user_account.authority,
         session_key: session_pubkey,
+        terms_hash,
+        reason,
     });
 
+    revoke_sub_keys(user_account, session_pubkey, reason, now);
+
     Ok(())
 }
+
+/// Revoke the sub-keys `parent` delegated, down the whole chain. Sub-keys are always
+/// stored inline, whether their parent is or lives in a `SessionKeyAccount`.
+pub fn revoke_sub_keys(
+    user_account: &mut UserAccount,
+    parent: Pubkey,
+    reason: RevocationReason,
+    now: i64,
+) {
+    let authority = user_account.authority;
+    let mut parents = vec![parent];
+    while let Some(parent) = parents.pop() {
+        for key in user_account
+            .session_keys
+            .iter_mut()
+            .filter(|k| !k.is_revoked && k.delegated_by == Some(parent))
+        {
+            key.revoke(Some(reason), now);
+            parents.push(key.pubkey);
+            emit!(SessionKeyRevoked {
+                authority,
+                session_key: key.pubkey,
+                terms_hash: key.terms_hash,
+                reason,
+            });
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::contexts::RevokeAllSessionKeys;
+use crate::events::SessionKeyRevoked;
+use crate::state::RevocationReason;
+use anchor_lang::prelude::*;
+
+/// Revoke every live inline key that can still be valid after `horizon`, leaving
+/// shorter-lived keys alone. Keys stored in `SessionKeyAccount`s aren't covered; revoke
+/// them with `revoke_session_key_account`.
+pub fn handler(ctx: Context<RevokeAllSessionKeys>, horizon: i64) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let authority = user_account.authority;
+    let clock = Clock::get()?;
+
+    let mut count = 0u32;
+    for session_key in &mut user_account.session_keys {
+        if session_key.is_revoked
+            || session_key.is_expired(&clock)
+            || !session_key.expires_after(horizon, &clock)
+        {
+            continue;
+        }
+        session_key.revoke(Some(RevocationReason::Manual), clock.unix_timestamp);
+        count += 1;
+
+        emit!(SessionKeyRevoked {
+            authority,
+            session_key: session_key.pubkey,
+            terms_hash: session_key.terms_hash,
+            reason: RevocationReason::Manual,
+        });
+    }
+
+    msg!("Revoked {} session keys expiring after {}", count, horizon);
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::contexts::UpdateSessionKey;
+use crate::errors::ErrorCode;
+use crate::events::{SessionKeyRevoked, SessionKeyRotated};
+use crate::instructions::create_session_key::add_session_key;
+use crate::state::RevocationReason;
+use anchor_lang::prelude::*;
+
+/// Replace a session key with a new pubkey in one transaction.
+///
+/// The old key is revoked as `Rotated` and kept for the record; the new one copies its
+/// permissions, allowlists, expiry and spend and use counters, so rotating can't be
+/// used to reset limits. Sub-keys the old key delegated move to the new one, so they
+/// are still revoked with it. The new key takes a slot of its own, so a full account
+/// needs `cleanup_session_keys` first.
+pub fn handler(
+    ctx: Context<UpdateSessionKey>,
+    old_session_key: Pubkey,
+    new_session_key: Pubkey,
+) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let authority = user_account.authority;
+    let clock = Clock::get()?;
+
+    let old = user_account
+        .find_session_key_mut(&old_session_key)
+        .ok_or(ErrorCode::SessionKeyNotFound)?;
+    require!(!old.is_revoked, ErrorCode::SessionKeyRevoked);
+    require!(!old.is_expired(&clock), ErrorCode::SessionKeyExpired);
+
+    let mut rotated = old.clone();
+    old.revoke(Some(RevocationReason::Rotated), clock.unix_timestamp);
+    let terms_hash = old.terms_hash;
+
+    rotated.pubkey = new_session_key;
+    rotated.created_at = clock.unix_timestamp;
+    rotated.is_suspended = false;
+    add_session_key(user_account, rotated, &clock, false)?;
+    for key in user_account
+        .session_keys
+        .iter_mut()
+        .filter(|k| k.delegated_by == Some(old_session_key))
+    {
+        key.delegated_by = Some(new_session_key);
+    }
+
+    msg!(
+        "Session key {} rotated to {}",
+        old_session_key,
+        new_session_key
+    );
+
+    emit!(SessionKeyRevoked {
+        authority,
+        session_key: old_session_key,
+        terms_hash,
+        reason: RevocationReason::Rotated,
+    });
+    emit!(SessionKeyRotated {
+        authority,
+        old_session_key,
+        new_session_key,
+    });
+
+    Ok(())
+}
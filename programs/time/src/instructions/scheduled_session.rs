@@ -0,0 +1,96 @@
+use crate::constants::{
+    MAX_ALLOWED_DISCRIMINATORS, MAX_ALLOWED_PROGRAMS, MAX_ALLOWED_RECIPIENTS, MAX_SESSION_MINTS,
+};
+use crate::contexts::{ActivateScheduledSession, CancelScheduledSession, ScheduleSessionKey};
+use crate::errors::ErrorCode;
+use crate::events::{ScheduledSessionCancelled, SessionKeyScheduled};
+use crate::instructions::create_session_key::{add_session_key, build_session_key};
+use crate::state::{CreateSessionKeyArgs, DiscriminatorFilter};
+use anchor_lang::prelude::*;
+
+/// Record a grant that becomes a session key once `activate_at` passes
+pub fn schedule_handler(
+    ctx: Context<ScheduleSessionKey>,
+    args: CreateSessionKeyArgs,
+    activate_at: i64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(
+        activate_at > clock.unix_timestamp,
+        ErrorCode::InvalidActivationTime
+    );
+    if let Some(expires_at_time) = args.expires_at_time {
+        require!(
+            expires_at_time > activate_at,
+            ErrorCode::InvalidActivationTime
+        );
+    }
+
+    require!(
+        args.allowed_recipients.len() <= MAX_ALLOWED_RECIPIENTS,
+        ErrorCode::TooManyAllowedRecipients
+    );
+    require!(
+        args.allowed_mints.len() <= MAX_SESSION_MINTS,
+        ErrorCode::TooManySessionMints
+    );
+    require!(
+        args.allowed_programs.len() <= MAX_ALLOWED_PROGRAMS,
+        ErrorCode::TooManyAllowedPrograms
+    );
+    require!(
+        args.allowed_discriminators.len() <= MAX_ALLOWED_DISCRIMINATORS,
+        ErrorCode::TooManyAllowedDiscriminators
+    );
+    DiscriminatorFilter::check_all(&args.allowed_discriminators, &args.allowed_programs)?;
+
+    let session_pubkey = args.session_pubkey;
+    let expires_at_time = args.expires_at_time;
+    let expires_at_slot = args.expires_at_slot;
+
+    let scheduled = &mut ctx.accounts.scheduled_session;
+    scheduled.user_account = ctx.accounts.user_account.key();
+    scheduled.args = args;
+    scheduled.activate_at = activate_at;
+    scheduled.bump = ctx.bumps.scheduled_session;
+
+    msg!(
+        "Session key {} scheduled to activate at {}",
+        session_pubkey,
+        activate_at
+    );
+
+    emit!(SessionKeyScheduled {
+        authority: ctx.accounts.authority.key(),
+        session_key: session_pubkey,
+        activate_at,
+        expires_at_time,
+        expires_at_slot,
+    });
+
+    Ok(())
+}
+
+/// Cancel a grant before activation; the PDA is closed back to the authority
+pub fn cancel_handler(ctx: Context<CancelScheduledSession>) -> Result<()> {
+    emit!(ScheduledSessionCancelled {
+        authority: ctx.accounts.authority.key(),
+        session_key: ctx.accounts.scheduled_session.args.session_pubkey,
+    });
+
+    Ok(())
+}
+
+/// Add the scheduled session key to the account. Callable by anyone once due; the
+/// usual creation checks (expiry, key limit, duplicates) apply at this point.
+pub fn activate_handler(ctx: Context<ActivateScheduledSession>) -> Result<()> {
+    let clock = Clock::get()?;
+    let scheduled = &ctx.accounts.scheduled_session;
+    require!(
+        clock.unix_timestamp >= scheduled.activate_at,
+        ErrorCode::ScheduledSessionNotDue
+    );
+
+    let session_key = build_session_key(&scheduled.args, &clock);
+    add_session_key(&mut ctx.accounts.user_account, session_key, &clock, false)
+}
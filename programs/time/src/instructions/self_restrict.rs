@@ -0,0 +1,76 @@
+use crate::contexts::SelfRestrict;
+use crate::errors::ErrorCode;
+use crate::events::SessionKeyUpdated;
+use crate::state::{SessionKey, SessionPermissions};
+use anchor_lang::prelude::*;
+
+/// Let a session key drop privileges on its own, e.g. after a setup phase. It can
+/// narrow its permissions and add or shorten expiry bounds, never the reverse, so the
+/// authority doesn't need to co-sign.
+///
+/// New permissions are compared with the ones in force now and apply at once; they
+/// replace any widening still waiting out the account's delay.
+pub fn handler(
+    ctx: Context<SelfRestrict>,
+    new_expires_at_time: Option<i64>,
+    new_expires_at_slot: Option<i64>,
+    new_permissions: Option<SessionPermissions>,
+) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let authority = user_account.authority;
+    let session_pubkey = ctx.accounts.session_signer.key();
+    let clock = Clock::get()?;
+
+    let session_key = user_account.session_key_mut(
+        ctx.accounts.session_key_account.as_deref_mut(),
+        &session_pubkey,
+    )?;
+    require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
+    require!(
+        !session_key.is_expired(&clock),
+        ErrorCode::SessionKeyExpired
+    );
+
+    if new_expires_at_time.is_some() || new_expires_at_slot.is_some() {
+        SessionKey::check_expiry(new_expires_at_time, new_expires_at_slot, &clock)?;
+        let shortens = |new: Option<i64>, old: Option<i64>| match (new, old) {
+            (Some(new), Some(old)) => new <= old,
+            _ => true,
+        };
+        require!(
+            shortens(new_expires_at_time, session_key.expires_at_time)
+                && shortens(new_expires_at_slot, session_key.expires_at_slot),
+            ErrorCode::RestrictionWidens
+        );
+        if let Some(time) = new_expires_at_time {
+            session_key.set_expires_at_time(time, clock.unix_timestamp);
+        }
+        if let Some(slot) = new_expires_at_slot {
+            session_key.expires_at_slot = Some(slot);
+        }
+    }
+
+    if let Some(permissions) = new_permissions {
+        let current = session_key.permissions_at(clock.unix_timestamp);
+        require!(!permissions.widens(&current), ErrorCode::RestrictionWidens);
+        session_key.permissions = permissions;
+        session_key.prior_permissions = None;
+        session_key.widening_active_at = 0;
+    }
+
+    msg!("Session key restricted itself: {}", session_pubkey);
+
+    emit!(SessionKeyUpdated {
+        authority,
+        session_key: session_pubkey,
+        expires_at_time: session_key.expires_at_time,
+        expires_at_slot: session_key.expires_at_slot,
+        max_expires_at: session_key.max_expires_at,
+        permissions: session_key.permissions,
+        permissions_active_at: session_key.widening_active_at,
+        terms_hash: session_key.terms_hash,
+        label: session_key.label,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::contexts::{CreateSessionKeyFromPreset, PublishPreset, UpdatePreset};
+use crate::errors::ErrorCode;
+use crate::events::{PresetPublished, SessionKeyCreatedFromPreset};
+use crate::instructions::create_session_key::add_session_key;
+use crate::state::{ServicePreset, SessionKey, SessionPermissions};
+use anchor_lang::prelude::*;
+
+/// Publish a new preset under the operator's service entry
+pub fn publish_handler(
+    ctx: Context<PublishPreset>,
+    preset_id: u16,
+    permissions: SessionPermissions,
+    max_duration_seconds: i64,
+) -> Result<()> {
+    let preset = &mut ctx.accounts.preset;
+    preset.service = ctx.accounts.service.key();
+    preset.operator = ctx.accounts.operator.key();
+    preset.preset_id = preset_id;
+    preset.version = 0;
+    preset.bump = ctx.bumps.preset;
+
+    write_preset(preset, None, permissions, max_duration_seconds)
+}
+
+/// Replace a preset's contents, bumping its version and emitting the previous permissions
+pub fn update_handler(
+    ctx: Context<UpdatePreset>,
+    permissions: SessionPermissions,
+    max_duration_seconds: i64,
+) -> Result<()> {
+    let preset = &mut ctx.accounts.preset;
+    let previous = preset.permissions;
+    write_preset(preset, Some(previous), permissions, max_duration_seconds)
+}
+
+/// Grant a session exactly the preset's permissions, pinned to the reviewed version
+pub fn create_session_key_handler(
+    ctx: Context<CreateSessionKeyFromPreset>,
+    session_pubkey: Pubkey,
+    expires_at: i64,
+    expected_version: u32,
+    terms_hash: Option<[u8; 32]>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let preset = &ctx.accounts.preset;
+
+    require!(
+        preset.version == expected_version,
+        ErrorCode::PresetVersionMismatch
+    );
+    if preset.max_duration_seconds > 0 {
+        require!(
+            expires_at <= clock.unix_timestamp + preset.max_duration_seconds,
+            ErrorCode::PresetDurationExceeded
+        );
+    }
+
+    let session_key = SessionKey {
+        pubkey: session_pubkey,
+        created_at: clock.unix_timestamp,
+        expires_at_time: Some(expires_at),
+        expires_at_slot: None,
+        permissions: preset.permissions,
+        is_revoked: false,
+        label: [0; 32],
+        preset: Some(preset.key()),
+        preset_version: preset.version,
+        terms_hash: terms_hash.unwrap_or_default(),
+        total_spent: 0,
+        spend_window_start: clock.unix_timestamp,
+        spent_in_window: 0,
+        use_window_start: clock.unix_timestamp,
+        uses_in_window: 0,
+        max_uses: 0,
+        use_count: 0,
+        valid_from: 0,
+        allowed_recipients: Vec::new(),
+        allowed_mints: Vec::new(),
+        allowed_programs: Vec::new(),
+        allowed_discriminators: Vec::new(),
+        prior_permissions: None,
+        widening_active_at: 0,
+        is_suspended: false,
+        revocation_reason: None,
+        failed_attempts: 0,
+        failure_window_start: clock.unix_timestamp,
+        locked_until: 0,
+        delegated_by: None,
+        last_used_at: 0,
+        idle_timeout: 0,
+        max_expires_at: 0,
+        revoked_at: 0,
+        scope: Pubkey::default(),
+        domain_hash: [0; 32],
+    };
+    add_session_key(&mut ctx.accounts.user_account, session_key, &clock, false)?;
+
+    emit!(SessionKeyCreatedFromPreset {
+        authority: ctx.accounts.user_account.authority,
+        session_key: session_pubkey,
+        preset: preset.key(),
+        preset_version: preset.version,
+    });
+
+    Ok(())
+}
+
+fn write_preset(
+    preset: &mut Account<ServicePreset>,
+    previous_permissions: Option<SessionPermissions>,
+    permissions: SessionPermissions,
+    max_duration_seconds: i64,
+) -> Result<()> {
+    require!(max_duration_seconds >= 0, ErrorCode::InvalidExpiry);
+
+    if previous_permissions.is_some() {
+        preset.version += 1;
+    }
+    preset.permissions = permissions;
+    preset.max_duration_seconds = max_duration_seconds;
+    preset.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(PresetPublished {
+        service: preset.service,
+        preset: preset.key(),
+        preset_id: preset.preset_id,
+        version: preset.version,
+        previous_permissions,
+        permissions,
+        max_duration_seconds,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::constants::MAX_SERVICE_SESSION_KEYS;
+use crate::contexts::{RegisterService, SetServiceVerified, UpdateService};
+use crate::errors::ErrorCode;
+use crate::events::{ServiceRegistered, ServiceVerificationUpdated};
+use crate::state::ServiceEntry;
+use anchor_lang::prelude::*;
+
+/// Create the operator's registry entry (unverified)
+pub fn register_handler(
+    ctx: Context<RegisterService>,
+    name: [u8; 32],
+    url_hash: [u8; 32],
+    session_keys: Vec<Pubkey>,
+) -> Result<()> {
+    let service = &mut ctx.accounts.service;
+    service.operator = ctx.accounts.operator.key();
+    service.registered_at = Clock::get()?.unix_timestamp;
+    service.bump = ctx.bumps.service;
+
+    write_entry(service, name, url_hash, session_keys)
+}
+
+/// Replace the entry's details; verification must be granted again afterwards
+pub fn update_handler(
+    ctx: Context<UpdateService>,
+    name: [u8; 32],
+    url_hash: [u8; 32],
+    session_keys: Vec<Pubkey>,
+) -> Result<()> {
+    write_entry(&mut ctx.accounts.service, name, url_hash, session_keys)
+}
+
+/// Mark an entry as verified (or revoke verification)
+pub fn set_verified_handler(ctx: Context<SetServiceVerified>, verified: bool) -> Result<()> {
+    let service = &mut ctx.accounts.service;
+    service.verified = verified;
+
+    emit!(ServiceVerificationUpdated {
+        operator: service.operator,
+        verified,
+    });
+
+    Ok(())
+}
+
+fn write_entry(
+    service: &mut ServiceEntry,
+    name: [u8; 32],
+    url_hash: [u8; 32],
+    session_keys: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        session_keys.len() <= MAX_SERVICE_SESSION_KEYS,
+        ErrorCode::TooManyServiceSessionKeys
+    );
+
+    service.name = name;
+    service.url_hash = url_hash;
+    service.session_keys = session_keys;
+    service.verified = false;
+    service.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(ServiceRegistered {
+        operator: service.operator,
+        name,
+        url_hash,
+        session_keys: service.session_keys.clone(),
+    });
+
+    Ok(())
+}
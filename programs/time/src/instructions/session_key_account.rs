@@ -0,0 +1,84 @@
+use crate::contexts::{CloseSessionKeyAccount, CreateSessionKeyAccount, RevokeSessionKeyAccount};
+use crate::errors::ErrorCode;
+use crate::events::{SessionKeyAccountClosed, SessionKeyRevoked};
+use crate::instructions::create_session_key::{
+    build_session_key, created_event, validate_new_session_key,
+};
+use crate::instructions::revoke_session_key::revoke_sub_keys;
+use crate::state::{CreateSessionKeyArgs, RevocationReason};
+use anchor_lang::prelude::*;
+
+/// Create a session key in its own PDA. It takes the same arguments as
+/// `create_session_key_v2` but doesn't count against the inline capacity.
+pub fn create_handler(
+    ctx: Context<CreateSessionKeyAccount>,
+    args: CreateSessionKeyArgs,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let session_key = build_session_key(&args, &clock);
+    validate_new_session_key(&session_key, &clock)?;
+
+    // One key, one home: an inline key of the same pubkey would shadow this one
+    let user_account = &mut ctx.accounts.user_account;
+    require!(
+        user_account
+            .find_session_key(&args.session_pubkey)
+            .is_none(),
+        ErrorCode::SessionKeyAlreadyExists
+    );
+    user_account.session_key_accounts += 1;
+
+    let event = created_event(user_account.authority, &session_key, false);
+    let key_account = &mut ctx.accounts.session_key_account;
+    key_account.user_account = user_account.key();
+    key_account.generation = user_account.session_generation;
+    key_account.session_key = session_key;
+    key_account.bump = ctx.bumps.session_key_account;
+
+    emit!(event);
+    Ok(())
+}
+
+/// Revoke a PDA-stored session key and any sub-keys it delegated, keeping the account
+/// for its history
+pub fn revoke_handler(
+    ctx: Context<RevokeSessionKeyAccount>,
+    reason: RevocationReason,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let session_key = &mut ctx.accounts.session_key_account.session_key;
+    require!(!session_key.is_revoked, ErrorCode::SessionKeyAlreadyRevoked);
+    session_key.revoke(Some(reason), now);
+
+    emit!(SessionKeyRevoked {
+        authority: ctx.accounts.user_account.authority,
+        session_key: session_key.pubkey,
+        terms_hash: session_key.terms_hash,
+        reason,
+    });
+
+    let session_pubkey = session_key.pubkey;
+    revoke_sub_keys(&mut ctx.accounts.user_account, session_pubkey, reason, now);
+    Ok(())
+}
+
+/// Close a PDA-stored session key and return its rent. An active key stops working at
+/// once, and sub-keys it delegated are revoked as `Manual`.
+pub fn close_handler(ctx: Context<CloseSessionKeyAccount>) -> Result<()> {
+    let session_pubkey = ctx.accounts.session_key_account.session_key.pubkey;
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.session_key_accounts = user_account.session_key_accounts.saturating_sub(1);
+
+    emit!(SessionKeyAccountClosed {
+        authority: user_account.authority,
+        session_key: session_pubkey,
+    });
+
+    revoke_sub_keys(
+        user_account,
+        session_pubkey,
+        RevocationReason::Manual,
+        Clock::get()?.unix_timestamp,
+    );
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use crate::contexts::SetAccountPaused;
+use crate::events::AccountPauseChanged;
+use anchor_lang::prelude::*;
+
+/// Pause or unpause every session execution on the account. Revoked keys stay revoked
+/// when a kill phrase pause is lifted.
+pub fn handler(ctx: Context<SetAccountPaused>, paused: bool) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.is_paused = paused;
+
+    msg!("Account {}", if paused { "paused" } else { "unpaused" });
+
+    emit!(AccountPauseChanged {
+        authority: user_account.authority,
+        paused,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use crate::contexts::SetAttestationGate;
+use crate::events::AttestationGateUpdated;
+use crate::state::AttestationGate;
+use anchor_lang::prelude::*;
+
+/// Set or clear the attestation token gate required for session executions
+pub fn handler(
+    ctx: Context<SetAttestationGate>,
+    attestation_gate: Option<AttestationGate>,
+) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.attestation_gate = attestation_gate;
+
+    msg!("Attestation gate set to: {:?}", attestation_gate);
+
+    emit!(AttestationGateUpdated {
+        authority: user_account.authority,
+        attestation_gate,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::constants::MAX_BLACKOUT_WINDOWS;
+use crate::contexts::SetBlackoutWindows;
+use crate::errors::ErrorCode;
+use crate::events::BlackoutWindowsUpdated;
+use crate::state::BlackoutWindow;
+use anchor_lang::prelude::*;
+
+/// Replace the account's blackout calendar; an empty list lifts all blackouts
+pub fn handler(ctx: Context<SetBlackoutWindows>, windows: Vec<BlackoutWindow>) -> Result<()> {
+    require!(
+        windows.len() <= MAX_BLACKOUT_WINDOWS,
+        ErrorCode::TooManyBlackoutWindows
+    );
+    require!(
+        windows.iter().all(|w| w.start < w.end),
+        ErrorCode::InvalidBlackoutWindow
+    );
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.blackout_windows = windows;
+
+    msg!(
+        "Blackout calendar set: {} windows",
+        user_account.blackout_windows.len()
+    );
+
+    emit!(BlackoutWindowsUpdated {
+        authority: user_account.authority,
+        windows: user_account.blackout_windows.clone(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use crate::contexts::SetGuardProgram;
+use crate::events::GuardProgramUpdated;
+use anchor_lang::prelude::*;
+
+/// Set or clear the guard program that can veto session executions for this account
+pub fn handler(ctx: Context<SetGuardProgram>, guard_program: Option<Pubkey>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.guard_program = guard_program;
+
+    msg!("Guard program set to: {:?}", guard_program);
+
+    emit!(GuardProgramUpdated {
+        authority: user_account.authority,
+        guard_program,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use crate::constants::MAX_SLOT_HASH_AGE;
+use crate::contexts::SetMaxSlotAge;
+use crate::errors::ErrorCode;
+use crate::events::MaxSlotAgeUpdated;
+use anchor_lang::prelude::*;
+
+/// Set how stale a transaction's referenced slot hash may be; 0 turns the check off
+pub fn handler(ctx: Context<SetMaxSlotAge>, max_slot_age: u32) -> Result<()> {
+    require!(
+        max_slot_age <= MAX_SLOT_HASH_AGE,
+        ErrorCode::InvalidMaxSlotAge
+    );
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.max_slot_age = max_slot_age;
+
+    msg!("Max slot age set to: {}", max_slot_age);
+
+    emit!(MaxSlotAgeUpdated {
+        authority: user_account.authority,
+        max_slot_age,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use crate::contexts::SetPermanentDelegateOptIn;
+use crate::events::PermanentDelegateOptInUpdated;
+use anchor_lang::prelude::*;
+
+/// Allow or refuse Token-2022 mints with a permanent delegate. Their issuer can move any
+/// holder's tokens, so they are refused until the authority opts in.
+pub fn handler(ctx: Context<SetPermanentDelegateOptIn>, allowed: bool) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.allow_permanent_delegate_mints = allowed;
+
+    msg!("Permanent delegate mints allowed: {}", allowed);
+
+    emit!(PermanentDelegateOptInUpdated {
+        authority: user_account.authority,
+        allowed,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::contexts::SetSavingsRule;
+use crate::errors::ErrorCode;
+use crate::events::SavingsRuleUpdated;
+use crate::state::SavingsRule;
+use anchor_lang::prelude::*;
+
+/// Set or clear the savings rule applied to outgoing delegated transfers
+pub fn handler(ctx: Context<SetSavingsRule>, savings_rule: Option<SavingsRule>) -> Result<()> {
+    if let Some(rule) = savings_rule {
+        require!(
+            rule.basis_points <= 10_000 && (rule.basis_points > 0 || rule.round_up_unit > 0),
+            ErrorCode::InvalidSavingsRule
+        );
+    }
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.savings_rule = savings_rule;
+
+    msg!("Savings rule set to: {:?}", savings_rule);
+
+    emit!(SavingsRuleUpdated {
+        authority: user_account.authority,
+        savings_rule,
+    });
+
+    Ok(())
+}
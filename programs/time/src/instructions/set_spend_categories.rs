@@ -0,0 +1,36 @@
+use crate::constants::MAX_SPEND_CATEGORIES;
+use crate::contexts::SetSpendCategories;
+use crate::errors::ErrorCode;
+use crate::events::SpendCategoriesUpdated;
+use crate::state::SpendCategory;
+use anchor_lang::prelude::*;
+
+/// Replace the account's spend taxonomy; ids must be unique
+pub fn handler(ctx: Context<SetSpendCategories>, categories: Vec<SpendCategory>) -> Result<()> {
+    require!(
+        categories.len() <= MAX_SPEND_CATEGORIES,
+        ErrorCode::TooManySpendCategories
+    );
+    require!(
+        categories
+            .iter()
+            .enumerate()
+            .all(|(i, c)| categories[..i].iter().all(|other| other.id != c.id)),
+        ErrorCode::DuplicateSpendCategory
+    );
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.spend_categories = categories;
+
+    msg!(
+        "Spend taxonomy set: {} categories",
+        user_account.spend_categories.len()
+    );
+
+    emit!(SpendCategoriesUpdated {
+        authority: user_account.authority,
+        categories: user_account.spend_categories.clone(),
+    });
+
+    Ok(())
+}
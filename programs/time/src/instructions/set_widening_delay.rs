@@ -0,0 +1,26 @@
+use crate::constants::MAX_WIDENING_DELAY_SECONDS;
+use crate::contexts::SetWideningDelay;
+use crate::errors::ErrorCode;
+use crate::events::WideningDelayUpdated;
+use anchor_lang::prelude::*;
+
+/// Set how long widened session permissions wait before taking effect; 0 turns it off.
+/// Updates already waiting keep the delay they were given.
+pub fn handler(ctx: Context<SetWideningDelay>, seconds: u32) -> Result<()> {
+    require!(
+        seconds <= MAX_WIDENING_DELAY_SECONDS,
+        ErrorCode::InvalidWideningDelay
+    );
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.widening_delay_seconds = seconds;
+
+    msg!("Permission widening delay set to: {}s", seconds);
+
+    emit!(WideningDelayUpdated {
+        authority: user_account.authority,
+        widening_delay_seconds: seconds,
+    });
+
+    Ok(())
+}
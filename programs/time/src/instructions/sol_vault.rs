@@ -0,0 +1,320 @@
+use crate::contexts::{DepositSol, SessionTransferSol, SessionWithdrawSol, WithdrawSol};
+use crate::errors::ErrorCode;
+use crate::events::{ProtocolFeeCollected, SolDeposited, SolWithdrawn};
+use crate::guard::{enforce_guard, GuardCheck, GuardedAction};
+use crate::policy::{enforce_policy, PolicyRequest};
+use crate::slot_proof::{verify_recent_slot, SlotReference};
+use crate::state::{PolicyAccount, ProgramConfig, SessionKeyAccount, Treasury, UserAccount};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+
+/// Move lamports from the depositor into the user account PDA
+pub fn deposit_handler(ctx: Context<DepositSol>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: ctx.accounts.user_account.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit!(SolDeposited {
+        authority: ctx.accounts.user_account.authority,
+        depositor: ctx.accounts.depositor.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Return lamports from the vault to the authority
+pub fn withdraw_handler(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
+    let recipient = ctx.accounts.authority.to_account_info();
+    pay_from_vault(&ctx.accounts.user_account, &recipient, amount)?;
+
+    emit!(SolWithdrawn {
+        authority: ctx.accounts.user_account.authority,
+        session_key: None,
+        recipient: recipient.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Pay lamports from the vault to the session key, charged against its limits like a
+/// token transfer
+pub fn session_withdraw_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SessionWithdrawSol<'info>>,
+    amount: u64,
+    slot_ref: Option<SlotReference>,
+    scope: Option<Pubkey>,
+) -> Result<()> {
+    let session_pubkey = ctx.accounts.session_signer.key();
+    let session_key = ctx
+        .accounts
+        .user_account
+        .session_key(ctx.accounts.session_key_account.as_deref(), &session_pubkey)?;
+    require!(
+        session_key.allows_scope(scope.as_ref()),
+        ErrorCode::SessionScopeMismatch
+    );
+    // The protocol fee leaves the vault on top of the payout, so it is charged too
+    let charged = amount
+        .checked_add(ProgramConfig::protocol_fee(
+            &ctx.accounts.program_config,
+            amount,
+        )?)
+        .ok_or(ErrorCode::InvalidAmount)?;
+    ProgramConfig::enforce(&ctx.accounts.program_config, charged)?;
+    guard_sol_payout(
+        &ctx.accounts.user_account,
+        &ctx.accounts.session_signer,
+        ctx.remaining_accounts,
+        &session_pubkey,
+        amount,
+    )?;
+    charge_session_spend(
+        &mut ctx.accounts.user_account,
+        ctx.accounts.session_key_account.as_deref_mut(),
+        ctx.accounts.policy.as_deref(),
+        &session_pubkey,
+        &session_pubkey,
+        charged,
+        ctx.accounts.slot_hashes.as_deref(),
+        slot_ref.as_ref(),
+    )?;
+
+    let recipient = ctx.accounts.session_signer.to_account_info();
+    pay_from_vault(&ctx.accounts.user_account, &recipient, amount)?;
+    collect_sol_fee(
+        &ctx.accounts.program_config,
+        &ctx.accounts.user_account,
+        ctx.accounts.treasury.as_ref(),
+        &session_pubkey,
+        amount,
+    )?;
+
+    emit!(SolWithdrawn {
+        authority: ctx.accounts.user_account.authority,
+        session_key: Some(session_pubkey),
+        recipient: session_pubkey,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Pay lamports from the vault to `destination` on the session key's authority. The
+/// recipient allowlist and consent requirement apply on top of the usual session checks.
+pub fn session_transfer_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SessionTransferSol<'info>>,
+    amount: u64,
+    slot_ref: Option<SlotReference>,
+    scope: Option<Pubkey>,
+) -> Result<()> {
+    let session_pubkey = ctx.accounts.session_signer.key();
+    let destination = ctx.accounts.destination.key();
+    let session_key = ctx
+        .accounts
+        .user_account
+        .session_key(ctx.accounts.session_key_account.as_deref(), &session_pubkey)?;
+    require!(
+        session_key.allows_scope(scope.as_ref()),
+        ErrorCode::SessionScopeMismatch
+    );
+    // The protocol fee leaves the vault on top of the payout, so it is charged too
+    let charged = amount
+        .checked_add(ProgramConfig::protocol_fee(
+            &ctx.accounts.program_config,
+            amount,
+        )?)
+        .ok_or(ErrorCode::InvalidAmount)?;
+    ProgramConfig::enforce(&ctx.accounts.program_config, charged)?;
+    require!(
+        session_key.allows_recipient(&destination, &destination),
+        ErrorCode::RecipientNotAllowed
+    );
+    // The consent's address is checked by its seeds, so having one is enough
+    require!(
+        !ctx.accounts.user_account.require_recipient_consent
+            || ctx.accounts.recipient_consent.is_some(),
+        ErrorCode::RecipientConsentMissing
+    );
+    guard_sol_payout(
+        &ctx.accounts.user_account,
+        &ctx.accounts.session_signer,
+        ctx.remaining_accounts,
+        &destination,
+        amount,
+    )?;
+
+    charge_session_spend(
+        &mut ctx.accounts.user_account,
+        ctx.accounts.session_key_account.as_deref_mut(),
+        ctx.accounts.policy.as_deref(),
+        &session_pubkey,
+        &destination,
+        charged,
+        ctx.accounts.slot_hashes.as_deref(),
+        slot_ref.as_ref(),
+    )?;
+
+    let recipient = ctx.accounts.destination.to_account_info();
+    pay_from_vault(&ctx.accounts.user_account, &recipient, amount)?;
+    collect_sol_fee(
+        &ctx.accounts.program_config,
+        &ctx.accounts.user_account,
+        ctx.accounts.treasury.as_ref(),
+        &session_pubkey,
+        amount,
+    )?;
+
+    emit!(SolWithdrawn {
+        authority: ctx.accounts.user_account.authority,
+        session_key: Some(session_pubkey),
+        recipient: destination,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Validate a session spend of `amount` lamports, protocol fee included, to `recipient`
+/// and record it on the key: validity, blackout, slot proof, the account's policy, `can_transfer`, the lifetime and
+/// per-window caps and the use limit. Accounts with controls these checks can't apply to
+/// SOL, such as an attestation gate or the blocklist, refuse session SOL spends outright.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn charge_session_spend(
+    user_account: &mut Account<UserAccount>,
+    key_account: Option<&mut SessionKeyAccount>,
+    policy: Option<&PolicyAccount>,
+    session_pubkey: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    slot_hashes: Option<&AccountInfo>,
+    slot_ref: Option<&SlotReference>,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    let clock = Clock::get()?;
+
+    require!(!user_account.is_paused, ErrorCode::AccountPaused);
+    require!(
+        !user_account.has_token_only_controls(),
+        ErrorCode::SolSpendUnsupported
+    );
+    if let Some(window) = user_account.active_blackout(clock.unix_timestamp) {
+        msg!("Blackout in effect until {}", window.end);
+        return err!(ErrorCode::BlackoutActive);
+    }
+    if user_account.max_slot_age > 0 {
+        let slot_hashes = slot_hashes.ok_or(ErrorCode::SlotProofRequired)?;
+        let reference = slot_ref.ok_or(ErrorCode::SlotProofRequired)?;
+        verify_recent_slot(
+            slot_hashes,
+            reference,
+            user_account.max_slot_age,
+            clock.slot,
+        )?;
+    }
+    enforce_policy(
+        user_account,
+        policy,
+        &PolicyRequest {
+            session_key: *session_pubkey,
+            now: clock.unix_timestamp,
+            amount,
+            mint: Some(native_mint::ID),
+            recipient: Some(*recipient),
+            recipient_account: Some(*recipient),
+        },
+    )?;
+
+    let session_key = user_account.session_key_mut(key_account, session_pubkey)?;
+    require!(
+        !session_key.is_locked(clock.unix_timestamp),
+        ErrorCode::SessionKeyLocked
+    );
+    let charge = session_key.check_spend(amount, &clock)?;
+    session_key.record_spend(&charge);
+    Ok(())
+}
+
+/// Give the account's guard program, if any, a chance to veto a session payout of
+/// `amount` lamports to `destination`
+fn guard_sol_payout<'info>(
+    user_account: &Account<'info, UserAccount>,
+    session_signer: &Signer<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    destination: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    enforce_guard(
+        user_account.guard_program,
+        &user_account.to_account_info(),
+        &session_signer.to_account_info(),
+        remaining_accounts,
+        &GuardCheck {
+            user_account: user_account.key(),
+            authority: user_account.authority,
+            session_key: session_signer.key(),
+            action: GuardedAction::SolTransfer {
+                destination: *destination,
+                amount,
+            },
+        },
+    )
+}
+
+/// Move `amount` lamports out of the user account PDA, keeping it rent exempt.
+/// The PDA carries data, so it can't be a system transfer source; the program debits it
+/// directly as its owner.
+pub(crate) fn pay_from_vault(
+    user_account: &Account<UserAccount>,
+    recipient: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    let vault = user_account.to_account_info();
+    let reserve = Rent::get()?.minimum_balance(vault.data_len());
+    require!(
+        vault.lamports().saturating_sub(reserve) >= amount,
+        ErrorCode::InsufficientVaultBalance
+    );
+
+    **vault.try_borrow_mut_lamports()? -= amount;
+    **recipient.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+/// Pay the protocol fee on a session payout of `amount` from the vault into the treasury,
+/// on top of the payout itself
+fn collect_sol_fee(
+    program_config: &AccountInfo,
+    user_account: &Account<UserAccount>,
+    treasury: Option<&Account<Treasury>>,
+    session_pubkey: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let fee = ProgramConfig::protocol_fee(program_config, amount)?;
+    if fee == 0 {
+        return Ok(());
+    }
+    let treasury = treasury.ok_or(ErrorCode::FeeAccountMissing)?;
+    pay_from_vault(user_account, &treasury.to_account_info(), fee)?;
+
+    emit!(ProtocolFeeCollected {
+        authority: user_account.authority,
+        session_key: *session_pubkey,
+        mint: native_mint::ID,
+        amount: fee,
+    });
+
+    Ok(())
+}
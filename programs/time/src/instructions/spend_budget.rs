@@ -0,0 +1,136 @@
+use crate::constants::{MAX_ALLOWED_MINTS, MAX_PROGRAM_BUDGETS, MAX_SPEND_BUDGET_WINDOW_SECONDS};
+use crate::contexts::{
+    CloseSpendBudget, InitializeSpendBudget, SetProgramBudget, UpdateSpendBudget,
+};
+use crate::errors::ErrorCode;
+use crate::events::{ProgramBudgetUpdated, SpendBudgetClosed, SpendBudgetUpdated};
+use crate::state::ProgramBudget;
+use anchor_lang::prelude::*;
+
+/// Create the spend budget shared by all session keys for one mint
+pub fn initialize_handler(
+    ctx: Context<InitializeSpendBudget>,
+    limit: u64,
+    window_seconds: i64,
+) -> Result<()> {
+    require!(
+        (0..=MAX_SPEND_BUDGET_WINDOW_SECONDS).contains(&window_seconds),
+        ErrorCode::InvalidExpiry
+    );
+    let user_account = &mut ctx.accounts.user_account;
+    require!(
+        user_account.budgeted_mints.len() < MAX_ALLOWED_MINTS,
+        ErrorCode::TooManySpendBudgets
+    );
+    let mint = ctx.accounts.mint.key();
+    user_account.budgeted_mints.push(mint);
+
+    let spend_budget = &mut ctx.accounts.spend_budget;
+    spend_budget.user_account = user_account.key();
+    spend_budget.mint = mint;
+    spend_budget.limit = limit;
+    spend_budget.window_seconds = window_seconds;
+    spend_budget.window_start = Clock::get()?.unix_timestamp;
+    spend_budget.spent = 0;
+    spend_budget.bump = ctx.bumps.spend_budget;
+    spend_budget.program_budgets = Vec::new();
+
+    emit!(SpendBudgetUpdated {
+        authority: user_account.authority,
+        mint,
+        limit,
+        window_seconds,
+    });
+
+    Ok(())
+}
+
+/// Change the limit or window; spend already recorded in the current window is kept
+pub fn update_handler(
+    ctx: Context<UpdateSpendBudget>,
+    limit: u64,
+    window_seconds: i64,
+) -> Result<()> {
+    require!(
+        (0..=MAX_SPEND_BUDGET_WINDOW_SECONDS).contains(&window_seconds),
+        ErrorCode::InvalidExpiry
+    );
+    let spend_budget = &mut ctx.accounts.spend_budget;
+    spend_budget.limit = limit;
+    spend_budget.window_seconds = window_seconds;
+
+    emit!(SpendBudgetUpdated {
+        authority: ctx.accounts.user_account.authority,
+        mint: spend_budget.mint,
+        limit,
+        window_seconds,
+    });
+
+    Ok(())
+}
+
+/// Cap the mint's value routed into `program` by custom actions, over the budget's
+/// window, or remove the cap with None. A program can be capped under one mint only.
+pub fn set_program_handler(
+    ctx: Context<SetProgramBudget>,
+    program: Pubkey,
+    limit: Option<u64>,
+) -> Result<()> {
+    let spend_budget = &mut ctx.accounts.spend_budget;
+    let user_account = &mut ctx.accounts.user_account;
+    let existing = spend_budget
+        .program_budgets
+        .iter()
+        .position(|b| b.program == program);
+
+    match (limit, existing) {
+        (Some(limit), Some(index)) => spend_budget.program_budgets[index].limit = limit,
+        (Some(limit), None) => {
+            require!(
+                !user_account.budgeted_programs.contains(&program)
+                    && user_account.budgeted_programs.len() < MAX_PROGRAM_BUDGETS,
+                ErrorCode::TooManyProgramBudgets
+            );
+            user_account.budgeted_programs.push(program);
+            spend_budget.program_budgets.push(ProgramBudget {
+                program,
+                limit,
+                window_start: Clock::get()?.unix_timestamp,
+                spent: 0,
+            });
+        }
+        (None, Some(index)) => {
+            spend_budget.program_budgets.remove(index);
+            user_account.budgeted_programs.retain(|p| *p != program);
+        }
+        (None, None) => return err!(ErrorCode::ProgramBudgetMissing),
+    }
+
+    emit!(ProgramBudgetUpdated {
+        authority: user_account.authority,
+        mint: spend_budget.mint,
+        program,
+        limit,
+    });
+
+    Ok(())
+}
+
+/// Remove the budget; transfers of the mint are then limited only by session permissions,
+/// and programs it capped are no longer capped
+pub fn close_handler(ctx: Context<CloseSpendBudget>) -> Result<()> {
+    let mint = ctx.accounts.spend_budget.mint;
+    let capped = &ctx.accounts.spend_budget.program_budgets;
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.budgeted_mints.retain(|m| *m != mint);
+    user_account
+        .budgeted_programs
+        .retain(|p| !capped.iter().any(|b| b.program == *p));
+
+    emit!(SpendBudgetClosed {
+        authority: user_account.authority,
+        mint,
+    });
+
+    Ok(())
+}
@@ -1,4 +1,9 @@
+use crate::constants::MAX_DELEGATED_TOKEN_ACCOUNTS;
 use crate::contexts::SplApproveDelegate;
+use crate::events::MintRiskAssessed;
+use crate::instructions::create_session_key::{add_session_key, build_session_key};
+use crate::mint_risk::require_mint_allowed;
+use crate::state::{CreateSessionKeyArgs, DelegateBump, ProgramConfig};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{self, Approve};
 
@@ -7,7 +12,7 @@ pub fn handler(ctx: Context<SplApproveDelegate>, amount: u64) -> Result<()> {
     // Optional: enforce mint allowlist or custom flags via user_account fields if you add them
 
     // Derive expected delegate PDA from user_account and mint
-    let (expected_delegate, _bump) = Pubkey::find_program_address(
+    let (expected_delegate, bump) = Pubkey::find_program_address(
         &[
             b"delegate",
             ctx.accounts.user_account.key().as_ref(),
@@ -33,6 +38,17 @@ pub fn handler(ctx: Context<SplApproveDelegate>, amount: u64) -> Result<()> {
         crate::errors::ErrorCode::InsufficientPermissions
     );
 
+    ProgramConfig::enforce_mint(&ctx.accounts.program_config, &ctx.accounts.mint.key())?;
+    let flags = require_mint_allowed(
+        &ctx.accounts.user_account,
+        &ctx.accounts.mint.to_account_info(),
+    )?;
+    emit!(MintRiskAssessed {
+        authority: ctx.accounts.user_account.authority,
+        mint: ctx.accounts.mint.key(),
+        flags,
+    });
+
     // Enforce allowed mints allowlist if present
     if !ctx.accounts.user_account.allowed_mints.is_empty() {
         require!(
@@ -45,6 +61,24 @@ pub fn handler(ctx: Context<SplApproveDelegate>, amount: u64) -> Result<()> {
         );
     }
 
+    // Remember the approval so the account can't be closed while it is open
+    let token_account = ctx.accounts.token_account.key();
+    let delegated = &mut ctx.accounts.user_account.delegated_token_accounts;
+    if !delegated.contains(&token_account) {
+        require!(
+            delegated.len() < MAX_DELEGATED_TOKEN_ACCOUNTS,
+            crate::errors::ErrorCode::TooManyDelegations
+        );
+        delegated.push(token_account);
+    }
+
+    // Cache the bump for transfers; kept after revocation since it never changes
+    let mint = ctx.accounts.mint.key();
+    let bumps = &mut ctx.accounts.user_account.delegate_bumps;
+    if !bumps.iter().any(|d| d.mint == mint) && bumps.len() < MAX_DELEGATED_TOKEN_ACCOUNTS {
+        bumps.push(DelegateBump { mint, bump });
+    }
+
     token_interface::approve(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
@@ -57,3 +91,16 @@ pub fn handler(ctx: Context<SplApproveDelegate>, amount: u64) -> Result<()> {
         amount,
     )
 }
+
+/// Create a session key and approve the mint's delegate in one instruction, so
+/// onboarding needs a single signature and never leaves a key without an approval
+pub fn approve_and_create_session_handler(
+    ctx: Context<SplApproveDelegate>,
+    args: CreateSessionKeyArgs,
+    amount: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let session_key = build_session_key(&args, &clock);
+    add_session_key(&mut ctx.accounts.user_account, session_key, &clock, false)?;
+    handler(ctx, amount)
+}
@@ -1,52 +1,93 @@
-use crate::contexts::SplDelegatedTransfer;
+use crate::contexts::{SplDelegatedTransfer, SplDelegatedTransferWithAta};
 use crate::errors::ErrorCode;
-use crate::state::ExpirationType;
+use crate::events::{
+    ExecutionAttested, ProtocolFeeCollected, SavingsDiverted, TransferBlocked, TransferExecuted,
+};
+use crate::guard::{enforce_guard, GuardCheck, GuardedAction};
+use crate::instructions::activity_digest::record_execution;
+use crate::instructions::gas_tank::reimburse_fee;
+use crate::instructions::operator_reputation::bonded_reputation;
+use crate::mint_risk::require_mint_allowed;
+use crate::policy::{enforce_policy, PolicyRequest};
+use crate::receipts::{attested_leaf, operator_payload, receipt_leaf, verify_operator_signature};
+use crate::slot_proof::{verify_recent_slot, SlotReference};
+use crate::state::{ProgramConfig, RecipientCheck, Treasury};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::InstructionData;
+use anchor_spl::associated_token;
 use anchor_spl::token_interface::{self, TransferChecked};
 
 /// Perform SPL token transfer using PDA delegate, gated by session key time/permissions
-pub fn handler(ctx: Context<SplDelegatedTransfer>, amount: u64) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SplDelegatedTransfer<'info>>,
+    amount: u64,
+    slot_ref: Option<SlotReference>,
+    category: Option<u16>,
+    scope: Option<Pubkey>,
+    recipient: Option<RecipientCheck>,
+) -> Result<()> {
     let user_account = &ctx.accounts.user_account;
     let session_signer = &ctx.accounts.session_signer;
     let clock = Clock::get()?;
 
     // Find the session key
-    let session_key = user_account
-        .session_keys
-        .iter()
-        .find(|k| k.pubkey == session_signer.key())
-        .ok_or(ErrorCode::SessionKeyNotFound)?;
+    let session_key = user_account.session_key(
+        ctx.accounts.session_key_account.as_deref(),
+        &session_signer.key(),
+    )?;
 
-    // Validate
-    require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
-    match session_key.expiration_type {
-        ExpirationType::Time => require!(
-            session_key.expires_at > clock.unix_timestamp,
-            ErrorCode::SessionKeyExpired
-        ),
-        ExpirationType::BlockHeight => require!(
-            session_key.expires_at > clock.slot as i64,
-            ErrorCode::SessionKeyExpired
-        ),
+    // The protocol fee and any savings diversion leave the owner's account on top of the
+    // transfer, so the caps and limits below are charged for all of it
+    let fee = ProgramConfig::protocol_fee(&ctx.accounts.program_config, amount)?;
+    let saved = user_account
+        .savings_rule
+        .filter(|r| r.mint == ctx.accounts.mint.key())
+        .map_or(0, |rule| rule.savings_for(amount));
+    let charged = amount
+        .checked_add(fee)
+        .and_then(|total| total.checked_add(saved))
+        .ok_or(ErrorCode::InvalidAmount)?;
+
+    require!(!user_account.is_paused, ErrorCode::AccountPaused);
+    ProgramConfig::enforce(&ctx.accounts.program_config, charged)?;
+    require!(
+        session_key.allows_scope(scope.as_ref()),
+        ErrorCode::SessionScopeMismatch
+    );
+    if let Some(window) = user_account.active_blackout(clock.unix_timestamp) {
+        msg!("Blackout in effect until {}", window.end);
+        return err!(ErrorCode::BlackoutActive);
     }
+
+    // Validate
     require!(
-        session_key.permissions.can_transfer,
-        ErrorCode::InsufficientPermissions
+        !session_key.is_locked(clock.unix_timestamp),
+        ErrorCode::SessionKeyLocked
     );
-    if session_key.permissions.max_transfer_amount > 0 {
-        require!(
-            amount <= session_key.permissions.max_transfer_amount,
-            ErrorCode::InsufficientPermissions
-        );
+    let charge = session_key.check_spend(charged, &clock)?;
+
+    // Refuse stale pre-signed transactions when the account requires a recent slot proof
+    if user_account.max_slot_age > 0 {
+        let slot_hashes = ctx
+            .accounts
+            .slot_hashes
+            .as_ref()
+            .ok_or(ErrorCode::SlotProofRequired)?;
+        let reference = slot_ref.as_ref().ok_or(ErrorCode::SlotProofRequired)?;
+        verify_recent_slot(
+            slot_hashes,
+            reference,
+            user_account.max_slot_age,
+            clock.slot,
+        )?;
     }
 
     // Check delegate PDA matches expected for (user_account, mint)
-    let (expected_delegate, bump) = Pubkey::find_program_address(
-        &[
-            b"delegate",
-            user_account.key().as_ref(),
-            ctx.accounts.mint.key().as_ref(),
-        ],
+    let (expected_delegate, bump) = user_account.delegate_address(
+        &user_account.key(),
+        &ctx.accounts.mint.key(),
         ctx.program_id,
     );
     require_keys_eq!(
@@ -54,18 +95,201 @@ pub fn handler(ctx: Context<SplDelegatedTransfer>, amount: u64) -> Result<()> {
         ctx.accounts.delegate_authority.key(),
         ErrorCode::InsufficientPermissions
     );
+    // The context checks this too; repeated so the guarantee doesn't hinge on the account
+    // constraints staying in place
+    user_account.check_source_token(&ctx.accounts.from_token, &ctx.accounts.mint.key())?;
+
+    // Enforce the account's and the session's mint allowlists; both must admit the mint
+    require!(
+        session_key.allows_mint(&user_account.allowed_mints, &ctx.accounts.mint.key()),
+        ErrorCode::MintNotAllowed
+    );
+    require_mint_allowed(user_account, &ctx.accounts.mint.to_account_info())?;
+    ProgramConfig::enforce_mint(&ctx.accounts.program_config, &ctx.accounts.mint.key())?;
+
+    // The caller's intended recipient, when named, must own the destination
+    if let Some(recipient) = recipient {
+        recipient.verify(
+            &ctx.accounts.to_token.key(),
+            &ctx.accounts.to_token,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.token_program.key(),
+        )?;
+    }
+
+    // Keys restricted to known destinations may only pay those wallets or token accounts
+    require!(
+        session_key.allows_recipient(&ctx.accounts.to_token.owner, &ctx.accounts.to_token.key()),
+        ErrorCode::RecipientNotAllowed
+    );
+    enforce_policy(
+        user_account,
+        ctx.accounts.policy.as_deref(),
+        &PolicyRequest {
+            session_key: session_signer.key(),
+            now: clock.unix_timestamp,
+            amount: charged,
+            mint: Some(ctx.accounts.mint.key()),
+            recipient: Some(ctx.accounts.to_token.owner),
+            recipient_account: Some(ctx.accounts.to_token.key()),
+        },
+    )?;
 
-    // Enforce allowed mints allowlist if present
-    if !user_account.allowed_mints.is_empty() {
+    // Category tags must come from the account's taxonomy
+    if let Some(category) = category {
         require!(
             user_account
-                .allowed_mints
+                .spend_categories
                 .iter()
-                .any(|m| m == &ctx.accounts.mint.key()),
-            ErrorCode::MintNotAllowed
+                .any(|c| c.id == category),
+            ErrorCode::UnknownSpendCategory
+        );
+    }
+
+    // Require a live operator bond of the configured asset and size
+    if let Some(requirement) = user_account.bond_requirement {
+        let bond = ctx
+            .accounts
+            .operator_bond
+            .as_ref()
+            .ok_or(ErrorCode::BondRequired)?;
+        require!(
+            bond.release_requested_at == 0,
+            ErrorCode::BondReleasePending
         );
+        require!(
+            bond.mint == requirement.mint && bond.amount >= requirement.min_amount,
+            ErrorCode::BondInsufficient
+        );
+    }
+
+    // Screen the recipient against the program blocklist if this account opted in.
+    // A blocked transfer fails with RecipientBlocked; TransferBlocked stays in its logs.
+    if user_account.enforce_blocklist {
+        let blocklist = ctx
+            .accounts
+            .blocklist
+            .as_ref()
+            .ok_or(ErrorCode::BlocklistMissing)?;
+        let recipient = ctx.accounts.to_token.owner;
+        if blocklist.is_blocked(&recipient) || blocklist.is_blocked(&ctx.accounts.to_token.key()) {
+            msg!("Transfer to blocked recipient {} refused", recipient);
+            emit!(TransferBlocked {
+                authority: user_account.authority,
+                session_key: session_signer.key(),
+                recipient,
+                amount,
+            });
+            return err!(ErrorCode::RecipientBlocked);
+        }
+    }
+
+    // Recipients must have accepted payouts if the account requires it; the consent's
+    // address is checked by its seeds against the `to_token` owner
+    require!(
+        !user_account.require_recipient_consent || ctx.accounts.recipient_consent.is_some(),
+        ErrorCode::RecipientConsentMissing
+    );
+
+    // Enforce the attestation (KYC) token gate if configured
+    if let Some(gate) = user_account.attestation_gate {
+        if gate.requires_session_holder() {
+            gate.verify(
+                &session_signer.key(),
+                ctx.accounts.session_attestation.as_deref(),
+            )?;
+        }
+        if gate.requires_recipient() {
+            gate.verify(
+                &ctx.accounts.to_token.owner,
+                ctx.accounts.recipient_attestation.as_deref(),
+            )?;
+        }
     }
 
+    // Give the account's guard program, if any, a chance to veto the transfer
+    enforce_guard(
+        user_account.guard_program,
+        &user_account.to_account_info(),
+        &session_signer.to_account_info(),
+        ctx.remaining_accounts,
+        &GuardCheck {
+            user_account: user_account.key(),
+            authority: user_account.authority,
+            session_key: session_signer.key(),
+            action: GuardedAction::SplTransfer {
+                mint: ctx.accounts.mint.key(),
+                destination: ctx.accounts.to_token.key(),
+                amount,
+            },
+        },
+    )?;
+
+    // The bonded operator must have countersigned this exact transfer if the account asks
+    let operator_signature = if user_account.require_operator_signature {
+        let bond = ctx
+            .accounts
+            .operator_bond
+            .as_ref()
+            .ok_or(ErrorCode::BondRequired)?;
+        let instructions = ctx
+            .accounts
+            .instructions_sysvar
+            .as_ref()
+            .ok_or(ErrorCode::OperatorSignatureMissing)?;
+        let payload = operator_payload(
+            &user_account.key(),
+            &session_signer.key(),
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.to_token.key(),
+            amount,
+            session_key.use_count,
+        );
+        let signature = verify_operator_signature(instructions, &bond.operator, &payload)?;
+        Some((bond.operator, signature))
+    } else {
+        None
+    };
+
+    // Check for the digest and stats up front so a missing account fails before any transfer
+    let digest_interval = user_account.digest_interval;
+    if digest_interval > 0 {
+        require!(
+            ctx.accounts.activity_digest.is_some(),
+            ErrorCode::ActivityDigestMissing
+        );
+    }
+    if user_account.stats_enabled {
+        require!(
+            ctx.accounts.account_stats.is_some(),
+            ErrorCode::AccountStatsMissing
+        );
+    }
+
+    // Charge the account-level budget for this mint. The budget PDA is writable here, so
+    // transfers from different sessions in the same slot are serialized by the runtime
+    if user_account
+        .budgeted_mints
+        .contains(&ctx.accounts.mint.key())
+    {
+        ctx.accounts
+            .spend_budget
+            .as_mut()
+            .ok_or(ErrorCode::SpendBudgetMissing)?
+            .charge(charged, &clock)?;
+    }
+
+    // Record the session's cumulative and windowed spend and its use count
+    let session_pubkey = session_signer.key();
+    {
+        let key = ctx.accounts.user_account.session_key_mut(
+            ctx.accounts.session_key_account.as_deref_mut(),
+            &session_pubkey,
+        )?;
+        key.record_spend(&charge);
+    }
+    let user_account = &ctx.accounts.user_account;
+
     // CPI to token transfer with delegate PDA as authority
     // Bind to locals so the referenced bytes live long enough for signer seeds
     let user_key = user_account.key();
@@ -86,5 +310,198 @@ pub fn handler(ctx: Context<SplDelegatedTransfer>, amount: u64) -> Result<()> {
         ),
         amount,
         decimals,
-    )
+    )?;
+
+    // Charge the protocol fee on top of the transfer, into the treasury's token account
+    if fee > 0 {
+        let fee_token = ctx
+            .accounts
+            .fee_token
+            .as_ref()
+            .ok_or(ErrorCode::FeeAccountMissing)?;
+        require!(
+            fee_token.owner == Treasury::address(ctx.program_id) && fee_token.mint == mint_key,
+            ErrorCode::FeeAccountMismatch
+        );
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.from_token.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: fee_token.to_account_info(),
+                    authority: ctx.accounts.delegate_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            fee,
+            decimals,
+        )?;
+
+        emit!(ProtocolFeeCollected {
+            authority: user_account.authority,
+            session_key: session_pubkey,
+            mint: mint_key,
+            amount: fee,
+        });
+    }
+
+    // Divert the configured share of the transfer into the authority's savings vault
+    if let Some(rule) = user_account.savings_rule.filter(|r| r.mint == mint_key) {
+        if saved > 0 {
+            let vault = ctx
+                .accounts
+                .savings_vault
+                .as_ref()
+                .ok_or(ErrorCode::SavingsVaultMissing)?;
+            require!(
+                vault.key() == rule.vault && vault.owner == user_account.authority,
+                ErrorCode::SavingsVaultMismatch
+            );
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.from_token.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: vault.to_account_info(),
+                        authority: ctx.accounts.delegate_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                saved,
+                decimals,
+            )?;
+
+            emit!(SavingsDiverted {
+                authority: user_account.authority,
+                session_key: session_signer.key(),
+                mint: mint_key,
+                vault: rule.vault,
+                amount,
+                saved,
+            });
+        }
+    }
+
+    // Cover the session signer's fee from the account's gas tank, not the user's funds
+    if let Some(gas_tank) = ctx.accounts.gas_tank.as_mut() {
+        reimburse_fee(
+            gas_tank,
+            user_account.authority,
+            &ctx.accounts.session_signer.to_account_info(),
+        )?;
+    }
+
+    // Credit the bonded operator's public track record
+    if let Some(reputation) = bonded_reputation(
+        ctx.accounts.operator_bond.as_ref(),
+        ctx.accounts.operator_reputation.as_mut(),
+    )? {
+        reputation.total_volume += amount as u128;
+    }
+
+    let destination = ctx.accounts.to_token.key();
+    let mut receipt = receipt_leaf(
+        &session_signer.key(),
+        &mint_key,
+        &destination,
+        amount,
+        clock.slot,
+    );
+    if let Some((operator, signature)) = operator_signature {
+        receipt = attested_leaf(&receipt, &operator, &signature);
+        emit!(ExecutionAttested {
+            authority: user_account.authority,
+            session_key: session_signer.key(),
+            operator,
+            receipt,
+            signature,
+        });
+    }
+
+    // Append to the lifetime history so this execution can later be proven on its own
+    if user_account.stats_enabled {
+        if let Some(stats) = ctx.accounts.account_stats.as_mut() {
+            stats.record(receipt, &session_signer.key(), amount, category, &clock)?;
+        }
+    }
+
+    // Either fold the execution into the running digest or log it individually
+    match ctx.accounts.activity_digest.as_mut() {
+        Some(digest) if digest_interval > 0 => {
+            record_execution(digest, digest_interval, receipt, amount, clock.slot)
+        }
+        _ => emit!(TransferExecuted {
+            authority: user_account.authority,
+            session_key: session_signer.key(),
+            mint: mint_key,
+            destination,
+            amount,
+            category,
+        }),
+    }
+
+    Ok(())
+}
+
+/// Create the recipient's associated token account if it doesn't exist yet, then run
+/// `spl_delegated_transfer` into it. Remaining accounts are that instruction's accounts,
+/// in order, followed by its own remaining accounts; they're forwarded to a call back
+/// into this program, so every check of the plain transfer applies unchanged.
+pub fn with_ata_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SplDelegatedTransferWithAta<'info>>,
+    amount: u64,
+    slot_ref: Option<SlotReference>,
+    category: Option<u16>,
+    scope: Option<Pubkey>,
+) -> Result<()> {
+    associated_token::create_idempotent(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: ctx.accounts.session_signer.to_account_info(),
+            associated_token: ctx.accounts.to_token.to_account_info(),
+            authority: ctx.accounts.recipient.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    let accounts = ctx
+        .remaining_accounts
+        .iter()
+        .map(|a| {
+            if a.is_writable {
+                AccountMeta::new(a.key(), a.is_signer)
+            } else {
+                AccountMeta::new_readonly(a.key(), a.is_signer)
+            }
+        })
+        .collect();
+    let mut infos = ctx.remaining_accounts.to_vec();
+    infos.push(ctx.accounts.time_program.to_account_info());
+
+    // Pinning the recipient's ATA keeps the forwarded transfer from paying anywhere else
+    let data = crate::instruction::SplDelegatedTransfer {
+        amount,
+        slot_ref,
+        category,
+        scope,
+        recipient: Some(RecipientCheck {
+            owner: ctx.accounts.recipient.key(),
+            require_ata: true,
+        }),
+    }
+    .data();
+    invoke(
+        &Instruction {
+            program_id: *ctx.program_id,
+            accounts,
+            data,
+        },
+        &infos,
+    )?;
+
+    Ok(())
 }
@@ -4,6 +4,12 @@ use anchor_spl::token_interface::{self, Revoke};
 
 /// Revoke Token Program delegate (owner clears delegate)
 pub fn handler(ctx: Context<SplRevokeDelegate>) -> Result<()> {
+    let token_account = ctx.accounts.token_account.key();
+    ctx.accounts
+        .user_account
+        .delegated_token_accounts
+        .retain(|a| a != &token_account);
+
     token_interface::revoke(CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         Revoke {
@@ -0,0 +1,49 @@
+use crate::contexts::SuspendSessionKey;
+use crate::errors::ErrorCode;
+use crate::events::{SessionKeyResumed, SessionKeySuspended};
+use anchor_lang::prelude::*;
+
+/// Pause a session key, e.g. while its bot is under maintenance. It keeps its limits,
+/// usage and expiry, and can't execute until resumed.
+pub fn suspend_handler(ctx: Context<SuspendSessionKey>, session_pubkey: Pubkey) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let authority = user_account.authority;
+    let session_key = user_account.session_key_mut(
+        ctx.accounts.session_key_account.as_deref_mut(),
+        &session_pubkey,
+    )?;
+    require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
+    require!(!session_key.is_suspended, ErrorCode::SessionKeySuspended);
+    session_key.is_suspended = true;
+
+    msg!("Session key suspended: {}", session_pubkey);
+
+    emit!(SessionKeySuspended {
+        authority,
+        session_key: session_pubkey,
+    });
+
+    Ok(())
+}
+
+/// Let a suspended session key execute again
+pub fn resume_handler(ctx: Context<SuspendSessionKey>, session_pubkey: Pubkey) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let authority = user_account.authority;
+    let session_key = user_account.session_key_mut(
+        ctx.accounts.session_key_account.as_deref_mut(),
+        &session_pubkey,
+    )?;
+    require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
+    require!(session_key.is_suspended, ErrorCode::SessionKeyNotSuspended);
+    session_key.is_suspended = false;
+
+    msg!("Session key resumed: {}", session_pubkey);
+
+    emit!(SessionKeyResumed {
+        authority,
+        session_key: session_pubkey,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::contexts::TransferSessionToNewKey;
+use crate::errors::ErrorCode;
+use crate::events::SessionKeyTransferred;
+use anchor_lang::prelude::*;
+
+/// Hand a session over to a new pubkey, e.g. when its holder switches devices.
+///
+/// Only the pubkey changes: permissions, allowlists, expiry and every spend and use
+/// counter carry over, so the handoff can't be used to reset limits.
+pub fn handler(
+    ctx: Context<TransferSessionToNewKey>,
+    old_session_key: Pubkey,
+    new_session_key: Pubkey,
+) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let signer = ctx.accounts.signer.key();
+    let clock = Clock::get()?;
+
+    let by_authority = signer == user_account.authority;
+    require!(
+        by_authority || signer == old_session_key,
+        ErrorCode::Unauthorized
+    );
+    require!(
+        user_account.find_session_key(&new_session_key).is_none(),
+        ErrorCode::SessionKeyAlreadyExists
+    );
+
+    let session_key = user_account
+        .find_session_key_mut(&old_session_key)
+        .ok_or(ErrorCode::SessionKeyNotFound)?;
+    let permissions = session_key.permissions_at(clock.unix_timestamp);
+
+    require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
+    require!(!session_key.is_suspended, ErrorCode::SessionKeySuspended);
+    require!(!session_key.is_exhausted(), ErrorCode::SessionKeyExhausted);
+    require!(
+        !session_key.is_expired(&clock),
+        ErrorCode::SessionKeyExpired
+    );
+    if !by_authority {
+        require!(
+            permissions.can_self_rotate,
+            ErrorCode::InsufficientPermissions
+        );
+    }
+
+    session_key.pubkey = new_session_key;
+    user_account.sort_session_keys();
+
+    msg!(
+        "Session key {} transferred to {}",
+        old_session_key,
+        new_session_key
+    );
+
+    emit!(SessionKeyTransferred {
+        authority: user_account.authority,
+        old_session_key,
+        new_session_key,
+        by_authority,
+    });
+
+    Ok(())
+}
@@ -1,16 +1,40 @@
 use crate::constants::MAX_ALLOWED_MINTS;
 use crate::contexts::UpdateAllowedMints;
+use crate::events::MintRiskAssessed;
+use crate::mint_risk::{require_mint_allowed, MintRisk};
 use anchor_lang::prelude::*;
 
-/// Set or replace the allowlist of SPL token mints for this user account
-pub fn handler(ctx: Context<UpdateAllowedMints>, mints: Vec<Pubkey>) -> Result<()> {
-    require!(mints.len() <= MAX_ALLOWED_MINTS, crate::errors::ErrorCode::TooManyAllowedMints);
+/// Set or replace the allowlist of SPL token mints for this user account. Remaining
+/// accounts: any of the listed mints, to screen them for permanent delegates up front and
+/// return their risk flags.
+pub fn handler(ctx: Context<UpdateAllowedMints>, mints: Vec<Pubkey>) -> Result<Vec<MintRisk>> {
+    require!(
+        mints.len() <= MAX_ALLOWED_MINTS,
+        crate::errors::ErrorCode::TooManyAllowedMints
+    );
+
+    // Listed mints passed in remaining accounts are screened now; the rest are screened
+    // when they are approved or transferred
+    let mut risks = Vec::new();
+    for mint in ctx
+        .remaining_accounts
+        .iter()
+        .filter(|a| mints.contains(a.key))
+    {
+        let flags = require_mint_allowed(&ctx.accounts.user_account, mint)?;
+        emit!(MintRiskAssessed {
+            authority: ctx.accounts.user_account.authority,
+            mint: mint.key(),
+            flags,
+        });
+        risks.push(MintRisk {
+            mint: mint.key(),
+            flags,
+        });
+    }
 
     let user_account = &mut ctx.accounts.user_account;
     user_account.allowed_mints = mints;
 
-    Ok(())
+    Ok(risks)
 }
-
-
-
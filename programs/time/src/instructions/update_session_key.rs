@@ -1,65 +1,85 @@
 use crate::contexts::UpdateSessionKey;
 use crate::errors::ErrorCode;
 use crate::events::SessionKeyUpdated;
-use crate::state::{ExpirationType, SessionPermissions};
+use crate::state::{SessionKey, SessionPermissions};
 use anchor_lang::prelude::*;
 
 /// Update/modify an existing session key (e.g., extend expiry, change permissions)
 pub fn handler(
     ctx: Context<UpdateSessionKey>,
     session_pubkey: Pubkey,
-    new_expires_at: Option<i64>,
+    new_expires_at_time: Option<i64>,
+    new_expires_at_slot: Option<i64>,
     new_permissions: Option<SessionPermissions>,
 ) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
     let clock = Clock::get()?;
 
-    // Store authority before mutable borrow
+    // Store authority and delay before mutable borrow
     let authority = user_account.authority;
+    let widening_delay = user_account.widening_delay_seconds;
 
     // Find the session key
     let session_key = user_account
-        .session_keys
-        .iter_mut()
-        .find(|k| k.pubkey == session_pubkey)
+        .find_session_key_mut(&session_pubkey)
         .ok_or(ErrorCode::SessionKeyNotFound)?;
 
     require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
 
-    // Update expiry if provided
-    if let Some(expires_at) = new_expires_at {
-        // Validate based on expiration type
-        match session_key.expiration_type {
-            ExpirationType::Time => {
-                require!(expires_at > clock.unix_timestamp, ErrorCode::InvalidExpiry);
-            }
-            ExpirationType::BlockHeight => {
-                require!(expires_at > clock.slot as i64, ErrorCode::InvalidExpiry);
-            }
+    // Update whichever expiry bounds are provided; a bound can be added but not removed
+    if new_expires_at_time.is_some() || new_expires_at_slot.is_some() {
+        SessionKey::check_expiry(new_expires_at_time, new_expires_at_slot, &clock)?;
+        if let Some(time) = new_expires_at_time {
+            session_key.set_expires_at_time(time, clock.unix_timestamp);
+        }
+        if let Some(slot) = new_expires_at_slot {
+            session_key.expires_at_slot = Some(slot);
         }
-        session_key.expires_at = expires_at;
         msg!(
-            "Session key expiry updated to: {} (type: {:?})",
-            expires_at,
-            session_key.expiration_type
+            "Session key expiry updated to time: {:?}, slot: {:?}",
+            session_key.expires_at_time,
+            session_key.expires_at_slot
         );
     }
 
-    // Update permissions if provided
+    // Update permissions if provided; widened ones wait out the account's delay while
+    // the permissions in force now keep applying
     if let Some(permissions) = new_permissions {
+        let current = session_key.permissions_at(clock.unix_timestamp);
+        if widening_delay > 0 && permissions.widens(&current) {
+            session_key.prior_permissions = Some(current);
+            session_key.widening_active_at = clock.unix_timestamp + widening_delay as i64;
+            msg!(
+                "Session key permissions widened, effective at {}",
+                session_key.widening_active_at
+            );
+        } else {
+            session_key.prior_permissions = None;
+            session_key.widening_active_at = 0;
+            msg!("Session key permissions updated");
+        }
         session_key.permissions = permissions;
-        msg!("Session key permissions updated");
     }
 
     // Store updated values before releasing mutable borrow
-    let final_expires_at = session_key.expires_at;
+    let expires_at_time = session_key.expires_at_time;
+    let expires_at_slot = session_key.expires_at_slot;
+    let max_expires_at = session_key.max_expires_at;
     let final_permissions = session_key.permissions;
+    let permissions_active_at = session_key.widening_active_at;
+    let terms_hash = session_key.terms_hash;
+    let label = session_key.label;
 
     emit!(SessionKeyUpdated {
         authority,
         session_key: session_pubkey,
-        expires_at: final_expires_at,
+        expires_at_time,
+        expires_at_slot,
+        max_expires_at,
         permissions: final_permissions,
+        permissions_active_at,
+        terms_hash,
+        label,
     });
 
     Ok(())
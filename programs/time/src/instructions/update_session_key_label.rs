@@ -0,0 +1,37 @@
+use crate::contexts::UpdateSessionKey;
+use crate::errors::ErrorCode;
+use crate::events::SessionKeyUpdated;
+use anchor_lang::prelude::*;
+
+/// Rename a session key. The label is display-only, so it changes without the
+/// widening delay and leaves the key's limits alone.
+pub fn handler(
+    ctx: Context<UpdateSessionKey>,
+    session_pubkey: Pubkey,
+    label: [u8; 32],
+) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let authority = user_account.authority;
+
+    let session_key = user_account
+        .find_session_key_mut(&session_pubkey)
+        .ok_or(ErrorCode::SessionKeyNotFound)?;
+    require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
+    session_key.label = label;
+
+    msg!("Session key label updated: {}", session_pubkey);
+
+    emit!(SessionKeyUpdated {
+        authority,
+        session_key: session_pubkey,
+        expires_at_time: session_key.expires_at_time,
+        expires_at_slot: session_key.expires_at_slot,
+        max_expires_at: session_key.max_expires_at,
+        permissions: session_key.permissions,
+        permissions_active_at: session_key.widening_active_at,
+        terms_hash: session_key.terms_hash,
+        label,
+    });
+
+    Ok(())
+}
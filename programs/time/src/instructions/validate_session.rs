@@ -0,0 +1,28 @@
+use crate::contexts::ValidateSession;
+use crate::state::SessionPermissions;
+use crate::validation::check_session;
+use anchor_lang::prelude::*;
+
+/// Check that `session_pubkey` is a live session key of the user account granting
+/// `required_permissions`, usable for `scope`, while the account isn't paused or in a
+/// blackout. Returns the verdict as return data instead of failing, so calling programs
+/// can decide what a rejected key means for them.
+pub fn handler(
+    ctx: Context<ValidateSession>,
+    session_pubkey: Pubkey,
+    required_permissions: SessionPermissions,
+    scope: Option<Pubkey>,
+) -> Result<bool> {
+    let verdict = check_session(
+        &ctx.accounts.user_account,
+        ctx.accounts.session_key_account.as_deref(),
+        &session_pubkey,
+        &required_permissions,
+        scope.as_ref(),
+        &Clock::get()?,
+    );
+    if let Err(error) = &verdict {
+        msg!("Session key {} rejected: {}", session_pubkey, error);
+    }
+    Ok(verdict.is_ok())
+}
@@ -0,0 +1,22 @@
+use crate::contexts::VerifySessionDomain;
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// Check that a session key was granted from the dapp origin hashing to `domain_hash`.
+/// Changes nothing, so wallets and indexers can simulate it, and dapps can prepend it to
+/// a transaction to refuse keys granted elsewhere.
+pub fn handler(
+    ctx: Context<VerifySessionDomain>,
+    session_pubkey: Pubkey,
+    domain_hash: [u8; 32],
+) -> Result<()> {
+    let session_key = ctx
+        .accounts
+        .user_account
+        .session_key(ctx.accounts.session_key_account.as_deref(), &session_pubkey)?;
+    require!(
+        session_key.domain_hash == domain_hash,
+        ErrorCode::SessionDomainMismatch
+    );
+    Ok(())
+}
@@ -1,17 +1,56 @@
+// Anchor 0.31's `#[program]` emits its IDL instructions, which call the deprecated
+// `AccountInfo::realloc`, as a sibling module at the crate root. Lint levels can only
+// reach them from here, so the modules below warn on deprecations again.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 
 // Module declarations
+#[cfg(feature = "cu-hints")]
+#[warn(deprecated)]
+pub mod compute_budget;
+#[warn(deprecated)]
 pub mod constants;
+#[warn(deprecated)]
 pub mod contexts;
+#[cfg(feature = "cpi")]
+#[warn(deprecated)]
+pub mod cpi_helpers;
+#[warn(deprecated)]
 pub mod errors;
+#[warn(deprecated)]
 pub mod events;
+#[warn(deprecated)]
+pub mod guard;
+#[warn(deprecated)]
 pub mod instructions;
+#[warn(deprecated)]
+pub mod mint_risk;
+#[warn(deprecated)]
+pub mod policy;
+#[warn(deprecated)]
+pub mod receipts;
+#[warn(deprecated)]
+pub mod slot_proof;
+#[warn(deprecated)]
 pub mod state;
+#[cfg(feature = "library")]
+#[warn(deprecated)]
+pub mod validation;
+#[cfg(not(feature = "library"))]
+#[allow(dead_code)]
+#[warn(deprecated)]
+mod validation;
 
 // Re-exports for external use
 pub use constants::*;
 pub use contexts::*;
 pub use events::*;
+pub use guard::*;
+pub use mint_risk::*;
+pub use policy::*;
+pub use receipts::*;
+pub use slot_proof::*;
 pub use state::*;
 
 use instructions::*;
@@ -22,9 +61,21 @@ declare_id!("DdtvbkajRMQj26vuAUaV96hDtzE1mzvB7k64VeWzoWib");
 pub mod time {
     use super::*;
 
-    /// Initialize a user account that can hold session keys
-    pub fn initialize_user_account(ctx: Context<InitializeUserAccount>) -> Result<()> {
-        initialize_user_account::handler(ctx)
+    /// Initialize a user account with space for `max_session_keys` session keys
+    pub fn initialize_user_account(
+        ctx: Context<InitializeUserAccount>,
+        max_session_keys: u16,
+    ) -> Result<()> {
+        initialize_user_account::handler(ctx, max_session_keys)
+    }
+
+    /// Initialize a user account and create its first session key in the same call
+    pub fn initialize_with_session_key(
+        ctx: Context<InitializeUserAccount>,
+        max_session_keys: u16,
+        args: CreateSessionKeyArgs,
+    ) -> Result<()> {
+        initialize_user_account::with_session_key_handler(ctx, max_session_keys, args)
     }
 
     /// Initialize with allowed mints and initial lamport deposit
@@ -36,13 +87,15 @@ pub mod time {
         initialize_user_account::handler_with_config(ctx, allowed_mints, initial_deposit_lamports)
     }
 
-    /// Create a new session key with specified permissions and expiry
+    /// Create a new session key with specified permissions and expiry.
+    /// Deprecated: logs a warning and flags the event; use `create_session_key_v2`.
     pub fn create_session_key(
         ctx: Context<CreateSessionKey>,
         session_pubkey: Pubkey,
         expires_at: i64,
         expiration_type: ExpirationType,
         permissions: SessionPermissions,
+        terms_hash: Option<[u8; 32]>,
     ) -> Result<()> {
         create_session_key::handler(
             ctx,
@@ -50,33 +103,171 @@ pub mod time {
             expires_at,
             expiration_type,
             permissions,
+            terms_hash,
         )
     }
 
-    /// Revoke an existing session key
+    /// Create a new session key from versioned arguments, including a label
+    pub fn create_session_key_v2(
+        ctx: Context<CreateSessionKey>,
+        args: CreateSessionKeyArgs,
+    ) -> Result<()> {
+        create_session_key::handler_v2(ctx, args)
+    }
+
+    /// Same as `create_session_key_v2`, creating the user account first when it is missing
+    pub fn create_session_key_init_if_needed(
+        ctx: Context<CreateSessionKeyInitIfNeeded>,
+        args: CreateSessionKeyArgs,
+    ) -> Result<()> {
+        create_session_key::init_if_needed_handler(ctx, args)
+    }
+
+    /// Revoke an existing session key, recording why
     pub fn revoke_session_key(
         ctx: Context<RevokeSessionKey>,
         session_pubkey: Pubkey,
+        reason: RevocationReason,
     ) -> Result<()> {
-        revoke_session_key::handler(ctx, session_pubkey)
+        revoke_session_key::handler(ctx, session_pubkey, reason)
     }
 
     /// Update/modify an existing session key (e.g., extend expiry, change permissions)
     pub fn update_session_key(
         ctx: Context<UpdateSessionKey>,
         session_pubkey: Pubkey,
-        new_expires_at: Option<i64>,
+        new_expires_at_time: Option<i64>,
+        new_expires_at_slot: Option<i64>,
+        new_permissions: Option<SessionPermissions>,
+    ) -> Result<()> {
+        update_session_key::handler(
+            ctx,
+            session_pubkey,
+            new_expires_at_time,
+            new_expires_at_slot,
+            new_permissions,
+        )
+    }
+
+    /// Set a session key's display label
+    pub fn update_session_key_label(
+        ctx: Context<UpdateSessionKey>,
+        session_pubkey: Pubkey,
+        label: [u8; 32],
+    ) -> Result<()> {
+        update_session_key_label::handler(ctx, session_pubkey, label)
+    }
+
+    /// Fail unless the session key was granted from the origin hashing to `domain_hash`;
+    /// pass its `SessionKeyAccount` when it isn't stored inline
+    pub fn verify_session_domain(
+        ctx: Context<VerifySessionDomain>,
+        session_pubkey: Pubkey,
+        domain_hash: [u8; 32],
+    ) -> Result<()> {
+        verify_session_domain::handler(ctx, session_pubkey, domain_hash)
+    }
+
+    /// Return whether the session key is active, grants `required_permissions` and may
+    /// act for `scope`, for other programs to CPI into before trusting a session signer.
+    /// Pass its `SessionKeyAccount` when it isn't stored inline.
+    pub fn validate_session(
+        ctx: Context<ValidateSession>,
+        session_pubkey: Pubkey,
+        required_permissions: SessionPermissions,
+        scope: Option<Pubkey>,
+    ) -> Result<bool> {
+        validate_session::handler(ctx, session_pubkey, required_permissions, scope)
+    }
+
+    /// Pause a session key until `resume_session_key`; pass its `SessionKeyAccount`
+    /// when it isn't stored inline
+    pub fn suspend_session_key(
+        ctx: Context<SuspendSessionKey>,
+        session_pubkey: Pubkey,
+    ) -> Result<()> {
+        suspend_session_key::suspend_handler(ctx, session_pubkey)
+    }
+
+    /// Let a suspended session key execute again
+    pub fn resume_session_key(
+        ctx: Context<SuspendSessionKey>,
+        session_pubkey: Pubkey,
+    ) -> Result<()> {
+        suspend_session_key::resume_handler(ctx, session_pubkey)
+    }
+
+    /// Signed by a session key alone: narrow its own permissions or shorten its own
+    /// expiry; anything that would widen it is rejected
+    pub fn self_restrict(
+        ctx: Context<SelfRestrict>,
+        new_expires_at_time: Option<i64>,
+        new_expires_at_slot: Option<i64>,
         new_permissions: Option<SessionPermissions>,
     ) -> Result<()> {
-        update_session_key::handler(ctx, session_pubkey, new_expires_at, new_permissions)
+        self_restrict::handler(
+            ctx,
+            new_expires_at_time,
+            new_expires_at_slot,
+            new_permissions,
+        )
     }
 
-    /// Execute an action using a session key
-    // Removed SOL execution endpoint; using SPL delegated transfer instead
+    /// Signed by a session key with `can_delegate`: create a sub-key that stays within
+    /// the signer's permissions, expiry and lists, reserving its lifetime cap and uses
+    pub fn delegate_session_key(
+        ctx: Context<DelegateSessionKey>,
+        args: CreateSessionKeyArgs,
+    ) -> Result<()> {
+        delegate_session_key::handler(ctx, args)
+    }
+
+    /// Limit how many levels of sub-keys can be delegated below a granted key (0 = none)
+    pub fn set_max_delegation_depth(
+        ctx: Context<SetMaxDelegationDepth>,
+        max_delegation_depth: u8,
+    ) -> Result<()> {
+        delegate_session_key::set_max_depth_handler(ctx, max_delegation_depth)
+    }
 
     /// Clean up expired or revoked session keys to save space
-    pub fn cleanup_session_keys(ctx: Context<CleanupSessionKeys>) -> Result<()> {
-        cleanup_session_keys::handler(ctx)
+    pub fn cleanup_session_keys(ctx: Context<CleanupSessionKeys>, limit: u8) -> Result<()> {
+        cleanup_session_keys::handler(ctx, limit)
+    }
+
+    /// Remove expired session keys from any account, paying the caller a small bounty
+    /// from its SOL vault
+    pub fn crank_cleanup(ctx: Context<CrankCleanup>, limit: u8) -> Result<()> {
+        cleanup_session_keys::crank_handler(ctx, limit)
+    }
+
+    /// Keep expired, revoked or used-up session keys for `retention_seconds` before
+    /// either cleanup may remove them
+    pub fn set_cleanup_retention(
+        ctx: Context<SetCleanupRetention>,
+        retention_seconds: u32,
+    ) -> Result<()> {
+        cleanup_session_keys::set_retention_handler(ctx, retention_seconds)
+    }
+
+    /// Move a session key's grant, limits and usage to a new pubkey; callable by the
+    /// authority or by the old key itself when it has `can_self_rotate`
+    pub fn transfer_session_to_new_key(
+        ctx: Context<TransferSessionToNewKey>,
+        old_session_key: Pubkey,
+        new_session_key: Pubkey,
+    ) -> Result<()> {
+        transfer_session_to_new_key::handler(ctx, old_session_key, new_session_key)
+    }
+
+    /// Revoke a session key and re-grant it to a new pubkey with the same permissions,
+    /// limits, usage and remaining expiry
+    pub fn rotate_session_key(
+        ctx: Context<UpdateSessionKey>,
+        old_session_key: Pubkey,
+        new_session_key: Pubkey,
+    ) -> Result<()> {
+        rotate_session_key::handler(ctx, old_session_key, new_session_key)
     }
 
     /// Revoke all session keys at once (emergency function)
@@ -84,26 +275,658 @@ pub mod time {
         revoke_all_session_keys::handler(ctx)
     }
 
-    // Removed SOL deposit/withdraw endpoints
+    /// Revoke every inline session key that can still be valid after `horizon` (unix
+    /// timestamp), keeping shorter-lived keys
+    pub fn revoke_sessions_expiring_after(
+        ctx: Context<RevokeAllSessionKeys>,
+        horizon: i64,
+    ) -> Result<()> {
+        revoke_sessions_expiring_after::handler(ctx, horizon)
+    }
+
+    /// Create a session key in its own PDA, outside the account's inline capacity
+    pub fn create_session_key_account(
+        ctx: Context<CreateSessionKeyAccount>,
+        args: CreateSessionKeyArgs,
+    ) -> Result<()> {
+        session_key_account::create_handler(ctx, args)
+    }
+
+    /// Revoke a PDA-stored session key
+    pub fn revoke_session_key_account(
+        ctx: Context<RevokeSessionKeyAccount>,
+        reason: RevocationReason,
+    ) -> Result<()> {
+        session_key_account::revoke_handler(ctx, reason)
+    }
+
+    /// Close a PDA-stored session key and reclaim its rent
+    pub fn close_session_key_account(ctx: Context<CloseSessionKeyAccount>) -> Result<()> {
+        session_key_account::close_handler(ctx)
+    }
+
+    /// Grow or shrink the account's session key capacity, paying or refunding rent
+    pub fn resize_user_account(ctx: Context<ResizeUserAccount>, new_capacity: u16) -> Result<()> {
+        resize_user_account::handler(ctx, new_capacity)
+    }
+
+    /// Upgrade an account written by an older program version to the current layout,
+    /// filling in defaults for fields it lacks. Anyone may pay for the extra space.
+    pub fn migrate_user_account(ctx: Context<MigrateUserAccount>) -> Result<()> {
+        migrate_user_account::handler(ctx)
+    }
+
+    /// Close the user account and return its rent and vault balance to the authority.
+    /// Remaining accounts: every token account in `delegated_token_accounts`.
+    pub fn close_user_account<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseUserAccount<'info>>,
+    ) -> Result<()> {
+        close_user_account::handler(ctx)
+    }
+
+    /// Close a dormant account, keeping only a hash of its state in a small archive PDA
+    pub fn archive_user_account(ctx: Context<ArchiveUserAccount>) -> Result<()> {
+        archive_user_account::archive_handler(ctx)
+    }
+
+    /// Recreate an archived account from a payload matching its archived hash
+    pub fn restore_user_account(ctx: Context<RestoreUserAccount>, data: Vec<u8>) -> Result<()> {
+        archive_user_account::restore_handler(ctx, data)
+    }
+
+    // ===== SOL VAULT =====
+
+    /// Deposit lamports into the user account PDA
+    pub fn deposit_sol(ctx: Context<DepositSol>, amount: u64) -> Result<()> {
+        sol_vault::deposit_handler(ctx, amount)
+    }
+
+    /// Withdraw lamports above the rent reserve back to the authority
+    pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
+        sol_vault::withdraw_handler(ctx, amount)
+    }
+
+    /// Session key withdraws lamports to itself, within its permissions and limits.
+    /// `slot_ref` is required when the account sets a max slot age; `scope` must name
+    /// the key's dapp when it has one. Remaining accounts: `[guard_program, ..guard
+    /// accounts]` when a guard program is configured.
+    pub fn session_withdraw_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SessionWithdrawSol<'info>>,
+        amount: u64,
+        slot_ref: Option<SlotReference>,
+        scope: Option<Pubkey>,
+    ) -> Result<()> {
+        sol_vault::session_withdraw_handler(ctx, amount, slot_ref, scope)
+    }
+
+    /// Session key pays lamports from the vault to any system account, within its
+    /// permissions, limits and recipient allowlist. Remaining accounts as for
+    /// `session_withdraw_sol`.
+    pub fn session_transfer_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SessionTransferSol<'info>>,
+        amount: u64,
+        slot_ref: Option<SlotReference>,
+        scope: Option<Pubkey>,
+    ) -> Result<()> {
+        sol_vault::session_transfer_handler(ctx, amount, slot_ref, scope)
+    }
+
+    // ===== GAS TANK =====
+
+    /// Create the account's gas tank, reimbursing `fee_per_execution` lamports per execution
+    pub fn initialize_gas_tank(
+        ctx: Context<InitializeGasTank>,
+        fee_per_execution: u64,
+    ) -> Result<()> {
+        gas_tank::initialize_handler(ctx, fee_per_execution)
+    }
+
+    /// Change the per-execution reimbursement
+    pub fn set_gas_tank_fee(ctx: Context<SetGasTankFee>, fee_per_execution: u64) -> Result<()> {
+        gas_tank::set_fee_handler(ctx, fee_per_execution)
+    }
+
+    /// Add lamports to the gas tank
+    pub fn top_up_gas_tank(ctx: Context<TopUpGasTank>, amount: u64) -> Result<()> {
+        gas_tank::top_up_handler(ctx, amount)
+    }
 
     // ===== SPL TOKEN FLOW =====
     pub fn spl_approve_delegate(ctx: Context<SplApproveDelegate>, amount: u64) -> Result<()> {
         spl_approve_delegate::handler(ctx, amount)
     }
 
-    pub fn spl_delegated_transfer(ctx: Context<SplDelegatedTransfer>, amount: u64) -> Result<()> {
-        spl_delegated_transfer::handler(ctx, amount)
+    /// Create a session key and approve the SPL delegate for `mint` atomically, so a new
+    /// key is never left without its token approval
+    pub fn approve_and_create_session(
+        ctx: Context<SplApproveDelegate>,
+        args: CreateSessionKeyArgs,
+        amount: u64,
+    ) -> Result<()> {
+        spl_approve_delegate::approve_and_create_session_handler(ctx, args, amount)
+    }
+
+    /// Remaining accounts: `[guard_program, ..guard accounts]` when a guard program is configured.
+    /// `slot_ref` is required when the account sets a max slot age; `category` tags the
+    /// transfer with an id from the account's spend taxonomy. `scope` must name the
+    /// session key's dapp when it has one. `recipient` names the wallet that must own
+    /// `to_token`, optionally as its associated token account.
+    pub fn spl_delegated_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SplDelegatedTransfer<'info>>,
+        amount: u64,
+        slot_ref: Option<SlotReference>,
+        category: Option<u16>,
+        scope: Option<Pubkey>,
+        recipient: Option<RecipientCheck>,
+    ) -> Result<()> {
+        spl_delegated_transfer::handler(ctx, amount, slot_ref, category, scope, recipient)
+    }
+
+    /// `spl_delegated_transfer` to `recipient`'s associated token account, creating it
+    /// first (paid by the session key) if it doesn't exist. Remaining accounts: the
+    /// `spl_delegated_transfer` accounts in order, then its remaining accounts.
+    pub fn spl_delegated_transfer_with_ata<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SplDelegatedTransferWithAta<'info>>,
+        amount: u64,
+        slot_ref: Option<SlotReference>,
+        category: Option<u16>,
+        scope: Option<Pubkey>,
+    ) -> Result<()> {
+        spl_delegated_transfer::with_ata_handler(ctx, amount, slot_ref, category, scope)
     }
 
     pub fn spl_revoke_delegate(ctx: Context<SplRevokeDelegate>) -> Result<()> {
         spl_revoke_delegate::handler(ctx)
     }
 
-    /// Update the list of allowed SPL mints for this user account
+    /// Update the list of allowed SPL mints for this user account; returns the risk flags of
+    /// listed mints passed in remaining accounts
     pub fn update_allowed_mints(
         ctx: Context<UpdateAllowedMints>,
         mints: Vec<Pubkey>,
-    ) -> Result<()> {
+    ) -> Result<Vec<MintRisk>> {
         update_allowed_mints::handler(ctx, mints)
     }
+
+    /// Opt this account in or out of screening transfers against the program blocklist
+    pub fn set_blocklist_enforcement(
+        ctx: Context<SetBlocklistEnforcement>,
+        enabled: bool,
+    ) -> Result<()> {
+        manage_blocklist::set_enforcement_handler(ctx, enabled)
+    }
+
+    // ===== SPEND BUDGETS =====
+
+    /// Cap how much of a mint all session keys together can spend per window
+    pub fn initialize_spend_budget(
+        ctx: Context<InitializeSpendBudget>,
+        limit: u64,
+        window_seconds: i64,
+    ) -> Result<()> {
+        spend_budget::initialize_handler(ctx, limit, window_seconds)
+    }
+
+    /// Change a spend budget's limit or window
+    pub fn update_spend_budget(
+        ctx: Context<UpdateSpendBudget>,
+        limit: u64,
+        window_seconds: i64,
+    ) -> Result<()> {
+        spend_budget::update_handler(ctx, limit, window_seconds)
+    }
+
+    /// Cap the budget mint's value that custom actions route into `program` per window,
+    /// or remove the cap with None
+    pub fn set_program_budget(
+        ctx: Context<SetProgramBudget>,
+        program: Pubkey,
+        limit: Option<u64>,
+    ) -> Result<()> {
+        spend_budget::set_program_handler(ctx, program, limit)
+    }
+
+    /// Remove a spend budget and reclaim its rent
+    pub fn close_spend_budget(ctx: Context<CloseSpendBudget>) -> Result<()> {
+        spend_budget::close_handler(ctx)
+    }
+
+    // ===== ACTIVITY DIGESTS =====
+
+    /// Aggregate execution events into one digest every `interval` executions
+    pub fn enable_activity_digest(ctx: Context<EnableActivityDigest>, interval: u32) -> Result<()> {
+        activity_digest::enable_handler(ctx, interval)
+    }
+
+    /// Change the digest interval; 0 returns to per-execution events
+    pub fn set_digest_interval(ctx: Context<SetDigestInterval>, interval: u32) -> Result<()> {
+        activity_digest::set_interval_handler(ctx, interval)
+    }
+
+    /// Start recording every execution into a lifetime Merkle history for light verification
+    pub fn initialize_account_stats(ctx: Context<InitializeAccountStats>) -> Result<()> {
+        account_stats::initialize_handler(ctx)
+    }
+
+    // ===== SCHEDULED GRANTS =====
+
+    /// Record a session key grant that takes effect at `activate_at`
+    pub fn schedule_session_key(
+        ctx: Context<ScheduleSessionKey>,
+        args: CreateSessionKeyArgs,
+        activate_at: i64,
+    ) -> Result<()> {
+        scheduled_session::schedule_handler(ctx, args, activate_at)
+    }
+
+    /// Drop a scheduled grant before it activates and reclaim its rent
+    pub fn cancel_scheduled_session(ctx: Context<CancelScheduledSession>) -> Result<()> {
+        scheduled_session::cancel_handler(ctx)
+    }
+
+    /// Permissionless crank that adds a scheduled grant's session key once it is due
+    pub fn activate_scheduled_session(ctx: Context<ActivateScheduledSession>) -> Result<()> {
+        scheduled_session::activate_handler(ctx)
+    }
+
+    // ===== PAYOUT PIPELINES =====
+
+    /// Define a payout pipeline (up to 4 transfer/swap steps with per-step limits)
+    pub fn create_pipeline(
+        ctx: Context<CreatePipeline>,
+        pipeline_id: u16,
+        steps: Vec<PipelineStep>,
+    ) -> Result<()> {
+        pipeline::create_handler(ctx, pipeline_id, steps)
+    }
+
+    /// Delete a payout pipeline and reclaim its rent
+    pub fn close_pipeline(ctx: Context<ClosePipeline>) -> Result<()> {
+        pipeline::close_handler(ctx)
+    }
+
+    /// Run a payout pipeline with a session key; one `PipelineStepArgs` per step.
+    /// Remaining accounts, per step in order:
+    /// transfer `[from_token, mint, to_token, delegate_authority]`,
+    /// swap `[swap_program, source_token, destination_token, delegate_authority,
+    /// ..account_count forwarded]`, after the guard program when one is configured.
+    /// Refused with `PipelineControlsUnsupported` while the account uses controls only
+    /// direct transfers check, or when a step could owe a protocol fee or savings cut
+    pub fn execute_pipeline<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecutePipeline<'info>>,
+        step_args: Vec<PipelineStepArgs>,
+        slot_ref: Option<SlotReference>,
+        scope: Option<Pubkey>,
+    ) -> Result<()> {
+        pipeline::execute_handler(ctx, step_args, slot_ref, scope)
+    }
+
+    // ===== CUSTOM ACTIONS =====
+
+    /// CPI into a program on the session key's `allowed_programs`, signed by the account's
+    /// executor PDA. Remaining accounts: `[target_program, ..accounts forwarded to it]`,
+    /// after the guard program when one is configured
+    pub fn execute_custom<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteCustom<'info>>,
+        data: Vec<u8>,
+        slot_ref: Option<SlotReference>,
+        scope: Option<Pubkey>,
+    ) -> Result<()> {
+        execute_custom::handler(ctx, data, slot_ref, scope)
+    }
+
+    // ===== OPERATOR BONDS =====
+
+    /// Require (or stop requiring) session operators to lock a bond before executing
+    pub fn set_bond_requirement(
+        ctx: Context<SetBondRequirement>,
+        bond_requirement: Option<BondRequirement>,
+    ) -> Result<()> {
+        operator_bond::set_requirement_handler(ctx, bond_requirement)
+    }
+
+    /// Require (or stop requiring) the bonded operator to countersign each delegated transfer
+    pub fn set_operator_signature_required(
+        ctx: Context<SetOperatorSignatureRequired>,
+        required: bool,
+    ) -> Result<()> {
+        operator_bond::set_signature_required_handler(ctx, required)
+    }
+
+    /// Operator locks SOL as a bond backing a session key
+    pub fn post_bond(ctx: Context<PostBond>, session_pubkey: Pubkey, amount: u64) -> Result<()> {
+        operator_bond::post_sol_handler(ctx, session_pubkey, amount)
+    }
+
+    /// Operator locks SPL tokens as a bond backing a session key
+    pub fn post_bond_spl(
+        ctx: Context<PostBondSpl>,
+        session_pubkey: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        operator_bond::post_spl_handler(ctx, session_pubkey, amount)
+    }
+
+    /// Operator starts the dispute window once its session is revoked or expired
+    pub fn request_bond_release(ctx: Context<RequestBondRelease>) -> Result<()> {
+        operator_bond::request_release_handler(ctx)
+    }
+
+    /// Authority slashes a bond backed by an upheld violation report
+    pub fn slash_bond(ctx: Context<SlashBond>, amount: u64) -> Result<()> {
+        operator_bond::slash_handler(ctx, amount)
+    }
+
+    /// Operator reclaims the remaining bond after the dispute window
+    pub fn withdraw_bond(ctx: Context<WithdrawBond>) -> Result<()> {
+        operator_bond::withdraw_handler(ctx)
+    }
+
+    /// Operator creates its public reputation record (required before posting bonds)
+    pub fn initialize_operator_reputation(
+        ctx: Context<InitializeOperatorReputation>,
+    ) -> Result<()> {
+        operator_reputation::initialize_handler(ctx)
+    }
+
+    // ===== SERVICE REGISTRY =====
+
+    /// Operator publishes its name, URL hash and session keys for wallets to display
+    pub fn register_service(
+        ctx: Context<RegisterService>,
+        name: [u8; 32],
+        url_hash: [u8; 32],
+        session_keys: Vec<Pubkey>,
+    ) -> Result<()> {
+        service_registry::register_handler(ctx, name, url_hash, session_keys)
+    }
+
+    /// Operator updates its registry entry; this clears any verification
+    pub fn update_service(
+        ctx: Context<UpdateService>,
+        name: [u8; 32],
+        url_hash: [u8; 32],
+        session_keys: Vec<Pubkey>,
+    ) -> Result<()> {
+        service_registry::update_handler(ctx, name, url_hash, session_keys)
+    }
+
+    /// Operator removes its registry entry and reclaims rent
+    pub fn close_service(_ctx: Context<CloseService>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Program upgrade authority marks a service entry as verified (or not)
+    pub fn set_service_verified(ctx: Context<SetServiceVerified>, verified: bool) -> Result<()> {
+        service_registry::set_verified_handler(ctx, verified)
+    }
+
+    /// Operator publishes a recommended permission preset for its service
+    pub fn publish_preset(
+        ctx: Context<PublishPreset>,
+        preset_id: u16,
+        permissions: SessionPermissions,
+        max_duration_seconds: i64,
+    ) -> Result<()> {
+        service_presets::publish_handler(ctx, preset_id, permissions, max_duration_seconds)
+    }
+
+    /// Operator changes a preset; existing sessions keep what they were granted
+    pub fn update_preset(
+        ctx: Context<UpdatePreset>,
+        permissions: SessionPermissions,
+        max_duration_seconds: i64,
+    ) -> Result<()> {
+        service_presets::update_handler(ctx, permissions, max_duration_seconds)
+    }
+
+    /// Create a time-based session key granting exactly a service preset's permissions
+    pub fn create_session_key_from_preset(
+        ctx: Context<CreateSessionKeyFromPreset>,
+        session_pubkey: Pubkey,
+        expires_at: i64,
+        expected_version: u32,
+        terms_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        service_presets::create_session_key_handler(
+            ctx,
+            session_pubkey,
+            expires_at,
+            expected_version,
+            terms_hash,
+        )
+    }
+
+    // ===== DISPUTES =====
+
+    /// File a violation report against a session key, citing the offending transaction
+    pub fn report_violation(
+        ctx: Context<ReportViolation>,
+        session_pubkey: Pubkey,
+        evidence: [u8; 64],
+    ) -> Result<()> {
+        disputes::report_handler(ctx, session_pubkey, evidence)
+    }
+
+    /// Resolve a violation report once its timelock has elapsed
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, upheld: bool) -> Result<()> {
+        disputes::resolve_handler(ctx, upheld)
+    }
+
+    // ===== ADMIN: BLOCKLIST REGISTRY =====
+
+    /// Create the program-wide blocklist registry (program upgrade authority only)
+    pub fn initialize_blocklist(ctx: Context<InitializeBlocklist>) -> Result<()> {
+        manage_blocklist::initialize_handler(ctx)
+    }
+
+    /// Add and remove addresses from the blocklist registry (admin only)
+    pub fn update_blocklist(
+        ctx: Context<UpdateBlocklist>,
+        additions: Vec<Pubkey>,
+        removals: Vec<Pubkey>,
+    ) -> Result<()> {
+        manage_blocklist::update_handler(ctx, additions, removals)
+    }
+
+    // ===== ADMIN: PROGRAM CONFIG =====
+
+    /// Create the program config with its global per-transfer cap (0 = none); program
+    /// upgrade authority only. Until it exists, no program-wide pause or limit applies.
+    pub fn initialize_program_config(
+        ctx: Context<InitializeProgramConfig>,
+        max_transfer_amount: u64,
+    ) -> Result<()> {
+        program_config::initialize_handler(ctx, max_transfer_amount)
+    }
+
+    /// Pause or unpause every session execution across the program (admin only)
+    pub fn set_paused(ctx: Context<UpdateProgramConfig>, paused: bool) -> Result<()> {
+        program_config::set_paused_handler(ctx, paused)
+    }
+
+    /// Change the config's admin and global limits (admin only)
+    pub fn update_config(
+        ctx: Context<UpdateProgramConfig>,
+        admin: Pubkey,
+        max_transfer_amount: u64,
+    ) -> Result<()> {
+        program_config::update_handler(ctx, admin, max_transfer_amount)
+    }
+
+    /// Add mints to and remove mints from the program-wide denylist (admin only)
+    pub fn update_mint_denylist(
+        ctx: Context<UpdateProgramConfig>,
+        additions: Vec<Pubkey>,
+        removals: Vec<Pubkey>,
+    ) -> Result<()> {
+        program_config::update_mint_denylist_handler(ctx, additions, removals)
+    }
+
+    /// Set the protocol fee charged on top of session transfers, in basis points (admin
+    /// only)
+    pub fn set_fee(ctx: Context<UpdateProgramConfig>, fee_bps: u16) -> Result<()> {
+        program_config::set_fee_handler(ctx, fee_bps)
+    }
+
+    /// Withdraw collected protocol fees, in lamports or in one mint (admin only)
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        program_config::withdraw_fees_handler(ctx, amount)
+    }
+
+    /// Set or clear the attestation (KYC) token gate checked on every session execution
+    pub fn set_attestation_gate(
+        ctx: Context<SetAttestationGate>,
+        attestation_gate: Option<AttestationGate>,
+    ) -> Result<()> {
+        set_attestation_gate::handler(ctx, attestation_gate)
+    }
+
+    /// Replace the blackout calendar; session executions are refused inside any window
+    pub fn set_blackout_windows(
+        ctx: Context<SetBlackoutWindows>,
+        windows: Vec<BlackoutWindow>,
+    ) -> Result<()> {
+        set_blackout_windows::handler(ctx, windows)
+    }
+
+    /// Require executions to reference a slot hash at most `max_slot_age` slots old (0 = off)
+    pub fn set_max_slot_age(ctx: Context<SetMaxSlotAge>, max_slot_age: u32) -> Result<()> {
+        set_max_slot_age::handler(ctx, max_slot_age)
+    }
+
+    /// Delay widened session permissions by `seconds` after each update (0 = off)
+    pub fn set_widening_delay(ctx: Context<SetWideningDelay>, seconds: u32) -> Result<()> {
+        set_widening_delay::handler(ctx, seconds)
+    }
+
+    /// Set or clear the rule saving a percentage and/or round-up of every outgoing transfer
+    pub fn set_savings_rule(
+        ctx: Context<SetSavingsRule>,
+        savings_rule: Option<SavingsRule>,
+    ) -> Result<()> {
+        set_savings_rule::handler(ctx, savings_rule)
+    }
+
+    /// Replace the spend category taxonomy transfers can be tagged with
+    pub fn set_spend_categories(
+        ctx: Context<SetSpendCategories>,
+        categories: Vec<SpendCategory>,
+    ) -> Result<()> {
+        set_spend_categories::handler(ctx, categories)
+    }
+
+    /// Set or clear the guard program consulted before every session execution
+    pub fn set_guard_program(
+        ctx: Context<SetGuardProgram>,
+        guard_program: Option<Pubkey>,
+    ) -> Result<()> {
+        set_guard_program::handler(ctx, guard_program)
+    }
+
+    /// Opt in or out of Token-2022 mints with a permanent delegate
+    pub fn set_permanent_delegate_opt_in(
+        ctx: Context<SetPermanentDelegateOptIn>,
+        allowed: bool,
+    ) -> Result<()> {
+        set_permanent_delegate_opt_in::handler(ctx, allowed)
+    }
+
+    /// Lock session keys whose spends keep getting refused, or clear the policy with `None`
+    pub fn set_lockout_policy(
+        ctx: Context<SetLockoutPolicy>,
+        lockout_policy: Option<LockoutPolicy>,
+    ) -> Result<()> {
+        lockout::set_handler(ctx, lockout_policy)
+    }
+
+    /// Count a spend the session key refused toward the account's lockout policy
+    pub fn record_refused_spend(
+        ctx: Context<RecordRefusedSpend>,
+        session_pubkey: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        lockout::record_refused_handler(ctx, session_pubkey, amount)
+    }
+
+    // ===== POLICY =====
+
+    /// Attach a declarative policy that every session spend is checked against
+    pub fn initialize_policy(
+        ctx: Context<InitializePolicy>,
+        document: PolicyDocument,
+    ) -> Result<()> {
+        manage_policy::initialize_handler(ctx, document)
+    }
+
+    /// Replace the account's policy document
+    pub fn update_policy(ctx: Context<UpdatePolicy>, document: PolicyDocument) -> Result<()> {
+        manage_policy::update_handler(ctx, document)
+    }
+
+    /// Remove the account's policy and reclaim its rent
+    pub fn close_policy(ctx: Context<ClosePolicy>) -> Result<()> {
+        manage_policy::close_handler(ctx)
+    }
+
+    // ===== EMERGENCY REVOCATION =====
+
+    /// Commit to a kill phrase (`kill_phrase_commitment`; zero clears it)
+    pub fn set_kill_phrase(ctx: Context<SetKillPhrase>, commitment: [u8; 32]) -> Result<()> {
+        kill_phrase::set_handler(ctx, commitment)
+    }
+
+    /// Present the kill phrase to revoke every session key and pause the account; callable
+    /// by anyone who knows it
+    pub fn trigger_kill_phrase(ctx: Context<TriggerKillPhrase>, phrase: Vec<u8>) -> Result<()> {
+        kill_phrase::trigger_handler(ctx, phrase)
+    }
+
+    /// Designate (or with None, remove) a guardian key that can revoke every session
+    /// key if the authority is lost or compromised
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Option<Pubkey>) -> Result<()> {
+        guardian::set_handler(ctx, guardian)
+    }
+
+    /// Guardian revokes every session key; it can't create keys or move funds
+    pub fn guardian_revoke_all(ctx: Context<GuardianRevokeAll>) -> Result<()> {
+        guardian::revoke_all_handler(ctx)
+    }
+
+    /// Pause or unpause every session execution on the account
+    pub fn set_account_paused(ctx: Context<SetAccountPaused>, paused: bool) -> Result<()> {
+        set_account_paused::handler(ctx, paused)
+    }
+
+    // ===== RECIPIENT CONSENT =====
+
+    /// Require session payouts to go only to recipients that accepted them
+    pub fn set_recipient_consent_required(
+        ctx: Context<SetRecipientConsentRequired>,
+        required: bool,
+    ) -> Result<()> {
+        recipient_consent::set_required_handler(ctx, required)
+    }
+
+    /// Signed by a recipient: accept session payouts from a user account
+    pub fn accept_payments(ctx: Context<AcceptPayments>) -> Result<()> {
+        recipient_consent::accept_handler(ctx)
+    }
+
+    /// Signed by a recipient: stop accepting payouts and reclaim the consent's rent
+    pub fn withdraw_payment_consent(ctx: Context<WithdrawPaymentConsent>) -> Result<()> {
+        recipient_consent::withdraw_handler(ctx)
+    }
+
+    // ===== BUILD VERIFICATION =====
+
+    /// Program upgrade authority records the hash of a verified build of the deployed
+    /// program, so integrators can check a deployment against its source
+    pub fn publish_build_hash(
+        ctx: Context<PublishBuildHash>,
+        build_hash: [u8; 32],
+        source_commit: [u8; 20],
+    ) -> Result<()> {
+        publish_build_hash::handler(ctx, build_hash, source_commit)
+    }
 }
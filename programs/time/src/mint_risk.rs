@@ -0,0 +1,71 @@
+//! Token-2022 extension checks on mints before session keys may move them, and the risk
+//! flags wallets show when a mint is allowlisted or approved.
+
+use crate::errors::ErrorCode;
+use crate::state::UserAccount;
+use anchor_lang::prelude::*;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig,
+        transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Mint,
+};
+
+/// The mint has a freeze authority that can lock any holder's account
+pub const MINT_RISK_FREEZE_AUTHORITY: u8 = 1 << 0;
+/// The mint charges, or can start charging, a fee on every transfer
+pub const MINT_RISK_TRANSFER_FEE: u8 = 1 << 1;
+/// The mint's permanent delegate can move or burn any holder's tokens
+pub const MINT_RISK_PERMANENT_DELEGATE: u8 = 1 << 2;
+/// Transfers of the mint call into an issuer-chosen hook program
+pub const MINT_RISK_TRANSFER_HOOK: u8 = 1 << 3;
+
+/// Risk flags of a mint, as returned by `update_allowed_mints`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct MintRisk {
+    pub mint: Pubkey,
+    /// `MINT_RISK_*` bits
+    pub flags: u8,
+}
+
+/// `MINT_RISK_*` flags of an SPL Token or Token-2022 mint; 0 for any other account
+pub fn risk_flags(mint: &AccountInfo) -> Result<u8> {
+    if *mint.owner != spl_token_2022::ID && *mint.owner != TOKEN_PROGRAM_ID {
+        return Ok(0);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    let mut flags = 0;
+    if state.base.freeze_authority.is_some() {
+        flags |= MINT_RISK_FREEZE_AUTHORITY;
+    }
+    if state.get_extension::<TransferFeeConfig>().is_ok() {
+        flags |= MINT_RISK_TRANSFER_FEE;
+    }
+    if state
+        .get_extension::<PermanentDelegate>()
+        .is_ok_and(|extension| extension.delegate.0 != Pubkey::default())
+    {
+        flags |= MINT_RISK_PERMANENT_DELEGATE;
+    }
+    if state.get_extension::<TransferHook>().is_ok() {
+        flags |= MINT_RISK_TRANSFER_HOOK;
+    }
+    Ok(flags)
+}
+
+/// Reject Token-2022 mints with a permanent delegate, which can move or burn any holder's
+/// tokens regardless of the approvals this program manages, unless the account has opted
+/// in to them. Returns the mint's risk flags.
+pub fn require_mint_allowed(user_account: &UserAccount, mint: &AccountInfo) -> Result<u8> {
+    let flags = risk_flags(mint)?;
+    if !user_account.allow_permanent_delegate_mints && flags & MINT_RISK_PERMANENT_DELEGATE != 0 {
+        msg!("Mint {} has a permanent delegate", mint.key);
+        return err!(ErrorCode::PermanentDelegateNotAllowed);
+    }
+    Ok(flags)
+}
@@ -0,0 +1,166 @@
+//! Declarative spending policy.
+//!
+//! A `PolicyDocument` is a versioned list of rules stored in the account's `PolicyAccount`
+//! and checked by `evaluate` on every session spend. New rule types ship as new
+//! `PolicyCondition` variants here instead of new `UserAccount` or `SessionKey` fields.
+//! Variants are only ever appended, so stored documents keep decoding after an upgrade.
+
+use crate::constants::{MAX_POLICY_LIST_LEN, MAX_POLICY_RULES};
+use crate::errors::ErrorCode;
+use crate::state::{PolicyAccount, UserAccount};
+use anchor_lang::prelude::*;
+
+/// Document version this program evaluates
+pub const POLICY_VERSION: u8 = 1;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PolicyDocument {
+    /// Must be `POLICY_VERSION`
+    pub version: u8,
+    /// Every applicable rule must pass
+    pub rules: Vec<PolicyRule>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PolicyRule {
+    /// Session key the rule applies to (none = every key)
+    pub session_key: Option<Pubkey>,
+    pub condition: PolicyCondition,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum PolicyCondition {
+    /// Largest amount a single spend may move
+    MaxAmount(u64),
+    /// Spends only within `[start, end)`, as unix timestamps
+    ActiveBetween { start: i64, end: i64 },
+    /// Spends only on the given UTC weekdays (bit 0 = Monday) between the given minutes
+    /// of the day; `end_minute` before `start_minute` wraps past midnight
+    Schedule {
+        weekdays: u8,
+        start_minute: u16,
+        end_minute: u16,
+    },
+    /// Recipients (wallets or token accounts) spends may pay
+    Recipients(Vec<Pubkey>),
+    /// Mints spends may move; native SOL is the wrapped SOL mint
+    Mints(Vec<Pubkey>),
+}
+
+/// What a session is about to do, as seen by the policy. Fields an action doesn't have
+/// (e.g. a recipient for a custom action) are `None`, and rules on them are skipped.
+pub struct PolicyRequest {
+    pub session_key: Pubkey,
+    pub now: i64,
+    pub amount: u64,
+    pub mint: Option<Pubkey>,
+    /// Owner of the receiving account, or the receiving wallet itself
+    pub recipient: Option<Pubkey>,
+    /// Receiving token account, or the receiving wallet itself
+    pub recipient_account: Option<Pubkey>,
+}
+
+impl PolicyDocument {
+    /// Check the document is a version this program understands and fits its account
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.version == POLICY_VERSION,
+            ErrorCode::PolicyVersionUnsupported
+        );
+        require!(
+            self.rules.len() <= MAX_POLICY_RULES,
+            ErrorCode::InvalidPolicy
+        );
+        for rule in &self.rules {
+            let valid = match &rule.condition {
+                PolicyCondition::MaxAmount(_) => true,
+                PolicyCondition::ActiveBetween { start, end } => start < end,
+                PolicyCondition::Schedule {
+                    weekdays,
+                    start_minute,
+                    end_minute,
+                } => {
+                    *weekdays & 0x7f != 0
+                        && *start_minute < MINUTES_PER_DAY
+                        && *end_minute < MINUTES_PER_DAY
+                }
+                PolicyCondition::Recipients(list) | PolicyCondition::Mints(list) => {
+                    list.len() <= MAX_POLICY_LIST_LEN
+                }
+            };
+            require!(valid, ErrorCode::InvalidPolicy);
+        }
+        Ok(())
+    }
+
+    /// Fail with `PolicyViolation` on the first rule for this session key that the
+    /// request breaks
+    pub fn evaluate(&self, request: &PolicyRequest) -> Result<()> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            if rule
+                .session_key
+                .is_some_and(|key| key != request.session_key)
+            {
+                continue;
+            }
+            if !rule.condition.allows(request) {
+                msg!("Policy rule {} rejected the spend", index);
+                return err!(ErrorCode::PolicyViolation);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PolicyCondition {
+    fn allows(&self, request: &PolicyRequest) -> bool {
+        match self {
+            PolicyCondition::MaxAmount(max) => request.amount <= *max,
+            PolicyCondition::ActiveBetween { start, end } => {
+                *start <= request.now && request.now < *end
+            }
+            PolicyCondition::Schedule {
+                weekdays,
+                start_minute,
+                end_minute,
+            } => {
+                // 1970-01-01 was a Thursday, day 3 counting from Monday
+                let day = (request.now.div_euclid(86_400) + 3).rem_euclid(7);
+                let minute = (request.now.rem_euclid(86_400) / 60) as u16;
+                let in_hours = if start_minute <= end_minute {
+                    *start_minute <= minute && minute < *end_minute
+                } else {
+                    minute >= *start_minute || minute < *end_minute
+                };
+                weekdays & (1 << day) != 0 && in_hours
+            }
+            PolicyCondition::Recipients(list) => {
+                match (request.recipient, request.recipient_account) {
+                    (Some(owner), Some(account)) => {
+                        list.contains(&owner) || list.contains(&account)
+                    }
+                    _ => true,
+                }
+            }
+            PolicyCondition::Mints(list) => request.mint.is_none_or(|mint| list.contains(&mint)),
+        }
+    }
+}
+
+/// Evaluate the account's policy, if it has one. `policy` must be passed whenever
+/// `has_policy` is set, so leaving the account out can't skip the rules.
+pub fn enforce_policy(
+    user_account: &UserAccount,
+    policy: Option<&PolicyAccount>,
+    request: &PolicyRequest,
+) -> Result<()> {
+    match policy {
+        Some(policy) => policy.document.evaluate(request),
+        None => {
+            require!(!user_account.has_policy, ErrorCode::PolicyRequired);
+            Ok(())
+        }
+    }
+}
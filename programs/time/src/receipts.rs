@@ -0,0 +1,171 @@
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+// ===== EXECUTION RECEIPTS =====
+
+/// Leaf committed for every session execution, so any single action can later be proven
+/// against a published root: `sha256(session_key || mint || destination || amount_le || slot_le)`
+pub fn receipt_leaf(
+    session_key: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+    slot: u64,
+) -> [u8; 32] {
+    hashv(&[
+        session_key.as_ref(),
+        mint.as_ref(),
+        destination.as_ref(),
+        &amount.to_le_bytes(),
+        &slot.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+// ===== OPERATOR SIGNATURES =====
+
+/// Size of the Ed25519 program's per-signature offsets record
+const ED25519_OFFSETS_SIZE: usize = 14;
+
+/// Message a bonded operator signs to authorize one transfer:
+/// `sha256("operator_receipt" || user_account || session_key || mint || destination ||
+/// amount_le || use_count_le)`. The key's `use_count` before the transfer makes each
+/// signature good for a single execution.
+pub fn operator_payload(
+    user_account: &Pubkey,
+    session_key: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+    use_count: u32,
+) -> [u8; 32] {
+    hashv(&[
+        b"operator_receipt",
+        user_account.as_ref(),
+        session_key.as_ref(),
+        mint.as_ref(),
+        destination.as_ref(),
+        &amount.to_le_bytes(),
+        &use_count.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Receipt leaf of an operator-signed execution, committing to who signed and how:
+/// `sha256(receipt || operator || signature)`
+pub fn attested_leaf(receipt: &[u8; 32], operator: &Pubkey, signature: &[u8; 64]) -> [u8; 32] {
+    hashv(&[receipt, operator.as_ref(), signature]).to_bytes()
+}
+
+/// Find an Ed25519 program instruction earlier in this transaction that verified
+/// `operator`'s signature over `message`, and return the signature. The runtime has
+/// already checked the signature; this only confirms what was checked. Offsets must
+/// point into the Ed25519 instruction itself.
+pub fn verify_operator_signature(
+    instructions: &AccountInfo,
+    operator: &Pubkey,
+    message: &[u8; 32],
+) -> Result<[u8; 64]> {
+    let current = load_current_index_checked(instructions)?;
+    for index in 0..current {
+        let ix = load_instruction_at_checked(index as usize, instructions)?;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+        let data = &ix.data;
+        let count = data.first().copied().unwrap_or(0) as usize;
+        for offsets in data
+            .get(2..2 + count * ED25519_OFFSETS_SIZE)
+            .unwrap_or_default()
+            .chunks_exact(ED25519_OFFSETS_SIZE)
+        {
+            let field = |i: usize| u16::from_le_bytes([offsets[2 * i], offsets[2 * i + 1]]);
+            let inline = [1, 3, 6].iter().all(|&i| field(i) == u16::MAX);
+            let slice = |offset: u16, len: usize| data.get(offset as usize..offset as usize + len);
+            let (Some(signature), Some(pubkey), Some(signed)) = (
+                slice(field(0), 64),
+                slice(field(2), 32),
+                slice(field(4), field(5) as usize),
+            ) else {
+                continue;
+            };
+            if inline && pubkey == operator.as_ref() && signed == message {
+                return Ok(signature.try_into().unwrap());
+            }
+        }
+    }
+    err!(ErrorCode::OperatorSignatureMissing)
+}
+
+/// Append-only Merkle tree of fixed depth `D` that stores only its right-most frontier,
+/// so appends cost `O(D)` hashes and constant space. Empty leaves are all-zero.
+/// Holds up to `2^D - 1` leaves: the frontier has no slot for the completed root.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MerkleFrontier<const D: usize> {
+    /// Left siblings on the path of the next leaf, one per level
+    pub branch: [[u8; 32]; D],
+    /// Number of leaves appended
+    pub count: u64,
+}
+
+impl<const D: usize> Default for MerkleFrontier<D> {
+    fn default() -> Self {
+        Self {
+            branch: [[0; 32]; D],
+            count: 0,
+        }
+    }
+}
+
+impl<const D: usize> MerkleFrontier<D> {
+    pub const SIZE: usize = 32 * D + 8;
+
+    /// Maximum number of leaves
+    pub const CAPACITY: u64 = (1u64 << D) - 1;
+
+    /// Whether another leaf fits in the tree
+    pub fn is_full(&self) -> bool {
+        self.count >= Self::CAPACITY
+    }
+
+    /// Append a leaf; callers must check `is_full` first
+    pub fn append(&mut self, leaf: [u8; 32]) {
+        let mut node = leaf;
+        let mut size = self.count + 1;
+        self.count = size;
+        for branch in self.branch.iter_mut() {
+            if size & 1 == 1 {
+                *branch = node;
+                return;
+            }
+            node = hash_pair(branch, &node);
+            size >>= 1;
+        }
+    }
+
+    /// Root over all appended leaves, padding the rest of the tree with zero leaves
+    pub fn root(&self) -> [u8; 32] {
+        let mut node = [0u8; 32];
+        let mut zero = [0u8; 32];
+        let mut size = self.count;
+        for branch in self.branch.iter() {
+            node = if size & 1 == 1 {
+                hash_pair(branch, &node)
+            } else {
+                hash_pair(&node, &zero)
+            };
+            zero = hash_pair(&zero, &zero);
+            size >>= 1;
+        }
+        node
+    }
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[left, right]).to_bytes()
+}
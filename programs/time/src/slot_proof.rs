@@ -0,0 +1,46 @@
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+
+// ===== RECENT SLOT PROOFS =====
+
+/// Size of one `SlotHashes` entry: 8 (slot) + 32 (hash)
+const SLOT_HASH_ENTRY_SIZE: usize = 8 + 32;
+
+/// Slot and bank hash, read from the `SlotHashes` sysvar when the transaction was built,
+/// that an execution commits to so it can't land long after it was signed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SlotReference {
+    pub slot: u64,
+    pub hash: [u8; 32],
+}
+
+/// Check that `reference` names a slot at most `max_age` slots old whose hash matches
+/// the `SlotHashes` sysvar. Entries are stored newest first, so the scan stops as soon
+/// as it passes the referenced slot.
+pub fn verify_recent_slot(
+    slot_hashes: &AccountInfo,
+    reference: &SlotReference,
+    max_age: u32,
+    current_slot: u64,
+) -> Result<()> {
+    require!(
+        current_slot.saturating_sub(reference.slot) <= max_age as u64,
+        ErrorCode::SlotProofInvalid
+    );
+
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 8, ErrorCode::SlotProofInvalid);
+    let len = u64::from_le_bytes(data[..8].try_into().unwrap()) as usize;
+    let found = data[8..]
+        .chunks_exact(SLOT_HASH_ENTRY_SIZE)
+        .take(len)
+        .map(|entry| {
+            let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+            (slot, &entry[8..])
+        })
+        .take_while(|(slot, _)| *slot >= reference.slot)
+        .any(|(slot, hash)| slot == reference.slot && hash == reference.hash);
+    require!(found, ErrorCode::SlotProofInvalid);
+
+    Ok(())
+}
@@ -1,5 +1,21 @@
-use crate::constants::{MAX_ALLOWED_MINTS, SESSION_KEY_SIZE};
+use crate::constants::{
+    ATTESTATION_GATE_SIZE, BLACKOUT_WINDOW_SIZE, BOND_REQUIREMENT_SIZE, CATEGORY_TOTAL_SIZE,
+    DELEGATE_BUMP_SIZE, DIGEST_TREE_DEPTH, DISCRIMINATOR_FILTER_SIZE, HEATMAP_HOURS,
+    HISTORY_TREE_DEPTH, LOCKOUT_POLICY_SIZE, MAX_ALLOWED_DISCRIMINATORS, MAX_ALLOWED_MINTS,
+    MAX_ALLOWED_PROGRAMS, MAX_ALLOWED_RECIPIENTS, MAX_BLACKOUT_WINDOWS,
+    MAX_DELEGATED_TOKEN_ACCOUNTS, MAX_DENIED_MINTS, MAX_HEATMAP_SESSIONS, MAX_PIPELINE_STEPS,
+    MAX_POLICY_RULES, MAX_PROGRAM_BUDGETS, MAX_SERVICE_SESSION_KEYS, MAX_SESSION_MINTS,
+    MAX_SPEND_CATEGORIES, PIPELINE_STEP_SIZE, POLICY_RULE_SIZE, PROGRAM_BUDGET_SIZE,
+    SAVINGS_RULE_SIZE, SECONDS_PER_DAY, SESSION_HEATMAP_SIZE, SESSION_KEY_SIZE,
+    SPEND_CATEGORY_SIZE,
+};
+use crate::errors::ErrorCode;
+use crate::policy::PolicyDocument;
+use crate::receipts::MerkleFrontier;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock::DEFAULT_MS_PER_SLOT;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::TokenAccount;
 
 // ===== ACCOUNT STRUCTURES =====
 
@@ -13,6 +29,70 @@ pub struct UserAccount {
     pub bump: u8,
     /// Optional allowlist of SPL Token mints permitted for delegated transfers. Empty = allow any
     pub allowed_mints: Vec<Pubkey>,
+    /// Optional guard program CPI'd into before every session execution; it can veto the action
+    pub guard_program: Option<Pubkey>,
+    /// Optional attestation (e.g. KYC) token gate checked on every session execution
+    pub attestation_gate: Option<AttestationGate>,
+    /// Whether transfers must be screened against the program-wide blocklist registry
+    pub enforce_blocklist: bool,
+    /// Optional bond that session operators must lock before executing
+    pub bond_requirement: Option<BondRequirement>,
+    /// Number of violation reports filed, used to derive report PDAs
+    pub violation_reports: u32,
+    /// Mints with a `SpendBudget`; transfers of these mints must charge it
+    pub budgeted_mints: Vec<Pubkey>,
+    /// Executions aggregated per `ActivityDigest` event; 0 emits one event per execution
+    pub digest_interval: u32,
+    /// Whether an `AccountStats` PDA exists and must be updated on every execution
+    pub stats_enabled: bool,
+    /// Time ranges during which every session execution is refused
+    pub blackout_windows: Vec<BlackoutWindow>,
+    /// If non-zero, executions must reference a slot hash at most this many slots old
+    pub max_slot_age: u32,
+    /// Optional rule diverting part of every outgoing transfer of a mint into savings
+    pub savings_rule: Option<SavingsRule>,
+    /// Authority-defined categories transfers may be tagged with
+    pub spend_categories: Vec<SpendCategory>,
+    /// Seconds widened session permissions wait before taking effect (0 = immediately)
+    pub widening_delay_seconds: u32,
+    /// Token accounts approved through `spl_approve_delegate` and not yet revoked
+    pub delegated_token_accounts: Vec<Pubkey>,
+    /// Number of session keys the account has space for
+    pub session_key_capacity: u16,
+    /// Bumped by `revoke_all_session_keys`; `SessionKeyAccount`s from older generations
+    /// read as revoked
+    pub session_generation: u32,
+    /// Open `SessionKeyAccount` PDAs; all must be closed before the account can be
+    pub session_key_accounts: u32,
+    /// Delegate PDA bumps per mint, cached by `spl_approve_delegate` so transfers can
+    /// skip `find_program_address`
+    pub delegate_bumps: Vec<DelegateBump>,
+    /// Allow Token-2022 mints with a permanent delegate in approvals and transfers
+    pub allow_permanent_delegate_mints: bool,
+    /// A `PolicyAccount` exists and must be passed to every session spend
+    pub has_policy: bool,
+    /// Commitment to the emergency kill phrase (zero = none), see `kill_phrase_commitment`
+    pub kill_phrase_hash: [u8; 32],
+    /// Every session execution is refused until the authority unpauses
+    pub is_paused: bool,
+    /// Optional lockout of session keys that keep failing spend checks
+    pub lockout_policy: Option<LockoutPolicy>,
+    /// Session payouts need the recipient's `RecipientConsent` for this account
+    pub require_recipient_consent: bool,
+    /// How many levels of sub-keys `delegate_session_key` may create below a granted key
+    pub max_delegation_depth: u8,
+    /// Delegated transfers need the bonded operator's Ed25519 signature over the action
+    pub require_operator_signature: bool,
+    /// Seconds a key stays stored after it expires, is revoked or is used up before
+    /// cleanup may remove it (0 = as soon as it stops being valid)
+    pub retention_seconds: u32,
+    /// Recovery key that can revoke every session key, but not create keys or move funds
+    pub guardian: Option<Pubkey>,
+    /// Programs whose custom actions are capped by a per-program spend budget
+    pub budgeted_programs: Vec<Pubkey>,
+    /// Layout version the account was written with; 0 for accounts that predate
+    /// versioning
+    pub version: u8,
 }
 
 impl UserAccount {
@@ -23,13 +103,988 @@ impl UserAccount {
         32 + // authority
         4 + (max_keys * SESSION_KEY_SIZE) + // session_keys vec
         1 + // bump
-        4 + (MAX_ALLOWED_MINTS * 32) // allowed_mints vec capacity
+        4 + (MAX_ALLOWED_MINTS * 32) + // allowed_mints vec capacity
+        1 + 32 + // guard_program
+        1 + ATTESTATION_GATE_SIZE + // attestation_gate
+        1 + // enforce_blocklist
+        1 + BOND_REQUIREMENT_SIZE + // bond_requirement
+        4 + // violation_reports
+        4 + (MAX_ALLOWED_MINTS * 32) + // budgeted_mints vec capacity
+        4 + // digest_interval
+        1 + // stats_enabled
+        4 + (MAX_BLACKOUT_WINDOWS * BLACKOUT_WINDOW_SIZE) + // blackout_windows vec capacity
+        4 + // max_slot_age
+        1 + SAVINGS_RULE_SIZE + // savings_rule
+        4 + (MAX_SPEND_CATEGORIES * SPEND_CATEGORY_SIZE) + // spend_categories vec capacity
+        4 + // widening_delay_seconds
+        4 + (MAX_DELEGATED_TOKEN_ACCOUNTS * 32) + // delegated_token_accounts vec capacity
+        2 + // session_key_capacity
+        4 + // session_generation
+        4 + // session_key_accounts
+        4 + (MAX_DELEGATED_TOKEN_ACCOUNTS * DELEGATE_BUMP_SIZE) + // delegate_bumps vec capacity
+        1 + // allow_permanent_delegate_mints
+        1 + // has_policy
+        32 + // kill_phrase_hash
+        1 + // is_paused
+        1 + LOCKOUT_POLICY_SIZE + // lockout_policy
+        1 + // require_recipient_consent
+        1 + // max_delegation_depth
+        1 + // require_operator_signature
+        4 + // retention_seconds
+        1 + 32 + // guardian
+        4 + (MAX_PROGRAM_BUDGETS * 32) + // budgeted_programs vec capacity
+        1 // version
     }
+
+    /// Refuse a token account that isn't the authority's own account of `mint`. The
+    /// delegate PDA may have been approved on other accounts too, so having it as
+    /// delegate isn't enough to make a token account the authority's.
+    pub fn check_source_token(&self, token_account: &TokenAccount, mint: &Pubkey) -> Result<()> {
+        require_keys_eq!(
+            token_account.owner,
+            self.authority,
+            ErrorCode::SourceTokenOwnerMismatch
+        );
+        require_keys_eq!(
+            token_account.mint,
+            *mint,
+            ErrorCode::SourceTokenMintMismatch
+        );
+        Ok(())
+    }
+
+    /// This account's delegate PDA for `mint` and its bump, from the cached bump when
+    /// there is one. `user_account` is this account's own address.
+    pub fn delegate_address(
+        &self,
+        user_account: &Pubkey,
+        mint: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        if let Some(cached) = self.delegate_bumps.iter().find(|d| d.mint == *mint) {
+            if let Ok(address) = Pubkey::create_program_address(
+                &[
+                    b"delegate",
+                    user_account.as_ref(),
+                    mint.as_ref(),
+                    &[cached.bump],
+                ],
+                program_id,
+            ) {
+                return (address, cached.bump);
+            }
+        }
+        Pubkey::find_program_address(
+            &[b"delegate", user_account.as_ref(), mint.as_ref()],
+            program_id,
+        )
+    }
+
+    /// Whether the account turns on a control only token transfers can check: an
+    /// attestation gate, blocklist screening, an operator bond or countersignature, or
+    /// stats or digest recording
+    pub fn has_token_only_controls(&self) -> bool {
+        self.attestation_gate.is_some()
+            || self.enforce_blocklist
+            || self.bond_requirement.is_some()
+            || self.require_operator_signature
+            || self.stats_enabled
+            || self.digest_interval > 0
+    }
+
+    /// Blackout window covering `now`, if any
+    pub fn active_blackout(&self, now: i64) -> Option<&BlackoutWindow> {
+        self.blackout_windows.iter().find(|w| w.contains(now))
+    }
+
+    /// Inline session key `pubkey`. The list is kept sorted by pubkey, so this is a binary
+    /// search; lists written before sorting fall back to a scan until their next insert.
+    pub fn find_session_key(&self, pubkey: &Pubkey) -> Option<&SessionKey> {
+        self.session_key_index(pubkey)
+            .map(|i| &self.session_keys[i])
+    }
+
+    /// How many delegations `key` is below a key the authority granted. Parents are
+    /// followed through the inline list and `key_account`, a PDA-stored key in the
+    /// chain, and the walk is bounded by how many keys that is.
+    pub fn delegation_depth(
+        &self,
+        key: &SessionKey,
+        key_account: Option<&SessionKeyAccount>,
+    ) -> usize {
+        let mut depth = 0;
+        let mut parent = key.delegated_by;
+        while let Some(pubkey) = parent {
+            depth += 1;
+            if depth > self.session_keys.len() + 1 {
+                break;
+            }
+            parent = self
+                .find_session_key(&pubkey)
+                .or_else(|| {
+                    key_account
+                        .map(|account| &account.session_key)
+                        .filter(|k| k.pubkey == pubkey)
+                })
+                .and_then(|k| k.delegated_by);
+        }
+        depth
+    }
+
+    /// Mutable counterpart of `find_session_key`
+    pub fn find_session_key_mut(&mut self, pubkey: &Pubkey) -> Option<&mut SessionKey> {
+        self.session_key_index(pubkey)
+            .map(move |i| &mut self.session_keys[i])
+    }
+
+    fn session_key_index(&self, pubkey: &Pubkey) -> Option<usize> {
+        match self.session_keys.binary_search_by_key(pubkey, |k| k.pubkey) {
+            Ok(i) => Some(i),
+            Err(_) => self.session_keys.iter().position(|k| k.pubkey == *pubkey),
+        }
+    }
+
+    /// Add an inline session key, keeping the list sorted by pubkey
+    pub fn insert_session_key(&mut self, session_key: SessionKey) {
+        self.session_keys.push(session_key);
+        self.sort_session_keys();
+    }
+
+    /// Restore pubkey order after a key's pubkey changed. Also sorts lists written
+    /// before keys were kept in order.
+    pub fn sort_session_keys(&mut self) {
+        self.session_keys.sort_unstable_by_key(|k| k.pubkey);
+    }
+
+    /// Session key `pubkey`, read from its own `SessionKeyAccount` when one is passed and
+    /// from the inline list otherwise
+    pub fn session_key<'a>(
+        &'a self,
+        key_account: Option<&'a SessionKeyAccount>,
+        pubkey: &Pubkey,
+    ) -> Result<&'a SessionKey> {
+        match key_account {
+            Some(account) => {
+                require!(
+                    account.generation == self.session_generation,
+                    ErrorCode::SessionKeyRevoked
+                );
+                Ok(&account.session_key)
+            }
+            None => self
+                .find_session_key(pubkey)
+                .ok_or_else(|| error!(ErrorCode::SessionKeyNotFound)),
+        }
+    }
+
+    /// Mutable counterpart of `session_key`, for recording usage
+    pub fn session_key_mut<'a>(
+        &'a mut self,
+        key_account: Option<&'a mut SessionKeyAccount>,
+        pubkey: &Pubkey,
+    ) -> Result<&'a mut SessionKey> {
+        match key_account {
+            Some(account) => {
+                require!(
+                    account.generation == self.session_generation,
+                    ErrorCode::SessionKeyRevoked
+                );
+                Ok(&mut account.session_key)
+            }
+            None => self
+                .find_session_key_mut(pubkey)
+                .ok_or_else(|| error!(ErrorCode::SessionKeyNotFound)),
+        }
+    }
+}
+
+/// A session key stored in its own PDA instead of inline in `UserAccount`, so an account
+/// isn't limited by its inline capacity and each key can be closed for its rent
+#[account]
+pub struct SessionKeyAccount {
+    /// User account the key acts for
+    pub user_account: Pubkey,
+    /// `UserAccount::session_generation` when the key was created
+    pub generation: u32,
+    pub session_key: SessionKey,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl SessionKeyAccount {
+    pub const SEED_PREFIX: &'static [u8] = b"session";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        4 + // generation
+        SESSION_KEY_SIZE + // session_key
+        1; // bump
+}
+
+/// Hash of a dormant `UserAccount`'s state, kept while the account itself is closed so
+/// the authority stops paying rent for it. `restore_user_account` recreates the account
+/// from a payload matching `state_hash`.
+#[account]
+pub struct ArchivedUserAccount {
+    pub authority: Pubkey,
+    /// SHA-256 of the Borsh-serialized `UserAccount`, without its discriminator
+    pub state_hash: [u8; 32],
+    /// Capacity the account is recreated with
+    pub session_key_capacity: u16,
+    pub archived_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ArchivedUserAccount {
+    pub const SEED_PREFIX: &'static [u8] = b"archive";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // authority
+        32 + // state_hash
+        2 + // session_key_capacity
+        8 + // archived_at
+        1; // bump
+}
+
+/// A recipient's standing acceptance of session payouts from one user account, for
+/// accounts that set `require_recipient_consent`
+#[account]
+pub struct RecipientConsent {
+    pub user_account: Pubkey,
+    /// Wallet accepting payouts; for token transfers, the owner of the token account
+    pub recipient: Pubkey,
+    pub accepted_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl RecipientConsent {
+    pub const SEED_PREFIX: &'static [u8] = b"consent";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        32 + // recipient
+        8 + // accepted_at
+        1; // bump
+}
+
+/// A build hash the upgrade authority vouches for, published with `publish_build_hash`.
+/// One record per hash, so every verified release stays on record.
+#[account]
+pub struct PublishedBuild {
+    /// SHA-256 of the program's executable, as `solana-verify get-executable-hash` prints it
+    pub build_hash: [u8; 32],
+    /// Git commit the build was made from
+    pub source_commit: [u8; 20],
+    /// `ProgramData` deployment slot when the hash was published
+    pub deployment_slot: u64,
+    pub published_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl PublishedBuild {
+    pub const SEED_PREFIX: &'static [u8] = b"build";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // build_hash
+        20 + // source_commit
+        8 + // deployment_slot
+        8 + // published_at
+        1; // bump
+}
+
+/// Declarative rules every session spend of a user account is checked against, on top
+/// of each key's own permissions. See the `policy` module for the interpreter.
+#[account]
+pub struct PolicyAccount {
+    /// User account the policy belongs to
+    pub user_account: Pubkey,
+    pub document: PolicyDocument,
+    pub updated_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl PolicyAccount {
+    pub const SEED_PREFIX: &'static [u8] = b"policy";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        1 + 4 + (MAX_POLICY_RULES * POLICY_RULE_SIZE) + // document
+        8 + // updated_at
+        1; // bump
+}
+
+/// Account-level spending budget for one mint, shared by all session keys.
+///
+/// Spend is tracked here rather than on the `UserAccount` or per session so that every
+/// transfer of the mint takes a write lock on this one PDA: the runtime then serializes
+/// concurrent executions from different sessions, and each sees the previous one's spend.
+#[account]
+pub struct SpendBudget {
+    /// User account the budget belongs to
+    pub user_account: Pubkey,
+    /// Mint the budget is denominated in
+    pub mint: Pubkey,
+    /// Maximum amount (base units) that can be spent per window
+    pub limit: u64,
+    /// Window length in seconds; 0 makes the limit a lifetime cap
+    pub window_seconds: i64,
+    /// Unix timestamp the current window started at
+    pub window_start: i64,
+    /// Amount spent in the current window
+    pub spent: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Caps on the mint's value routed into individual programs by custom actions,
+    /// over the same window
+    pub program_budgets: Vec<ProgramBudget>,
+}
+
+impl SpendBudget {
+    pub const SEED_PREFIX: &'static [u8] = b"spend_budget";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        32 + // mint
+        8 + // limit
+        8 + // window_seconds
+        8 + // window_start
+        8 + // spent
+        1 + // bump
+        4 + (MAX_PROGRAM_BUDGETS * PROGRAM_BUDGET_SIZE); // program_budgets vec capacity
+
+    /// Charge `amount` routed into `program` against its cap
+    pub fn charge_program(&mut self, program: &Pubkey, amount: u64, clock: &Clock) -> Result<()> {
+        let window_seconds = self.window_seconds;
+        let budget = self
+            .program_budgets
+            .iter_mut()
+            .find(|b| b.program == *program)
+            .ok_or(ErrorCode::ProgramBudgetMissing)?;
+        if window_seconds > 0 {
+            roll_window(
+                &mut budget.window_start,
+                &mut budget.spent,
+                window_seconds,
+                clock.unix_timestamp,
+            );
+        }
+
+        let spent = budget
+            .spent
+            .checked_add(amount)
+            .filter(|spent| *spent <= budget.limit)
+            .ok_or(ErrorCode::ProgramBudgetExceeded)?;
+        budget.spent = spent;
+        Ok(())
+    }
+
+    /// Roll the window forward if it has elapsed, then charge `amount` against the limit
+    pub fn charge(&mut self, amount: u64, clock: &Clock) -> Result<()> {
+        if self.window_seconds > 0 {
+            roll_window(
+                &mut self.window_start,
+                &mut self.spent,
+                self.window_seconds,
+                clock.unix_timestamp,
+            );
+        }
+
+        let spent = self
+            .spent
+            .checked_add(amount)
+            .filter(|spent| *spent <= self.limit)
+            .ok_or(ErrorCode::SpendBudgetExceeded)?;
+        self.spent = spent;
+        Ok(())
+    }
+
+    /// Amount still available in the current window
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.spent)
+    }
+}
+
+/// Start a new window once the current one has elapsed. Windows stay aligned to the
+/// original start so they can't be shifted by timing spends.
+pub fn roll_window(window_start: &mut i64, spent: &mut u64, window_seconds: i64, now: i64) {
+    if now >= window_start.saturating_add(window_seconds) {
+        let elapsed = now.saturating_sub(*window_start);
+        *window_start += elapsed - elapsed % window_seconds;
+        *spent = 0;
+    }
+}
+
+/// Running aggregate of executions since the last `ActivityDigest` event
+#[account]
+pub struct ActivityDigest {
+    /// User account the digest belongs to
+    pub user_account: Pubkey,
+    /// Number of digests emitted so far
+    pub sequence: u64,
+    /// Sum of amounts executed in the current batch
+    pub total_amount: u128,
+    /// Slot of the first execution in the current batch
+    pub first_slot: u64,
+    /// Merkle tree over the receipts of the current batch
+    pub receipts: MerkleFrontier<DIGEST_TREE_DEPTH>,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ActivityDigest {
+    pub const SEED_PREFIX: &'static [u8] = b"activity_digest";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        8 + // sequence
+        16 + // total_amount
+        8 + // first_slot
+        MerkleFrontier::<DIGEST_TREE_DEPTH>::SIZE + // receipts
+        1; // bump
+
+    /// Start a new batch
+    pub fn reset(&mut self) {
+        self.total_amount = 0;
+        self.first_slot = 0;
+        self.receipts = MerkleFrontier::default();
+    }
+}
+
+/// Lifetime execution history of a user account. `history_root` commits to the
+/// `receipt_leaf` of every execution in order, so an off-chain prover can show any past
+/// action to a third party with a Merkle proof instead of a history replay.
+#[account]
+pub struct AccountStats {
+    /// User account the stats belong to
+    pub user_account: Pubkey,
+    /// Sum of all executed amounts
+    pub total_volume: u128,
+    /// Unix timestamp of the latest execution
+    pub last_execution_at: i64,
+    /// Root of `history` after the latest execution
+    pub history_root: [u8; 32],
+    /// Append-only tree over every execution receipt; `history.count` is the execution count
+    pub history: MerkleFrontier<HISTORY_TREE_DEPTH>,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Volume per spend category, for the first `MAX_SPEND_CATEGORIES` categories seen
+    pub category_totals: Vec<CategoryTotal>,
+    /// Hourly execution counts per session key, for the first `MAX_HEATMAP_SESSIONS` keys seen
+    pub session_heatmaps: Vec<SessionHeatmap>,
+}
+
+impl AccountStats {
+    pub const SEED_PREFIX: &'static [u8] = b"stats";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        16 + // total_volume
+        8 + // last_execution_at
+        32 + // history_root
+        MerkleFrontier::<HISTORY_TREE_DEPTH>::SIZE + // history
+        1 + // bump
+        4 + (MAX_SPEND_CATEGORIES * CATEGORY_TOTAL_SIZE) + // category_totals vec capacity
+        4 + (MAX_HEATMAP_SESSIONS * SESSION_HEATMAP_SIZE); // session_heatmaps vec capacity
+
+    /// Append an execution receipt and refresh the published root
+    pub fn record(
+        &mut self,
+        leaf: [u8; 32],
+        session_key: &Pubkey,
+        amount: u64,
+        category: Option<u16>,
+        clock: &Clock,
+    ) -> Result<()> {
+        require!(!self.history.is_full(), ErrorCode::HistoryFull);
+        self.history.append(leaf);
+        self.history_root = self.history.root();
+        self.total_volume += amount as u128;
+        self.last_execution_at = clock.unix_timestamp;
+        if let Some(category) = category {
+            self.record_category(category, amount);
+        }
+        self.record_hour(session_key, clock.unix_timestamp);
+        Ok(())
+    }
+
+    fn record_hour(&mut self, session_key: &Pubkey, now: i64) {
+        let hour = now.div_euclid(3600);
+        if let Some(heatmap) = self
+            .session_heatmaps
+            .iter_mut()
+            .find(|h| h.session_key == *session_key)
+        {
+            heatmap.record(hour);
+        } else if self.session_heatmaps.len() < MAX_HEATMAP_SESSIONS {
+            let mut heatmap = SessionHeatmap {
+                session_key: *session_key,
+                last_hour: hour,
+                hours: [0; HEATMAP_HOURS],
+            };
+            heatmap.record(hour);
+            self.session_heatmaps.push(heatmap);
+        } else {
+            msg!("Session {} not tracked: heatmap table full", session_key);
+        }
+    }
+
+    fn record_category(&mut self, category: u16, amount: u64) {
+        if let Some(total) = self
+            .category_totals
+            .iter_mut()
+            .find(|t| t.category == category)
+        {
+            total.volume += amount as u128;
+            total.count += 1;
+        } else if self.category_totals.len() < MAX_SPEND_CATEGORIES {
+            self.category_totals.push(CategoryTotal {
+                category,
+                volume: amount as u128,
+                count: 1,
+            });
+        } else {
+            msg!("Category {} not tracked: stats table full", category);
+        }
+    }
+}
+
+/// Lamports set aside for session operating costs, kept apart from the user's funds.
+///
+/// The tank has no withdrawal path: its balance above the rent reserve only ever leaves
+/// as fee reimbursements to session signers.
+#[account]
+pub struct GasTank {
+    /// User account the tank belongs to
+    pub user_account: Pubkey,
+    /// Lamports reimbursed to the session signer per execution
+    pub fee_per_execution: u64,
+    /// Lamports ever added with `top_up_gas_tank`
+    pub total_topped_up: u64,
+    /// Lamports ever paid out as reimbursements
+    pub total_reimbursed: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl GasTank {
+    pub const SEED_PREFIX: &'static [u8] = b"gas_tank";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        8 + // fee_per_execution
+        8 + // total_topped_up
+        8 + // total_reimbursed
+        1; // bump
+}
+
+/// Program-wide registry of sanctioned addresses, maintained by the program admin
+#[account]
+pub struct BlocklistRegistry {
+    /// Admin allowed to edit the registry (the program upgrade authority at creation)
+    pub admin: Pubkey,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Blocked addresses, kept sorted for binary search
+    pub addresses: Vec<Pubkey>,
+}
+
+impl BlocklistRegistry {
+    pub const SEED_PREFIX: &'static [u8] = b"blocklist";
+
+    pub fn space(max_entries: usize) -> usize {
+        8 + // discriminator
+        32 + // admin
+        1 + // bump
+        4 + (max_entries * 32) // addresses vec
+    }
+
+    pub fn is_blocked(&self, address: &Pubkey) -> bool {
+        self.addresses.binary_search(address).is_ok()
+    }
+}
+
+/// Program-wide settings maintained by the program admin: a pause switch for incidents
+/// and limits that apply to every account
+#[account]
+pub struct ProgramConfig {
+    /// Admin allowed to change the config (the program upgrade authority at creation)
+    pub admin: Pubkey,
+    /// Refuses every session execution while set
+    pub is_paused: bool,
+    /// Most a single session transfer may move, in base units or lamports (0 = no cap)
+    pub max_transfer_amount: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Mints no account may delegate or move, e.g. known scam tokens; kept sorted for
+    /// binary search
+    pub denied_mints: Vec<Pubkey>,
+    /// Protocol fee charged on top of each session transfer, in basis points (0 = none)
+    pub fee_bps: u16,
+}
+
+impl ProgramConfig {
+    pub const SEED_PREFIX: &'static [u8] = b"program_config";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // admin
+        1 + // is_paused
+        8 + // max_transfer_amount
+        1 + // bump
+        4 + (32 * MAX_DENIED_MINTS) + // denied_mints
+        2; // fee_bps
+
+    /// Config passed at its PDA address, or none while it hasn't been initialized
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::try_deserialize(&mut &info.data.borrow()[..])?))
+    }
+
+    /// Refuse a session execution moving `amount` while paused or above the global cap
+    pub fn check_execution(&self, amount: u64) -> Result<()> {
+        require!(!self.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            self.max_transfer_amount == 0 || amount <= self.max_transfer_amount,
+            ErrorCode::GlobalLimitExceeded
+        );
+        Ok(())
+    }
+
+    /// `check_execution` against the config at `info`; nothing applies before it exists
+    pub fn enforce(info: &AccountInfo, amount: u64) -> Result<()> {
+        match Self::load(info)? {
+            Some(config) => config.check_execution(amount),
+            None => Ok(()),
+        }
+    }
+
+    pub fn denies_mint(&self, mint: &Pubkey) -> bool {
+        self.denied_mints.binary_search(mint).is_ok()
+    }
+
+    /// Refuse `mint` if the config at `info` denies it
+    pub fn enforce_mint(info: &AccountInfo, mint: &Pubkey) -> Result<()> {
+        if let Some(config) = Self::load(info)? {
+            require!(!config.denies_mint(mint), ErrorCode::MintDenied);
+        }
+        Ok(())
+    }
+
+    /// Protocol fee owed on a transfer of `amount`, rounded down
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
+
+    /// `fee_for` against the config at `info`; no fee is charged before it exists
+    pub fn protocol_fee(info: &AccountInfo, amount: u64) -> Result<u64> {
+        Ok(Self::load(info)?.map_or(0, |config| config.fee_for(amount)))
+    }
+}
+
+/// Program-owned PDA collecting protocol fees: lamports directly, tokens in token
+/// accounts it owns. Only the config admin can withdraw them.
+#[account]
+pub struct Treasury {
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub const SEED_PREFIX: &'static [u8] = b"treasury";
+
+    pub const SPACE: usize = 8 + // discriminator
+        1; // bump
+
+    /// Treasury PDA of `program_id`
+    pub fn address(program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX], program_id).0
+    }
+}
+
+/// Collateral locked by a session operator, slashable by the account authority
+#[account]
+pub struct OperatorBond {
+    /// User account the bonded session belongs to
+    pub user_account: Pubkey,
+    /// Session key the bond backs
+    pub session_key: Pubkey,
+    /// Wallet that posted the bond and receives it back on withdrawal
+    pub operator: Pubkey,
+    /// Bonded SPL mint, or None for SOL held as lamports on this PDA
+    pub mint: Option<Pubkey>,
+    /// Amount currently bonded (excluding rent)
+    pub amount: u64,
+    /// Total amount slashed so far
+    pub slashed_amount: u64,
+    /// Unix timestamp when the bond was posted
+    pub posted_at: i64,
+    /// Unix timestamp when the operator asked for release (0 = not requested)
+    pub release_requested_at: i64,
+    /// Seconds after a release request during which violations can still be reported
+    pub dispute_window_seconds: i64,
+    /// Violation reports against this bond that are not yet resolved
+    pub open_disputes: u32,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl OperatorBond {
+    pub const SEED_PREFIX: &'static [u8] = b"bond";
+    pub const VAULT_SEED_PREFIX: &'static [u8] = b"bond_vault";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        32 + // session_key
+        32 + // operator
+        33 + // mint
+        8 + // amount
+        8 + // slashed_amount
+        8 + // posted_at
+        8 + // release_requested_at
+        8 + // dispute_window_seconds
+        4 + // open_disputes
+        1; // bump
+
+    /// Whether the dispute window has elapsed after a release request
+    pub fn is_releasable(&self, clock: &Clock) -> bool {
+        self.release_requested_at > 0
+            && clock.unix_timestamp >= self.release_requested_at + self.dispute_window_seconds
+    }
+}
+
+/// A filed complaint that a session key misbehaved, resolved after a timelock
+#[account]
+pub struct ViolationReport {
+    /// User account the offending session belongs to
+    pub user_account: Pubkey,
+    /// Session key accused of the violation
+    pub session_key: Pubkey,
+    /// Who filed the report
+    pub reporter: Pubkey,
+    /// Signature of the offending transaction
+    pub evidence: [u8; 64],
+    /// Sequence number of the report within the user account
+    pub index: u32,
+    /// Unix timestamp when the report was filed
+    pub reported_at: i64,
+    /// Earliest unix timestamp at which the dispute can be resolved
+    pub resolvable_at: i64,
+    /// Unix timestamp of resolution (0 while pending)
+    pub resolved_at: i64,
+    /// Current outcome
+    pub status: DisputeStatus,
+    /// Whether an upheld report has already been used to slash a bond
+    pub slash_applied: bool,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ViolationReport {
+    pub const SEED_PREFIX: &'static [u8] = b"violation";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        32 + // session_key
+        32 + // reporter
+        64 + // evidence
+        4 + // index
+        8 + // reported_at
+        8 + // resolvable_at
+        8 + // resolved_at
+        1 + // status
+        1 + // slash_applied
+        1; // bump
+}
+
+/// Public track record of a session operator, written only by this program
+#[account]
+pub struct OperatorReputation {
+    /// Operator identity (the wallet that posts bonds)
+    pub operator: Pubkey,
+    /// Number of bonded sessions the operator has taken on
+    pub accounts_served: u64,
+    /// Total amount moved by the operator's bonded sessions
+    pub total_volume: u128,
+    /// Violation reports filed against the operator's sessions
+    pub violations_reported: u32,
+    /// Violation reports upheld against the operator's sessions
+    pub violations_upheld: u32,
+    /// Total bond slashed from the operator
+    pub total_slashed: u64,
+    /// Bonded sessions that ended with the bond withdrawn
+    pub sessions_completed: u32,
+    /// Sum of completed session durations (bond posted until release requested)
+    pub total_session_seconds: u64,
+    /// Unix timestamp of the last update
+    pub last_updated: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl OperatorReputation {
+    pub const SEED_PREFIX: &'static [u8] = b"reputation";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // operator
+        8 + // accounts_served
+        16 + // total_volume
+        4 + // violations_reported
+        4 + // violations_upheld
+        8 + // total_slashed
+        4 + // sessions_completed
+        8 + // total_session_seconds
+        8 + // last_updated
+        1; // bump
+
+    pub fn average_session_seconds(&self) -> u64 {
+        self.total_session_seconds
+            .checked_div(self.sessions_completed as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Public registry entry describing a well-known operator (bot, relayer, dApp)
+#[account]
+pub struct ServiceEntry {
+    /// Operator that owns the entry
+    pub operator: Pubkey,
+    /// Display name, UTF-8, zero padded
+    pub name: [u8; 32],
+    /// Hash of the service URL, so wallets can match it against the requesting origin
+    pub url_hash: [u8; 32],
+    /// Session keys the operator uses when acting for users
+    pub session_keys: Vec<Pubkey>,
+    /// Set by the program admin once the operator's identity has been checked
+    pub verified: bool,
+    /// Unix timestamp when the entry was registered
+    pub registered_at: i64,
+    /// Unix timestamp of the last update
+    pub updated_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ServiceEntry {
+    pub const SEED_PREFIX: &'static [u8] = b"service";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // operator
+        32 + // name
+        32 + // url_hash
+        4 + (MAX_SERVICE_SESSION_KEYS * 32) + // session_keys vec
+        1 + // verified
+        8 + // registered_at
+        8 + // updated_at
+        1; // bump
+}
+
+/// Recommended permission preset published by a registered service
+#[account]
+pub struct ServicePreset {
+    /// Service registry entry the preset belongs to
+    pub service: Pubkey,
+    /// Operator owning the service
+    pub operator: Pubkey,
+    /// Identifier of the preset within the service
+    pub preset_id: u16,
+    /// Permissions granted to sessions created from this preset
+    pub permissions: SessionPermissions,
+    /// Longest session the service asks for (0 = no cap)
+    pub max_duration_seconds: i64,
+    /// Incremented on every update
+    pub version: u32,
+    /// Unix timestamp of the last update
+    pub updated_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ServicePreset {
+    pub const SEED_PREFIX: &'static [u8] = b"preset";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // service
+        32 + // operator
+        2 + // preset_id
+        42 + // permissions
+        8 + // max_duration_seconds
+        4 + // version
+        8 + // updated_at
+        1; // bump
+}
+
+/// Session key grant recorded ahead of time, added to the account once `activate_at` passes
+#[account]
+pub struct ScheduledSession {
+    /// User account the grant belongs to
+    pub user_account: Pubkey,
+    /// Arguments the session key will be created with
+    pub args: CreateSessionKeyArgs,
+    /// Unix timestamp from which anyone may activate the grant
+    pub activate_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ScheduledSession {
+    pub const SEED_PREFIX: &'static [u8] = b"scheduled_session";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        CreateSessionKeyArgs::SIZE + // args
+        8 + // activate_at
+        1; // bump
+}
+
+/// Authority-defined sequence of token moves a session key may run as one execution,
+/// e.g. a delegated transfer into a swap into a final recipient. Each step carries its
+/// own limit, so complex payouts don't need `can_execute_custom`.
+#[account]
+pub struct PayoutPipeline {
+    /// User account the pipeline belongs to
+    pub user_account: Pubkey,
+    /// Identifier of the pipeline within the account
+    pub pipeline_id: u16,
+    /// Steps run in order
+    pub steps: Vec<PipelineStep>,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl PayoutPipeline {
+    pub const SEED_PREFIX: &'static [u8] = b"pipeline";
+
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // user_account
+        2 + // pipeline_id
+        4 + (MAX_PIPELINE_STEPS * PIPELINE_STEP_SIZE) + // steps vec capacity
+        1; // bump
 }
 
 // ===== DATA STRUCTURES =====
 
-/// Expiration type for session keys - either time-based or block-height-based
+/// Why a session key was revoked, for indexers to tell emergencies from routine rotation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum RevocationReason {
+    /// The key or the device holding it may be in someone else's hands
+    Compromised,
+    /// Revoked as part of tidying up a key that had run its course
+    Expired,
+    /// Replaced by a new key
+    Rotated,
+    /// Revoked by the user with no further detail
+    Manual,
+    /// Application-defined reason code
+    Other(u8),
+}
+
+/// Single-bound expiry selector taken by `create_session_key` (v1). Session keys store
+/// an optional time and slot bound instead; see `SessionKey::expires_at_time`.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
 pub enum ExpirationType {
     /// Expires at a specific Unix timestamp
@@ -38,40 +1093,455 @@ pub enum ExpirationType {
     BlockHeight,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+impl ExpirationType {
+    /// Map a v1 `(expires_at, expiration_type)` pair onto `(expires_at_time, expires_at_slot)`
+    pub fn bounds(self, expires_at: i64) -> (Option<i64>, Option<i64>) {
+        match self {
+            ExpirationType::Time => (Some(expires_at), None),
+            ExpirationType::BlockHeight => (None, Some(expires_at)),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct SessionKey {
     /// Public key of the session key
     pub pubkey: Pubkey,
     /// Unix timestamp when the key was created
     pub created_at: i64,
-    /// Expiration value (either timestamp or block height based on expiration_type)
-    pub expires_at: i64,
-    /// Type of expiration (Time or BlockHeight)
-    pub expiration_type: ExpirationType,
+    /// Unix timestamp the key expires at, if time-bounded
+    pub expires_at_time: Option<i64>,
+    /// Slot the key expires at, if slot-bounded. With both bounds set, whichever comes
+    /// first wins.
+    pub expires_at_slot: Option<i64>,
     /// Permissions granted to this session key
     pub permissions: SessionPermissions,
     /// Whether the key has been revoked
     pub is_revoked: bool,
     /// Optional label for identifying the key
     pub label: [u8; 32],
+    /// Service preset the key was granted from, if any
+    pub preset: Option<Pubkey>,
+    /// Version of the preset at grant time, to surface later changes
+    pub preset_version: u32,
+    /// Hash of the human-readable grant description the user consented to (zero if none)
+    pub terms_hash: [u8; 32],
+    /// Total amount transferred with this key, capped by `permissions.max_transfer_amount`
+    pub total_spent: u64,
+    /// Unix timestamp the current spend window started at
+    pub spend_window_start: i64,
+    /// Amount transferred in the current spend window, capped by `permissions.daily_limit`
+    pub spent_in_window: u64,
+    /// Unix timestamp the current use window started at
+    pub use_window_start: i64,
+    /// Executions in the current use window, capped by `permissions.max_uses_per_window`
+    pub uses_in_window: u64,
+    /// Total executions allowed over the key's lifetime (0 = unlimited, 1 = single-use)
+    pub max_uses: u32,
+    /// Executions performed with this key
+    pub use_count: u32,
+    /// Unix timestamp before which the key can't execute (0 = active immediately)
+    pub valid_from: i64,
+    /// Wallets or token accounts the key may pay (empty = any recipient)
+    pub allowed_recipients: Vec<Pubkey>,
+    /// Mints the key may move, on top of the account's `allowed_mints` (empty = no
+    /// further restriction)
+    pub allowed_mints: Vec<Pubkey>,
+    /// Programs the key may call with `execute_custom` (empty = none)
+    pub allowed_programs: Vec<Pubkey>,
+    /// Instructions the key may send with `execute_custom`, per program (a program
+    /// without filters accepts any instruction)
+    pub allowed_discriminators: Vec<DiscriminatorFilter>,
+    /// Permissions in force until `widening_active_at`, set when an update widened them
+    pub prior_permissions: Option<SessionPermissions>,
+    /// Unix timestamp the current permissions take effect at (0 = already in effect)
+    pub widening_active_at: i64,
+    /// Paused by the authority; unlike revocation, `resume_session_key` undoes it
+    pub is_suspended: bool,
+    /// Reason given when the key was revoked (none for keys revoked with
+    /// `revoke_all_session_keys`)
+    pub revocation_reason: Option<RevocationReason>,
+    /// Spends refused in the current failure window, counted toward the account's
+    /// `lockout_policy`
+    pub failed_attempts: u8,
+    /// Unix timestamp the current failure window started at
+    pub failure_window_start: i64,
+    /// Unix timestamp before which the key can't execute after a lockout (0 = never locked)
+    pub locked_until: i64,
+    /// Session key that created this one with `delegate_session_key` (none if the
+    /// authority did)
+    pub delegated_by: Option<Pubkey>,
+    /// Unix timestamp of the key's last successful spend or execution (0 = never used)
+    pub last_used_at: i64,
+    /// Seconds of inactivity that expire the key (0 = fixed expiry). Each use moves
+    /// `expires_at_time` to this far ahead, up to `max_expires_at`.
+    pub idle_timeout: u32,
+    /// Latest `expires_at_time` a sliding key can reach (0 = not sliding)
+    pub max_expires_at: i64,
+    /// Unix timestamp the key was revoked at (0 = not revoked)
+    pub revoked_at: i64,
+    /// Dapp or program the key is bound to; executing instructions must name it
+    /// (default pubkey = unscoped)
+    pub scope: Pubkey,
+    /// SHA-256 of the dapp origin the key was granted from (zero if none)
+    pub domain_hash: [u8; 32],
 }
 
 impl SessionKey {
-    /// Check if the session key is expired based on its expiration type
+    /// Check if the session key has passed either of its expiry bounds
     pub fn is_expired(&self, clock: &Clock) -> bool {
-        match self.expiration_type {
-            ExpirationType::Time => self.expires_at <= clock.unix_timestamp,
-            ExpirationType::BlockHeight => self.expires_at <= clock.slot as i64,
+        self.expires_at_time
+            .is_some_and(|time| time <= clock.unix_timestamp)
+            || self
+                .expires_at_slot
+                .is_some_and(|slot| slot <= clock.slot as i64)
+    }
+
+    /// Validate an expiry pair: at least one bound must be set and each in the future
+    pub fn check_expiry(
+        expires_at_time: Option<i64>,
+        expires_at_slot: Option<i64>,
+        clock: &Clock,
+    ) -> Result<()> {
+        require!(
+            expires_at_time.is_some() || expires_at_slot.is_some(),
+            ErrorCode::InvalidExpiry
+        );
+        if let Some(time) = expires_at_time {
+            require!(time > clock.unix_timestamp, ErrorCode::InvalidExpiry);
         }
+        if let Some(slot) = expires_at_slot {
+            require!(slot > clock.slot as i64, ErrorCode::InvalidExpiry);
+        }
+        Ok(())
+    }
+
+    /// Whether the key can still be valid after `horizon`. A slot bound is converted
+    /// to time at the default 400 ms slot, so keys bounded only by slot are estimated.
+    pub fn expires_after(&self, horizon: i64, clock: &Clock) -> bool {
+        let slot_time = self.expires_at_slot.map(|slot| {
+            let slots_left = slot.saturating_sub(clock.slot as i64);
+            clock
+                .unix_timestamp
+                .saturating_add(slots_left.saturating_mul(DEFAULT_MS_PER_SLOT as i64) / 1000)
+        });
+        [self.latest_expires_at_time(), slot_time]
+            .into_iter()
+            .flatten()
+            .min()
+            .is_none_or(|expiry| expiry > horizon)
     }
 
-    /// Check if the session key is valid (not revoked and not expired)
+    /// Check if the key has used up its `max_uses`
+    pub fn is_exhausted(&self) -> bool {
+        self.max_uses > 0 && self.use_count >= self.max_uses
+    }
+
+    /// Check if the key may pay `owner`'s `token_account`; either may be listed
+    pub fn allows_recipient(&self, owner: &Pubkey, token_account: &Pubkey) -> bool {
+        self.allowed_recipients.is_empty()
+            || self
+                .allowed_recipients
+                .iter()
+                .any(|r| r == owner || r == token_account)
+    }
+
+    /// Check if the key may move `mint`: it must pass both the account-level list and
+    /// the key's own list, where an empty list allows any mint
+    pub fn allows_mint(&self, account_mints: &[Pubkey], mint: &Pubkey) -> bool {
+        (account_mints.is_empty() || account_mints.contains(mint))
+            && (self.allowed_mints.is_empty() || self.allowed_mints.contains(mint))
+    }
+
+    /// Check if the key may execute for `scope`: a scoped key only for its own dapp
+    pub fn allows_scope(&self, scope: Option<&Pubkey>) -> bool {
+        self.scope == Pubkey::default() || scope == Some(&self.scope)
+    }
+
+    /// Check if `data` may be sent to `program`: it must match one of the filters for
+    /// that program, if the key has any
+    pub fn allows_discriminator(&self, program: &Pubkey, data: &[u8]) -> bool {
+        let mut filters = self
+            .allowed_discriminators
+            .iter()
+            .filter(|f| f.program == *program)
+            .peekable();
+        filters.peek().is_none() || filters.any(|f| f.matches(data))
+    }
+
+    /// Permissions that apply at `now`: the prior set while a widening is still delayed
+    pub fn permissions_at(&self, now: i64) -> SessionPermissions {
+        match self.prior_permissions {
+            Some(prior) if now < self.widening_active_at => prior,
+            _ => self.permissions,
+        }
+    }
+
+    /// Revoke the key at `now`, recording why
+    pub fn revoke(&mut self, reason: Option<RevocationReason>, now: i64) {
+        self.is_revoked = true;
+        self.revocation_reason = reason;
+        self.revoked_at = now;
+    }
+
+    /// Unix timestamp the key stopped being usable, if it has: the earliest of its
+    /// revocation, expiry (a slot bound is converted at the default 400 ms slot) and the
+    /// execution that used up `max_uses`
+    pub fn ended_at(&self, clock: &Clock) -> Option<i64> {
+        let now = clock.unix_timestamp;
+        let slot_expiry = self
+            .expires_at_slot
+            .filter(|&slot| slot <= clock.slot as i64)
+            .map(|slot| {
+                let slots_ago = (clock.slot as i64).saturating_sub(slot);
+                now.saturating_sub(slots_ago.saturating_mul(DEFAULT_MS_PER_SLOT as i64) / 1000)
+            });
+        [
+            self.is_revoked.then_some(self.revoked_at),
+            self.expires_at_time.filter(|&time| time <= now),
+            slot_expiry,
+            self.is_exhausted().then_some(self.last_used_at),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// Check if the session key is valid (not revoked, expired or exhausted)
     pub fn is_valid(&self, clock: &Clock) -> bool {
-        !self.is_revoked && !self.is_expired(clock)
+        !self.is_revoked && !self.is_expired(clock) && !self.is_exhausted()
+    }
+
+    /// Check if `child` stays within this key at `now`: no wider permissions, no later
+    /// expiry or earlier `valid_from`, this key's scope if it has one, and recipient,
+    /// mint and program lists inside this key's. Lifetime caps and use counts are
+    /// checked against what's left separately.
+    pub fn covers(&self, child: &SessionKey, now: i64) -> bool {
+        let within = |bound: Option<i64>, child_bound: Option<i64>| match bound {
+            Some(bound) => child_bound.is_some_and(|c| c <= bound),
+            None => true,
+        };
+        // An empty recipient or mint list allows any, so the child must then list its own
+        let subset = |list: &[Pubkey], child_list: &[Pubkey]| {
+            list.is_empty()
+                || (!child_list.is_empty() && child_list.iter().all(|k| list.contains(k)))
+        };
+        let filters_within = child.allowed_programs.iter().all(|program| {
+            let filters: Vec<_> = self
+                .allowed_discriminators
+                .iter()
+                .filter(|f| f.program == *program)
+                .collect();
+            let child_filters: Vec<_> = child
+                .allowed_discriminators
+                .iter()
+                .filter(|f| f.program == *program)
+                .collect();
+            filters.is_empty()
+                || (!child_filters.is_empty()
+                    && child_filters.iter().all(|c| {
+                        filters
+                            .iter()
+                            .any(|f| f.discriminator == c.discriminator && f.len == c.len)
+                    }))
+        });
+
+        !child.permissions.widens(&self.permissions_at(now))
+            && within(self.expires_at_time, child.latest_expires_at_time())
+            && within(self.expires_at_slot, child.expires_at_slot)
+            && child.valid_from >= self.valid_from
+            && (self.scope == Pubkey::default() || child.scope == self.scope)
+            && subset(&self.allowed_recipients, &child.allowed_recipients)
+            && subset(&self.allowed_mints, &child.allowed_mints)
+            && child
+                .allowed_programs
+                .iter()
+                .all(|p| self.allowed_programs.contains(p))
+            && filters_within
+    }
+
+    /// Check if a lockout keeps the key from executing at `now`
+    pub fn is_locked(&self, now: i64) -> bool {
+        self.locked_until > now
+    }
+
+    /// Validate a spend of `amount` against the key: validity, `can_transfer`, the
+    /// lifetime and per-window caps and the use limit. Returns the counters to record.
+    pub fn check_spend(&self, amount: u64, clock: &Clock) -> Result<SpendCharge> {
+        let permissions = self.permissions_at(clock.unix_timestamp);
+
+        require!(!self.is_revoked, ErrorCode::SessionKeyRevoked);
+        require!(!self.is_suspended, ErrorCode::SessionKeySuspended);
+        require!(!self.is_exhausted(), ErrorCode::SessionKeyExhausted);
+        require!(
+            self.valid_from <= clock.unix_timestamp,
+            ErrorCode::SessionKeyNotYetValid
+        );
+        require!(!self.is_expired(clock), ErrorCode::SessionKeyExpired);
+        require!(permissions.can_transfer, ErrorCode::InsufficientPermissions);
+
+        // max_transfer_amount caps the key's cumulative spend, not each transfer
+        let total_spent = self
+            .total_spent
+            .checked_add(amount)
+            .ok_or(ErrorCode::SessionSpendCapExceeded)?;
+        if permissions.max_transfer_amount > 0 {
+            require!(
+                total_spent <= permissions.max_transfer_amount,
+                ErrorCode::SessionSpendCapExceeded
+            );
+        }
+
+        // daily_limit caps spend per window; the window resets automatically once it elapses
+        let mut spend_window_start = self.spend_window_start;
+        let mut spent_in_window = self.spent_in_window;
+        roll_window(
+            &mut spend_window_start,
+            &mut spent_in_window,
+            permissions.spend_window(),
+            clock.unix_timestamp,
+        );
+        let spent_in_window = spent_in_window
+            .checked_add(amount)
+            .ok_or(ErrorCode::DailyLimitExceeded)?;
+        if permissions.daily_limit > 0 {
+            require!(
+                spent_in_window <= permissions.daily_limit,
+                ErrorCode::DailyLimitExceeded
+            );
+        }
+
+        // max_uses_per_window throttles how often the key may act, independent of amounts
+        let mut use_window_start = self.use_window_start;
+        let mut uses_in_window = self.uses_in_window;
+        roll_window(
+            &mut use_window_start,
+            &mut uses_in_window,
+            permissions.use_window(),
+            clock.unix_timestamp,
+        );
+        let uses_in_window = uses_in_window + 1;
+        if permissions.max_uses_per_window > 0 {
+            require!(
+                uses_in_window <= permissions.max_uses_per_window as u64,
+                ErrorCode::RateLimitExceeded
+            );
+        }
+
+        Ok(SpendCharge {
+            total_spent,
+            spend_window_start,
+            spent_in_window,
+            use_window_start,
+            uses_in_window,
+            used_at: clock.unix_timestamp,
+        })
     }
+
+    /// Record a spend validated by `check_spend`. A successful spend ends the run of
+    /// failed attempts.
+    pub fn record_spend(&mut self, charge: &SpendCharge) {
+        self.total_spent = charge.total_spent;
+        self.spend_window_start = charge.spend_window_start;
+        self.spent_in_window = charge.spent_in_window;
+        self.use_window_start = charge.use_window_start;
+        self.uses_in_window = charge.uses_in_window;
+        self.use_count += 1;
+        self.failed_attempts = 0;
+        self.mark_used(charge.used_at);
+    }
+
+    /// Record a successful use at `now`, pushing a sliding key's expiry out to its
+    /// idle timeout
+    pub fn mark_used(&mut self, now: i64) {
+        self.last_used_at = now;
+        if self.idle_timeout > 0 {
+            let deadline = now.saturating_add(self.idle_timeout as i64);
+            self.expires_at_time = Some(deadline.min(self.max_expires_at));
+        }
+    }
+
+    /// Set the time bound. For a sliding key this is the new cap, and the current
+    /// deadline restarts from `now` without passing it.
+    pub fn set_expires_at_time(&mut self, time: i64, now: i64) {
+        if self.idle_timeout > 0 {
+            self.max_expires_at = time;
+            let deadline = now.saturating_add(self.idle_timeout as i64);
+            self.expires_at_time = Some(deadline.min(time));
+        } else {
+            self.expires_at_time = Some(time);
+        }
+    }
+
+    /// Latest time bound the key can reach: the cap for sliding keys
+    pub fn latest_expires_at_time(&self) -> Option<i64> {
+        if self.idle_timeout > 0 {
+            Some(self.max_expires_at)
+        } else {
+            self.expires_at_time
+        }
+    }
+
+    /// Count a refused spend at `now`, locking the key once `policy.max_failures` pile
+    /// up within one window. Returns whether the key was locked.
+    pub fn record_failure(&mut self, policy: &LockoutPolicy, now: i64) -> bool {
+        let mut failed_attempts = self.failed_attempts as u64;
+        roll_window(
+            &mut self.failure_window_start,
+            &mut failed_attempts,
+            policy.window_seconds as i64,
+            now,
+        );
+        self.failed_attempts = (failed_attempts + 1).min(u8::MAX as u64) as u8;
+        if self.failed_attempts < policy.max_failures {
+            return false;
+        }
+        self.locked_until = now.saturating_add(policy.lock_seconds as i64);
+        self.failed_attempts = 0;
+        true
+    }
+}
+
+/// Counters a validated spend leaves on its session key, see `SessionKey::check_spend`
+pub struct SpendCharge {
+    pub total_spent: u64,
+    pub spend_window_start: i64,
+    pub spent_in_window: u64,
+    pub use_window_start: i64,
+    pub uses_in_window: u64,
+    pub used_at: i64,
 }
 
+/// Instruction a session key may send to one program with `execute_custom`
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct DiscriminatorFilter {
+    pub program: Pubkey,
+    /// Expected leading bytes of the instruction data, zero padded
+    pub discriminator: [u8; 8],
+    /// Bytes of `discriminator` to compare: 8 for Anchor programs, 1 for native ones
+    pub len: u8,
+}
+
+impl DiscriminatorFilter {
+    /// Check if `data` starts with the filter's discriminator
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.starts_with(&self.discriminator[..self.len as usize])
+    }
+
+    /// Validate a key's filters: each compares 1 to 8 bytes and names one of its
+    /// `allowed_programs`
+    pub fn check_all(filters: &[Self], allowed_programs: &[Pubkey]) -> Result<()> {
+        require!(
+            filters
+                .iter()
+                .all(|f| (1..=8).contains(&f.len) && allowed_programs.contains(&f.program)),
+            ErrorCode::InvalidDiscriminatorFilter
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
 pub struct SessionPermissions {
     /// Can transfer tokens/SOL
     pub can_transfer: bool,
@@ -79,22 +1549,493 @@ pub struct SessionPermissions {
     pub can_delegate: bool,
     /// Can execute custom program instructions
     pub can_execute_custom: bool,
-    /// Maximum amount that can be transferred (0 = unlimited)
+    /// Can hand itself over to a new key with `transfer_session_to_new_key`
+    pub can_self_rotate: bool,
+    /// Maximum total amount the key can transfer over its lifetime (0 = unlimited)
     pub max_transfer_amount: u64,
+    /// Maximum amount the key can transfer per spend window (0 = unlimited)
+    pub daily_limit: u64,
+    /// Length of the spend window in seconds (0 = 24 hours)
+    pub spend_window_seconds: u32,
+    /// Maximum number of executions per use window (0 = unlimited)
+    pub max_uses_per_window: u32,
+    /// Length of the use window in seconds (0 = 24 hours)
+    pub use_window_seconds: u32,
     /// Custom permission flags for extensibility
     pub custom_flags: u32,
+    /// Highest invoke stack height `execute_custom` may run at: 1 = only as a top-level
+    /// instruction, 2 = also from one level of CPI (0 = no limit)
+    pub max_cpi_depth: u8,
+    /// Most accounts `execute_custom` may forward to the target program (0 = no limit)
+    pub max_custom_accounts: u8,
 }
 
-impl Default for SessionPermissions {
-    fn default() -> Self {
-        Self {
-            can_transfer: false,
-            can_delegate: false,
-            can_execute_custom: false,
-            max_transfer_amount: 0,
-            custom_flags: 0,
+impl SessionPermissions {
+    /// Spend window length, applying the 24 hour default
+    pub fn spend_window(&self) -> i64 {
+        match self.spend_window_seconds {
+            0 => SECONDS_PER_DAY,
+            seconds => seconds as i64,
         }
     }
+
+    /// Use window length, applying the 24 hour default
+    pub fn use_window(&self) -> i64 {
+        match self.use_window_seconds {
+            0 => SECONDS_PER_DAY,
+            seconds => seconds as i64,
+        }
+    }
+
+    /// Check if these permissions allow anything `previous` didn't: a new capability or
+    /// custom flag, a raised or removed limit, or a shorter window
+    pub fn widens(&self, previous: &SessionPermissions) -> bool {
+        // A limit of 0 means unlimited
+        let raises = |new: u64, old: u64| old > 0 && (new == 0 || new > old);
+        (self.can_transfer && !previous.can_transfer)
+            || (self.can_delegate && !previous.can_delegate)
+            || (self.can_execute_custom && !previous.can_execute_custom)
+            || (self.can_self_rotate && !previous.can_self_rotate)
+            || raises(self.max_transfer_amount, previous.max_transfer_amount)
+            || raises(self.daily_limit, previous.daily_limit)
+            || raises(
+                self.max_uses_per_window as u64,
+                previous.max_uses_per_window as u64,
+            )
+            || self.spend_window() < previous.spend_window()
+            || self.use_window() < previous.use_window()
+            || self.custom_flags & !previous.custom_flags != 0
+            || raises(self.max_cpi_depth as u64, previous.max_cpi_depth as u64)
+            || raises(
+                self.max_custom_accounts as u64,
+                previous.max_custom_accounts as u64,
+            )
+    }
+
+    /// Check if these permissions include everything `required` asks for: its
+    /// capabilities and custom flags, and at least its nonzero transfer limits
+    pub fn grants(&self, required: &SessionPermissions) -> bool {
+        // A limit of 0 means unlimited here and "no minimum" in `required`
+        let covers = |limit: u64, minimum: u64| limit == 0 || limit >= minimum;
+        (self.can_transfer || !required.can_transfer)
+            && (self.can_delegate || !required.can_delegate)
+            && (self.can_execute_custom || !required.can_execute_custom)
+            && (self.can_self_rotate || !required.can_self_rotate)
+            && required.custom_flags & !self.custom_flags == 0
+            && covers(self.max_transfer_amount, required.max_transfer_amount)
+            && covers(self.daily_limit, required.daily_limit)
+    }
+}
+
+/// Who must hold the attestation token for an execution to proceed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AttestationSubject {
+    /// The session key signing the execution
+    SessionHolder,
+    /// The owner of the destination token account
+    Recipient,
+    /// Both the session key and the recipient
+    Both,
+}
+
+/// Requires holders to carry a credential token (e.g. a soulbound KYC mint)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct AttestationGate {
+    /// Mint of the attestation token
+    pub mint: Pubkey,
+    /// Whose holdings are checked
+    pub subject: AttestationSubject,
+}
+
+impl AttestationGate {
+    pub fn requires_session_holder(&self) -> bool {
+        matches!(
+            self.subject,
+            AttestationSubject::SessionHolder | AttestationSubject::Both
+        )
+    }
+
+    pub fn requires_recipient(&self) -> bool {
+        matches!(
+            self.subject,
+            AttestationSubject::Recipient | AttestationSubject::Both
+        )
+    }
+
+    /// Check that `holder` owns a non-empty token account of the attestation mint
+    pub fn verify(&self, holder: &Pubkey, token_account: Option<&TokenAccount>) -> Result<()> {
+        let token_account = token_account.ok_or(ErrorCode::AttestationMissing)?;
+        require!(
+            token_account.owner == *holder
+                && token_account.mint == self.mint
+                && token_account.amount > 0,
+            ErrorCode::AttestationInvalid
+        );
+        Ok(())
+    }
 }
 
-// Removed unused SessionAction enum
+/// Arguments to `create_session_key_v2`. New fields are added here rather than as extra
+/// instruction parameters.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CreateSessionKeyArgs {
+    pub session_pubkey: Pubkey,
+    /// Unix timestamp expiry bound
+    pub expires_at_time: Option<i64>,
+    /// Slot expiry bound; the key expires at whichever bound comes first
+    pub expires_at_slot: Option<i64>,
+    pub permissions: SessionPermissions,
+    /// Hash of the grant terms the user consented to
+    pub terms_hash: Option<[u8; 32]>,
+    /// Free-form display label, e.g. a UTF-8 app name padded with zeros
+    pub label: [u8; 32],
+    /// Total executions allowed (0 = unlimited, 1 = single-use)
+    pub max_uses: u32,
+    /// Unix timestamp the key becomes usable at (0 = immediately)
+    pub valid_from: i64,
+    /// Recipients the key may pay, at most `MAX_ALLOWED_RECIPIENTS` (empty = any)
+    pub allowed_recipients: Vec<Pubkey>,
+    /// Mints the key may move, at most `MAX_SESSION_MINTS`, intersected with the
+    /// account's `allowed_mints` (empty = no further restriction)
+    pub allowed_mints: Vec<Pubkey>,
+    /// Programs the key may call with `execute_custom`, at most `MAX_ALLOWED_PROGRAMS`
+    pub allowed_programs: Vec<Pubkey>,
+    /// Per-program instruction filters for `execute_custom`, at most
+    /// `MAX_ALLOWED_DISCRIMINATORS` (programs without filters accept any instruction)
+    pub allowed_discriminators: Vec<DiscriminatorFilter>,
+    /// Seconds of inactivity that expire the key (0 = fixed expiry). When set,
+    /// `expires_at_time` is the cap each use can extend the key up to.
+    pub idle_timeout: u32,
+    /// Dapp or program identifier the key is bound to, which executing instructions
+    /// must pass (default pubkey = usable anywhere)
+    pub scope: Pubkey,
+    /// SHA-256 of the dapp origin requesting the grant, e.g. `https://app.example`
+    pub domain_hash: Option<[u8; 32]>,
+}
+
+impl CreateSessionKeyArgs {
+    pub const SIZE: usize = 32 + // session_pubkey
+        9 + // expires_at_time
+        9 + // expires_at_slot
+        42 + // permissions
+        33 + // terms_hash
+        32 + // label
+        4 + // max_uses
+        8 + // valid_from
+        4 + (32 * MAX_ALLOWED_RECIPIENTS) + // allowed_recipients
+        4 + (32 * MAX_SESSION_MINTS) + // allowed_mints
+        4 + (32 * MAX_ALLOWED_PROGRAMS) + // allowed_programs
+        4 + (DISCRIMINATOR_FILTER_SIZE * MAX_ALLOWED_DISCRIMINATORS) + // allowed_discriminators
+        4 + // idle_timeout
+        32 + // scope
+        33; // domain_hash
+}
+
+/// Half-open range `[start, end)` of Unix timestamps, e.g. a trading halt or maintenance freeze
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct BlackoutWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl BlackoutWindow {
+    pub fn contains(&self, now: i64) -> bool {
+        self.start <= now && now < self.end
+    }
+}
+
+/// Locks a session key for `lock_seconds` once `max_failures` of its spends are refused
+/// within `window_seconds`, so probing a key's limits isn't free
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct LockoutPolicy {
+    pub max_failures: u8,
+    pub window_seconds: u32,
+    pub lock_seconds: u32,
+}
+
+/// Moves part of every outgoing delegated transfer of `mint` into the authority's `vault`,
+/// in the same instruction. Percentage and round-up may be combined.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SavingsRule {
+    /// Mint the rule applies to
+    pub mint: Pubkey,
+    /// Authority-owned token account of `mint` receiving the savings
+    pub vault: Pubkey,
+    /// Share of each transfer saved, in basis points (0 = off)
+    pub basis_points: u16,
+    /// Round each transfer up to a multiple of this many base units and save the
+    /// difference (0 = off)
+    pub round_up_unit: u64,
+}
+
+impl SavingsRule {
+    /// Amount to divert for an outgoing transfer of `amount`
+    pub fn savings_for(&self, amount: u64) -> u64 {
+        let share = (amount as u128 * self.basis_points as u128 / 10_000) as u64;
+        let round_up = match self.round_up_unit {
+            0 => 0,
+            unit => (unit - amount % unit) % unit,
+        };
+        share.saturating_add(round_up)
+    }
+}
+
+/// Who the caller means to pay, checked against `to_token` so a transfer can't be
+/// redirected to some other token account of the mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct RecipientCheck {
+    /// Wallet that must own the destination token account
+    pub owner: Pubkey,
+    /// Also require the destination to be `owner`'s associated token account
+    pub require_ata: bool,
+}
+
+impl RecipientCheck {
+    pub fn verify(
+        &self,
+        destination: &Pubkey,
+        to_token: &TokenAccount,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<()> {
+        require_keys_eq!(to_token.owner, self.owner, ErrorCode::RecipientMismatch);
+        if self.require_ata {
+            let ata =
+                get_associated_token_address_with_program_id(&self.owner, mint, token_program);
+            require_keys_eq!(*destination, ata, ErrorCode::RecipientNotCanonicalAta);
+        }
+        Ok(())
+    }
+}
+
+/// Bond a session operator must lock before its session key may execute
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct BondRequirement {
+    /// SPL mint of the bond, or None for SOL
+    pub mint: Option<Pubkey>,
+    /// Minimum bonded amount
+    pub min_amount: u64,
+    /// Seconds after a release request during which the authority may still slash
+    pub dispute_window_seconds: i64,
+}
+
+/// Outcome of a violation report
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DisputeStatus {
+    Pending,
+    Upheld,
+    Dismissed,
+}
+
+/// One step of a `PayoutPipeline`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PipelineStep {
+    /// Delegated transfer of at most `max_amount` of `mint` into the `destination` token account
+    Transfer {
+        mint: Pubkey,
+        destination: Pubkey,
+        max_amount: u64,
+    },
+    /// CPI into the swap `program` with the `mint_in` delegate PDA as signer; the owner's
+    /// source token account may lose at most `max_amount_in` over the call, and the
+    /// authority's `destination` token account must gain at least `min_amount_out`
+    Swap {
+        program: Pubkey,
+        mint_in: Pubkey,
+        max_amount_in: u64,
+        destination: Pubkey,
+        min_amount_out: u64,
+    },
+}
+
+/// Per-step input supplied by the session key when running a pipeline
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PipelineStepArgs {
+    /// Transfer amount; ignored for swaps, whose spend is measured instead
+    pub amount: u64,
+    /// Instruction data for a swap step (empty for transfers)
+    pub data: Vec<u8>,
+    /// Number of remaining accounts forwarded to a swap program
+    pub account_count: u8,
+}
+
+/// Entry of an account's spend taxonomy, e.g. `{ id: 3, label: "groceries" }`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SpendCategory {
+    pub id: u16,
+    /// UTF-8 display label padded with zeros
+    pub label: [u8; 16],
+}
+
+/// Executions of one session key per hour over the last `HEATMAP_HOURS` hours, as a ring
+/// indexed by `unix hour % HEATMAP_HOURS`. Buckets are cleared lazily on the next record,
+/// so readers should treat buckets for hours after `last_hour` as empty.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SessionHeatmap {
+    pub session_key: Pubkey,
+    /// Unix hour (`timestamp / 3600`) of the latest recorded execution
+    pub last_hour: i64,
+    pub hours: [u16; HEATMAP_HOURS],
+}
+
+impl SessionHeatmap {
+    /// Count an execution in `hour`, first clearing buckets for the hours skipped since
+    /// the last one
+    pub fn record(&mut self, hour: i64) {
+        let len = HEATMAP_HOURS as i64;
+        if hour > self.last_hour {
+            let skipped = (hour - self.last_hour).min(len);
+            for h in hour - skipped + 1..=hour {
+                self.hours[h.rem_euclid(len) as usize] = 0;
+            }
+            self.last_hour = hour;
+        }
+        let bucket = &mut self.hours[hour.rem_euclid(len) as usize];
+        *bucket = bucket.saturating_add(1);
+    }
+}
+
+/// Bump of the account's delegate PDA for one mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct DelegateBump {
+    pub mint: Pubkey,
+    pub bump: u8,
+}
+
+/// Lifetime volume of one spend category in `AccountStats`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct CategoryTotal {
+    pub category: u16,
+    pub volume: u128,
+    pub count: u64,
+}
+
+/// Cap on one mint's value routed into a program by custom actions, in a `SpendBudget`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct ProgramBudget {
+    pub program: Pubkey,
+    /// Maximum amount (base units) per window of the owning budget
+    pub limit: u64,
+    /// Unix timestamp the current window started at
+    pub window_start: i64,
+    /// Amount routed in the current window
+    pub spent: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{
+        DEFAULT_SESSION_KEY_CAPACITY, MAX_INIT_SESSION_KEY_CAPACITY, MAX_SESSION_KEY_CAPACITY,
+    };
+    use crate::instructions::create_session_key::build_session_key;
+    use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+    /// Largest account a program can allocate through CPI, which `init` and
+    /// `init_if_needed` use
+    const MAX_CPI_ALLOCATION: usize = 10_240;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn clock_at(unix_timestamp: i64) -> Clock {
+        Clock {
+            slot: 1_000,
+            unix_timestamp,
+            ..Clock::default()
+        }
+    }
+
+    /// Transfer key valid for an hour from `NOW`
+    fn session_key(permissions: SessionPermissions) -> SessionKey {
+        let args = CreateSessionKeyArgs {
+            session_pubkey: Pubkey::new_unique(),
+            expires_at_time: Some(NOW + 3_600),
+            expires_at_slot: None,
+            permissions: SessionPermissions {
+                can_transfer: true,
+                ..permissions
+            },
+            terms_hash: None,
+            label: [0; 32],
+            max_uses: 0,
+            valid_from: 0,
+            allowed_recipients: vec![],
+            allowed_mints: vec![],
+            allowed_programs: vec![],
+            allowed_discriminators: vec![],
+            idle_timeout: 0,
+            scope: Pubkey::default(),
+            domain_hash: None,
+        };
+        build_session_key(&args, &clock_at(NOW))
+    }
+
+    #[test]
+    fn user_account_fits_cpi_allocation() {
+        assert!(UserAccount::space(DEFAULT_SESSION_KEY_CAPACITY) <= MAX_CPI_ALLOCATION);
+        assert!(UserAccount::space(MAX_INIT_SESSION_KEY_CAPACITY) <= MAX_CPI_ALLOCATION);
+        // One resize takes an account created at the cap to the ceiling
+        assert!(
+            UserAccount::space(MAX_SESSION_KEY_CAPACITY)
+                - UserAccount::space(MAX_INIT_SESSION_KEY_CAPACITY)
+                <= MAX_PERMITTED_DATA_INCREASE
+        );
+    }
+
+    #[test]
+    fn roll_window_stays_aligned_and_saturates() {
+        let (mut start, mut spent) = (NOW, 10);
+        roll_window(&mut start, &mut spent, 100, NOW + 99);
+        assert_eq!((start, spent), (NOW, 10));
+        roll_window(&mut start, &mut spent, 100, NOW + 250);
+        assert_eq!((start, spent), (NOW + 200, 0));
+
+        // A window too long to end before i64::MAX never rolls over
+        spent = 10;
+        roll_window(&mut start, &mut spent, i64::MAX, NOW + 300);
+        assert_eq!((start, spent), (NOW + 200, 10));
+    }
+
+    #[test]
+    fn covers_only_narrower_children() {
+        const PROGRAM: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+        let recipient = Pubkey::new_unique();
+        let mut parent = session_key(SessionPermissions {
+            daily_limit: 100,
+            ..Default::default()
+        });
+        parent.allowed_recipients = vec![recipient];
+        parent.allowed_programs = vec![PROGRAM];
+        let child = || {
+            let mut child = session_key(SessionPermissions {
+                daily_limit: 50,
+                ..Default::default()
+            });
+            child.allowed_recipients = vec![recipient];
+            child
+        };
+        let covered = |edit: fn(&mut SessionKey)| {
+            let mut key = child();
+            edit(&mut key);
+            parent.covers(&key, NOW)
+        };
+
+        assert!(covered(|_| {}));
+        assert!(covered(|k| k.allowed_programs = vec![PROGRAM]));
+        assert!(!covered(|k| k.permissions.daily_limit = 0));
+        assert!(!covered(|k| k.permissions.can_execute_custom = true));
+        assert!(!covered(|k| k.expires_at_time = Some(NOW + 7_200)));
+        assert!(!covered(|k| k.expires_at_time = None));
+        assert!(!covered(|k| k.valid_from = -1));
+        // An empty list allows any recipient, so it isn't inside the parent's
+        assert!(!covered(|k| k.allowed_recipients.clear()));
+        assert!(!covered(|k| k.allowed_programs = vec![Pubkey::new_unique()]));
+
+        // A scoped parent only covers children with its scope
+        parent.scope = Pubkey::new_unique();
+        assert!(!parent.covers(&child(), NOW));
+        let mut scoped = child();
+        scoped.scope = parent.scope;
+        assert!(parent.covers(&scoped, NOW));
+    }
+}
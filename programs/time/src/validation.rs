@@ -0,0 +1,86 @@
+//! Session key checks other programs can link against instead of calling
+//! `validate_session` through CPI (enable the `library` feature). An integrating program
+//! takes the user's `UserAccount` as `Account<UserAccount>` with `seeds::program =
+//! time::ID`, plus its `SessionKeyAccount` for keys stored outside the account, and calls
+//! `require_session_signer` with the signer of its own instruction. Failures use this
+//! program's error codes.
+
+use crate::errors::ErrorCode;
+use crate::state::{SessionKey, SessionKeyAccount, SessionPermissions, UserAccount};
+use anchor_lang::prelude::*;
+
+/// Check that `session_pubkey` is a session key of `user_account` that can act right
+/// now: unrevoked, unexpired, not used up, suspended or locked out, past its
+/// `valid_from`, on an account that isn't paused or in a blackout. Pass the key's
+/// `SessionKeyAccount` when it isn't stored inline.
+pub fn check_active<'a>(
+    user_account: &'a UserAccount,
+    key_account: Option<&'a SessionKeyAccount>,
+    session_pubkey: &Pubkey,
+    clock: &Clock,
+) -> Result<&'a SessionKey> {
+    let now = clock.unix_timestamp;
+    require!(!user_account.is_paused, ErrorCode::AccountPaused);
+    require!(
+        user_account.active_blackout(now).is_none(),
+        ErrorCode::BlackoutActive
+    );
+
+    let session_key = user_account.session_key(key_account, session_pubkey)?;
+    require!(!session_key.is_revoked, ErrorCode::SessionKeyRevoked);
+    require!(!session_key.is_expired(clock), ErrorCode::SessionKeyExpired);
+    require!(!session_key.is_exhausted(), ErrorCode::SessionKeyExhausted);
+    require!(!session_key.is_suspended, ErrorCode::SessionKeySuspended);
+    require!(!session_key.is_locked(now), ErrorCode::SessionKeyLocked);
+    require!(
+        session_key.valid_from <= now,
+        ErrorCode::SessionKeyNotYetValid
+    );
+    Ok(session_key)
+}
+
+/// `check_active`, then check the key's current permissions grant `required` and that
+/// it may act for `scope`
+pub fn check_session<'a>(
+    user_account: &'a UserAccount,
+    key_account: Option<&'a SessionKeyAccount>,
+    session_pubkey: &Pubkey,
+    required: &SessionPermissions,
+    scope: Option<&Pubkey>,
+    clock: &Clock,
+) -> Result<&'a SessionKey> {
+    let session_key = check_active(user_account, key_account, session_pubkey, clock)?;
+    require!(
+        session_key
+            .permissions_at(clock.unix_timestamp)
+            .grants(required),
+        ErrorCode::InsufficientPermissions
+    );
+    require!(
+        session_key.allows_scope(scope),
+        ErrorCode::SessionScopeMismatch
+    );
+    Ok(session_key)
+}
+
+/// `check_session` for `session_signer`, which must have signed the instruction
+pub fn require_session_signer<'a>(
+    user_account: &'a UserAccount,
+    key_account: Option<&'a SessionKeyAccount>,
+    session_signer: &AccountInfo,
+    required: &SessionPermissions,
+    scope: Option<&Pubkey>,
+) -> Result<&'a SessionKey> {
+    require!(
+        session_signer.is_signer,
+        anchor_lang::error::ErrorCode::AccountNotSigner
+    );
+    check_session(
+        user_account,
+        key_account,
+        session_signer.key,
+        required,
+        scope,
+        &Clock::get()?,
+    )
+}
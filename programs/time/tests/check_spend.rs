@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use time::constants::SECONDS_PER_DAY;
+use time::errors::ErrorCode;
+use time::state::{SessionKey, SessionPermissions};
+use time_test_utils::{clock_at, session_key, warp};
+
+const NOW: i64 = 1_700_000_000;
+
+/// Transfer key valid for an hour from `NOW`
+fn transfer_key(permissions: SessionPermissions) -> SessionKey {
+    let permissions = SessionPermissions {
+        can_transfer: true,
+        ..permissions
+    };
+    session_key(&Pubkey::new_unique(), permissions, NOW + 3_600, NOW)
+}
+
+fn refusal(key: &SessionKey, amount: u64, clock: &Clock) -> Option<Error> {
+    key.check_spend(amount, clock).err()
+}
+
+#[test]
+fn check_spend_enforces_lifetime_and_window_caps() {
+    let mut key = transfer_key(SessionPermissions {
+        max_transfer_amount: 100,
+        daily_limit: 60,
+        ..Default::default()
+    });
+    let clock = clock_at(1_000, NOW);
+
+    key.record_spend(&key.check_spend(60, &clock).unwrap());
+    assert_eq!(key.total_spent, 60);
+    assert_eq!(key.use_count, 1);
+    assert_eq!(
+        refusal(&key, 1, &clock),
+        Some(ErrorCode::DailyLimitExceeded.into())
+    );
+
+    // The window resets once it elapses, the lifetime cap doesn't
+    let next_day = warp(&clock, SECONDS_PER_DAY);
+    key.expires_at_time = Some(NOW + 2 * SECONDS_PER_DAY);
+    key.record_spend(&key.check_spend(40, &next_day).unwrap());
+    assert_eq!(key.spent_in_window, 40);
+    assert_eq!(
+        refusal(&key, 1, &next_day),
+        Some(ErrorCode::SessionSpendCapExceeded.into())
+    );
+    assert_eq!(
+        refusal(&key, u64::MAX, &next_day),
+        Some(ErrorCode::SessionSpendCapExceeded.into())
+    );
+}
+
+#[test]
+fn check_spend_refuses_unusable_keys() {
+    let clock = clock_at(1_000, NOW);
+    let refused = |edit: fn(&mut SessionKey)| {
+        let mut key = transfer_key(SessionPermissions::default());
+        edit(&mut key);
+        refusal(&key, 1, &clock)
+    };
+
+    assert!(refused(|_| {}).is_none());
+    assert_eq!(
+        refused(|k| k.revoke(None, NOW)),
+        Some(ErrorCode::SessionKeyRevoked.into())
+    );
+    assert_eq!(
+        refused(|k| k.is_suspended = true),
+        Some(ErrorCode::SessionKeySuspended.into())
+    );
+    assert_eq!(
+        refused(|k| {
+            k.max_uses = 1;
+            k.use_count = 1;
+        }),
+        Some(ErrorCode::SessionKeyExhausted.into())
+    );
+    assert_eq!(
+        refused(|k| k.valid_from = NOW + 1),
+        Some(ErrorCode::SessionKeyNotYetValid.into())
+    );
+    assert_eq!(
+        refused(|k| k.expires_at_time = Some(NOW)),
+        Some(ErrorCode::SessionKeyExpired.into())
+    );
+    assert_eq!(
+        refused(|k| k.permissions.can_transfer = false),
+        Some(ErrorCode::InsufficientPermissions.into())
+    );
+    assert_eq!(
+        refused(|k| {
+            k.permissions.max_uses_per_window = 1;
+            k.uses_in_window = 1;
+        }),
+        Some(ErrorCode::RateLimitExceeded.into())
+    );
+}
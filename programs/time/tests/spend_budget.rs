@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token::state::Account as TokenState;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use time::errors::ErrorCode;
+use time::state::{ProgramConfig, SpendBudget};
+use time_test_utils::*;
+use tokio::task::JoinSet;
+
+const LIMIT: u64 = 1_000;
+
+/// Session keys of one account spending a shared budget in the same slot: the budget
+/// PDA is writable in every transfer, so the runtime serializes them and each sees
+/// the spend of the ones before it
+#[tokio::test]
+async fn parallel_transfers_never_overspend_a_shared_budget() {
+    let mut context = program_test().start_with_context().await;
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let now = clock.unix_timestamp;
+    let authority = context.payer.pubkey();
+
+    let sessions: Vec<Keypair> = (0..6).map(|_| Keypair::new()).collect();
+    let mut account = blank_user_account(&authority);
+    account.session_keys = sessions
+        .iter()
+        .map(|s| session_key(&s.pubkey(), transfer_permissions(0), now + 3_600, now))
+        .collect();
+    let (user, _) = user_account_address(&authority);
+    let mint = Pubkey::new_unique();
+    let (delegate, _) = delegate_address(&user, &mint);
+    let from = Pubkey::new_unique();
+    let (to, to_account) = funded_ata(&mint, &Pubkey::new_unique(), 0);
+    for (address, fixture) in [
+        (user, user_account(&account)),
+        (mint, tokens::mint(&authority, 6, 1_000_000)),
+        (
+            from,
+            delegated_token_account(&mint, &authority, 1_000_000, &delegate, 1_000_000),
+        ),
+        (to, to_account),
+    ] {
+        context.set_account(
+            &address,
+            &solana_sdk::account::Account::from(fixture).into(),
+        );
+    }
+
+    let spend_budget = Pubkey::find_program_address(
+        &[SpendBudget::SEED_PREFIX, user.as_ref(), mint.as_ref()],
+        &time::ID,
+    )
+    .0;
+    let initialize = Instruction {
+        program_id: time::ID,
+        accounts: time::accounts::InitializeSpendBudget {
+            spend_budget,
+            user_account: user,
+            mint,
+            authority,
+            system_program: anchor_lang::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: time::instruction::InitializeSpendBudget {
+            limit: LIMIT,
+            window_seconds: 86_400,
+        }
+        .data(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[initialize],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // Twelve distinct transfers of 100..=111 from six keys, 1,266 in all, against a
+    // budget of 1,000
+    let mut transfers = JoinSet::new();
+    for (i, amount) in (100..112).enumerate() {
+        let session = &sessions[i % sessions.len()];
+        let transfer = Instruction {
+            program_id: time::ID,
+            accounts: time::accounts::SplDelegatedTransfer {
+                session_signer: session.pubkey(),
+                user_account: user,
+                program_config: Pubkey::find_program_address(
+                    &[ProgramConfig::SEED_PREFIX],
+                    &time::ID,
+                )
+                .0,
+                session_key_account: None,
+                policy: None,
+                from_token: from,
+                to_token: to,
+                mint,
+                delegate_authority: delegate,
+                session_attestation: None,
+                recipient_attestation: None,
+                blocklist: None,
+                recipient_consent: None,
+                operator_bond: None,
+                operator_reputation: None,
+                spend_budget: Some(spend_budget),
+                activity_digest: None,
+                account_stats: None,
+                savings_vault: None,
+                fee_token: None,
+                slot_hashes: None,
+                gas_tank: None,
+                instructions_sysvar: None,
+                token_program: TOKEN_PROGRAM_ID,
+            }
+            .to_account_metas(None),
+            data: time::instruction::SplDelegatedTransfer {
+                amount,
+                recipient: None,
+                scope: None,
+                slot_ref: None,
+                category: None,
+            }
+            .data(),
+        };
+        let transaction = Transaction::new_signed_with_payer(
+            &[transfer],
+            Some(&authority),
+            &[&context.payer, session],
+            context.last_blockhash,
+        );
+        let banks = context.banks_client.clone();
+        transfers.spawn(async move { (amount, banks.process_transaction(transaction).await) });
+    }
+
+    let mut paid = 0;
+    let mut refused = 0;
+    while let Some(result) = transfers.join_next().await {
+        match result.unwrap() {
+            (amount, Ok(())) => paid += amount,
+            (_, Err(BanksClientError::TransactionError(error))) => {
+                assert_eq!(
+                    error,
+                    TransactionError::InstructionError(
+                        0,
+                        InstructionError::Custom(ErrorCode::SpendBudgetExceeded.into())
+                    )
+                );
+                refused += 1;
+            }
+            (_, Err(error)) => panic!("transfer failed to process: {error}"),
+        }
+    }
+    assert!(refused > 0);
+    assert!(paid <= LIMIT);
+
+    let budget = context
+        .banks_client
+        .get_account(spend_budget)
+        .await
+        .unwrap()
+        .unwrap();
+    let budget = SpendBudget::try_deserialize(&mut &budget.data[..]).unwrap();
+    assert_eq!(budget.spent, paid);
+    let received = context.banks_client.get_account(to).await.unwrap().unwrap();
+    assert_eq!(TokenState::unpack(&received.data).unwrap().amount, paid);
+}
@@ -0,0 +1,114 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  merkleProof,
+  merkleRoot,
+  receiptLeaf,
+  verifyMerkleProof,
+} from "../app/receipts";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Account stats", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("commits every execution to a provable history root", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [accountStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), ctx.userPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializeAccountStats()
+      .accountsStrict({
+        accountStats,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const transfer = (amount: number, stats: PublicKey | null) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: stats,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      await transfer(1, null);
+      assert.fail("expected AccountStatsMissing");
+    } catch (e) {
+      assert.include(String(e), "AccountStatsMissing");
+    }
+
+    const leaves: Buffer[] = [];
+    for (const amount of [10, 20, 30]) {
+      const sig = await transfer(amount, accountStats);
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      leaves.push(
+        receiptLeaf({
+          sessionKey: ctx.session.publicKey,
+          mint: ctx.mint,
+          destination: ctx.recipientAta,
+          amount: BigInt(amount),
+          slot: BigInt(tx.slot),
+        })
+      );
+    }
+
+    const stats = await program.account.accountStats.fetch(accountStats);
+    assert.equal(stats.history.count.toNumber(), 3);
+    assert.equal(stats.totalVolume.toString(), "60");
+    assert.deepEqual(Buffer.from(stats.historyRoot), merkleRoot(leaves));
+
+    // A third party only needs the receipt, its index, the proof and the on-chain root
+    const proof = merkleProof(leaves, 1);
+    assert.isTrue(verifyMerkleProof(leaves[1], 1, proof, stats.historyRoot));
+    assert.isFalse(verifyMerkleProof(leaves[0], 1, proof, stats.historyRoot));
+
+    // Hourly usage per key, so "this key never runs at 3am" is checkable on-chain
+    const heatmap = stats.sessionHeatmaps.find((h) =>
+      h.sessionKey.equals(ctx.session.publicKey)
+    );
+    const hour = Math.floor(stats.lastExecutionAt.toNumber() / 3600);
+    assert.equal(heatmap.lastHour.toNumber(), hour);
+    assert.equal(heatmap.hours.reduce((a, b) => a + b, 0), 3);
+    assert.equal(heatmap.hours[hour % heatmap.hours.length], 3);
+  });
+});
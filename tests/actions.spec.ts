@@ -0,0 +1,98 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram, Transaction } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  buildSessionActionTransaction,
+  sessionActionMetadata,
+} from "../app/actions";
+import { airdropLamports, deriveUserPda, transferPermissions } from "./helpers";
+
+describe("Session actions (Blinks)", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("builds wallet-signable extend and revoke transactions", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const session = Keypair.generate();
+    const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+    await program.methods
+      .createSessionKey(
+        session.publicKey,
+        new BN(expiresAt),
+        { time: {} },
+        transferPermissions(new BN(0)),
+        null
+      )
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const metadata = sessionActionMetadata(
+      "https://example.com/api/actions",
+      session.publicKey,
+      "https://example.com/icon.png"
+    );
+    assert.deepEqual(
+      metadata.links.actions.map((a) => a.label),
+      ["Revoke session", "Extend session"]
+    );
+
+    const send = async (transaction: string) => {
+      const tx = Transaction.from(Buffer.from(transaction, "base64"));
+      tx.sign(authority);
+      const sig = await provider.connection.sendRawTransaction(tx.serialize());
+      await provider.connection.confirmTransaction(sig, "confirmed");
+    };
+    const entry = async () =>
+      (await program.account.userAccount.fetch(userPda)).sessionKeys.find(
+        (k) => k.pubkey.equals(session.publicKey)
+      );
+
+    const request = { account: authority.publicKey.toBase58() };
+    const extend = await buildSessionActionTransaction(
+      program,
+      "extend",
+      request,
+      session.publicKey,
+      7200
+    );
+    await send(extend.transaction);
+    assert.equal((await entry()).expiresAtTime.toNumber(), expiresAt + 7200);
+
+    const revoke = await buildSessionActionTransaction(
+      program,
+      "revoke",
+      request,
+      session.publicKey
+    );
+    await send(revoke.transaction);
+    assert.isTrue((await entry()).isRevoked);
+  });
+});
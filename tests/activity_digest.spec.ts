@@ -0,0 +1,141 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { createHash } from "crypto";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+const sha256 = (...parts: Buffer[]) =>
+  createHash("sha256").update(Buffer.concat(parts)).digest();
+
+// Mirrors MerkleFrontier::root for a depth-16 tree padded with zero leaves
+function receiptsRoot(leaves: Buffer[], depth = 16): Buffer {
+  let level = leaves;
+  let zero = Buffer.alloc(32);
+  for (let h = 0; h < depth; h++) {
+    const next: Buffer[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(sha256(level[i], level[i + 1] ?? zero));
+    }
+    level = next.length ? next : [sha256(zero, zero)];
+    zero = sha256(zero, zero);
+  }
+  return level[0];
+}
+
+function u64le(value: number | bigint): Buffer {
+  const buf = Buffer.alloc(8);
+  buf.writeBigUInt64LE(BigInt(value));
+  return buf;
+}
+
+describe("Activity digests", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const parser = new anchor.EventParser(program.programId, program.coder);
+
+  async function eventsOf(sig: string) {
+    const tx = await provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return {
+      slot: tx.slot,
+      events: [...parser.parseLogs(tx.meta.logMessages)],
+    };
+  }
+
+  it("emits one digest per interval with a verifiable receipts root", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [activityDigest] = PublicKey.findProgramAddressSync(
+      [Buffer.from("activity_digest"), ctx.userPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .enableActivityDigest(3)
+      .accountsStrict({
+        activityDigest,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const transfer = (amount: number, digest: PublicKey | null) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: digest,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      await transfer(1, null);
+      assert.fail("expected ActivityDigestMissing");
+    } catch (e) {
+      assert.include(String(e), "ActivityDigestMissing");
+    }
+
+    const leaves: Buffer[] = [];
+    let digestEvent: any = null;
+    for (const amount of [100, 200, 300]) {
+      const { slot, events } = await eventsOf(
+        await transfer(amount, activityDigest)
+      );
+      assert.notInclude(events.map((e) => e.name), "transferExecuted");
+      leaves.push(
+        sha256(
+          ctx.session.publicKey.toBuffer(),
+          ctx.mint.toBuffer(),
+          ctx.recipientAta.toBuffer(),
+          u64le(amount),
+          u64le(slot)
+        )
+      );
+      digestEvent =
+        events.find((e) => e.name === "activityDigestEmitted") ?? digestEvent;
+    }
+
+    assert.isNotNull(digestEvent);
+    assert.equal(digestEvent.data.sequence.toNumber(), 0);
+    assert.equal(digestEvent.data.count.toNumber(), 3);
+    assert.equal(digestEvent.data.totalAmount.toString(), "600");
+    assert.deepEqual(
+      Buffer.from(digestEvent.data.receiptsRoot),
+      receiptsRoot(leaves)
+    );
+
+    const digest = await program.account.activityDigest.fetch(activityDigest);
+    assert.equal(digest.sequence.toNumber(), 1);
+    assert.equal(digest.receipts.count.toNumber(), 0);
+  });
+});
@@ -10,7 +10,14 @@ import {
 import { Time } from "../target/types/time";
 import { assert } from "chai";
 import { TOKEN_PROGRAM_ID, mintTo } from "@solana/spl-token";
-import { airdropLamports, createMintAndAtas, deriveUserPda } from "./helpers";
+import {
+  airdropLamports,
+  createMintAndAtas,
+  deriveUserPda,
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
 
 describe("Allowed mints behavior", () => {
   const provider = anchor.AnchorProvider.env();
@@ -29,10 +36,11 @@ describe("Allowed mints behavior", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -83,6 +91,7 @@ describe("Allowed mints behavior", () => {
       .splApproveDelegate(new BN(200_000_000))
       .accountsStrict({
         userAccount: userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
         authority: authority.publicKey,
         tokenAccount: ownerAtaA,
         mint: mintA,
@@ -106,9 +115,17 @@ describe("Allowed mints behavior", () => {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(200_000_000),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: userPda,
@@ -120,14 +137,31 @@ describe("Allowed mints behavior", () => {
 
     // Transfer with allowed mint A succeeds
     const ix = await program.methods
-      .splDelegatedTransfer(new BN(100_000_000))
+      .splDelegatedTransfer(new BN(100_000_000), null, null, null, null)
       .accountsStrict({
         sessionSigner: session.publicKey,
         userAccount: userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
+        sessionKeyAccount: null,
+        policy: null,
         fromToken: ownerAtaA,
         toToken: recipAtaA,
         mint: mintA,
         delegateAuthority: delegateA,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: null,
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: null,
+        activityDigest: null,
+        accountStats: null,
+        savingsVault: null,
+        feeToken: null,
+        slotHashes: null,
+        gasTank: null,
+        instructionsSysvar: null,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([session])
@@ -155,14 +189,31 @@ describe("Allowed mints behavior", () => {
     // Now transferring with mintA should fail due to MintNotAllowed
     try {
       await program.methods
-        .splDelegatedTransfer(new BN(1))
+        .splDelegatedTransfer(new BN(1), null, null, null, null)
         .accountsStrict({
           sessionSigner: session.publicKey,
           userAccount: userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
           fromToken: ownerAtaA,
           toToken: recipAtaA,
           mint: mintA,
           delegateAuthority: delegateA,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([session])
@@ -172,4 +223,104 @@ describe("Allowed mints behavior", () => {
       assert.include(e.toString(), "MintNotAllowed");
     }
   });
+
+  it("intersects a session's mints with the account allowlist", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const otherMint = Keypair.generate().publicKey;
+    const createKey = (session: Keypair, allowedMints: PublicKey[]) =>
+      program.methods
+        .createSessionKeyV2({
+          sessionPubkey: session.publicKey,
+          expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+          expiresAtSlot: null,
+          permissions: transferPermissions(new BN(0)),
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints,
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const transfer = (session: Keypair) =>
+      program.methods
+        .splDelegatedTransfer(new BN(1), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([session])
+        .rpc();
+    const setAccountMints = (mints: PublicKey[]) =>
+      program.methods
+        .updateAllowedMints(mints)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+
+    const scoped = Keypair.generate();
+    const elsewhere = Keypair.generate();
+    for (const kp of [scoped, elsewhere]) {
+      await airdropLamports(
+        provider.connection,
+        kp.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+    }
+    await createKey(scoped, [ctx.mint]);
+    await createKey(elsewhere, [otherMint]);
+
+    await transfer(scoped);
+    try {
+      await transfer(elsewhere);
+      assert.fail("expected MintNotAllowed");
+    } catch (e) {
+      assert.include(String(e), "MintNotAllowed");
+    }
+
+    // The session list can only narrow the account list, never widen it
+    await setAccountMints([otherMint]);
+    try {
+      await transfer(scoped);
+      assert.fail("expected MintNotAllowed");
+    } catch (e) {
+      assert.include(String(e), "MintNotAllowed");
+    }
+  });
 });
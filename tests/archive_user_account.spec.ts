@@ -0,0 +1,123 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, EventParser } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  deriveUserPda,
+  transferPermissions,
+} from "./helpers";
+
+describe("Archive user account", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("closes a dormant account to a hash and restores it", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    const [archivePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("archive"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .setWideningDelay(3600)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+    const before = await program.account.userAccount.fetch(userPda);
+
+    const signature = await program.methods
+      .archiveUserAccount()
+      .accountsStrict({
+        userAccount: userPda,
+        archive: archivePda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc({ commitment: "confirmed" });
+    assert.isNull(await provider.connection.getAccountInfo(userPda));
+    const archive = await program.account.archivedUserAccount.fetch(
+      archivePda
+    );
+    assert.equal(archive.sessionKeyCapacity, 6);
+
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new EventParser(program.programId, program.coder);
+    const archived = Array.from(parser.parseLogs(tx!.meta!.logMessages!)).find(
+      (e) => e.name === "userAccountArchived"
+    );
+    const data = Buffer.from(archived!.data.data as Buffer);
+
+    const restore = (payload: Buffer) =>
+      program.methods
+        .restoreUserAccount(payload)
+        .accountsStrict({
+          archive: archivePda,
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+    const tampered = Buffer.from(data);
+    tampered[tampered.length - 1] ^= 1;
+    try {
+      await restore(tampered);
+      assert.fail("expected ArchiveMismatch");
+    } catch (e) {
+      assert.include(String(e), "ArchiveMismatch");
+    }
+
+    await restore(data);
+    assert.isNull(await provider.connection.getAccountInfo(archivePda));
+    const after = await program.account.userAccount.fetch(userPda);
+    assert.equal(after.wideningDelaySeconds, before.wideningDelaySeconds);
+    assert.equal(after.sessionKeyCapacity, 6);
+    assert.isTrue(after.authority.equals(authority.publicKey));
+
+    // Restored at full capacity, so keys can be created again
+    await program.methods
+      .createSessionKey(
+        Keypair.generate().publicKey,
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        { time: {} },
+        transferPermissions(new BN(0)),
+        null
+      )
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+  });
+});
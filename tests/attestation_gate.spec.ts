@@ -0,0 +1,203 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Attestation gate", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const feePayer: any = (provider.wallet as any).payer;
+
+  it("requires the recipient to hold the attestation token", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const issuer = Keypair.generate();
+    const kycMint = await createMint(
+      provider.connection,
+      feePayer,
+      issuer.publicKey,
+      null,
+      0
+    );
+
+    await program.methods
+      .setAttestationGate({ mint: kycMint, subject: { recipient: {} } })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const recipientKyc = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      feePayer,
+      kycMint,
+      ctx.recipient.publicKey
+    );
+
+    const transfer = (recipientAttestation: anchor.web3.PublicKey | null) =>
+      program.methods
+        .splDelegatedTransfer(new BN(1_000), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    try {
+      await transfer(null);
+      assert.fail("expected AttestationMissing");
+    } catch (e) {
+      assert.include(String(e), "AttestationMissing");
+    }
+
+    // Empty credential account is not enough
+    try {
+      await transfer(recipientKyc.address);
+      assert.fail("expected AttestationInvalid");
+    } catch (e) {
+      assert.include(String(e), "AttestationInvalid");
+    }
+
+    await mintTo(
+      provider.connection,
+      feePayer,
+      kycMint,
+      recipientKyc.address,
+      issuer,
+      1
+    );
+    await transfer(recipientKyc.address);
+  });
+
+  it("rejects credentials of another holder or mint", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const issuer = Keypair.generate();
+    const newMint = () =>
+      createMint(provider.connection, feePayer, issuer.publicKey, null, 0);
+    const kycMint = await newMint();
+    const otherMint = await newMint();
+    const credential = async (mint: PublicKey, holder: PublicKey) => {
+      const account = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        feePayer,
+        mint,
+        holder
+      );
+      await mintTo(
+        provider.connection,
+        feePayer,
+        mint,
+        account.address,
+        issuer,
+        1
+      );
+      return account.address;
+    };
+    const sessionKyc = await credential(kycMint, ctx.session.publicKey);
+    const recipientKyc = await credential(kycMint, ctx.recipient.publicKey);
+    const recipientOther = await credential(otherMint, ctx.recipient.publicKey);
+
+    await program.methods
+      .setAttestationGate({ mint: kycMint, subject: { both: {} } })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const transfer = (
+      sessionAttestation: PublicKey | null,
+      recipientAttestation: PublicKey
+    ) =>
+      program.methods
+        .splDelegatedTransfer(new BN(1_000), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation,
+          recipientAttestation,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+    const expectRefused = async (run: () => Promise<unknown>, code: string) => {
+      try {
+        await run();
+        assert.fail(`expected ${code}`);
+      } catch (e) {
+        assert.include(String(e), code);
+      }
+    };
+
+    // With both subjects gated, the session holder needs its own credential
+    await expectRefused(
+      () => transfer(null, recipientKyc),
+      "AttestationMissing"
+    );
+    // The recipient's credential doesn't vouch for the session key
+    await expectRefused(
+      () => transfer(recipientKyc, recipientKyc),
+      "AttestationInvalid"
+    );
+    // A funded token account of another mint isn't a credential
+    await expectRefused(
+      () => transfer(sessionKyc, recipientOther),
+      "AttestationInvalid"
+    );
+
+    await transfer(sessionKyc, recipientKyc);
+  });
+});
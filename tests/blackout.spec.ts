@@ -0,0 +1,84 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Blackout calendar", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("refuses session transfers inside a blackout window", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const setWindows = (windows: { start: number; end: number }[]) =>
+      program.methods
+        .setBlackoutWindows(
+          windows.map((w) => ({ start: new BN(w.start), end: new BN(w.end) }))
+        )
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const transfer = (amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    const now = Math.floor(Date.now() / 1000);
+    try {
+      await setWindows([{ start: now + 10, end: now + 10 }]);
+      assert.fail("expected InvalidBlackoutWindow");
+    } catch (e) {
+      assert.include(String(e), "InvalidBlackoutWindow");
+    }
+
+    // A future window doesn't block today's transfers
+    await setWindows([{ start: now + 86_400, end: now + 90_000 }]);
+    await transfer(1);
+
+    await setWindows([
+      { start: now + 86_400, end: now + 90_000 },
+      { start: now - 60, end: now + 3600 },
+    ]);
+    try {
+      await transfer(2);
+      assert.fail("expected BlackoutActive");
+    } catch (e) {
+      assert.include(String(e), "BlackoutActive");
+    }
+
+    await setWindows([]);
+    await transfer(2);
+  });
+});
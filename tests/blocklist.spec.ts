@@ -0,0 +1,100 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, EventParser } from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, getAccount } from "@solana/spl-token";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+import { programDataAddress } from "../app/verify_deployment";
+
+describe("Recipient blocklist", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("fails transfers to a blocklisted recipient", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [blocklist] = PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist")],
+      program.programId
+    );
+    if (!(await program.account.blocklistRegistry.fetchNullable(blocklist))) {
+      // anchor test deploys with the provider wallet as upgrade authority
+      await program.methods
+        .initializeBlocklist()
+        .accountsStrict({
+          blocklist,
+          admin: provider.wallet.publicKey,
+          program: program.programId,
+          programData: programDataAddress(program.programId),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+    const updateBlocklist = (additions: PublicKey[], removals: PublicKey[]) =>
+      program.methods
+        .updateBlocklist(additions, removals)
+        .accountsStrict({ blocklist, admin: provider.wallet.publicKey })
+        .rpc();
+    await updateBlocklist([ctx.recipient.publicKey], []);
+    await program.methods
+      .setBlocklistEnforcement(true)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    try {
+      await program.methods
+        .splDelegatedTransfer(new BN(1_000), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+      assert.fail("expected RecipientBlocked");
+    } catch (e) {
+      assert.include(String(e), "RecipientBlocked");
+      // The refusal is still reported in the failed transaction's logs
+      const parser = new EventParser(program.programId, program.coder);
+      const names = Array.from(parser.parseLogs((e as any).logs ?? [])).map(
+        (event) => event.name
+      );
+      assert.include(names, "transferBlocked");
+    } finally {
+      // Later specs share the validator
+      await updateBlocklist([], [ctx.recipient.publicKey]);
+    }
+
+    const recipientToken = await getAccount(
+      provider.connection,
+      ctx.recipientAta
+    );
+    assert.equal(Number(recipientToken.amount), 0);
+  });
+});
@@ -0,0 +1,84 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  deriveUserPda,
+  transferPermissions,
+} from "./helpers";
+
+describe("Cleanup retention", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("keeps revoked keys for the retention window", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .setCleanupRetention(3)
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+
+    const session = Keypair.generate().publicKey;
+    await program.methods
+      .createSessionKey(
+        session,
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        { time: {} },
+        transferPermissions(new BN(0)),
+        null
+      )
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .revokeSessionKey(session, { manual: {} })
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+
+    const stored = async () =>
+      (await program.account.userAccount.fetch(userPda)).sessionKeys;
+    const key = (await stored())[0];
+    assert.isAbove(key.revokedAt.toNumber(), 0);
+
+    const cleanup = () =>
+      program.methods
+        .cleanupSessionKeys(0)
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    await cleanup();
+    assert.lengthOf(await stored(), 1);
+
+    await new Promise((resolve) => setTimeout(resolve, 4_000));
+    await cleanup();
+    assert.lengthOf(await stored(), 0);
+  });
+});
@@ -0,0 +1,78 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { setupDelegatedSession } from "./helpers";
+
+describe("Close user account", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("refunds rent once sessions and delegates are gone", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const close = () =>
+      program.methods
+        .closeUserAccount()
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: ctx.ownerAta, isSigner: false, isWritable: false },
+        ])
+        .signers([ctx.authority])
+        .rpc();
+
+    let userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    assert.isTrue(userAccount.delegatedTokenAccounts[0].equals(ctx.ownerAta));
+
+    try {
+      await close();
+      assert.fail("expected SessionKeysStillActive");
+    } catch (e) {
+      assert.include(String(e), "SessionKeysStillActive");
+    }
+
+    await program.methods
+      .revokeAllSessionKeys()
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    try {
+      await close();
+      assert.fail("expected DelegationsStillActive");
+    } catch (e) {
+      assert.include(String(e), "DelegationsStillActive");
+    }
+
+    await program.methods
+      .splRevokeDelegate()
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        tokenAccount: ctx.ownerAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    assert.lengthOf(userAccount.delegatedTokenAccounts, 0);
+
+    const rent = await provider.connection.getBalance(ctx.userPda);
+    const before = await provider.connection.getBalance(
+      ctx.authority.publicKey
+    );
+    await close();
+    const after = await provider.connection.getBalance(ctx.authority.publicKey);
+
+    assert.isNull(await provider.connection.getAccountInfo(ctx.userPda));
+    // The provider wallet pays the fee, so the whole balance comes back
+    assert.equal(after - before, rent);
+  });
+});
@@ -0,0 +1,104 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { COMPUTE_UNIT_HINTS } from "../app/compute_budget";
+import {
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Compute budget hints", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  // Outermost "consumed" line for our program includes any CPIs it made
+  function consumed(logs: readonly string[]): number {
+    const pattern = new RegExp(
+      `Program ${program.programId.toBase58()} consumed (\\d+) of`
+    );
+    const units = logs
+      .map((line) => pattern.exec(line))
+      .filter((m) => m !== null)
+      .map((m) => Number(m[1]));
+    assert.isNotEmpty(units, "no compute usage in logs");
+    return units[units.length - 1];
+  }
+
+  it("stays within the hints for session and transfer instructions", async () => {
+    const ctx = await setupDelegatedSession(
+      provider,
+      program,
+      new BN(1_000_000)
+    );
+
+    const createSessionKey = () =>
+      program.methods
+        .createSessionKey(
+          Keypair.generate().publicKey,
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          { time: {} },
+          transferPermissions(new BN(0)),
+          null
+        )
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority]);
+
+    // Fill the account so the duplicate scan runs over the longest list
+    for (let i = 0; i < 4; i++) {
+      await createSessionKey().rpc();
+    }
+    const create = await createSessionKey().simulate();
+    assert.isAtMost(consumed(create.raw), COMPUTE_UNIT_HINTS.createSessionKey);
+
+    const { raw } = await program.methods
+      .splDelegatedTransfer(new BN(1_000), null, null, null, null)
+      .accountsStrict({
+        sessionSigner: ctx.session.publicKey,
+        userAccount: ctx.userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
+        sessionKeyAccount: null,
+        policy: null,
+        fromToken: ctx.ownerAta,
+        toToken: ctx.recipientAta,
+        mint: ctx.mint,
+        delegateAuthority: ctx.delegateAuth,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: null,
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: null,
+        activityDigest: null,
+        accountStats: null,
+        savingsVault: null,
+        feeToken: null,
+        slotHashes: null,
+        gasTank: null,
+        instructionsSysvar: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([ctx.session])
+      .simulate();
+    assert.isAtMost(consumed(raw), COMPUTE_UNIT_HINTS.splDelegatedTransfer);
+
+    const revoke = await program.methods
+      .revokeSessionKey(ctx.session.publicKey, { manual: {} })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .simulate();
+    assert.isAtMost(consumed(revoke.raw), COMPUTE_UNIT_HINTS.revokeSessionKey);
+  });
+});
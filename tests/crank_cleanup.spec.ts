@@ -0,0 +1,92 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  deriveUserPda,
+  transferPermissions,
+} from "./helpers";
+
+describe("Cleanup crank", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("pays anyone a bounty for removing expired keys", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 10))
+      .accountsStrict({
+        userAccount: userPda,
+        depositor: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const grant = (expiry: number, type: any) =>
+      program.methods
+        .createSessionKey(
+          Keypair.generate().publicKey,
+          new BN(expiry),
+          type,
+          transferPermissions(new BN(0)),
+          null
+        )
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    const slot = await provider.connection.getSlot();
+    await grant(slot + 5, { blockHeight: {} });
+    await grant(Math.floor(Date.now() / 1000) + 3600, { time: {} });
+    while ((await provider.connection.getSlot()) <= slot + 5) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+
+    // The provider wallet pays the fee, so the cranker's balance moves only by
+    // the bounty
+    const cranker = Keypair.generate();
+    await airdropLamports(provider.connection, cranker.publicKey, LAMPORTS);
+    const crank = () =>
+      program.methods
+        .crankCleanup(0)
+        .accountsStrict({ userAccount: userPda, cranker: cranker.publicKey })
+        .signers([cranker])
+        .rpc();
+    const before = await provider.connection.getBalance(cranker.publicKey);
+    await crank();
+    const after = await provider.connection.getBalance(cranker.publicKey);
+    assert.equal(after - before, 10_000);
+
+    const { sessionKeys } = await program.account.userAccount.fetch(userPda);
+    assert.lengthOf(sessionKeys, 1);
+    try {
+      await crank();
+      assert.fail("expected NothingToCleanUp");
+    } catch (e) {
+      assert.include(String(e), "NothingToCleanUp");
+    }
+  });
+});
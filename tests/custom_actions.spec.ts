@@ -0,0 +1,262 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+const MEMO_PROGRAM_ID = new PublicKey(
+  "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"
+);
+
+describe("Custom actions", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  const createCustomKey = async (
+    ctx: Awaited<ReturnType<typeof setupDelegatedSession>>,
+    allowedDiscriminators: {
+      program: PublicKey;
+      discriminator: number[];
+      len: number;
+    }[],
+    limits: { maxCpiDepth?: number; maxCustomAccounts?: number } = {}
+  ) => {
+    const session = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      session.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await program.methods
+      .createSessionKeyV2({
+        sessionPubkey: session.publicKey,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+        expiresAtSlot: null,
+        permissions: {
+          ...transferPermissions(new BN(0)),
+          canExecuteCustom: true,
+          ...limits,
+        },
+        termsHash: null,
+        label: Array(32).fill(0),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [MEMO_PROGRAM_ID],
+        allowedDiscriminators,
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    return session;
+  };
+
+  // The memo program requires every account it is given to sign, so a
+  // successful memo naming the executor proves the PDA signed the CPI
+  const executeFor =
+    (ctx: Awaited<ReturnType<typeof setupDelegatedSession>>) =>
+    (
+      signer: Keypair,
+      target: PublicKey,
+      memo = "rent paid",
+      extra: PublicKey[] = []
+    ) => {
+      const [executor] = PublicKey.findProgramAddressSync(
+        [Buffer.from("executor"), ctx.userPda.toBuffer()],
+        program.programId
+      );
+      return program.methods
+        .executeCustom(Buffer.from(memo), null, null)
+        .accountsStrict({
+          sessionSigner: signer.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          spendBudget: null,
+          executor,
+          slotHashes: null,
+        })
+        .remainingAccounts([
+          { pubkey: target, isSigner: false, isWritable: false },
+          { pubkey: executor, isSigner: false, isWritable: false },
+          ...extra.map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: false,
+          })),
+        ])
+        .signers([signer])
+        .rpc();
+    };
+
+  it("only calls programs on the session's allowlist", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = await createCustomKey(ctx, []);
+    const execute = executeFor(ctx);
+
+    await execute(session, MEMO_PROGRAM_ID);
+
+    try {
+      await execute(session, SystemProgram.programId);
+      assert.fail("expected ProgramNotAllowed");
+    } catch (e) {
+      assert.include(String(e), "ProgramNotAllowed");
+    }
+
+    // The helper's session key can transfer but not execute custom actions
+    try {
+      await execute(ctx.session, MEMO_PROGRAM_ID);
+      assert.fail("expected InsufficientPermissions");
+    } catch (e) {
+      assert.include(String(e), "InsufficientPermissions");
+    }
+
+    const userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    const entry = userAccount.sessionKeys.find((k) =>
+      k.pubkey.equals(session.publicKey)
+    );
+    assert.equal(entry.useCount, 1);
+  });
+
+  it("restricts custom actions to allowed discriminators", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = await createCustomKey(ctx, [
+      {
+        program: MEMO_PROGRAM_ID,
+        discriminator: Array.from(Buffer.from("rent pai")),
+        len: 8,
+      },
+    ]);
+    const execute = executeFor(ctx);
+
+    await execute(session, MEMO_PROGRAM_ID, "rent paid for May");
+    try {
+      await execute(session, MEMO_PROGRAM_ID, "withdraw everything");
+      assert.fail("expected InstructionNotAllowed");
+    } catch (e) {
+      assert.include(String(e), "InstructionNotAllowed");
+    }
+  });
+
+  it("matches one-byte tags for native programs", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = await createCustomKey(ctx, [
+      {
+        program: MEMO_PROGRAM_ID,
+        discriminator: ["r".charCodeAt(0), 0, 0, 0, 0, 0, 0, 0],
+        len: 1,
+      },
+    ]);
+    const execute = executeFor(ctx);
+
+    await execute(session, MEMO_PROGRAM_ID, "refund");
+    try {
+      await execute(session, MEMO_PROGRAM_ID, "withdraw");
+      assert.fail("expected InstructionNotAllowed");
+    } catch (e) {
+      assert.include(String(e), "InstructionNotAllowed");
+    }
+  });
+
+  it("caps CPI depth and forwarded accounts", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    // Top-level only, with the executor as the single forwarded account
+    const session = await createCustomKey(ctx, [], {
+      maxCpiDepth: 1,
+      maxCustomAccounts: 1,
+    });
+    const execute = executeFor(ctx);
+
+    await execute(session, MEMO_PROGRAM_ID);
+    try {
+      await execute(session, MEMO_PROGRAM_ID, "rent paid", [
+        Keypair.generate().publicKey,
+      ]);
+      assert.fail("expected TooManyCustomAccounts");
+    } catch (e) {
+      assert.include(String(e), "TooManyCustomAccounts");
+    }
+  });
+
+  it("refuses custom actions while token-only controls are on", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = await createCustomKey(ctx, []);
+    const execute = executeFor(ctx);
+    const setEnforcement = (enabled: boolean) =>
+      program.methods
+        .setBlocklistEnforcement(enabled)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+
+    // The blocklist has no recipient to screen, so the action isn't run at all
+    await setEnforcement(true);
+    try {
+      await execute(session, MEMO_PROGRAM_ID);
+      assert.fail("expected CustomActionUnsupported");
+    } catch (e) {
+      assert.include(String(e), "CustomActionUnsupported");
+    }
+
+    await setEnforcement(false);
+    await execute(session, MEMO_PROGRAM_ID);
+
+    // Nor can the operator countersign one
+    const setSignatureRequired = (required: boolean) =>
+      program.methods
+        .setOperatorSignatureRequired(required)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    await setSignatureRequired(true);
+    try {
+      await execute(session, MEMO_PROGRAM_ID);
+      assert.fail("expected CustomActionUnsupported");
+    } catch (e) {
+      assert.include(String(e), "CustomActionUnsupported");
+    } finally {
+      await setSignatureRequired(false);
+    }
+  });
+
+  it("rejects filters for programs the key can't call", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    for (const filter of [
+      {
+        program: SystemProgram.programId,
+        discriminator: Array(8).fill(0),
+        len: 4,
+      },
+      { program: MEMO_PROGRAM_ID, discriminator: Array(8).fill(0), len: 0 },
+    ]) {
+      try {
+        await createCustomKey(ctx, [filter]);
+        assert.fail("expected InvalidDiscriminatorFilter");
+      } catch (e) {
+        assert.include(String(e), "InvalidDiscriminatorFilter");
+      }
+    }
+  });
+});
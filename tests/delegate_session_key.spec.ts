@@ -0,0 +1,366 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  deriveUserPda,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Delegated sub-keys", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  const grant = (
+    sessionPubkey: PublicKey,
+    expiresAtTime: number,
+    permissions: ReturnType<typeof transferPermissions>
+  ) => ({
+    sessionPubkey,
+    expiresAtTime: new BN(expiresAtTime),
+    expiresAtSlot: null,
+    permissions,
+    termsHash: null,
+    label: Array(32).fill(0),
+    maxUses: 0,
+    validFrom: new BN(0),
+    allowedRecipients: [],
+    allowedMints: [],
+    allowedPrograms: [],
+    allowedDiscriminators: [],
+    idleTimeout: 0,
+    scope: PublicKey.default,
+    domainHash: null,
+  });
+
+  it("keeps sub-keys within their parent and revokes them", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(4)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 10))
+      .accountsStrict({
+        userAccount: userPda,
+        depositor: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const now = Math.floor(Date.now() / 1000);
+    const parent = Keypair.generate();
+    await program.methods
+      .createSessionKeyV2(
+        grant(parent.publicKey, now + 3600, {
+          ...transferPermissions(new BN(10_000)),
+          canDelegate: true,
+        })
+      )
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const child = Keypair.generate();
+    const delegate = (signer: Keypair, args: ReturnType<typeof grant>) =>
+      program.methods
+        .delegateSessionKey(args)
+        .accountsStrict({
+          sessionSigner: signer.publicKey,
+          userAccount: userPda,
+          sessionKeyAccount: null,
+        })
+        .signers([signer])
+        .rpc();
+    const expectWidens = async (args: ReturnType<typeof grant>) => {
+      try {
+        await delegate(parent, args);
+        assert.fail("expected DelegationWidens");
+      } catch (e) {
+        assert.include(String(e), "DelegationWidens");
+      }
+    };
+
+    // More than the parent has left, uncapped, outliving it, or a new power
+    await expectWidens(
+      grant(child.publicKey, now + 600, transferPermissions(new BN(20_000)))
+    );
+    await expectWidens(
+      grant(child.publicKey, now + 600, transferPermissions(new BN(0)))
+    );
+    await expectWidens(
+      grant(child.publicKey, now + 7200, transferPermissions(new BN(4_000)))
+    );
+    await expectWidens(
+      grant(child.publicKey, now + 600, {
+        ...transferPermissions(new BN(4_000)),
+        canExecuteCustom: true,
+      })
+    );
+
+    await delegate(
+      parent,
+      grant(child.publicKey, now + 600, transferPermissions(new BN(4_000)))
+    );
+    let account = await program.account.userAccount.fetch(userPda);
+    const find = (key: PublicKey) =>
+      account.sessionKeys.find((k) => k.pubkey.equals(key))!;
+    assert.isTrue(find(child.publicKey).delegatedBy!.equals(parent.publicKey));
+    // The child's cap is reserved out of the parent's
+    assert.equal(find(parent.publicKey).totalSpent.toNumber(), 4_000);
+
+    const merchant = Keypair.generate().publicKey;
+    await program.methods
+      .sessionTransferSol(new BN(1_000), null, null)
+      .accountsStrict({
+        sessionSigner: child.publicKey,
+        userAccount: userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
+        sessionKeyAccount: null,
+        policy: null,
+        destination: merchant,
+        recipientConsent: null,
+        slotHashes: null,
+        treasury: null,
+      })
+      .signers([child])
+      .rpc();
+
+    // The child lacks canDelegate, so it can't go further
+    try {
+      await delegate(
+        child,
+        grant(
+          Keypair.generate().publicKey,
+          now + 300,
+          transferPermissions(new BN(1_000))
+        )
+      );
+      assert.fail("expected InsufficientPermissions");
+    } catch (e) {
+      assert.include(String(e), "InsufficientPermissions");
+    }
+
+    await program.methods
+      .revokeSessionKey(parent.publicKey, { compromised: {} })
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    account = await program.account.userAccount.fetch(userPda);
+    assert.isTrue(find(child.publicKey).isRevoked);
+    assert.deepEqual(find(child.publicKey).revocationReason, {
+      compromised: {},
+    });
+  });
+
+  it("limits chain depth and keeps chains across rotation", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const now = Math.floor(Date.now() / 1000);
+    const delegating = (cap: number) => ({
+      ...transferPermissions(new BN(cap)),
+      canDelegate: true,
+    });
+    const root = Keypair.generate();
+    await program.methods
+      .createSessionKeyV2(grant(root.publicKey, now + 3600, delegating(10_000)))
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const delegate = (signer: Keypair, args: ReturnType<typeof grant>) =>
+      program.methods
+        .delegateSessionKey(args)
+        .accountsStrict({
+          sessionSigner: signer.publicKey,
+          userAccount: userPda,
+          sessionKeyAccount: null,
+        })
+        .signers([signer])
+        .rpc();
+    const setDepth = (depth: number) =>
+      program.methods
+        .setMaxDelegationDepth(depth)
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    const expectTooDeep = async (attempt: () => Promise<string>) => {
+      try {
+        await attempt();
+        assert.fail("expected DelegationTooDeep");
+      } catch (e) {
+        assert.include(String(e), "DelegationTooDeep");
+      }
+    };
+
+    const child = Keypair.generate();
+    await delegate(root, grant(child.publicKey, now + 600, delegating(4_000)));
+
+    // New accounts allow direct sub-keys only
+    const grandchild = Keypair.generate();
+    const grandchildArgs = grant(
+      grandchild.publicKey,
+      now + 300,
+      transferPermissions(new BN(1_000))
+    );
+    await expectTooDeep(() => delegate(child, grandchildArgs));
+    await expectTooDeep(() => setDepth(5));
+
+    await setDepth(2);
+    await delegate(child, grandchildArgs);
+
+    // Rotating the root moves its sub-keys to the new key
+    const newRoot = Keypair.generate();
+    await program.methods
+      .rotateSessionKey(root.publicKey, newRoot.publicKey)
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    let account = await program.account.userAccount.fetch(userPda);
+    const find = (key: PublicKey) =>
+      account.sessionKeys.find((k) => k.pubkey.equals(key))!;
+    assert.isTrue(find(child.publicKey).delegatedBy!.equals(newRoot.publicKey));
+
+    await program.methods
+      .revokeSessionKey(newRoot.publicKey, { compromised: {} })
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    account = await program.account.userAccount.fetch(userPda);
+    assert.isTrue(find(child.publicKey).isRevoked);
+    assert.isTrue(find(grandchild.publicKey).isRevoked);
+  });
+
+  it("revokes sub-keys of a key stored in its own PDA", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(4)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const now = Math.floor(Date.now() / 1000);
+    const parent = Keypair.generate();
+    const [keyPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("session"), userPda.toBuffer(), parent.publicKey.toBuffer()],
+      program.programId
+    );
+    const createParent = () =>
+      program.methods
+        .createSessionKeyAccount(
+          grant(parent.publicKey, now + 3600, {
+            ...transferPermissions(new BN(0)),
+            canDelegate: true,
+          })
+        )
+        .accountsStrict({
+          sessionKeyAccount: keyPda,
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    const closeParent = () =>
+      program.methods
+        .closeSessionKeyAccount()
+        .accountsStrict({
+          sessionKeyAccount: keyPda,
+          userAccount: userPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    await createParent();
+    const delegate = (child: Keypair) =>
+      program.methods
+        .delegateSessionKey(
+          grant(child.publicKey, now + 600, transferPermissions(new BN(1_000)))
+        )
+        .accountsStrict({
+          sessionSigner: parent.publicKey,
+          userAccount: userPda,
+          sessionKeyAccount: keyPda,
+        })
+        .signers([parent])
+        .rpc();
+    const revoked = async (child: Keypair) =>
+      (await program.account.userAccount.fetch(userPda)).sessionKeys.find(
+        (k) => k.pubkey.equals(child.publicKey)
+      )!.isRevoked;
+
+    const first = Keypair.generate();
+    await delegate(first);
+    await program.methods
+      .revokeSessionKeyAccount({ compromised: {} })
+      .accountsStrict({
+        sessionKeyAccount: keyPda,
+        userAccount: userPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+    assert.isTrue(await revoked(first));
+
+    // Closing an active parent takes its sub-keys with it too
+    await closeParent();
+    await createParent();
+    const second = Keypair.generate();
+    await delegate(second);
+    await closeParent();
+    assert.isTrue(await revoked(second));
+  });
+});
@@ -0,0 +1,44 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  PROGRAM_ERRORS,
+  explainError,
+  programErrorMessage,
+} from "../app/errors";
+
+describe("Error catalog", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("covers every program error in every locale", () => {
+    const idlErrors = program.idl.errors.map((e) => [e.code, e.name]);
+    const catalog = PROGRAM_ERRORS.map((name, i) => [6000 + i, name]);
+    assert.deepEqual(catalog, idlErrors);
+
+    for (const [code] of idlErrors) {
+      for (const locale of ["en", "es", "fr"] as const) {
+        assert.ok(programErrorMessage(code as number, locale));
+      }
+    }
+  });
+
+  it("explains raw program and token failures", () => {
+    const sessionExpired = explainError(
+      new Error("failed: custom program error: 0x1776"),
+      "es"
+    );
+    assert.equal(sessionExpired.key, "SessionKeyExpired");
+    assert.isTrue(sessionExpired.isProgramError);
+
+    const insufficient = explainError({
+      logs: [
+        "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]",
+        "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA failed: custom program error: 0x1",
+      ],
+    });
+    assert.equal(insufficient.key, "token:1");
+  });
+});
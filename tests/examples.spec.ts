@@ -0,0 +1,229 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { SessionGame } from "../target/types/session_game";
+import { SessionCounter } from "../target/types/session_counter";
+import { SubscriptionMerchant } from "../target/types/subscription_merchant";
+import { assert } from "chai";
+import { getAccount, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Example integrations", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const game = anchor.workspace.SessionGame as Program<SessionGame>;
+  const counter = anchor.workspace.SessionCounter as Program<SessionCounter>;
+  const merchant = anchor.workspace
+    .SubscriptionMerchant as Program<SubscriptionMerchant>;
+
+  it("game accepts moves from flagged session keys", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [player] = PublicKey.findProgramAddressSync(
+      [Buffer.from("player"), ctx.userPda.toBuffer()],
+      game.programId
+    );
+    await game.methods
+      .initializePlayer()
+      .accountsStrict({
+        player,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const play = (signer: Keypair, points: number) =>
+      game.methods
+        .playMove(points)
+        .accountsStrict({
+          sessionSigner: signer.publicKey,
+          userAccount: ctx.userPda,
+          sessionKeyAccount: null,
+          player,
+          timeProgram: program.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    try {
+      await play(ctx.session, 10);
+      assert.fail("expected SessionRejected");
+    } catch (e) {
+      assert.include(String(e), "SessionRejected");
+    }
+
+    await program.methods
+      .updateSessionKey(ctx.session.publicKey, null, null, {
+        ...transferPermissions(new BN(0)),
+        customFlags: 1,
+        maxCpiDepth: 0,
+        maxCustomAccounts: 0,
+      })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    await play(ctx.session, 10);
+    await play(ctx.session, 5);
+
+    const stranger = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      stranger.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    try {
+      await play(stranger, 100);
+      assert.fail("expected SessionRejected");
+    } catch (e) {
+      assert.include(String(e), "SessionRejected");
+    }
+
+    const state = await game.account.player.fetch(player);
+    assert.equal(state.score.toNumber(), 15);
+    assert.equal(state.moves.toNumber(), 2);
+  });
+
+  it("merchant collects each period through a PDA session key", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [subscription] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("subscription"),
+        ctx.userPda.toBuffer(),
+        ctx.recipientAta.toBuffer(),
+      ],
+      merchant.programId
+    );
+    const [session] = PublicKey.findProgramAddressSync(
+      [Buffer.from("session"), subscription.toBuffer()],
+      merchant.programId
+    );
+
+    await merchant.methods
+      .createSubscription(new BN(100), new BN(3600))
+      .accountsStrict({
+        subscription,
+        session,
+        userAccount: ctx.userPda,
+        merchantToken: ctx.recipientAta,
+        mint: ctx.mint,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    await program.methods
+      .createSessionKey(
+        session,
+        new BN(Math.floor(Date.now() / 1000) + 86_400),
+        { time: {} },
+        transferPermissions(new BN(1_000)),
+        null
+      )
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const collect = () =>
+      merchant.methods
+        .collect()
+        .accountsStrict({
+          subscription,
+          session,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          fromToken: ctx.ownerAta,
+          merchantToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          feeToken: null,
+          timeProgram: program.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await collect();
+    const received = await getAccount(provider.connection, ctx.recipientAta);
+    assert.equal(Number(received.amount), 100);
+
+    // The next period isn't due for an hour
+    try {
+      await collect();
+      assert.fail("expected NotDue");
+    } catch (e) {
+      assert.include(String(e), "NotDue");
+    }
+
+    const userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    const entry = userAccount.sessionKeys.find((k) => k.pubkey.equals(session));
+    assert.equal(entry.totalSpent.toNumber(), 100);
+  });
+
+  it("counter accepts session keys time validates over CPI", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [counterPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("counter"), ctx.userPda.toBuffer()],
+      counter.programId
+    );
+    await counter.methods
+      .initializeCounter()
+      .accountsStrict({
+        counter: counterPda,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const increment = () =>
+      counter.methods
+        .increment()
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          sessionKeyAccount: null,
+          counter: counterPda,
+          timeProgram: program.programId,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    // The key lacks the counter's flag until the authority grants it
+    try {
+      await increment();
+      assert.fail("expected SessionRejected");
+    } catch (e) {
+      assert.include(String(e), "SessionRejected");
+    }
+
+    await program.methods
+      .updateSessionKey(ctx.session.publicKey, null, null, {
+        ...transferPermissions(new BN(0)),
+        customFlags: 1 << 1,
+      })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    await increment();
+    await increment();
+
+    const state = await counter.account.counter.fetch(counterPda);
+    assert.equal(state.count.toNumber(), 2);
+  });
+});
@@ -0,0 +1,98 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Gas tank", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("reimburses session fees from its own pool", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [gasTank] = PublicKey.findProgramAddressSync(
+      [Buffer.from("gas_tank"), ctx.userPda.toBuffer()],
+      program.programId
+    );
+    const initialize = (fee: number) =>
+      program.methods
+        .initializeGasTank(new BN(fee))
+        .accountsStrict({
+          gasTank,
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const transfer = () =>
+      program.methods
+        .splDelegatedTransfer(new BN(1), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    try {
+      await initialize(1_000_000);
+      assert.fail("expected InvalidGasFee");
+    } catch (e) {
+      assert.include(String(e), "InvalidGasFee");
+    }
+    await initialize(5_000);
+
+    // An empty tank doesn't block the transfer
+    await transfer();
+    let tank = await program.account.gasTank.fetch(gasTank);
+    assert.equal(tank.totalReimbursed.toNumber(), 0);
+
+    await program.methods
+      .topUpGasTank(new BN(20_000))
+      .accountsStrict({
+        gasTank,
+        userAccount: ctx.userPda,
+        payer: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    // The provider wallet pays the fee here, so the session signer simply
+    // ends up 5_000 lamports richer
+    const before = await provider.connection.getBalance(ctx.session.publicKey);
+    await transfer();
+    const after = await provider.connection.getBalance(ctx.session.publicKey);
+    assert.equal(after - before, 5_000);
+
+    tank = await program.account.gasTank.fetch(gasTank);
+    assert.equal(tank.totalToppedUp.toNumber(), 20_000);
+    assert.equal(tank.totalReimbursed.toNumber(), 5_000);
+  });
+});
@@ -0,0 +1,225 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  AccountMeta,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+} from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+// The memo program requires every account it is given to sign, and the user
+// account never does, so as a guard it vetoes every action
+const MEMO_PROGRAM_ID = new PublicKey(
+  "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"
+);
+
+describe("Guard program", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  const expectRefused = async (run: () => Promise<unknown>, code?: string) => {
+    try {
+      await run();
+    } catch (e) {
+      if (code) assert.include(String(e), code);
+      return;
+    }
+    assert.fail(`expected ${code ?? "the guard to veto"}`);
+  };
+
+  it("is consulted by SOL payouts and pipelines", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const setGuard = (guard: PublicKey | null) =>
+      program.methods
+        .setGuardProgram(guard)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const guardAccounts = (withGuard: boolean): AccountMeta[] =>
+      withGuard
+        ? [{ pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false }]
+        : [];
+
+    const withdraw = (withGuard: boolean) =>
+      program.methods
+        .sessionWithdrawSol(new BN(LAMPORTS / 100), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .remainingAccounts(guardAccounts(withGuard))
+        .signers([ctx.session])
+        .rpc();
+
+    const pipelineId = 1;
+    const id = Buffer.alloc(2);
+    id.writeUInt16LE(pipelineId);
+    const [pipeline] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pipeline"), ctx.userPda.toBuffer(), id],
+      program.programId
+    );
+    await program.methods
+      .createPipeline(pipelineId, [
+        {
+          transfer: {
+            mint: ctx.mint,
+            destination: ctx.recipientAta,
+            maxAmount: new BN(500),
+          },
+        },
+      ])
+      .accountsStrict({
+        pipeline,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const runPipeline = (withGuard: boolean) =>
+      program.methods
+        .executePipeline(
+          [{ amount: new BN(100), data: Buffer.alloc(0), accountCount: 0 }],
+          null,
+          null
+        )
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          pipeline,
+          spendBudget: null,
+          slotHashes: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          ...guardAccounts(withGuard),
+          { pubkey: ctx.ownerAta, isSigner: false, isWritable: true },
+          { pubkey: ctx.mint, isSigner: false, isWritable: false },
+          { pubkey: ctx.recipientAta, isSigner: false, isWritable: true },
+          { pubkey: ctx.delegateAuth, isSigner: false, isWritable: false },
+        ])
+        .signers([ctx.session])
+        .rpc();
+
+    await setGuard(MEMO_PROGRAM_ID);
+    await expectRefused(() => withdraw(false), "GuardProgramMissing");
+    await expectRefused(() => withdraw(true));
+    // The guard's slot comes first, so a run leaving it out is one account short
+    await expectRefused(() => runPipeline(false), "PipelineStepMismatch");
+    await expectRefused(() => runPipeline(true));
+
+    await setGuard(null);
+    await withdraw(false);
+    await runPipeline(false);
+  });
+
+  it("is consulted by custom actions", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = Keypair.generate();
+    await airdropLamports(provider.connection, session.publicKey, LAMPORTS);
+    await program.methods
+      .createSessionKeyV2({
+        sessionPubkey: session.publicKey,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+        expiresAtSlot: null,
+        permissions: {
+          ...transferPermissions(new BN(0)),
+          canExecuteCustom: true,
+        },
+        termsHash: null,
+        label: Array(32).fill(0),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [MEMO_PROGRAM_ID],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const setGuard = (guard: PublicKey | null) =>
+      program.methods
+        .setGuardProgram(guard)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+
+    const [executor] = PublicKey.findProgramAddressSync(
+      [Buffer.from("executor"), ctx.userPda.toBuffer()],
+      program.programId
+    );
+    const execute = (withGuard: boolean) =>
+      program.methods
+        .executeCustom(Buffer.from("rent paid"), null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          spendBudget: null,
+          executor,
+          slotHashes: null,
+        })
+        .remainingAccounts([
+          ...(withGuard
+            ? [{ pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false }]
+            : []),
+          { pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false },
+          { pubkey: executor, isSigner: false, isWritable: false },
+        ])
+        .signers([session])
+        .rpc();
+
+    await setGuard(MEMO_PROGRAM_ID);
+    // Left out, the guard's slot is taken by the target program and the
+    // target's by the executor
+    await expectRefused(() => execute(false), "ProgramNotAllowed");
+    await expectRefused(() => execute(true));
+
+    await setGuard(null);
+    await execute(false);
+  });
+});
@@ -0,0 +1,83 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { airdropLamports, setupDelegatedSession } from "./helpers";
+
+describe("Guardian", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("lets the guardian revoke every key and nothing else", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const guardian = Keypair.generate();
+    await airdropLamports(provider.connection, guardian.publicKey, LAMPORTS);
+
+    const revokeAll = (signer: Keypair) =>
+      program.methods
+        .guardianRevokeAll()
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          guardian: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    // No guardian designated yet
+    try {
+      await revokeAll(guardian);
+      assert.fail("expected NotGuardian");
+    } catch (e) {
+      assert.include(String(e), "NotGuardian");
+    }
+
+    await program.methods
+      .setGuardian(guardian.publicKey)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    let account = await program.account.userAccount.fetch(ctx.userPda);
+    assert.isTrue(account.guardian.equals(guardian.publicKey));
+
+    // The guardian can't change the setting itself
+    try {
+      await program.methods
+        .setGuardian(null)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: guardian.publicKey,
+        })
+        .signers([guardian])
+        .rpc();
+      assert.fail("expected the authority check to fail");
+    } catch (e) {
+      assert.match(String(e), /ConstraintHasOne|has.one/i);
+    }
+
+    const stranger = Keypair.generate();
+    await airdropLamports(provider.connection, stranger.publicKey, LAMPORTS);
+    try {
+      await revokeAll(stranger);
+      assert.fail("expected NotGuardian");
+    } catch (e) {
+      assert.include(String(e), "NotGuardian");
+    }
+
+    const generation = account.sessionGeneration;
+    await revokeAll(guardian);
+    account = await program.account.userAccount.fetch(ctx.userPda);
+    assert.isTrue(account.sessionKeys.every((k) => k.isRevoked));
+    assert.deepEqual(account.sessionKeys[0].revocationReason, {
+      compromised: {},
+    });
+    assert.equal(account.sessionGeneration, generation + 1);
+    assert.isFalse(account.isPaused);
+    assert.isTrue(account.guardian.equals(guardian.publicKey));
+  });
+});
@@ -1,9 +1,19 @@
 import * as anchor from "@coral-xyz/anchor";
-import { PublicKey, Keypair, Connection } from "@solana/web3.js";
 import {
+  PublicKey,
+  Keypair,
+  Connection,
+  SystemProgram,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
   createMint,
   getOrCreateAssociatedTokenAccount,
+  mintTo,
 } from "@solana/spl-token";
+import { assert } from "chai";
+import { existsSync, mkdirSync, readFileSync, writeFileSync } from "fs";
+import * as path from "path";
 
 export async function airdropLamports(
   connection: Connection,
@@ -21,6 +31,13 @@ export function deriveUserPda(programId: PublicKey, authority: PublicKey) {
   );
 }
 
+export function deriveProgramConfigPda(programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("program_config")],
+    programId
+  )[0];
+}
+
 export async function createMintAndAtas(
   connection: Connection,
   feePayer: Keypair,
@@ -48,3 +65,189 @@ export async function createMintAndAtas(
   };
 }
 
+
+export function transferPermissions(maxTransferAmount: anchor.BN) {
+  return {
+    canTransfer: true,
+    canDelegate: false,
+    canExecuteCustom: false,
+    canSelfRotate: false,
+    maxTransferAmount,
+    dailyLimit: new anchor.BN(0),
+    spendWindowSeconds: 0,
+    maxUsesPerWindow: 0,
+    useWindowSeconds: 0,
+    customFlags: 0,
+    maxCpiDepth: 0,
+    maxCustomAccounts: 0,
+  };
+}
+
+// Initializes a user account, funds a fresh mint, approves the delegate PDA and
+// registers a transfer-capable session key funded for fees.
+export async function setupDelegatedSession(
+  provider: anchor.AnchorProvider,
+  program: anchor.Program<any>,
+  maxTransferAmount = new anchor.BN(0)
+) {
+  const authority = Keypair.generate();
+  await airdropLamports(
+    provider.connection,
+    authority.publicKey,
+    2 * anchor.web3.LAMPORTS_PER_SOL
+  );
+  const [userPda] = await deriveUserPda(program.programId, authority.publicKey);
+  await program.methods
+    .initializeUserAccount(6)
+    .accountsStrict({
+      userAccount: userPda,
+      authority: authority.publicKey,
+      payer: authority.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+
+  const recipient = Keypair.generate();
+  const feePayer: any = (provider.wallet as any).payer;
+  const { mint, ownerAta, recipientAta } = await createMintAndAtas(
+    provider.connection,
+    feePayer,
+    authority.publicKey,
+    recipient.publicKey,
+    6
+  );
+  await mintTo(
+    provider.connection,
+    authority,
+    mint,
+    ownerAta,
+    authority.publicKey,
+    1_000_000_000n
+  );
+
+  const [delegateAuth] = PublicKey.findProgramAddressSync(
+    [Buffer.from("delegate"), userPda.toBuffer(), mint.toBuffer()],
+    program.programId
+  );
+  await program.methods
+    .splApproveDelegate(new anchor.BN(1_000_000_000))
+    .accountsStrict({
+      userAccount: userPda,
+      programConfig: deriveProgramConfigPda(program.programId),
+      authority: authority.publicKey,
+      tokenAccount: ownerAta,
+      mint,
+      delegateAuthority: delegateAuth,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([authority])
+    .rpc();
+
+  const session = Keypair.generate();
+  await program.methods
+    .createSessionKey(
+      session.publicKey,
+      new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+      { time: {} },
+      transferPermissions(maxTransferAmount),
+      null
+    )
+    .accountsStrict({
+      userAccount: userPda,
+      authority: authority.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+  await airdropLamports(
+    provider.connection,
+    session.publicKey,
+    anchor.web3.LAMPORTS_PER_SOL
+  );
+
+  return {
+    authority,
+    userPda,
+    recipient,
+    mint,
+    ownerAta,
+    recipientAta,
+    delegateAuth,
+    session,
+  };
+}
+
+// Fields that depend on when or with which keys a scenario ran; snapshots
+// record only whether they are set
+const VOLATILE_FIELDS = [
+  "bump",
+  "createdAt",
+  "expiresAtTime",
+  "expiresAtSlot",
+  "validFrom",
+  "spendWindowStart",
+  "useWindowStart",
+  "failureWindowStart",
+  "lastUsedAt",
+  "maxExpiresAt",
+  "revokedAt",
+  "wideningActiveAt",
+  "updatedAt",
+  "timestamp",
+];
+
+// Account state reduced to what should be identical on every run: pubkeys
+// become the names in `names` (or "<pubkey>"), numbers become strings and
+// volatile fields become "<set>" or "<unset>"
+export function normalizeSnapshot(
+  value: any,
+  names: Record<string, string>,
+  field?: string
+): any {
+  if (field !== undefined && VOLATILE_FIELDS.indexOf(field) !== -1) {
+    const unset =
+      value === null ||
+      value === 0 ||
+      (anchor.BN.isBN(value) && value.isZero());
+    return unset ? "<unset>" : "<set>";
+  }
+  if (value === null || value === undefined) return null;
+  if (value instanceof PublicKey) {
+    return names[value.toBase58()] ?? "<pubkey>";
+  }
+  if (anchor.BN.isBN(value)) return value.toString();
+  if (Array.isArray(value)) {
+    return value.map((item) => normalizeSnapshot(item, names));
+  }
+  if (typeof value === "object") {
+    const out: Record<string, any> = {};
+    for (const key of Object.keys(value).sort()) {
+      out[key] = normalizeSnapshot(value[key], names, key);
+    }
+    return out;
+  }
+  return value;
+}
+
+// Compare `actual` with tests/snapshots/<name>.json. A missing snapshot is
+// recorded (outside CI); UPDATE_SNAPSHOTS=1 re-records an intended change.
+export function matchSnapshot(name: string, actual: any) {
+  const file = path.join(__dirname, "snapshots", `${name}.json`);
+  const json = JSON.stringify(actual, null, 2) + "\n";
+  if (process.env.UPDATE_SNAPSHOTS || (!existsSync(file) && !process.env.CI)) {
+    mkdirSync(path.dirname(file), { recursive: true });
+    writeFileSync(file, json);
+    return;
+  }
+  assert.isTrue(
+    existsSync(file),
+    `missing snapshot tests/snapshots/${name}.json`
+  );
+  assert.deepEqual(
+    JSON.parse(json),
+    JSON.parse(readFileSync(file, "utf8")),
+    `${name} drifted from tests/snapshots/${name}.json; ` +
+      "rerun with UPDATE_SNAPSHOTS=1 if the change is intended"
+  );
+}
@@ -1,9 +1,13 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
-import { SystemProgram, Keypair } from "@solana/web3.js";
+import { Program, BN } from "@coral-xyz/anchor";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
 import { Time } from "../target/types/time";
 import { assert } from "chai";
-import { airdropLamports, deriveUserPda } from "./helpers";
+import {
+  airdropLamports,
+  deriveUserPda,
+  transferPermissions,
+} from "./helpers";
 
 describe("User Account Initialization ", () => {
   const provider = anchor.AnchorProvider.env();
@@ -23,10 +27,11 @@ describe("User Account Initialization ", () => {
     );
 
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -49,10 +54,11 @@ describe("User Account Initialization ", () => {
     );
 
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -60,10 +66,11 @@ describe("User Account Initialization ", () => {
 
     try {
       await program.methods
-        .initializeUserAccount()
+        .initializeUserAccount(6)
         .accountsStrict({
           userAccount: userPda,
           authority: authority.publicKey,
+          payer: authority.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([authority])
@@ -73,4 +80,176 @@ describe("User Account Initialization ", () => {
       assert(true);
     }
   });
+
+  it("sizes the account for the requested session key capacity", async () => {
+    const init = async (maxSessionKeys: number) => {
+      const authority = Keypair.generate();
+      await airdropLamports(
+        provider.connection,
+        authority.publicKey,
+        1 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      const [userPda] = await deriveUserPda(
+        program.programId,
+        authority.publicKey
+      );
+      await program.methods
+        .initializeUserAccount(maxSessionKeys)
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          payer: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      return userPda;
+    };
+
+    try {
+      await init(0);
+      assert.fail("expected InvalidSessionKeyCapacity");
+    } catch (e) {
+      assert.include(String(e), "InvalidSessionKeyCapacity");
+    }
+
+    const small = await init(2);
+    const large = await init(7);
+    const acct = await program.account.userAccount.fetch(small);
+    assert.equal(acct.sessionKeyCapacity, 2);
+    const size = async (pda) =>
+      (await provider.connection.getAccountInfo(pda)).data.length;
+    assert.isBelow(await size(small), await size(large));
+  });
+
+  it("registers the first session key with the account", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      1 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    const session = Keypair.generate();
+    const init = (expiresAtTime: number) =>
+      program.methods
+        .initializeWithSessionKey(4, {
+          sessionPubkey: session.publicKey,
+          expiresAtTime: new BN(expiresAtTime),
+          expiresAtSlot: null,
+          permissions: transferPermissions(new BN(0)),
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          payer: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+    // An invalid key leaves no account behind
+    try {
+      await init(Math.floor(Date.now() / 1000) - 60);
+      assert.fail("expected InvalidExpiry");
+    } catch (e) {
+      assert.include(String(e), "InvalidExpiry");
+    }
+    assert.isNull(await provider.connection.getAccountInfo(userPda));
+
+    await init(Math.floor(Date.now() / 1000) + 3600);
+    const acct = await program.account.userAccount.fetch(userPda);
+    assert.equal(acct.sessionKeyCapacity, 4);
+    assert.lengthOf(acct.sessionKeys, 1);
+    assert.isTrue(acct.sessionKeys[0].pubkey.equals(session.publicKey));
+  });
+
+  it("creates the account on the fly for a first session key", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      1 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    const grant = (session: PublicKey) =>
+      program.methods
+        .createSessionKeyInitIfNeeded({
+          sessionPubkey: session,
+          expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+          expiresAtSlot: null,
+          permissions: transferPermissions(new BN(0)),
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          payer: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+    const first = Keypair.generate().publicKey;
+    await grant(first);
+    let acct = await program.account.userAccount.fetch(userPda);
+    assert.isTrue(acct.authority.equals(authority.publicKey));
+    assert.equal(acct.sessionKeyCapacity, 6);
+
+    // The existing account is reused, keeping its keys
+    await grant(Keypair.generate().publicKey);
+    acct = await program.account.userAccount.fetch(userPda);
+    assert.lengthOf(acct.sessionKeys, 2);
+    assert.isTrue(acct.sessionKeys.some((k) => k.pubkey.equals(first)));
+  });
+
+  it("lets a sponsor pay the rent for the user's account", async () => {
+    // The user holds no lamports; the provider wallet pays fees and rent
+    const authority = Keypair.generate();
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(4)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const acct = await program.account.userAccount.fetch(userPda);
+    assert.isTrue(acct.authority.equals(authority.publicKey));
+    assert.equal(await provider.connection.getBalance(authority.publicKey), 0);
+  });
 });
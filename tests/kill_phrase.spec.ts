@@ -0,0 +1,105 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { killPhraseCommitment } from "../app/sdk";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Kill phrase", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("lets anyone with the phrase revoke every key and pause", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const phrase = "correct horse battery staple";
+    await program.methods
+      .setKillPhrase(killPhraseCommitment(ctx.userPda, phrase))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    // Support staff with no key on the account
+    const helper = Keypair.generate();
+    await airdropLamports(provider.connection, helper.publicKey, LAMPORTS);
+    const trigger = (text: string) =>
+      program.methods
+        .triggerKillPhrase(Buffer.from(text, "utf8"))
+        .accountsStrict({ userAccount: ctx.userPda, caller: helper.publicKey })
+        .signers([helper])
+        .rpc();
+
+    try {
+      await trigger("wrong phrase");
+      assert.fail("expected KillPhraseMismatch");
+    } catch (e) {
+      assert.include(String(e), "KillPhraseMismatch");
+    }
+    await trigger(phrase);
+
+    let account = await program.account.userAccount.fetch(ctx.userPda);
+    assert.isTrue(account.isPaused);
+    assert.deepEqual(account.killPhraseHash, new Array(32).fill(0));
+    assert.isTrue(account.sessionKeys.every((k) => k.isRevoked));
+    assert.deepEqual(account.sessionKeys[0].revocationReason, {
+      compromised: {},
+    });
+
+    try {
+      await program.methods
+        .sessionTransferSol(new BN(1_000), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: helper.publicKey,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+      assert.fail("expected AccountPaused");
+    } catch (e) {
+      assert.include(String(e), "AccountPaused");
+    }
+    try {
+      await trigger(phrase);
+      assert.fail("expected KillPhraseNotSet");
+    } catch (e) {
+      assert.include(String(e), "KillPhraseNotSet");
+    }
+
+    await program.methods
+      .setAccountPaused(false)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    account = await program.account.userAccount.fetch(ctx.userPda);
+    assert.isFalse(account.isPaused);
+  });
+});
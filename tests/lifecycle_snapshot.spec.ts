@@ -0,0 +1,143 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { encodeLabel } from "../app/sdk";
+import {
+  airdropLamports,
+  deriveUserPda,
+  matchSnapshot,
+  normalizeSnapshot,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+// Records the full account state after each step of a canonical lifecycle and
+// diffs it against tests/snapshots/lifecycle.json, so layout or behavior
+// changes show up as a reviewed snapshot update
+describe("Lifecycle snapshot", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("onboard, grant, spend, expire, cleanup, close", async () => {
+    const authority = Keypair.generate();
+    const session = Keypair.generate();
+    const merchant = Keypair.generate().publicKey;
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    const names = {
+      [authority.publicKey.toBase58()]: "authority",
+      [session.publicKey.toBase58()]: "session",
+      [merchant.toBase58()]: "merchant",
+      [userPda.toBase58()]: "userAccount",
+    };
+    const steps: Record<string, any> = {};
+    const record = async (step: string) => {
+      const account = await program.account.userAccount.fetchNullable(
+        userPda
+      );
+      steps[step] = normalizeSnapshot(account, names);
+    };
+
+    // Onboard
+    await program.methods
+      .initializeUserAccount(4)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 10))
+      .accountsStrict({
+        userAccount: userPda,
+        depositor: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await record("onboard");
+
+    // Grant a short, slot-bounded key
+    const slot = await provider.connection.getSlot();
+    const expiresAtSlot = slot + 20;
+    await program.methods
+      .createSessionKeyV2({
+        sessionPubkey: session.publicKey,
+        expiresAtTime: null,
+        expiresAtSlot: new BN(expiresAtSlot),
+        permissions: transferPermissions(new BN(LAMPORTS / 20)),
+        termsHash: null,
+        label: encodeLabel("snapshot"),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [merchant],
+        allowedMints: [],
+        allowedPrograms: [],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await record("grant");
+
+    // Spend
+    await program.methods
+      .sessionTransferSol(new BN(LAMPORTS / 100), null, null)
+      .accountsStrict({
+        sessionSigner: session.publicKey,
+        userAccount: userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
+        sessionKeyAccount: null,
+        policy: null,
+        destination: merchant,
+        recipientConsent: null,
+        slotHashes: null,
+        treasury: null,
+      })
+      .signers([session])
+      .rpc();
+    await record("spend");
+
+    // Expire
+    while ((await provider.connection.getSlot()) <= expiresAtSlot) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    await record("expire");
+
+    // Cleanup
+    await program.methods
+      .cleanupSessionKeys(0)
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await record("cleanup");
+
+    // Close
+    await program.methods
+      .closeUserAccount()
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await record("close");
+
+    assert.isNull(steps.close);
+    matchSnapshot("lifecycle", steps);
+  });
+});
@@ -0,0 +1,151 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, EventParser } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Session key lockout", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("locks a key after repeated over-limit spends", async () => {
+    // Lifetime cap of 10,000 lamports
+    const ctx = await setupDelegatedSession(provider, program, new BN(10_000));
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 10))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const merchant = Keypair.generate().publicKey;
+    const spend = (amount: number) =>
+      program.methods
+        .sessionTransferSol(new BN(amount), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: merchant,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc({ commitment: "confirmed" });
+    const report = (amount: number, reporter = ctx.authority) =>
+      program.methods
+        .recordRefusedSpend(ctx.session.publicKey, new BN(amount))
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          sessionKeyAccount: null,
+          reporter: reporter.publicKey,
+        })
+        .signers([reporter])
+        .rpc({ commitment: "confirmed" });
+    const sessionKey = async () =>
+      (await program.account.userAccount.fetch(ctx.userPda)).sessionKeys[0];
+
+    // Without a policy there is nothing to count refusals toward
+    try {
+      await report(20_000);
+      assert.fail("expected LockoutPolicyMissing");
+    } catch (e) {
+      assert.include(String(e), "LockoutPolicyMissing");
+    }
+
+    try {
+      await program.methods
+        .setLockoutPolicy({
+          maxFailures: 0,
+          windowSeconds: 60,
+          lockSeconds: 60,
+        })
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+      assert.fail("expected InvalidLockoutPolicy");
+    } catch (e) {
+      assert.include(String(e), "InvalidLockoutPolicy");
+    }
+    await program.methods
+      .setLockoutPolicy({
+        maxFailures: 3,
+        windowSeconds: 600,
+        lockSeconds: 600,
+      })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    // A good spend goes through and leaves the count at zero
+    await spend(1_000);
+
+    // Refused spends still fail and move nothing
+    const before = await provider.connection.getBalance(ctx.userPda);
+    try {
+      await spend(20_000);
+      assert.fail("expected SessionSpendCapExceeded");
+    } catch (e) {
+      assert.include(String(e), "SessionSpendCapExceeded");
+    }
+    assert.equal(await provider.connection.getBalance(ctx.userPda), before);
+
+    // Only the authority or guardian reports refusals, and only real ones
+    try {
+      await report(20_000, Keypair.generate());
+      assert.fail("expected Unauthorized");
+    } catch (e) {
+      assert.include(String(e), "Unauthorized");
+    }
+    try {
+      await report(1_000);
+      assert.fail("expected SpendNotRefused");
+    } catch (e) {
+      assert.include(String(e), "SpendNotRefused");
+    }
+
+    await report(20_000);
+    await report(20_000);
+    let key = await sessionKey();
+    assert.equal(key.failedAttempts, 2);
+    assert.equal(key.totalSpent.toNumber(), 1_000);
+
+    const signature = await report(20_000);
+    key = await sessionKey();
+    assert.equal(key.failedAttempts, 0);
+    assert.isAbove(key.lockedUntil.toNumber(), Math.floor(Date.now() / 1000));
+
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new EventParser(program.programId, program.coder);
+    const names = Array.from(parser.parseLogs(tx!.meta!.logMessages!)).map(
+      (event) => event.name
+    );
+    assert.includeMembers(names, ["spendAttemptFailed", "sessionKeyLocked"]);
+
+    // Even a spend within limits is refused while locked
+    try {
+      await spend(1_000);
+      assert.fail("expected SessionKeyLocked");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyLocked");
+    }
+  });
+});
@@ -0,0 +1,160 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { operatorPayload, operatorSignatureInstruction } from "../app/sdk";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Operator-signed receipts", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const parser = new anchor.EventParser(program.programId, program.coder);
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("needs the bonded operator's countersignature", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const operator = Keypair.generate();
+    await airdropLamports(provider.connection, operator.publicKey, LAMPORTS);
+    const [reputation] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reputation"), operator.publicKey.toBuffer()],
+      program.programId
+    );
+    const [bond] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("bond"),
+        ctx.userPda.toBuffer(),
+        ctx.session.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    await program.methods
+      .initializeOperatorReputation()
+      .accountsStrict({
+        reputation,
+        operator: operator.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([operator])
+      .rpc();
+    await program.methods
+      .postBond(ctx.session.publicKey, new BN(LAMPORTS / 10))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        bond,
+        operator: operator.publicKey,
+        reputation,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([operator])
+      .rpc();
+    await program.methods
+      .setOperatorSignatureRequired(true)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const amount = new BN(1_000);
+    const transfer = (pre: TransactionInstruction[], withBond = true) =>
+      program.methods
+        .splDelegatedTransfer(amount, null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: withBond ? bond : null,
+          operatorReputation: reputation,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .preInstructions(pre)
+        .signers([ctx.session])
+        .rpc({ commitment: "confirmed" });
+    const payload = (useCount: number, signedAmount = amount) =>
+      operatorPayload(
+        ctx.userPda,
+        ctx.session.publicKey,
+        ctx.mint,
+        ctx.recipientAta,
+        signedAmount,
+        useCount
+      );
+    const expectMissing = async (pre: TransactionInstruction[]) => {
+      try {
+        await transfer(pre);
+        assert.fail("expected OperatorSignatureMissing");
+      } catch (e) {
+        assert.include(String(e), "OperatorSignatureMissing");
+      }
+    };
+
+    await expectMissing([]);
+    // Someone other than the bonded operator can't sign off
+    await expectMissing([
+      operatorSignatureInstruction(Keypair.generate(), payload(0)),
+    ]);
+    // A countersignature for another amount doesn't cover this transfer
+    await expectMissing([
+      operatorSignatureInstruction(operator, payload(0, amount.addn(1))),
+    ]);
+    // Without the bond there is no operator to check the signature against
+    try {
+      await transfer(
+        [operatorSignatureInstruction(operator, payload(0))],
+        false
+      );
+      assert.fail("expected BondRequired");
+    } catch (e) {
+      assert.include(String(e), "BondRequired");
+    }
+
+    const sig = await transfer([
+      operatorSignatureInstruction(operator, payload(0)),
+    ]);
+    const tx = await provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const attested = [...parser.parseLogs(tx.meta.logMessages)].find(
+      (e) => e.name === "executionAttested"
+    );
+    assert.isDefined(attested);
+    assert.isTrue(attested.data.operator.equals(operator.publicKey));
+    assert.isTrue(attested.data.sessionKey.equals(ctx.session.publicKey));
+
+    // The signature covered use 0, so it can't authorize the next transfer
+    await expectMissing([operatorSignatureInstruction(operator, payload(0))]);
+  });
+});
@@ -3,8 +3,19 @@ import { Program, BN } from "@coral-xyz/anchor";
 import { Keypair, SystemProgram, PublicKey } from "@solana/web3.js";
 import { Time } from "../target/types/time";
 import { assert } from "chai";
-import { TOKEN_PROGRAM_ID, mintTo } from "@solana/spl-token";
-import { airdropLamports, createMintAndAtas, deriveUserPda } from "./helpers";
+import {
+  TOKEN_PROGRAM_ID,
+  approve,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import {
+  airdropLamports,
+  createMintAndAtas,
+  deriveUserPda,
+  deriveProgramConfigPda,
+  setupDelegatedSession,
+} from "./helpers";
 
 describe("PDA isolation & authority enforcement", () => {
   const provider = anchor.AnchorProvider.env();
@@ -23,10 +34,11 @@ describe("PDA isolation & authority enforcement", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -49,9 +61,17 @@ describe("PDA isolation & authority enforcement", () => {
             canTransfer: false,
             canDelegate: false,
             canExecuteCustom: false,
+            canSelfRotate: false,
             maxTransferAmount: new BN(0),
+            dailyLimit: new BN(0),
+            spendWindowSeconds: 0,
+            maxUsesPerWindow: 0,
+            useWindowSeconds: 0,
             customFlags: 0,
-          }
+            maxCpiDepth: 0,
+            maxCustomAccounts: 0,
+          },
+          null
         )
         .accountsStrict({
           userAccount: owner.userPda,
@@ -84,9 +104,17 @@ describe("PDA isolation & authority enforcement", () => {
           canTransfer: false,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(0),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: owner.userPda,
@@ -98,7 +126,7 @@ describe("PDA isolation & authority enforcement", () => {
 
     try {
       await program.methods
-        .revokeSessionKey(session.publicKey)
+        .revokeSessionKey(session.publicKey, { manual: {} })
         .accountsStrict({
           userAccount: owner.userPda,
           authority: attacker.authority.publicKey,
@@ -121,10 +149,14 @@ describe("PDA isolation & authority enforcement", () => {
     const attacker = await setupUser();
 
     // cleanupSessionKeys
-    for (const call of ["cleanupSessionKeys", "revokeAllSessionKeys"]) {
+    const calls: [string, any[]][] = [
+      ["cleanupSessionKeys", [0]],
+      ["revokeAllSessionKeys", []],
+    ];
+    for (const [call, args] of calls) {
       try {
         // @ts-ignore dynamic method
-        await program.methods[call]()
+        await program.methods[call](...args)
           .accountsStrict({
             userAccount: owner.userPda,
             authority: attacker.authority.publicKey,
@@ -195,6 +227,7 @@ describe("PDA isolation & authority enforcement", () => {
         .splApproveDelegate(new BN(1))
         .accountsStrict({
           userAccount: owner.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
           authority: attacker.authority.publicKey,
           tokenAccount: ownerAta,
           mint,
@@ -255,6 +288,7 @@ describe("PDA isolation & authority enforcement", () => {
       .splApproveDelegate(new BN(300_000_000))
       .accountsStrict({
         userAccount: owner.userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
         authority: owner.authority.publicKey,
         tokenAccount: ownerAta,
         mint,
@@ -275,9 +309,17 @@ describe("PDA isolation & authority enforcement", () => {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(300_000_000),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: attacker.userPda,
@@ -289,14 +331,31 @@ describe("PDA isolation & authority enforcement", () => {
 
     try {
       await program.methods
-        .splDelegatedTransfer(new BN(1))
+        .splDelegatedTransfer(new BN(1), null, null, null, null)
         .accountsStrict({
           sessionSigner: foreignSession.publicKey,
           userAccount: owner.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
           fromToken: ownerAta,
           toToken: recipientAta,
           mint,
           delegateAuthority: delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([foreignSession])
@@ -306,4 +365,76 @@ describe("PDA isolation & authority enforcement", () => {
       assert.include(e.toString(), "SessionKeyNotFound");
     }
   });
+
+  it("rejects spending a token account the authority doesn't own", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+
+    // A bystander's account that happens to approve the same delegate PDA
+    const bystander = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      bystander.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const bystanderAta = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        bystander,
+        ctx.mint,
+        bystander.publicKey
+      )
+    ).address;
+    await mintTo(
+      provider.connection,
+      ctx.authority,
+      ctx.mint,
+      bystanderAta,
+      ctx.authority,
+      1_000
+    );
+    await approve(
+      provider.connection,
+      bystander,
+      bystanderAta,
+      ctx.delegateAuth,
+      bystander,
+      1_000
+    );
+
+    try {
+      await program.methods
+        .splDelegatedTransfer(new BN(1), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: bystanderAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+      assert.fail("expected SourceTokenOwnerMismatch");
+    } catch (e) {
+      assert.include(e.toString(), "SourceTokenOwnerMismatch");
+    }
+  });
 });
@@ -0,0 +1,166 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  sendAndConfirmTransaction,
+} from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createInitializeMintInstruction,
+  createInitializePermanentDelegateInstruction,
+  getMintLen,
+} from "@solana/spl-token";
+import {
+  airdropLamports,
+  deriveUserPda,
+  deriveProgramConfigPda,
+} from "./helpers";
+import { MINT_RISK } from "../app/sdk";
+
+describe("Permanent delegate mints", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const payer: Keypair = (provider.wallet as any).payer;
+
+  async function createPermanentDelegateMint(issuer: PublicKey) {
+    const mint = Keypair.generate();
+    const space = getMintLen([ExtensionType.PermanentDelegate]);
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: payer.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space,
+        lamports:
+          await provider.connection.getMinimumBalanceForRentExemption(space),
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializePermanentDelegateInstruction(
+        mint.publicKey,
+        issuer,
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(
+        mint.publicKey,
+        6,
+        issuer,
+        null,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await sendAndConfirmTransaction(provider.connection, tx, [payer, mint]);
+    return mint.publicKey;
+  }
+
+  it("refuses clawback-capable mints until the account opts in", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const mint = await createPermanentDelegateMint(
+      Keypair.generate().publicKey
+    );
+    const tokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      mint,
+      authority.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const [delegateAuth] = PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), userPda.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+
+    const allowlist = () =>
+      program.methods
+        .updateAllowedMints([mint])
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: mint, isSigner: false, isWritable: false },
+        ])
+        .signers([authority])
+        .rpc();
+    const approve = () =>
+      program.methods
+        .splApproveDelegate(new BN(1_000))
+        .accountsStrict({
+          userAccount: userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          authority: authority.publicKey,
+          tokenAccount,
+          mint,
+          delegateAuthority: delegateAuth,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+
+    for (const attempt of [allowlist, approve]) {
+      try {
+        await attempt();
+        assert.fail("expected PermanentDelegateNotAllowed");
+      } catch (e) {
+        assert.include(String(e), "PermanentDelegateNotAllowed");
+      }
+    }
+
+    await program.methods
+      .setPermanentDelegateOptIn(true)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+
+    // Listed mints passed along come back with their risk flags
+    const { events } = await program.methods
+      .updateAllowedMints([mint])
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+      })
+      .remainingAccounts([{ pubkey: mint, isSigner: false, isWritable: false }])
+      .signers([authority])
+      .simulate();
+    const assessed = events.find((e) => e.name === "mintRiskAssessed");
+    assert.isTrue(assessed.data.mint.equals(mint));
+    assert.equal(assessed.data.flags, MINT_RISK.permanentDelegate);
+
+    await allowlist();
+    await approve();
+
+    const userAccount = await program.account.userAccount.fetch(userPda);
+    assert.isTrue(userAccount.allowPermanentDelegateMints);
+    assert.isTrue(userAccount.delegatedTokenAccounts[0].equals(tokenAccount));
+  });
+});
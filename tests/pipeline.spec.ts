@@ -0,0 +1,391 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  AccountMeta,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+} from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { SwapPool } from "../target/types/swap_pool";
+import { assert } from "chai";
+import {
+  TOKEN_PROGRAM_ID,
+  approve,
+  createAccount,
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { programDataAddress } from "../app/verify_deployment";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Payout pipelines", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const swapPool = anchor.workspace.SwapPool as Program<SwapPool>;
+
+  it("runs a transfer + swap pipeline within its per-step limits", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const feePayer = (provider.wallet as any).payer;
+    const pipelineId = 1;
+    const id = Buffer.alloc(2);
+    id.writeUInt16LE(pipelineId);
+    const [pipeline] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pipeline"), ctx.userPda.toBuffer(), id],
+      program.programId
+    );
+
+    // Pool paying mintOut one for one, and the owner's account receiving it
+    const mintOut = await createMint(
+      provider.connection,
+      feePayer,
+      feePayer.publicKey,
+      null,
+      6
+    );
+    const [pool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), ctx.mint.toBuffer(), mintOut.toBuffer()],
+      swapPool.programId
+    );
+    const ata = async (mint: PublicKey, owner: PublicKey) =>
+      (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          feePayer,
+          mint,
+          owner,
+          true
+        )
+      ).address;
+    const vaultIn = await ata(ctx.mint, pool);
+    const vaultOut = await ata(mintOut, pool);
+    const ownerOut = await ata(mintOut, ctx.authority.publicKey);
+    const attackerOut = await ata(mintOut, Keypair.generate().publicKey);
+    await mintTo(
+      provider.connection,
+      feePayer,
+      mintOut,
+      vaultOut,
+      feePayer,
+      1_000_000
+    );
+
+    const createPipeline = (swapProgram: PublicKey) =>
+      program.methods
+        .createPipeline(pipelineId, [
+          {
+            transfer: {
+              mint: ctx.mint,
+              destination: ctx.recipientAta,
+              maxAmount: new BN(500),
+            },
+          },
+          {
+            swap: {
+              program: swapProgram,
+              mintIn: ctx.mint,
+              maxAmountIn: new BN(100),
+              destination: ownerOut,
+              minAmountOut: new BN(40),
+            },
+          },
+        ])
+        .accountsStrict({
+          pipeline,
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+
+    // Neither this program nor a token program may receive the delegate's
+    // signature as a "swap"
+    for (const swapProgram of [program.programId, TOKEN_PROGRAM_ID]) {
+      try {
+        await createPipeline(swapProgram);
+        assert.fail("expected InvalidPipeline");
+      } catch (e) {
+        assert.include(String(e), "InvalidPipeline");
+      }
+    }
+    await createPipeline(swapPool.programId);
+
+    const execute = async (
+      transferAmount: number,
+      swapAmount: number,
+      {
+        swapSource = ctx.ownerAta,
+        swapDestination = ownerOut,
+        pinnedDestination = ownerOut,
+      }: {
+        swapSource?: PublicKey;
+        swapDestination?: PublicKey;
+        pinnedDestination?: PublicKey;
+      } = {}
+    ) => {
+      const swapIx = await swapPool.methods
+        .swap(new BN(swapAmount))
+        .accountsStrict({
+          user: ctx.delegateAuth,
+          source: swapSource,
+          destination: swapDestination,
+          pool,
+          vaultIn,
+          vaultOut,
+          mintIn: ctx.mint,
+          mintOut,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+      const forwarded: AccountMeta[] = swapIx.keys.map((k) => ({
+        ...k,
+        isSigner: false,
+      }));
+      return program.methods
+        .executePipeline(
+          [
+            {
+              amount: new BN(transferAmount),
+              data: Buffer.alloc(0),
+              accountCount: 0,
+            },
+            {
+              amount: new BN(0),
+              data: swapIx.data,
+              accountCount: forwarded.length,
+            },
+          ],
+          null,
+          null
+        )
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          pipeline,
+          spendBudget: null,
+          slotHashes: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          { pubkey: ctx.ownerAta, isSigner: false, isWritable: true },
+          { pubkey: ctx.mint, isSigner: false, isWritable: false },
+          { pubkey: ctx.recipientAta, isSigner: false, isWritable: true },
+          { pubkey: ctx.delegateAuth, isSigner: false, isWritable: false },
+          { pubkey: swapPool.programId, isSigner: false, isWritable: false },
+          { pubkey: ctx.ownerAta, isSigner: false, isWritable: true },
+          { pubkey: pinnedDestination, isSigner: false, isWritable: true },
+          { pubkey: ctx.delegateAuth, isSigner: false, isWritable: false },
+          ...forwarded,
+        ])
+        .signers([ctx.session])
+        .rpc();
+    };
+
+    await execute(400, 50);
+    const recipient = await getAccount(provider.connection, ctx.recipientAta);
+    assert.equal(Number(recipient.amount), 400);
+    const output = await getAccount(provider.connection, ownerOut);
+    assert.equal(Number(output.amount), 50);
+
+    try {
+      await execute(600, 50);
+      assert.fail("expected PipelineStepLimitExceeded");
+    } catch (e) {
+      assert.include(String(e), "PipelineStepLimitExceeded");
+    }
+    try {
+      await execute(400, 150);
+      assert.fail("expected PipelineStepLimitExceeded");
+    } catch (e) {
+      assert.include(String(e), "PipelineStepLimitExceeded");
+    }
+    try {
+      await execute(400, 20);
+      assert.fail("expected SwapOutputTooLow");
+    } catch (e) {
+      assert.include(String(e), "SwapOutputTooLow");
+    }
+
+    // A session can't send the swap output anywhere but the pinned account
+    try {
+      await execute(400, 50, { swapDestination: attackerOut });
+      assert.fail("expected SwapOutputTooLow");
+    } catch (e) {
+      assert.include(String(e), "SwapOutputTooLow");
+    }
+    try {
+      await execute(400, 50, {
+        swapDestination: attackerOut,
+        pinnedDestination: attackerOut,
+      });
+      assert.fail("expected PipelineStepMismatch");
+    } catch (e) {
+      assert.include(String(e), "PipelineStepMismatch");
+    }
+    const attacker = await getAccount(provider.connection, attackerOut);
+    assert.equal(Number(attacker.amount), 0);
+
+    // Nor can it point the swap at another of the owner's accounts approved to
+    // the same delegate, whose balance the step doesn't measure
+    const savings = await createAccount(
+      provider.connection,
+      feePayer,
+      ctx.mint,
+      ctx.authority.publicKey,
+      Keypair.generate()
+    );
+    await mintTo(
+      provider.connection,
+      feePayer,
+      ctx.mint,
+      savings,
+      ctx.authority,
+      1_000
+    );
+    await approve(
+      provider.connection,
+      feePayer,
+      savings,
+      ctx.delegateAuth,
+      ctx.authority,
+      1_000
+    );
+    try {
+      await execute(400, 50, { swapSource: savings });
+      assert.fail("expected SwapAccountNotAllowed");
+    } catch (e) {
+      assert.include(String(e), "SwapAccountNotAllowed");
+    }
+    const untouched = await getAccount(provider.connection, savings);
+    assert.equal(Number(untouched.amount), 1_000);
+
+    const userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    const entry = userAccount.sessionKeys.find((k) =>
+      k.pubkey.equals(ctx.session.publicKey)
+    );
+    assert.equal(entry.useCount, 1);
+    assert.equal(entry.totalSpent.toNumber(), 450);
+  });
+
+  it("charges each run to the session's spend cap", async () => {
+    const ctx = await setupDelegatedSession(provider, program, new BN(500));
+    const pipelineId = 2;
+    const id = Buffer.alloc(2);
+    id.writeUInt16LE(pipelineId);
+    const [pipeline] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pipeline"), ctx.userPda.toBuffer(), id],
+      program.programId
+    );
+    await program.methods
+      .createPipeline(pipelineId, [
+        {
+          transfer: {
+            mint: ctx.mint,
+            destination: ctx.recipientAta,
+            maxAmount: new BN(400),
+          },
+        },
+      ])
+      .accountsStrict({
+        pipeline,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const execute = (amount: number) =>
+      program.methods
+        .executePipeline(
+          [{ amount: new BN(amount), data: Buffer.alloc(0), accountCount: 0 }],
+          null,
+          null
+        )
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          pipeline,
+          spendBudget: null,
+          slotHashes: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          { pubkey: ctx.ownerAta, isSigner: false, isWritable: true },
+          { pubkey: ctx.mint, isSigner: false, isWritable: false },
+          { pubkey: ctx.recipientAta, isSigner: false, isWritable: true },
+          { pubkey: ctx.delegateAuth, isSigner: false, isWritable: false },
+        ])
+        .signers([ctx.session])
+        .rpc();
+
+    // Each run is within the step limit, but the second passes the key's cap
+    await execute(300);
+    try {
+      await execute(300);
+      assert.fail("expected SessionSpendCapExceeded");
+    } catch (e) {
+      assert.include(String(e), "SessionSpendCapExceeded");
+    }
+
+    const userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    const entry = userAccount.sessionKeys.find((k) =>
+      k.pubkey.equals(ctx.session.publicKey)
+    );
+    assert.equal(entry.totalSpent.toNumber(), 300);
+    assert.equal(entry.useCount, 1);
+
+    // Steps can't screen recipients against the blocklist, so a blocklisted
+    // recipient isn't paid through a pipeline of an account enforcing it
+    const [blocklist] = PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist")],
+      program.programId
+    );
+    if (!(await program.account.blocklistRegistry.fetchNullable(blocklist))) {
+      // anchor test deploys with the provider wallet as upgrade authority
+      await program.methods
+        .initializeBlocklist()
+        .accountsStrict({
+          blocklist,
+          admin: provider.wallet.publicKey,
+          program: program.programId,
+          programData: programDataAddress(program.programId),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+    const updateBlocklist = (additions: PublicKey[], removals: PublicKey[]) =>
+      program.methods
+        .updateBlocklist(additions, removals)
+        .accountsStrict({ blocklist, admin: provider.wallet.publicKey })
+        .rpc();
+    await updateBlocklist([ctx.recipient.publicKey], []);
+    await program.methods
+      .setBlocklistEnforcement(true)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    try {
+      await execute(100);
+      assert.fail("expected PipelineControlsUnsupported");
+    } catch (e) {
+      assert.include(String(e), "PipelineControlsUnsupported");
+    } finally {
+      // Later specs share the validator
+      await updateBlocklist([], [ctx.recipient.publicKey]);
+    }
+  });
+});
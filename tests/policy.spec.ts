@@ -0,0 +1,97 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Policy documents", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("checks session spends against the account's rules", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const [policyPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("policy"), ctx.userPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializePolicy({
+        version: 1,
+        rules: [
+          {
+            sessionKey: ctx.session.publicKey,
+            condition: { maxAmount: { 0: new BN(LAMPORTS / 100) } },
+          },
+        ],
+      })
+      .accountsStrict({
+        policy: policyPda,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const merchant = Keypair.generate().publicKey;
+    const send = (amount: number, policy: PublicKey | null) =>
+      program.methods
+        .sessionTransferSol(new BN(amount), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy,
+          destination: merchant,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    try {
+      await send(LAMPORTS / 50, policyPda);
+      assert.fail("expected PolicyViolation");
+    } catch (e) {
+      assert.include(String(e), "PolicyViolation");
+    }
+    try {
+      await send(LAMPORTS / 100, null);
+      assert.fail("expected PolicyRequired");
+    } catch (e) {
+      assert.include(String(e), "PolicyRequired");
+    }
+    await send(LAMPORTS / 100, policyPda);
+    assert.equal(
+      await provider.connection.getBalance(merchant),
+      LAMPORTS / 100
+    );
+
+    await program.methods
+      .closePolicy()
+      .accountsStrict({
+        policy: policyPda,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const account = await program.account.userAccount.fetch(ctx.userPda);
+    assert.isFalse(account.hasPolicy);
+  });
+});
@@ -0,0 +1,122 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { PolicyBuilder } from "../app/policy";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Policy builder", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("flags conflicting, unreachable and always-deny rules", () => {
+    const bot = Keypair.generate().publicKey;
+    const [a, b, c] = [0, 1, 2].map(() => Keypair.generate().publicKey);
+    const findings = new PolicyBuilder()
+      .maxAmount(100)
+      .maxAmount(500, bot)
+      .recipients([a, b])
+      .recipients([c], bot)
+      .mints([])
+      .activeBetween(200, 100)
+      .lint();
+
+    const kinds = (kind: string) =>
+      findings.filter((f) => f.kind === kind).map((f) => f.rules);
+    assert.deepEqual(kinds("unreachable"), [[1, 0]]);
+    assert.deepEqual(kinds("conflict"), [[2, 3]]);
+    assert.deepEqual(kinds("always-deny"), [[4]]);
+    assert.deepEqual(kinds("invalid"), [[5]]);
+    assert.throws(
+      () => new PolicyBuilder().activeBetween(200, 100).build(),
+      /start must be before end/
+    );
+  });
+
+  it("describes the effective permissions per session key", () => {
+    const bot = new PublicKey("11111111111111111111111111111112");
+    const text = new PolicyBuilder()
+      .maxAmount(1_000)
+      .maxAmount(250, bot)
+      .schedule(["Mon", "Fri"], 9 * 60, 17 * 60, bot)
+      .describe();
+    assert.include(text, "Every session key:\n  Max per spend: 1000");
+    assert.include(text, "Max per spend: 250");
+    assert.include(text, "Schedule: Mon, Fri 09:00-17:00 UTC");
+  });
+
+  it("simulates the same decision the program makes", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const merchant = Keypair.generate().publicKey;
+    const policy = new PolicyBuilder()
+      .maxAmount(LAMPORTS)
+      .recipients([merchant], ctx.session.publicKey);
+    const [policyPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("policy"), ctx.userPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializePolicy({ version: 1, rules: policy.build() })
+      .accountsStrict({
+        policy: policyPda,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const send = (destination: PublicKey) =>
+      program.methods
+        .sessionTransferSol(new BN(LAMPORTS / 100), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: policyPda,
+          destination,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    const stranger = Keypair.generate().publicKey;
+    const decision = policy.simulate({
+      sessionKey: ctx.session.publicKey,
+      amount: LAMPORTS / 100,
+      recipient: stranger,
+    });
+    assert.deepEqual(decision, { allowed: false, rule: 1 });
+    try {
+      await send(stranger);
+      assert.fail("expected PolicyViolation");
+    } catch (e) {
+      assert.include(String(e), "PolicyViolation");
+    }
+
+    assert.isTrue(
+      policy.simulate({
+        sessionKey: ctx.session.publicKey,
+        amount: LAMPORTS / 100,
+        recipient: merchant,
+      }).allowed
+    );
+    await send(merchant);
+  });
+});
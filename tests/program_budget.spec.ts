@@ -0,0 +1,168 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Program budgets", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("caps value custom actions route into one program", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const feePayer: any = (provider.wallet as any).payer;
+    const [executor] = PublicKey.findProgramAddressSync(
+      [Buffer.from("executor"), ctx.userPda.toBuffer()],
+      program.programId
+    );
+    const executorAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      feePayer,
+      ctx.mint,
+      executor,
+      true
+    );
+    await mintTo(
+      provider.connection,
+      ctx.authority,
+      ctx.mint,
+      executorAta.address,
+      ctx.authority.publicKey,
+      1_000_000n
+    );
+
+    // A key that may call the token program, so the executor pays directly
+    const session = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      session.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await program.methods
+      .createSessionKeyV2({
+        sessionPubkey: session.publicKey,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+        expiresAtSlot: null,
+        permissions: {
+          ...transferPermissions(new BN(0)),
+          canExecuteCustom: true,
+        },
+        termsHash: null,
+        label: Array(32).fill(0),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [TOKEN_PROGRAM_ID],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const [spendBudget] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("spend_budget"),
+        ctx.userPda.toBuffer(),
+        ctx.mint.toBuffer(),
+      ],
+      program.programId
+    );
+    await program.methods
+      .initializeSpendBudget(new BN(1_000_000_000), new BN(86_400))
+      .accountsStrict({
+        spendBudget,
+        userAccount: ctx.userPda,
+        mint: ctx.mint,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    await program.methods
+      .setProgramBudget(TOKEN_PROGRAM_ID, new BN(5_000))
+      .accountsStrict({
+        spendBudget,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    // SPL Token `Transfer` (tag 3) from the executor's account
+    const pay = (amount: number, budget: PublicKey | null) => {
+      const data = Buffer.alloc(9);
+      data.writeUInt8(3, 0);
+      data.writeBigUInt64LE(BigInt(amount), 1);
+      return program.methods
+        .executeCustom(data, null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          spendBudget: budget,
+          executor,
+          slotHashes: null,
+        })
+        .remainingAccounts([
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          { pubkey: executorAta.address, isSigner: false, isWritable: true },
+          { pubkey: ctx.recipientAta, isSigner: false, isWritable: true },
+          { pubkey: executor, isSigner: false, isWritable: false },
+        ])
+        .signers([session])
+        .rpc();
+    };
+    const expectError = async (promise: Promise<string>, name: string) => {
+      try {
+        await promise;
+        assert.fail(`expected ${name}`);
+      } catch (e) {
+        assert.include(String(e), name);
+      }
+    };
+
+    await expectError(pay(1_000, null), "ProgramBudgetMissing");
+    await pay(3_000, spendBudget);
+    let budget = await program.account.spendBudget.fetch(spendBudget);
+    assert.equal(budget.programBudgets[0].spent.toNumber(), 3_000);
+    // Custom actions don't draw on the mint's own limit
+    assert.equal(budget.spent.toNumber(), 0);
+    await expectError(pay(3_000, spendBudget), "ProgramBudgetExceeded");
+
+    // Removing the cap frees the program again
+    await program.methods
+      .setProgramBudget(TOKEN_PROGRAM_ID, null)
+      .accountsStrict({
+        spendBudget,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    await pay(3_000, null);
+    budget = await program.account.spendBudget.fetch(spendBudget);
+    assert.lengthOf(budget.programBudgets, 0);
+  });
+});
@@ -0,0 +1,227 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { programDataAddress } from "../app/verify_deployment";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Program config", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const programConfig = deriveProgramConfigPda(program.programId);
+  const [treasury] = PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+  const admin = provider.wallet.publicKey;
+
+  const setPaused = (paused: boolean, signer?: Keypair) =>
+    program.methods
+      .setPaused(paused)
+      .accountsStrict({
+        programConfig,
+        admin: signer ? signer.publicKey : admin,
+      })
+      .signers(signer ? [signer] : [])
+      .rpc();
+  const setCap = (maxTransferAmount: number) =>
+    program.methods
+      .updateConfig(admin, new BN(maxTransferAmount))
+      .accountsStrict({ programConfig, admin })
+      .rpc();
+
+  const transferFrom = (
+    ctx: Awaited<ReturnType<typeof setupDelegatedSession>>,
+    amount: number,
+    feeToken: PublicKey | null = null
+  ) =>
+    program.methods
+      .splDelegatedTransfer(new BN(amount), null, null, null, null)
+      .accountsStrict({
+        sessionSigner: ctx.session.publicKey,
+        userAccount: ctx.userPda,
+        programConfig,
+        sessionKeyAccount: null,
+        policy: null,
+        fromToken: ctx.ownerAta,
+        toToken: ctx.recipientAta,
+        mint: ctx.mint,
+        delegateAuthority: ctx.delegateAuth,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: null,
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: null,
+        activityDigest: null,
+        accountStats: null,
+        savingsVault: null,
+        feeToken,
+        slotHashes: null,
+        gasTank: null,
+        instructionsSysvar: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([ctx.session])
+      .rpc();
+
+  it("pauses every account and caps transfers program-wide", async () => {
+    // anchor test deploys with the provider wallet as upgrade authority
+    await program.methods
+      .initializeProgramConfig(new BN(0))
+      .accountsStrict({
+        programConfig,
+        treasury,
+        admin,
+        program: program.programId,
+        programData: programDataAddress(program.programId),
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const ctx = await setupDelegatedSession(provider, program);
+    const transfer = (amount: number) => transferFrom(ctx, amount);
+
+    const outsider = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      outsider.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    try {
+      await setPaused(true, outsider);
+      assert.fail("expected Unauthorized");
+    } catch (e) {
+      assert.include(String(e), "Unauthorized");
+    }
+
+    try {
+      await setPaused(true);
+      try {
+        await transfer(1);
+        assert.fail("expected ProgramPaused");
+      } catch (e) {
+        assert.include(String(e), "ProgramPaused");
+      }
+      await setPaused(false);
+      await transfer(1);
+
+      await setCap(10);
+      try {
+        await transfer(11);
+        assert.fail("expected GlobalLimitExceeded");
+      } catch (e) {
+        assert.include(String(e), "GlobalLimitExceeded");
+      }
+      await transfer(10);
+    } finally {
+      // Later specs share the validator
+      await setPaused(false);
+      await setCap(0);
+    }
+
+    const config = await program.account.programConfig.fetch(programConfig);
+    assert.isTrue(config.admin.equals(admin));
+    assert.isFalse(config.isPaused);
+  });
+
+  it("refuses denied mints for every account", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const updateDenylist = (additions: PublicKey[], removals: PublicKey[]) =>
+      program.methods
+        .updateMintDenylist(additions, removals)
+        .accountsStrict({ programConfig, admin })
+        .rpc();
+    const transfer = () => transferFrom(ctx, 1);
+
+    try {
+      await updateDenylist([ctx.mint, ctx.mint], []);
+      const config = await program.account.programConfig.fetch(programConfig);
+      assert.lengthOf(config.deniedMints, 1);
+      try {
+        await transfer();
+        assert.fail("expected MintDenied");
+      } catch (e) {
+        assert.include(String(e), "MintDenied");
+      }
+    } finally {
+      await updateDenylist([], [ctx.mint]);
+    }
+    await transfer();
+  });
+
+  it("charges a protocol fee the admin can withdraw", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const setFee = (feeBps: number) =>
+      program.methods
+        .setFee(feeBps)
+        .accountsStrict({ programConfig, admin })
+        .rpc();
+    const feeToken = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        (provider.wallet as anchor.Wallet).payer,
+        ctx.mint,
+        treasury,
+        true
+      )
+    ).address;
+    const balance = async (tokenAccount: PublicKey) =>
+      Number((await getAccount(provider.connection, tokenAccount)).amount);
+
+    try {
+      await setFee(10_001);
+      assert.fail("expected InvalidProtocolFee");
+    } catch (e) {
+      assert.include(String(e), "InvalidProtocolFee");
+    }
+
+    try {
+      await setFee(100);
+      try {
+        await transferFrom(ctx, 1_000);
+        assert.fail("expected FeeAccountMissing");
+      } catch (e) {
+        assert.include(String(e), "FeeAccountMissing");
+      }
+
+      const owner = await balance(ctx.ownerAta);
+      await transferFrom(ctx, 1_000, feeToken);
+      assert.equal(await balance(ctx.recipientAta), 1_000);
+      assert.equal(await balance(feeToken), 10);
+      assert.equal(owner - (await balance(ctx.ownerAta)), 1_010);
+      // The fee leaves the owner's account too, so the key is charged for it
+      const account = await program.account.userAccount.fetch(ctx.userPda);
+      assert.equal(account.sessionKeys[0].totalSpent.toNumber(), 1_010);
+    } finally {
+      await setFee(0);
+    }
+
+    await program.methods
+      .withdrawFees(new BN(10))
+      .accountsStrict({
+        programConfig,
+        admin,
+        treasury,
+        destination: ctx.recipientAta,
+        mint: ctx.mint,
+        feeToken,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    assert.equal(await balance(feeToken), 0);
+    assert.equal(await balance(ctx.recipientAta), 1_010);
+  });
+});
@@ -0,0 +1,187 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  createAssociatedTokenAccount,
+} from "@solana/spl-token";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Per-session recipient allowlist", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("only lets a restricted key pay listed recipients", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      session.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const createKey = (allowedRecipients: PublicKey[]) =>
+      program.methods
+        .createSessionKeyV2({
+          sessionPubkey: session.publicKey,
+          expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+          expiresAtSlot: null,
+          permissions: transferPermissions(new BN(0)),
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients,
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const transfer = (toToken: PublicKey) =>
+      program.methods
+        .splDelegatedTransfer(new BN(1), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([session])
+        .rpc();
+
+    try {
+      await createKey(
+        Array.from({ length: 5 }, () => Keypair.generate().publicKey)
+      );
+      assert.fail("expected TooManyAllowedRecipients");
+    } catch (e) {
+      assert.include(String(e), "TooManyAllowedRecipients");
+    }
+
+    await createKey([ctx.recipientAta]);
+    await transfer(ctx.recipientAta);
+
+    const stranger = Keypair.generate();
+    const strangerAta = await createAssociatedTokenAccount(
+      provider.connection,
+      (provider.wallet as any).payer,
+      ctx.mint,
+      stranger.publicKey
+    );
+    try {
+      await transfer(strangerAta);
+      assert.fail("expected RecipientNotAllowed");
+    } catch (e) {
+      assert.include(String(e), "RecipientNotAllowed");
+    }
+
+    const userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    const entry = userAccount.sessionKeys.find((k) =>
+      k.pubkey.equals(session.publicKey)
+    );
+    assert.isTrue(entry.allowedRecipients[0].equals(ctx.recipientAta));
+  });
+
+  it("refuses a destination the named recipient doesn't own", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const transfer = (
+      toToken: PublicKey,
+      recipient: { owner: PublicKey; requireAta: boolean }
+    ) =>
+      program.methods
+        .splDelegatedTransfer(new BN(1), null, null, null, recipient)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+    const owner = ctx.recipient.publicKey;
+
+    try {
+      await transfer(ctx.recipientAta, {
+        owner: Keypair.generate().publicKey,
+        requireAta: false,
+      });
+      assert.fail("expected RecipientMismatch");
+    } catch (e) {
+      assert.include(String(e), "RecipientMismatch");
+    }
+    await transfer(ctx.recipientAta, { owner, requireAta: true });
+
+    // The recipient's own token account, but not its associated one
+    const auxiliary = await createAccount(
+      provider.connection,
+      (provider.wallet as any).payer,
+      ctx.mint,
+      owner,
+      Keypair.generate()
+    );
+    try {
+      await transfer(auxiliary, { owner, requireAta: true });
+      assert.fail("expected RecipientNotCanonicalAta");
+    } catch (e) {
+      assert.include(String(e), "RecipientNotCanonicalAta");
+    }
+    await transfer(auxiliary, { owner, requireAta: false });
+  });
+});
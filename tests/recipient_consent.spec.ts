@@ -0,0 +1,140 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Recipient consent", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("only pays recipients that accepted payouts", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 10))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    await program.methods
+      .setRecipientConsentRequired(true)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const merchant = Keypair.generate();
+    await airdropLamports(provider.connection, merchant.publicKey, LAMPORTS);
+    const consentOf = (recipient: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("consent"), ctx.userPda.toBuffer(), recipient.toBuffer()],
+        program.programId
+      )[0];
+    const pay = (recipientConsent: PublicKey | null) =>
+      program.methods
+        .sessionTransferSol(new BN(1_000), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: merchant.publicKey,
+          recipientConsent,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+    const expectMissing = async (recipientConsent: PublicKey | null) => {
+      try {
+        await pay(recipientConsent);
+        assert.fail("expected RecipientConsentMissing");
+      } catch (e) {
+        assert.include(String(e), "RecipientConsentMissing");
+      }
+    };
+
+    await expectMissing(null);
+
+    const accept = (recipient: Keypair) =>
+      program.methods
+        .acceptPayments()
+        .accountsStrict({
+          recipientConsent: consentOf(recipient.publicKey),
+          userAccount: ctx.userPda,
+          recipient: recipient.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([recipient])
+        .rpc();
+    await accept(merchant);
+    const consent = await program.account.recipientConsent.fetch(
+      consentOf(merchant.publicKey)
+    );
+    assert.isTrue(consent.userAccount.equals(ctx.userPda));
+    assert.isTrue(consent.recipient.equals(merchant.publicKey));
+    await pay(consentOf(merchant.publicKey));
+
+    // Token payouts check the consent of the token account's owner
+    await airdropLamports(
+      provider.connection,
+      ctx.recipient.publicKey,
+      LAMPORTS
+    );
+    await accept(ctx.recipient);
+    await program.methods
+      .splDelegatedTransfer(new BN(1_000), null, null, null, null)
+      .accountsStrict({
+        sessionSigner: ctx.session.publicKey,
+        userAccount: ctx.userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
+        sessionKeyAccount: null,
+        policy: null,
+        fromToken: ctx.ownerAta,
+        toToken: ctx.recipientAta,
+        mint: ctx.mint,
+        delegateAuthority: ctx.delegateAuth,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: consentOf(ctx.recipient.publicKey),
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: null,
+        activityDigest: null,
+        accountStats: null,
+        savingsVault: null,
+        feeToken: null,
+        slotHashes: null,
+        gasTank: null,
+        instructionsSysvar: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([ctx.session])
+      .rpc();
+
+    await program.methods
+      .withdrawPaymentConsent()
+      .accountsStrict({
+        recipientConsent: consentOf(merchant.publicKey),
+        recipient: merchant.publicKey,
+      })
+      .signers([merchant])
+      .rpc();
+    await expectMissing(null);
+  });
+});
@@ -0,0 +1,69 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { replay } from "../app/replay";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Incident replay", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("replays session spends without findings", async () => {
+    const fromSlot = await provider.connection.getSlot();
+    const ctx = await setupDelegatedSession(provider, program, new BN(100));
+    const transfer = (amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc({ commitment: "confirmed" });
+
+    await transfer(30);
+    await transfer(70);
+    // Rejected on-chain, so it never shows up in the replay
+    try {
+      await transfer(1);
+      assert.fail("expected SessionSpendCapExceeded");
+    } catch (e) {
+      assert.include(String(e), "SessionSpendCapExceeded");
+    }
+
+    const report = await replay(
+      program,
+      ctx.authority.publicKey,
+      fromSlot,
+      await provider.connection.getSlot()
+    );
+    assert.equal(report.checkedSpends, 2);
+    assert.lengthOf(report.unknownKeys, 0);
+    assert.deepEqual(report.findings, []);
+  });
+});
@@ -0,0 +1,94 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession } from "./helpers";
+
+describe("Resize user account", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("grows and shrinks session key capacity", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const resize = (capacity: number) =>
+      program.methods
+        .resizeUserAccount(capacity)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const size = async () =>
+      (await provider.connection.getAccountInfo(ctx.userPda)).data.length;
+    const capacity = async () =>
+      (await program.account.userAccount.fetch(ctx.userPda)).sessionKeyCapacity;
+
+    assert.equal(await capacity(), 6);
+    const initialSize = await size();
+
+    // The one existing session key must still fit
+    try {
+      await resize(0);
+      assert.fail("expected InvalidSessionKeyCapacity");
+    } catch (e) {
+      assert.include(String(e), "InvalidSessionKeyCapacity");
+    }
+
+    // Past what a new account can hold, up to the ceiling of 10
+    await resize(10);
+    assert.equal(await capacity(), 10);
+    const grownSize = await size();
+    assert.isAbove(grownSize, initialSize);
+
+    try {
+      await resize(11);
+      assert.fail("expected InvalidSessionKeyCapacity");
+    } catch (e) {
+      assert.include(String(e), "InvalidSessionKeyCapacity");
+    }
+
+    const before = await provider.connection.getBalance(
+      ctx.authority.publicKey
+    );
+    await resize(1);
+    assert.equal(await capacity(), 1);
+    assert.isBelow(await size(), initialSize);
+    const after = await provider.connection.getBalance(ctx.authority.publicKey);
+    assert.isAbove(after, before);
+
+    // Growing takes steps of at most 10 KB
+    try {
+      await resize(10);
+      assert.fail("expected AccountReallocExceedsLimit");
+    } catch (e) {
+      assert.include(String(e), "AccountReallocExceedsLimit");
+    }
+    await resize(9);
+    await resize(10);
+    assert.equal(await size(), grownSize);
+  });
+
+  it("only migrates accounts written with an older layout", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const account = await program.account.userAccount.fetch(ctx.userPda);
+    assert.equal(account.version, 1);
+
+    try {
+      await program.methods
+        .migrateUserAccount()
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("expected AccountAlreadyMigrated");
+    } catch (e) {
+      assert.include(String(e), "AccountAlreadyMigrated");
+    }
+  });
+});
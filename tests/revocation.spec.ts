@@ -1,10 +1,16 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program, BN } from "@coral-xyz/anchor";
+import { Program, BN, EventParser } from "@coral-xyz/anchor";
 import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
 import { Time } from "../target/types/time";
 import { assert } from "chai";
 import { TOKEN_PROGRAM_ID, mintTo } from "@solana/spl-token";
-import { airdropLamports, createMintAndAtas, deriveUserPda } from "./helpers";
+import {
+  airdropLamports,
+  createMintAndAtas,
+  deriveUserPda,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
 
 describe("Session Key Revocation ", () => {
   const provider = anchor.AnchorProvider.env();
@@ -23,10 +29,11 @@ describe("Session Key Revocation ", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -62,6 +69,7 @@ describe("Session Key Revocation ", () => {
       .splApproveDelegate(new BN(300_000_000))
       .accountsStrict({
         userAccount: userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
         authority: authority.publicKey,
         tokenAccount: ownerAta,
         mint,
@@ -87,9 +95,17 @@ describe("Session Key Revocation ", () => {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(300_000_000),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: userPda,
@@ -100,20 +116,41 @@ describe("Session Key Revocation ", () => {
       .rpc();
 
     await program.methods
-      .revokeSessionKey(session.publicKey)
+      .revokeSessionKey(session.publicKey, { compromised: {} })
       .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
       .signers([authority])
       .rpc();
+    const revoked = (
+      await program.account.userAccount.fetch(userPda)
+    ).sessionKeys.find((k) => k.pubkey.equals(session.publicKey));
+    assert.deepEqual(revoked.revocationReason, { compromised: {} });
     try {
       await program.methods
-        .splDelegatedTransfer(new BN(1))
+        .splDelegatedTransfer(new BN(1), null, null, null, null)
         .accountsStrict({
           sessionSigner: session.publicKey,
           userAccount: userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
           fromToken: ownerAta,
           toToken: recipientAta,
           mint,
           delegateAuthority: delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([session])
@@ -134,10 +171,11 @@ describe("Session Key Revocation ", () => {
       );
       const [pda] = await deriveUserPda(program.programId, a.publicKey);
       await program.methods
-        .initializeUserAccount()
+        .initializeUserAccount(6)
         .accountsStrict({
           userAccount: pda,
           authority: a.publicKey,
+          payer: a.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([a])
@@ -154,9 +192,17 @@ describe("Session Key Revocation ", () => {
               canTransfer: false,
               canDelegate: false,
               canExecuteCustom: false,
+              canSelfRotate: false,
               maxTransferAmount: new BN(0),
+              dailyLimit: new BN(0),
+              spendWindowSeconds: 0,
+              maxUsesPerWindow: 0,
+              useWindowSeconds: 0,
               customFlags: 0,
-            }
+              maxCpiDepth: 0,
+              maxCustomAccounts: 0,
+            },
+            null
           )
           .accountsStrict({
             userAccount: pda,
@@ -173,13 +219,13 @@ describe("Session Key Revocation ", () => {
     const acct1 = await program.account.userAccount.fetch(userPda);
     const keyPub = acct1.sessionKeys[0].pubkey as PublicKey;
     await program.methods
-      .revokeSessionKey(keyPub)
+      .revokeSessionKey(keyPub, { manual: {} })
       .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
       .signers([authority])
       .rpc();
     try {
       await program.methods
-        .revokeSessionKey(keyPub)
+        .revokeSessionKey(keyPub, { manual: {} })
         .accountsStrict({
           userAccount: userPda,
           authority: authority.publicKey,
@@ -198,4 +244,159 @@ describe("Session Key Revocation ", () => {
     const acct2 = await program.account.userAccount.fetch(userPda);
     acct2.sessionKeys.forEach((k: any) => assert.equal(k.isRevoked, true));
   });
+
+  it("revokes only keys outliving an expiry horizon", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const now = Math.floor(Date.now() / 1000);
+    const slot = await provider.connection.getSlot();
+    const grant = (expiry: number, type: any) => {
+      const key = Keypair.generate().publicKey;
+      return program.methods
+        .createSessionKey(
+          key,
+          new BN(expiry),
+          type,
+          {
+            canTransfer: false,
+            canDelegate: false,
+            canExecuteCustom: false,
+            canSelfRotate: false,
+            maxTransferAmount: new BN(0),
+            dailyLimit: new BN(0),
+            spendWindowSeconds: 0,
+            maxUsesPerWindow: 0,
+            useWindowSeconds: 0,
+            customFlags: 0,
+            maxCpiDepth: 0,
+            maxCustomAccounts: 0,
+          },
+          null
+        )
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc()
+        .then(() => key);
+    };
+    const shortLived = await grant(now + 600, { time: {} });
+    const longLived = await grant(now + 30 * 86_400, { time: {} });
+    // About 40 seconds of slots
+    const slotBounded = await grant(slot + 100, { blockHeight: {} });
+
+    await program.methods
+      .revokeSessionsExpiringAfter(new BN(now + 86_400))
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+
+    const { sessionKeys } = await program.account.userAccount.fetch(userPda);
+    const byKey = (key: PublicKey) =>
+      sessionKeys.find((k: any) => k.pubkey.equals(key));
+    assert.isFalse(byKey(shortLived).isRevoked);
+    assert.isFalse(byKey(slotBounded).isRevoked);
+    assert.isTrue(byKey(longLived).isRevoked);
+    assert.deepEqual(byKey(longLived).revocationReason, { manual: {} });
+  });
+
+  it("cleans up revoked keys in chunks", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    for (let i = 0; i < 3; i++) {
+      await program.methods
+        .createSessionKey(
+          Keypair.generate().publicKey,
+          new BN(Math.floor(Date.now() / 1000) + 600),
+          { time: {} },
+          transferPermissions(new BN(0)),
+          null
+        )
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    }
+    await program.methods
+      .revokeAllSessionKeys()
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+
+    const parser = new EventParser(program.programId, program.coder);
+    const cleanup = async (limit: number) => {
+      const signature = await program.methods
+        .cleanupSessionKeys(limit)
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return Array.from(parser.parseLogs(tx!.meta!.logMessages!)).find(
+        (e) => e.name === "sessionKeysCleaned"
+      )!.data;
+    };
+
+    const first = await cleanup(2);
+    assert.equal(first.removedCount, 2);
+    assert.equal(first.remainingCount, 1);
+    assert.equal(first.pendingCount, 1);
+    assert.isAbove(first.timestamp.toNumber(), 0);
+    let account = await program.account.userAccount.fetch(userPda);
+    assert.lengthOf(account.sessionKeys, 1);
+
+    const second = await cleanup(0);
+    assert.equal(second.removedCount, 1);
+    assert.equal(second.remainingCount, 0);
+    assert.equal(second.pendingCount, 0);
+    account = await program.account.userAccount.fetch(userPda);
+    assert.lengthOf(account.sessionKeys, 0);
+  });
 });
@@ -0,0 +1,79 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Rotate session key", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("revokes the old key and re-grants its limits and usage", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const merchant = Keypair.generate().publicKey;
+    const send = (signer: Keypair) =>
+      program.methods
+        .sessionTransferSol(new BN(LAMPORTS / 100), null, null)
+        .accountsStrict({
+          sessionSigner: signer.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: merchant,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([signer])
+        .rpc();
+
+    await send(ctx.session);
+    const next = Keypair.generate();
+    await program.methods
+      .rotateSessionKey(ctx.session.publicKey, next.publicKey)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    try {
+      await send(ctx.session);
+      assert.fail("expected SessionKeyRevoked");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyRevoked");
+    }
+    await send(next);
+
+    const { sessionKeys } = await program.account.userAccount.fetch(
+      ctx.userPda
+    );
+    const old = sessionKeys.find((k) =>
+      k.pubkey.equals(ctx.session.publicKey)
+    );
+    const rotated = sessionKeys.find((k) => k.pubkey.equals(next.publicKey));
+    assert.isTrue(old.isRevoked);
+    assert.deepEqual(old.revocationReason, { rotated: {} });
+    assert.isFalse(rotated.isRevoked);
+    assert.equal(rotated.totalSpent.toNumber(), (2 * LAMPORTS) / 100);
+    assert.equal(
+      rotated.expiresAtTime?.toNumber(),
+      old.expiresAtTime?.toNumber()
+    );
+  });
+});
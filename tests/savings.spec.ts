@@ -0,0 +1,94 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID, createAccount, getAccount } from "@solana/spl-token";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Savings rules", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("diverts a percentage plus round-up into the savings vault", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const vault = await createAccount(
+      provider.connection,
+      (provider.wallet as any).payer,
+      ctx.mint,
+      ctx.authority.publicKey,
+      Keypair.generate()
+    );
+    const setRule = (basisPoints: number, roundUpUnit: number) =>
+      program.methods
+        .setSavingsRule({
+          mint: ctx.mint,
+          vault,
+          basisPoints,
+          roundUpUnit: new BN(roundUpUnit),
+        })
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const transfer = (amount: number, savingsVault: PublicKey | null) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    try {
+      await setRule(10_001, 0);
+      assert.fail("expected InvalidSavingsRule");
+    } catch (e) {
+      assert.include(String(e), "InvalidSavingsRule");
+    }
+
+    // 10% of 1234 = 123, plus 66 to round 1234 up to 1300
+    await setRule(1_000, 100);
+    try {
+      await transfer(1_234, null);
+      assert.fail("expected SavingsVaultMissing");
+    } catch (e) {
+      assert.include(String(e), "SavingsVaultMissing");
+    }
+    await transfer(1_234, vault);
+
+    const saved = await getAccount(provider.connection, vault);
+    assert.equal(Number(saved.amount), 189);
+    const recipient = await getAccount(provider.connection, ctx.recipientAta);
+    assert.equal(Number(recipient.amount), 1_234);
+    // The diversion leaves the owner's account too, so the key is charged for it
+    const account = await program.account.userAccount.fetch(ctx.userPda);
+    assert.equal(account.sessionKeys[0].totalSpent.toNumber(), 1_234 + 189);
+  });
+});
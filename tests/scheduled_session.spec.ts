@@ -0,0 +1,122 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { airdropLamports, deriveUserPda, transferPermissions } from "./helpers";
+
+describe("Scheduled session grants", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("activates after the scheduled time and can be cancelled before", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const scheduledPda = (session: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("scheduled_session"),
+          userPda.toBuffer(),
+          session.toBuffer(),
+        ],
+        program.programId
+      )[0];
+    const now = Math.floor(Date.now() / 1000);
+    const schedule = (session: PublicKey, activateAt: number) =>
+      program.methods
+        .scheduleSessionKey(
+          {
+            sessionPubkey: session,
+            expiresAtTime: new BN(activateAt + 3600),
+            expiresAtSlot: null,
+            permissions: transferPermissions(new BN(0)),
+            termsHash: null,
+            label: Array(32).fill(0),
+            maxUses: 0,
+            validFrom: new BN(0),
+            allowedRecipients: [],
+            allowedMints: [],
+            allowedPrograms: [],
+            allowedDiscriminators: [],
+            idleTimeout: 0,
+            scope: PublicKey.default,
+            domainHash: null,
+          },
+          new BN(activateAt)
+        )
+        .accountsStrict({
+          scheduledSession: scheduledPda(session),
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    // Activation is permissionless: the provider wallet cranks it
+    const activate = (session: PublicKey) =>
+      program.methods
+        .activateScheduledSession()
+        .accountsStrict({
+          scheduledSession: scheduledPda(session),
+          userAccount: userPda,
+          authority: authority.publicKey,
+        })
+        .rpc();
+    const sessionKeys = async () =>
+      (await program.account.userAccount.fetch(userPda)).sessionKeys;
+
+    const cancelled = Keypair.generate().publicKey;
+    await schedule(cancelled, now + 3600);
+    await program.methods
+      .cancelScheduledSession()
+      .accountsStrict({
+        scheduledSession: scheduledPda(cancelled),
+        userAccount: userPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+    assert.isNull(
+      await provider.connection.getAccountInfo(scheduledPda(cancelled))
+    );
+
+    const session = Keypair.generate().publicKey;
+    await schedule(session, now + 3);
+    try {
+      await activate(session);
+      assert.fail("expected ScheduledSessionNotDue");
+    } catch (e) {
+      assert.include(String(e), "ScheduledSessionNotDue");
+    }
+    assert.lengthOf(await sessionKeys(), 0);
+
+    await new Promise((resolve) => setTimeout(resolve, 5_000));
+    await activate(session);
+    const keys = await sessionKeys();
+    assert.lengthOf(keys, 1);
+    assert.isTrue(keys[0].pubkey.equals(session));
+    assert.isNull(
+      await provider.connection.getAccountInfo(scheduledPda(session))
+    );
+  });
+});
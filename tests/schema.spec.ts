@@ -0,0 +1,39 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Idl, Program } from "@coral-xyz/anchor";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { readFileSync } from "fs";
+import { schemas } from "../app/schema";
+import { setupDelegatedSession } from "./helpers";
+
+describe("Borsh schemas", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("describes accounts as they are stored on-chain", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    // The raw IDL, as `yarn dump-schema` reads it; `program.idl` is camelCased
+    const idl: Idl = JSON.parse(readFileSync("target/idl/time.json", "utf8"));
+    const schema = schemas(idl);
+
+    const userAccount = schema.accounts.find((a) => a.name === "UserAccount");
+    const info = await provider.connection.getAccountInfo(ctx.userPda);
+    assert.deepEqual(
+      Array.from(info.data.subarray(0, 8)),
+      userAccount.discriminator
+    );
+
+    const layout = schema.types[userAccount.type];
+    assert.equal(layout.kind, "struct");
+    if (layout.kind === "struct") {
+      assert.deepEqual(layout.fields[0], { name: "authority", type: "pubkey" });
+      const sessionKeys = layout.fields.find((f) => f.name === "session_keys");
+      assert.deepEqual(sessionKeys.type, { vec: { defined: "SessionKey" } });
+    }
+
+    // Every referenced type is included
+    assert.exists(schema.types["SessionPermissions"]);
+    assert.exists(schema.events.find((e) => e.name === "TransferExecuted"));
+  });
+});
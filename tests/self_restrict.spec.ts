@@ -0,0 +1,97 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Self-restriction", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("lets a key narrow itself but never widen", async () => {
+    const ctx = await setupDelegatedSession(
+      provider,
+      program,
+      new BN(LAMPORTS / 10)
+    );
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const restrict = (
+      expiresAtTime: BN | null,
+      permissions: ReturnType<typeof transferPermissions> | null
+    ) =>
+      program.methods
+        .selfRestrict(expiresAtTime, null, permissions)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          sessionKeyAccount: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+    const expectWidens = async (attempt: Promise<string>) => {
+      try {
+        await attempt;
+        assert.fail("expected RestrictionWidens");
+      } catch (e) {
+        assert.include(String(e), "RestrictionWidens");
+      }
+    };
+    const fetchKey = async () =>
+      (await program.account.userAccount.fetch(ctx.userPda)).sessionKeys.find(
+        (k) => k.pubkey.equals(ctx.session.publicKey)
+      );
+
+    await expectWidens(restrict(null, transferPermissions(new BN(0))));
+    const { expiresAtTime } = await fetchKey();
+    await expectWidens(restrict(expiresAtTime.addn(3600), null));
+
+    const shorter = expiresAtTime.subn(60);
+    await restrict(shorter, transferPermissions(new BN(LAMPORTS / 100)));
+    let key = await fetchKey();
+    assert.equal(key.expiresAtTime.toNumber(), shorter.toNumber());
+    assert.equal(key.permissions.maxTransferAmount.toNumber(), LAMPORTS / 100);
+
+    await restrict(null, {
+      ...transferPermissions(new BN(LAMPORTS / 100)),
+      canTransfer: false,
+    });
+    try {
+      await program.methods
+        .sessionTransferSol(new BN(1_000), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: Keypair.generate().publicKey,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+      assert.fail("expected InsufficientPermissions");
+    } catch (e) {
+      assert.include(String(e), "InsufficientPermissions");
+    }
+    key = await fetchKey();
+    assert.isFalse(key.permissions.canTransfer);
+  });
+});
@@ -0,0 +1,60 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Session key activity", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("records when and how often a key was used", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const fetchKey = async () =>
+      (await program.account.userAccount.fetch(ctx.userPda)).sessionKeys.find(
+        (k) => k.pubkey.equals(ctx.session.publicKey)
+      )!;
+
+    let key = await fetchKey();
+    assert.equal(key.useCount, 0);
+    assert.equal(key.lastUsedAt.toNumber(), 0);
+
+    const before = Math.floor(Date.now() / 1000) - 60;
+    await program.methods
+      .splDelegatedTransfer(new BN(1_000), null, null, null, null)
+      .accountsStrict({
+        sessionSigner: ctx.session.publicKey,
+        userAccount: ctx.userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
+        sessionKeyAccount: null,
+        policy: null,
+        fromToken: ctx.ownerAta,
+        toToken: ctx.recipientAta,
+        mint: ctx.mint,
+        delegateAuthority: ctx.delegateAuth,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: null,
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: null,
+        activityDigest: null,
+        accountStats: null,
+        savingsVault: null,
+        feeToken: null,
+        slotHashes: null,
+        gasTank: null,
+        instructionsSysvar: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([ctx.session])
+      .rpc();
+
+    key = await fetchKey();
+    assert.equal(key.useCount, 1);
+    assert.isAtLeast(key.lastUsedAt.toNumber(), before);
+  });
+});
@@ -0,0 +1,85 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, EventParser } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { hashDomain } from "../app/sdk";
+import { airdropLamports, deriveUserPda, transferPermissions } from "./helpers";
+
+describe("Session key domain binding", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+  const parser = new EventParser(program.programId, program.coder);
+
+  it("records the granting origin and verifies it", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(4)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const session = Keypair.generate();
+    const domainHash = hashDomain("https://game.example/play");
+    const created = await program.methods
+      .createSessionKeyV2({
+        sessionPubkey: session.publicKey,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+        expiresAtSlot: null,
+        permissions: transferPermissions(new BN(0)),
+        termsHash: null,
+        label: Array(32).fill(0),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash,
+      })
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc({ commitment: "confirmed" });
+    const tx = await provider.connection.getTransaction(created, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const event = Array.from(parser.parseLogs(tx!.meta!.logMessages!)).find(
+      (e) => e.name === "sessionKeyCreated"
+    );
+    assert.deepEqual(event!.data.domainHash as number[], domainHash);
+
+    const verify = (hash: number[]) =>
+      program.methods
+        .verifySessionDomain(session.publicKey, hash)
+        .accountsStrict({ userAccount: userPda, sessionKeyAccount: null })
+        .rpc();
+
+    // Any page on the same origin hashes the same
+    await verify(hashDomain("https://game.example/"));
+    try {
+      await verify(hashDomain("https://game-example.phish"));
+      assert.fail("expected SessionDomainMismatch");
+    } catch (e) {
+      assert.include(String(e), "SessionDomainMismatch");
+    }
+  });
+});
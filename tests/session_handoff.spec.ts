@@ -0,0 +1,128 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Session handoff", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("lets a key hand itself to a new device, keeping its usage", async () => {
+    const ctx = await setupDelegatedSession(provider, program, new BN(100));
+    const handoff = (signer: Keypair, from: Keypair, to: Keypair) =>
+      program.methods
+        .transferSessionToNewKey(from.publicKey, to.publicKey)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          signer: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    const transfer = (signer: Keypair, amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: signer.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+
+    await transfer(ctx.session, 60);
+
+    const phone = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      phone.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    try {
+      await handoff(ctx.session, ctx.session, phone);
+      assert.fail("expected InsufficientPermissions");
+    } catch (e) {
+      assert.include(String(e), "InsufficientPermissions");
+    }
+
+    await program.methods
+      .updateSessionKey(ctx.session.publicKey, null, null, {
+        ...transferPermissions(new BN(100)),
+        canSelfRotate: true,
+      })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    // Nobody else can move the session
+    const stranger = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      stranger.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    try {
+      await handoff(stranger, ctx.session, stranger);
+      assert.fail("expected Unauthorized");
+    } catch (e) {
+      assert.include(String(e), "Unauthorized");
+    }
+
+    await handoff(ctx.session, ctx.session, phone);
+
+    try {
+      await transfer(ctx.session, 1);
+      assert.fail("expected SessionKeyNotFound");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyNotFound");
+    }
+
+    // The lifetime cap still counts the 60 spent before the handoff
+    try {
+      await transfer(phone, 50);
+      assert.fail("expected SessionSpendCapExceeded");
+    } catch (e) {
+      assert.include(String(e), "SessionSpendCapExceeded");
+    }
+    await transfer(phone, 40);
+
+    const userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    const entry = userAccount.sessionKeys.find((k) =>
+      k.pubkey.equals(phone.publicKey)
+    );
+    assert.equal(entry.totalSpent.toNumber(), 100);
+    assert.equal(entry.useCount, 2);
+  });
+});
@@ -0,0 +1,193 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Session key accounts", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("stores a key in its own PDA and reclaims it on close", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const session = Keypair.generate();
+    const [keyPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("session"),
+        ctx.userPda.toBuffer(),
+        session.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const createKey = () =>
+      program.methods
+        .createSessionKeyAccount({
+          sessionPubkey: session.publicKey,
+          expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+          expiresAtSlot: null,
+          permissions: transferPermissions(new BN(LAMPORTS / 10)),
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          sessionKeyAccount: keyPda,
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    await createKey();
+
+    let userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    assert.equal(userAccount.sessionKeyAccounts, 1);
+    assert.isUndefined(
+      userAccount.sessionKeys.find((k) => k.pubkey.equals(session.publicKey))
+    );
+
+    // Indexable by user account without knowing the session keys
+    const listed = await program.account.sessionKeyAccount.all([
+      { memcmp: { offset: 8, bytes: ctx.userPda.toBase58() } },
+    ]);
+    assert.lengthOf(listed, 1);
+    assert.isTrue(listed[0].publicKey.equals(keyPda));
+
+    const merchant = Keypair.generate().publicKey;
+    const send = (lamports: number) =>
+      program.methods
+        .sessionTransferSol(new BN(lamports), null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: keyPda,
+          policy: null,
+          destination: merchant,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([session])
+        .rpc();
+    await send(LAMPORTS / 20);
+    let keyAccount = await program.account.sessionKeyAccount.fetch(keyPda);
+    assert.equal(keyAccount.sessionKey.totalSpent.toNumber(), LAMPORTS / 20);
+
+    // Without its PDA the key isn't found inline
+    try {
+      await program.methods
+        .sessionTransferSol(new BN(1), null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: merchant,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([session])
+        .rpc();
+      assert.fail("expected SessionKeyNotFound");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyNotFound");
+    }
+
+    // Revoking everything invalidates key accounts through the generation
+    await program.methods
+      .revokeAllSessionKeys()
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    try {
+      await send(1);
+      assert.fail("expected SessionKeyRevoked");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyRevoked");
+    }
+
+    try {
+      await program.methods
+        .closeUserAccount()
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+      assert.fail("expected SessionKeyAccountsOpen");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyAccountsOpen");
+    }
+
+    const rent = await provider.connection.getBalance(keyPda);
+    const before = await provider.connection.getBalance(
+      ctx.authority.publicKey
+    );
+    await program.methods
+      .closeSessionKeyAccount()
+      .accountsStrict({
+        sessionKeyAccount: keyPda,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const after = await provider.connection.getBalance(ctx.authority.publicKey);
+    assert.equal(after - before, rent);
+    assert.isNull(await provider.connection.getAccountInfo(keyPda));
+    userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    assert.equal(userAccount.sessionKeyAccounts, 0);
+
+    // A key re-created after the revoke-all belongs to the new generation
+    await createKey();
+    keyAccount = await program.account.sessionKeyAccount.fetch(keyPda);
+    assert.equal(keyAccount.generation, userAccount.sessionGeneration);
+    await program.methods
+      .revokeSessionKeyAccount({ manual: {} })
+      .accountsStrict({
+        sessionKeyAccount: keyPda,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    try {
+      await send(1);
+      assert.fail("expected SessionKeyRevoked");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyRevoked");
+    }
+  });
+});
@@ -0,0 +1,102 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, EventParser } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { decodeLabel, encodeLabel } from "../app/sdk";
+import {
+  airdropLamports,
+  deriveUserPda,
+  transferPermissions,
+} from "./helpers";
+
+describe("Session key labels", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const parser = new EventParser(program.programId, program.coder);
+
+  const eventsOf = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return Array.from(parser.parseLogs(tx!.meta!.logMessages!));
+  };
+
+  it("names a key at creation and renames it", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const session = Keypair.generate().publicKey;
+    const created = await program.methods
+      .createSessionKeyV2({
+        sessionPubkey: session,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+        expiresAtSlot: null,
+        permissions: transferPermissions(new BN(0)),
+        termsHash: null,
+        label: encodeLabel("Trading bot"),
+        maxUses: 0,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc({ commitment: "confirmed" });
+    const createdEvent = (await eventsOf(created)).find(
+      (e) => e.name === "sessionKeyCreated"
+    );
+    assert.equal(
+      decodeLabel(createdEvent!.data.label as number[]),
+      "Trading bot"
+    );
+
+    const renamed = await program.methods
+      .updateSessionKeyLabel(session, encodeLabel("Grocery app"))
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc({ commitment: "confirmed" });
+    const updatedEvent = (await eventsOf(renamed)).find(
+      (e) => e.name === "sessionKeyUpdated"
+    );
+    assert.equal(
+      decodeLabel(updatedEvent!.data.label as number[]),
+      "Grocery app"
+    );
+
+    const { sessionKeys } = await program.account.userAccount.fetch(userPda);
+    assert.equal(decodeLabel(sessionKeys[0].label), "Grocery app");
+  });
+});
@@ -0,0 +1,71 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, transferPermissions } from "./helpers";
+
+describe("Session key order", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  const assertSorted = (keys: { pubkey: PublicKey }[]) => {
+    for (let i = 1; i < keys.length; i++) {
+      assert.isBelow(
+        Buffer.compare(keys[i - 1].pubkey.toBuffer(), keys[i].pubkey.toBuffer()),
+        0
+      );
+    }
+  };
+
+  it("keeps inline keys sorted by pubkey for binary search", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    for (let i = 0; i < 4; i++) {
+      await program.methods
+        .createSessionKey(
+          Keypair.generate().publicKey,
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          { time: {} },
+          transferPermissions(new BN(0)),
+          null
+        )
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    }
+    let { sessionKeys } = await program.account.userAccount.fetch(ctx.userPda);
+    assert.lengthOf(sessionKeys, 5);
+    assertSorted(sessionKeys);
+
+    // A handoff changes the pubkey, so the list is re-sorted
+    const next = Keypair.generate();
+    await program.methods
+      .transferSessionToNewKey(ctx.session.publicKey, next.publicKey)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        signer: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    ({ sessionKeys } = await program.account.userAccount.fetch(ctx.userPda));
+    assertSorted(sessionKeys);
+
+    await program.methods
+      .revokeSessionKey(next.publicKey, { manual: {} })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    ({ sessionKeys } = await program.account.userAccount.fetch(ctx.userPda));
+    assert.isTrue(
+      sessionKeys.find((k) => k.pubkey.equals(next.publicKey)).isRevoked
+    );
+  });
+});
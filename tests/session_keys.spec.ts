@@ -1,6 +1,6 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
-import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
 import { Time } from "../target/types/time";
 import { assert } from "chai";
 import { airdropLamports, deriveUserPda } from "./helpers";
@@ -22,16 +22,23 @@ describe("Session Keys (time/block)", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
       .rpc();
 
     const session = Keypair.generate();
+    const termsHash = Array.from(
+      Buffer.from(
+        anchor.utils.sha256.hash("Transfer up to 1 token for 1h"),
+        "hex"
+      )
+    );
     await program.methods
       .createSessionKey(
         session.publicKey,
@@ -41,9 +48,17 @@ describe("Session Keys (time/block)", () => {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(1_000_000_000),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        termsHash
       )
       .accountsStrict({
         userAccount: userPda,
@@ -55,7 +70,7 @@ describe("Session Keys (time/block)", () => {
 
     const newExpiry = new BN(Math.floor(Date.now() / 1000) + 7200);
     await program.methods
-      .updateSessionKey(session.publicKey, newExpiry, null)
+      .updateSessionKey(session.publicKey, newExpiry, null, null)
       .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
       .signers([authority])
       .rpc();
@@ -64,7 +79,8 @@ describe("Session Keys (time/block)", () => {
     const entry = acct.sessionKeys.find(
       (k: any) => k.pubkey.toBase58() === session.publicKey.toBase58()
     );
-    assert.equal(entry.expiresAt.toNumber(), newExpiry.toNumber());
+    assert.equal(entry.expiresAtTime.toNumber(), newExpiry.toNumber());
+    assert.deepEqual(entry.termsHash, termsHash);
   });
 
   it("creates a block-height session key", async () => {
@@ -79,10 +95,11 @@ describe("Session Keys (time/block)", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -99,9 +116,17 @@ describe("Session Keys (time/block)", () => {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(100_000_000),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: userPda,
@@ -115,7 +140,8 @@ describe("Session Keys (time/block)", () => {
     const entry = acct.sessionKeys.find(
       (k: any) => k.pubkey.toBase58() === session.publicKey.toBase58()
     );
-    assert.deepEqual(entry.expirationType, { blockHeight: {} });
+    assert.isNull(entry.expiresAtTime);
+    assert.equal(entry.expiresAtSlot.toNumber(), slot + 50);
   });
 
   it("enforces maximum number of session keys per user", async () => {
@@ -130,18 +156,19 @@ describe("Session Keys (time/block)", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
       .rpc();
 
-    // MAX_SESSION_KEYS is 10. Create 10, then the 11th should fail.
+    // DEFAULT_SESSION_KEY_CAPACITY is 6. Create 6, then the 7th should fail.
     const now = Math.floor(Date.now() / 1000);
-    for (let i = 0; i < 10; i++) {
+    for (let i = 0; i < 6; i++) {
       const sk = Keypair.generate();
       await program.methods
         .createSessionKey(
@@ -152,9 +179,17 @@ describe("Session Keys (time/block)", () => {
             canTransfer: false,
             canDelegate: false,
             canExecuteCustom: false,
+            canSelfRotate: false,
             maxTransferAmount: new BN(0),
+            dailyLimit: new BN(0),
+            spendWindowSeconds: 0,
+            maxUsesPerWindow: 0,
+            useWindowSeconds: 0,
             customFlags: 0,
-          }
+            maxCpiDepth: 0,
+            maxCustomAccounts: 0,
+          },
+          null
         )
         .accountsStrict({
           userAccount: userPda,
@@ -176,9 +211,17 @@ describe("Session Keys (time/block)", () => {
             canTransfer: false,
             canDelegate: false,
             canExecuteCustom: false,
+            canSelfRotate: false,
             maxTransferAmount: new BN(0),
+            dailyLimit: new BN(0),
+            spendWindowSeconds: 0,
+            maxUsesPerWindow: 0,
+            useWindowSeconds: 0,
             customFlags: 0,
-          }
+            maxCpiDepth: 0,
+            maxCustomAccounts: 0,
+          },
+          null
         )
         .accountsStrict({
           userAccount: userPda,
@@ -205,10 +248,11 @@ describe("Session Keys (time/block)", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -225,9 +269,17 @@ describe("Session Keys (time/block)", () => {
           canTransfer: false,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(0),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: userPda,
@@ -240,7 +292,7 @@ describe("Session Keys (time/block)", () => {
     // Try to update to a past slot
     try {
       await program.methods
-        .updateSessionKey(session.publicKey, new BN(slot - 1), null)
+        .updateSessionKey(session.publicKey, null, new BN(slot - 1), null)
         .accountsStrict({
           userAccount: userPda,
           authority: authority.publicKey,
@@ -265,10 +317,11 @@ describe("Session Keys (time/block)", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -283,9 +336,17 @@ describe("Session Keys (time/block)", () => {
           canTransfer: false,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(0),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: userPda,
@@ -306,6 +367,7 @@ describe("Session Keys (time/block)", () => {
         .updateSessionKey(
           session.publicKey,
           new BN(Math.floor(Date.now() / 1000) + 7200),
+          null,
           null
         )
         .accountsStrict({ userAccount: userPda, authority: wrong.publicKey })
@@ -336,10 +398,11 @@ describe("Session Keys (time/block)", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -355,9 +418,17 @@ describe("Session Keys (time/block)", () => {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(100_000_000),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: userPda,
@@ -372,11 +443,18 @@ describe("Session Keys (time/block)", () => {
       canTransfer: true,
       canDelegate: true,
       canExecuteCustom: true,
+      canSelfRotate: false,
       maxTransferAmount: new BN(500_000_000),
+      dailyLimit: new BN(0),
+      spendWindowSeconds: 0,
+      maxUsesPerWindow: 0,
+      useWindowSeconds: 0,
       customFlags: 1,
+      maxCpiDepth: 0,
+      maxCustomAccounts: 0,
     };
     await program.methods
-      .updateSessionKey(session.publicKey, newExp, newPerms)
+      .updateSessionKey(session.publicKey, newExp, null, newPerms)
       .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
       .signers([authority])
       .rpc();
@@ -385,9 +463,207 @@ describe("Session Keys (time/block)", () => {
     const entry = acct.sessionKeys.find(
       (k: any) => k.pubkey.toBase58() === session.publicKey.toBase58()
     );
-    assert.equal(entry.expiresAt.toNumber(), newExp.toNumber());
+    assert.equal(entry.expiresAtTime.toNumber(), newExp.toNumber());
     assert.equal(entry.permissions.canDelegate, true);
     assert.equal(entry.permissions.canExecuteCustom, true);
     assert.equal(entry.permissions.customFlags, 1);
   });
+
+  it("creates a labeled key via v2 and flags the deprecated v1 path", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const createdEvent = async (sig: string) => {
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const events = [...parser.parseLogs(tx.meta.logMessages)];
+      return {
+        logs: tx.meta.logMessages.join("\n"),
+        event: events.find((e) => e.name === "sessionKeyCreated"),
+      };
+    };
+    const permissions = {
+      canTransfer: true,
+      canDelegate: false,
+      canExecuteCustom: false,
+      canSelfRotate: false,
+      maxTransferAmount: new BN(1_000),
+      dailyLimit: new BN(0),
+      spendWindowSeconds: 0,
+      maxUsesPerWindow: 0,
+      useWindowSeconds: 0,
+      customFlags: 0,
+      maxCpiDepth: 0,
+      maxCustomAccounts: 0,
+    };
+    const expiresAt = new BN(Math.floor(Date.now() / 1000) + 3600);
+
+    const v2Session = Keypair.generate();
+    const label = Buffer.alloc(32);
+    label.write("checkout-bot");
+    const v2 = await createdEvent(
+      await program.methods
+        .createSessionKeyV2({
+          sessionPubkey: v2Session.publicKey,
+          expiresAtTime: expiresAt,
+          expiresAtSlot: null,
+          permissions,
+          termsHash: null,
+          label: Array.from(label),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" })
+    );
+    assert.equal(v2.event.data.deprecated, false);
+    assert.notInclude(v2.logs, "Deprecated");
+
+    const v1Session = Keypair.generate();
+    const v1 = await createdEvent(
+      await program.methods
+        .createSessionKey(
+          v1Session.publicKey,
+          expiresAt,
+          { time: {} },
+          permissions,
+          null
+        )
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" })
+    );
+    assert.equal(v1.event.data.deprecated, true);
+    assert.include(v1.logs, "Deprecated: create_session_key");
+
+    const acct = await program.account.userAccount.fetch(userPda);
+    const entry = acct.sessionKeys.find((k: any) =>
+      k.pubkey.equals(v2Session.publicKey)
+    );
+    assert.deepEqual(Buffer.from(entry.label), label);
+  });
+
+  it("expires at whichever of the time and slot bounds comes first", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      authority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(6)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const create = (expiresAtTime: BN | null, expiresAtSlot: BN | null) =>
+      program.methods
+        .createSessionKeyV2({
+          sessionPubkey: Keypair.generate().publicKey,
+          expiresAtTime,
+          expiresAtSlot,
+          permissions: {
+            canTransfer: true,
+            canDelegate: false,
+            canExecuteCustom: false,
+            canSelfRotate: false,
+            maxTransferAmount: new BN(0),
+            dailyLimit: new BN(0),
+            spendWindowSeconds: 0,
+            maxUsesPerWindow: 0,
+            useWindowSeconds: 0,
+            customFlags: 0,
+            maxCpiDepth: 0,
+            maxCustomAccounts: 0,
+          },
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+    try {
+      await create(null, null);
+      assert.fail("expected InvalidExpiry");
+    } catch (e) {
+      assert.include(String(e), "InvalidExpiry");
+    }
+
+    // Time bound an hour out, slot bound a few slots out: the slot wins
+    const slot = await provider.connection.getSlot();
+    await create(
+      new BN(Math.floor(Date.now() / 1000) + 3600),
+      new BN(slot + 5)
+    );
+    while ((await provider.connection.getSlot()) <= slot + 5) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    await program.methods
+      .cleanupSessionKeys(0)
+      .accountsStrict({ userAccount: userPda, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    const acct = await program.account.userAccount.fetch(userPda);
+    assert.lengthOf(acct.sessionKeys, 0);
+  });
 });
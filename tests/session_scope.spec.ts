@@ -0,0 +1,114 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  deriveUserPda,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Session key scopes", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("only executes for the dapp a key is scoped to", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(4)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 10))
+      .accountsStrict({
+        userAccount: userPda,
+        depositor: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const grant = (session: Keypair, scope: PublicKey) =>
+      program.methods
+        .createSessionKeyV2({
+          sessionPubkey: session.publicKey,
+          expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+          expiresAtSlot: null,
+          permissions: transferPermissions(new BN(0)),
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    const pay = (session: Keypair, scope: PublicKey | null) =>
+      program.methods
+        .sessionTransferSol(new BN(1_000), null, scope)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: Keypair.generate().publicKey,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([session])
+        .rpc();
+
+    const game = Keypair.generate().publicKey;
+    const scoped = Keypair.generate();
+    await grant(scoped, game);
+    const key = (
+      await program.account.userAccount.fetch(userPda)
+    ).sessionKeys.find((k) => k.pubkey.equals(scoped.publicKey))!;
+    assert.isTrue(key.scope.equals(game));
+
+    // Another service, or a caller naming no scope, can't use the game's key
+    for (const scope of [null, Keypair.generate().publicKey]) {
+      try {
+        await pay(scoped, scope);
+        assert.fail("expected SessionScopeMismatch");
+      } catch (e) {
+        assert.include(String(e), "SessionScopeMismatch");
+      }
+    }
+    await pay(scoped, game);
+
+    // Unscoped keys work whatever the caller names
+    const open = Keypair.generate();
+    await grant(open, PublicKey.default);
+    await pay(open, null);
+    await pay(open, game);
+  });
+});
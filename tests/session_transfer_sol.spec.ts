@@ -0,0 +1,134 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Session SOL transfer", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("pays third parties from the vault without the authority", async () => {
+    const ctx = await setupDelegatedSession(
+      provider,
+      program,
+      new BN(LAMPORTS / 10)
+    );
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const send = (destination: PublicKey, lamports: number) =>
+      program.methods
+        .sessionTransferSol(new BN(lamports), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    const merchant = Keypair.generate().publicKey;
+    await send(merchant, LAMPORTS / 20);
+    assert.equal(
+      await provider.connection.getBalance(merchant),
+      LAMPORTS / 20
+    );
+
+    // Same lifetime cap as every other session spend
+    try {
+      await send(merchant, LAMPORTS / 10);
+      assert.fail("expected SessionSpendCapExceeded");
+    } catch (e) {
+      assert.include(String(e), "SessionSpendCapExceeded");
+    }
+
+    const entry = (
+      await program.account.userAccount.fetch(ctx.userPda)
+    ).sessionKeys.find((k) => k.pubkey.equals(ctx.session.publicKey));
+    assert.equal(entry.totalSpent.toNumber(), LAMPORTS / 20);
+  });
+
+  it("refuses transfers under token-only controls", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const send = () =>
+      program.methods
+        .sessionTransferSol(new BN(LAMPORTS / 100), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: Keypair.generate().publicKey,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+    const expectRefused = async () => {
+      try {
+        await send();
+        assert.fail("expected SolSpendUnsupported");
+      } catch (e) {
+        assert.include(String(e), "SolSpendUnsupported");
+      }
+    };
+
+    // A KYC gate on recipients can't be checked for a plain wallet
+    const gate = (mint: PublicKey | null) =>
+      program.methods
+        .setAttestationGate(mint && { mint, subject: { recipient: {} } })
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    await gate(Keypair.generate().publicKey);
+    await expectRefused();
+    await gate(null);
+
+    // Nor can an operator countersign a payout it has no token payload for
+    const countersign = (required: boolean) =>
+      program.methods
+        .setOperatorSignatureRequired(required)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    await countersign(true);
+    await expectRefused();
+    await countersign(false);
+
+    await send();
+  });
+});
@@ -0,0 +1,136 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  deriveUserPda,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Sliding expiration", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("extends keys on use, up to their cap", async () => {
+    const authority = Keypair.generate();
+    await airdropLamports(provider.connection, authority.publicKey, LAMPORTS);
+    const [userPda] = await deriveUserPda(
+      program.programId,
+      authority.publicKey
+    );
+    await program.methods
+      .initializeUserAccount(4)
+      .accountsStrict({
+        userAccount: userPda,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 10))
+      .accountsStrict({
+        userAccount: userPda,
+        depositor: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const grant = (
+      session: Keypair,
+      expiresAtTime: number | null,
+      idleTimeout: number
+    ) =>
+      program.methods
+        .createSessionKeyV2({
+          sessionPubkey: session.publicKey,
+          expiresAtTime: expiresAtTime === null ? null : new BN(expiresAtTime),
+          expiresAtSlot: expiresAtTime === null ? new BN(1e9) : null,
+          permissions: transferPermissions(new BN(0)),
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(0),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: userPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    const pay = (session: Keypair) =>
+      program.methods
+        .sessionTransferSol(new BN(1_000), null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: Keypair.generate().publicKey,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([session])
+        .rpc();
+    const fetchKey = async (key: PublicKey) =>
+      (await program.account.userAccount.fetch(userPda)).sessionKeys.find(
+        (k) => k.pubkey.equals(key)
+      )!;
+
+    // Sliding keys need a time cap
+    try {
+      await grant(Keypair.generate(), null, 60);
+      assert.fail("expected InvalidExpiry");
+    } catch (e) {
+      assert.include(String(e), "InvalidExpiry");
+    }
+
+    const now = Math.floor(Date.now() / 1000);
+    const capped = Keypair.generate();
+    await grant(capped, now + 30, 3600);
+    let key = await fetchKey(capped.publicKey);
+    assert.equal(key.maxExpiresAt.toNumber(), now + 30);
+    assert.equal(key.expiresAtTime!.toNumber(), now + 30);
+
+    const sliding = Keypair.generate();
+    await grant(sliding, now + 3600, 2);
+    key = await fetchKey(sliding.publicKey);
+    assert.isAtMost(key.expiresAtTime!.toNumber(), now + 30);
+    const firstDeadline = key.expiresAtTime!.toNumber();
+
+    await new Promise((resolve) => setTimeout(resolve, 1_000));
+    await pay(sliding);
+    key = await fetchKey(sliding.publicKey);
+    assert.isAbove(key.expiresAtTime!.toNumber(), firstDeadline);
+    assert.equal(
+      key.expiresAtTime!.toNumber(),
+      key.lastUsedAt.toNumber() + key.idleTimeout
+    );
+
+    // Left idle past its timeout, the key dies well before its cap
+    await new Promise((resolve) => setTimeout(resolve, 4_000));
+    try {
+      await pay(sliding);
+      assert.fail("expected SessionKeyExpired");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyExpired");
+    }
+  });
+});
@@ -0,0 +1,113 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { SYSVAR_SLOT_HASHES_PUBKEY } from "@solana/web3.js";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Recent slot proofs", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  const newestSlotHash = async () => {
+    const info = await provider.connection.getAccountInfo(
+      SYSVAR_SLOT_HASHES_PUBKEY
+    );
+    return {
+      slot: new BN(info!.data.subarray(8, 16), "le"),
+      hash: Array.from(info!.data.subarray(16, 48)),
+    };
+  };
+
+  it("requires a recent slot reference once max_slot_age is set", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const transfer = (
+      slotRef: { slot: BN; hash: number[] } | null,
+      withSysvar = true
+    ) =>
+      program.methods
+        .splDelegatedTransfer(new BN(1), slotRef, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: withSysvar ? SYSVAR_SLOT_HASHES_PUBKEY : null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+    const setMaxSlotAge = (maxSlotAge: number) =>
+      program.methods
+        .setMaxSlotAge(maxSlotAge)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+
+    try {
+      await setMaxSlotAge(513);
+      assert.fail("expected InvalidMaxSlotAge");
+    } catch (e) {
+      assert.include(String(e), "InvalidMaxSlotAge");
+    }
+
+    // Off by default: no reference needed
+    await transfer(null, false);
+
+    await setMaxSlotAge(150);
+    try {
+      await transfer(null);
+      assert.fail("expected SlotProofRequired");
+    } catch (e) {
+      assert.include(String(e), "SlotProofRequired");
+    }
+
+    const fresh = await newestSlotHash();
+    try {
+      await transfer({ slot: fresh.slot, hash: new Array(32).fill(0) });
+      assert.fail("expected SlotProofInvalid");
+    } catch (e) {
+      assert.include(String(e), "SlotProofInvalid");
+    }
+
+    await transfer(fresh);
+
+    // A reference older than the allowed age is rejected even if still in
+    // the sysvar
+    await setMaxSlotAge(1);
+    const stale = await newestSlotHash();
+    await new Promise((resolve) => setTimeout(resolve, 2_000));
+    try {
+      await transfer(stale);
+      assert.fail("expected SlotProofInvalid");
+    } catch (e) {
+      assert.include(String(e), "SlotProofInvalid");
+    }
+
+    const userAccount = await program.account.userAccount.fetch(ctx.userPda);
+    assert.equal(userAccount.maxSlotAge, 1);
+  });
+});
@@ -0,0 +1,138 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("SOL vault", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("holds lamports on the user account PDA", async () => {
+    const ctx = await setupDelegatedSession(
+      provider,
+      program,
+      new BN(LAMPORTS / 10)
+    );
+    const balance = () => provider.connection.getBalance(ctx.userPda);
+    const sessionWithdraw = (signer: Keypair, lamports: number) =>
+      program.methods
+        .sessionWithdrawSol(new BN(lamports), null, null)
+        .accountsStrict({
+          sessionSigner: signer.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([signer])
+        .rpc();
+
+    const start = await balance();
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    assert.equal(await balance(), start + LAMPORTS / 2);
+
+    // The session key is capped at 0.1 SOL over its lifetime
+    await sessionWithdraw(ctx.session, LAMPORTS / 20);
+    try {
+      await sessionWithdraw(ctx.session, LAMPORTS / 10);
+      assert.fail("expected SessionSpendCapExceeded");
+    } catch (e) {
+      assert.include(String(e), "SessionSpendCapExceeded");
+    }
+
+    const stranger = Keypair.generate();
+    await airdropLamports(provider.connection, stranger.publicKey, LAMPORTS);
+    try {
+      await sessionWithdraw(stranger, 1);
+      assert.fail("expected SessionKeyNotFound");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyNotFound");
+    }
+
+    // The authority can take out everything but the rent reserve
+    const withdraw = (lamports: number) =>
+      program.methods
+        .withdrawSol(new BN(lamports))
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const available = (await balance()) - start;
+    try {
+      await withdraw(available + 1);
+      assert.fail("expected InsufficientVaultBalance");
+    } catch (e) {
+      assert.include(String(e), "InsufficientVaultBalance");
+    }
+    await withdraw(available);
+    assert.equal(await balance(), start);
+  });
+
+  it("refuses session withdrawals under token-only controls", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    const setEnforcement = (enabled: boolean) =>
+      program.methods
+        .setBlocklistEnforcement(enabled)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const sessionWithdraw = () =>
+      program.methods
+        .sessionWithdrawSol(new BN(LAMPORTS / 100), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    // The blocklist can't screen a SOL payout, so it isn't paid at all
+    await setEnforcement(true);
+    try {
+      await sessionWithdraw();
+      assert.fail("expected SolSpendUnsupported");
+    } catch (e) {
+      assert.include(String(e), "SolSpendUnsupported");
+    }
+
+    await setEnforcement(false);
+    await sessionWithdraw();
+  });
+});
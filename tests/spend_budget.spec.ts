@@ -0,0 +1,492 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID, getAccount } from "@solana/spl-token";
+import {
+  airdropLamports,
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Spend budgets", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("serializes parallel executions from several sessions", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [spendBudget] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("spend_budget"),
+        ctx.userPda.toBuffer(),
+        ctx.mint.toBuffer(),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .initializeSpendBudget(new BN(5_000), new BN(3600))
+      .accountsStrict({
+        spendBudget,
+        userAccount: ctx.userPda,
+        mint: ctx.mint,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const sessions = [ctx.session];
+    for (let i = 0; i < 2; i++) {
+      const session = Keypair.generate();
+      await airdropLamports(
+        provider.connection,
+        session.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await program.methods
+        .createSessionKey(
+          session.publicKey,
+          new BN(Math.floor(Date.now() / 1000) + 3600),
+          { time: {} },
+          transferPermissions(new BN(0)),
+          null
+        )
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+      sessions.push(session);
+    }
+
+    const transfer = (session: Keypair, budget: PublicKey | null) =>
+      program.methods
+        .splDelegatedTransfer(new BN(1_000), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: budget,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([session])
+        .rpc();
+
+    try {
+      await transfer(ctx.session, null);
+      assert.fail("expected SpendBudgetMissing");
+    } catch (e) {
+      assert.include(String(e), "SpendBudgetMissing");
+    }
+
+    // 12 transfers of 1_000 against a 5_000 budget, fired concurrently from 3 sessions
+    const results = await Promise.allSettled(
+      Array.from({ length: 12 }, (_, i) =>
+        transfer(sessions[i % sessions.length], spendBudget)
+      )
+    );
+    const succeeded = results.filter((r) => r.status === "fulfilled").length;
+    const rejected = results.filter(
+      (r): r is PromiseRejectedResult => r.status === "rejected"
+    );
+    assert.equal(succeeded, 5);
+    for (const r of rejected) {
+      assert.include(String(r.reason), "SpendBudgetExceeded");
+    }
+
+    const budget = await program.account.spendBudget.fetch(spendBudget);
+    assert.equal(budget.spent.toNumber(), 5_000);
+    const recipient = await getAccount(provider.connection, ctx.recipientAta);
+    assert.equal(Number(recipient.amount), 5_000);
+  });
+
+  it("caps a session key's cumulative spend", async () => {
+    const ctx = await setupDelegatedSession(provider, program, new BN(1_000));
+    const transfer = (amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    await transfer(600);
+    await transfer(400);
+    try {
+      await transfer(1);
+      assert.fail("expected SessionSpendCapExceeded");
+    } catch (e) {
+      assert.include(String(e), "SessionSpendCapExceeded");
+    }
+
+    const acct = await program.account.userAccount.fetch(ctx.userPda);
+    const entry = acct.sessionKeys.find((k) =>
+      k.pubkey.equals(ctx.session.publicKey)
+    );
+    assert.equal(entry.totalSpent.toNumber(), 1_000);
+  });
+
+  it("resets a session's window limit once the window elapses", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      session.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await program.methods
+      .createSessionKey(
+        session.publicKey,
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        { time: {} },
+        {
+          ...transferPermissions(new BN(0)),
+          dailyLimit: new BN(1_000),
+          spendWindowSeconds: 2,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
+        },
+        null
+      )
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const transfer = (amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([session])
+        .rpc();
+
+    await transfer(600);
+    try {
+      await transfer(500);
+      assert.fail("expected DailyLimitExceeded");
+    } catch (e) {
+      assert.include(String(e), "DailyLimitExceeded");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 3_000));
+    await transfer(500);
+  });
+
+  it("throttles a session key's executions per use window", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      session.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await program.methods
+      .createSessionKey(
+        session.publicKey,
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        { time: {} },
+        {
+          ...transferPermissions(new BN(0)),
+          maxUsesPerWindow: 2,
+          useWindowSeconds: 2,
+        },
+        null
+      )
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const transfer = (amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([session])
+        .rpc();
+
+    await transfer(1);
+    await transfer(2);
+    try {
+      await transfer(3);
+      assert.fail("expected RateLimitExceeded");
+    } catch (e) {
+      assert.include(String(e), "RateLimitExceeded");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 3_000));
+    await transfer(3);
+  });
+
+  it("rejects a single-use session key after its first transfer", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      session.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await program.methods
+      .createSessionKeyV2({
+        sessionPubkey: session.publicKey,
+        expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+        expiresAtSlot: null,
+        permissions: transferPermissions(new BN(0)),
+        termsHash: null,
+        label: Array(32).fill(0),
+        maxUses: 1,
+        validFrom: new BN(0),
+        allowedRecipients: [],
+        allowedMints: [],
+        allowedPrograms: [],
+        allowedDiscriminators: [],
+        idleTimeout: 0,
+        scope: PublicKey.default,
+        domainHash: null,
+      })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const transfer = (amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([session])
+        .rpc();
+
+    await transfer(1_000);
+    try {
+      await transfer(1);
+      assert.fail("expected SessionKeyExhausted");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyExhausted");
+    }
+
+    const acct = await program.account.userAccount.fetch(ctx.userPda);
+    const entry = acct.sessionKeys.find((k) =>
+      k.pubkey.equals(session.publicKey)
+    );
+    assert.equal(entry.useCount, 1);
+  });
+
+  it("rejects a session key before its valid_from time", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const session = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      session.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const now = Math.floor(Date.now() / 1000);
+    const createKey = (validFrom: number, expiresAt: number) =>
+      program.methods
+        .createSessionKeyV2({
+          sessionPubkey: session.publicKey,
+          expiresAtTime: new BN(expiresAt),
+          expiresAtSlot: null,
+          permissions: transferPermissions(new BN(0)),
+          termsHash: null,
+          label: Array(32).fill(0),
+          maxUses: 0,
+          validFrom: new BN(validFrom),
+          allowedRecipients: [],
+          allowedMints: [],
+          allowedPrograms: [],
+          allowedDiscriminators: [],
+          idleTimeout: 0,
+          scope: PublicKey.default,
+          domainHash: null,
+        })
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ctx.authority])
+        .rpc();
+
+    try {
+      await createKey(now + 7200, now + 3600);
+      assert.fail("expected InvalidExpiry");
+    } catch (e) {
+      assert.include(String(e), "InvalidExpiry");
+    }
+    await createKey(now + 3, now + 3600);
+
+    const transfer = (amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([session])
+        .rpc();
+
+    try {
+      await transfer(1);
+      assert.fail("expected SessionKeyNotYetValid");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyNotYetValid");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 5_000));
+    await transfer(1);
+  });
+});
@@ -0,0 +1,119 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Spend categories", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  const label = (text: string) => {
+    const buf = Buffer.alloc(16);
+    buf.write(text, "utf8");
+    return Array.from(buf);
+  };
+
+  it("tags transfers and totals them per category", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const [accountStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), ctx.userPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeAccountStats()
+      .accountsStrict({
+        accountStats,
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const setCategories = (categories: { id: number; label: number[] }[]) =>
+      program.methods
+        .setSpendCategories(categories)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const transfer = (amount: number, category: number | null) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, category, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ctx.session])
+        .rpc();
+
+    try {
+      await setCategories([
+        { id: 1, label: label("food") },
+        { id: 1, label: label("rent") },
+      ]);
+      assert.fail("expected DuplicateSpendCategory");
+    } catch (e) {
+      assert.include(String(e), "DuplicateSpendCategory");
+    }
+
+    await setCategories([
+      { id: 1, label: label("food") },
+      { id: 2, label: label("travel") },
+    ]);
+
+    try {
+      await transfer(10, 7);
+      assert.fail("expected UnknownSpendCategory");
+    } catch (e) {
+      assert.include(String(e), "UnknownSpendCategory");
+    }
+
+    await transfer(10, 1);
+    await transfer(15, 1);
+    await transfer(40, 2);
+    await transfer(5, null);
+
+    const stats = await program.account.accountStats.fetch(accountStats);
+    assert.equal(stats.totalVolume.toString(), "70");
+    const totals: Record<number, { volume: string; count: number }> = {};
+    for (const t of stats.categoryTotals) {
+      totals[t.category] = {
+        volume: t.volume.toString(),
+        count: t.count.toNumber(),
+      };
+    }
+    assert.deepEqual(totals, {
+      1: { volume: "25", count: 2 },
+      2: { volume: "40", count: 1 },
+    });
+  });
+});
@@ -4,11 +4,21 @@ import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
 import { Time } from "../target/types/time";
 import { assert } from "chai";
 import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
+  getAccount,
+  getAssociatedTokenAddressSync,
   getOrCreateAssociatedTokenAccount,
   mintTo,
 } from "@solana/spl-token";
-import { airdropLamports, createMintAndAtas, deriveUserPda } from "./helpers";
+import {
+  airdropLamports,
+  createMintAndAtas,
+  deriveUserPda,
+  deriveProgramConfigPda,
+  setupDelegatedSession,
+  transferPermissions,
+} from "./helpers";
 
 describe("SPL Delegation", () => {
   const provider = anchor.AnchorProvider.env();
@@ -27,10 +37,11 @@ describe("SPL Delegation", () => {
       authority.publicKey
     );
     await program.methods
-      .initializeUserAccount()
+      .initializeUserAccount(6)
       .accountsStrict({
         userAccount: userPda,
         authority: authority.publicKey,
+        payer: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([authority])
@@ -65,7 +76,7 @@ describe("SPL Delegation", () => {
       .signers([authority])
       .rpc();
 
-    const [delegateAuth] = PublicKey.findProgramAddressSync(
+    const [delegateAuth, delegateBump] = PublicKey.findProgramAddressSync(
       [Buffer.from("delegate"), userPda.toBuffer(), mint.toBuffer()],
       program.programId
     );
@@ -74,6 +85,7 @@ describe("SPL Delegation", () => {
       .splApproveDelegate(new BN(300_000_000))
       .accountsStrict({
         userAccount: userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
         authority: authority.publicKey,
         tokenAccount: ownerAta,
         mint,
@@ -83,6 +95,12 @@ describe("SPL Delegation", () => {
       .signers([authority])
       .rpc();
 
+    // Transfers reuse the bump cached here instead of searching for it
+    const { delegateBumps } = await program.account.userAccount.fetch(userPda);
+    assert.lengthOf(delegateBumps, 1);
+    assert.isTrue(delegateBumps[0].mint.equals(mint));
+    assert.equal(delegateBumps[0].bump, delegateBump);
+
     const session = Keypair.generate();
     await program.methods
       .createSessionKey(
@@ -93,9 +111,17 @@ describe("SPL Delegation", () => {
           canTransfer: true,
           canDelegate: false,
           canExecuteCustom: false,
+          canSelfRotate: false,
           maxTransferAmount: new BN(300_000_000),
+          dailyLimit: new BN(0),
+          spendWindowSeconds: 0,
+          maxUsesPerWindow: 0,
+          useWindowSeconds: 0,
           customFlags: 0,
-        }
+          maxCpiDepth: 0,
+          maxCustomAccounts: 0,
+        },
+        null
       )
       .accountsStrict({
         userAccount: userPda,
@@ -113,14 +139,31 @@ describe("SPL Delegation", () => {
     );
 
     await program.methods
-      .splDelegatedTransfer(new BN(100_000_000))
+      .splDelegatedTransfer(new BN(100_000_000), null, null, null, null)
       .accountsStrict({
         sessionSigner: session.publicKey,
         userAccount: userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
+        sessionKeyAccount: null,
+        policy: null,
         fromToken: ownerAta,
         toToken: recipientAta,
         mint,
         delegateAuthority: delegateAuth,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: null,
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: null,
+        activityDigest: null,
+        accountStats: null,
+        savingsVault: null,
+        feeToken: null,
+        slotHashes: null,
+        gasTank: null,
+        instructionsSysvar: null,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([session])
@@ -134,4 +177,138 @@ describe("SPL Delegation", () => {
     );
     assert.equal(Number(refreshed.amount), 100_000_000);
   });
+
+  it("creates the recipient's token account when it is missing", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    const recipient = Keypair.generate().publicKey;
+    const toToken = getAssociatedTokenAddressSync(ctx.mint, recipient);
+    assert.isNull(await provider.connection.getAccountInfo(toToken));
+
+    // The plain transfer's accounts, forwarded as remaining accounts
+    const inner = await program.methods
+      .splDelegatedTransfer(new BN(1_000), null, null, null, null)
+      .accountsStrict({
+        sessionSigner: ctx.session.publicKey,
+        userAccount: ctx.userPda,
+        programConfig: deriveProgramConfigPda(program.programId),
+        sessionKeyAccount: null,
+        policy: null,
+        fromToken: ctx.ownerAta,
+        toToken,
+        mint: ctx.mint,
+        delegateAuthority: ctx.delegateAuth,
+        sessionAttestation: null,
+        recipientAttestation: null,
+        blocklist: null,
+        recipientConsent: null,
+        operatorBond: null,
+        operatorReputation: null,
+        spendBudget: null,
+        activityDigest: null,
+        accountStats: null,
+        savingsVault: null,
+        feeToken: null,
+        slotHashes: null,
+        gasTank: null,
+        instructionsSysvar: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+    const transfer = () =>
+      program.methods
+        .splDelegatedTransferWithAta(new BN(1_000), null, null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          recipient,
+          toToken,
+          mint: ctx.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          timeProgram: program.programId,
+        })
+        .remainingAccounts(inner.keys)
+        .signers([ctx.session])
+        .rpc();
+
+    await transfer();
+    assert.equal(
+      Number((await getAccount(provider.connection, toToken)).amount),
+      1_000
+    );
+
+    // Idempotent once the account exists
+    await transfer();
+    assert.equal(
+      Number((await getAccount(provider.connection, toToken)).amount),
+      2_000
+    );
+  });
+
+  it("creates a session key and approves its delegate atomically", async () => {
+    const { authority, userPda } = await setupAuthorityAndPda();
+    const feePayer = (provider.wallet as anchor.Wallet).payer;
+    const { mint, ownerAta } = await createMintAndAtas(
+      provider.connection,
+      feePayer,
+      authority.publicKey,
+      Keypair.generate().publicKey,
+      6
+    );
+    const [delegateAuth] = PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), userPda.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+    const session = Keypair.generate();
+    const onboard = (delegateAuthority: PublicKey) =>
+      program.methods
+        .approveAndCreateSession(
+          {
+            sessionPubkey: session.publicKey,
+            expiresAtTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+            expiresAtSlot: null,
+            permissions: transferPermissions(new BN(0)),
+            termsHash: null,
+            label: Array(32).fill(0),
+            maxUses: 0,
+            validFrom: new BN(0),
+            allowedRecipients: [],
+            allowedMints: [],
+            allowedPrograms: [],
+            allowedDiscriminators: [],
+            idleTimeout: 0,
+            scope: PublicKey.default,
+            domainHash: null,
+          },
+          new BN(500)
+        )
+        .accountsStrict({
+          userAccount: userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          authority: authority.publicKey,
+          tokenAccount: ownerAta,
+          mint,
+          delegateAuthority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+
+    // A failed approval leaves no session key behind
+    try {
+      await onboard(Keypair.generate().publicKey);
+      assert.fail("expected InsufficientPermissions");
+    } catch (e) {
+      assert.include(String(e), "InsufficientPermissions");
+    }
+    let account = await program.account.userAccount.fetch(userPda);
+    assert.lengthOf(account.sessionKeys, 0);
+
+    await onboard(delegateAuth);
+    account = await program.account.userAccount.fetch(userPda);
+    assert.isTrue(account.sessionKeys[0].pubkey.equals(session.publicKey));
+    const token = await getAccount(provider.connection, ownerAta);
+    assert.isTrue(token.delegate!.equals(delegateAuth));
+    assert.equal(Number(token.delegatedAmount), 500);
+  });
 });
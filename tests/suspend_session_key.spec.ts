@@ -0,0 +1,95 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, deriveProgramConfigPda } from "./helpers";
+
+describe("Suspend session key", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+  const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+  it("pauses a key and resumes it with its usage intact", async () => {
+    const ctx = await setupDelegatedSession(provider, program);
+    await program.methods
+      .depositSol(new BN(LAMPORTS / 2))
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        depositor: ctx.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([ctx.authority])
+      .rpc();
+
+    const merchant = Keypair.generate().publicKey;
+    const send = () =>
+      program.methods
+        .sessionTransferSol(new BN(LAMPORTS / 100), null, null)
+        .accountsStrict({
+          sessionSigner: ctx.session.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          destination: merchant,
+          recipientConsent: null,
+          slotHashes: null,
+          treasury: null,
+        })
+        .signers([ctx.session])
+        .rpc();
+    const toggle = (suspend: boolean) =>
+      (suspend
+        ? program.methods.suspendSessionKey(ctx.session.publicKey)
+        : program.methods.resumeSessionKey(ctx.session.publicKey)
+      )
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          sessionKeyAccount: null,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const fetchKey = async () =>
+      (await program.account.userAccount.fetch(ctx.userPda)).sessionKeys.find(
+        (k) => k.pubkey.equals(ctx.session.publicKey)
+      );
+
+    await send();
+    await toggle(true);
+    try {
+      await send();
+      assert.fail("expected SessionKeySuspended");
+    } catch (e) {
+      assert.include(String(e), "SessionKeySuspended");
+    }
+
+    // Cleanup keeps a suspended key, unlike a revoked one
+    await program.methods
+      .cleanupSessionKeys(0)
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    let key = await fetchKey();
+    assert.isTrue(key.isSuspended);
+    assert.isFalse(key.isRevoked);
+
+    await toggle(false);
+    try {
+      await toggle(false);
+      assert.fail("expected SessionKeyNotSuspended");
+    } catch (e) {
+      assert.include(String(e), "SessionKeyNotSuspended");
+    }
+    await send();
+
+    key = await fetchKey();
+    assert.isFalse(key.isSuspended);
+    assert.equal(key.totalSpent.toNumber(), (2 * LAMPORTS) / 100);
+  });
+});
@@ -0,0 +1,52 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { setupDelegatedSession, transferPermissions } from "./helpers";
+
+describe("Session validation for other programs", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("returns whether a key grants the required permissions", async () => {
+    const ctx = await setupDelegatedSession(provider, program, new BN(1_000));
+    const validate = (
+      session: PublicKey,
+      required: ReturnType<typeof transferPermissions>
+    ): Promise<boolean> =>
+      program.methods
+        .validateSession(session, required, null)
+        .accountsStrict({ userAccount: ctx.userPda, sessionKeyAccount: null })
+        .view();
+
+    assert.isTrue(
+      await validate(ctx.session.publicKey, transferPermissions(new BN(0)))
+    );
+    assert.isTrue(
+      await validate(ctx.session.publicKey, transferPermissions(new BN(1_000)))
+    );
+
+    // Rejections come back as false rather than failing the transaction
+    const none = { ...transferPermissions(new BN(0)), canTransfer: false };
+    for (const required of [
+      transferPermissions(new BN(1_001)),
+      { ...none, canExecuteCustom: true },
+      { ...none, customFlags: 1 },
+    ]) {
+      assert.isFalse(await validate(ctx.session.publicKey, required));
+    }
+    assert.isFalse(await validate(Keypair.generate().publicKey, none));
+
+    await program.methods
+      .revokeSessionKey(ctx.session.publicKey, { manual: {} })
+      .accountsStrict({
+        userAccount: ctx.userPda,
+        authority: ctx.authority.publicKey,
+      })
+      .signers([ctx.authority])
+      .rpc();
+    assert.isFalse(await validate(ctx.session.publicKey, none));
+  });
+});
@@ -0,0 +1,48 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { createHash } from "crypto";
+import {
+  executableHash,
+  publishBuildHash,
+  publishedBuildAddress,
+} from "../app/verify_deployment";
+import { airdropLamports } from "./helpers";
+
+describe("Verifiable builds", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("hashes executables without their zero padding", () => {
+    const code = Buffer.from([1, 2, 0, 3]);
+    const padded = Buffer.concat([code, Buffer.alloc(64)]);
+    const expected = createHash("sha256").update(code).digest("hex");
+    assert.equal(executableHash(code), expected);
+    assert.equal(executableHash(padded), expected);
+  });
+
+  it("only takes build hashes from the upgrade authority", async () => {
+    const stranger = Keypair.generate();
+    await airdropLamports(
+      provider.connection,
+      stranger.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const hash = "ab".repeat(32);
+    try {
+      await publishBuildHash(program, stranger, hash, "cd".repeat(20));
+      assert.fail("expected the publish to be refused");
+    } catch (e) {
+      // Unauthorized when the program is upgradeable, a constraint otherwise
+      assert.match(String(e), /Unauthorized|Constraint/);
+    }
+    assert.isNull(
+      await program.account.publishedBuild.fetchNullable(
+        publishedBuildAddress(program.programId, hash)
+      )
+    );
+  });
+});
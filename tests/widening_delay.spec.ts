@@ -0,0 +1,115 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { Time } from "../target/types/time";
+import { assert } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  setupDelegatedSession,
+  transferPermissions,
+  deriveProgramConfigPda,
+} from "./helpers";
+
+describe("Permission widening delay", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Time as Program<Time>;
+
+  it("keeps the old scope until the delay passes", async () => {
+    const ctx = await setupDelegatedSession(provider, program, new BN(100));
+    const setDelay = (seconds: number) =>
+      program.methods
+        .setWideningDelay(seconds)
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const updateCap = (cap: number) =>
+      program.methods
+        .updateSessionKey(
+          ctx.session.publicKey,
+          null,
+          null,
+          transferPermissions(new BN(cap))
+        )
+        .accountsStrict({
+          userAccount: ctx.userPda,
+          authority: ctx.authority.publicKey,
+        })
+        .signers([ctx.authority])
+        .rpc();
+    const transfer = (signer: Keypair, amount: number) =>
+      program.methods
+        .splDelegatedTransfer(new BN(amount), null, null, null, null)
+        .accountsStrict({
+          sessionSigner: signer.publicKey,
+          userAccount: ctx.userPda,
+          programConfig: deriveProgramConfigPda(program.programId),
+          sessionKeyAccount: null,
+          policy: null,
+          fromToken: ctx.ownerAta,
+          toToken: ctx.recipientAta,
+          mint: ctx.mint,
+          delegateAuthority: ctx.delegateAuth,
+          sessionAttestation: null,
+          recipientAttestation: null,
+          blocklist: null,
+          recipientConsent: null,
+          operatorBond: null,
+          operatorReputation: null,
+          spendBudget: null,
+          activityDigest: null,
+          accountStats: null,
+          savingsVault: null,
+          feeToken: null,
+          slotHashes: null,
+          gasTank: null,
+          instructionsSysvar: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+    const entry = async () =>
+      (await program.account.userAccount.fetch(ctx.userPda)).sessionKeys.find(
+        (k) => k.pubkey.equals(ctx.session.publicKey)
+      );
+
+    try {
+      await setDelay(8 * 24 * 60 * 60);
+      assert.fail("expected InvalidWideningDelay");
+    } catch (e) {
+      assert.include(String(e), "InvalidWideningDelay");
+    }
+
+    await setDelay(3600);
+    await updateCap(1_000);
+
+    const widened = await entry();
+    assert.equal(widened.priorPermissions.maxTransferAmount.toNumber(), 100);
+    assert.isAbove(widened.wideningActiveAt.toNumber(), 0);
+
+    // Still bound by the old cap of 100
+    await transfer(ctx.session, 100);
+    try {
+      await transfer(ctx.session, 1);
+      assert.fail("expected SessionSpendCapExceeded");
+    } catch (e) {
+      assert.include(String(e), "SessionSpendCapExceeded");
+    }
+
+    // Narrowing takes effect at once and cancels the pending widening
+    await updateCap(50);
+    const narrowed = await entry();
+    assert.isNull(narrowed.priorPermissions);
+    assert.equal(narrowed.wideningActiveAt.toNumber(), 0);
+    assert.equal(narrowed.permissions.maxTransferAmount.toNumber(), 50);
+
+    // Without a delay, widening applies immediately
+    await setDelay(0);
+    await updateCap(200);
+    await transfer(ctx.session, 50);
+    assert.isNull((await entry()).priorPermissions);
+  });
+});